@@ -8,6 +8,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use uuid::Uuid;
 
@@ -275,6 +276,32 @@ impl Alert {
         self.updated_at = Utc::now();
     }
 
+    /// Compute the deduplication fingerprint over the given fields
+    ///
+    /// Fields are `title`, `description`, `severity`, `source`, or a label
+    /// written as `labels.<name>`. Unknown fields and missing labels
+    /// contribute an empty value. The fingerprint is a SHA-256 digest over
+    /// the sorted fields, so it does not depend on their order and stays the
+    /// same across restarts and releases.
+    pub fn fingerprint<T: AsRef<str>>(&self, fields: &[T]) -> String {
+        let mut fields = fields.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+        fields.sort_unstable();
+        fields.dedup();
+
+        stable_fingerprint(fields.into_iter().map(|field| {
+            let value = match field {
+                "title" => Some(self.title.clone()),
+                "description" => Some(self.description.clone()),
+                "severity" => Some(self.severity.to_string()),
+                "source" => Some(self.source.clone()),
+                _ => field
+                    .strip_prefix("labels.")
+                    .and_then(|label| self.context.labels.get(label).cloned()),
+            };
+            (field, value)
+        }))
+    }
+
     /// Calculate priority score based on severity and other factors
     fn calculate_priority_score(severity: AlertSeverity) -> f64 {
         match severity {
@@ -286,15 +313,13 @@ impl Alert {
         }
     }
 
-    /// Generate fingerprint for deduplication
+    /// Generate fingerprint for deduplication, equal to the fingerprint over
+    /// `title` and `source`
     fn generate_fingerprint(title: &str, source: &str) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        let mut hasher = DefaultHasher::new();
-        title.hash(&mut hasher);
-        source.hash(&mut hasher);
-        format!("{:x}", hasher.finish())
+        stable_fingerprint([
+            ("source", Some(source.to_string())),
+            ("title", Some(title.to_string())),
+        ])
     }
 
     /// Get alert age in seconds
@@ -362,6 +387,21 @@ impl std::fmt::Display for AlertSeverity {
     }
 }
 
+impl std::str::FromStr for AlertSeverity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "critical" => Ok(Self::Critical),
+            "high" => Ok(Self::High),
+            "medium" => Ok(Self::Medium),
+            "warning" => Ok(Self::Warning),
+            "info" => Ok(Self::Info),
+            _ => Err(format!("Unknown alert severity: {}", s)),
+        }
+    }
+}
+
 impl std::fmt::Display for AlertStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -387,6 +427,29 @@ impl Default for AlertContext {
     }
 }
 
+/// Hex SHA-256 digest over length-prefixed field names and values
+fn stable_fingerprint<'x>(fields: impl IntoIterator<Item = (&'x str, Option<String>)>) -> String {
+    let mut hasher = Sha256::new();
+    for (field, value) in fields {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field.as_bytes());
+        match value {
+            Some(value) => {
+                hasher.update([1]);
+                hasher.update((value.len() as u64).to_be_bytes());
+                hasher.update(value.as_bytes());
+            }
+            None => hasher.update([0]),
+        }
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -451,6 +514,42 @@ mod tests {
         assert!(alert.silence_expires_at.is_some());
     }
 
+    #[test]
+    fn test_alert_fingerprint() {
+        let alert = |instance: &str| {
+            let mut alert = Alert::new(
+                "Disk full".to_string(),
+                "Disk usage above 95%".to_string(),
+                AlertSeverity::High,
+                "storage".to_string(),
+            );
+            alert.add_label("instance".to_string(), instance.to_string());
+            alert
+        };
+        let (mx1, mx2) = (alert("mx1"), alert("mx2"));
+
+        let fields = ["title", "severity", "source"];
+        assert_eq!(mx1.fingerprint(&fields), mx2.fingerprint(&fields));
+
+        let fields = ["title", "severity", "source", "labels.instance"];
+        assert_ne!(mx1.fingerprint(&fields), mx2.fingerprint(&fields));
+        assert_eq!(mx1.fingerprint(&fields), alert("mx1").fingerprint(&fields));
+
+        // Field order does not matter and the default matches title and source
+        let reordered = ["labels.instance", "source", "severity", "title"];
+        assert_eq!(mx1.fingerprint(&fields), mx1.fingerprint(&reordered));
+        assert_eq!(
+            mx1.context.fingerprint,
+            mx1.fingerprint(&["title", "source"])
+        );
+
+        // Fingerprints are persisted, so they must not change between releases
+        assert_eq!(
+            mx1.fingerprint(&["title", "source"]),
+            "46f2345bd9777382f732fe8bcc9f509efc83449ccaad485b0be8d0eb05172702"
+        );
+    }
+
     #[test]
     fn test_severity_ordering() {
         assert!(AlertSeverity::Critical.numeric_value() > AlertSeverity::High.numeric_value());
@@ -6,12 +6,15 @@
 
 //! Notification channels for alert delivery
 
-use crate::alert::Alert;
+use crate::alert::{Alert, AlertSeverity};
 use crate::error::{AlertingError, Result};
+use crate::templates::TemplateEngine;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::{info, warn, error, debug};
 
 /// Notification channel types
@@ -48,6 +51,12 @@ pub struct DeliveryResult {
     pub timestamp: chrono::DateTime<chrono::Utc>,
     /// Delivery duration in milliseconds
     pub duration_ms: u64,
+    /// Number of delivery attempts made
+    #[serde(default)]
+    pub attempts: u32,
+    /// Status code of the last response, if one was received
+    #[serde(default)]
+    pub status_code: Option<u16>,
     /// Error message if delivery failed
     pub error: Option<String>,
     /// Delivery metadata
@@ -84,12 +93,19 @@ pub struct RateLimitConfig {
     pub window_seconds: u64,
     /// Burst allowance
     pub burst: u32,
+    /// Longest time rate limited notifications wait for their summary
+    #[serde(default = "default_max_delay_seconds")]
+    pub max_delay_seconds: u64,
+}
+
+fn default_max_delay_seconds() -> u64 {
+    60
 }
 
 /// Retry configuration for channels
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RetryConfig {
-    /// Maximum number of retry attempts
+    /// Maximum number of delivery attempts, including the first one
     pub max_attempts: u32,
     /// Initial retry delay in seconds
     pub initial_delay_seconds: u64,
@@ -179,6 +195,9 @@ pub struct ChannelHealth {
 pub enum NotificationChannelImpl {
     Webhook(WebhookChannel),
     Slack(SlackChannel),
+    Telegram(TelegramChannel),
+    /// Channel implemented outside this crate
+    Custom(Arc<dyn NotificationChannel>),
 }
 
 impl NotificationChannelImpl {
@@ -187,6 +206,8 @@ impl NotificationChannelImpl {
         match self {
             Self::Webhook(channel) => channel.channel_type(),
             Self::Slack(channel) => channel.channel_type(),
+            Self::Telegram(channel) => channel.channel_type(),
+            Self::Custom(channel) => channel.channel_type(),
         }
     }
 
@@ -195,6 +216,8 @@ impl NotificationChannelImpl {
         match self {
             Self::Webhook(channel) => channel.name(),
             Self::Slack(channel) => channel.name(),
+            Self::Telegram(channel) => channel.name(),
+            Self::Custom(channel) => channel.name(),
         }
     }
 
@@ -203,6 +226,8 @@ impl NotificationChannelImpl {
         match self {
             Self::Webhook(channel) => channel.should_send_alert(alert).await,
             Self::Slack(channel) => channel.should_send_alert(alert).await,
+            Self::Telegram(channel) => channel.should_send_alert(alert).await,
+            Self::Custom(channel) => channel.should_send_alert(alert).await,
         }
     }
 
@@ -211,6 +236,8 @@ impl NotificationChannelImpl {
         match self {
             Self::Webhook(channel) => channel.send_alert(alert).await,
             Self::Slack(channel) => channel.send_alert(alert).await,
+            Self::Telegram(channel) => channel.send_alert(alert).await,
+            Self::Custom(channel) => channel.send_alert(alert).await,
         }
     }
 
@@ -219,6 +246,8 @@ impl NotificationChannelImpl {
         match self {
             Self::Webhook(channel) => channel.should_send_resolution(alert).await,
             Self::Slack(channel) => channel.should_send_resolution(alert).await,
+            Self::Telegram(channel) => channel.should_send_resolution(alert).await,
+            Self::Custom(channel) => channel.should_send_resolution(alert).await,
         }
     }
 
@@ -227,6 +256,8 @@ impl NotificationChannelImpl {
         match self {
             Self::Webhook(channel) => channel.send_resolution(alert).await,
             Self::Slack(channel) => channel.send_resolution(alert).await,
+            Self::Telegram(channel) => channel.send_resolution(alert).await,
+            Self::Custom(channel) => channel.send_resolution(alert).await,
         }
     }
 
@@ -235,6 +266,8 @@ impl NotificationChannelImpl {
         match self {
             Self::Webhook(channel) => channel.test_connection().await,
             Self::Slack(channel) => channel.test_connection().await,
+            Self::Telegram(channel) => channel.test_connection().await,
+            Self::Custom(channel) => channel.test_connection().await,
         }
     }
 
@@ -243,12 +276,19 @@ impl NotificationChannelImpl {
         match self {
             Self::Webhook(channel) => channel.health_check().await,
             Self::Slack(channel) => channel.health_check().await,
+            Self::Telegram(channel) => channel.health_check().await,
+            Self::Custom(channel) => channel.health_check().await,
         }
     }
 }
 
 /// Create a notification channel from configuration
-pub async fn create_channel(config: &ChannelConfig) -> Result<NotificationChannelImpl> {
+///
+/// Channels that format messages render them with `templates`.
+pub async fn create_channel(
+    config: &ChannelConfig,
+    templates: Arc<TemplateEngine>,
+) -> Result<NotificationChannelImpl> {
     info!("Creating notification channel: {} ({})", config.name, config.channel_type);
 
     match &config.channel_type {
@@ -256,7 +296,10 @@ pub async fn create_channel(config: &ChannelConfig) -> Result<NotificationChanne
             Ok(NotificationChannelImpl::Webhook(WebhookChannel::new(config).await?))
         }
         ChannelType::Slack => {
-            Ok(NotificationChannelImpl::Slack(SlackChannel::new(config).await?))
+            Ok(NotificationChannelImpl::Slack(SlackChannel::new(config).await?.with_templates(templates)))
+        }
+        ChannelType::Telegram => {
+            Ok(NotificationChannelImpl::Telegram(TelegramChannel::new(config).await?.with_templates(templates)))
         }
         ChannelType::Custom(name) => {
             warn!("Custom channel type not implemented: {}", name);
@@ -270,12 +313,17 @@ pub async fn create_channel(config: &ChannelConfig) -> Result<NotificationChanne
 }
 
 /// Webhook notification channel
+///
+/// Alerts are POSTed as JSON. Network errors, timeouts and `408`, `429` and
+/// `5xx` responses are retried with exponential backoff and jitter; any
+/// other non-2xx response fails the delivery immediately.
 #[derive(Debug)]
 pub struct WebhookChannel {
     name: String,
     url: String,
     headers: HashMap<String, String>,
     timeout_seconds: u64,
+    retry: RetryConfig,
     filter: FilterConfig,
     client: reqwest::Client,
 }
@@ -308,10 +356,74 @@ impl WebhookChannel {
             url,
             headers,
             timeout_seconds,
+            retry: config.retry.clone(),
             filter: config.filter.clone(),
             client,
         })
     }
+
+    /// POST an alert, retrying failed attempts as configured
+    async fn deliver(&self, alert: &Alert) -> DeliveryResult {
+        let start_time = std::time::Instant::now();
+        let max_attempts = self.retry.max_attempts.max(1);
+        let mut attempts = 0;
+
+        let (success, status_code, error) = loop {
+            attempts += 1;
+
+            let mut request = self.client.post(&self.url).json(alert);
+            for (key, value) in &self.headers {
+                request = request.header(key, value);
+            }
+
+            let (status_code, error, retryable) = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    break (true, Some(response.status().as_u16()), None);
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let retryable = status.is_server_error()
+                        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+                        || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+                    (Some(status.as_u16()), format!("HTTP {}", status), retryable)
+                }
+                Err(e) => (None, e.to_string(), true),
+            };
+
+            if !retryable || attempts >= max_attempts {
+                break (false, status_code, Some(error));
+            }
+
+            let delay = self.retry.delay(attempts);
+            warn!(
+                "Webhook delivery attempt {} for alert {} failed ({}), retrying in {:?}",
+                attempts, alert.id, error, delay
+            );
+            tokio::time::sleep(delay).await;
+        };
+
+        if success {
+            info!("Webhook notification sent successfully: {}", alert.id);
+        } else {
+            error!(
+                "Webhook notification for alert {} failed after {} attempts: {}",
+                alert.id,
+                attempts,
+                error.as_deref().unwrap_or_default()
+            );
+        }
+
+        DeliveryResult {
+            channel_type: ChannelType::Webhook,
+            success,
+            timestamp: chrono::Utc::now(),
+            duration_ms: start_time.elapsed().as_millis() as u64,
+            attempts,
+            status_code,
+            error,
+            metadata: HashMap::from([("url".to_string(), self.url.clone())]),
+        }
+    }
 }
 
 #[async_trait]
@@ -324,78 +436,22 @@ impl NotificationChannel for WebhookChannel {
         &self.name
     }
 
-    async fn should_send_alert(&self, _alert: &Alert) -> bool {
-        // TODO: Implement filtering logic
-        true
+    async fn should_send_alert(&self, alert: &Alert) -> bool {
+        self.filter.matches(alert)
     }
 
     async fn send_alert(&self, alert: &Alert) -> Result<DeliveryResult> {
-        let start_time = std::time::Instant::now();
-
         debug!("Sending webhook notification for alert: {}", alert.id);
-
-        let payload = serde_json::json!({
-            "alert_id": alert.id,
-            "title": alert.title,
-            "description": alert.description,
-            "severity": alert.severity,
-            "status": alert.status,
-            "source": alert.source,
-            "created_at": alert.created_at,
-            "labels": alert.context.labels,
-            "annotations": alert.context.annotations
-        });
-
-        let mut request = self.client.post(&self.url)
-            .json(&payload);
-
-        for (key, value) in &self.headers {
-            request = request.header(key, value);
-        }
-
-        match request.send().await {
-            Ok(response) => {
-                let duration = start_time.elapsed();
-                let success = response.status().is_success();
-
-                if success {
-                    info!("Webhook notification sent successfully: {}", alert.id);
-                } else {
-                    error!("Webhook notification failed with status: {}", response.status());
-                }
-
-                Ok(DeliveryResult {
-                    channel_type: ChannelType::Webhook,
-                    success,
-                    timestamp: chrono::Utc::now(),
-                    duration_ms: duration.as_millis() as u64,
-                    error: if success { None } else { Some(format!("HTTP {}", response.status())) },
-                    metadata: HashMap::new(),
-                })
-            }
-            Err(e) => {
-                let duration = start_time.elapsed();
-                error!("Webhook notification failed: {}", e);
-
-                Ok(DeliveryResult {
-                    channel_type: ChannelType::Webhook,
-                    success: false,
-                    timestamp: chrono::Utc::now(),
-                    duration_ms: duration.as_millis() as u64,
-                    error: Some(e.to_string()),
-                    metadata: HashMap::new(),
-                })
-            }
-        }
+        Ok(self.deliver(alert).await)
     }
 
-    async fn should_send_resolution(&self, _alert: &Alert) -> bool {
-        true
+    async fn should_send_resolution(&self, alert: &Alert) -> bool {
+        self.filter.matches(alert)
     }
 
     async fn send_resolution(&self, alert: &Alert) -> Result<DeliveryResult> {
-        // Similar to send_alert but with resolution payload
-        self.send_alert(alert).await
+        debug!("Sending webhook resolution for alert: {}", alert.id);
+        Ok(self.deliver(alert).await)
     }
 
     async fn test_connection(&self) -> Result<()> {
@@ -431,12 +487,18 @@ impl NotificationChannel for WebhookChannel {
 }
 
 /// Slack notification channel
+///
+/// Alerts are posted to an incoming webhook as Block Kit messages inside an
+/// attachment colored by severity. The message body is rendered from the
+/// configured template.
 #[derive(Debug)]
 pub struct SlackChannel {
     name: String,
     webhook_url: String,
     channel: Option<String>,
     username: Option<String>,
+    template: String,
+    templates: Option<Arc<TemplateEngine>>,
     filter: FilterConfig,
     client: reqwest::Client,
 }
@@ -449,6 +511,9 @@ impl SlackChannel {
 
         let channel = config.config.get("channel").cloned();
         let username = config.config.get("username").cloned();
+        let template = config.config.get("template")
+            .cloned()
+            .unwrap_or_else(|| "default_text".to_string());
 
         let client = reqwest::Client::new();
 
@@ -457,55 +522,50 @@ impl SlackChannel {
             webhook_url,
             channel,
             username,
+            template,
+            templates: None,
             filter: config.filter.clone(),
             client,
         })
     }
-}
-
-#[async_trait]
-impl NotificationChannel for SlackChannel {
-    fn channel_type(&self) -> ChannelType {
-        ChannelType::Slack
-    }
 
-    fn name(&self) -> &str {
-        &self.name
+    /// Render message bodies with the given template engine
+    pub fn with_templates(mut self, templates: Arc<TemplateEngine>) -> Self {
+        self.templates = Some(templates);
+        self
     }
 
-    async fn should_send_alert(&self, _alert: &Alert) -> bool {
-        true
-    }
-
-    async fn send_alert(&self, alert: &Alert) -> Result<DeliveryResult> {
-        let start_time = std::time::Instant::now();
-
-        let color = match alert.severity {
-            crate::alert::AlertSeverity::Critical => "danger",
-            crate::alert::AlertSeverity::High => "warning",
-            crate::alert::AlertSeverity::Medium => "warning",
-            crate::alert::AlertSeverity::Warning => "warning",
-            crate::alert::AlertSeverity::Info => "good",
+    /// Build the Block Kit payload for an alert
+    async fn payload(&self, alert: &Alert, resolved: bool) -> serde_json::Value {
+        let body = render_message(self.templates.as_deref(), &self.template, alert).await;
+        let title = if resolved {
+            format!("Resolved: {}", alert.title)
+        } else {
+            alert.title.clone()
         };
 
         let mut payload = serde_json::json!({
+            "text": title,
             "attachments": [{
-                "color": color,
-                "title": alert.title,
-                "text": alert.description,
-                "fields": [
+                "color": if resolved { "#36A64F" } else { alert.severity.color_code() },
+                "blocks": [
+                    {
+                        "type": "header",
+                        "text": { "type": "plain_text", "text": title }
+                    },
                     {
-                        "title": "Severity",
-                        "value": alert.severity.to_string(),
-                        "short": true
+                        "type": "section",
+                        "text": { "type": "mrkdwn", "text": body.text }
                     },
                     {
-                        "title": "Source",
-                        "value": alert.source,
-                        "short": true
+                        "type": "context",
+                        "elements": [
+                            { "type": "mrkdwn", "text": format!("*Severity:* {}", alert.severity) },
+                            { "type": "mrkdwn", "text": format!("*Source:* {}", alert.source) },
+                            { "type": "mrkdwn", "text": format!("<!date^{}^{{date_short_pretty}} {{time}}|{}>", alert.created_at.timestamp(), alert.created_at.to_rfc3339()) }
+                        ]
                     }
-                ],
-                "ts": alert.created_at.timestamp()
+                ]
             }]
         });
 
@@ -517,46 +577,38 @@ impl NotificationChannel for SlackChannel {
             payload["username"] = serde_json::Value::String(username.clone());
         }
 
-        match self.client.post(&self.webhook_url)
-            .json(&payload)
-            .send()
-            .await
-        {
-            Ok(response) => {
-                let duration = start_time.elapsed();
-                let success = response.status().is_success();
-
-                Ok(DeliveryResult {
-                    channel_type: ChannelType::Slack,
-                    success,
-                    timestamp: chrono::Utc::now(),
-                    duration_ms: duration.as_millis() as u64,
-                    error: if success { None } else { Some(format!("HTTP {}", response.status())) },
-                    metadata: HashMap::new(),
-                })
-            }
-            Err(e) => {
-                let duration = start_time.elapsed();
-
-                Ok(DeliveryResult {
-                    channel_type: ChannelType::Slack,
-                    success: false,
-                    timestamp: chrono::Utc::now(),
-                    duration_ms: duration.as_millis() as u64,
-                    error: Some(e.to_string()),
-                    metadata: HashMap::new(),
-                })
-            }
-        }
+        payload
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for SlackChannel {
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::Slack
     }
 
-    async fn should_send_resolution(&self, _alert: &Alert) -> bool {
-        true
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn should_send_alert(&self, alert: &Alert) -> bool {
+        self.filter.matches(alert)
+    }
+
+    async fn send_alert(&self, alert: &Alert) -> Result<DeliveryResult> {
+        debug!("Sending Slack notification for alert: {}", alert.id);
+        let payload = self.payload(alert, false).await;
+        Ok(post_json(&self.client, &self.webhook_url, &payload, ChannelType::Slack).await)
+    }
+
+    async fn should_send_resolution(&self, alert: &Alert) -> bool {
+        self.filter.matches(alert)
     }
 
     async fn send_resolution(&self, alert: &Alert) -> Result<DeliveryResult> {
-        // Similar implementation for resolution notifications
-        self.send_alert(alert).await
+        debug!("Sending Slack resolution for alert: {}", alert.id);
+        let payload = self.payload(alert, true).await;
+        Ok(post_json(&self.client, &self.webhook_url, &payload, ChannelType::Slack).await)
     }
 
     async fn test_connection(&self) -> Result<()> {
@@ -568,7 +620,7 @@ impl NotificationChannel for SlackChannel {
             .json(&test_payload)
             .send()
             .await
-            .map_err(|e| AlertingError::network(format!("Connection test failed: {}", e)))?;
+            .map_err(|e| AlertingError::network(format!("Connection test failed: {}", e.without_url())))?;
 
         if response.status().is_success() {
             Ok(())
@@ -589,6 +641,251 @@ impl NotificationChannel for SlackChannel {
     }
 }
 
+impl fmt::Debug for TelegramChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TelegramChannel")
+            .field("name", &self.name)
+            .field("api_url", &self.api_url)
+            .field("bot_token", &"<redacted>")
+            .field("chat_id", &self.chat_id)
+            .field("template", &self.template)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Telegram notification channel
+///
+/// Alerts are sent with the bot API `sendMessage` method using Markdown
+/// formatting. Messages Telegram cannot parse as Markdown are sent again as
+/// plain text.
+pub struct TelegramChannel {
+    name: String,
+    api_url: String,
+    bot_token: String,
+    chat_id: String,
+    template: String,
+    templates: Option<Arc<TemplateEngine>>,
+    filter: FilterConfig,
+    client: reqwest::Client,
+}
+
+impl TelegramChannel {
+    pub async fn new(config: &ChannelConfig) -> Result<Self> {
+        let bot_token = config.config.get("bot_token")
+            .ok_or_else(|| AlertingError::config("Telegram bot token not configured"))?
+            .clone();
+        let chat_id = config.config.get("chat_id")
+            .ok_or_else(|| AlertingError::config("Telegram chat ID not configured"))?
+            .clone();
+        let api_url = config.config.get("api_url")
+            .map(|url| url.trim_end_matches('/').to_string())
+            .unwrap_or_else(|| "https://api.telegram.org".to_string());
+        let template = config.config.get("template")
+            .cloned()
+            .unwrap_or_else(|| "default_telegram".to_string());
+
+        let client = reqwest::Client::new();
+
+        Ok(Self {
+            name: config.name.clone(),
+            api_url,
+            bot_token,
+            chat_id,
+            template,
+            templates: None,
+            filter: config.filter.clone(),
+            client,
+        })
+    }
+
+    /// Render message bodies with the given template engine
+    pub fn with_templates(mut self, templates: Arc<TemplateEngine>) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    /// Method URL, which embeds the bot token and must never be logged
+    fn method_url(&self, method: &str) -> String {
+        format!("{}/bot{}/{}", self.api_url, self.bot_token, method)
+    }
+
+    async fn send_message(&self, alert: &Alert, resolved: bool) -> DeliveryResult {
+        let mut message = render_message(self.templates.as_deref(), &self.template, alert).await;
+        if resolved {
+            message.text = format!("Resolved: {}\n\n{}", alert.title, message.text);
+        }
+
+        let url = self.method_url("sendMessage");
+        let mut payload = serde_json::json!({
+            "chat_id": self.chat_id,
+            "text": message.text,
+            "disable_web_page_preview": true,
+        });
+        if message.rendered {
+            payload["parse_mode"] = serde_json::Value::String("Markdown".to_string());
+        }
+
+        let result = post_json(&self.client, &url, &payload, ChannelType::Telegram).await;
+        if message.rendered && result.status_code == Some(400) {
+            warn!("Telegram rejected Markdown for alert {}, sending as plain text", alert.id);
+            if let Some(payload) = payload.as_object_mut() {
+                payload.remove("parse_mode");
+            }
+            let retry = post_json(&self.client, &url, &payload, ChannelType::Telegram).await;
+            return DeliveryResult {
+                attempts: result.attempts + retry.attempts,
+                duration_ms: result.duration_ms + retry.duration_ms,
+                ..retry
+            };
+        }
+
+        result
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for TelegramChannel {
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::Telegram
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn should_send_alert(&self, alert: &Alert) -> bool {
+        self.filter.matches(alert)
+    }
+
+    async fn send_alert(&self, alert: &Alert) -> Result<DeliveryResult> {
+        debug!("Sending Telegram notification for alert: {}", alert.id);
+        Ok(self.send_message(alert, false).await)
+    }
+
+    async fn should_send_resolution(&self, alert: &Alert) -> bool {
+        self.filter.matches(alert)
+    }
+
+    async fn send_resolution(&self, alert: &Alert) -> Result<DeliveryResult> {
+        debug!("Sending Telegram resolution for alert: {}", alert.id);
+        Ok(self.send_message(alert, true).await)
+    }
+
+    async fn test_connection(&self) -> Result<()> {
+        let response = self.client.get(self.method_url("getMe"))
+            .send()
+            .await
+            .map_err(|e| AlertingError::network(format!("Connection test failed: {}", e.without_url())))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(AlertingError::network(format!("Connection test failed with status: {}", response.status())))
+        }
+    }
+
+    async fn health_check(&self) -> Result<ChannelHealth> {
+        Ok(ChannelHealth {
+            healthy: true,
+            last_success: Some(chrono::Utc::now()),
+            last_failure: None,
+            consecutive_failures: 0,
+            success_rate: 1.0,
+            avg_delivery_time_ms: 150.0,
+        })
+    }
+}
+
+impl fmt::Debug for TelegramChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TelegramChannel")
+            .field("name", &self.name)
+            .field("api_url", &self.api_url)
+            .field("bot_token", &"<redacted>")
+            .field("chat_id", &self.chat_id)
+            .field("template", &self.template)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Rendered message body
+struct RenderedMessage {
+    text: String,
+    /// Whether the text came from the template rather than the fallback
+    rendered: bool,
+}
+
+/// Render the message body of an alert
+///
+/// Falls back to a plain summary when no template engine is set or the
+/// template cannot be rendered, so that the alert is still delivered.
+async fn render_message(templates: Option<&TemplateEngine>, template: &str, alert: &Alert) -> RenderedMessage {
+    if let Some(templates) = templates {
+        match templates.render_alert(alert, template).await {
+            Ok(text) => {
+                return RenderedMessage {
+                    text: text.trim().to_string(),
+                    rendered: true,
+                };
+            }
+            Err(e) => {
+                warn!("Failed to render template {} for alert {}: {}", template, alert.id, e);
+            }
+        }
+    }
+
+    RenderedMessage {
+        text: format!(
+            "[{}] {}\n{}\nSource: {}",
+            alert.severity.to_string().to_uppercase(),
+            alert.title,
+            alert.description,
+            alert.source
+        ),
+        rendered: false,
+    }
+}
+
+/// POST a JSON payload once and record the outcome
+///
+/// Transport errors are reported without the URL, which may embed a secret
+/// such as a Telegram bot token or a Slack webhook path.
+async fn post_json(
+    client: &reqwest::Client,
+    url: &str,
+    payload: &serde_json::Value,
+    channel_type: ChannelType,
+) -> DeliveryResult {
+    let start_time = std::time::Instant::now();
+
+    let (success, status_code, error) = match client.post(url).json(payload).send().await {
+        Ok(response) => {
+            let status = response.status();
+            if status.is_success() {
+                (true, Some(status.as_u16()), None)
+            } else {
+                (false, Some(status.as_u16()), Some(format!("HTTP {}", status)))
+            }
+        }
+        Err(e) => (false, None, Some(e.without_url().to_string())),
+    };
+
+    if let Some(error) = &error {
+        error!("{} notification failed: {}", channel_type, error);
+    }
+
+    DeliveryResult {
+        channel_type,
+        success,
+        timestamp: chrono::Utc::now(),
+        duration_ms: start_time.elapsed().as_millis() as u64,
+        attempts: 1,
+        status_code,
+        error,
+        metadata: HashMap::new(),
+    }
+}
+
 impl fmt::Display for ChannelType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -605,6 +902,227 @@ impl fmt::Display for ChannelType {
     }
 }
 
+impl RetryConfig {
+    /// Delay before the retry following the given attempt (1-based)
+    ///
+    /// The base delay grows by the backoff multiplier after every attempt
+    /// when exponential backoff is enabled and is capped at the maximum
+    /// delay. A random jitter of up to half the delay is subtracted so that
+    /// clients do not retry in lockstep.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let initial = self.initial_delay_seconds as f64;
+        let base = if self.exponential_backoff {
+            initial * self.backoff_multiplier.max(1.0).powi(attempt.saturating_sub(1) as i32)
+        } else {
+            initial
+        };
+        let base = base.min(self.max_delay_seconds as f64);
+
+        Duration::from_secs_f64(base * (1.0 - jitter() / 2.0))
+    }
+}
+
+/// Random value in `[0, 1)`
+fn jitter() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+impl FilterConfig {
+    /// Check whether an alert passes the severity and label filters
+    ///
+    /// Severity bounds that cannot be parsed are ignored.
+    pub fn matches(&self, alert: &Alert) -> bool {
+        let severity = alert.severity.numeric_value();
+        let bound = |value: &Option<String>| {
+            value.as_deref().and_then(|value| match value.parse::<AlertSeverity>() {
+                Ok(severity) => Some(severity.numeric_value()),
+                Err(e) => {
+                    warn!("Ignoring severity filter: {}", e);
+                    None
+                }
+            })
+        };
+
+        bound(&self.min_severity).is_none_or(|min| severity >= min)
+            && bound(&self.max_severity).is_none_or(|max| severity <= max)
+            && self
+                .label_filters
+                .iter()
+                .all(|(key, value)| alert.context.labels.get(key) == Some(value))
+    }
+}
+
+/// Maximum number of alert titles listed in a summary notification
+const SUMMARY_MAX_TITLES: usize = 10;
+
+/// Token-bucket rate limiter for a notification channel
+///
+/// The bucket holds `max_messages + burst` tokens and refills at
+/// `max_messages` per window. Alerts arriving while the bucket is empty are
+/// coalesced and later sent as a single summary notification.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    state: std::sync::Mutex<RateLimiterState>,
+}
+
+#[derive(Debug)]
+struct RateLimiterState {
+    tokens: f64,
+    last_refill: tokio::time::Instant,
+    coalesced: Vec<(String, AlertSeverity)>,
+    coalesced_since: Option<tokio::time::Instant>,
+}
+
+/// Rate limiting decision for a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    /// Send the notification now
+    Send,
+    /// The notification was coalesced into the pending summary, which should
+    /// be flushed after the given delay if this is the first coalesced alert
+    Coalesced { flush_after: Option<Duration> },
+}
+
+/// Result of flushing coalesced notifications
+#[derive(Debug)]
+pub enum RateLimitFlush {
+    /// Summary notification to send
+    Summary(Alert),
+    /// No token is available yet, retry after the given delay
+    Wait(Duration),
+    /// Nothing was coalesced
+    Empty,
+}
+
+impl RateLimiter {
+    /// Create a new rate limiter with a full bucket
+    pub fn new(config: &RateLimitConfig) -> Self {
+        let config = config.clone();
+        let state = RateLimiterState {
+            tokens: Self::capacity(&config),
+            last_refill: tokio::time::Instant::now(),
+            coalesced: Vec::new(),
+            coalesced_since: None,
+        };
+
+        Self {
+            config,
+            state: std::sync::Mutex::new(state),
+        }
+    }
+
+    /// Take a token for an alert notification, or coalesce the alert
+    pub fn acquire(&self, alert: &Alert) -> RateLimitDecision {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state);
+
+        if state.coalesced.is_empty() && state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            return RateLimitDecision::Send;
+        }
+
+        state.coalesced.push((alert.title.clone(), alert.severity));
+        let flush_after = if state.coalesced_since.is_none() {
+            state.coalesced_since = Some(tokio::time::Instant::now());
+            Some(self.next_flush(&state))
+        } else {
+            None
+        };
+
+        RateLimitDecision::Coalesced { flush_after }
+    }
+
+    /// Build the summary of the coalesced alerts once a token is available
+    /// or the maximum delay has passed
+    pub fn flush(&self, channel_name: &str) -> RateLimitFlush {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state);
+
+        let Some(coalesced_since) = state.coalesced_since else {
+            return RateLimitFlush::Empty;
+        };
+        if state.tokens < 1.0 && coalesced_since.elapsed() < self.max_delay() {
+            return RateLimitFlush::Wait(self.next_flush(&state));
+        }
+
+        state.tokens = (state.tokens - 1.0).max(0.0);
+        state.coalesced_since = None;
+        let coalesced = std::mem::take(&mut state.coalesced);
+        RateLimitFlush::Summary(Self::summary(channel_name, &coalesced))
+    }
+
+    fn summary(channel_name: &str, coalesced: &[(String, AlertSeverity)]) -> Alert {
+        let severity = coalesced
+            .iter()
+            .map(|(_, severity)| *severity)
+            .max_by_key(|severity| severity.numeric_value())
+            .unwrap_or(AlertSeverity::Info);
+
+        let mut description = format!(
+            "Notifications rate limited on channel {}: {}",
+            channel_name,
+            coalesced
+                .iter()
+                .take(SUMMARY_MAX_TITLES)
+                .map(|(title, _)| title.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if coalesced.len() > SUMMARY_MAX_TITLES {
+            description.push_str(&format!(" and {} more", coalesced.len() - SUMMARY_MAX_TITLES));
+        }
+
+        let mut alert = Alert::new(
+            format!("{} similar alerts suppressed", coalesced.len()),
+            description,
+            severity,
+            "alerting".to_string(),
+        );
+        alert.add_label("channel".to_string(), channel_name.to_string());
+        alert.add_label("suppressed_count".to_string(), coalesced.len().to_string());
+        alert
+    }
+
+    fn refill(&self, state: &mut RateLimiterState) {
+        let now = tokio::time::Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.rate()).min(Self::capacity(&self.config));
+        state.last_refill = now;
+    }
+
+    /// Delay until the next token or the maximum delay, whichever is first
+    fn next_flush(&self, state: &RateLimiterState) -> Duration {
+        let next_token = Duration::from_secs_f64((1.0 - state.tokens).max(0.0) / self.rate());
+        let max_delay = self.max_delay().saturating_sub(
+            state.coalesced_since.map(|since| since.elapsed()).unwrap_or_default(),
+        );
+        next_token.min(max_delay)
+    }
+
+    /// Tokens added per second
+    fn rate(&self) -> f64 {
+        self.config.max_messages.max(1) as f64 / self.config.window_seconds.max(1) as f64
+    }
+
+    fn capacity(config: &RateLimitConfig) -> f64 {
+        (config.max_messages.max(1) + config.burst) as f64
+    }
+
+    fn max_delay(&self) -> Duration {
+        Duration::from_secs(self.config.max_delay_seconds)
+    }
+}
+
 impl Default for RetryConfig {
     fn default() -> Self {
         Self {
@@ -647,6 +1165,8 @@ mod tests {
             success: true,
             timestamp: chrono::Utc::now(),
             duration_ms: 100,
+            attempts: 1,
+            status_code: Some(200),
             error: None,
             metadata: HashMap::new(),
         };
@@ -661,4 +1181,352 @@ mod tests {
         assert_eq!(config.max_attempts, 3);
         assert!(config.exponential_backoff);
     }
+
+    fn webhook_config(url: String, max_attempts: u32) -> ChannelConfig {
+        ChannelConfig {
+            name: "ops-webhook".to_string(),
+            channel_type: ChannelType::Webhook,
+            config: HashMap::from([("url".to_string(), url)]),
+            enabled: true,
+            priority: 1,
+            rate_limit: None,
+            retry: RetryConfig {
+                max_attempts,
+                initial_delay_seconds: 0,
+                ..RetryConfig::default()
+            },
+            filter: FilterConfig::default(),
+        }
+    }
+
+    fn test_alert(severity: AlertSeverity) -> Alert {
+        Alert::new(
+            "Queue backlog".to_string(),
+            "Outbound queue above threshold".to_string(),
+            severity,
+            "queue".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_webhook_retries_then_succeeds() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let channel = WebhookChannel::new(&webhook_config(format!("{}/hook", server.uri()), 3))
+            .await
+            .unwrap();
+        let alert = test_alert(AlertSeverity::High);
+        let result = channel.send_alert(&alert).await.unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.attempts, 3);
+        assert_eq!(result.status_code, Some(200));
+        assert!(result.error.is_none());
+
+        // The body is the serialized alert
+        let requests = server.received_requests().await.unwrap();
+        let body: Alert = serde_json::from_slice(&requests[2].body).unwrap();
+        assert_eq!(body.id, alert.id);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_gives_up() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let channel = WebhookChannel::new(&webhook_config(server.uri(), 2)).await.unwrap();
+        let result = channel.send_alert(&test_alert(AlertSeverity::High)).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.attempts, 2);
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+        assert_eq!(result.status_code, Some(503));
+        assert_eq!(result.error.as_deref(), Some("HTTP 503 Service Unavailable"));
+
+        // Client errors are not retried
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400))
+            .mount(&server)
+            .await;
+
+        let channel = WebhookChannel::new(&webhook_config(server.uri(), 3)).await.unwrap();
+        let result = channel.send_alert(&test_alert(AlertSeverity::High)).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_severity_filter() {
+        let mut config = webhook_config("http://localhost/hook".to_string(), 0);
+        config.filter.min_severity = Some("high".to_string());
+        let channel = WebhookChannel::new(&config).await.unwrap();
+
+        assert!(channel.should_send_alert(&test_alert(AlertSeverity::Critical)).await);
+        assert!(channel.should_send_alert(&test_alert(AlertSeverity::High)).await);
+        assert!(!channel.should_send_alert(&test_alert(AlertSeverity::Medium)).await);
+        assert!(!channel.should_send_resolution(&test_alert(AlertSeverity::Info)).await);
+    }
+
+    fn channel_config(name: &str, channel_type: ChannelType, config: &[(&str, String)]) -> ChannelConfig {
+        ChannelConfig {
+            name: name.to_string(),
+            channel_type,
+            config: config.iter().map(|(key, value)| (key.to_string(), value.clone())).collect(),
+            enabled: true,
+            priority: 1,
+            rate_limit: None,
+            retry: RetryConfig::default(),
+            filter: FilterConfig::default(),
+        }
+    }
+
+    async fn template_engine() -> Arc<TemplateEngine> {
+        let mut templates = TemplateEngine::new(&HashMap::new()).await.unwrap();
+        templates.add_template(crate::templates::AlertTemplate::new(
+            "disk".to_string(),
+            "{{title}} on {{label.instance}}: {{description}}".to_string(),
+            crate::templates::TemplateFormat::Markdown,
+        ));
+        Arc::new(templates)
+    }
+
+    fn disk_alert() -> Alert {
+        let mut alert = Alert::new(
+            "Disk full".to_string(),
+            "Usage above 95%".to_string(),
+            AlertSeverity::Critical,
+            "storage".to_string(),
+        );
+        alert.add_label("instance".to_string(), "mx1".to_string());
+        alert
+    }
+
+    #[tokio::test]
+    async fn test_slack_block_kit_payload() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/slack"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let config = channel_config(
+            "ops-slack",
+            ChannelType::Slack,
+            &[
+                ("webhook_url", format!("{}/slack", server.uri())),
+                ("channel", "#ops".to_string()),
+                ("template", "disk".to_string()),
+            ],
+        );
+        let channel = create_channel(&config, template_engine().await).await.unwrap();
+        let result = channel.send_alert(&disk_alert()).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.channel_type, ChannelType::Slack);
+
+        let requests = server.received_requests().await.unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(payload["channel"], "#ops");
+        assert_eq!(payload["text"], "Disk full");
+        let attachment = &payload["attachments"][0];
+        assert_eq!(attachment["color"], AlertSeverity::Critical.color_code());
+        let blocks = attachment["blocks"].as_array().unwrap();
+        assert_eq!(blocks[0]["type"], "header");
+        assert_eq!(blocks[0]["text"]["text"], "Disk full");
+        assert_eq!(blocks[1]["type"], "section");
+        assert_eq!(blocks[1]["text"]["type"], "mrkdwn");
+        assert_eq!(blocks[1]["text"]["text"], "Disk full on mx1: Usage above 95%");
+        assert_eq!(blocks[2]["type"], "context");
+    }
+
+    #[tokio::test]
+    async fn test_telegram_send_message() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/bot123:secret/sendMessage"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "ok": true })))
+            .mount(&server)
+            .await;
+
+        let config = |template: &str| {
+            channel_config(
+                "ops-telegram",
+                ChannelType::Telegram,
+                &[
+                    ("bot_token", "123:secret".to_string()),
+                    ("chat_id", "-100200300".to_string()),
+                    ("api_url", server.uri()),
+                    ("template", template.to_string()),
+                ],
+            )
+        };
+
+        let channel = create_channel(&config("disk"), template_engine().await).await.unwrap();
+        let result = channel.send_alert(&disk_alert()).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.channel_type, ChannelType::Telegram);
+
+        let requests = server.received_requests().await.unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(payload["chat_id"], "-100200300");
+        assert_eq!(payload["parse_mode"], "Markdown");
+        assert_eq!(payload["text"], "Disk full on mx1: Usage above 95%");
+
+        // Unknown templates fall back to a plain summary
+        let channel = create_channel(&config("missing"), template_engine().await).await.unwrap();
+        assert!(channel.send_alert(&disk_alert()).await.unwrap().success);
+
+        let requests = server.received_requests().await.unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert!(payload.get("parse_mode").is_none());
+        assert_eq!(payload["text"], "[CRITICAL] Disk full\nUsage above 95%\nSource: storage");
+
+        // Markdown Telegram cannot parse is sent again as plain text
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({ "parse_mode": "Markdown" })))
+            .respond_with(ResponseTemplate::new(400))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let mut config = config("disk");
+        config.config.insert("api_url".to_string(), server.uri());
+        let channel = create_channel(&config, template_engine().await).await.unwrap();
+        let result = channel.send_alert(&disk_alert()).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_telegram_errors_hide_bot_token() {
+        // Nothing listens on the discard port, so the request fails to connect
+        let config = channel_config(
+            "ops-telegram",
+            ChannelType::Telegram,
+            &[
+                ("bot_token", "123:secret".to_string()),
+                ("chat_id", "-100200300".to_string()),
+                ("api_url", "http://127.0.0.1:9".to_string()),
+            ],
+        );
+        let channel = create_channel(&config, template_engine().await).await.unwrap();
+
+        let result = channel.send_alert(&disk_alert()).await.unwrap();
+        assert!(!result.success);
+        assert!(!result.error.unwrap().contains("secret"));
+
+        let err = channel.test_connection().await.unwrap_err();
+        assert!(!err.to_string().contains("secret"));
+        assert!(!format!("{:?}", channel).contains("secret"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter() {
+        let limiter = RateLimiter::new(&RateLimitConfig {
+            max_messages: 2,
+            window_seconds: 1,
+            burst: 1,
+            max_delay_seconds: 60,
+        });
+        let alert = test_alert(AlertSeverity::Warning);
+
+        // The bucket starts with max_messages + burst tokens
+        for _ in 0..3 {
+            assert_eq!(limiter.acquire(&alert), RateLimitDecision::Send);
+        }
+        assert_eq!(
+            limiter.acquire(&alert),
+            RateLimitDecision::Coalesced {
+                flush_after: Some(Duration::from_millis(500))
+            }
+        );
+        let mut critical = test_alert(AlertSeverity::Critical);
+        critical.title = "Queue stalled".to_string();
+        assert_eq!(limiter.acquire(&critical), RateLimitDecision::Coalesced { flush_after: None });
+        assert!(matches!(limiter.flush("ops"), RateLimitFlush::Wait(_)));
+
+        // Later alerts queue behind the summary even once tokens are back
+        tokio::time::advance(Duration::from_millis(500)).await;
+        assert_eq!(limiter.acquire(&alert), RateLimitDecision::Coalesced { flush_after: None });
+
+        let RateLimitFlush::Summary(summary) = limiter.flush("ops") else {
+            panic!("expected a summary");
+        };
+        assert_eq!(summary.title, "3 similar alerts suppressed");
+        assert_eq!(summary.severity, AlertSeverity::Critical);
+        assert!(summary.description.contains("Queue stalled"));
+        assert_eq!(summary.context.labels["suppressed_count"], "3");
+        assert!(matches!(limiter.flush("ops"), RateLimitFlush::Empty));
+
+        // Without tokens the summary waits at most max_delay_seconds
+        let limiter = RateLimiter::new(&RateLimitConfig {
+            max_messages: 1,
+            window_seconds: 3600,
+            burst: 0,
+            max_delay_seconds: 60,
+        });
+        assert_eq!(limiter.acquire(&alert), RateLimitDecision::Send);
+        assert_eq!(
+            limiter.acquire(&alert),
+            RateLimitDecision::Coalesced {
+                flush_after: Some(Duration::from_secs(60))
+            }
+        );
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert!(matches!(limiter.flush("ops"), RateLimitFlush::Summary(_)));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let config = RetryConfig {
+            max_attempts: 5,
+            initial_delay_seconds: 2,
+            max_delay_seconds: 10,
+            backoff_multiplier: 2.0,
+            exponential_backoff: true,
+        };
+
+        for (attempt, base) in [(1, 2.0), (2, 4.0), (3, 8.0), (4, 10.0), (10, 10.0)] {
+            let delay = config.delay(attempt).as_secs_f64();
+            assert!(delay > base / 2.0 && delay <= base, "attempt {}: {:?}", attempt, delay);
+        }
+
+        let linear = RetryConfig {
+            exponential_backoff: false,
+            ..config
+        };
+        assert!(linear.delay(4).as_secs_f64() <= 2.0);
+    }
 }
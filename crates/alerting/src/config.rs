@@ -61,6 +61,13 @@ pub struct AlertingConfig {
     /// Deduplication window
     pub deduplication_window: Duration,
 
+    /// Alert fields making up the deduplication fingerprint
+    ///
+    /// Besides `title`, `description`, `severity` and `source`, label values
+    /// can be included as `labels.<name>`.
+    #[serde(default = "default_deduplication_fields")]
+    pub deduplication_fields: Vec<String>,
+
     /// Enable alert grouping
     pub enable_grouping: bool,
 
@@ -142,6 +149,10 @@ pub struct StorageConfig {
     /// Storage connection string
     pub connection_string: String,
 
+    /// Maximum number of alerts kept by the in-memory history
+    #[serde(default = "default_max_history_alerts")]
+    pub max_history_alerts: usize,
+
     /// Connection pool size
     pub pool_size: u32,
 
@@ -172,6 +183,8 @@ pub struct StorageConfig {
 pub enum StorageBackend {
     /// In-memory storage (for testing)
     Memory,
+    /// JSON lines file at the connection string path
+    File,
     /// SQLite database
     Sqlite,
     /// PostgreSQL database
@@ -357,12 +370,21 @@ impl Default for AlertingConfig {
             default_timeout: Some(Duration::from_secs(3600)), // 1 hour
             enable_deduplication: true,
             deduplication_window: Duration::from_secs(300), // 5 minutes
+            deduplication_fields: default_deduplication_fields(),
             enable_grouping: false,
             grouping: GroupingConfig::default(),
         }
     }
 }
 
+fn default_max_history_alerts() -> usize {
+    crate::store::DEFAULT_MAX_HISTORY_ALERTS
+}
+
+fn default_deduplication_fields() -> Vec<String> {
+    ["title", "severity", "source"].into_iter().map(String::from).collect()
+}
+
 impl Default for GlobalConfig {
     fn default() -> Self {
         Self {
@@ -401,6 +423,7 @@ impl Default for StorageConfig {
         Self {
             backend: StorageBackend::Memory,
             connection_string: "memory://".to_string(),
+            max_history_alerts: default_max_history_alerts(),
             pool_size: 10,
             connection_timeout: Duration::from_secs(30),
             query_timeout: Duration::from_secs(60),
@@ -9,6 +9,7 @@
 use crate::alert::Alert;
 use crate::error::{AlertingError, Result};
 use crate::rules::AlertRule;
+use crate::suppression::{MaintenanceWindow, SuppressionConfig, SuppressionManager};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -18,6 +19,7 @@ use tracing::{info, warn, error, debug};
 #[derive(Debug)]
 pub struct AlertingEngine {
     rules: Arc<RwLock<Vec<AlertRule>>>,
+    suppression: Arc<RwLock<SuppressionManager>>,
     config: HashMap<String, String>,
     running: Arc<RwLock<bool>>,
 }
@@ -29,6 +31,7 @@ impl AlertingEngine {
         
         Ok(Self {
             rules: Arc::new(RwLock::new(Vec::new())),
+            suppression: Arc::new(RwLock::new(SuppressionManager::new(SuppressionConfig::default()))),
             config: config.clone(),
             running: Arc::new(RwLock::new(false)),
         })
@@ -74,9 +77,49 @@ impl AlertingEngine {
     /// Check if an alert should be suppressed
     pub async fn should_suppress(&self, alert: &Alert) -> Result<bool> {
         debug!("Checking suppression for alert: {}", alert.id);
-        
-        // TODO: Implement suppression logic
-        Ok(false)
+
+        Ok(self.suppression.read().await.should_suppress(alert))
+    }
+
+    /// Add a maintenance window
+    ///
+    /// Windows that do not recur are removed once they end.
+    pub async fn add_maintenance_window(&self, window: MaintenanceWindow) -> Result<()> {
+        window.validate()?;
+        info!("Adding maintenance window: {}", window.name);
+
+        if window.recurrence.is_none() {
+            let end_time = window.end_time;
+            let remaining = (end_time - chrono::Utc::now()).to_std().unwrap_or_default();
+            let suppression = self.suppression.clone();
+
+            tokio::spawn(async move {
+                tokio::time::sleep(remaining).await;
+
+                let removed = suppression.write().await.cleanup_at(end_time);
+                debug!("Removed {} expired maintenance windows", removed);
+            });
+        }
+
+        self.suppression.write().await.add_maintenance_window(window);
+        Ok(())
+    }
+
+    /// Remove a maintenance window
+    pub async fn remove_maintenance_window(&self, name: &str) -> bool {
+        info!("Removing maintenance window: {}", name);
+        self.suppression.write().await.remove_maintenance_window(name)
+    }
+
+    /// Get the maintenance windows active now
+    pub async fn active_maintenance_windows(&self) -> Vec<MaintenanceWindow> {
+        self.suppression
+            .read()
+            .await
+            .get_active_windows()
+            .into_iter()
+            .cloned()
+            .collect()
     }
     
     /// Add a new alert rule
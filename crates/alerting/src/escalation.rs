@@ -6,10 +6,14 @@
 
 //! Alert escalation policies and management
 
+use crate::alert::Alert;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
 
+/// Alert label selecting the escalation policy for an alert
+pub const ESCALATION_POLICY_LABEL: &str = "escalation_policy";
+
 /// Escalation policy for alerts
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EscalationPolicy {
@@ -157,6 +161,27 @@ impl EscalationLevel {
     }
 }
 
+impl EscalationConfig {
+    /// Get the escalation policy that applies to an alert
+    ///
+    /// The policy named by the alert's `escalation_policy` label takes
+    /// precedence over the default policy. Disabled policies and policies
+    /// without levels never apply.
+    pub fn policy_for(&self, alert: &Alert) -> Option<&EscalationPolicy> {
+        if !self.global_settings.enabled {
+            return None;
+        }
+
+        alert
+            .context
+            .labels
+            .get(ESCALATION_POLICY_LABEL)
+            .or(self.default_policy.as_ref())
+            .and_then(|name| self.policies.get(name))
+            .filter(|policy| policy.enabled && !policy.levels.is_empty())
+    }
+}
+
 impl Default for EscalationConfig {
     fn default() -> Self {
         Self {
@@ -238,6 +263,33 @@ mod tests {
         assert!(!level.should_repeat(3));
     }
 
+    #[test]
+    fn test_policy_selection() {
+        let mut config = EscalationConfig::default();
+        for name in ["default", "database"] {
+            let mut policy = EscalationPolicy::new(name.to_string());
+            policy.add_level(EscalationLevel::new(0, Duration::ZERO, vec![name.to_string()]));
+            config.policies.insert(name.to_string(), policy);
+        }
+
+        let mut alert = Alert::new(
+            "Test Alert".to_string(),
+            "Test Description".to_string(),
+            crate::alert::AlertSeverity::High,
+            "test_source".to_string(),
+        );
+        assert!(config.policy_for(&alert).is_none());
+
+        config.default_policy = Some("default".to_string());
+        assert_eq!(config.policy_for(&alert).unwrap().name, "default");
+
+        alert.add_label(ESCALATION_POLICY_LABEL.to_string(), "database".to_string());
+        assert_eq!(config.policy_for(&alert).unwrap().name, "database");
+
+        config.policies.get_mut("database").unwrap().disable();
+        assert!(config.policy_for(&alert).is_none());
+    }
+
     #[test]
     fn test_global_escalation_settings() {
         let settings = GlobalEscalationSettings::default();
@@ -23,22 +23,25 @@ pub mod error;
 pub mod escalation;
 pub mod metrics;
 pub mod rules;
+pub mod store;
 pub mod templates;
 pub mod suppression;
 
 pub use alert::{Alert, AlertSeverity, AlertStatus, AlertContext};
-pub use channels::{NotificationChannelImpl, ChannelType, DeliveryResult, ChannelConfig};
+pub use channels::{NotificationChannel, NotificationChannelImpl, ChannelType, DeliveryResult, ChannelConfig};
 pub use config::AlertingConfig;
 pub use engine::AlertingEngine;
 pub use error::{AlertingError, Result};
-pub use escalation::{EscalationPolicy, EscalationLevel};
+pub use escalation::{EscalationPolicy, EscalationLevel, EscalationConfig};
 pub use metrics::AlertingMetrics;
 pub use rules::{AlertRule, RuleCondition, RuleAction};
+pub use store::{AlertHistoryFilter, AlertStore, FileAlertStore, MemoryAlertStore};
+pub use suppression::MaintenanceWindow;
 pub use templates::{AlertTemplate, TemplateEngine};
 
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{info, warn, error};
+use tracing::{debug, info, warn, error};
 use uuid::Uuid;
 
 /// Main alerting service
@@ -52,14 +55,53 @@ struct AlertingServiceInner {
     config: AlertingConfig,
     engine: AlertingEngine,
     channels: Vec<NotificationChannelImpl>,
-    template_engine: TemplateEngine,
+    /// Rate limiters, indexed like `channels`
+    rate_limiters: Vec<Option<channels::RateLimiter>>,
+    template_engine: Arc<TemplateEngine>,
     metrics: Arc<RwLock<AlertingMetrics>>,
     active_alerts: Arc<RwLock<std::collections::HashMap<Uuid, Alert>>>,
+    escalation: RwLock<EscalationConfig>,
+    store: Arc<dyn AlertStore>,
 }
 
 impl AlertingService {
     /// Create a new alerting service
+    ///
+    /// Alert history is stored in the backend selected by the storage
+    /// configuration.
     pub async fn new(config: AlertingConfig) -> Result<Self> {
+        let store: Arc<dyn AlertStore> = match config.storage.backend {
+            config::StorageBackend::Memory => {
+                Arc::new(MemoryAlertStore::new(config.storage.max_history_alerts))
+            }
+            config::StorageBackend::File => {
+                let connection_string = &config.storage.connection_string;
+                let path = connection_string.strip_prefix("file://").unwrap_or(connection_string);
+                Arc::new(FileAlertStore::new(path))
+            }
+            ref backend => {
+                warn!("Storage backend {:?} not supported, keeping alert history in memory", backend);
+                Arc::new(MemoryAlertStore::new(config.storage.max_history_alerts))
+            }
+        };
+
+        Self::with_store(config, store).await
+    }
+
+    /// Create a new alerting service storing alert history in `store`
+    pub async fn with_store(config: AlertingConfig, store: Arc<dyn AlertStore>) -> Result<Self> {
+        Self::with_channels(config, store, Vec::new()).await
+    }
+
+    /// Create a new alerting service that also notifies channels implemented
+    /// outside this crate
+    ///
+    /// Custom channels are not rate limited.
+    pub async fn with_channels(
+        config: AlertingConfig,
+        store: Arc<dyn AlertStore>,
+        custom_channels: Vec<Arc<dyn NotificationChannel>>,
+    ) -> Result<Self> {
         info!("Initializing alerting service");
 
         // Create alerting engine
@@ -73,20 +115,36 @@ impl AlertingService {
                     "webhook" => ChannelType::Webhook,
                     "slack" => ChannelType::Slack,
                     "email" => ChannelType::Email,
+                    "telegram" => ChannelType::Telegram,
                     _ => ChannelType::Webhook,
                 },
                 config: ch.clone(),
                 enabled: ch.get("enabled").and_then(|v| v.parse().ok()).unwrap_or(true),
                 priority: ch.get("priority").and_then(|v| v.parse().ok()).unwrap_or(1),
-                rate_limit: None,
+                rate_limit: ch.get("rate_limit").and_then(|v| v.parse().ok()).map(|max_messages| {
+                    channels::RateLimitConfig {
+                        max_messages,
+                        window_seconds: ch.get("rate_limit_window").and_then(|v| v.parse().ok()).unwrap_or(1),
+                        burst: ch.get("rate_limit_burst").and_then(|v| v.parse().ok()).unwrap_or(0),
+                        max_delay_seconds: ch.get("rate_limit_max_delay").and_then(|v| v.parse().ok()).unwrap_or(60),
+                    }
+                }),
                 retry: channels::RetryConfig::default(),
                 filter: channels::FilterConfig::default(),
             }
         }).collect();
-        let channels = Self::initialize_channels(&channel_configs).await?;
-
         // Create template engine
-        let template_engine = TemplateEngine::new(&config.templates).await?;
+        let template_engine = Arc::new(TemplateEngine::new(&config.templates).await?);
+
+        let mut channels = Self::initialize_channels(&channel_configs, &template_engine).await?;
+        let mut rate_limiters = channel_configs
+            .iter()
+            .map(|config| config.rate_limit.as_ref().map(channels::RateLimiter::new))
+            .collect::<Vec<_>>();
+        for channel in custom_channels {
+            channels.push(NotificationChannelImpl::Custom(channel));
+            rate_limiters.push(None);
+        }
 
         // Create metrics collector
         let metrics = Arc::new(RwLock::new(AlertingMetrics::new()));
@@ -94,14 +152,23 @@ impl AlertingService {
         // Initialize active alerts storage
         let active_alerts = Arc::new(RwLock::new(std::collections::HashMap::new()));
 
+        // Policies are registered at runtime, only the default is configured
+        let escalation = EscalationConfig {
+            default_policy: config.escalation.get("default_policy").cloned(),
+            ..EscalationConfig::default()
+        };
+
         Ok(Self {
             inner: Arc::new(AlertingServiceInner {
                 config,
                 engine,
                 channels,
+                rate_limiters,
                 template_engine,
                 metrics,
                 active_alerts,
+                escalation: RwLock::new(escalation),
+                store,
             }),
         })
     }
@@ -138,30 +205,60 @@ impl AlertingService {
     pub async fn fire_alert(&self, alert: Alert) -> Result<Uuid> {
         info!("Firing alert: {}", alert.title);
 
-        // Check if alert should be suppressed
+        let mut alert = alert;
+        alert.context.fingerprint = alert.fingerprint(&self.inner.config.deduplication_fields);
+
+        // Suppressed alerts are kept for history but not notified
         if self.inner.engine.should_suppress(&alert).await? {
-            info!("Alert suppressed: {}", alert.title);
-            return Ok(alert.id);
+            alert.suppress();
         }
 
-        // Check for existing similar alerts (deduplication)
+        // Check for existing alerts with the same fingerprint (deduplication)
         if let Some(existing_id) = self.find_similar_alert(&alert).await? {
             info!("Alert deduplicated with existing alert: {}", existing_id);
             self.update_alert_count(existing_id).await?;
             return Ok(existing_id);
         }
 
+        if alert.status == AlertStatus::Suppressed {
+            info!("Alert suppressed: {}", alert.title);
+            self.inner.active_alerts.write().await.insert(alert.id, alert.clone());
+            self.persist(&alert).await;
+            self.inner.metrics.write().await.record_alert_fired(&alert);
+            return Ok(alert.id);
+        }
+
+        // Escalated alerts start at the first level of their policy
+        let policy = {
+            let escalation = self.inner.escalation.read().await;
+            escalation.policy_for(&alert).cloned().map(|policy| (policy, escalation.global_settings.clone()))
+        };
+        if let Some((policy, _)) = &policy {
+            let first = &policy.levels[0];
+            let next_escalation = policy
+                .get_next_level(first.level)
+                .map(|next| chrono::Utc::now() + chrono::Duration::from_std(next.delay).unwrap_or_default());
+            alert.set_escalation_level(first.level, next_escalation);
+        }
+
         // Store the alert
         {
             let mut active_alerts = self.inner.active_alerts.write().await;
             active_alerts.insert(alert.id, alert.clone());
         }
+        self.persist(&alert).await;
 
         // Process the alert through the engine
         self.inner.engine.process_alert(alert.clone()).await?;
 
         // Send notifications
-        self.send_notifications(&alert).await?;
+        match policy {
+            Some((policy, settings)) => {
+                self.send_notifications(&alert, Some(&policy.levels[0].channels)).await?;
+                self.start_escalation(alert.id, policy, settings);
+            }
+            None => self.send_notifications(&alert, None).await?,
+        }
 
         // Update metrics
         {
@@ -193,6 +290,7 @@ impl AlertingService {
         if resolved {
             // Send resolution notifications
             if let Some(alert) = self.get_alert(alert_id).await? {
+                self.persist(&alert).await;
                 self.send_resolution_notifications(&alert).await?;
             }
 
@@ -210,12 +308,92 @@ impl AlertingService {
         Ok(resolved)
     }
 
+    /// Acknowledge an alert, stopping its escalation
+    pub async fn acknowledge_alert(&self, alert_id: Uuid, by: &str) -> Result<bool> {
+        info!("Acknowledging alert: {} by {}", alert_id, by);
+
+        let mut active_alerts = self.inner.active_alerts.write().await;
+        match active_alerts.get_mut(&alert_id) {
+            Some(alert) if alert.status == AlertStatus::Firing => {
+                alert.acknowledge(by.to_string());
+                alert.next_escalation = None;
+                let alert = alert.clone();
+                drop(active_alerts);
+
+                self.persist(&alert).await;
+                Ok(true)
+            }
+            Some(alert) => {
+                warn!("Alert {} cannot be acknowledged while {}", alert_id, alert.status);
+                Ok(false)
+            }
+            None => {
+                warn!("Alert not found for acknowledgement: {}", alert_id);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Add a maintenance window suppressing matching alerts while active
+    pub async fn add_maintenance_window(&self, window: MaintenanceWindow) -> Result<()> {
+        self.inner.engine.add_maintenance_window(window).await
+    }
+
+    /// Remove a maintenance window
+    pub async fn remove_maintenance_window(&self, name: &str) -> bool {
+        self.inner.engine.remove_maintenance_window(name).await
+    }
+
+    /// Set the escalation policies
+    pub async fn set_escalation_config(&self, config: EscalationConfig) {
+        *self.inner.escalation.write().await = config;
+    }
+
+    /// Add an escalation policy, replacing any policy with the same name
+    pub async fn add_escalation_policy(&self, policy: EscalationPolicy) {
+        info!("Adding escalation policy: {}", policy.name);
+        self.inner
+            .escalation
+            .write()
+            .await
+            .policies
+            .insert(policy.name.clone(), policy);
+    }
+
     /// Get an alert by ID
     pub async fn get_alert(&self, alert_id: Uuid) -> Result<Option<Alert>> {
         let active_alerts = self.inner.active_alerts.read().await;
         Ok(active_alerts.get(&alert_id).cloned())
     }
 
+    /// Query the alert history
+    ///
+    /// Stored alerts are combined with the alerts still held in memory, whose
+    /// state is the most recent.
+    pub async fn query_history(&self, filter: AlertHistoryFilter) -> Result<Vec<Alert>> {
+        let unpaged = AlertHistoryFilter {
+            offset: 0,
+            limit: None,
+            ..filter.clone()
+        };
+
+        let mut alerts = self
+            .inner
+            .store
+            .query(&unpaged)
+            .await?
+            .into_iter()
+            .map(|alert| (alert.id, alert))
+            .collect::<std::collections::HashMap<_, _>>();
+        for alert in self.inner.active_alerts.read().await.values() {
+            if filter.matches(alert) {
+                alerts.insert(alert.id, alert.clone());
+            }
+        }
+
+        Ok(filter.apply(alerts.into_values()))
+    }
+
     /// List active alerts
     pub async fn list_active_alerts(&self) -> Result<Vec<Alert>> {
         let active_alerts = self.inner.active_alerts.read().await;
@@ -242,11 +420,12 @@ impl AlertingService {
     /// Initialize notification channels from configuration
     async fn initialize_channels(
         channel_configs: &[ChannelConfig],
+        template_engine: &Arc<TemplateEngine>,
     ) -> Result<Vec<NotificationChannelImpl>> {
         let mut channels = Vec::new();
 
         for config in channel_configs {
-            let channel = channels::create_channel(config).await?;
+            let channel = channels::create_channel(config, template_engine.clone()).await?;
             channels.push(channel);
         }
 
@@ -255,11 +434,28 @@ impl AlertingService {
     }
 
     /// Send notifications for an alert
-    async fn send_notifications(&self, alert: &Alert) -> Result<()> {
+    ///
+    /// When `channel_names` is given, only the named channels are notified.
+    async fn send_notifications(&self, alert: &Alert, channel_names: Option<&[String]>) -> Result<()> {
         let mut delivery_results = Vec::new();
 
-        for channel in &self.inner.channels {
+        for (index, channel) in self.inner.channels.iter().enumerate() {
+            if channel_names.is_some_and(|names| !names.iter().any(|name| name == channel.name())) {
+                continue;
+            }
+
             if channel.should_send_alert(alert).await {
+                // Notifications over the channel's rate are sent as a summary
+                if let Some(limiter) = &self.inner.rate_limiters[index] {
+                    if let channels::RateLimitDecision::Coalesced { flush_after } = limiter.acquire(alert) {
+                        debug!("Alert {} rate limited on channel {}", alert.id, channel.name());
+                        if let Some(delay) = flush_after {
+                            self.schedule_rate_limit_summary(index, delay);
+                        }
+                        continue;
+                    }
+                }
+
                 match channel.send_alert(alert).await {
                     Ok(result) => {
                         delivery_results.push(result);
@@ -283,6 +479,92 @@ impl AlertingService {
         Ok(())
     }
 
+    /// Send the summary of a channel's rate limited notifications once the
+    /// rate limiter allows it
+    fn schedule_rate_limit_summary(&self, index: usize, delay: std::time::Duration) {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let channel = &service.inner.channels[index];
+            let Some(limiter) = &service.inner.rate_limiters[index] else {
+                return;
+            };
+
+            let mut delay = delay;
+            loop {
+                tokio::time::sleep(delay).await;
+
+                match limiter.flush(channel.name()) {
+                    channels::RateLimitFlush::Summary(summary) => {
+                        match channel.send_alert(&summary).await {
+                            Ok(result) => {
+                                info!("Rate limit summary sent via {}: {}", channel.channel_type(), summary.title);
+                                service.inner.metrics.write().await.record_notification_sent(&result);
+                            }
+                            Err(e) => {
+                                error!("Failed to send rate limit summary via {}: {}", channel.channel_type(), e);
+                            }
+                        }
+                        return;
+                    }
+                    channels::RateLimitFlush::Wait(next) => delay = next,
+                    channels::RateLimitFlush::Empty => return,
+                }
+            }
+        });
+    }
+
+    /// Start the escalation timer of a newly fired alert
+    fn start_escalation(
+        &self,
+        alert_id: Uuid,
+        policy: EscalationPolicy,
+        settings: escalation::GlobalEscalationSettings,
+    ) {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let started = tokio::time::Instant::now();
+            let mut current = policy.levels[0].level;
+
+            while let Some(level) = policy.get_next_level(current) {
+                if level.level > settings.max_level
+                    || settings
+                        .escalation_timeout
+                        .is_some_and(|timeout| started.elapsed() + level.delay > timeout)
+                {
+                    break;
+                }
+
+                tokio::time::sleep(level.delay).await;
+
+                // Only alerts nobody has acknowledged or resolved are escalated
+                let alert = {
+                    let mut active_alerts = service.inner.active_alerts.write().await;
+                    match active_alerts.get_mut(&alert_id) {
+                        Some(alert) if alert.status == AlertStatus::Firing => {
+                            let next_escalation = policy.get_next_level(level.level).map(|next| {
+                                chrono::Utc::now() + chrono::Duration::from_std(next.delay).unwrap_or_default()
+                            });
+                            alert.set_escalation_level(level.level, next_escalation);
+                            alert.clone()
+                        }
+                        _ => {
+                            debug!("Escalation of alert {} stopped", alert_id);
+                            return;
+                        }
+                    }
+                };
+
+                info!("Escalating alert {} to level {}", alert_id, level.level);
+                if let Err(e) = service.send_notifications(&alert, Some(&level.channels)).await {
+                    error!("Failed to send escalation of alert {}: {}", alert_id, e);
+                }
+                current = level.level;
+            }
+        });
+    }
+
     /// Send resolution notifications
     async fn send_resolution_notifications(&self, alert: &Alert) -> Result<()> {
         for channel in &self.inner.channels {
@@ -301,33 +583,31 @@ impl AlertingService {
         Ok(())
     }
 
-    /// Find similar alert for deduplication
+    /// Find an active alert with the same fingerprint for deduplication
+    ///
+    /// Suppressed alerts are only deduplicated with other suppressed alerts,
+    /// so that alerts raised after a maintenance window are notified.
     async fn find_similar_alert(&self, alert: &Alert) -> Result<Option<Uuid>> {
-        let active_alerts = self.inner.active_alerts.read().await;
-
-        for (id, existing_alert) in active_alerts.iter() {
-            if self.alerts_are_similar(alert, existing_alert) {
-                return Ok(Some(*id));
-            }
+        if !self.inner.config.enable_deduplication {
+            return Ok(None);
         }
 
-        Ok(None)
-    }
-
-    /// Check if two alerts are similar for deduplication
-    fn alerts_are_similar(&self, alert1: &Alert, alert2: &Alert) -> bool {
-        // Simple similarity check - can be made more sophisticated
-        alert1.title == alert2.title &&
-        alert1.severity == alert2.severity &&
-        alert1.source == alert2.source
+        let suppressed = alert.status == AlertStatus::Suppressed;
+        let active_alerts = self.inner.active_alerts.read().await;
+        Ok(active_alerts
+            .values()
+            .find(|existing_alert| {
+                existing_alert.context.fingerprint == alert.context.fingerprint
+                    && (existing_alert.status == AlertStatus::Suppressed) == suppressed
+            })
+            .map(|existing_alert| existing_alert.id))
     }
 
     /// Update alert count for deduplicated alerts
     async fn update_alert_count(&self, alert_id: Uuid) -> Result<()> {
         let mut active_alerts = self.inner.active_alerts.write().await;
         if let Some(alert) = active_alerts.get_mut(&alert_id) {
-            alert.count += 1;
-            alert.last_occurrence = chrono::Utc::now();
+            alert.update_occurrence();
         }
         Ok(())
     }
@@ -354,9 +634,46 @@ impl AlertingService {
         });
     }
 
+    /// Save an alert to the history store
+    async fn persist(&self, alert: &Alert) {
+        if let Err(e) = self.inner.store.save(std::slice::from_ref(alert)).await {
+            error!("Failed to store alert {}: {}", alert.id, e);
+            self.inner.metrics.write().await.record_error(
+                "store",
+                &e.to_string(),
+                std::collections::HashMap::from([("alert_id".to_string(), alert.id.to_string())]),
+            );
+        }
+    }
+
+    /// Drop the alerts resolved before `cutoff` from memory
+    ///
+    /// The alerts are written to the store first and kept in memory if that
+    /// fails.
+    async fn cleanup_resolved_alerts(&self, cutoff: chrono::DateTime<chrono::Utc>) -> Result<usize> {
+        let mut alerts = self.inner.active_alerts.write().await;
+        let expired = alerts
+            .values()
+            .filter(|alert| {
+                alert.status == AlertStatus::Resolved && alert.resolved_at.is_some_and(|t| t <= cutoff)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        self.inner.store.save(&expired).await?;
+        for alert in &expired {
+            alerts.remove(&alert.id);
+        }
+
+        Ok(expired.len())
+    }
+
     /// Start alert cleanup background task
     async fn start_alert_cleanup(&self) {
-        let active_alerts = self.inner.active_alerts.clone();
+        let service = self.clone();
         let cleanup_interval = self.inner.config.cleanup_interval;
 
         tokio::spawn(async move {
@@ -368,12 +685,10 @@ impl AlertingService {
                 // Clean up resolved alerts older than retention period
                 let cutoff_time = chrono::Utc::now() - chrono::Duration::hours(24);
 
-                {
-                    let mut alerts = active_alerts.write().await;
-                    alerts.retain(|_, alert| {
-                        alert.status != AlertStatus::Resolved ||
-                        alert.resolved_at.map_or(true, |t| t > cutoff_time)
-                    });
+                match service.cleanup_resolved_alerts(cutoff_time).await {
+                    Ok(0) => {}
+                    Ok(removed) => debug!("Archived {} resolved alerts", removed),
+                    Err(e) => error!("Failed to archive resolved alerts: {}", e),
                 }
             }
         });
@@ -383,6 +698,7 @@ impl AlertingService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
 
     #[tokio::test]
     async fn test_service_creation() {
@@ -422,4 +738,355 @@ mod tests {
         let alert = service.get_alert(alert_id).await.unwrap().unwrap();
         assert_eq!(alert.status, AlertStatus::Resolved);
     }
+
+    #[tokio::test]
+    async fn test_fingerprint_deduplication() {
+        let alert = |instance: &str| {
+            let mut alert = Alert::new(
+                "Backend unreachable".to_string(),
+                "Health check failed".to_string(),
+                AlertSeverity::High,
+                "store".to_string(),
+            );
+            alert.add_label("instance".to_string(), instance.to_string());
+            alert
+        };
+
+        // The default fingerprint ignores labels
+        let service = AlertingService::new(AlertingConfig::default()).await.unwrap();
+        let first = service.fire_alert(alert("mx1")).await.unwrap();
+        assert_eq!(service.fire_alert(alert("mx2")).await.unwrap(), first);
+        let active_alerts = service.list_active_alerts().await.unwrap();
+        assert_eq!(active_alerts.len(), 1);
+        assert_eq!(active_alerts[0].count, 2);
+
+        let mut config = AlertingConfig::default();
+        config.deduplication_fields.push("labels.instance".to_string());
+        let service = AlertingService::new(config).await.unwrap();
+        let mx1 = service.fire_alert(alert("mx1")).await.unwrap();
+        let mx2 = service.fire_alert(alert("mx2")).await.unwrap();
+        assert_ne!(mx1, mx2);
+        assert_eq!(service.list_active_alerts().await.unwrap().len(), 2);
+
+        // Repeats of the same instance are still deduplicated
+        assert_eq!(service.fire_alert(alert("mx1")).await.unwrap(), mx1);
+        assert_eq!(service.get_alert(mx1).await.unwrap().unwrap().count, 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_maintenance_window() {
+        let service = AlertingService::new(AlertingConfig::default()).await.unwrap();
+        let alert = |instance: &str| {
+            let mut alert = Alert::new(
+                "Backend unreachable".to_string(),
+                "Health check failed".to_string(),
+                AlertSeverity::High,
+                "store".to_string(),
+            );
+            alert.add_label("instance".to_string(), instance.to_string());
+            alert
+        };
+
+        let now = chrono::Utc::now();
+        let window = MaintenanceWindow::new(
+            "mx1_upgrade".to_string(),
+            now - chrono::Duration::minutes(1),
+            now + chrono::Duration::minutes(10),
+        )
+        .with_label("instance".to_string(), "mx1".to_string());
+        service.add_maintenance_window(window).await.unwrap();
+        tokio::task::yield_now().await;
+
+        // Matching alerts are suppressed but recorded
+        let suppressed_id = service.fire_alert(alert("mx1")).await.unwrap();
+        let suppressed = service.get_alert(suppressed_id).await.unwrap().unwrap();
+        assert_eq!(suppressed.status, AlertStatus::Suppressed);
+        assert_eq!(service.get_metrics().await.alerts_by_status["suppressed"], 1);
+
+        let firing_id = service.fire_alert(alert("mx2")).await.unwrap();
+        assert_eq!(service.get_alert(firing_id).await.unwrap().unwrap().status, AlertStatus::Firing);
+
+        // The window is removed once it ends
+        tokio::time::advance(Duration::from_secs(11 * 60)).await;
+        tokio::task::yield_now().await;
+        assert!(service.inner.engine.active_maintenance_windows().await.is_empty());
+
+        let alert_id = service.fire_alert(alert("mx1")).await.unwrap();
+        assert_ne!(alert_id, suppressed_id);
+        assert_eq!(service.get_alert(alert_id).await.unwrap().unwrap().status, AlertStatus::Firing);
+    }
+
+    #[tokio::test]
+    async fn test_channel_rate_limit() {
+        use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let mut config = AlertingConfig::default();
+        config.channels.push(std::collections::HashMap::from([
+            ("name".to_string(), "ops".to_string()),
+            ("type".to_string(), "webhook".to_string()),
+            ("url".to_string(), server.uri()),
+            ("rate_limit".to_string(), "5".to_string()),
+        ]));
+        let service = AlertingService::new(config).await.unwrap();
+
+        // 50 distinct alerts within a second
+        for i in 0..50 {
+            service
+                .fire_alert(Alert::new(
+                    format!("Delivery failure {}", i),
+                    "Remote host refused the message".to_string(),
+                    AlertSeverity::Warning,
+                    "queue".to_string(),
+                ))
+                .await
+                .unwrap();
+        }
+        assert!(requests_to(&server, "/").await <= 5);
+
+        // The rest arrive as one summary once a token is available
+        let received = || async {
+            server
+                .received_requests()
+                .await
+                .unwrap()
+                .iter()
+                .map(|request| serde_json::from_slice::<Alert>(&request.body).unwrap())
+                .collect::<Vec<_>>()
+        };
+        for _ in 0..100 {
+            if received().await.iter().any(|alert| alert.source == "alerting") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let alerts = received().await;
+        assert!(alerts.len() <= 6, "{} notifications sent", alerts.len());
+        let summary = alerts.last().unwrap();
+        assert!(summary.title.ends_with("similar alerts suppressed"));
+        let suppressed: usize = summary.context.labels["suppressed_count"].parse().unwrap();
+        assert_eq!(alerts.len() - 1 + suppressed, 50);
+    }
+
+    #[tokio::test]
+    async fn test_alert_history() {
+        let path = std::env::temp_dir().join(format!("alert-history-{}.jsonl", Uuid::new_v4()));
+        let service = AlertingService::with_store(
+            AlertingConfig::default(),
+            Arc::new(FileAlertStore::new(&path)),
+        )
+        .await
+        .unwrap();
+
+        let mut ids = Vec::new();
+        for (title, severity) in [
+            ("Disk full", AlertSeverity::Critical),
+            ("Queue stalled", AlertSeverity::Warning),
+            ("Certificate expired", AlertSeverity::Critical),
+        ] {
+            let alert = Alert::new(title.to_string(), String::new(), severity, "server".to_string());
+            ids.push(service.fire_alert(alert).await.unwrap());
+        }
+        assert!(service.resolve_alert(ids[0], None).await.unwrap());
+        assert!(service.resolve_alert(ids[1], None).await.unwrap());
+
+        // Resolved alerts past the retention boundary leave memory
+        let removed = service
+            .cleanup_resolved_alerts(chrono::Utc::now() + chrono::Duration::minutes(1))
+            .await
+            .unwrap();
+        assert_eq!(removed, 2);
+        assert_eq!(service.list_active_alerts().await.unwrap().len(), 1);
+
+        let critical = AlertHistoryFilter {
+            severities: vec![AlertSeverity::Critical],
+            ..Default::default()
+        };
+        let history = service.query_history(critical.clone()).await.unwrap();
+        assert_eq!(history.len(), 2);
+        let disk = history.iter().find(|alert| alert.id == ids[0]).unwrap();
+        assert_eq!(disk.status, AlertStatus::Resolved);
+        assert!(history.iter().any(|alert| alert.id == ids[2]));
+
+        let page = service
+            .query_history(AlertHistoryFilter {
+                limit: Some(1),
+                ..critical
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 1);
+
+        let history = FileAlertStore::new(&path)
+            .query(&AlertHistoryFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 3);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    async fn requests_to(server: &wiremock::MockServer, path: &str) -> usize {
+        server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|request| request.url.path() == path)
+            .count()
+    }
+
+    /// Channel name and alert of every notification delivered
+    type SentLog = Arc<std::sync::Mutex<Vec<(String, Uuid)>>>;
+
+    /// Channel recording the alerts it was asked to deliver
+    #[derive(Debug)]
+    struct RecordingChannel {
+        name: String,
+        sent: SentLog,
+    }
+
+    #[async_trait::async_trait]
+    impl NotificationChannel for RecordingChannel {
+        fn channel_type(&self) -> ChannelType {
+            ChannelType::Custom("recording".to_string())
+        }
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        async fn should_send_alert(&self, _alert: &Alert) -> bool {
+            true
+        }
+
+        async fn send_alert(&self, alert: &Alert) -> Result<DeliveryResult> {
+            self.sent.lock().unwrap().push((self.name.clone(), alert.id));
+            Ok(DeliveryResult {
+                channel_type: self.channel_type(),
+                success: true,
+                timestamp: chrono::Utc::now(),
+                duration_ms: 0,
+                attempts: 1,
+                status_code: None,
+                error: None,
+                metadata: std::collections::HashMap::new(),
+            })
+        }
+
+        async fn should_send_resolution(&self, _alert: &Alert) -> bool {
+            false
+        }
+
+        async fn send_resolution(&self, alert: &Alert) -> Result<DeliveryResult> {
+            self.send_alert(alert).await
+        }
+
+        async fn test_connection(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn health_check(&self) -> Result<channels::ChannelHealth> {
+            Ok(channels::ChannelHealth {
+                healthy: true,
+                last_success: None,
+                last_failure: None,
+                consecutive_failures: 0,
+                success_rate: 1.0,
+                avg_delivery_time_ms: 0.0,
+            })
+        }
+    }
+
+    async fn escalation_service() -> (AlertingService, SentLog) {
+        let mut config = AlertingConfig::default();
+        config.escalation.insert("default_policy".to_string(), "oncall".to_string());
+        let sent = SentLog::default();
+        let channels = ["primary", "secondary"]
+            .into_iter()
+            .map(|name| {
+                Arc::new(RecordingChannel {
+                    name: name.to_string(),
+                    sent: sent.clone(),
+                }) as Arc<dyn NotificationChannel>
+            })
+            .collect();
+        let service =
+            AlertingService::with_channels(config, Arc::new(MemoryAlertStore::default()), channels)
+                .await
+                .unwrap();
+
+        let mut policy = EscalationPolicy::new("oncall".to_string());
+        policy.add_level(EscalationLevel::new(0, Duration::ZERO, vec!["primary".to_string()]));
+        policy.add_level(EscalationLevel::new(1, Duration::from_secs(300), vec!["secondary".to_string()]));
+        service.add_escalation_policy(policy).await;
+
+        (service, sent)
+    }
+
+    fn sent_to(sent: &SentLog, channel: &str) -> usize {
+        sent.lock().unwrap().iter().filter(|(name, _)| name == channel).count()
+    }
+
+    fn escalation_alert() -> Alert {
+        Alert::new(
+            "Queue stalled".to_string(),
+            "Outbound queue is not draining".to_string(),
+            AlertSeverity::Critical,
+            "queue".to_string(),
+        )
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_alert_escalation() {
+        let (service, sent) = escalation_service().await;
+
+        // Only the first level is notified when the alert fires
+        let alert_id = service.fire_alert(escalation_alert()).await.unwrap();
+        assert_eq!(sent_to(&sent, "primary"), 1);
+        assert_eq!(sent_to(&sent, "secondary"), 0);
+
+        // Level 1 is notified once its delay passes without acknowledgement
+        tokio::time::sleep(Duration::from_secs(299)).await;
+        assert_eq!(service.get_alert(alert_id).await.unwrap().unwrap().escalation_level, 0);
+        assert_eq!(sent_to(&sent, "secondary"), 0);
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        assert_eq!(sent_to(&sent, "primary"), 1);
+        assert_eq!(sent_to(&sent, "secondary"), 1);
+        assert!(sent.lock().unwrap().iter().all(|(_, id)| *id == alert_id));
+
+        let alert = service.get_alert(alert_id).await.unwrap().unwrap();
+        assert_eq!(alert.escalation_level, 1);
+        assert!(alert.next_escalation.is_none());
+
+        // There are no further levels
+        tokio::time::sleep(Duration::from_secs(3600)).await;
+        assert_eq!(sent.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_acknowledged_alert_is_not_escalated() {
+        let (service, sent) = escalation_service().await;
+
+        let alert_id = service.fire_alert(escalation_alert()).await.unwrap();
+        assert!(service.acknowledge_alert(alert_id, "alice").await.unwrap());
+        assert!(!service.acknowledge_alert(alert_id, "bob").await.unwrap());
+
+        tokio::time::sleep(Duration::from_secs(600)).await;
+
+        assert_eq!(sent_to(&sent, "primary"), 1);
+        assert_eq!(sent_to(&sent, "secondary"), 0);
+        let alert = service.get_alert(alert_id).await.unwrap().unwrap();
+        assert_eq!(alert.status, AlertStatus::Acknowledged);
+        assert_eq!(alert.acknowledged_by.as_deref(), Some("alice"));
+        assert_eq!(alert.escalation_level, 0);
+    }
 }
@@ -384,6 +384,8 @@ mod tests {
             success: true,
             timestamp: chrono::Utc::now(),
             duration_ms: 100,
+            attempts: 1,
+            status_code: None,
             error: None,
             metadata: HashMap::new(),
         };
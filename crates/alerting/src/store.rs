@@ -0,0 +1,479 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! Alert history storage
+
+use crate::alert::{Alert, AlertSeverity};
+use crate::error::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
+use tokio::sync::{Mutex, RwLock};
+use tracing::warn;
+use uuid::Uuid;
+
+/// Alert history query
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertHistoryFilter {
+    /// Only alerts created at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only alerts created before this time
+    pub to: Option<DateTime<Utc>>,
+    /// Only alerts with one of these severities (empty means all)
+    pub severities: Vec<AlertSeverity>,
+    /// Only alerts from one of these sources (empty means all)
+    pub sources: Vec<String>,
+    /// Number of matching alerts to skip
+    pub offset: usize,
+    /// Maximum number of alerts to return
+    pub limit: Option<usize>,
+}
+
+impl AlertHistoryFilter {
+    /// Check if an alert matches the time range, severity and source filters
+    pub fn matches(&self, alert: &Alert) -> bool {
+        self.matches_fields(alert.created_at, alert.severity, &alert.source)
+    }
+
+    /// Check the filtered fields of an alert
+    fn matches_fields(&self, created_at: DateTime<Utc>, severity: AlertSeverity, source: &str) -> bool {
+        self.from.is_none_or(|from| created_at >= from)
+            && self.to.is_none_or(|to| created_at < to)
+            && (self.severities.is_empty() || self.severities.contains(&severity))
+            && (self.sources.is_empty() || self.sources.iter().any(|s| s == source))
+    }
+
+    /// Keep the matching alerts, newest first, and apply pagination
+    pub fn apply(&self, alerts: impl IntoIterator<Item = Alert>) -> Vec<Alert> {
+        let mut alerts = alerts
+            .into_iter()
+            .filter(|alert| self.matches(alert))
+            .collect::<Vec<_>>();
+        alerts.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(a.id.cmp(&b.id)));
+
+        alerts
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
+
+/// Persistent alert history
+///
+/// Saving an alert that is already stored replaces the previous record.
+#[async_trait]
+pub trait AlertStore: std::fmt::Debug + Send + Sync {
+    /// Save the current state of alerts
+    async fn save(&self, alerts: &[Alert]) -> Result<()>;
+
+    /// Query stored alerts
+    async fn query(&self, filter: &AlertHistoryFilter) -> Result<Vec<Alert>>;
+}
+
+/// Default number of alerts kept by [`MemoryAlertStore`]
+pub const DEFAULT_MAX_HISTORY_ALERTS: usize = 10_000;
+
+/// Alert store keeping the history in memory
+///
+/// Once more than the configured number of alerts are stored, the alerts
+/// created first are dropped.
+#[derive(Debug)]
+pub struct MemoryAlertStore {
+    max_alerts: usize,
+    history: RwLock<MemoryHistory>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryHistory {
+    alerts: HashMap<Uuid, Alert>,
+    /// Stored alerts ordered by creation time
+    by_age: BTreeSet<(DateTime<Utc>, Uuid)>,
+}
+
+impl MemoryAlertStore {
+    /// Create a store keeping at most `max_alerts` alerts
+    pub fn new(max_alerts: usize) -> Self {
+        Self {
+            max_alerts: max_alerts.max(1),
+            history: RwLock::new(MemoryHistory::default()),
+        }
+    }
+
+    /// Number of stored alerts
+    pub async fn len(&self) -> usize {
+        self.history.read().await.alerts.len()
+    }
+
+    /// Whether no alerts are stored
+    pub async fn is_empty(&self) -> bool {
+        self.history.read().await.alerts.is_empty()
+    }
+}
+
+impl Default for MemoryAlertStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_HISTORY_ALERTS)
+    }
+}
+
+#[async_trait]
+impl AlertStore for MemoryAlertStore {
+    async fn save(&self, alerts: &[Alert]) -> Result<()> {
+        let mut history = self.history.write().await;
+        let history = &mut *history;
+        for alert in alerts {
+            if let Some(previous) = history.alerts.insert(alert.id, alert.clone()) {
+                history.by_age.remove(&(previous.created_at, previous.id));
+            }
+            history.by_age.insert((alert.created_at, alert.id));
+        }
+
+        while history.alerts.len() > self.max_alerts {
+            let Some((_, id)) = history.by_age.pop_first() else {
+                break;
+            };
+            history.alerts.remove(&id);
+        }
+        Ok(())
+    }
+
+    async fn query(&self, filter: &AlertHistoryFilter) -> Result<Vec<Alert>> {
+        Ok(filter.apply(self.history.read().await.alerts.values().cloned()))
+    }
+}
+
+/// Alert store appending alerts to a JSON lines file
+///
+/// Every save appends the alert's current state; the last record of an
+/// alert wins. The file is scanned once to build an index of the latest
+/// record of each alert, so queries only read back the records they return.
+/// Lines that cannot be parsed are skipped with a warning.
+#[derive(Debug)]
+pub struct FileAlertStore {
+    path: PathBuf,
+    index: Mutex<Option<FileIndex>>,
+}
+
+#[derive(Debug, Default)]
+struct FileIndex {
+    entries: HashMap<Uuid, IndexEntry>,
+    /// Length of the file
+    end: u64,
+    /// Whether the file ends with a partial line
+    needs_newline: bool,
+}
+
+/// Location and filterable fields of an alert's latest record
+#[derive(Debug, Clone)]
+struct IndexEntry {
+    offset: u64,
+    len: usize,
+    created_at: DateTime<Utc>,
+    severity: AlertSeverity,
+    source: String,
+}
+
+impl IndexEntry {
+    fn new(alert: &Alert, offset: u64, len: usize) -> Self {
+        Self {
+            offset,
+            len,
+            created_at: alert.created_at,
+            severity: alert.severity,
+            source: alert.source.clone(),
+        }
+    }
+}
+
+impl FileAlertStore {
+    /// Create a store backed by the given file
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            index: Mutex::new(None),
+        }
+    }
+
+    /// Path of the history file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Scan the file, keeping the offset of the latest record of each alert
+    async fn build_index(&self) -> Result<FileIndex> {
+        let file = match tokio::fs::File::open(&self.path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(FileIndex::default()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut reader = BufReader::new(file);
+        let mut index = FileIndex::default();
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line).await?;
+            if read == 0 {
+                break;
+            }
+
+            let offset = index.end;
+            index.end += read as u64;
+            index.needs_newline = line.last() != Some(&b'\n');
+
+            let record = line.trim_ascii();
+            if record.is_empty() {
+                continue;
+            }
+            match serde_json::from_slice::<Alert>(record) {
+                Ok(alert) => {
+                    index
+                        .entries
+                        .insert(alert.id, IndexEntry::new(&alert, offset, read));
+                }
+                Err(e) => {
+                    warn!(
+                        "Skipping unreadable alert record at offset {} of {}: {}",
+                        offset,
+                        self.path.display(),
+                        e
+                    );
+                }
+            }
+        }
+
+        Ok(index)
+    }
+}
+
+#[async_trait]
+impl AlertStore for FileAlertStore {
+    async fn save(&self, alerts: &[Alert]) -> Result<()> {
+        if alerts.is_empty() {
+            return Ok(());
+        }
+
+        let mut guard = self.index.lock().await;
+        if guard.is_none() {
+            *guard = Some(self.build_index().await?);
+        }
+        let index = guard.as_mut().unwrap();
+
+        // Start on a new line if the last write was cut short
+        let mut records = Vec::new();
+        if index.needs_newline {
+            records.push(b'\n');
+        }
+        let mut entries = Vec::with_capacity(alerts.len());
+        for alert in alerts {
+            let offset = index.end + records.len() as u64;
+            serde_json::to_writer(&mut records, alert)?;
+            records.push(b'\n');
+            let len = (index.end + records.len() as u64 - offset) as usize;
+            entries.push((alert.id, IndexEntry::new(alert, offset, len)));
+        }
+
+        if let Some(parent) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&records).await?;
+        file.flush().await?;
+
+        index.end += records.len() as u64;
+        index.needs_newline = false;
+        index.entries.extend(entries);
+        Ok(())
+    }
+
+    async fn query(&self, filter: &AlertHistoryFilter) -> Result<Vec<Alert>> {
+        let mut guard = self.index.lock().await;
+        if guard.is_none() {
+            *guard = Some(self.build_index().await?);
+        }
+        let index = guard.as_ref().unwrap();
+
+        // Filter and page on the index, newest first like `AlertHistoryFilter::apply`
+        let mut matching = index
+            .entries
+            .iter()
+            .filter(|(_, entry)| filter.matches_fields(entry.created_at, entry.severity, &entry.source))
+            .collect::<Vec<_>>();
+        matching.sort_by(|(a_id, a), (b_id, b)| b.created_at.cmp(&a.created_at).then(a_id.cmp(b_id)));
+
+        let page = matching
+            .into_iter()
+            .skip(filter.offset)
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .map(|(_, entry)| entry.clone())
+            .collect::<Vec<_>>();
+        if page.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut file = tokio::fs::File::open(&self.path).await?;
+        let mut alerts = Vec::with_capacity(page.len());
+        let mut record = Vec::new();
+        for entry in page {
+            record.resize(entry.len, 0);
+            file.seek(std::io::SeekFrom::Start(entry.offset)).await?;
+            file.read_exact(&mut record).await?;
+            match serde_json::from_slice::<Alert>(record.trim_ascii()) {
+                Ok(alert) => alerts.push(alert),
+                Err(e) => {
+                    warn!(
+                        "Skipping unreadable alert record at offset {} of {}: {}",
+                        entry.offset,
+                        self.path.display(),
+                        e
+                    );
+                }
+            }
+        }
+
+        Ok(alerts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(title: &str, severity: AlertSeverity, source: &str) -> Alert {
+        Alert::new(title.to_string(), String::new(), severity, source.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_file_alert_store() {
+        let path = std::env::temp_dir().join(format!("alert-history-{}.jsonl", Uuid::new_v4()));
+        let store = FileAlertStore::new(&path);
+        assert!(store.query(&AlertHistoryFilter::default()).await.unwrap().is_empty());
+
+        let mut disk = alert("Disk full", AlertSeverity::Critical, "storage");
+        let queue = alert("Queue stalled", AlertSeverity::Warning, "queue");
+        store.save(&[disk.clone(), queue.clone()]).await.unwrap();
+
+        // Later records replace earlier ones
+        disk.resolve(None);
+        store.save(&[disk.clone()]).await.unwrap();
+
+        let reopened = FileAlertStore::new(&path);
+        let all = reopened.query(&AlertHistoryFilter::default()).await.unwrap();
+        assert_eq!(all.len(), 2);
+
+        let critical = reopened
+            .query(&AlertHistoryFilter {
+                severities: vec![AlertSeverity::Critical],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(critical.len(), 1);
+        assert_eq!(critical[0].id, disk.id);
+        assert!(critical[0].resolved_at.is_some());
+
+        let by_source = reopened
+            .query(&AlertHistoryFilter {
+                sources: vec!["queue".to_string()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(by_source[0].id, queue.id);
+
+        let page = reopened
+            .query(&AlertHistoryFilter {
+                offset: 1,
+                limit: Some(5),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 1);
+
+        let future = reopened
+            .query(&AlertHistoryFilter {
+                from: Some(Utc::now() + chrono::Duration::hours(1)),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(future.is_empty());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_file_alert_store_skips_bad_records() {
+        let path = std::env::temp_dir().join(format!("alert-history-{}.jsonl", Uuid::new_v4()));
+        let disk = alert("Disk full", AlertSeverity::Critical, "storage");
+        let queue = alert("Queue stalled", AlertSeverity::Warning, "queue");
+
+        // A corrupt line and a record cut short by a crash
+        let truncated = serde_json::to_string(&queue).unwrap();
+        std::fs::write(
+            &path,
+            format!(
+                "{}\nnot json\n{}",
+                serde_json::to_string(&disk).unwrap(),
+                &truncated[..truncated.len() / 2]
+            ),
+        )
+        .unwrap();
+
+        let store = FileAlertStore::new(&path);
+        let all = store.query(&AlertHistoryFilter::default()).await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].id, disk.id);
+
+        // Records appended after the partial line are still readable
+        store.save(&[queue.clone()]).await.unwrap();
+        assert_eq!(store.query(&AlertHistoryFilter::default()).await.unwrap().len(), 2);
+        let reopened = FileAlertStore::new(&path)
+            .query(&AlertHistoryFilter {
+                sources: vec!["queue".to_string()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(reopened.len(), 1);
+        assert_eq!(reopened[0].id, queue.id);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_memory_alert_store_cap() {
+        let store = MemoryAlertStore::new(2);
+        let mut alerts = (0..3)
+            .map(|i| alert(&format!("Alert {}", i), AlertSeverity::Info, "test"))
+            .collect::<Vec<_>>();
+        for (i, alert) in alerts.iter_mut().enumerate() {
+            alert.created_at = Utc::now() + chrono::Duration::seconds(i as i64);
+        }
+
+        store.save(&alerts[..2]).await.unwrap();
+        // Saving an alert again does not count twice
+        store.save(&alerts[..1]).await.unwrap();
+        assert_eq!(store.len().await, 2);
+
+        // The oldest alert is dropped first
+        store.save(&alerts[2..]).await.unwrap();
+        let stored = store.query(&AlertHistoryFilter::default()).await.unwrap();
+        assert_eq!(
+            stored.iter().map(|alert| alert.id).collect::<Vec<_>>(),
+            vec![alerts[2].id, alerts[1].id]
+        );
+    }
+}
@@ -7,8 +7,11 @@
 //! Alert suppression and silencing
 
 use crate::alert::Alert;
+use crate::error::{AlertingError, Result};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::time::Duration;
 
 /// Alert suppression configuration
@@ -71,6 +74,15 @@ pub struct MaintenanceWindow {
     pub affected_sources: Vec<String>,
     /// Affected severities (empty means all)
     pub affected_severities: Vec<String>,
+    /// Labels an alert must carry with the same values (empty means all)
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    /// Cron expression (with seconds) repeating the window
+    ///
+    /// Each occurrence at or after the start time opens the window for the
+    /// duration between start and end time. Recurring windows never expire.
+    #[serde(default)]
+    pub recurrence: Option<String>,
     /// Window metadata
     pub metadata: HashMap<String, String>,
 }
@@ -160,23 +172,71 @@ impl MaintenanceWindow {
             description: None,
             affected_sources: Vec::new(),
             affected_severities: Vec::new(),
+            labels: HashMap::new(),
+            recurrence: None,
             metadata: HashMap::new(),
         }
     }
     
     /// Check if window is currently active
     pub fn is_active(&self) -> bool {
-        if !self.active {
+        self.is_active_at(Utc::now())
+    }
+
+    /// Check if window is active at the given time
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        if !self.active || now < self.start_time {
             return false;
         }
-        
-        let now = chrono::Utc::now();
-        now >= self.start_time && now <= self.end_time
+
+        match &self.recurrence {
+            None => now <= self.end_time,
+            Some(expression) => {
+                let Ok(schedule) = cron::Schedule::from_str(expression) else {
+                    return false;
+                };
+                schedule
+                    .after(&(now - self.duration()))
+                    .take_while(|occurrence| *occurrence <= now)
+                    .any(|occurrence| occurrence >= self.start_time)
+            }
+        }
+    }
+
+    /// Check whether a non-recurring window has ended
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.recurrence.is_none() && self.end_time <= now
+    }
+
+    /// Validate the window
+    pub fn validate(&self) -> Result<()> {
+        if self.end_time <= self.start_time {
+            return Err(AlertingError::config(format!(
+                "Maintenance window {} ends before it starts",
+                self.name
+            )));
+        }
+
+        if let Some(expression) = &self.recurrence {
+            cron::Schedule::from_str(expression).map_err(|e| {
+                AlertingError::config(format!(
+                    "Invalid recurrence for maintenance window {}: {}",
+                    self.name, e
+                ))
+            })?;
+        }
+
+        Ok(())
     }
     
     /// Check if alert should be suppressed by this window
     pub fn should_suppress(&self, alert: &Alert) -> bool {
-        if !self.is_active() {
+        self.should_suppress_at(alert, Utc::now())
+    }
+
+    /// Check if alert should be suppressed by this window at the given time
+    pub fn should_suppress_at(&self, alert: &Alert, now: DateTime<Utc>) -> bool {
+        if !self.is_active_at(now) {
             return false;
         }
         
@@ -192,8 +252,11 @@ impl MaintenanceWindow {
                 return false;
             }
         }
-        
-        true
+
+        // Check label selector
+        self.labels
+            .iter()
+            .all(|(key, value)| alert.context.labels.get(key) == Some(value))
     }
     
     /// Set affected sources
@@ -213,6 +276,18 @@ impl MaintenanceWindow {
         self.description = Some(description);
         self
     }
+
+    /// Restrict the window to alerts carrying a label
+    pub fn with_label(mut self, key: String, value: String) -> Self {
+        self.labels.insert(key, value);
+        self
+    }
+
+    /// Repeat the window on a cron schedule
+    pub fn with_recurrence(mut self, expression: String) -> Self {
+        self.recurrence = Some(expression);
+        self
+    }
     
     /// Activate the window
     pub fn activate(&mut self) {
@@ -336,12 +411,14 @@ impl SuppressionManager {
     
     /// Clean up expired windows and rules
     pub fn cleanup(&mut self) {
-        let now = chrono::Utc::now();
-        
-        // Remove expired maintenance windows
-        self.config.maintenance_windows.retain(|window| {
-            window.end_time > now || window.active
-        });
+        self.cleanup_at(Utc::now());
+    }
+
+    /// Remove the maintenance windows that have ended by the given time
+    pub fn cleanup_at(&mut self, now: DateTime<Utc>) -> usize {
+        let initial_len = self.config.maintenance_windows.len();
+        self.config.maintenance_windows.retain(|window| !window.is_expired_at(now));
+        initial_len - self.config.maintenance_windows.len()
     }
 }
 
@@ -412,6 +489,56 @@ mod tests {
         assert!(window.should_suppress(&alert));
     }
 
+    #[test]
+    fn test_maintenance_window_selector() {
+        let now = Utc::now();
+        let window = MaintenanceWindow::new(
+            "mx1_upgrade".to_string(),
+            now - chrono::Duration::minutes(5),
+            now + chrono::Duration::minutes(5),
+        )
+        .with_label("instance".to_string(), "mx1".to_string());
+
+        let mut alert = Alert::new(
+            "Test Alert".to_string(),
+            "Test Description".to_string(),
+            AlertSeverity::Warning,
+            "test_source".to_string(),
+        );
+        assert!(!window.should_suppress(&alert));
+
+        alert.add_label("instance".to_string(), "mx1".to_string());
+        assert!(window.should_suppress(&alert));
+        assert!(!window.should_suppress_at(&alert, now + chrono::Duration::minutes(10)));
+        assert!(window.is_expired_at(now + chrono::Duration::minutes(10)));
+    }
+
+    #[test]
+    fn test_recurring_maintenance_window() {
+        use chrono::TimeZone;
+
+        // Every day from 02:00 to 03:00
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 2, 0, 0).unwrap();
+        let window = MaintenanceWindow::new(
+            "nightly_backup".to_string(),
+            start,
+            start + chrono::Duration::hours(1),
+        )
+        .with_recurrence("0 0 2 * * *".to_string());
+        assert!(window.validate().is_ok());
+
+        let day = |hour, minute| Utc.with_ymd_and_hms(2024, 3, 15, hour, minute, 0).unwrap();
+        assert!(!window.is_active_at(day(1, 59)));
+        assert!(window.is_active_at(day(2, 0)));
+        assert!(window.is_active_at(day(2, 30)));
+        assert!(!window.is_active_at(day(3, 30)));
+        assert!(!window.is_active_at(Utc.with_ymd_and_hms(2023, 12, 31, 2, 30, 0).unwrap()));
+        assert!(!window.is_expired_at(day(3, 30)));
+
+        let invalid = window.clone().with_recurrence("every night".to_string());
+        assert!(invalid.validate().is_err());
+    }
+
     #[test]
     fn test_suppression_manager() {
         let mut manager = SuppressionManager::new(SuppressionConfig::default());
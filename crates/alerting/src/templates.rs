@@ -246,7 +246,18 @@ impl TemplateEngine {
             metadata: HashMap::new(),
         };
         
+        // Default Telegram template (legacy Markdown)
+        let default_telegram = AlertTemplate {
+            name: "default_telegram".to_string(),
+            content: "*{{title}}*\n\n{{description}}\n\n*Severity:* {{severity}}\n*Source:* {{source}}\n*Time:* {{created_at}}".to_string(),
+            template_type: TemplateType::Alert,
+            format: TemplateFormat::Markdown,
+            variables: HashMap::new(),
+            metadata: HashMap::new(),
+        };
+        
         self.templates.insert(default_text.name.clone(), default_text);
+        self.templates.insert(default_telegram.name.clone(), default_telegram);
         self.templates.insert(default_html.name.clone(), default_html);
         self.templates.insert(default_markdown.name.clone(), default_markdown);
         
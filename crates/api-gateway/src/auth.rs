@@ -1,15 +1,346 @@
 //! Authentication module
 
-/// Auth manager
-pub struct AuthManager;
+use crate::error::{GatewayError, Result};
+use crate::gateway::GatewayRequest;
+use crate::AuthInfo;
+use async_trait::async_trait;
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
 
-/// Auth configuration
-pub struct AuthConfig;
+/// Header carrying API keys
+pub const API_KEY_HEADER: &str = "X-API-Key";
 
 /// Authentication method
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AuthMethod {
     ApiKey,
     Bearer,
     Basic,
 }
+
+/// Auth configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// HS256 secret bearer JWTs are signed with, bearer tokens are rejected when unset
+    pub jwt_secret: Option<String>,
+    /// Required `iss` claim
+    pub jwt_issuer: Option<String>,
+    /// Required `aud` claim
+    pub jwt_audience: Option<String>,
+    /// API keys accepted by the default key store
+    #[serde(default)]
+    pub api_keys: HashMap<String, ApiKey>,
+}
+
+/// API key entry
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub user_id: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Scopes the key grants, a key without scopes only reaches routes requiring none
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+/// Store API keys are looked up in
+#[async_trait]
+pub trait ApiKeyStore: Send + Sync {
+    /// Look up an API key, `None` if it does not exist
+    async fn lookup(&self, key: &str) -> Result<Option<ApiKey>>;
+}
+
+/// API key store backed by the gateway configuration
+#[derive(Debug, Default)]
+pub struct StaticApiKeyStore {
+    keys: HashMap<String, ApiKey>,
+}
+
+impl StaticApiKeyStore {
+    /// Create new key store
+    pub fn new(keys: HashMap<String, ApiKey>) -> Self {
+        Self { keys }
+    }
+}
+
+#[async_trait]
+impl ApiKeyStore for StaticApiKeyStore {
+    async fn lookup(&self, key: &str) -> Result<Option<ApiKey>> {
+        Ok(self.keys.get(key).cloned())
+    }
+}
+
+/// Auth manager
+pub struct AuthManager {
+    config: AuthConfig,
+    api_keys: Arc<dyn ApiKeyStore>,
+}
+
+impl AuthManager {
+    /// Create new auth manager using the configured API keys
+    pub fn new(config: AuthConfig) -> Self {
+        let api_keys = Arc::new(StaticApiKeyStore::new(config.api_keys.clone()));
+        Self { config, api_keys }
+    }
+
+    /// Look API keys up in another store
+    pub fn with_api_key_store(mut self, store: Arc<dyn ApiKeyStore>) -> Self {
+        self.api_keys = store;
+        self
+    }
+
+    /// Authenticate a request from its bearer token or API key
+    ///
+    /// Returns `None` when the request carries no credentials and an
+    /// `Unauthorized` error when the credentials it carries are invalid.
+    pub async fn authenticate(&self, request: &GatewayRequest) -> Result<Option<AuthInfo>> {
+        if let Some(authorization) = request.header("Authorization") {
+            let token = authorization
+                .strip_prefix("Bearer ")
+                .or_else(|| authorization.strip_prefix("bearer "))
+                .ok_or_else(|| {
+                    GatewayError::Unauthorized("unsupported authorization scheme".to_string())
+                })?;
+            return self.authenticate_jwt(token.trim()).map(Some);
+        }
+
+        if let Some(key) = request.header(API_KEY_HEADER) {
+            return self.authenticate_api_key(key.trim()).await.map(Some);
+        }
+
+        Ok(None)
+    }
+
+    fn authenticate_jwt(&self, token: &str) -> Result<AuthInfo> {
+        let secret = self.config.jwt_secret.as_ref().ok_or_else(|| {
+            GatewayError::Unauthorized("bearer tokens are not accepted".to_string())
+        })?;
+
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.validate_aud = self.config.jwt_audience.is_some();
+        if let Some(audience) = &self.config.jwt_audience {
+            validation.set_audience(&[audience]);
+        }
+        if let Some(issuer) = &self.config.jwt_issuer {
+            validation.set_issuer(&[issuer]);
+        }
+
+        let claims = decode::<HashMap<String, Value>>(
+            token,
+            &DecodingKey::from_secret(secret.as_bytes()),
+            &validation,
+        )
+        .map_err(|err| GatewayError::Unauthorized(format!("invalid token: {}", err)))?
+        .claims;
+
+        let user_id = claims
+            .get("sub")
+            .and_then(Value::as_str)
+            .ok_or_else(|| GatewayError::Unauthorized("token has no subject".to_string()))?
+            .to_string();
+        let roles = match claims.get("roles") {
+            Some(Value::Array(roles)) => roles
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            Some(Value::String(role)) => vec![role.clone()],
+            _ => Vec::new(),
+        };
+        let expires_at = claims
+            .get("exp")
+            .and_then(Value::as_i64)
+            .and_then(|exp| chrono::DateTime::from_timestamp(exp, 0));
+
+        Ok(AuthInfo {
+            user_id,
+            roles,
+            auth_method: AuthMethod::Bearer,
+            expires_at,
+            claims,
+        })
+    }
+
+    async fn authenticate_api_key(&self, key: &str) -> Result<AuthInfo> {
+        let entry = self
+            .api_keys
+            .lookup(key)
+            .await?
+            .ok_or_else(|| GatewayError::Unauthorized("unknown API key".to_string()))?;
+
+        // Scopes are exposed the same way as a JWT `scope` claim
+        let mut claims = HashMap::new();
+        if !entry.scopes.is_empty() {
+            claims.insert("scope".to_string(), Value::String(entry.scopes.join(" ")));
+        }
+
+        Ok(AuthInfo {
+            user_id: entry.user_id,
+            roles: entry.roles,
+            auth_method: AuthMethod::ApiKey,
+            expires_at: None,
+            claims,
+        })
+    }
+}
+
+impl AuthInfo {
+    /// Check if the credentials grant a scope
+    ///
+    /// Scopes are read from the `scope` or `scp` claim, either as a
+    /// space-delimited string or as an array of strings. Credentials without
+    /// scopes grant none.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        ["scope", "scp"]
+            .iter()
+            .filter_map(|claim| self.claims.get(*claim))
+            .any(|scopes| match scopes {
+                Value::String(scopes) => scopes.split_whitespace().any(|granted| granted == scope),
+                Value::Array(scopes) => {
+                    scopes.iter().any(|granted| granted.as_str() == Some(scope))
+                }
+                _ => false,
+            })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    pub(crate) const SECRET: &str = "test-secret";
+
+    pub(crate) fn token(claims: Value) -> String {
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(SECRET.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    pub(crate) fn config() -> AuthConfig {
+        AuthConfig {
+            jwt_secret: Some(SECRET.to_string()),
+            api_keys: HashMap::from([(
+                "key-123".to_string(),
+                ApiKey {
+                    user_id: "service@example.com".to_string(),
+                    roles: vec!["service".to_string()],
+                    scopes: vec!["mail:read".to_string()],
+                },
+            )]),
+            ..Default::default()
+        }
+    }
+
+    fn in_an_hour() -> i64 {
+        chrono::Utc::now().timestamp() + 3600
+    }
+
+    #[tokio::test]
+    async fn test_jwt_claims() {
+        let auth = AuthManager::new(config());
+        let request = GatewayRequest::new("GET", "/api/mail").with_header(
+            "Authorization",
+            &format!(
+                "Bearer {}",
+                token(json!({"sub": "alice@example.com", "roles": ["admin", "user"], "exp": in_an_hour()}))
+            ),
+        );
+
+        let info = auth.authenticate(&request).await.unwrap().unwrap();
+        assert_eq!(info.user_id, "alice@example.com");
+        assert_eq!(info.roles, vec!["admin", "user"]);
+        assert_eq!(info.auth_method, AuthMethod::Bearer);
+        assert_eq!(
+            info.expires_at.unwrap().timestamp(),
+            info.claims["exp"].as_i64().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_jwt() {
+        let auth = AuthManager::new(AuthConfig {
+            jwt_audience: Some("a3mailer".to_string()),
+            ..config()
+        });
+
+        for claims in [
+            json!({"sub": "alice@example.com", "aud": "a3mailer", "exp": chrono::Utc::now().timestamp() - 3600}),
+            json!({"sub": "alice@example.com", "aud": "other", "exp": in_an_hour()}),
+            json!({"aud": "a3mailer", "exp": in_an_hour()}),
+        ] {
+            let request = GatewayRequest::new("GET", "/")
+                .with_header("Authorization", &format!("Bearer {}", token(claims)));
+            assert!(matches!(
+                auth.authenticate(&request).await,
+                Err(GatewayError::Unauthorized(_))
+            ));
+        }
+
+        let request =
+            GatewayRequest::new("GET", "/").with_header("Authorization", "Basic dXNlcjpwYXNz");
+        assert!(matches!(
+            auth.authenticate(&request).await,
+            Err(GatewayError::Unauthorized(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_api_key_scopes() {
+        let auth = AuthManager::new(config());
+
+        let request = GatewayRequest::new("GET", "/").with_header("x-api-key", "key-123");
+        let info = auth.authenticate(&request).await.unwrap().unwrap();
+        assert_eq!(info.user_id, "service@example.com");
+        assert_eq!(info.auth_method, AuthMethod::ApiKey);
+        assert!(info.has_scope("mail:read"));
+        assert!(!info.has_scope("mail:write"));
+
+        let request = GatewayRequest::new("GET", "/").with_header(API_KEY_HEADER, "key-456");
+        assert!(matches!(
+            auth.authenticate(&request).await,
+            Err(GatewayError::Unauthorized(_))
+        ));
+
+        assert!(auth
+            .authenticate(&GatewayRequest::new("GET", "/"))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_scope_claims() {
+        let info = |claims: Value| AuthInfo {
+            user_id: "alice@example.com".to_string(),
+            roles: Vec::new(),
+            auth_method: AuthMethod::Bearer,
+            expires_at: None,
+            claims: serde_json::from_value(claims).unwrap(),
+        };
+
+        for claims in [
+            json!({"scope": "mail:read mail:send"}),
+            json!({"scp": "mail:read mail:send"}),
+            json!({"scope": ["mail:read", "mail:send"]}),
+            json!({"scp": ["mail:read", "mail:send"]}),
+        ] {
+            let info = info(claims);
+            assert!(info.has_scope("mail:read"));
+            assert!(info.has_scope("mail:send"));
+            assert!(!info.has_scope("mail:write"));
+        }
+
+        // Missing or malformed scope claims grant nothing
+        for claims in [json!({}), json!({"scope": 42}), json!({"scp": [42]})] {
+            assert!(!info(claims).has_scope("mail:read"));
+        }
+    }
+}
@@ -1,10 +1,247 @@
 //! Caching module
 
-/// Cache manager
-pub struct CacheManager;
+use crate::gateway::{GatewayRequest, GatewayResponse};
+use crate::RouteMatcher;
+use moka::future::Cache;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Header telling clients whether a response came from the cache
+pub const CACHE_HEADER: &str = "X-Cache";
 
 /// Cache configuration
-pub struct CacheConfig;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    /// Time to live of responses without a `max-age`
+    pub default_ttl: Duration,
+    /// Maximum number of cached responses
+    pub max_entries: u64,
+    /// Requests whose responses may be cached, the first matching policy applies
+    #[serde(default)]
+    pub policies: Vec<CachePolicy>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_ttl: Duration::from_secs(60),
+            max_entries: 10_000,
+            policies: Vec::new(),
+        }
+    }
+}
 
 /// Cache policy
-pub struct CachePolicy;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachePolicy {
+    /// Requests the policy applies to
+    pub matcher: RouteMatcher,
+    /// Time to live overriding the backend's `Cache-Control`
+    pub ttl: Option<Duration>,
+    /// Request headers that select between cached responses
+    #[serde(default)]
+    pub vary: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    response: GatewayResponse,
+    expires_at: Instant,
+}
+
+/// Cache manager for idempotent backend responses
+pub struct CacheManager {
+    config: CacheConfig,
+    entries: Cache<String, CachedResponse>,
+}
+
+impl CacheManager {
+    /// Create new cache manager
+    pub fn new(config: &CacheConfig) -> Self {
+        Self {
+            config: config.clone(),
+            entries: Cache::new(config.max_entries),
+        }
+    }
+
+    /// Get the cache key of a request, `None` if its response is not cacheable
+    pub fn key(&self, request: &GatewayRequest) -> Option<String> {
+        let policy = self.policy(request)?;
+
+        let mut key = format!("{} {}", request.method.to_ascii_uppercase(), request.path);
+        for name in &policy.vary {
+            key.push_str(&format!(
+                "\n{}: {}",
+                name.to_ascii_lowercase(),
+                request.header(name).unwrap_or_default()
+            ));
+        }
+        Some(key)
+    }
+
+    /// Get a cached response
+    pub async fn get(&self, key: &str) -> Option<GatewayResponse> {
+        let cached = self.entries.get(key).await?;
+        if cached.expires_at <= Instant::now() {
+            self.entries.invalidate(key).await;
+            return None;
+        }
+
+        let mut response = cached.response;
+        response
+            .headers
+            .insert(CACHE_HEADER.to_string(), "HIT".to_string());
+        Some(response)
+    }
+
+    /// Cache a backend response if its status and `Cache-Control` allow it
+    ///
+    /// Responses to authenticated requests are only cached when the
+    /// backend marks them public, they could otherwise leak between users.
+    pub async fn store(
+        &self,
+        key: &str,
+        request: &GatewayRequest,
+        response: &GatewayResponse,
+        authenticated: bool,
+    ) -> bool {
+        if !matches!(response.status, 200 | 203 | 300 | 301 | 404 | 410)
+            || response
+                .header("Vary")
+                .is_some_and(|vary| vary.trim() == "*")
+        {
+            return false;
+        }
+
+        let Some(policy) = self.policy(request) else {
+            return false;
+        };
+        let Some(ttl) = ttl(
+            response.header("Cache-Control"),
+            policy.ttl,
+            self.config.default_ttl,
+            authenticated,
+        ) else {
+            return false;
+        };
+
+        let mut response = response.clone();
+        response.headers.remove(CACHE_HEADER);
+        self.entries
+            .insert(
+                key.to_string(),
+                CachedResponse {
+                    response,
+                    expires_at: Instant::now() + ttl,
+                },
+            )
+            .await;
+        true
+    }
+
+    /// Drop every cached response
+    pub fn clear(&self) {
+        self.entries.invalidate_all();
+    }
+
+    fn policy(&self, request: &GatewayRequest) -> Option<&CachePolicy> {
+        if !self.config.enabled
+            || !(request.method.eq_ignore_ascii_case("GET")
+                || request.method.eq_ignore_ascii_case("HEAD"))
+        {
+            return None;
+        }
+
+        self.config
+            .policies
+            .iter()
+            .find(|policy| policy.matcher.matches(request))
+    }
+}
+
+/// Time to live of a response, `None` if it must not be cached
+fn ttl(
+    cache_control: Option<&str>,
+    policy_ttl: Option<Duration>,
+    default_ttl: Duration,
+    authenticated: bool,
+) -> Option<Duration> {
+    let mut max_age = None;
+    let mut shared_max_age = None;
+    let mut public = false;
+
+    for directive in cache_control.unwrap_or_default().split(',') {
+        let (name, value) = directive
+            .trim()
+            .split_once('=')
+            .map_or((directive.trim(), None), |(name, value)| {
+                (name.trim(), Some(value.trim().trim_matches('"')))
+            });
+        let seconds = value.and_then(|value| value.parse::<u64>().ok());
+
+        match name.to_ascii_lowercase().as_str() {
+            "no-store" | "no-cache" | "private" => return None,
+            "public" => public = true,
+            "max-age" => max_age = seconds,
+            "s-maxage" => shared_max_age = seconds,
+            _ => {}
+        }
+    }
+
+    if authenticated && !public && shared_max_age.is_none() {
+        return None;
+    }
+
+    let ttl = policy_ttl.unwrap_or_else(|| {
+        shared_max_age
+            .or(max_age)
+            .map(Duration::from_secs)
+            .unwrap_or(default_ttl)
+    });
+    (!ttl.is_zero()).then_some(ttl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_ttl_from_cache_control() {
+        assert_eq!(ttl(None, None, DEFAULT, false), Some(DEFAULT));
+        assert_eq!(
+            ttl(Some("public, max-age=300"), None, DEFAULT, false),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(
+            ttl(Some("max-age=300, s-maxage=30"), None, DEFAULT, false),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(ttl(Some("max-age=0"), None, DEFAULT, false), None);
+        assert_eq!(ttl(Some("No-Store"), None, DEFAULT, false), None);
+        assert_eq!(ttl(Some("private, max-age=60"), None, DEFAULT, false), None);
+    }
+
+    #[test]
+    fn test_ttl_policy_override() {
+        let policy_ttl = Some(Duration::from_secs(5));
+        assert_eq!(
+            ttl(Some("max-age=300"), policy_ttl, DEFAULT, false),
+            policy_ttl
+        );
+        // The override never makes an uncacheable response cacheable
+        assert_eq!(ttl(Some("no-store"), policy_ttl, DEFAULT, false), None);
+    }
+
+    #[test]
+    fn test_ttl_authenticated() {
+        assert_eq!(ttl(Some("max-age=60"), None, DEFAULT, true), None);
+        assert_eq!(
+            ttl(Some("public, max-age=60"), None, DEFAULT, true),
+            Some(Duration::from_secs(60))
+        );
+    }
+}
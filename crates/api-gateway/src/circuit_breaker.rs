@@ -1,15 +1,314 @@
 //! Circuit breaker module
 
-/// Circuit breaker
-pub struct CircuitBreaker;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
 
 /// Circuit breaker configuration
-pub struct CircuitBreakerConfig;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures before the circuit opens
+    pub failure_threshold: u32,
+    /// Consecutive successes in half-open state before the circuit closes,
+    /// also the number of trial requests admitted at once while half-open
+    pub success_threshold: u32,
+    /// How long the circuit stays open before allowing a trial request
+    pub open_timeout: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            success_threshold: 1,
+            open_timeout: Duration::from_secs(30),
+        }
+    }
+}
 
 /// Circuit state
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CircuitState {
     Closed,
     Open,
     HalfOpen,
 }
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    consecutive_successes: u32,
+    trials_in_flight: u32,
+    opened_at: Option<Instant>,
+}
+
+/// Circuit breaker
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: String,
+    config: CircuitBreakerConfig,
+    inner: RwLock<BreakerState>,
+}
+
+impl CircuitBreaker {
+    /// Create new circuit breaker
+    pub fn new(name: impl Into<String>, config: CircuitBreakerConfig) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            inner: RwLock::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                consecutive_successes: 0,
+                trials_in_flight: 0,
+                opened_at: None,
+            }),
+        }
+    }
+
+    /// Get breaker name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get current state, moving from open to half-open once the timeout elapsed
+    pub async fn state(&self) -> CircuitState {
+        let mut inner = self.inner.write().await;
+        self.refresh(&mut inner);
+        inner.state
+    }
+
+    /// Check whether a request may be sent to the backend
+    ///
+    /// While half-open only `success_threshold` trial requests are admitted
+    /// at a time, the rest are rejected as if the circuit was open. Every
+    /// admitted request must be followed by `record_success` or
+    /// `record_failure`.
+    pub async fn allow_request(&self) -> bool {
+        let mut inner = self.inner.write().await;
+        self.refresh(&mut inner);
+
+        match inner.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => {
+                if inner.trials_in_flight < self.config.success_threshold.max(1) {
+                    inner.trials_in_flight += 1;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    /// Record a successful backend call
+    pub async fn record_success(&self) {
+        let mut inner = self.inner.write().await;
+        self.refresh(&mut inner);
+        inner.consecutive_failures = 0;
+
+        if inner.state == CircuitState::HalfOpen {
+            inner.trials_in_flight = inner.trials_in_flight.saturating_sub(1);
+            inner.consecutive_successes += 1;
+            if inner.consecutive_successes >= self.config.success_threshold {
+                info!("Circuit breaker {} closed", self.name);
+                inner.state = CircuitState::Closed;
+                inner.consecutive_successes = 0;
+                inner.trials_in_flight = 0;
+                inner.opened_at = None;
+            }
+        }
+    }
+
+    /// Record a failed backend call
+    pub async fn record_failure(&self) {
+        let mut inner = self.inner.write().await;
+        self.refresh(&mut inner);
+        inner.consecutive_successes = 0;
+        inner.consecutive_failures += 1;
+
+        let should_open = match inner.state {
+            CircuitState::HalfOpen => true,
+            CircuitState::Closed => inner.consecutive_failures >= self.config.failure_threshold,
+            CircuitState::Open => false,
+        };
+
+        if should_open {
+            warn!(
+                "Circuit breaker {} opened after {} consecutive failures",
+                self.name, inner.consecutive_failures
+            );
+            inner.state = CircuitState::Open;
+            inner.trials_in_flight = 0;
+            inner.opened_at = Some(Instant::now());
+        }
+    }
+
+    /// Force the circuit open
+    pub async fn trip(&self) {
+        let mut inner = self.inner.write().await;
+        inner.state = CircuitState::Open;
+        inner.trials_in_flight = 0;
+        inner.opened_at = Some(Instant::now());
+    }
+
+    /// Force the circuit closed and clear counters
+    pub async fn reset(&self) {
+        let mut inner = self.inner.write().await;
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+        inner.consecutive_successes = 0;
+        inner.trials_in_flight = 0;
+        inner.opened_at = None;
+    }
+
+    fn refresh(&self, inner: &mut BreakerState) {
+        if inner.state == CircuitState::Open {
+            if let Some(opened_at) = inner.opened_at {
+                if opened_at.elapsed() >= self.config.open_timeout {
+                    info!("Circuit breaker {} half-open", self.name);
+                    inner.state = CircuitState::HalfOpen;
+                    inner.consecutive_successes = 0;
+                    inner.trials_in_flight = 0;
+                }
+            }
+        }
+    }
+}
+
+/// Per-endpoint circuit breaker registry
+#[derive(Debug, Default)]
+pub struct CircuitBreakerRegistry {
+    default_config: CircuitBreakerConfig,
+    breakers: RwLock<HashMap<String, Arc<CircuitBreaker>>>,
+}
+
+impl CircuitBreakerRegistry {
+    /// Create new registry
+    pub fn new(default_config: CircuitBreakerConfig) -> Self {
+        Self {
+            default_config,
+            breakers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Get the breaker for an endpoint, creating it on first use
+    pub async fn get(&self, endpoint: &str, config: Option<&CircuitBreakerConfig>) -> Arc<CircuitBreaker> {
+        if let Some(breaker) = self.breakers.read().await.get(endpoint) {
+            return breaker.clone();
+        }
+
+        self.breakers
+            .write()
+            .await
+            .entry(endpoint.to_string())
+            .or_insert_with(|| {
+                Arc::new(CircuitBreaker::new(
+                    endpoint,
+                    config.cloned().unwrap_or_else(|| self.default_config.clone()),
+                ))
+            })
+            .clone()
+    }
+
+    /// Get the states of all registered breakers
+    pub async fn states(&self) -> HashMap<String, CircuitState> {
+        let breakers = self.breakers.read().await;
+        let mut states = HashMap::with_capacity(breakers.len());
+        for (endpoint, breaker) in breakers.iter() {
+            states.insert(endpoint.clone(), breaker.state().await);
+        }
+        states
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(open_timeout: Duration) -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_threshold: 2,
+            success_threshold: 1,
+            open_timeout,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_opens_after_threshold() {
+        let breaker = CircuitBreaker::new("/api/test", config(Duration::from_secs(60)));
+
+        breaker.record_failure().await;
+        assert_eq!(breaker.state().await, CircuitState::Closed);
+        breaker.record_failure().await;
+        assert_eq!(breaker.state().await, CircuitState::Open);
+        assert!(!breaker.allow_request().await);
+    }
+
+    #[tokio::test]
+    async fn test_half_open_recovery() {
+        let breaker = CircuitBreaker::new("/api/test", config(Duration::from_millis(10)));
+        breaker.trip().await;
+        assert_eq!(breaker.state().await, CircuitState::Open);
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(breaker.state().await, CircuitState::HalfOpen);
+
+        breaker.record_success().await;
+        assert_eq!(breaker.state().await, CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_half_open_failure_reopens() {
+        let breaker = CircuitBreaker::new("/api/test", config(Duration::from_millis(10)));
+        breaker.trip().await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        breaker.record_failure().await;
+        assert_eq!(breaker.state().await, CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_half_open_admits_one_trial() {
+        let breaker = Arc::new(CircuitBreaker::new(
+            "/api/test",
+            config(Duration::from_millis(10)),
+        ));
+        breaker.trip().await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let attempts = (0..10)
+            .map(|_| {
+                let breaker = breaker.clone();
+                tokio::spawn(async move { breaker.allow_request().await })
+            })
+            .collect::<Vec<_>>();
+        let mut admitted = 0;
+        for attempt in attempts {
+            if attempt.await.unwrap() {
+                admitted += 1;
+            }
+        }
+        assert_eq!(admitted, 1);
+        assert_eq!(breaker.state().await, CircuitState::HalfOpen);
+
+        // The trial succeeds and the circuit closes for everyone
+        breaker.record_success().await;
+        assert!(breaker.allow_request().await);
+        assert!(breaker.allow_request().await);
+    }
+
+    #[tokio::test]
+    async fn test_registry_per_endpoint() {
+        let registry = CircuitBreakerRegistry::new(config(Duration::from_secs(60)));
+        registry.get("/a", None).await.trip().await;
+
+        assert!(!registry.get("/a", None).await.allow_request().await);
+        assert!(registry.get("/b", None).await.allow_request().await);
+    }
+}
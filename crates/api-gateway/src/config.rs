@@ -1,5 +1,10 @@
 //! API gateway configuration
 
+use crate::auth::AuthConfig;
+use crate::cache::CacheConfig;
+use crate::circuit_breaker::CircuitBreakerConfig;
+use crate::rate_limiter::RateLimitConfig;
+use crate::transform::TransformConfig;
 use serde::{Deserialize, Serialize};
 
 /// Gateway configuration
@@ -8,6 +13,21 @@ pub struct GatewayConfig {
     pub listen_address: String,
     pub listen_port: u16,
     pub enabled: bool,
+    /// Default circuit breaker settings for routes
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Credentials accepted by the gateway
+    #[serde(default)]
+    pub auth: AuthConfig,
+    /// Request rate limits
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// Response caching
+    #[serde(default)]
+    pub cache: CacheConfig,
+    /// Transforms applied to every route
+    #[serde(default)]
+    pub transforms: TransformConfig,
 }
 
 impl Default for GatewayConfig {
@@ -16,6 +36,11 @@ impl Default for GatewayConfig {
             listen_address: "0.0.0.0".to_string(),
             listen_port: 8080,
             enabled: true,
+            circuit_breaker: CircuitBreakerConfig::default(),
+            auth: AuthConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            cache: CacheConfig::default(),
+            transforms: TransformConfig::default(),
         }
     }
 }
@@ -16,6 +16,26 @@ pub enum GatewayError {
     #[error("Network error: {0}")]
     Network(#[from] std::io::Error),
 
+    /// Circuit breaker is open for the route
+    #[error("Circuit open for route: {0}")]
+    CircuitOpen(String),
+
+    /// Backend call failed
+    #[error("Bad gateway: {0}")]
+    BadGateway(String),
+
+    /// Missing or invalid credentials
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// Credentials lack a required scope
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    /// Client exceeded a rate limit, retry after the given time
+    #[error("Rate limit exceeded, retry after {0:?}")]
+    RateLimited(std::time::Duration),
+
     /// Generic error
     #[error("Gateway error: {0}")]
     Generic(String),
@@ -0,0 +1,91 @@
+//! Fallback responses served while a backend is degraded
+
+use crate::gateway::GatewayResponse;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Header added to every fallback response
+pub const DEGRADED_HEADER: &str = "X-Gateway-Degraded";
+
+/// Why a fallback response was served
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DegradedReason {
+    /// The route's circuit breaker is open
+    CircuitOpen,
+    /// The backend call failed
+    BackendError,
+}
+
+impl DegradedReason {
+    /// Header value for this reason
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DegradedReason::CircuitOpen => "circuit-open",
+            DegradedReason::BackendError => "backend-error",
+        }
+    }
+}
+
+/// Per-route fallback configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FallbackConfig {
+    /// Serve a fixed response
+    Static {
+        status: u16,
+        content_type: String,
+        body: String,
+    },
+    /// Serve the last successful response seen for the route
+    LastGood,
+}
+
+/// Store of last-good responses keyed by route
+#[derive(Debug, Default)]
+pub struct FallbackCache {
+    last_good: RwLock<HashMap<String, GatewayResponse>>,
+}
+
+impl FallbackCache {
+    /// Create new fallback cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember a successful response for a route
+    pub async fn store(&self, route: &str, response: &GatewayResponse) {
+        self.last_good
+            .write()
+            .await
+            .insert(route.to_string(), response.clone());
+    }
+
+    /// Build the fallback response for a route, if one is available
+    pub async fn resolve(
+        &self,
+        route: &str,
+        config: &FallbackConfig,
+        reason: DegradedReason,
+    ) -> Option<GatewayResponse> {
+        let mut response = match config {
+            FallbackConfig::Static {
+                status,
+                content_type,
+                body,
+            } => {
+                let mut response = GatewayResponse::new(*status, body.clone().into_bytes());
+                response
+                    .headers
+                    .insert("Content-Type".to_string(), content_type.clone());
+                response
+            }
+            FallbackConfig::LastGood => self.last_good.read().await.get(route).cloned()?,
+        };
+
+        response
+            .headers
+            .insert(DEGRADED_HEADER.to_string(), reason.as_str().to_string());
+        Some(response)
+    }
+}
@@ -1,16 +1,127 @@
 //! API gateway implementation
 
-use crate::{GatewayConfig, error::Result};
+use crate::auth::{ApiKeyStore, AuthManager};
+use crate::cache::{CacheManager, CACHE_HEADER};
+use crate::circuit_breaker::{CircuitBreakerRegistry, CircuitState};
+use crate::error::GatewayError;
+use crate::fallback::{DegradedReason, FallbackCache, FallbackConfig, DEGRADED_HEADER};
+use crate::openapi::{OpenApiSpec, OPENAPI_PATH};
+use crate::rate_limiter::RateLimiter;
+use crate::transform::TransformManager;
+use crate::router::Route;
+use crate::{AuthInfo, BackendService, GatewayConfig, RequestContext, error::Result};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use tracing::{debug, warn};
+
+/// Request received from a gateway client
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GatewayRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl GatewayRequest {
+    /// Create new request
+    pub fn new(method: &str, path: &str) -> Self {
+        Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Add a header
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Get a header value (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Response returned to gateway clients
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GatewayResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl GatewayResponse {
+    /// Create new response
+    pub fn new(status: u16, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            headers: HashMap::new(),
+            body,
+        }
+    }
+
+    /// Get a header value (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Check whether the response indicates a server-side failure
+    pub fn is_server_error(&self) -> bool {
+        self.status >= 500
+    }
+
+    fn error(status: u16, error: &str) -> Self {
+        let mut response = Self::new(status, format!("{{\"error\":\"{}\"}}", error).into_bytes());
+        response
+            .headers
+            .insert("Content-Type".to_string(), "application/json".to_string());
+        response
+    }
+}
 
 /// API gateway
 pub struct ApiGateway {
     config: GatewayConfig,
+    breakers: CircuitBreakerRegistry,
+    fallbacks: FallbackCache,
+    auth: AuthManager,
+    rate_limiter: RateLimiter,
+    cache: CacheManager,
+    transforms: TransformManager,
+    routes: RwLock<Vec<Route>>,
+    backends: RwLock<Vec<BackendService>>,
 }
 
 impl ApiGateway {
     /// Create new API gateway
     pub async fn new(config: GatewayConfig) -> Result<Self> {
-        Ok(Self { config })
+        Ok(Self {
+            breakers: CircuitBreakerRegistry::new(config.circuit_breaker.clone()),
+            fallbacks: FallbackCache::new(),
+            auth: AuthManager::new(config.auth.clone()),
+            rate_limiter: RateLimiter::new(&config.rate_limit)?,
+            cache: CacheManager::new(&config.cache),
+            transforms: TransformManager::new(config.transforms.clone()),
+            routes: RwLock::new(Vec::new()),
+            backends: RwLock::new(Vec::new()),
+            config,
+        })
+    }
+
+    /// Look API keys up in another store instead of the configured keys
+    pub fn with_api_key_store(mut self, store: Arc<dyn ApiKeyStore>) -> Self {
+        self.auth = self.auth.with_api_key_store(store);
+        self
     }
 
     /// Start gateway
@@ -18,4 +129,800 @@ impl ApiGateway {
         // TODO: Implement gateway startup
         Ok(())
     }
+
+    /// Get gateway configuration
+    pub fn config(&self) -> &GatewayConfig {
+        &self.config
+    }
+
+    /// Register a route
+    pub fn add_route(&self, route: Route) {
+        self.routes.write().unwrap().push(route);
+    }
+
+    /// Register a backend service
+    pub fn add_backend(&self, backend: BackendService) {
+        self.backends.write().unwrap().push(backend);
+    }
+
+    /// Get all registered routes
+    pub fn routes(&self) -> Vec<Route> {
+        self.routes.read().unwrap().clone()
+    }
+
+    /// Describe the registered routes as an OpenAPI document, served at
+    /// `/openapi.json`
+    pub fn generate_openapi(&self) -> OpenApiSpec {
+        OpenApiSpec::generate(
+            &self.routes.read().unwrap(),
+            &self.backends.read().unwrap(),
+            &self.config.auth,
+        )
+    }
+
+    /// Get the response cache
+    pub fn cache(&self) -> &CacheManager {
+        &self.cache
+    }
+
+    /// Get the per-endpoint circuit breakers
+    pub fn circuit_breakers(&self) -> &CircuitBreakerRegistry {
+        &self.breakers
+    }
+
+    /// Get the circuit state of every endpoint called so far
+    pub async fn circuit_states(&self) -> HashMap<String, CircuitState> {
+        self.breakers.states().await
+    }
+
+    /// Handle a request for a route
+    ///
+    /// The request is authenticated and counted against the rate limits
+    /// before it is dispatched, requests with invalid credentials, without
+    /// the credentials the route requires or over a limit are answered by
+    /// the gateway and never reach the backend. Cacheable requests are
+    /// served from the cache when possible, otherwise `call` receives the
+    /// request as rewritten by the request transforms. Response transforms
+    /// apply to backend and cached responses alike. While the route's
+    /// circuit is open and it has no fallback the gateway answers `503`.
+    /// The OpenAPI document is served to anyone.
+    pub async fn handle<F, Fut>(
+        &self,
+        route: &Route,
+        request: &GatewayRequest,
+        context: &mut RequestContext,
+        call: F,
+    ) -> Result<GatewayResponse>
+    where
+        F: FnOnce(GatewayRequest) -> Fut,
+        Fut: Future<Output = Result<GatewayResponse>>,
+    {
+        if request.method.eq_ignore_ascii_case("GET") && request.path == OPENAPI_PATH {
+            let mut response = GatewayResponse::new(
+                200,
+                serde_json::to_vec(&self.generate_openapi()).unwrap_or_default(),
+            );
+            response
+                .headers
+                .insert("Content-Type".to_string(), "application/json".to_string());
+            return Ok(response);
+        }
+
+        match self.authorize(route, request).await {
+            Ok(auth_info) => context.auth_info = auth_info,
+            Err(GatewayError::Unauthorized(reason)) => {
+                debug!("Rejecting request {}: {}", context.request_id, reason);
+                let mut response = GatewayResponse::error(401, "unauthorized");
+                response
+                    .headers
+                    .insert("WWW-Authenticate".to_string(), "Bearer".to_string());
+                return Ok(response);
+            }
+            Err(GatewayError::Forbidden(reason)) => {
+                debug!("Rejecting request {}: {}", context.request_id, reason);
+                return Ok(GatewayResponse::error(403, "forbidden"));
+            }
+            Err(err) => return Err(err),
+        }
+
+        match self.rate_limiter.check(route, request, context) {
+            Ok(()) => {}
+            Err(GatewayError::RateLimited(retry_after)) => {
+                debug!("Rate limiting request {}", context.request_id);
+                // Retry-After is in whole seconds, round up so retries are not early
+                let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+                let mut response = GatewayResponse::error(429, "rate_limited");
+                response
+                    .headers
+                    .insert("Retry-After".to_string(), seconds.max(1).to_string());
+                return Ok(response);
+            }
+            Err(err) => return Err(err),
+        }
+
+        let backend_request = self
+            .transforms
+            .transform_request(route, request.clone(), context);
+        let mut response = match self
+            .fetch(route, request, context, || call(backend_request))
+            .await
+        {
+            Ok(response) => response,
+            Err(GatewayError::CircuitOpen(endpoint)) => {
+                debug!("Short-circuiting request {} to {}", context.request_id, endpoint);
+                return Ok(GatewayResponse::error(503, "service_unavailable"));
+            }
+            Err(GatewayError::BadGateway(_)) => {
+                return Ok(GatewayResponse::error(502, "bad_gateway"));
+            }
+            Err(err) => return Err(err),
+        };
+        self.transforms.transform_response(route, &mut response);
+        Ok(response)
+    }
+
+    async fn fetch<F, Fut>(
+        &self,
+        route: &Route,
+        request: &GatewayRequest,
+        context: &RequestContext,
+        call: F,
+    ) -> Result<GatewayResponse>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<GatewayResponse>>,
+    {
+        let Some(cache_key) = self.cache.key(request) else {
+            return self.dispatch(route, call).await;
+        };
+        if let Some(response) = self.cache.get(&cache_key).await {
+            return Ok(response);
+        }
+
+        let mut response = self.dispatch(route, call).await?;
+        if response.header(DEGRADED_HEADER).is_none() {
+            self.cache
+                .store(&cache_key, request, &response, context.auth_info.is_some())
+                .await;
+        }
+        response
+            .headers
+            .insert(CACHE_HEADER.to_string(), "MISS".to_string());
+        Ok(response)
+    }
+
+    async fn authorize(&self, route: &Route, request: &GatewayRequest) -> Result<Option<AuthInfo>> {
+        let auth_info = self.auth.authenticate(request).await?;
+
+        match &auth_info {
+            Some(info) => {
+                if let Some(scope) = route
+                    .required_scopes
+                    .iter()
+                    .find(|scope| !info.has_scope(scope))
+                {
+                    return Err(GatewayError::Forbidden(format!(
+                        "{} lacks scope {}",
+                        info.user_id, scope
+                    )));
+                }
+            }
+            None if route.auth_required || !route.required_scopes.is_empty() => {
+                return Err(GatewayError::Unauthorized(
+                    "missing credentials".to_string(),
+                ));
+            }
+            None => {}
+        }
+
+        Ok(auth_info)
+    }
+
+    /// Dispatch a request for a route through its circuit breaker,
+    /// serving the route's fallback while the backend is degraded
+    pub async fn dispatch<F, Fut>(&self, route: &Route, call: F) -> Result<GatewayResponse>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<GatewayResponse>>,
+    {
+        let endpoint = route.endpoint();
+        let breaker = self
+            .breakers
+            .get(&endpoint, route.circuit_breaker.as_ref())
+            .await;
+
+        if !breaker.allow_request().await {
+            return match self.fallback(route, DegradedReason::CircuitOpen).await {
+                Some(response) => Ok(response),
+                None => Err(GatewayError::CircuitOpen(endpoint)),
+            };
+        }
+
+        match call().await {
+            Ok(response) if !response.is_server_error() => {
+                breaker.record_success().await;
+                if matches!(route.fallback, Some(FallbackConfig::LastGood)) {
+                    self.fallbacks.store(&route.path, &response).await;
+                }
+                Ok(response)
+            }
+            Ok(response) => {
+                breaker.record_failure().await;
+                Ok(self
+                    .fallback(route, DegradedReason::BackendError)
+                    .await
+                    .unwrap_or(response))
+            }
+            Err(err) => {
+                warn!("Backend call for route {} failed: {}", route.path, err);
+                breaker.record_failure().await;
+                match self.fallback(route, DegradedReason::BackendError).await {
+                    Some(response) => Ok(response),
+                    None => Err(GatewayError::BadGateway(err.to_string())),
+                }
+            }
+        }
+    }
+
+    async fn fallback(&self, route: &Route, reason: DegradedReason) -> Option<GatewayResponse> {
+        match &route.fallback {
+            Some(config) => self.fallbacks.resolve(&route.path, config, reason).await,
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_breaker::{CircuitBreakerConfig, CircuitState};
+    use crate::transform::{RequestTransform, ResponseTransform};
+    use std::time::Duration;
+
+    fn route(fallback: FallbackConfig) -> Route {
+        Route {
+            path: "/api/mail".to_string(),
+            circuit_breaker: Some(CircuitBreakerConfig {
+                failure_threshold: 1,
+                success_threshold: 1,
+                open_timeout: Duration::from_millis(20),
+            }),
+            fallback: Some(fallback),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_breaker_serves_static_fallback() {
+        let gateway = ApiGateway::new(GatewayConfig::default()).await.unwrap();
+        let route = route(FallbackConfig::Static {
+            status: 200,
+            content_type: "application/json".to_string(),
+            body: "{\"degraded\":true}".to_string(),
+        });
+
+        gateway
+            .circuit_breakers()
+            .get(&route.path, route.circuit_breaker.as_ref())
+            .await
+            .trip()
+            .await;
+
+        let called = std::sync::atomic::AtomicBool::new(false);
+        let response = gateway
+            .dispatch(&route, || async {
+                called.store(true, std::sync::atomic::Ordering::Relaxed);
+                Ok(GatewayResponse::new(200, Vec::new()))
+            })
+            .await
+            .unwrap();
+        assert!(!called.load(std::sync::atomic::Ordering::Relaxed));
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, b"{\"degraded\":true}");
+        assert_eq!(response.header(DEGRADED_HEADER), Some("circuit-open"));
+    }
+
+    #[tokio::test]
+    async fn test_last_good_fallback_and_recovery() {
+        let gateway = ApiGateway::new(GatewayConfig::default()).await.unwrap();
+        let route = route(FallbackConfig::LastGood);
+
+        let response = gateway
+            .dispatch(&route, || async { Ok(GatewayResponse::new(200, b"fresh".to_vec())) })
+            .await
+            .unwrap();
+        assert_eq!(response.header(DEGRADED_HEADER), None);
+
+        // Failure opens the breaker and serves the cached response
+        let response = gateway
+            .dispatch(&route, || async { Err(GatewayError::Generic("down".to_string())) })
+            .await
+            .unwrap();
+        assert_eq!(response.body, b"fresh");
+        assert_eq!(response.header(DEGRADED_HEADER), Some("backend-error"));
+
+        let response = gateway
+            .dispatch(&route, || async { Ok(GatewayResponse::new(200, b"unused".to_vec())) })
+            .await
+            .unwrap();
+        assert_eq!(response.body, b"fresh");
+        assert_eq!(response.header(DEGRADED_HEADER), Some("circuit-open"));
+
+        // Once the open timeout elapses the trial request closes the breaker
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let response = gateway
+            .dispatch(&route, || async { Ok(GatewayResponse::new(200, b"recovered".to_vec())) })
+            .await
+            .unwrap();
+        assert_eq!(response.body, b"recovered");
+        assert_eq!(response.header(DEGRADED_HEADER), None);
+        assert_eq!(
+            gateway.circuit_breakers().get(&route.path, None).await.state().await,
+            CircuitState::Closed
+        );
+    }
+
+    async fn auth_gateway() -> ApiGateway {
+        ApiGateway::new(GatewayConfig {
+            auth: crate::auth::tests::config(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+    }
+
+    fn protected_route() -> Route {
+        Route {
+            auth_required: true,
+            ..Default::default()
+        }
+    }
+
+    fn context() -> RequestContext {
+        RequestContext::new("127.0.0.1".parse().unwrap())
+    }
+
+    async fn handle(
+        gateway: &ApiGateway,
+        route: &Route,
+        request: &GatewayRequest,
+    ) -> (GatewayResponse, RequestContext, bool) {
+        handle_from(gateway, route, request, context()).await
+    }
+
+    async fn handle_from(
+        gateway: &ApiGateway,
+        route: &Route,
+        request: &GatewayRequest,
+        mut context: RequestContext,
+    ) -> (GatewayResponse, RequestContext, bool) {
+        let called = std::sync::atomic::AtomicBool::new(false);
+        let response = gateway
+            .handle(route, request, &mut context, |_| async {
+                called.store(true, std::sync::atomic::Ordering::Relaxed);
+                Ok(GatewayResponse::new(200, b"mail".to_vec()))
+            })
+            .await
+            .unwrap();
+        (response, context, called.load(std::sync::atomic::Ordering::Relaxed))
+    }
+
+    #[tokio::test]
+    async fn test_valid_jwt_populates_context() {
+        let gateway = auth_gateway().await;
+        let token = crate::auth::tests::token(serde_json::json!({
+            "sub": "alice@example.com",
+            "roles": ["user"],
+            "exp": chrono::Utc::now().timestamp() + 3600,
+        }));
+        let request = GatewayRequest::new("GET", "/api/mail")
+            .with_header("Authorization", &format!("Bearer {}", token));
+
+        let (response, context, called) = handle(&gateway, &protected_route(), &request).await;
+        assert!(called);
+        assert_eq!(response.status, 200);
+        let auth_info = context.auth_info.unwrap();
+        assert_eq!(auth_info.user_id, "alice@example.com");
+        assert_eq!(auth_info.roles, vec!["user"]);
+        assert_eq!(auth_info.claims["sub"], "alice@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_expired_jwt_rejected() {
+        let gateway = auth_gateway().await;
+        let token = crate::auth::tests::token(serde_json::json!({
+            "sub": "alice@example.com",
+            "exp": chrono::Utc::now().timestamp() - 3600,
+        }));
+        let request = GatewayRequest::new("GET", "/api/mail")
+            .with_header("Authorization", &format!("Bearer {}", token));
+
+        // Invalid credentials are rejected even on routes that do not require them
+        for route in [protected_route(), Route::default()] {
+            let (response, context, called) = handle(&gateway, &route, &request).await;
+            assert!(!called);
+            assert_eq!(response.status, 401);
+            assert_eq!(response.header("www-authenticate"), Some("Bearer"));
+            assert!(context.auth_info.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_key_scopes_enforced() {
+        let gateway = auth_gateway().await;
+        let request = GatewayRequest::new("GET", "/api/mail").with_header("X-API-Key", "key-123");
+
+        let route = Route {
+            required_scopes: vec!["mail:read".to_string()],
+            ..protected_route()
+        };
+        let (response, context, called) = handle(&gateway, &route, &request).await;
+        assert!(called);
+        assert_eq!(response.status, 200);
+        assert_eq!(context.auth_info.unwrap().user_id, "service@example.com");
+
+        let route = Route {
+            required_scopes: vec!["mail:write".to_string()],
+            ..protected_route()
+        };
+        let (response, _, called) = handle(&gateway, &route, &request).await;
+        assert!(!called);
+        assert_eq!(response.status, 403);
+    }
+
+    #[tokio::test]
+    async fn test_unscoped_token_rejected_on_scoped_route() {
+        let gateway = auth_gateway().await;
+        let token = crate::auth::tests::token(serde_json::json!({
+            "sub": "alice@example.com",
+            "exp": chrono::Utc::now().timestamp() + 3600,
+        }));
+        let request = GatewayRequest::new("GET", "/api/mail")
+            .with_header("Authorization", &format!("Bearer {}", token));
+        let route = Route {
+            required_scopes: vec!["mail:read".to_string()],
+            ..protected_route()
+        };
+
+        let (response, _, called) = handle(&gateway, &route, &request).await;
+        assert!(!called);
+        assert_eq!(response.status, 403);
+
+        // The same token still reaches routes that require no scope
+        let (response, _, called) = handle(&gateway, &protected_route(), &request).await;
+        assert!(called);
+        assert_eq!(response.status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_missing_credentials_on_protected_route() {
+        let gateway = auth_gateway().await;
+        let request = GatewayRequest::new("GET", "/api/mail");
+
+        let (response, _, called) = handle(&gateway, &protected_route(), &request).await;
+        assert!(!called);
+        assert_eq!(response.status, 401);
+
+        // Public routes serve anonymous requests
+        let (response, context, called) = handle(&gateway, &Route::default(), &request).await;
+        assert!(called);
+        assert_eq!(response.status, 200);
+        assert!(context.auth_info.is_none());
+    }
+
+    async fn rate_limited_gateway(period: Duration) -> ApiGateway {
+        ApiGateway::new(GatewayConfig {
+            rate_limit: crate::rate_limiter::RateLimitConfig {
+                enabled: true,
+                rules: vec![crate::rate_limiter::RateLimitRule {
+                    matcher: crate::RouteMatcher {
+                        path: "/api/mail".to_string(),
+                        ..Default::default()
+                    },
+                    requests: 2,
+                    period,
+                    key: crate::rate_limiter::RateLimitKey::ClientIp,
+                }],
+            },
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+    }
+
+    fn client(ip: &str) -> RequestContext {
+        RequestContext::new(ip.parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_per_client() {
+        let gateway = rate_limited_gateway(Duration::from_secs(60)).await;
+        let route = Route::default();
+        let request = GatewayRequest::new("GET", "/api/mail");
+
+        for _ in 0..2 {
+            let (response, _, called) =
+                handle_from(&gateway, &route, &request, client("10.0.0.1")).await;
+            assert!(called);
+            assert_eq!(response.status, 200);
+        }
+
+        let (response, _, called) =
+            handle_from(&gateway, &route, &request, client("10.0.0.1")).await;
+        assert!(!called);
+        assert_eq!(response.status, 429);
+        let retry_after: u64 = response.header("Retry-After").unwrap().parse().unwrap();
+        assert!((1..=30).contains(&retry_after));
+
+        // Another client on the same route keeps its own limit
+        let (response, _, called) =
+            handle_from(&gateway, &route, &request, client("10.0.0.2")).await;
+        assert!(called);
+        assert_eq!(response.status, 200);
+
+        // Other routes are not limited by the rule
+        let request = GatewayRequest::new("GET", "/api/contacts");
+        let (response, _, _) = handle_from(&gateway, &route, &request, client("10.0.0.1")).await;
+        assert_eq!(response.status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_refills() {
+        let gateway = rate_limited_gateway(Duration::from_millis(200)).await;
+        let route = Route::default();
+        let request = GatewayRequest::new("GET", "/api/mail");
+
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let (response, _, _) =
+                handle_from(&gateway, &route, &request, client("10.0.0.1")).await;
+            statuses.push(response.status);
+        }
+        assert_eq!(statuses, vec![200, 200, 429]);
+
+        // One request is allowed again every 100ms
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        let (response, _, _) = handle_from(&gateway, &route, &request, client("10.0.0.1")).await;
+        assert_eq!(response.status, 200);
+        let (response, _, _) = handle_from(&gateway, &route, &request, client("10.0.0.1")).await;
+        assert_eq!(response.status, 429);
+    }
+
+    async fn caching_gateway(vary: Vec<String>) -> ApiGateway {
+        ApiGateway::new(GatewayConfig {
+            cache: crate::cache::CacheConfig {
+                policies: vec![crate::cache::CachePolicy {
+                    matcher: crate::RouteMatcher::default(),
+                    ttl: None,
+                    vary,
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn handle_cached(
+        gateway: &ApiGateway,
+        request: &GatewayRequest,
+        cache_control: &str,
+        body: &str,
+    ) -> (GatewayResponse, bool) {
+        let called = std::sync::atomic::AtomicBool::new(false);
+        let response = gateway
+            .handle(&Route::default(), request, &mut context(), |_| async {
+                called.store(true, std::sync::atomic::Ordering::Relaxed);
+                let mut response = GatewayResponse::new(200, body.as_bytes().to_vec());
+                response
+                    .headers
+                    .insert("Cache-Control".to_string(), cache_control.to_string());
+                Ok(response)
+            })
+            .await
+            .unwrap();
+        (response, called.load(std::sync::atomic::Ordering::Relaxed))
+    }
+
+    #[tokio::test]
+    async fn test_cached_get() {
+        let gateway = caching_gateway(Vec::new()).await;
+        let request = GatewayRequest::new("GET", "/api/mail/1");
+
+        let (response, called) = handle_cached(&gateway, &request, "max-age=60", "first").await;
+        assert!(called);
+        assert_eq!(response.header(CACHE_HEADER), Some("MISS"));
+
+        let (response, called) = handle_cached(&gateway, &request, "max-age=60", "second").await;
+        assert!(!called);
+        assert_eq!(response.header(CACHE_HEADER), Some("HIT"));
+        assert_eq!(response.body, b"first");
+
+        // Only idempotent requests are cached
+        let request = GatewayRequest::new("POST", "/api/mail/1");
+        let (response, called) = handle_cached(&gateway, &request, "max-age=60", "posted").await;
+        assert!(called);
+        assert_eq!(response.header(CACHE_HEADER), None);
+    }
+
+    #[tokio::test]
+    async fn test_no_store_never_cached() {
+        let gateway = caching_gateway(Vec::new()).await;
+        let request = GatewayRequest::new("GET", "/api/mail/1");
+
+        for body in ["first", "second"] {
+            let (response, called) = handle_cached(&gateway, &request, "no-store", body).await;
+            assert!(called);
+            assert_eq!(response.header(CACHE_HEADER), Some("MISS"));
+            assert_eq!(response.body, body.as_bytes());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_varies_on_configured_headers() {
+        let gateway = caching_gateway(vec!["Accept-Language".to_string()]).await;
+        let english =
+            GatewayRequest::new("GET", "/api/mail/1").with_header("Accept-Language", "en");
+        let german =
+            GatewayRequest::new("GET", "/api/mail/1").with_header("accept-language", "de");
+
+        let (_, called) = handle_cached(&gateway, &english, "max-age=60", "hello").await;
+        assert!(called);
+        let (response, called) = handle_cached(&gateway, &german, "max-age=60", "hallo").await;
+        assert!(called);
+        assert_eq!(response.header(CACHE_HEADER), Some("MISS"));
+
+        let (response, called) = handle_cached(&gateway, &english, "max-age=60", "unused").await;
+        assert!(!called);
+        assert_eq!(response.body, b"hello");
+        let (response, called) = handle_cached(&gateway, &german, "max-age=60", "unused").await;
+        assert!(!called);
+        assert_eq!(response.body, b"hallo");
+
+        // Headers outside the policy do not split the cache
+        let request = english.with_header("User-Agent", "test");
+        let (response, called) = handle_cached(&gateway, &request, "max-age=60", "unused").await;
+        assert!(!called);
+        assert_eq!(response.body, b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_path_prefix_rewrite_reaches_backend() {
+        let gateway = ApiGateway::new(GatewayConfig::default()).await.unwrap();
+        let route = Route {
+            request_transforms: vec![
+                RequestTransform::RewritePathPrefix {
+                    from: "/api/v1".to_string(),
+                    to: "/jmap".to_string(),
+                },
+                RequestTransform::CorrelationId {
+                    header: "X-Correlation-ID".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+        let request = GatewayRequest::new("GET", "/api/v1/mailbox/get?limit=10");
+        let mut context = context();
+
+        let received = std::sync::Mutex::new(None);
+        gateway
+            .handle(&route, &request, &mut context, |backend_request| async {
+                *received.lock().unwrap() = Some(backend_request);
+                Ok(GatewayResponse::new(200, Vec::new()))
+            })
+            .await
+            .unwrap();
+
+        let received = received.into_inner().unwrap().unwrap();
+        assert_eq!(received.path, "/jmap/mailbox/get?limit=10");
+        assert_eq!(
+            received.header("X-Correlation-ID"),
+            Some(context.request_id.to_string().as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_response_header_redaction() {
+        let gateway = ApiGateway::new(GatewayConfig {
+            transforms: crate::transform::TransformConfig {
+                response: vec![ResponseTransform::RemoveHeader {
+                    name: "Server".to_string(),
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let route = Route {
+            response_transforms: vec![ResponseTransform::RemoveHeader {
+                name: "x-backend-host".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let response = gateway
+            .handle(&route, &GatewayRequest::new("GET", "/api/mail"), &mut context(), |_| async {
+                let mut response = GatewayResponse::new(200, Vec::new());
+                for (name, value) in [
+                    ("X-Backend-Host", "10.0.0.5"),
+                    ("server", "stalwart"),
+                    ("Content-Type", "application/json"),
+                ] {
+                    response.headers.insert(name.to_string(), value.to_string());
+                }
+                Ok(response)
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.header("X-Backend-Host"), None);
+        assert_eq!(response.header("Server"), None);
+        assert_eq!(response.header("Content-Type"), Some("application/json"));
+    }
+
+    #[tokio::test]
+    async fn test_backend_failures_open_circuit() {
+        let gateway = ApiGateway::new(GatewayConfig::default()).await.unwrap();
+        let route = Route {
+            backend: Some("jmap".to_string()),
+            circuit_breaker: Some(CircuitBreakerConfig {
+                failure_threshold: 3,
+                success_threshold: 1,
+                open_timeout: Duration::from_millis(20),
+            }),
+            ..Default::default()
+        };
+        let request = GatewayRequest::new("GET", "/api/mail");
+
+        let send = |status: u16| {
+            let called = std::sync::atomic::AtomicBool::new(false);
+            let gateway = &gateway;
+            let route = &route;
+            let request = &request;
+            async move {
+                let response = gateway
+                    .handle(route, request, &mut context(), |_| async {
+                        called.store(true, std::sync::atomic::Ordering::Relaxed);
+                        Ok(GatewayResponse::new(status, Vec::new()))
+                    })
+                    .await
+                    .unwrap();
+                (response.status, called.load(std::sync::atomic::Ordering::Relaxed))
+            }
+        };
+
+        for _ in 0..3 {
+            assert_eq!(send(500).await, (500, true));
+        }
+        assert_eq!(
+            gateway.circuit_states().await.get("jmap /api/*"),
+            Some(&CircuitState::Open)
+        );
+
+        // The backend is not called while the circuit is open
+        assert_eq!(send(200).await, (503, false));
+        assert_eq!(send(200).await, (503, false));
+
+        // A successful half-open probe closes the circuit
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(send(200).await, (200, true));
+        assert_eq!(
+            gateway.circuit_states().await.get("jmap /api/*"),
+            Some(&CircuitState::Closed)
+        );
+        assert_eq!(send(200).await, (200, true));
+    }
+
+    #[tokio::test]
+    async fn test_open_breaker_without_fallback_errors() {
+        let gateway = ApiGateway::new(GatewayConfig::default()).await.unwrap();
+        let route = Route::default();
+        gateway.circuit_breakers().get(&route.path, None).await.trip().await;
+
+        let result = gateway
+            .dispatch(&route, || async { Ok(GatewayResponse::new(200, Vec::new())) })
+            .await;
+        assert!(matches!(result, Err(GatewayError::CircuitOpen(_))));
+    }
 }
@@ -57,19 +57,23 @@ pub mod cache;
 pub mod transform;
 pub mod load_balancer;
 pub mod circuit_breaker;
+pub mod fallback;
+pub mod openapi;
 pub mod middleware;
 pub mod metrics;
 pub mod error;
 
 pub use config::GatewayConfig;
-pub use gateway::ApiGateway;
+pub use gateway::{ApiGateway, GatewayRequest, GatewayResponse};
 pub use router::{Router, Route, RouteConfig};
-pub use auth::{AuthManager, AuthConfig, AuthMethod};
-pub use rate_limiter::{RateLimiter, RateLimitConfig, RateLimitRule};
-pub use cache::{CacheManager, CacheConfig, CachePolicy};
-pub use transform::{TransformManager, RequestTransform, ResponseTransform};
+pub use auth::{AuthManager, AuthConfig, AuthMethod, ApiKey, ApiKeyStore, StaticApiKeyStore};
+pub use rate_limiter::{RateLimiter, RateLimitConfig, RateLimitRule, RateLimitKey};
+pub use cache::{CacheManager, CacheConfig, CachePolicy, CACHE_HEADER};
+pub use transform::{TransformManager, TransformConfig, RequestTransform, ResponseTransform};
 pub use load_balancer::{LoadBalancer, LoadBalancingAlgorithm, Backend};
-pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerRegistry, CircuitState};
+pub use fallback::{FallbackConfig, FallbackCache, DegradedReason, DEGRADED_HEADER};
+pub use openapi::{OpenApiSpec, OPENAPI_PATH};
 pub use error::{GatewayError, Result};
 
 /// Gateway status
@@ -104,6 +108,20 @@ pub struct RequestContext {
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
+impl RequestContext {
+    /// Create a context for a new request from a client
+    pub fn new(client_ip: std::net::IpAddr) -> Self {
+        Self {
+            request_id: uuid::Uuid::new_v4(),
+            client_ip,
+            user_agent: None,
+            auth_info: None,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::new(),
+        }
+    }
+}
+
 /// Authentication information
 #[derive(Debug, Clone)]
 pub struct AuthInfo {
@@ -151,6 +169,53 @@ pub struct RouteMatcher {
     pub query_params: HashMap<String, String>,
 }
 
+impl RouteMatcher {
+    /// Check if a request matches, a path ending in `*` matches every path
+    /// starting with the part before it
+    pub fn matches(&self, request: &gateway::GatewayRequest) -> bool {
+        let (path, query) = request
+            .path
+            .split_once('?')
+            .unwrap_or((request.path.as_str(), ""));
+
+        let path_matches = match self.path.strip_suffix('*') {
+            Some(prefix) => path.starts_with(prefix),
+            None => path == self.path,
+        };
+        if !path_matches {
+            return false;
+        }
+
+        if let Some(method) = &self.method {
+            if !method.eq_ignore_ascii_case(&request.method) {
+                return false;
+            }
+        }
+
+        if let Some(host) = &self.host {
+            let request_host = request
+                .header("Host")
+                .map(|value| value.split(':').next().unwrap_or(value));
+            if !request_host.is_some_and(|request_host| request_host.eq_ignore_ascii_case(host)) {
+                return false;
+            }
+        }
+
+        let headers_match = self
+            .headers
+            .iter()
+            .all(|(name, value)| request.header(name) == Some(value.as_str()));
+        let query_matches = self.query_params.iter().all(|(name, value)| {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .any(|(key, found)| key == name && found == value)
+        });
+
+        headers_match && query_matches
+    }
+}
+
 /// Main API gateway context
 pub struct GatewayContext {
     pub config: GatewayConfig,
@@ -288,6 +353,37 @@ mod tests {
         assert_eq!(backends[0].name, "stalwart-backend");
     }
 
+    #[test]
+    fn test_route_matcher() {
+        let matcher = RouteMatcher {
+            path: "/api/mail/*".to_string(),
+            method: Some("GET".to_string()),
+            host: Some("mail.example.com".to_string()),
+            query_params: HashMap::from([("folder".to_string(), "inbox".to_string())]),
+            ..Default::default()
+        };
+        let request = GatewayRequest::new("get", "/api/mail/messages?folder=inbox&page=2")
+            .with_header("host", "mail.example.com:8080");
+        assert!(matcher.matches(&request));
+
+        assert!(!matcher.matches(&GatewayRequest {
+            method: "POST".to_string(),
+            ..request.clone()
+        }));
+        assert!(!matcher.matches(&GatewayRequest {
+            path: "/api/mail/messages?folder=sent".to_string(),
+            ..request.clone()
+        }));
+        assert!(!matcher.matches(&GatewayRequest {
+            path: "/api/contacts?folder=inbox".to_string(),
+            ..request.clone()
+        }));
+        assert!(!matcher.matches(
+            &GatewayRequest::new("GET", "/api/mail/messages?folder=inbox")
+                .with_header("Host", "other.example.com")
+        ));
+    }
+
     #[test]
     fn test_default_backend_service() {
         let backend = BackendService::default();
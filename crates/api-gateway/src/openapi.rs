@@ -0,0 +1,282 @@
+//! OpenAPI description of the gateway's routes
+
+use crate::auth::AuthConfig;
+use crate::router::Route;
+use crate::BackendService;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Path the OpenAPI document is served at
+pub const OPENAPI_PATH: &str = "/openapi.json";
+
+const BEARER_SCHEME: &str = "bearerAuth";
+const API_KEY_SCHEME: &str = "apiKeyAuth";
+
+/// OpenAPI 3.1 document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenApiSpec {
+    pub openapi: String,
+    pub info: OpenApiInfo,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<OpenApiTag>,
+    /// Operations by path and lowercase method
+    pub paths: BTreeMap<String, BTreeMap<String, OpenApiOperation>>,
+    pub components: OpenApiComponents,
+}
+
+/// Document metadata
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenApiInfo {
+    pub title: String,
+    pub version: String,
+}
+
+/// Tag grouping the operations of a backend service
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenApiTag {
+    pub name: String,
+}
+
+/// Operation on a path
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenApiOperation {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<OpenApiParameter>,
+    /// Alternative security requirements, any one of them grants access
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub security: Vec<BTreeMap<String, Vec<String>>>,
+    pub responses: BTreeMap<String, OpenApiResponse>,
+}
+
+/// Path parameter
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenApiParameter {
+    pub name: String,
+    #[serde(rename = "in")]
+    pub location: String,
+    pub required: bool,
+    pub schema: serde_json::Value,
+}
+
+/// Response description
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenApiResponse {
+    pub description: String,
+}
+
+/// Reusable components
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenApiComponents {
+    #[serde(rename = "securitySchemes")]
+    pub security_schemes: BTreeMap<String, serde_json::Value>,
+}
+
+impl OpenApiSpec {
+    /// Describe the given routes, skipping internal ones
+    pub fn generate(routes: &[Route], backends: &[BackendService], auth: &AuthConfig) -> Self {
+        let mut security_schemes = BTreeMap::new();
+        if auth.jwt_secret.is_some() {
+            security_schemes.insert(
+                BEARER_SCHEME.to_string(),
+                serde_json::json!({"type": "http", "scheme": "bearer", "bearerFormat": "JWT"}),
+            );
+        }
+        security_schemes.insert(
+            API_KEY_SCHEME.to_string(),
+            serde_json::json!({
+                "type": "apiKey",
+                "in": "header",
+                "name": crate::auth::API_KEY_HEADER,
+            }),
+        );
+
+        let mut paths: BTreeMap<String, BTreeMap<String, OpenApiOperation>> = BTreeMap::new();
+        for route in routes.iter().filter(|route| !route.internal) {
+            let (path, parameters) = openapi_path(&route.path);
+
+            let requires_auth = route.auth_required || !route.required_scopes.is_empty();
+            let security = if requires_auth {
+                security_schemes
+                    .keys()
+                    .map(|scheme| BTreeMap::from([(scheme.clone(), route.required_scopes.clone())]))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            let mut responses = BTreeMap::from([(
+                "200".to_string(),
+                OpenApiResponse {
+                    description: "Successful response".to_string(),
+                },
+            )]);
+            if requires_auth {
+                responses.insert(
+                    "401".to_string(),
+                    OpenApiResponse {
+                        description: "Missing or invalid credentials".to_string(),
+                    },
+                );
+            }
+            if !route.required_scopes.is_empty() {
+                responses.insert(
+                    "403".to_string(),
+                    OpenApiResponse {
+                        description: "Credentials lack a required scope".to_string(),
+                    },
+                );
+            }
+
+            paths.entry(path).or_default().insert(
+                route.method.to_ascii_lowercase(),
+                OpenApiOperation {
+                    tags: route.backend.iter().cloned().collect(),
+                    parameters,
+                    security,
+                    responses,
+                },
+            );
+        }
+
+        // Tag the backend services that serve at least one described route
+        let tags = backends
+            .iter()
+            .filter(|backend| {
+                paths
+                    .values()
+                    .flat_map(|operations| operations.values())
+                    .any(|operation| operation.tags.contains(&backend.name))
+            })
+            .map(|backend| OpenApiTag {
+                name: backend.name.clone(),
+            })
+            .collect();
+
+        Self {
+            openapi: "3.1.0".to_string(),
+            info: OpenApiInfo {
+                title: "Stalwart Mail Server API".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            tags,
+            paths,
+            components: OpenApiComponents { security_schemes },
+        }
+    }
+}
+
+/// Convert a route path into an OpenAPI path, a trailing `*` becomes a
+/// `{path}` parameter
+fn openapi_path(path: &str) -> (String, Vec<OpenApiParameter>) {
+    match path.strip_suffix('*') {
+        Some(prefix) => (
+            format!("{}{{path}}", prefix),
+            vec![OpenApiParameter {
+                name: "path".to_string(),
+                location: "path".to_string(),
+                required: true,
+                schema: serde_json::json!({"type": "string"}),
+            }],
+        ),
+        None => (path.to_string(), Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gateway::{ApiGateway, GatewayRequest, GatewayResponse};
+    use crate::{GatewayConfig, RequestContext};
+
+    #[tokio::test]
+    async fn test_generate_from_registered_routes() {
+        let gateway = ApiGateway::new(GatewayConfig {
+            auth: AuthConfig {
+                jwt_secret: Some("secret".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        gateway.add_backend(BackendService {
+            name: "jmap".to_string(),
+            ..Default::default()
+        });
+        gateway.add_backend(BackendService {
+            name: "unused".to_string(),
+            ..Default::default()
+        });
+        gateway.add_route(Route {
+            path: "/api/mail/*".to_string(),
+            method: "GET".to_string(),
+            backend: Some("jmap".to_string()),
+            required_scopes: vec!["mail:read".to_string()],
+            ..Default::default()
+        });
+        gateway.add_route(Route {
+            path: "/api/status".to_string(),
+            method: "GET".to_string(),
+            ..Default::default()
+        });
+        gateway.add_route(Route {
+            path: "/internal/reload".to_string(),
+            method: "POST".to_string(),
+            internal: true,
+            ..Default::default()
+        });
+
+        let spec = gateway.generate_openapi();
+        assert_eq!(spec.openapi, "3.1.0");
+        assert_eq!(
+            spec.paths.keys().collect::<Vec<_>>(),
+            vec!["/api/mail/{path}", "/api/status"]
+        );
+        assert_eq!(
+            spec.components.security_schemes.keys().collect::<Vec<_>>(),
+            vec![API_KEY_SCHEME, BEARER_SCHEME]
+        );
+        assert_eq!(
+            spec.tags,
+            vec![OpenApiTag {
+                name: "jmap".to_string()
+            }]
+        );
+
+        let mail = &spec.paths["/api/mail/{path}"]["get"];
+        assert_eq!(mail.tags, vec!["jmap"]);
+        assert_eq!(mail.parameters[0].name, "path");
+        assert_eq!(
+            mail.security,
+            vec![
+                BTreeMap::from([(API_KEY_SCHEME.to_string(), vec!["mail:read".to_string()])]),
+                BTreeMap::from([(BEARER_SCHEME.to_string(), vec!["mail:read".to_string()])]),
+            ]
+        );
+        assert!(spec.paths["/api/status"]["get"].security.is_empty());
+
+        // The document is served without credentials
+        let mut context = RequestContext::new("127.0.0.1".parse().unwrap());
+        let response = gateway
+            .handle(
+                &Route::default(),
+                &GatewayRequest::new("GET", OPENAPI_PATH),
+                &mut context,
+                |_| async { Ok(GatewayResponse::new(404, Vec::new())) },
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status, 200);
+        let document: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(
+            document["components"]["securitySchemes"]["bearerAuth"]["scheme"],
+            "bearer"
+        );
+        assert_eq!(
+            document["paths"]["/api/mail/{path}"]["get"]["security"][0]["apiKeyAuth"][0],
+            "mail:read"
+        );
+    }
+}
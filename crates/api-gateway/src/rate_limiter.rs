@@ -1,10 +1,235 @@
 //! Rate limiting module
 
-/// Rate limiter
-pub struct RateLimiter;
+use crate::error::{GatewayError, Result};
+use crate::gateway::GatewayRequest;
+use crate::router::Route;
+use crate::{RequestContext, RouteMatcher};
+use governor::clock::{Clock, DefaultClock};
+use governor::state::keyed::DefaultKeyedStateStore;
+use governor::{Quota, RateLimiter as KeyedLimiter};
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU32;
+use std::time::Duration;
+
+/// What requests are counted against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitKey {
+    /// Each client IP has its own limit
+    #[default]
+    ClientIp,
+    /// Each authenticated user has its own limit, anonymous requests are limited by client IP
+    User,
+    /// Each user and client IP pair has its own limit
+    UserAndClientIp,
+}
 
 /// Rate limit configuration
-pub struct RateLimitConfig;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Rules checked against every request, all matching rules apply
+    #[serde(default)]
+    pub rules: Vec<RateLimitRule>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: Vec::new(),
+        }
+    }
+}
 
 /// Rate limit rule
-pub struct RateLimitRule;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitRule {
+    /// Requests the rule applies to
+    pub matcher: RouteMatcher,
+    /// Requests allowed per period, also the size of a burst
+    pub requests: u32,
+    /// Period over which the requests are allowed
+    pub period: Duration,
+    #[serde(default)]
+    pub key: RateLimitKey,
+}
+
+/// Rate limiter
+///
+/// Each rule is a token bucket per route and client, refilled evenly over
+/// the rule's period.
+pub struct RateLimiter {
+    rules: Vec<(
+        RateLimitRule,
+        KeyedLimiter<String, DefaultKeyedStateStore<String>, DefaultClock>,
+    )>,
+    clock: DefaultClock,
+}
+
+impl RateLimiter {
+    /// Create new rate limiter
+    pub fn new(config: &RateLimitConfig) -> Result<Self> {
+        let rules = if config.enabled {
+            config
+                .rules
+                .iter()
+                .map(|rule| {
+                    let quota = NonZeroU32::new(rule.requests)
+                        .and_then(|requests| {
+                            Quota::with_period(rule.period / requests.get())
+                                .map(|quota| quota.allow_burst(requests))
+                        })
+                        .ok_or_else(|| {
+                            GatewayError::Configuration(format!(
+                                "Invalid rate limit for {}: {} requests per {:?}",
+                                rule.matcher.path, rule.requests, rule.period
+                            ))
+                        })?;
+                    Ok((rule.clone(), KeyedLimiter::keyed(quota)))
+                })
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            rules,
+            clock: DefaultClock::default(),
+        })
+    }
+
+    /// Count a request against every matching rule
+    ///
+    /// Returns `RateLimited` with the time until the request would be
+    /// allowed when any rule is exceeded.
+    pub fn check(
+        &self,
+        route: &Route,
+        request: &GatewayRequest,
+        context: &RequestContext,
+    ) -> Result<()> {
+        for (rule, limiter) in &self.rules {
+            if !rule.matcher.matches(request) {
+                continue;
+            }
+
+            let key = format!("{}|{}", route.path, client_key(rule.key, context));
+            if let Err(not_until) = limiter.check_key(&key) {
+                return Err(GatewayError::RateLimited(
+                    not_until.wait_time_from(self.clock.now()),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Forget buckets that have refilled completely
+    pub fn retain_recent(&self) {
+        for (_, limiter) in &self.rules {
+            limiter.retain_recent();
+        }
+    }
+}
+
+fn client_key(key: RateLimitKey, context: &RequestContext) -> String {
+    let user = context.auth_info.as_ref().map(|info| info.user_id.as_str());
+    match (key, user) {
+        (RateLimitKey::ClientIp, _) | (RateLimitKey::User, None) => {
+            format!("ip:{}", context.client_ip)
+        }
+        (RateLimitKey::User, Some(user)) => format!("user:{}", user),
+        (RateLimitKey::UserAndClientIp, user) => {
+            format!("user:{}|ip:{}", user.unwrap_or_default(), context.client_ip)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthMethod;
+    use crate::AuthInfo;
+    use std::collections::HashMap;
+
+    fn limiter(key: RateLimitKey) -> RateLimiter {
+        RateLimiter::new(&RateLimitConfig {
+            enabled: true,
+            rules: vec![RateLimitRule {
+                matcher: RouteMatcher {
+                    path: "/api/mail/*".to_string(),
+                    ..Default::default()
+                },
+                requests: 2,
+                period: Duration::from_millis(200),
+                key,
+            }],
+        })
+        .unwrap()
+    }
+
+    fn context(ip: &str, user: Option<&str>) -> RequestContext {
+        let mut context = RequestContext::new(ip.parse().unwrap());
+        context.auth_info = user.map(|user| AuthInfo {
+            user_id: user.to_string(),
+            roles: Vec::new(),
+            auth_method: AuthMethod::Bearer,
+            expires_at: None,
+            claims: HashMap::new(),
+        });
+        context
+    }
+
+    #[test]
+    fn test_user_key_shared_across_clients() {
+        let limiter = limiter(RateLimitKey::User);
+        let route = Route::default();
+        let request = GatewayRequest::new("GET", "/api/mail/inbox");
+
+        assert!(limiter
+            .check(&route, &request, &context("10.0.0.1", Some("alice")))
+            .is_ok());
+        assert!(limiter
+            .check(&route, &request, &context("10.0.0.2", Some("alice")))
+            .is_ok());
+        assert!(limiter
+            .check(&route, &request, &context("10.0.0.3", Some("alice")))
+            .is_err());
+        assert!(limiter
+            .check(&route, &request, &context("10.0.0.3", Some("bob")))
+            .is_ok());
+        assert!(limiter
+            .check(&route, &request, &context("10.0.0.3", None))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_unmatched_requests_unlimited() {
+        let limiter = limiter(RateLimitKey::ClientIp);
+        let route = Route::default();
+        let request = GatewayRequest::new("GET", "/api/contacts");
+        for _ in 0..10 {
+            assert!(limiter
+                .check(&route, &request, &context("10.0.0.1", None))
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_invalid_rule() {
+        let config = RateLimitConfig {
+            enabled: true,
+            rules: vec![RateLimitRule {
+                matcher: RouteMatcher::default(),
+                requests: 0,
+                period: Duration::from_secs(1),
+                key: RateLimitKey::ClientIp,
+            }],
+        };
+        assert!(matches!(
+            RateLimiter::new(&config),
+            Err(GatewayError::Configuration(_))
+        ));
+    }
+}
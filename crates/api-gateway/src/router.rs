@@ -1,5 +1,9 @@
 //! Request routing
 
+use crate::circuit_breaker::CircuitBreakerConfig;
+use crate::fallback::FallbackConfig;
+use crate::transform::{RequestTransform, ResponseTransform};
+
 /// Router
 pub struct Router;
 
@@ -8,6 +12,33 @@ pub struct Router;
 pub struct Route {
     pub path: String,
     pub method: String,
+    /// Backend service the route forwards to
+    pub backend: Option<String>,
+    /// Circuit breaker settings overriding the gateway default
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// Response served while the route's backend is degraded
+    pub fallback: Option<FallbackConfig>,
+    /// Reject requests without valid credentials
+    pub auth_required: bool,
+    /// Scopes the credentials must grant
+    pub required_scopes: Vec<String>,
+    /// Transforms applied, in order, to requests before they reach the backend
+    pub request_transforms: Vec<RequestTransform>,
+    /// Transforms applied, in order, to responses before they reach the client
+    pub response_transforms: Vec<ResponseTransform>,
+    /// Leave the route out of the OpenAPI document
+    pub internal: bool,
+}
+
+impl Route {
+    /// Name of the endpoint the route's circuit breaker tracks, one per
+    /// backend and path
+    pub fn endpoint(&self) -> String {
+        match &self.backend {
+            Some(backend) => format!("{} {}", backend, self.path),
+            None => self.path.clone(),
+        }
+    }
 }
 
 /// Route configuration
@@ -18,6 +49,14 @@ impl Default for Route {
         Self {
             path: "/api/*".to_string(),
             method: "GET".to_string(),
+            backend: None,
+            circuit_breaker: None,
+            fallback: None,
+            auth_required: false,
+            required_scopes: Vec::new(),
+            request_transforms: Vec::new(),
+            response_transforms: Vec::new(),
+            internal: false,
         }
     }
 }
@@ -1,10 +1,264 @@
 //! Request/response transformation
 
-/// Transform manager
-pub struct TransformManager;
+use crate::gateway::{GatewayRequest, GatewayResponse};
+use crate::router::Route;
+use crate::RequestContext;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Transforms applied to every route
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransformConfig {
+    #[serde(default)]
+    pub request: Vec<RequestTransform>,
+    #[serde(default)]
+    pub response: Vec<ResponseTransform>,
+}
 
 /// Request transform
-pub struct RequestTransform;
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RequestTransform {
+    /// Set a header, replacing any existing value
+    SetHeader { name: String, value: String },
+    /// Remove a header
+    RemoveHeader { name: String },
+    /// Rename a header, keeping its value
+    RenameHeader { from: String, to: String },
+    /// Replace the leading path segments matching `from` with `to`
+    RewritePathPrefix { from: String, to: String },
+    /// Set a header to the request ID unless the client sent one
+    CorrelationId { header: String },
+}
 
 /// Response transform
-pub struct ResponseTransform;
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseTransform {
+    /// Set a header, replacing any existing value
+    SetHeader { name: String, value: String },
+    /// Remove a header, such as one exposing backend details
+    RemoveHeader { name: String },
+    /// Rename a header, keeping its value
+    RenameHeader { from: String, to: String },
+    /// Replace every occurrence of `find` in a UTF-8 body with `replace`
+    RewriteBody { find: String, replace: String },
+}
+
+impl RequestTransform {
+    /// Apply the transform to a request
+    pub fn apply(&self, request: &mut GatewayRequest, context: &RequestContext) {
+        match self {
+            RequestTransform::SetHeader { name, value } => {
+                set_header(&mut request.headers, name, value.clone())
+            }
+            RequestTransform::RemoveHeader { name } => {
+                remove_header(&mut request.headers, name);
+            }
+            RequestTransform::RenameHeader { from, to } => {
+                rename_header(&mut request.headers, from, to)
+            }
+            RequestTransform::RewritePathPrefix { from, to } => {
+                if let Some(path) = rewrite_path_prefix(&request.path, from, to) {
+                    request.path = path;
+                }
+            }
+            RequestTransform::CorrelationId { header } => {
+                if request.header(header).is_none() {
+                    set_header(
+                        &mut request.headers,
+                        header,
+                        context.request_id.to_string(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl ResponseTransform {
+    /// Apply the transform to a response
+    pub fn apply(&self, response: &mut GatewayResponse) {
+        match self {
+            ResponseTransform::SetHeader { name, value } => {
+                set_header(&mut response.headers, name, value.clone())
+            }
+            ResponseTransform::RemoveHeader { name } => {
+                remove_header(&mut response.headers, name);
+            }
+            ResponseTransform::RenameHeader { from, to } => {
+                rename_header(&mut response.headers, from, to)
+            }
+            ResponseTransform::RewriteBody { find, replace } => {
+                if find.is_empty() {
+                    return;
+                }
+                if let Ok(body) = std::str::from_utf8(&response.body) {
+                    if body.contains(find.as_str()) {
+                        response.body = body.replace(find.as_str(), replace).into_bytes();
+                        // The original length no longer applies
+                        remove_header(&mut response.headers, "Content-Length");
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Transform manager
+///
+/// Requests pass through the gateway-wide transforms and then the route's,
+/// responses through the route's and then the gateway-wide ones, each list
+/// in order, so the outermost transforms see what the client sees.
+#[derive(Debug, Clone, Default)]
+pub struct TransformManager {
+    config: TransformConfig,
+}
+
+impl TransformManager {
+    /// Create new transform manager
+    pub fn new(config: TransformConfig) -> Self {
+        Self { config }
+    }
+
+    /// Transform a client request into the request sent to the backend
+    pub fn transform_request(
+        &self,
+        route: &Route,
+        mut request: GatewayRequest,
+        context: &RequestContext,
+    ) -> GatewayRequest {
+        for transform in self.config.request.iter().chain(&route.request_transforms) {
+            transform.apply(&mut request, context);
+        }
+        request
+    }
+
+    /// Transform a backend response into the response sent to the client
+    pub fn transform_response(&self, route: &Route, response: &mut GatewayResponse) {
+        for transform in route.response_transforms.iter().chain(&self.config.response) {
+            transform.apply(response);
+        }
+    }
+}
+
+fn set_header(headers: &mut HashMap<String, String>, name: &str, value: String) {
+    remove_header(headers, name);
+    headers.insert(name.to_string(), value);
+}
+
+fn remove_header(headers: &mut HashMap<String, String>, name: &str) -> Option<String> {
+    let key = headers
+        .keys()
+        .find(|key| key.eq_ignore_ascii_case(name))?
+        .clone();
+    headers.remove(&key)
+}
+
+fn rename_header(headers: &mut HashMap<String, String>, from: &str, to: &str) {
+    if let Some(value) = remove_header(headers, from) {
+        set_header(headers, to, value);
+    }
+}
+
+/// Rewrite a path starting with `from` at a segment boundary, so a prefix
+/// of `/v1` matches `/v1/mail` but not `/v10/mail`
+fn rewrite_path_prefix(path: &str, from: &str, to: &str) -> Option<String> {
+    let rest = path.strip_prefix(from)?;
+    if from.ends_with('/') || rest.is_empty() || rest.starts_with(['/', '?']) {
+        Some(format!("{}{}", to, rest))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> RequestContext {
+        RequestContext::new("127.0.0.1".parse().unwrap())
+    }
+
+    #[test]
+    fn test_path_prefix_boundaries() {
+        assert_eq!(
+            rewrite_path_prefix("/api/v1/mail?page=2", "/api/v1", "/v2"),
+            Some("/v2/mail?page=2".to_string())
+        );
+        assert_eq!(
+            rewrite_path_prefix("/api/v1", "/api/v1", "/v2"),
+            Some("/v2".to_string())
+        );
+        assert_eq!(
+            rewrite_path_prefix("/api/mail", "/api/", "/"),
+            Some("/mail".to_string())
+        );
+        assert_eq!(rewrite_path_prefix("/api/v10/mail", "/api/v1", "/v2"), None);
+        assert_eq!(rewrite_path_prefix("/other", "/api", "/"), None);
+    }
+
+    #[test]
+    fn test_request_transforms_run_in_order() {
+        let manager = TransformManager::new(TransformConfig {
+            request: vec![
+                RequestTransform::CorrelationId {
+                    header: "X-Correlation-ID".to_string(),
+                },
+                RequestTransform::RenameHeader {
+                    from: "X-Client".to_string(),
+                    to: "X-Forwarded-Client".to_string(),
+                },
+            ],
+            response: Vec::new(),
+        });
+        // Route transforms see the output of the gateway-wide ones
+        let route = Route {
+            request_transforms: vec![
+                RequestTransform::SetHeader {
+                    name: "x-forwarded-client".to_string(),
+                    value: "gateway".to_string(),
+                },
+                RequestTransform::RemoveHeader {
+                    name: "Cookie".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+        let context = context();
+        let request = GatewayRequest::new("GET", "/api/mail")
+            .with_header("x-client", "mobile")
+            .with_header("cookie", "session=1");
+
+        let request = manager.transform_request(&route, request, &context);
+        assert_eq!(
+            request.header("X-Correlation-ID"),
+            Some(context.request_id.to_string().as_str())
+        );
+        assert_eq!(request.header("X-Forwarded-Client"), Some("gateway"));
+        assert_eq!(request.header("X-Client"), None);
+        assert_eq!(request.header("Cookie"), None);
+        assert_eq!(request.headers.len(), 2);
+
+        // An existing correlation ID is kept
+        let request = GatewayRequest::new("GET", "/").with_header("x-correlation-id", "abc");
+        let request = manager.transform_request(&Route::default(), request, &context);
+        assert_eq!(request.header("X-Correlation-ID"), Some("abc"));
+    }
+
+    #[test]
+    fn test_response_body_rewrite() {
+        let mut response = GatewayResponse::new(200, b"see http://backend:8080/mail".to_vec());
+        response
+            .headers
+            .insert("Content-Length".to_string(), "28".to_string());
+
+        ResponseTransform::RewriteBody {
+            find: "http://backend:8080".to_string(),
+            replace: "https://mail.example.com".to_string(),
+        }
+        .apply(&mut response);
+        assert_eq!(response.body, b"see https://mail.example.com/mail");
+        assert_eq!(response.header("Content-Length"), None);
+    }
+}
@@ -9,12 +9,13 @@
 use crate::{
     storage::StorageBackend,
     compression::{CompressionConfig, CompressionType},
-    encryption::{EncryptionConfig, EncryptionType},
+    encryption::{self, EncryptionConfig, EncryptionType, KeyProvider},
     error::{BackupError, Result},
+    retention::{CleanupReport, RetentionPolicy},
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::{BTreeMap, HashSet}, path::{Path, PathBuf}, sync::Arc};
 use tracing::{debug, info, warn};
 
 /// Backup manager responsible for creating and managing backups
@@ -22,15 +23,21 @@ use tracing::{debug, info, warn};
 pub struct BackupManager {
     storage: Arc<dyn StorageBackend>,
     config: BackupConfig,
+    key_provider: Option<Arc<dyn KeyProvider>>,
 }
 
 /// Backup configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupConfig {
+    /// Directory whose contents are backed up
+    pub source_dir: PathBuf,
     pub compression: CompressionConfig,
     pub encryption: EncryptionConfig,
     pub retention_days: u32,
     pub max_backups: usize,
+    /// Backups kept by `cleanup_old_backups`
+    #[serde(default)]
+    pub retention: RetentionPolicy,
     pub chunk_size: usize,
     pub verify_integrity: bool,
 }
@@ -68,9 +75,53 @@ pub struct BackupInfo {
     pub file_count: usize,
     pub checksum: String,
     pub description: Option<String>,
+    /// Backup this one was diffed against, `None` for full backups
+    pub parent_id: Option<String>,
     pub metadata: BackupMetadata,
 }
 
+/// Manifest stored alongside every backup
+///
+/// The manifest describes the complete state of the source at backup time.
+/// Objects that did not change since the parent backup are not stored again;
+/// their entry points at the backup that holds their content instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub info: BackupInfo,
+    pub objects: BTreeMap<String, ObjectEntry>,
+}
+
+impl BackupManifest {
+    /// Load and parse the manifest of a backup
+    pub async fn load(storage: &dyn StorageBackend, backup_id: &str) -> Result<Self> {
+        let manifest = storage.load_manifest(backup_id).await?;
+        serde_json::from_str(&manifest)
+            .map_err(|e| BackupError::InvalidFormat(format!("manifest of {}: {}", backup_id, e)))
+    }
+}
+
+/// A single backed up object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectEntry {
+    pub size: u64,
+    pub modified: DateTime<Utc>,
+    /// Hex-encoded SHA-256 of the object content
+    pub sha256: String,
+    /// Backup whose data blob contains the object content
+    pub stored_in: String,
+    /// Offset of the object content within that data blob
+    pub offset: u64,
+}
+
+impl ObjectEntry {
+    /// Content of the object within the data blob of the backup holding it
+    pub fn content<'a>(&self, data: &'a [u8]) -> Option<&'a [u8]> {
+        let offset = usize::try_from(self.offset).ok()?;
+        let size = usize::try_from(self.size).ok()?;
+        data.get(offset..offset.checked_add(size)?)
+    }
+}
+
 /// Metadata associated with a backup
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupMetadata {
@@ -78,10 +129,34 @@ pub struct BackupMetadata {
     pub hostname: String,
     pub compression_type: CompressionType,
     pub encryption_type: EncryptionType,
+    /// ID of the key the backup data was encrypted with
+    #[serde(default)]
+    pub encryption_key_id: Option<String>,
     pub chunk_count: usize,
     pub manifest_checksum: String,
 }
 
+/// Outcome of validating a backup
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub backup_id: String,
+    pub objects_checked: usize,
+    /// Objects whose content no longer matches the SHA-256 in the manifest
+    pub corrupted_objects: Vec<String>,
+    /// Objects whose content cannot be found in the backup holding it
+    pub missing_objects: Vec<String>,
+    /// Gaps in the chain of parent backups, only checked in deep mode
+    pub chain_errors: Vec<String>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.corrupted_objects.is_empty()
+            && self.missing_objects.is_empty()
+            && self.chain_errors.is_empty()
+    }
+}
+
 /// Result of a backup operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupResult {
@@ -103,23 +178,58 @@ impl BackupManager {
         Ok(Self {
             storage,
             config: config.clone(),
+            key_provider: None,
         })
     }
 
+    /// Use `key_provider` to obtain the keys backups are encrypted with
+    pub fn with_key_provider(mut self, key_provider: Arc<dyn KeyProvider>) -> Self {
+        self.key_provider = Some(key_provider);
+        self
+    }
+
     /// Perform a backup operation
     pub async fn backup(&self, options: BackupOptions) -> Result<BackupResult> {
+        self.backup_at(options, Utc::now()).await
+    }
+
+    /// Perform a backup operation, recording `created_at` as its creation time
+    pub(crate) async fn backup_at(
+        &self,
+        mut options: BackupOptions,
+        created_at: DateTime<Utc>,
+    ) -> Result<BackupResult> {
         let start_time = std::time::Instant::now();
         info!("Starting backup operation: {:?}", options.backup_type);
 
-        // Generate backup ID
-        let backup_id = self.generate_backup_id(&options).await?;
+        // Find the backup to diff against
+        let parent = match options.backup_type {
+            BackupType::Full => None,
+            BackupType::Incremental | BackupType::Differential => {
+                let parent = self.find_parent(options.backup_type).await?;
+                if parent.is_none() {
+                    info!(
+                        "No previous backup found, performing a full backup instead of {:?}",
+                        options.backup_type
+                    );
+                    options.backup_type = BackupType::Full;
+                }
+                parent
+            }
+        };
 
-        // Create backup manifest
-        let manifest = self.create_backup_manifest(&backup_id, &options).await?;
+        // Generate backup ID
+        let backup_id = self.generate_backup_id(&options, created_at).await?;
 
         // Perform the actual backup
-        let backup_data = self.collect_backup_data(&options).await?;
+        let (objects, backup_data) = self
+            .collect_backup_data(&backup_id, &options, parent.as_ref())
+            .await?;
         let original_size = backup_data.len() as u64;
+        let files_processed = objects
+            .values()
+            .filter(|entry| entry.stored_in == backup_id)
+            .count();
 
         // Compress if configured
         let compressed_data = if let Some(compression) = &options.compression {
@@ -129,50 +239,89 @@ impl BackupManager {
         };
 
         // Encrypt if configured
-        let final_data = if let Some(encryption) = &options.encryption {
-            self.encrypt_data(&compressed_data, *encryption).await?
-        } else {
-            compressed_data
+        let (final_data, encryption_key_id) = match options.encryption {
+            Some(encryption) if encryption != EncryptionType::None => {
+                let (key_id, data) = self
+                    .encrypt_data(&backup_id, &compressed_data, encryption)
+                    .await?;
+                (data, Some(key_id))
+            }
+            _ => (compressed_data, None),
         };
 
         // Store backup
         self.storage.store_backup(&backup_id, &final_data).await?;
 
-        // Store manifest
-        self.storage.store_manifest(&backup_id, &manifest).await?;
-
         // Create backup info
         let backup_info = BackupInfo {
-            id: backup_id,
+            id: backup_id.clone(),
             backup_type: options.backup_type,
-            created_at: Utc::now(),
+            created_at,
             size_bytes: original_size,
             compressed_size_bytes: final_data.len() as u64,
-            file_count: 0, // TODO: Calculate actual file count
+            file_count: objects.len(),
             checksum: self.calculate_checksum(&final_data).await?,
             description: options.description,
+            parent_id: parent.map(|parent| parent.info.id),
             metadata: BackupMetadata {
                 version: env!("CARGO_PKG_VERSION").to_string(),
                 hostname: gethostname::gethostname().to_string_lossy().to_string(),
                 compression_type: options.compression.unwrap_or(CompressionType::None),
                 encryption_type: options.encryption.unwrap_or(EncryptionType::None),
+                encryption_key_id,
                 chunk_count: 1, // TODO: Implement chunking
-                manifest_checksum: "".to_string(), // TODO: Calculate manifest checksum
+                manifest_checksum: self
+                    .calculate_checksum(&serde_json::to_vec(&objects)?)
+                    .await?,
             },
         };
 
+        // Store manifest
+        let manifest = BackupManifest {
+            info: backup_info.clone(),
+            objects,
+        };
+        self.storage
+            .store_manifest(&backup_id, &serde_json::to_string(&manifest)?)
+            .await?;
+
         let duration = start_time.elapsed();
-        info!("Backup completed in {:.2}s", duration.as_secs_f64());
+        info!("Backup {} completed in {:.2}s", backup_id, duration.as_secs_f64());
 
         Ok(BackupResult {
             backup_info,
             duration_seconds: duration.as_secs_f64(),
             bytes_processed: original_size,
-            files_processed: 0, // TODO: Track files processed
+            files_processed,
             warnings: Vec::new(),
         })
     }
 
+    /// Load and parse the manifest of a backup
+    pub async fn load_manifest(&self, backup_id: &str) -> Result<BackupManifest> {
+        BackupManifest::load(self.storage.as_ref(), backup_id).await
+    }
+
+    /// Find the manifest an incremental or differential backup is based on
+    ///
+    /// Incremental backups build on the most recent backup of any kind, while
+    /// differential backups always build on the most recent full backup.
+    async fn find_parent(&self, backup_type: BackupType) -> Result<Option<BackupManifest>> {
+        let parent = self
+            .list_backups()
+            .await?
+            .into_iter()
+            .filter(|info| {
+                backup_type == BackupType::Incremental || info.backup_type == BackupType::Full
+            })
+            .max_by_key(|info| info.created_at);
+
+        match parent {
+            Some(info) => self.load_manifest(&info.id).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
     /// List all available backups
     pub async fn list_backups(&self) -> Result<Vec<BackupInfo>> {
         debug!("Listing available backups");
@@ -180,38 +329,145 @@ impl BackupManager {
     }
 
     /// Validate backup integrity
-    pub async fn validate_backup(&self, backup_id: &str) -> Result<bool> {
-        info!("Validating backup: {}", backup_id);
-
-        // Load backup data
-        let backup_data = self.storage.load_backup(backup_id).await?;
+    ///
+    /// Recomputes the SHA-256 of every object stored in the backup. In `deep`
+    /// mode the chain of parent backups is also checked back to its full
+    /// backup, together with the objects the backup references from it.
+    pub async fn validate_backup(&self, backup_id: &str, deep: bool) -> Result<ValidationReport> {
+        info!("Validating backup: {} (deep: {})", backup_id, deep);
+
+        let manifest = self.load_manifest(backup_id).await?;
+        let mut report = ValidationReport {
+            backup_id: backup_id.to_string(),
+            ..Default::default()
+        };
+        if deep {
+            report.chain_errors = self.check_chain(&manifest.info).await?;
+        }
 
-        // Load manifest
-        let manifest = self.storage.load_manifest(backup_id).await?;
+        // Group the objects to check by the backup whose data blob holds them
+        let mut by_blob: BTreeMap<&str, Vec<(&String, &ObjectEntry)>> = BTreeMap::new();
+        for (key, entry) in &manifest.objects {
+            if deep || entry.stored_in == backup_id {
+                by_blob
+                    .entry(entry.stored_in.as_str())
+                    .or_default()
+                    .push((key, entry));
+            }
+        }
 
-        // Verify checksum
-        let calculated_checksum = self.calculate_checksum(&backup_data).await?;
+        for (blob_id, objects) in by_blob {
+            report.objects_checked += objects.len();
+            let keys = || objects.iter().map(|(key, _)| key.to_string());
+
+            if !self.storage.backup_exists(blob_id).await? {
+                warn!("Data of backup {} is missing", blob_id);
+                report.missing_objects.extend(keys());
+                continue;
+            }
+            let info = if blob_id == backup_id {
+                manifest.info.clone()
+            } else {
+                self.load_manifest(blob_id).await?.info
+            };
+            let data = self.storage.load_backup(blob_id).await?;
+            let data =
+                match encryption::decrypt_backup(self.key_provider.as_deref(), &info, data).await {
+                    Ok(data) => data,
+                    // Authentication failed, the encrypted blob was altered
+                    Err(BackupError::EncryptionError(e)) => {
+                        warn!("Data of backup {} is unreadable: {}", blob_id, e);
+                        report.corrupted_objects.extend(keys());
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+
+            for (key, entry) in objects {
+                match entry.content(&data) {
+                    Some(content) if self.calculate_checksum(content).await? == entry.sha256 => {}
+                    Some(_) => report.corrupted_objects.push(key.clone()),
+                    None => report.missing_objects.push(key.clone()),
+                }
+            }
+        }
 
-        // TODO: Compare with stored checksum
+        if !report.is_valid() {
+            warn!(
+                "Backup {} failed validation: {} corrupted, {} missing, {} chain errors",
+                backup_id,
+                report.corrupted_objects.len(),
+                report.missing_objects.len(),
+                report.chain_errors.len()
+            );
+        }
+        Ok(report)
+    }
 
-        Ok(true) // Placeholder
+    /// Follow parent references from `info` back to a full backup, describing any gap
+    async fn check_chain(&self, info: &BackupInfo) -> Result<Vec<String>> {
+        let backups = self.list_backups().await?;
+        let mut seen = HashSet::new();
+        let mut current = info;
+
+        loop {
+            if !seen.insert(current.id.as_str()) {
+                return Ok(vec![format!(
+                    "backup chain of {} contains a cycle at {}",
+                    info.id, current.id
+                )]);
+            }
+            let Some(parent_id) = &current.parent_id else {
+                if current.backup_type != BackupType::Full {
+                    return Ok(vec![format!(
+                        "backup chain of {} ends at {} which is not a full backup",
+                        info.id, current.id
+                    )]);
+                }
+                return Ok(Vec::new());
+            };
+            match backups.iter().find(|backup| &backup.id == parent_id) {
+                Some(parent) => current = parent,
+                None => {
+                    return Ok(vec![format!(
+                        "backup {} depends on missing backup {}",
+                        current.id, parent_id
+                    )])
+                }
+            }
+        }
     }
 
     /// Clean up old backups according to retention policy
-    pub async fn cleanup_old_backups(&self) -> Result<usize> {
+    ///
+    /// Backups are deleted newest first so that an interrupted cleanup never
+    /// leaves a retained incremental without its parent.
+    pub async fn cleanup_old_backups(&self) -> Result<CleanupReport> {
         info!("Cleaning up old backups");
 
         let backups = self.list_backups().await?;
-        let mut deleted_count = 0;
+        let report = self.config.retention.apply(&backups);
 
-        // TODO: Implement retention policy logic
+        for backup_id in report.deleted.iter().rev() {
+            debug!("Deleting backup {} per retention policy", backup_id);
+            self.storage.delete_backup(backup_id).await?;
+        }
 
-        Ok(deleted_count)
+        info!(
+            "Retention cleanup kept {} backups and deleted {}",
+            report.kept.len(),
+            report.deleted.len()
+        );
+        Ok(report)
     }
 
     /// Generate a unique backup ID
-    async fn generate_backup_id(&self, options: &BackupOptions) -> Result<String> {
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+    async fn generate_backup_id(
+        &self,
+        options: &BackupOptions,
+        created_at: DateTime<Utc>,
+    ) -> Result<String> {
+        let timestamp = created_at.format("%Y%m%d_%H%M%S_%6f");
         let backup_type = match options.backup_type {
             BackupType::Full => "full",
             BackupType::Incremental => "incr",
@@ -221,20 +477,67 @@ impl BackupManager {
         Ok(format!("{}_{}", backup_type, timestamp))
     }
 
-    /// Create backup manifest
-    async fn create_backup_manifest(
+    /// Collect data to be backed up
+    ///
+    /// Returns the manifest entries for every object in the source together
+    /// with the data blob holding the objects that changed since `parent`.
+    async fn collect_backup_data(
         &self,
         backup_id: &str,
         options: &BackupOptions,
-    ) -> Result<String> {
-        // TODO: Implement manifest creation
-        Ok(format!("manifest_{}", backup_id))
-    }
+        parent: Option<&BackupManifest>,
+    ) -> Result<(BTreeMap<String, ObjectEntry>, Vec<u8>)> {
+        let source_dir = &self.config.source_dir;
+        let mut objects = BTreeMap::new();
+        let mut data = Vec::new();
+
+        for entry in walkdir::WalkDir::new(source_dir).sort_by_file_name() {
+            let entry = entry.map_err(|e| BackupError::IoError(e.into()))?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let key = object_key(source_dir, entry.path());
+            if !is_included(&key, &options.include_patterns, &options.exclude_patterns) {
+                continue;
+            }
+
+            let metadata = entry.metadata().map_err(|e| BackupError::IoError(e.into()))?;
+            let size = metadata.len();
+            let modified = DateTime::<Utc>::from(metadata.modified()?);
+
+            // Unchanged size and modification time: keep referencing the parent copy
+            let previous = parent.and_then(|parent| parent.objects.get(&key));
+            if let Some(previous) = previous {
+                if previous.size == size && previous.modified == modified {
+                    objects.insert(key, previous.clone());
+                    continue;
+                }
+            }
+
+            let content = tokio::fs::read(entry.path()).await?;
+            let sha256 = self.calculate_checksum(&content).await?;
+            let object = match previous {
+                Some(previous) if previous.sha256 == sha256 => ObjectEntry {
+                    modified,
+                    ..previous.clone()
+                },
+                _ => {
+                    debug!("Storing changed object {}", key);
+                    let offset = data.len() as u64;
+                    data.extend_from_slice(&content);
+                    ObjectEntry {
+                        size: content.len() as u64,
+                        modified,
+                        sha256,
+                        stored_in: backup_id.to_string(),
+                        offset,
+                    }
+                }
+            };
+            objects.insert(key, object);
+        }
 
-    /// Collect data to be backed up
-    async fn collect_backup_data(&self, options: &BackupOptions) -> Result<Vec<u8>> {
-        // TODO: Implement data collection based on backup type and patterns
-        Ok(Vec::new())
+        Ok((objects, data))
     }
 
     /// Compress backup data
@@ -247,14 +550,29 @@ impl BackupManager {
         Ok(data.to_vec())
     }
 
-    /// Encrypt backup data
+    /// Encrypt backup data with the current managed key
+    ///
+    /// Returns the ID of the key used together with the encrypted data. The
+    /// backup and key IDs are authenticated along with the data, so a blob
+    /// cannot be passed off as another backup or as sealed with another key.
     async fn encrypt_data(
         &self,
+        backup_id: &str,
         data: &[u8],
         encryption_type: EncryptionType,
-    ) -> Result<Vec<u8>> {
-        // TODO: Implement encryption
-        Ok(data.to_vec())
+    ) -> Result<(String, Vec<u8>)> {
+        let key_provider = self.key_provider.as_ref().ok_or_else(|| {
+            BackupError::ConfigError(format!(
+                "{:?} encryption requested but no key provider is configured",
+                encryption_type
+            ))
+        })?;
+        let (key_id, key) = key_provider.current_key().await?;
+        debug!("Encrypting backup data with key {}", key_id);
+
+        let associated_data = encryption::associated_data(backup_id, &key_id);
+        let data = encryption::encrypt(encryption_type, &key, &associated_data, data)?;
+        Ok((key_id, data))
     }
 
     /// Calculate checksum of data
@@ -266,13 +584,62 @@ impl BackupManager {
     }
 }
 
+/// Manifest key of a file: its path relative to the source, `/` separated
+fn object_key(source_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(source_dir)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether `key` matches any include pattern (or there are none) and no exclude pattern
+pub(crate) fn is_included(key: &str, include: &[String], exclude: &[String]) -> bool {
+    (include.is_empty() || include.iter().any(|pattern| wildcard_match(pattern, key)))
+        && !exclude.iter().any(|pattern| wildcard_match(pattern, key))
+}
+
+/// Match `text` against a pattern where `*` matches any run of characters and `?` a single one
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let text = text.as_bytes();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
 impl Default for BackupConfig {
     fn default() -> Self {
         Self {
+            source_dir: "/var/lib/stalwart/data".into(),
             compression: CompressionConfig::default(),
             encryption: EncryptionConfig::default(),
             retention_days: 30,
             max_backups: 100,
+            retention: RetentionPolicy::default(),
             chunk_size: 64 * 1024 * 1024, // 64MB
             verify_integrity: true,
         }
@@ -286,8 +653,224 @@ impl Default for BackupOptions {
             include_patterns: vec!["*".to_string()],
             exclude_patterns: Vec::new(),
             compression: Some(CompressionType::Zstd),
-            encryption: Some(EncryptionType::Aes256Gcm),
+            encryption: None,
             description: None,
         }
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{LocalStorageBackend, LocalStorageConfig};
+    use chrono::{Datelike, NaiveDate};
+    use tempfile::TempDir;
+
+    async fn manager(source: &Path, storage: &Path) -> BackupManager {
+        let storage = LocalStorageBackend::new(&LocalStorageConfig {
+            path: storage.to_path_buf(),
+            create_directories: true,
+        })
+        .await
+        .unwrap();
+        let config = BackupConfig {
+            source_dir: source.to_path_buf(),
+            ..BackupConfig::default()
+        };
+
+        BackupManager::new(Arc::new(storage), &config).await.unwrap()
+    }
+
+    fn options(backup_type: BackupType) -> BackupOptions {
+        BackupOptions {
+            backup_type,
+            ..BackupOptions::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_incremental_stores_only_changed_objects() {
+        let source = TempDir::new().unwrap();
+        let storage = TempDir::new().unwrap();
+        std::fs::create_dir(source.path().join("mail")).unwrap();
+        std::fs::write(source.path().join("mail/1.eml"), b"first message").unwrap();
+        std::fs::write(source.path().join("mail/2.eml"), b"second message").unwrap();
+        std::fs::write(source.path().join("index.db"), b"index v1").unwrap();
+        let manager = manager(source.path(), storage.path()).await;
+
+        let full = manager.backup(options(BackupType::Full)).await.unwrap();
+        assert_eq!(full.files_processed, 3);
+        assert_eq!(full.backup_info.parent_id, None);
+
+        std::fs::write(source.path().join("index.db"), b"index v2 with more entries").unwrap();
+        let incr = manager.backup(options(BackupType::Incremental)).await.unwrap();
+        let incr_id = incr.backup_info.id.clone();
+        assert_eq!(incr.backup_info.backup_type, BackupType::Incremental);
+        assert_eq!(incr.backup_info.parent_id.as_deref(), Some(full.backup_info.id.as_str()));
+        assert_eq!(incr.files_processed, 1);
+        assert_eq!(incr.bytes_processed, b"index v2 with more entries".len() as u64);
+
+        let manifest = manager.load_manifest(&incr_id).await.unwrap();
+        assert_eq!(manifest.objects.len(), 3);
+        let stored = manifest
+            .objects
+            .iter()
+            .filter(|(_, entry)| entry.stored_in == incr_id)
+            .map(|(key, _)| key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(stored, vec!["index.db"]);
+        assert_eq!(manifest.objects["mail/1.eml"].stored_in, full.backup_info.id);
+    }
+
+    #[tokio::test]
+    async fn test_first_incremental_falls_back_to_full() {
+        let source = TempDir::new().unwrap();
+        let storage = TempDir::new().unwrap();
+        std::fs::write(source.path().join("a.eml"), b"a").unwrap();
+        let manager = manager(source.path(), storage.path()).await;
+
+        let result = manager.backup(options(BackupType::Incremental)).await.unwrap();
+        assert_eq!(result.backup_info.backup_type, BackupType::Full);
+        assert_eq!(result.backup_info.parent_id, None);
+        assert!(result.backup_info.id.starts_with("full_"));
+        assert_eq!(result.files_processed, 1);
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*", "mail/1.eml"));
+        assert!(wildcard_match("mail/*.eml", "mail/1.eml"));
+        assert!(wildcard_match("*.db", "index.db"));
+        assert!(wildcard_match("?.eml", "a.eml"));
+        assert!(!wildcard_match("*.db", "mail/1.eml"));
+        assert!(!wildcard_match("?.eml", "ab.eml"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_clean_backup() {
+        let source = TempDir::new().unwrap();
+        let storage = TempDir::new().unwrap();
+        std::fs::write(source.path().join("a.eml"), b"first message").unwrap();
+        std::fs::write(source.path().join("b.eml"), b"second message").unwrap();
+        let manager = manager(source.path(), storage.path()).await;
+
+        let full = manager.backup(options(BackupType::Full)).await.unwrap();
+        let report = manager.validate_backup(&full.backup_info.id, true).await.unwrap();
+        assert!(report.is_valid(), "{:?}", report);
+        assert_eq!(report.objects_checked, 2);
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_corrupted_object() {
+        let source = TempDir::new().unwrap();
+        let storage = TempDir::new().unwrap();
+        std::fs::write(source.path().join("a.eml"), b"first message").unwrap();
+        std::fs::write(source.path().join("b.eml"), b"second message").unwrap();
+        let manager = manager(source.path(), storage.path()).await;
+
+        let full = manager.backup(options(BackupType::Full)).await.unwrap();
+        let manifest = manager.load_manifest(&full.backup_info.id).await.unwrap();
+        let blob = storage.path().join(format!("{}.backup", full.backup_info.id));
+        let mut data = std::fs::read(&blob).unwrap();
+        data[manifest.objects["b.eml"].offset as usize] ^= 0xff;
+        std::fs::write(&blob, data).unwrap();
+
+        let report = manager.validate_backup(&full.backup_info.id, false).await.unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(report.corrupted_objects, vec!["b.eml".to_string()]);
+        assert!(report.missing_objects.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deep_validation_detects_broken_chain() {
+        let source = TempDir::new().unwrap();
+        let storage = TempDir::new().unwrap();
+        std::fs::write(source.path().join("a.eml"), b"first message").unwrap();
+        let manager = manager(source.path(), storage.path()).await;
+
+        let full = manager.backup(options(BackupType::Full)).await.unwrap();
+        std::fs::write(source.path().join("b.eml"), b"second message").unwrap();
+        let incr = manager.backup(options(BackupType::Incremental)).await.unwrap();
+        let incr_id = incr.backup_info.id;
+        manager
+            .storage
+            .delete_backup(&full.backup_info.id)
+            .await
+            .unwrap();
+
+        // The objects stored in the incremental itself are still intact
+        let report = manager.validate_backup(&incr_id, false).await.unwrap();
+        assert!(report.is_valid(), "{:?}", report);
+
+        let report = manager.validate_backup(&incr_id, true).await.unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(report.chain_errors.len(), 1);
+        assert!(report.chain_errors[0].contains(&full.backup_info.id));
+        assert_eq!(report.missing_objects, vec!["a.eml".to_string()]);
+        assert_eq!(report.objects_checked, 2);
+    }
+
+    #[tokio::test]
+    async fn test_gfs_cleanup_keeps_representatives_and_their_chains() {
+        let source = TempDir::new().unwrap();
+        let storage = TempDir::new().unwrap();
+        let storage_backend = LocalStorageBackend::new(&LocalStorageConfig {
+            path: storage.path().to_path_buf(),
+            create_directories: true,
+        })
+        .await
+        .unwrap();
+        let config = BackupConfig {
+            source_dir: source.path().to_path_buf(),
+            retention: RetentionPolicy {
+                daily: 3,
+                weekly: 2,
+                monthly: 3,
+            },
+            ..BackupConfig::default()
+        };
+        let manager = BackupManager::new(Arc::new(storage_backend), &config)
+            .await
+            .unwrap();
+
+        // Daily backups from Monday 2026-07-27 to Sunday 2026-09-13, full on Mondays
+        let first_day = NaiveDate::from_ymd_opt(2026, 7, 27).unwrap();
+        let mut ids = BTreeMap::new();
+        for day in first_day.iter_days().take(49) {
+            std::fs::write(source.path().join("state.db"), day.to_string()).unwrap();
+            let backup_type = if day.weekday() == chrono::Weekday::Mon {
+                BackupType::Full
+            } else {
+                BackupType::Incremental
+            };
+            let created_at = day.and_hms_opt(2, 0, 0).unwrap().and_utc();
+            let result = manager
+                .backup_at(options(backup_type), created_at)
+                .await
+                .unwrap();
+            ids.insert(day, result.backup_info.id);
+        }
+
+        let report = manager.cleanup_old_backups().await.unwrap();
+
+        // Daily: Sep 11-13, weekly: Sep 13 and Sep 6, monthly: Sep 13, Aug 31 and Jul 31,
+        // plus the chains back to the fulls of Jul 27, Aug 31 and Sep 7
+        let date = |month, day| NaiveDate::from_ymd_opt(2026, month, day).unwrap();
+        let expected = ids
+            .iter()
+            .filter(|(day, _)| **day <= date(7, 31) || **day >= date(8, 31))
+            .map(|(_, id)| id.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(report.kept, expected);
+        assert_eq!(report.deleted.len(), 30);
+        assert!(report.deleted.contains(&ids[&date(8, 24)]));
+
+        let remaining = manager.list_backups().await.unwrap();
+        assert_eq!(remaining.len(), report.kept.len());
+        for backup_id in &report.kept {
+            let validation = manager.validate_backup(backup_id, true).await.unwrap();
+            assert!(validation.is_valid(), "{:?}", validation);
+        }
+    }
+}
@@ -25,6 +25,9 @@ pub enum BackupError {
     
     #[error("Encryption error: {0}")]
     EncryptionError(String),
+
+    #[error("Encryption key {key_id} unavailable: {reason}")]
+    KeyUnavailable { key_id: String, reason: String },
     
     #[error("Storage error: {0}")]
     StorageError(String),
@@ -65,6 +68,9 @@ pub enum RestoreError {
     
     #[error("Partial restore failure: {successful} successful, {failed} failed")]
     PartialFailure { successful: usize, failed: usize },
+
+    #[error("No backup available at or before {0}")]
+    NoBackupForPoint(chrono::DateTime<chrono::Utc>),
 }
 
 impl From<RestoreError> for BackupError {
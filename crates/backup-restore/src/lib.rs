@@ -18,6 +18,7 @@
 
 pub mod backup;
 pub mod restore;
+pub mod retention;
 pub mod storage;
 pub mod compression;
 pub mod encryption;
@@ -26,11 +27,12 @@ pub mod config;
 pub mod error;
 pub mod metrics;
 
-pub use backup::{BackupManager, BackupOptions, BackupType};
-pub use restore::{RestoreManager, RestoreOptions, RestorePoint};
+pub use backup::{BackupManager, BackupManifest, BackupOptions, BackupType, ValidationReport};
+pub use restore::{DryRunReport, RestoreAction, RestoreManager, RestoreOptions, RestorePoint};
+pub use retention::{CleanupReport, RetentionPolicy};
 pub use storage::{StorageBackend, StorageConfig};
 pub use compression::{CompressionType, CompressionConfig};
-pub use encryption::{EncryptionConfig, EncryptionType};
+pub use encryption::{EncryptionConfig, EncryptionType, KeyProvider};
 pub use scheduler::{BackupScheduler, ScheduleConfig};
 pub use config::BackupRestoreConfig;
 pub use error::{BackupError, RestoreError, Result};
@@ -57,11 +59,30 @@ struct BackupRestoreServiceInner {
 impl BackupRestoreService {
     /// Create a new backup and restore service
     pub async fn new(config: BackupRestoreConfig) -> Result<Self> {
+        Self::build(config, None).await
+    }
+
+    /// Create a new backup and restore service encrypting backups with keys from `key_provider`
+    pub async fn with_key_provider(
+        config: BackupRestoreConfig,
+        key_provider: Arc<dyn KeyProvider>,
+    ) -> Result<Self> {
+        Self::build(config, Some(key_provider)).await
+    }
+
+    async fn build(
+        config: BackupRestoreConfig,
+        key_provider: Option<Arc<dyn KeyProvider>>,
+    ) -> Result<Self> {
         info!("Initializing backup and restore service");
 
         let storage_backend = storage::create_backend(&config.storage).await?;
-        let backup_manager = BackupManager::new(storage_backend.clone(), &config.backup).await?;
-        let restore_manager = RestoreManager::new(storage_backend, &config.restore).await?;
+        let mut backup_manager = BackupManager::new(storage_backend.clone(), &config.backup).await?;
+        let mut restore_manager = RestoreManager::new(storage_backend, &config.restore).await?;
+        if let Some(key_provider) = key_provider {
+            backup_manager = backup_manager.with_key_provider(key_provider.clone());
+            restore_manager = restore_manager.with_key_provider(key_provider);
+        }
         let scheduler = BackupScheduler::new(&config.schedule).await?;
         let metrics = Arc::new(RwLock::new(metrics::BackupMetrics::new()));
 
@@ -121,10 +142,11 @@ impl BackupRestoreService {
     pub async fn restore(&self, options: RestoreOptions) -> Result<restore::RestoreResult> {
         warn!("Starting restore operation with options: {:?}", options);
 
+        let dry_run = options.dry_run;
         let result = self.inner.restore_manager.restore(options).await?;
 
         // Update metrics
-        {
+        if !dry_run {
             let mut metrics = self.inner.metrics.write().await;
             metrics.record_restore(&result);
         }
@@ -143,13 +165,13 @@ impl BackupRestoreService {
         self.inner.metrics.read().await.clone()
     }
 
-    /// Validate backup integrity
-    pub async fn validate_backup(&self, backup_id: &str) -> Result<bool> {
-        self.inner.backup_manager.validate_backup(backup_id).await
+    /// Validate backup integrity, including its chain of parent backups when `deep` is set
+    pub async fn validate_backup(&self, backup_id: &str, deep: bool) -> Result<ValidationReport> {
+        self.inner.backup_manager.validate_backup(backup_id, deep).await
     }
 
     /// Clean up old backups according to retention policy
-    pub async fn cleanup_old_backups(&self) -> Result<usize> {
+    pub async fn cleanup_old_backups(&self) -> Result<CleanupReport> {
         self.inner.backup_manager.cleanup_old_backups().await
     }
 
@@ -7,20 +7,26 @@
 //! Restore management module
 
 use crate::{
-    backup::{BackupInfo, BackupType},
+    backup::{is_included, BackupInfo, BackupManifest, BackupType},
+    encryption::{self, KeyProvider},
     storage::StorageBackend,
-    error::{RestoreError, Result},
+    error::{BackupError, RestoreError, Result},
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, sync::Arc};
-use tracing::{info, warn};
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::{debug, info, warn};
 
 /// Restore manager responsible for restoring from backups
 #[derive(Debug)]
 pub struct RestoreManager {
     storage: Arc<dyn StorageBackend>,
     config: RestoreConfig,
+    key_provider: Option<Arc<dyn KeyProvider>>,
 }
 
 /// Restore configuration
@@ -41,6 +47,9 @@ pub struct RestoreOptions {
     pub exclude_patterns: Vec<String>,
     pub overwrite_existing: bool,
     pub verify_after_restore: bool,
+    /// Report what the restore would change without touching the target
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 /// Point-in-time restore point
@@ -61,6 +70,36 @@ pub struct RestoreResult {
     pub files_restored: usize,
     pub warnings: Vec<String>,
     pub errors: Vec<String>,
+    /// Planned changes, only set for dry runs
+    #[serde(default)]
+    pub dry_run_report: Option<DryRunReport>,
+}
+
+/// Changes a restore would make to its target
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DryRunReport {
+    pub changes: Vec<PlannedChange>,
+}
+
+/// A single object a restore would touch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedChange {
+    pub key: String,
+    pub action: RestoreAction,
+    /// Size of the restored object, or of the live object for deletions
+    pub size: u64,
+    /// The live object differs from its backed up content
+    pub conflict: bool,
+}
+
+/// What a restore does with an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RestoreAction {
+    Create,
+    Overwrite,
+    Delete,
+    /// Exists and `overwrite_existing` is not set
+    Skip,
 }
 
 impl RestoreManager {
@@ -74,27 +113,298 @@ impl RestoreManager {
         Ok(Self {
             storage,
             config: config.clone(),
+            key_provider: None,
         })
     }
 
+    /// Use `key_provider` to fetch the keys encrypted backups were sealed with
+    pub fn with_key_provider(mut self, key_provider: Arc<dyn KeyProvider>) -> Self {
+        self.key_provider = Some(key_provider);
+        self
+    }
+
     /// Perform a restore operation
     pub async fn restore(&self, options: RestoreOptions) -> Result<RestoreResult> {
         warn!("Starting restore operation for backup: {}", options.backup_id);
-        
-        // TODO: Implement restore logic
-        
+
+        let backups = self.storage.list_backups().await?;
+        let target = backups
+            .iter()
+            .find(|info| info.id == options.backup_id)
+            .ok_or_else(|| BackupError::BackupNotFound(options.backup_id.clone()))?;
+        let chain = Self::backup_chain(&backups, target)?;
+
+        Ok(self.apply_chain(chain, &options).await?)
+    }
+
+    /// Restore the state of the data as of `timestamp`
+    ///
+    /// The most recent backup taken at or before `timestamp` is restored by
+    /// applying its full backup followed by every incremental leading up to it.
+    pub async fn restore_to_point(
+        &self,
+        timestamp: DateTime<Utc>,
+        options: RestoreOptions,
+    ) -> std::result::Result<RestoreResult, RestoreError> {
+        warn!("Starting point-in-time restore to {}", timestamp);
+
+        let backups = self.storage.list_backups().await?;
+        let target = backups
+            .iter()
+            .filter(|info| info.created_at <= timestamp)
+            .max_by_key(|info| info.created_at)
+            .ok_or(RestoreError::NoBackupForPoint(timestamp))?;
+        let chain = Self::backup_chain(&backups, target)?;
+
+        self.apply_chain(chain, &options).await
+    }
+
+    /// Follow parent references from `target` back to its full backup
+    ///
+    /// Returns the chain ordered from the full backup to `target`.
+    fn backup_chain(backups: &[BackupInfo], target: &BackupInfo) -> Result<Vec<BackupInfo>> {
+        let mut chain = vec![target.clone()];
+
+        while let Some(parent_id) = chain.last().and_then(|info| info.parent_id.clone()) {
+            let parent = backups
+                .iter()
+                .find(|info| info.id == parent_id)
+                .ok_or_else(|| {
+                    BackupError::BackupNotFound(format!(
+                        "{} (parent of {})",
+                        parent_id,
+                        chain.last().map(|info| info.id.as_str()).unwrap_or_default()
+                    ))
+                })?;
+            if chain.len() > backups.len() {
+                return Err(BackupError::InvalidFormat(format!(
+                    "backup chain of {} contains a cycle",
+                    target.id
+                )));
+            }
+            chain.push(parent.clone());
+        }
+
+        if chain.last().map(|info| info.backup_type) != Some(BackupType::Full) {
+            return Err(BackupError::InvalidFormat(format!(
+                "backup chain of {} does not start with a full backup",
+                target.id
+            )));
+        }
+
+        chain.reverse();
+        Ok(chain)
+    }
+
+    /// Apply the backups of `chain` in order to the restore target
+    async fn apply_chain(
+        &self,
+        chain: Vec<BackupInfo>,
+        options: &RestoreOptions,
+    ) -> std::result::Result<RestoreResult, RestoreError> {
+        let start_time = std::time::Instant::now();
+        let target_path = options
+            .target_path
+            .as_deref()
+            .ok_or_else(|| RestoreError::TargetNotFound("no target path given".to_string()))?;
+        if options.dry_run {
+            return self.plan_chain(chain, options, target_path, start_time).await;
+        }
+        tokio::fs::create_dir_all(target_path)
+            .await
+            .map_err(|e| RestoreError::TargetNotWritable(format!("{}: {}", target_path.display(), e)))?;
+
+        let mut written = HashSet::new();
+        let mut skipped = HashSet::new();
+        let mut warnings = Vec::new();
+        let mut errors = Vec::new();
+        let mut bytes_restored = 0;
+        let mut final_manifest = None;
+
+        for info in &chain {
+            debug!("Applying backup {} ({:?})", info.id, info.backup_type);
+            let manifest = BackupManifest::load(self.storage.as_ref(), &info.id).await?;
+            let data = self.storage.load_backup(&info.id).await?;
+            let data = encryption::decrypt_backup(self.key_provider.as_deref(), info, data).await?;
+
+            for (key, entry) in &manifest.objects {
+                if entry.stored_in != info.id
+                    || !is_included(key, &options.include_patterns, &options.exclude_patterns)
+                {
+                    continue;
+                }
+                let path = object_path(target_path, key);
+                if !options.overwrite_existing
+                    && !written.contains(key)
+                    && (skipped.contains(key) || path.exists())
+                {
+                    if skipped.insert(key.clone()) {
+                        warnings.push(format!("{} exists and was not overwritten", key));
+                    }
+                    continue;
+                }
+
+                let content = entry.content(&data).ok_or_else(|| {
+                    BackupError::InvalidFormat(format!(
+                        "object {} lies outside the data of backup {}",
+                        key, info.id
+                    ))
+                })?;
+                if options.verify_after_restore && sha256(content) != entry.sha256 {
+                    errors.push(format!("{} failed checksum verification", key));
+                    continue;
+                }
+
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await.map_err(BackupError::IoError)?;
+                }
+                tokio::fs::write(&path, content).await.map_err(BackupError::IoError)?;
+                written.insert(key.clone());
+                bytes_restored += entry.size;
+            }
+
+            final_manifest = Some(manifest);
+        }
+
+        // Objects deleted by a later incremental must not survive the restore
+        if let Some(manifest) = &final_manifest {
+            for key in written.iter().filter(|key| !manifest.objects.contains_key(*key)) {
+                tokio::fs::remove_file(object_path(target_path, key))
+                    .await
+                    .map_err(BackupError::IoError)?;
+            }
+        }
+        let files_restored = written
+            .iter()
+            .filter(|key| {
+                final_manifest
+                    .as_ref()
+                    .is_some_and(|manifest| manifest.objects.contains_key(*key))
+            })
+            .count();
+
+        let target = chain.last().expect("backup chain is never empty");
+        info!(
+            "Restored {} objects from {} backup(s) up to {}",
+            files_restored,
+            chain.len(),
+            target.id
+        );
+
+        Ok(RestoreResult {
+            restore_point: RestorePoint {
+                id: target.id.clone(),
+                created_at: target.created_at,
+                description: target
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| format!("{:?} backup {}", target.backup_type, target.id)),
+                backup_chain: chain.iter().map(|info| info.id.clone()).collect(),
+            },
+            duration_seconds: start_time.elapsed().as_secs_f64(),
+            bytes_restored,
+            files_restored,
+            warnings,
+            errors,
+            dry_run_report: None,
+        })
+    }
+
+    /// Work out what applying `chain` would change below `target_path`
+    ///
+    /// Only manifests are read, so no backup data is loaded or decrypted.
+    async fn plan_chain(
+        &self,
+        chain: Vec<BackupInfo>,
+        options: &RestoreOptions,
+        target_path: &Path,
+        start_time: std::time::Instant,
+    ) -> std::result::Result<RestoreResult, RestoreError> {
+        // Latest backed up entry of every object the chain would write
+        let mut written = BTreeMap::new();
+        let mut final_manifest = None;
+        for info in &chain {
+            let manifest = BackupManifest::load(self.storage.as_ref(), &info.id).await?;
+            for (key, entry) in &manifest.objects {
+                if entry.stored_in == info.id
+                    && is_included(key, &options.include_patterns, &options.exclude_patterns)
+                {
+                    written.insert(key.clone(), entry.clone());
+                }
+            }
+            final_manifest = Some(manifest);
+        }
+        let final_manifest = final_manifest.expect("backup chain is never empty");
+
+        let mut changes = Vec::new();
+        let mut warnings = Vec::new();
+        for (key, backed_up) in &written {
+            let path = object_path(target_path, key);
+            let live = match tokio::fs::read(&path).await {
+                Ok(content) => Some(content),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(BackupError::IoError(e).into()),
+            };
+            let conflict = live
+                .as_ref()
+                .is_some_and(|content| sha256(content) != backed_up.sha256);
+
+            let change = match (final_manifest.objects.get(key), live) {
+                (Some(entry), None) => PlannedChange {
+                    key: key.clone(),
+                    action: RestoreAction::Create,
+                    size: entry.size,
+                    conflict: false,
+                },
+                (Some(entry), Some(_)) => PlannedChange {
+                    key: key.clone(),
+                    action: if options.overwrite_existing {
+                        RestoreAction::Overwrite
+                    } else {
+                        warnings.push(format!("{} exists and would not be overwritten", key));
+                        RestoreAction::Skip
+                    },
+                    size: entry.size,
+                    conflict,
+                },
+                // Deleted by a later incremental, the restore would remove the live copy
+                (None, Some(content)) if options.overwrite_existing => PlannedChange {
+                    key: key.clone(),
+                    action: RestoreAction::Delete,
+                    size: content.len() as u64,
+                    conflict,
+                },
+                (None, _) => continue,
+            };
+            changes.push(change);
+        }
+
+        let target = chain.last().expect("backup chain is never empty");
+        info!(
+            "Dry run of restore up to {}: {} object(s) would change",
+            target.id,
+            changes
+                .iter()
+                .filter(|change| change.action != RestoreAction::Skip)
+                .count()
+        );
+
         Ok(RestoreResult {
             restore_point: RestorePoint {
-                id: "placeholder".to_string(),
-                created_at: Utc::now(),
-                description: "Placeholder restore point".to_string(),
-                backup_chain: vec![options.backup_id],
+                id: target.id.clone(),
+                created_at: target.created_at,
+                description: target
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| format!("{:?} backup {}", target.backup_type, target.id)),
+                backup_chain: chain.iter().map(|info| info.id.clone()).collect(),
             },
-            duration_seconds: 0.0,
+            duration_seconds: start_time.elapsed().as_secs_f64(),
             bytes_restored: 0,
             files_restored: 0,
-            warnings: Vec::new(),
+            warnings,
             errors: Vec::new(),
+            dry_run_report: Some(DryRunReport { changes }),
         })
     }
 
@@ -111,6 +421,18 @@ impl RestoreManager {
     }
 }
 
+/// Location of a restored object below the restore target
+fn object_path(target: &Path, key: &str) -> PathBuf {
+    key.split('/')
+        .filter(|component| !component.is_empty() && *component != "." && *component != "..")
+        .fold(target.to_path_buf(), |path, component| path.join(component))
+}
+
+fn sha256(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(data))
+}
+
 impl Default for RestoreConfig {
     fn default() -> Self {
         Self {
@@ -121,3 +443,276 @@ impl Default for RestoreConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backup::{BackupConfig, BackupManager, BackupOptions},
+        encryption::EncryptionType,
+        storage::{LocalStorageBackend, LocalStorageConfig},
+    };
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    /// Keys indexed by ID, `None` marking a revoked key
+    #[derive(Debug, Default)]
+    struct TestKeys {
+        current: std::sync::Mutex<Option<String>>,
+        keys: std::sync::Mutex<HashMap<String, Option<Vec<u8>>>>,
+    }
+
+    impl TestKeys {
+        fn rotate(&self, key_id: &str) {
+            let material = vec![self.keys.lock().unwrap().len() as u8 + 1; 32];
+            self.keys.lock().unwrap().insert(key_id.to_string(), Some(material));
+            *self.current.lock().unwrap() = Some(key_id.to_string());
+        }
+
+        fn revoke(&self, key_id: &str) {
+            self.keys.lock().unwrap().insert(key_id.to_string(), None);
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl KeyProvider for TestKeys {
+        async fn current_key(&self) -> Result<(String, Vec<u8>)> {
+            let key_id = self.current.lock().unwrap().clone().expect("a current key");
+            Ok((key_id.clone(), self.key(&key_id).await?))
+        }
+
+        async fn key(&self, key_id: &str) -> Result<Vec<u8>> {
+            match self.keys.lock().unwrap().get(key_id) {
+                Some(Some(key)) => Ok(key.clone()),
+                Some(None) => Err(BackupError::KeyUnavailable {
+                    key_id: key_id.to_string(),
+                    reason: "revoked".to_string(),
+                }),
+                None => Err(BackupError::KeyUnavailable {
+                    key_id: key_id.to_string(),
+                    reason: "not found".to_string(),
+                }),
+            }
+        }
+    }
+
+    async fn managers(
+        source: &Path,
+        storage: &Path,
+        keys: Arc<TestKeys>,
+    ) -> (BackupManager, RestoreManager) {
+        let storage: Arc<dyn StorageBackend> = Arc::new(
+            LocalStorageBackend::new(&LocalStorageConfig {
+                path: storage.to_path_buf(),
+                create_directories: true,
+            })
+            .await
+            .unwrap(),
+        );
+        let backups = BackupManager::new(
+            storage.clone(),
+            &BackupConfig {
+                source_dir: source.to_path_buf(),
+                ..BackupConfig::default()
+            },
+        )
+        .await
+        .unwrap()
+        .with_key_provider(keys.clone());
+        let restores = RestoreManager::new(storage, &RestoreConfig::default())
+            .await
+            .unwrap()
+            .with_key_provider(keys);
+
+        (backups, restores)
+    }
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 15, hour, 0, 0).unwrap()
+    }
+
+    fn restore_options(target: &Path) -> RestoreOptions {
+        RestoreOptions {
+            backup_id: String::new(),
+            target_path: Some(target.to_path_buf()),
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            overwrite_existing: true,
+            verify_after_restore: true,
+            dry_run: false,
+        }
+    }
+
+    fn read(dir: &Path, key: &str) -> Option<String> {
+        std::fs::read_to_string(dir.join(key)).ok()
+    }
+
+    #[tokio::test]
+    async fn test_restore_to_point_applies_chain() {
+        let source = TempDir::new().unwrap();
+        let storage_dir = TempDir::new().unwrap();
+        let keys = Arc::new(TestKeys::default());
+        keys.rotate("backup-1");
+        let (backups, restores) = managers(source.path(), storage_dir.path(), keys).await;
+        let incremental = || BackupOptions {
+            backup_type: BackupType::Incremental,
+            ..BackupOptions::default()
+        };
+
+        // 01:00 full, 02:00 and 03:00 incrementals
+        std::fs::write(source.path().join("a.eml"), "a1").unwrap();
+        std::fs::write(source.path().join("b.eml"), "b1").unwrap();
+        let full = backups.backup_at(BackupOptions::default(), at(1)).await.unwrap();
+        std::fs::write(source.path().join("a.eml"), "a2 changed").unwrap();
+        std::fs::write(source.path().join("c.eml"), "c1").unwrap();
+        let first = backups.backup_at(incremental(), at(2)).await.unwrap();
+        std::fs::remove_file(source.path().join("b.eml")).unwrap();
+        std::fs::write(source.path().join("c.eml"), "c2 changed").unwrap();
+        backups.backup_at(incremental(), at(3)).await.unwrap();
+
+        let target = TempDir::new().unwrap();
+        let result = restores
+            .restore_to_point(at(2) + chrono::Duration::minutes(30), restore_options(target.path()))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.restore_point.backup_chain,
+            vec![full.backup_info.id.clone(), first.backup_info.id.clone()]
+        );
+        assert_eq!(read(target.path(), "a.eml").as_deref(), Some("a2 changed"));
+        assert_eq!(read(target.path(), "b.eml").as_deref(), Some("b1"));
+        assert_eq!(read(target.path(), "c.eml").as_deref(), Some("c1"));
+        assert_eq!(result.files_restored, 3);
+        assert!(result.errors.is_empty());
+
+        // The latest point also replays the deletion of b.eml
+        let target = TempDir::new().unwrap();
+        let result = restores
+            .restore_to_point(at(4), restore_options(target.path()))
+            .await
+            .unwrap();
+        assert_eq!(result.restore_point.backup_chain.len(), 3);
+        assert_eq!(read(target.path(), "b.eml"), None);
+        assert_eq!(read(target.path(), "c.eml").as_deref(), Some("c2 changed"));
+
+        let result = restores
+            .restore_to_point(at(0), restore_options(target.path()))
+            .await;
+        assert!(matches!(result, Err(RestoreError::NoBackupForPoint(_))));
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_restore_after_rotation_and_revocation() {
+        let source = TempDir::new().unwrap();
+        let storage_dir = TempDir::new().unwrap();
+        let keys = Arc::new(TestKeys::default());
+        let (backups, restores) = managers(source.path(), storage_dir.path(), keys.clone()).await;
+
+        keys.rotate("backup-1");
+        std::fs::write(source.path().join("a.eml"), "secret message").unwrap();
+        let encrypted = |backup_type| BackupOptions {
+            backup_type,
+            encryption: Some(EncryptionType::Aes256Gcm),
+            ..BackupOptions::default()
+        };
+        let full = backups.backup_at(encrypted(BackupType::Full), at(1)).await.unwrap();
+        assert_eq!(full.backup_info.metadata.encryption_type, EncryptionType::Aes256Gcm);
+        assert_eq!(full.backup_info.metadata.encryption_key_id.as_deref(), Some("backup-1"));
+        let stored = std::fs::read_dir(storage_dir.path())
+            .unwrap()
+            .map(|entry| std::fs::read(entry.unwrap().path()).unwrap())
+            .collect::<Vec<_>>();
+        assert!(!stored
+            .iter()
+            .any(|data| data.windows(14).any(|window| window == b"secret message")));
+
+        // Backups taken after a rotation use the new key, older ones keep theirs
+        keys.rotate("backup-2");
+        std::fs::write(source.path().join("b.eml"), "another message").unwrap();
+        let incr = backups
+            .backup_at(encrypted(BackupType::Incremental), at(2))
+            .await
+            .unwrap();
+        assert_eq!(incr.backup_info.metadata.encryption_key_id.as_deref(), Some("backup-2"));
+
+        let target = TempDir::new().unwrap();
+        let result = restores
+            .restore_to_point(at(3), restore_options(target.path()))
+            .await
+            .unwrap();
+        assert!(result.errors.is_empty());
+        assert_eq!(read(target.path(), "a.eml").as_deref(), Some("secret message"));
+        assert_eq!(read(target.path(), "b.eml").as_deref(), Some("another message"));
+
+        keys.revoke("backup-1");
+        let target = TempDir::new().unwrap();
+        let result = restores
+            .restore_to_point(at(3), restore_options(target.path()))
+            .await;
+        assert!(matches!(
+            result,
+            Err(RestoreError::BackupError(BackupError::KeyUnavailable { ref key_id, .. }))
+                if key_id == "backup-1"
+        ));
+        assert_eq!(read(target.path(), "a.eml"), None);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_reports_changes_without_touching_target() {
+        let source = TempDir::new().unwrap();
+        let storage_dir = TempDir::new().unwrap();
+        let keys = Arc::new(TestKeys::default());
+        keys.rotate("backup-1");
+        let (backups, restores) = managers(source.path(), storage_dir.path(), keys).await;
+
+        for key in ["same.eml", "edited.eml", "missing.eml", "removed.eml"] {
+            std::fs::write(source.path().join(key), format!("{} v1", key)).unwrap();
+        }
+        backups.backup_at(BackupOptions::default(), at(1)).await.unwrap();
+        std::fs::remove_file(source.path().join("removed.eml")).unwrap();
+        let incremental = BackupOptions {
+            backup_type: BackupType::Incremental,
+            ..BackupOptions::default()
+        };
+        let incr = backups.backup_at(incremental, at(2)).await.unwrap();
+
+        // Live data diverged from the backup in various ways
+        let live = TempDir::new().unwrap();
+        std::fs::write(live.path().join("same.eml"), "same.eml v1").unwrap();
+        std::fs::write(live.path().join("edited.eml"), "edited locally").unwrap();
+        std::fs::write(live.path().join("removed.eml"), "removed.eml v1").unwrap();
+        std::fs::write(live.path().join("unrelated.txt"), "not in any backup").unwrap();
+
+        let result = restores
+            .restore(RestoreOptions {
+                backup_id: incr.backup_info.id,
+                dry_run: true,
+                ..restore_options(live.path())
+            })
+            .await
+            .unwrap();
+        let change = |key: &str, action, size, conflict| PlannedChange {
+            key: key.to_string(),
+            action,
+            size,
+            conflict,
+        };
+        assert_eq!(
+            result.dry_run_report.unwrap().changes,
+            vec![
+                change("edited.eml", RestoreAction::Overwrite, 13, true),
+                change("missing.eml", RestoreAction::Create, 14, false),
+                change("removed.eml", RestoreAction::Delete, 14, false),
+                change("same.eml", RestoreAction::Overwrite, 11, false),
+            ]
+        );
+        assert_eq!(result.files_restored, 0);
+
+        assert_eq!(read(live.path(), "same.eml").as_deref(), Some("same.eml v1"));
+        assert_eq!(read(live.path(), "edited.eml").as_deref(), Some("edited locally"));
+        assert_eq!(read(live.path(), "removed.eml").as_deref(), Some("removed.eml v1"));
+        assert_eq!(read(live.path(), "missing.eml"), None);
+        assert_eq!(std::fs::read_dir(live.path()).unwrap().count(), 4);
+    }
+}
@@ -0,0 +1,109 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! Grandfather-father-son backup retention
+//!
+//! The newest backup of each of the most recent `daily` days, `weekly` ISO
+//! weeks and `monthly` months is retained. Every backup a retained backup
+//! depends on through its parent chain is retained as well, so cleanup never
+//! breaks a restorable chain.
+
+use crate::backup::BackupInfo;
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Number of backups to keep per time window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub daily: usize,
+    pub weekly: usize,
+    pub monthly: usize,
+}
+
+/// Outcome of applying a retention policy
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanupReport {
+    /// Retained backups, oldest first
+    pub kept: Vec<String>,
+    /// Deleted backups, oldest first
+    pub deleted: Vec<String>,
+}
+
+impl RetentionPolicy {
+    /// Split `backups` into the ones to keep and the ones to delete
+    pub fn apply(&self, backups: &[BackupInfo]) -> CleanupReport {
+        let mut backups = backups.iter().collect::<Vec<_>>();
+        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+
+        let mut retained = HashSet::new();
+        retained.extend(newest_per_period(&backups, self.daily, |date| {
+            (date.year(), date.ordinal())
+        }));
+        retained.extend(newest_per_period(&backups, self.weekly, |date| {
+            let week = date.iso_week();
+            (week.year(), week.week())
+        }));
+        retained.extend(newest_per_period(&backups, self.monthly, |date| {
+            (date.year(), date.month())
+        }));
+
+        // Keep everything the retained backups need to be restored
+        let by_id = backups
+            .iter()
+            .map(|info| (info.id.as_str(), *info))
+            .collect::<HashMap<_, _>>();
+        let mut pending = retained.iter().copied().collect::<Vec<_>>();
+        while let Some(id) = pending.pop() {
+            if let Some(parent_id) = by_id.get(id).and_then(|info| info.parent_id.as_deref()) {
+                if by_id.contains_key(parent_id) && retained.insert(parent_id) {
+                    pending.push(parent_id);
+                }
+            }
+        }
+
+        let mut report = CleanupReport::default();
+        for info in backups.iter().rev() {
+            if retained.contains(info.id.as_str()) {
+                report.kept.push(info.id.clone());
+            } else {
+                report.deleted.push(info.id.clone());
+            }
+        }
+        report
+    }
+}
+
+/// IDs of the newest backup in each of the `count` most recent periods
+///
+/// `backups` must be sorted newest first.
+fn newest_per_period<'a, K: Ord>(
+    backups: &[&'a BackupInfo],
+    count: usize,
+    period: impl Fn(NaiveDate) -> K,
+) -> Vec<&'a str> {
+    let mut newest = BTreeMap::new();
+    for info in backups {
+        let key = period(info.created_at.date_naive());
+        if !newest.contains_key(&key) {
+            if newest.len() == count {
+                break;
+            }
+            newest.insert(key, info.id.as_str());
+        }
+    }
+    newest.into_values().collect()
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            daily: 7,
+            weekly: 4,
+            monthly: 12,
+        }
+    }
+}
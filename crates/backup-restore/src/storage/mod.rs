@@ -6,11 +6,18 @@
 
 //! Storage backend abstraction for backups
 
-use crate::{backup::BackupInfo, error::Result};
+use crate::{
+    backup::{BackupInfo, BackupManifest},
+    error::Result,
+};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::{path::PathBuf, sync::Arc};
 
+pub mod s3;
+
+pub use s3::S3StorageBackend;
+
 /// Storage backend trait for backup storage
 #[async_trait]
 pub trait StorageBackend: Send + Sync + std::fmt::Debug {
@@ -66,6 +73,9 @@ pub struct LocalStorageConfig {
 }
 
 /// S3 storage configuration
+///
+/// Empty credentials fall back to the `AWS_ACCESS_KEY_ID`,
+/// `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN` environment variables.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct S3StorageConfig {
     pub bucket: String,
@@ -73,6 +83,43 @@ pub struct S3StorageConfig {
     pub access_key_id: String,
     pub secret_access_key: String,
     pub endpoint: Option<String>,
+    /// Key prefix under which backups are stored, e.g. `backups/mx1/`
+    #[serde(default)]
+    pub prefix: String,
+    /// Server-side encryption algorithm (`AES256` or `aws:kms`)
+    #[serde(default)]
+    pub server_side_encryption: Option<String>,
+    /// KMS key used when `server_side_encryption` is `aws:kms`
+    #[serde(default)]
+    pub sse_kms_key_id: Option<String>,
+    /// Objects larger than this are uploaded in parts
+    #[serde(default = "S3StorageConfig::default_multipart_threshold")]
+    pub multipart_threshold: usize,
+    #[serde(default = "S3StorageConfig::default_part_size")]
+    pub part_size: usize,
+    /// Attempts made after a transient (5xx or network) failure
+    #[serde(default = "S3StorageConfig::default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "S3StorageConfig::default_retry_base_delay_ms")]
+    pub retry_base_delay_ms: u64,
+}
+
+impl S3StorageConfig {
+    fn default_multipart_threshold() -> usize {
+        64 * 1024 * 1024 // 64MB
+    }
+
+    fn default_part_size() -> usize {
+        16 * 1024 * 1024 // 16MB
+    }
+
+    fn default_max_retries() -> u32 {
+        3
+    }
+
+    fn default_retry_base_delay_ms() -> u64 {
+        200
+    }
 }
 
 /// Azure Blob storage configuration
@@ -195,8 +242,24 @@ impl StorageBackend for LocalStorageBackend {
     }
     
     async fn list_backups(&self) -> Result<Vec<BackupInfo>> {
-        // TODO: Implement backup listing from filesystem
-        Ok(Vec::new())
+        let mut backups = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.config.path).await
+            .map_err(|e| crate::error::BackupError::IoError(e))?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("manifest") {
+                continue;
+            }
+            let manifest = tokio::fs::read_to_string(&path).await?;
+            match serde_json::from_str::<BackupManifest>(&manifest) {
+                Ok(manifest) => backups.push(manifest.info),
+                Err(e) => tracing::warn!("Skipping unreadable manifest {:?}: {}", path, e),
+            }
+        }
+
+        backups.sort_by_key(|info| info.created_at);
+        Ok(backups)
     }
     
     async fn delete_backup(&self, backup_id: &str) -> Result<()> {
@@ -234,18 +297,6 @@ impl StorageBackend for LocalStorageBackend {
 }
 
 // Placeholder implementations for cloud storage backends
-#[cfg(feature = "aws-s3")]
-#[derive(Debug)]
-pub struct S3StorageBackend;
-
-#[cfg(feature = "aws-s3")]
-impl S3StorageBackend {
-    pub async fn new(_config: &S3StorageConfig) -> Result<Self> {
-        // TODO: Implement S3 backend
-        Ok(Self)
-    }
-}
-
 #[cfg(feature = "azure-blob")]
 #[derive(Debug)]
 pub struct AzureStorageBackend;
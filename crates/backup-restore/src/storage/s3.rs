@@ -0,0 +1,910 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! S3 storage backend
+//!
+//! Backups are stored as `<prefix><backup_id>.backup` and
+//! `<prefix><backup_id>.manifest`. The backend talks to S3 through the
+//! [`S3Api`] trait so the upload, retry and listing logic can be exercised
+//! without a real bucket.
+
+use super::{S3StorageConfig, StorageBackend, StorageStats};
+use crate::{
+    backup::{BackupInfo, BackupManifest},
+    error::{BackupError, Result},
+};
+use async_trait::async_trait;
+use std::{future::Future, sync::Arc, time::Duration};
+use tracing::{debug, warn};
+
+/// Server-side encryption settings sent with every upload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerSideEncryption {
+    pub algorithm: String,
+    pub kms_key_id: Option<String>,
+}
+
+/// Error returned by an S3 request
+#[derive(Debug, Clone)]
+pub struct S3Error {
+    /// HTTP status of the response, `None` when no response was received
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+impl S3Error {
+    /// Server errors and requests that never got a response are worth retrying
+    pub fn is_transient(&self) -> bool {
+        self.status.map_or(true, |status| status >= 500)
+    }
+}
+
+impl std::fmt::Display for S3Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.status {
+            Some(status) => write!(f, "HTTP {}: {}", status, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Minimal set of S3 operations used by the backend
+#[async_trait]
+pub trait S3Api: Send + Sync + std::fmt::Debug {
+    async fn put_object(
+        &self,
+        key: &str,
+        body: Vec<u8>,
+        sse: Option<&ServerSideEncryption>,
+    ) -> std::result::Result<(), S3Error>;
+
+    /// Fetch an object, returning `None` when it does not exist
+    async fn get_object(&self, key: &str) -> std::result::Result<Option<Vec<u8>>, S3Error>;
+
+    async fn head_object(&self, key: &str) -> std::result::Result<bool, S3Error>;
+
+    /// List all keys starting with `prefix` together with their sizes
+    async fn list_objects(&self, prefix: &str) -> std::result::Result<Vec<(String, u64)>, S3Error>;
+
+    async fn delete_object(&self, key: &str) -> std::result::Result<(), S3Error>;
+
+    /// Start a multipart upload, returning its upload ID
+    async fn create_multipart_upload(
+        &self,
+        key: &str,
+        sse: Option<&ServerSideEncryption>,
+    ) -> std::result::Result<String, S3Error>;
+
+    /// Upload one part, returning its ETag
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: i32,
+        body: Vec<u8>,
+    ) -> std::result::Result<String, S3Error>;
+
+    async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: Vec<(i32, String)>,
+    ) -> std::result::Result<(), S3Error>;
+
+    async fn abort_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+    ) -> std::result::Result<(), S3Error>;
+}
+
+/// S3 storage backend
+#[derive(Debug)]
+pub struct S3StorageBackend {
+    client: Arc<dyn S3Api>,
+    prefix: String,
+    sse: Option<ServerSideEncryption>,
+    multipart_threshold: usize,
+    part_size: usize,
+    max_retries: u32,
+    retry_base_delay: Duration,
+}
+
+impl S3StorageBackend {
+    /// Create a backend talking to the bucket described by `config`
+    #[cfg(feature = "aws-s3")]
+    pub async fn new(config: &S3StorageConfig) -> Result<Self> {
+        let client = AwsS3Client::new(config)?;
+        Ok(Self::with_client(Arc::new(client), config))
+    }
+
+    /// Create a backend on top of an existing S3 client
+    pub fn with_client(client: Arc<dyn S3Api>, config: &S3StorageConfig) -> Self {
+        Self {
+            client,
+            prefix: config.prefix.clone(),
+            sse: config
+                .server_side_encryption
+                .as_ref()
+                .map(|algorithm| ServerSideEncryption {
+                    algorithm: algorithm.clone(),
+                    kms_key_id: config.sse_kms_key_id.clone(),
+                }),
+            multipart_threshold: config.multipart_threshold,
+            part_size: config.part_size.max(1),
+            max_retries: config.max_retries,
+            retry_base_delay: Duration::from_millis(config.retry_base_delay_ms),
+        }
+    }
+
+    fn backup_key(&self, backup_id: &str) -> String {
+        format!("{}{}.backup", self.prefix, backup_id)
+    }
+
+    fn manifest_key(&self, backup_id: &str) -> String {
+        format!("{}{}.manifest", self.prefix, backup_id)
+    }
+
+    /// Run `request`, retrying transient failures with exponential backoff
+    async fn retry<T, F, Fut>(&self, operation: &str, mut request: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = std::result::Result<T, S3Error>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match request().await {
+                Ok(result) => return Ok(result),
+                Err(err) if err.is_transient() && attempt < self.max_retries => {
+                    let delay = self.retry_base_delay * 2u32.saturating_pow(attempt);
+                    attempt += 1;
+                    warn!(
+                        "S3 {} failed ({}), retrying in {:?} (attempt {}/{})",
+                        operation, err, delay, attempt, self.max_retries
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(err) => {
+                    return Err(BackupError::StorageError(format!(
+                        "S3 {} failed: {}",
+                        operation, err
+                    )))
+                }
+            }
+        }
+    }
+
+    async fn put(&self, key: &str, data: &[u8]) -> Result<()> {
+        if data.len() <= self.multipart_threshold {
+            return self
+                .retry("PutObject", || {
+                    self.client
+                        .put_object(key, data.to_vec(), self.sse.as_ref())
+                })
+                .await;
+        }
+
+        let upload_id = self
+            .retry("CreateMultipartUpload", || {
+                self.client.create_multipart_upload(key, self.sse.as_ref())
+            })
+            .await?;
+        debug!(
+            "Uploading {} bytes to {} in parts of {} bytes",
+            data.len(),
+            key,
+            self.part_size
+        );
+
+        match self.upload_parts(key, &upload_id, data).await {
+            Ok(parts) => {
+                self.retry("CompleteMultipartUpload", || {
+                    self.client
+                        .complete_multipart_upload(key, &upload_id, parts.clone())
+                })
+                .await
+            }
+            Err(err) => {
+                if let Err(abort) = self.client.abort_multipart_upload(key, &upload_id).await {
+                    warn!("Failed to abort multipart upload of {}: {}", key, abort);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    async fn upload_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        data: &[u8],
+    ) -> Result<Vec<(i32, String)>> {
+        let mut parts = Vec::new();
+        for (idx, chunk) in data.chunks(self.part_size).enumerate() {
+            let part_number = idx as i32 + 1;
+            let etag = self
+                .retry("UploadPart", || {
+                    self.client
+                        .upload_part(key, upload_id, part_number, chunk.to_vec())
+                })
+                .await?;
+            parts.push((part_number, etag));
+        }
+        Ok(parts)
+    }
+
+    async fn get(&self, key: &str, backup_id: &str) -> Result<Vec<u8>> {
+        self.retry("GetObject", || self.client.get_object(key))
+            .await?
+            .ok_or_else(|| BackupError::BackupNotFound(backup_id.to_string()))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for S3StorageBackend {
+    async fn store_backup(&self, backup_id: &str, data: &[u8]) -> Result<()> {
+        self.put(&self.backup_key(backup_id), data).await
+    }
+
+    async fn load_backup(&self, backup_id: &str) -> Result<Vec<u8>> {
+        self.get(&self.backup_key(backup_id), backup_id).await
+    }
+
+    async fn store_manifest(&self, backup_id: &str, manifest: &str) -> Result<()> {
+        self.put(&self.manifest_key(backup_id), manifest.as_bytes())
+            .await
+    }
+
+    async fn load_manifest(&self, backup_id: &str) -> Result<String> {
+        let manifest = self.get(&self.manifest_key(backup_id), backup_id).await?;
+        String::from_utf8(manifest)
+            .map_err(|e| BackupError::InvalidFormat(format!("manifest of {}: {}", backup_id, e)))
+    }
+
+    async fn list_backups(&self) -> Result<Vec<BackupInfo>> {
+        let keys = self
+            .retry("ListObjectsV2", || self.client.list_objects(&self.prefix))
+            .await?;
+        let mut backups = Vec::new();
+
+        for (key, _) in keys {
+            let Some(backup_id) = key
+                .strip_prefix(self.prefix.as_str())
+                .and_then(|name| name.strip_suffix(".manifest"))
+            else {
+                continue;
+            };
+            // Keys in nested "directories" belong to another prefix
+            if backup_id.contains('/') {
+                continue;
+            }
+            let manifest = self.load_manifest(backup_id).await?;
+            match serde_json::from_str::<BackupManifest>(&manifest) {
+                Ok(manifest) => backups.push(manifest.info),
+                Err(e) => warn!("Skipping unreadable manifest {}: {}", key, e),
+            }
+        }
+
+        backups.sort_by_key(|info| info.created_at);
+        Ok(backups)
+    }
+
+    async fn delete_backup(&self, backup_id: &str) -> Result<()> {
+        let backup_key = self.backup_key(backup_id);
+        let manifest_key = self.manifest_key(backup_id);
+        self.retry("DeleteObject", || self.client.delete_object(&backup_key))
+            .await?;
+        self.retry("DeleteObject", || self.client.delete_object(&manifest_key))
+            .await
+    }
+
+    async fn backup_exists(&self, backup_id: &str) -> Result<bool> {
+        let key = self.backup_key(backup_id);
+        self.retry("HeadObject", || self.client.head_object(&key))
+            .await
+    }
+
+    async fn get_stats(&self) -> Result<StorageStats> {
+        let backups = self.list_backups().await?;
+        let total_size_bytes = self
+            .retry("ListObjectsV2", || self.client.list_objects(&self.prefix))
+            .await?
+            .into_iter()
+            .map(|(_, size)| size)
+            .sum();
+
+        Ok(StorageStats {
+            total_backups: backups.len(),
+            total_size_bytes,
+            available_space_bytes: None,
+            oldest_backup: backups.first().map(|info| info.id.clone()),
+            newest_backup: backups.last().map(|info| info.id.clone()),
+        })
+    }
+}
+
+/// [`S3Api`] implementation backed by the AWS SDK
+#[cfg(feature = "aws-s3")]
+#[derive(Debug)]
+pub struct AwsS3Client {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+}
+
+#[cfg(feature = "aws-s3")]
+impl AwsS3Client {
+    pub fn new(config: &S3StorageConfig) -> Result<Self> {
+        use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+
+        let env = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let (access_key_id, secret_access_key, session_token) = if config.access_key_id.is_empty() {
+            (
+                env("AWS_ACCESS_KEY_ID"),
+                env("AWS_SECRET_ACCESS_KEY"),
+                env("AWS_SESSION_TOKEN"),
+            )
+        } else {
+            (
+                Some(config.access_key_id.clone()),
+                Some(config.secret_access_key.clone()),
+                None,
+            )
+        };
+        let (Some(access_key_id), Some(secret_access_key)) = (access_key_id, secret_access_key)
+        else {
+            return Err(BackupError::ConfigError(
+                "Missing S3 credentials in configuration and environment".to_string(),
+            ));
+        };
+
+        let mut builder = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new(config.region.clone()))
+            .credentials_provider(Credentials::new(
+                access_key_id,
+                secret_access_key,
+                session_token,
+                None,
+                "a3mailer-backup",
+            ));
+        if let Some(endpoint) = &config.endpoint {
+            builder = builder.endpoint_url(endpoint).force_path_style(true);
+        }
+
+        Ok(Self {
+            client: aws_sdk_s3::Client::from_conf(builder.build()),
+            bucket: config.bucket.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "aws-s3")]
+fn sdk_error<E>(
+    err: aws_sdk_s3::error::SdkError<E, aws_sdk_s3::config::http::HttpResponse>,
+) -> S3Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    S3Error {
+        status: err
+            .raw_response()
+            .map(|response| response.status().as_u16()),
+        message: aws_sdk_s3::error::DisplayErrorContext(&err).to_string(),
+    }
+}
+
+#[cfg(feature = "aws-s3")]
+#[async_trait]
+impl S3Api for AwsS3Client {
+    async fn put_object(
+        &self,
+        key: &str,
+        body: Vec<u8>,
+        sse: Option<&ServerSideEncryption>,
+    ) -> std::result::Result<(), S3Error> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(body.into())
+            .set_server_side_encryption(sse.map(|sse| sse.algorithm.as_str().into()))
+            .set_ssekms_key_id(sse.and_then(|sse| sse.kms_key_id.clone()))
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(sdk_error)
+    }
+
+    async fn get_object(&self, key: &str) -> std::result::Result<Option<Vec<u8>>, S3Error> {
+        match self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(output) => output
+                .body
+                .collect()
+                .await
+                .map(|body| Some(body.into_bytes().to_vec()))
+                .map_err(|e| S3Error {
+                    status: None,
+                    message: e.to_string(),
+                }),
+            Err(err) if err.as_service_error().is_some_and(|e| e.is_no_such_key()) => Ok(None),
+            Err(err) => Err(sdk_error(err)),
+        }
+    }
+
+    async fn head_object(&self, key: &str) -> std::result::Result<bool, S3Error> {
+        match self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(err) if err.as_service_error().is_some_and(|e| e.is_not_found()) => Ok(false),
+            Err(err) => Err(sdk_error(err)),
+        }
+    }
+
+    async fn list_objects(&self, prefix: &str) -> std::result::Result<Vec<(String, u64)>, S3Error> {
+        let mut objects = Vec::new();
+        let mut continuation_token = None;
+
+        loop {
+            let output = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(prefix)
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .map_err(sdk_error)?;
+            objects.extend(output.contents().iter().filter_map(|object| {
+                Some((
+                    object.key()?.to_string(),
+                    object.size().unwrap_or_default().max(0) as u64,
+                ))
+            }));
+
+            continuation_token = output.next_continuation_token().map(str::to_string);
+            if continuation_token.is_none() {
+                return Ok(objects);
+            }
+        }
+    }
+
+    async fn delete_object(&self, key: &str) -> std::result::Result<(), S3Error> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(sdk_error)
+    }
+
+    async fn create_multipart_upload(
+        &self,
+        key: &str,
+        sse: Option<&ServerSideEncryption>,
+    ) -> std::result::Result<String, S3Error> {
+        let output = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .set_server_side_encryption(sse.map(|sse| sse.algorithm.as_str().into()))
+            .set_ssekms_key_id(sse.and_then(|sse| sse.kms_key_id.clone()))
+            .send()
+            .await
+            .map_err(sdk_error)?;
+
+        output
+            .upload_id()
+            .map(str::to_string)
+            .ok_or_else(|| S3Error {
+                status: None,
+                message: "CreateMultipartUpload returned no upload ID".to_string(),
+            })
+    }
+
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: i32,
+        body: Vec<u8>,
+    ) -> std::result::Result<String, S3Error> {
+        let output = self
+            .client
+            .upload_part()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(body.into())
+            .send()
+            .await
+            .map_err(sdk_error)?;
+
+        output.e_tag().map(str::to_string).ok_or_else(|| S3Error {
+            status: None,
+            message: format!("UploadPart {} returned no ETag", part_number),
+        })
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: Vec<(i32, String)>,
+    ) -> std::result::Result<(), S3Error> {
+        use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+
+        let parts = parts
+            .into_iter()
+            .map(|(part_number, etag)| {
+                CompletedPart::builder()
+                    .part_number(part_number)
+                    .e_tag(etag)
+                    .build()
+            })
+            .collect();
+        self.client
+            .complete_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(parts))
+                    .build(),
+            )
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(sdk_error)
+    }
+
+    async fn abort_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+    ) -> std::result::Result<(), S3Error> {
+        self.client
+            .abort_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(sdk_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backup::{BackupMetadata, BackupType},
+        compression::CompressionType,
+        encryption::EncryptionType,
+    };
+    use chrono::{TimeZone, Utc};
+    use std::{
+        collections::{BTreeMap, HashMap},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+    };
+
+    /// In-memory S3 that can be told to fail upcoming requests
+    #[derive(Debug, Default)]
+    struct MockS3 {
+        objects: Mutex<BTreeMap<String, Vec<u8>>>,
+        uploads: Mutex<HashMap<String, BTreeMap<i32, Vec<u8>>>>,
+        requests: AtomicUsize,
+        fail_next: AtomicUsize,
+        fail_status: AtomicUsize,
+        parts_uploaded: AtomicUsize,
+        last_sse: Mutex<Option<ServerSideEncryption>>,
+    }
+
+    impl MockS3 {
+        fn fail(&self, count: usize, status: u16) {
+            self.fail_status.store(status as usize, Ordering::SeqCst);
+            self.fail_next.store(count, Ordering::SeqCst);
+        }
+
+        fn request(&self) -> std::result::Result<(), S3Error> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            let failing = self
+                .fail_next
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if failing {
+                Err(S3Error {
+                    status: Some(self.fail_status.load(Ordering::SeqCst) as u16),
+                    message: "injected failure".to_string(),
+                })
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[async_trait]
+    impl S3Api for MockS3 {
+        async fn put_object(
+            &self,
+            key: &str,
+            body: Vec<u8>,
+            sse: Option<&ServerSideEncryption>,
+        ) -> std::result::Result<(), S3Error> {
+            self.request()?;
+            *self.last_sse.lock().unwrap() = sse.cloned();
+            self.objects.lock().unwrap().insert(key.to_string(), body);
+            Ok(())
+        }
+
+        async fn get_object(&self, key: &str) -> std::result::Result<Option<Vec<u8>>, S3Error> {
+            self.request()?;
+            Ok(self.objects.lock().unwrap().get(key).cloned())
+        }
+
+        async fn head_object(&self, key: &str) -> std::result::Result<bool, S3Error> {
+            self.request()?;
+            Ok(self.objects.lock().unwrap().contains_key(key))
+        }
+
+        async fn list_objects(
+            &self,
+            prefix: &str,
+        ) -> std::result::Result<Vec<(String, u64)>, S3Error> {
+            self.request()?;
+            Ok(self
+                .objects
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(key, _)| key.starts_with(prefix))
+                .map(|(key, body)| (key.clone(), body.len() as u64))
+                .collect())
+        }
+
+        async fn delete_object(&self, key: &str) -> std::result::Result<(), S3Error> {
+            self.request()?;
+            self.objects.lock().unwrap().remove(key);
+            Ok(())
+        }
+
+        async fn create_multipart_upload(
+            &self,
+            key: &str,
+            sse: Option<&ServerSideEncryption>,
+        ) -> std::result::Result<String, S3Error> {
+            self.request()?;
+            *self.last_sse.lock().unwrap() = sse.cloned();
+            let upload_id = format!("upload-{}", key);
+            self.uploads
+                .lock()
+                .unwrap()
+                .insert(upload_id.clone(), BTreeMap::new());
+            Ok(upload_id)
+        }
+
+        async fn upload_part(
+            &self,
+            _key: &str,
+            upload_id: &str,
+            part_number: i32,
+            body: Vec<u8>,
+        ) -> std::result::Result<String, S3Error> {
+            self.request()?;
+            self.parts_uploaded.fetch_add(1, Ordering::SeqCst);
+            self.uploads
+                .lock()
+                .unwrap()
+                .get_mut(upload_id)
+                .expect("unknown upload")
+                .insert(part_number, body);
+            Ok(format!("etag-{}", part_number))
+        }
+
+        async fn complete_multipart_upload(
+            &self,
+            key: &str,
+            upload_id: &str,
+            parts: Vec<(i32, String)>,
+        ) -> std::result::Result<(), S3Error> {
+            self.request()?;
+            let uploaded = self
+                .uploads
+                .lock()
+                .unwrap()
+                .remove(upload_id)
+                .expect("unknown upload");
+            let mut body = Vec::new();
+            for (part_number, etag) in parts {
+                assert_eq!(etag, format!("etag-{}", part_number));
+                body.extend_from_slice(&uploaded[&part_number]);
+            }
+            self.objects.lock().unwrap().insert(key.to_string(), body);
+            Ok(())
+        }
+
+        async fn abort_multipart_upload(
+            &self,
+            _key: &str,
+            upload_id: &str,
+        ) -> std::result::Result<(), S3Error> {
+            self.uploads.lock().unwrap().remove(upload_id);
+            Ok(())
+        }
+    }
+
+    fn config(prefix: &str) -> S3StorageConfig {
+        S3StorageConfig {
+            bucket: "backups".to_string(),
+            region: "us-east-1".to_string(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            endpoint: None,
+            prefix: prefix.to_string(),
+            server_side_encryption: None,
+            sse_kms_key_id: None,
+            multipart_threshold: 1024,
+            part_size: 400,
+            max_retries: 3,
+            retry_base_delay_ms: 1,
+        }
+    }
+
+    fn manifest(id: &str, day: u32) -> String {
+        serde_json::to_string(&BackupManifest {
+            info: BackupInfo {
+                id: id.to_string(),
+                backup_type: BackupType::Full,
+                created_at: Utc.with_ymd_and_hms(2026, 10, day, 0, 0, 0).unwrap(),
+                size_bytes: 0,
+                compressed_size_bytes: 0,
+                file_count: 0,
+                checksum: String::new(),
+                description: None,
+                parent_id: None,
+                metadata: BackupMetadata {
+                    version: "test".to_string(),
+                    hostname: "test".to_string(),
+                    compression_type: CompressionType::None,
+                    encryption_type: EncryptionType::None,
+                    chunk_count: 1,
+                    manifest_checksum: String::new(),
+                },
+            },
+            objects: BTreeMap::new(),
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_put_get_round_trip() {
+        let mock = Arc::new(MockS3::default());
+        let backend = S3StorageBackend::with_client(mock.clone(), &config("mx1/"));
+
+        backend
+            .store_backup("full_1", b"backup data")
+            .await
+            .unwrap();
+        backend.store_manifest("full_1", "{}").await.unwrap();
+        assert!(mock
+            .objects
+            .lock()
+            .unwrap()
+            .contains_key("mx1/full_1.backup"));
+
+        assert_eq!(backend.load_backup("full_1").await.unwrap(), b"backup data");
+        assert_eq!(backend.load_manifest("full_1").await.unwrap(), "{}");
+        assert!(backend.backup_exists("full_1").await.unwrap());
+
+        backend.delete_backup("full_1").await.unwrap();
+        assert!(!backend.backup_exists("full_1").await.unwrap());
+        assert!(matches!(
+            backend.load_backup("full_1").await,
+            Err(BackupError::BackupNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_backups_with_prefix() {
+        let mock = Arc::new(MockS3::default());
+        let backend = S3StorageBackend::with_client(mock.clone(), &config("mx1/"));
+        let other = S3StorageBackend::with_client(mock.clone(), &config("mx2/"));
+        let nested = S3StorageBackend::with_client(mock.clone(), &config("mx1/archive/"));
+
+        backend
+            .store_manifest("full_b", &manifest("full_b", 2))
+            .await
+            .unwrap();
+        backend
+            .store_manifest("full_a", &manifest("full_a", 1))
+            .await
+            .unwrap();
+        backend.store_backup("full_a", b"data").await.unwrap();
+        other
+            .store_manifest("full_c", &manifest("full_c", 3))
+            .await
+            .unwrap();
+        nested
+            .store_manifest("full_d", &manifest("full_d", 4))
+            .await
+            .unwrap();
+
+        let ids = backend
+            .list_backups()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|info| info.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["full_a", "full_b"]);
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_for_large_blob() {
+        let mock = Arc::new(MockS3::default());
+        let mut config = config("");
+        config.server_side_encryption = Some("aws:kms".to_string());
+        config.sse_kms_key_id = Some("backup-key".to_string());
+        let backend = S3StorageBackend::with_client(mock.clone(), &config);
+
+        let blob = (0..1500u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        backend.store_backup("full_big", &blob).await.unwrap();
+
+        assert_eq!(mock.parts_uploaded.load(Ordering::SeqCst), 4);
+        assert_eq!(backend.load_backup("full_big").await.unwrap(), blob);
+        assert_eq!(
+            mock.last_sse.lock().unwrap().clone(),
+            Some(ServerSideEncryption {
+                algorithm: "aws:kms".to_string(),
+                kms_key_id: Some("backup-key".to_string()),
+            })
+        );
+        assert!(mock.uploads.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        let mock = Arc::new(MockS3::default());
+        let backend = S3StorageBackend::with_client(mock.clone(), &config(""));
+
+        mock.fail(2, 503);
+        backend.store_backup("full_1", b"data").await.unwrap();
+        assert_eq!(mock.requests.load(Ordering::SeqCst), 3);
+
+        mock.fail(4, 500);
+        assert!(matches!(
+            backend.load_backup("full_1").await,
+            Err(BackupError::StorageError(_))
+        ));
+
+        // Client errors are not retried
+        mock.requests.store(0, Ordering::SeqCst);
+        mock.fail(1, 403);
+        assert!(backend.load_backup("full_1").await.is_err());
+        assert_eq!(mock.requests.load(Ordering::SeqCst), 1);
+    }
+}
@@ -14,7 +14,7 @@ use console::style;
 use jmap_client::client::Credentials;
 use modules::{
     UnwrapResult,
-    cli::{Cli, Client, Commands},
+    cli::{Cli, CliCommands, Client, Commands},
     is_localhost,
 };
 
@@ -25,6 +25,14 @@ pub mod modules;
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
     let args = Cli::parse();
+    let command = match args.command {
+        CliCommands::Remote(command) => command,
+        CliCommands::Completions { shell } => {
+            Cli::completions(shell, &mut std::io::stdout());
+            return Ok(());
+        }
+    };
+
     let url = args
         .url
         .or_else(|| std::env::var("URL").ok())
@@ -53,7 +61,7 @@ async fn main() -> std::io::Result<()> {
         url,
     };
 
-    match args.command {
+    match command {
         Commands::Account(command) => {
             command.exec(client).await;
         }
@@ -4,8 +4,11 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+use std::{io::Write, time::Duration};
+
 use super::dkim::Algorithm;
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use jmap_client::client::Credentials;
 use mail_parser::DateTime;
 use serde::Deserialize;
@@ -15,7 +18,7 @@ use serde::Deserialize;
 #[clap(name = "stalwart-cli")]
 pub struct Cli {
     #[clap(subcommand)]
-    pub command: Commands,
+    pub command: CliCommands,
     /// Server base URL
     #[clap(short, long)]
     pub url: Option<String>,
@@ -27,6 +30,21 @@ pub struct Cli {
     pub timeout: Option<u64>,
 }
 
+#[derive(Subcommand)]
+pub enum CliCommands {
+    #[clap(flatten)]
+    Remote(Commands),
+
+    /// Generate shell completion scripts
+    #[clap(hide = true)]
+    Completions {
+        /// Shell to generate completions for
+        #[clap(value_enum)]
+        shell: Shell,
+    },
+}
+
+/// Commands that run against a server
 #[derive(Subcommand)]
 pub enum Commands {
     /// Manage user accounts
@@ -70,6 +88,22 @@ pub enum Commands {
     Report(ReportCommands),
 }
 
+impl Cli {
+    /// Write the completion script for `shell`, bound to the installed binary name
+    ///
+    /// The binary is named after the package. `CARGO_BIN_NAME` is not set when
+    /// this module is built as part of the library. Only the server commands
+    /// are completed, as the zsh generator lists hidden subcommands too.
+    pub fn completions(shell: Shell, out: &mut impl Write) {
+        let options = Cli::command().get_arguments().cloned().fold(
+            clap::Command::new(env!("CARGO_PKG_NAME")),
+            clap::Command::arg,
+        );
+        let mut command = Commands::augment_subcommands(options);
+        clap_complete::generate(shell, &mut command, env!("CARGO_PKG_NAME"), out);
+    }
+}
+
 pub struct Client {
     pub url: String,
     pub credentials: Credentials,
@@ -89,7 +123,7 @@ pub enum AccountCommands {
         description: Option<String>,
         /// Quota in bytes
         #[clap(short, long)]
-        quota: Option<u32>,
+        quota: Option<u64>,
         /// Whether the account is an administrator
         #[clap(short, long)]
         is_admin: Option<bool>,
@@ -369,6 +403,23 @@ pub enum DkimCommands {
         /// Signature id
         signature_id: String,
     },
+
+    /// Rotate the DKIM key of a domain and print its DNS TXT record
+    Rotate {
+        /// Domain name to rotate the key for
+        domain: String,
+        /// Selector to publish the new key under
+        selector: String,
+        /// Algorithm to use
+        #[clap(long, value_enum, default_value_t)]
+        algorithm: Algorithm,
+        /// RSA key size in bits, defaults to 2048
+        #[clap(long)]
+        key_size: Option<usize>,
+        /// Signature to replace, defaults to '<algorithm>-<domain>'
+        #[clap(long)]
+        signature_id: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -401,6 +452,23 @@ pub enum ImportCommands {
         /// Path to the exported account directory
         path: String,
     },
+    /// Create user accounts from a CSV file
+    ///
+    /// The file must have a header row with the columns email, password,
+    /// quota and display_name. Quotas are in bytes or use a K, M, G or T
+    /// suffix.
+    Users {
+        /// Generate passwords for rows that leave the password empty
+        #[clap(short, long)]
+        generate_passwords: bool,
+
+        /// Validate the file and report what would be created without creating anything
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Path to the CSV file, or '-' for stdin
+        path: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -479,15 +547,32 @@ pub enum QueueCommands {
         #[clap(short, long)]
         #[arg(value_parser = parse_datetime)]
         after: Option<DateTime>,
+        /// Filter by recipient domain
+        #[clap(short, long)]
+        domain: Option<String>,
+        /// Only show messages queued for longer than this (e.g. 30m, 12h, 2d)
+        #[clap(short, long)]
+        #[arg(value_parser = parse_age)]
+        older_than: Option<Duration>,
+        /// Only show messages with a recipient in this status
+        #[clap(long)]
+        #[clap(value_enum)]
+        status: Option<QueueStatus>,
         /// Number of items to show per page
         #[clap(short, long)]
         page_size: Option<usize>,
+        /// Print the messages as JSON
+        #[clap(long)]
+        json: bool,
     },
 
-    /// Displays details about a queued message
+    /// Displays the envelope and delivery schedule of queued messages
     Status {
         #[clap(required = true)]
         ids: Vec<String>,
+        /// Print the messages as JSON
+        #[clap(long)]
+        json: bool,
     },
 
     /// Reschedule delivery
@@ -506,15 +591,19 @@ pub enum QueueCommands {
         #[clap(short, long)]
         #[arg(value_parser = parse_datetime)]
         after: Option<DateTime>,
-        /// Schedule delivery at a specific time
+        /// Schedule delivery at a specific time, defaults to now
         #[clap(short, long)]
         #[arg(value_parser = parse_datetime)]
         time: Option<DateTime>,
+        /// Print the outcome as JSON
+        #[clap(long)]
+        json: bool,
         // Reschedule one or multiple message ids
         ids: Vec<String>,
     },
 
-    /// Cancel delivery
+    /// Cancel delivery and delete queued messages
+    #[clap(alias = "delete")]
     Cancel {
         /// Apply to messages matching a sender address
         #[clap(short, long)]
@@ -530,11 +619,26 @@ pub enum QueueCommands {
         #[clap(short, long)]
         #[arg(value_parser = parse_datetime)]
         after: Option<DateTime>,
+        /// Print the outcome as JSON
+        #[clap(long)]
+        json: bool,
         // Cancel one or multiple message ids
         ids: Vec<String>,
     },
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum QueueStatus {
+    /// Waiting for delivery
+    Scheduled,
+    /// Delivered
+    Delivered,
+    /// Delivery failed temporarily
+    Tempfail,
+    /// Delivery failed permanently
+    Permfail,
+}
+
 #[derive(Subcommand)]
 pub enum ReportCommands {
     /// Shows reports queued for delivery
@@ -574,6 +678,23 @@ pub enum ReportFormat {
     Tls,
 }
 
+fn parse_age(arg: &str) -> Result<Duration, &'static str> {
+    let (value, unit) = arg.split_at(arg.find(|c: char| !c.is_ascii_digit()).unwrap_or(arg.len()));
+    let multiplier = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err("Invalid age unit, expected one of s, m, h or d"),
+    };
+    value
+        .parse::<u64>()
+        .map_err(|_| "Failed to parse age")?
+        .checked_mul(multiplier)
+        .map(Duration::from_secs)
+        .ok_or("Age is too large")
+}
+
 fn parse_datetime(arg: &str) -> Result<DateTime, &'static str> {
     if arg.contains('T') {
         DateTime::parse_rfc3339(arg).ok_or("Failed to parse RFC3339 datetime")
@@ -582,3 +703,71 @@ fn parse_datetime(arg: &str) -> Result<DateTime, &'static str> {
             .ok_or("Failed to parse RFC3339 datetime")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Descriptions of every visible subcommand, at any depth
+    fn visible_abouts(command: &clap::Command, abouts: &mut Vec<String>) {
+        for subcommand in command.get_subcommands().filter(|c| !c.is_hide_set()) {
+            if let Some(about) = subcommand.get_about() {
+                abouts.push(about.to_string());
+            }
+            visible_abouts(subcommand, abouts);
+        }
+    }
+
+    #[test]
+    fn parse_age_units() {
+        assert_eq!(parse_age("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_age("15m"), Ok(Duration::from_secs(15 * 60)));
+        assert_eq!(parse_age("2d"), Ok(Duration::from_secs(2 * 24 * 60 * 60)));
+        assert!(parse_age("1w").is_err());
+        assert!(parse_age("h").is_err());
+
+        // Ages that do not fit in seconds are rejected instead of wrapping
+        let max = u64::MAX.to_string();
+        assert_eq!(parse_age(&max), Ok(Duration::from_secs(u64::MAX)));
+        assert_eq!(parse_age(&format!("{max}m")), Err("Age is too large"));
+        assert_eq!(
+            parse_age(&format!("{}d", u64::MAX / (24 * 60 * 60) + 1)),
+            Err("Age is too large")
+        );
+    }
+
+    #[test]
+    fn completions_for_each_shell() {
+        let mut abouts = Vec::new();
+        visible_abouts(&Cli::command(), &mut abouts);
+        assert!(abouts.contains(&"Create a new user account".to_string()));
+
+        for shell in [
+            Shell::Bash,
+            Shell::Zsh,
+            Shell::Fish,
+            Shell::PowerShell,
+            Shell::Elvish,
+        ] {
+            let mut out = Vec::new();
+            Cli::completions(shell, &mut out);
+            let script = String::from_utf8(out).unwrap();
+
+            assert!(script.contains(env!("CARGO_PKG_NAME")), "{shell}");
+            assert!(
+                !script.contains("Generate shell completion scripts"),
+                "{shell} lists the hidden completions command"
+            );
+
+            // Bash completions carry no descriptions
+            if shell != Shell::Bash {
+                for about in &abouts {
+                    assert!(
+                        script.contains(about.as_str()),
+                        "{shell} is missing {about:?}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -15,7 +15,7 @@ use reqwest::{Method, StatusCode, header::AUTHORIZATION};
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use jmap_client::client::Credentials;
 
-use super::{cli::Client, host, is_localhost, UnwrapResult};
+use super::{cli::Client, host, is_localhost};
 
 /// Response wrapper for API responses
 #[derive(Deserialize)]
@@ -76,6 +76,23 @@ impl Client {
         url: &str,
         body: Option<B>,
     ) -> Option<R> {
+        self.send_request(method, url, body)
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("❌ {err}");
+                std::process::exit(1);
+            })
+    }
+
+    /// Make an HTTP request, returning failures to the caller instead of exiting
+    ///
+    /// Authentication failures still exit, as no further request can succeed.
+    pub async fn send_request<R: DeserializeOwned, B: Serialize>(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<B>,
+    ) -> Result<Option<R>, String> {
         let full_url = if self.url.ends_with('/') && url.starts_with('/') {
             // Remove duplicate slash
             format!("{}{}", self.url.trim_end_matches('/'), url)
@@ -88,7 +105,7 @@ impl Client {
         };
 
         // Log the request for debugging
-        println!("Making {} request to: {}", method, full_url);
+        eprintln!("Making {} request to: {}", method, full_url);
 
         let mut request = reqwest::Client::builder()
             .danger_accept_invalid_certs(is_localhost(&full_url))
@@ -106,57 +123,61 @@ impl Client {
 
         if let Some(body) = body {
             let serialized_body = serde_json::to_string(&body)
-                .unwrap_result("serialize request body");
+                .map_err(|err| format!("Failed to serialize request body: {err}"))?;
             request = request
                 .header("Content-Type", "application/json")
                 .body(serialized_body);
         }
 
-        let response = request.send().await.unwrap_result("send HTTP request");
+        let response = request
+            .send()
+            .await
+            .map_err(|err| format!("Failed to send HTTP request: {err}"))?;
 
         match response.status() {
             StatusCode::OK => {
-                println!("✓ Request successful");
+                eprintln!("✓ Request successful");
             }
             StatusCode::NOT_FOUND => {
-                println!("⚠ Resource not found");
-                return None;
+                eprintln!("⚠ Resource not found");
+                return Ok(None);
             }
             StatusCode::UNAUTHORIZED => {
                 eprintln!("❌ Authentication failed. Make sure the credentials are correct and that the account has administrator rights.");
                 std::process::exit(1);
             }
             status => {
-                let error_text = response.text().await.unwrap_result("fetch error text");
-                eprintln!("❌ Request failed with status {}: {}", status, error_text);
-                std::process::exit(1);
+                let error_text = response
+                    .text()
+                    .await
+                    .map_err(|err| format!("Failed to fetch error text: {err}"))?;
+                return Err(format!("Request failed with status {status}: {error_text}"));
             }
         }
 
-        let bytes = response.bytes().await.unwrap_result("fetch response bytes");
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|err| format!("Failed to fetch response bytes: {err}"))?;
 
         // Try to parse as our Response wrapper first
         match serde_json::from_slice::<Response<R>>(&bytes) {
             Ok(Response::Data { data }) => {
-                println!("✓ Successfully parsed response data");
-                Some(data)
-            }
-            Ok(Response::Error(error)) => {
-                eprintln!("❌ API error: {}", error);
-                std::process::exit(1);
+                eprintln!("✓ Successfully parsed response data");
+                Ok(Some(data))
             }
+            Ok(Response::Error(error)) => Err(format!("API error: {error}")),
             Err(_) => {
                 // If that fails, try to parse directly as R
                 match serde_json::from_slice::<R>(&bytes) {
                     Ok(data) => {
-                        println!("✓ Successfully parsed direct response");
-                        Some(data)
-                    }
-                    Err(parse_err) => {
-                        eprintln!("❌ Failed to parse response: {}", parse_err);
-                        eprintln!("Response body: {}", String::from_utf8_lossy(&bytes));
-                        std::process::exit(1);
+                        eprintln!("✓ Successfully parsed direct response");
+                        Ok(Some(data))
                     }
+                    Err(parse_err) => Err(format!(
+                        "Failed to parse response: {parse_err}\nResponse body: {}",
+                        String::from_utf8_lossy(&bytes)
+                    )),
                 }
             }
         }
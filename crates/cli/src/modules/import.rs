@@ -26,14 +26,18 @@ use mail_parser::mailbox::{
     maildir,
     mbox::{self, MessageIterator},
 };
-use rand::Rng;
-use serde::de::DeserializeOwned;
+use pwhash::sha512_crypt;
+use rand::{Rng, distr::Alphanumeric};
+use reqwest::Method;
+use serde::{Deserialize, de::DeserializeOwned};
 use tokio::{fs::File, io::AsyncReadExt};
 
 use crate::modules::{RETRY_ATTEMPTS, UnwrapResult, name_to_id};
 
 use super::{
+    Principal, Type,
     cli::{Client, ImportCommands, MailboxFormat},
+    dkim::is_valid_domain,
     export::{
         fetch_emails, fetch_identities, fetch_mailboxes, fetch_sieve_scripts,
         fetch_vacation_responses,
@@ -61,8 +65,69 @@ struct Message {
     internal_date: u64,
     contents: Vec<u8>,
 }
+
+/// Row of a user import file
+#[derive(Debug, Deserialize)]
+struct UserRecord {
+    email: String,
+    #[serde(default)]
+    password: String,
+    #[serde(default)]
+    quota: String,
+    #[serde(default)]
+    display_name: String,
+}
+
+#[derive(Debug)]
+struct NewUser {
+    email: String,
+    password: String,
+    generated_password: bool,
+    quota: Option<u64>,
+    display_name: Option<String>,
+}
+
+/// Outcome of a user import, rows are identified by their line number
+#[derive(Debug, Default)]
+pub struct UserImport {
+    pub created: Vec<String>,
+    pub skipped: Vec<String>,
+    pub failed: Vec<(u64, String)>,
+    pub generated_passwords: Vec<(String, String)>,
+}
+
+const GENERATED_PASSWORD_LEN: usize = 16;
+
 impl ImportCommands {
     pub async fn exec(self, client: Client) {
+        if let ImportCommands::Users {
+            generate_passwords,
+            dry_run,
+            path,
+        } = &self
+        {
+            let result = client
+                .import_users(&read_file(path), *generate_passwords, *dry_run)
+                .await
+                .unwrap_result("read CSV file");
+
+            if !result.generated_passwords.is_empty() {
+                write_passwords(&result.generated_passwords)
+                    .unwrap_result("write generated passwords");
+            }
+            eprintln!(
+                "\n{} {}, {} skipped, {} failed.",
+                result.created.len(),
+                if *dry_run { "to create" } else { "created" },
+                result.skipped.len(),
+                result.failed.len()
+            );
+            if !result.failed.is_empty() {
+                std::process::exit(1);
+            }
+            return;
+        }
+
         let mut client = client.into_jmap_client().await;
 
         match self {
@@ -443,10 +508,222 @@ impl ImportCommands {
                 import_identities(&client, &path).await;
                 import_vacation_responses(&client, &path).await;
             }
+            ImportCommands::Users { .. } => unreachable!(),
         }
     }
 }
 
+impl Client {
+    /// Create the user accounts listed in a CSV file
+    ///
+    /// Invalid rows are reported and skipped, as are accounts that already
+    /// exist. With `dry_run` the file is validated and checked against the
+    /// server, but nothing is created.
+    pub async fn import_users(
+        &self,
+        data: &[u8],
+        generate_passwords: bool,
+        dry_run: bool,
+    ) -> Result<UserImport, csv::Error> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(data);
+        let headers = reader.headers()?.clone();
+        if !headers.iter().any(|header| header == "email") {
+            return Err(csv::Error::from(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "missing 'email' column",
+            )));
+        }
+
+        let mut result = UserImport::default();
+        let mut seen = HashMap::new();
+        for record in reader.records() {
+            let (line, user) = match record {
+                Ok(record) => (
+                    record.position().map_or(0, |pos| pos.line()),
+                    record
+                        .deserialize::<UserRecord>(Some(&headers))
+                        .map_err(|err| err.to_string())
+                        .and_then(|record| record.validate(generate_passwords)),
+                ),
+                Err(err) => (
+                    err.position().map_or(0, |pos| pos.line()),
+                    Err(err.to_string()),
+                ),
+            };
+            let user = match user {
+                Ok(user) => user,
+                Err(err) => {
+                    eprintln!("Line {line}: {err}");
+                    result.failed.push((line, err));
+                    continue;
+                }
+            };
+            if let Some(first_line) = seen.insert(user.email.to_lowercase(), line) {
+                let err = format!("duplicate of line {first_line}");
+                eprintln!("Line {line}: {err}");
+                result.failed.push((line, err));
+                continue;
+            }
+
+            match self
+                .send_request::<Principal, String>(
+                    Method::GET,
+                    &format!("/api/principal/{}", user.email),
+                    None,
+                )
+                .await
+            {
+                Ok(None) => (),
+                Ok(Some(_)) => {
+                    eprintln!(
+                        "Line {line}: account {} already exists, skipping",
+                        user.email
+                    );
+                    result.skipped.push(user.email);
+                    continue;
+                }
+                Err(err) => {
+                    eprintln!("Line {line}: {err}");
+                    result.failed.push((line, err));
+                    continue;
+                }
+            }
+
+            if !dry_run {
+                let principal = match user.principal() {
+                    Ok(principal) => principal,
+                    Err(err) => {
+                        eprintln!("Line {line}: {err}");
+                        result.failed.push((line, err));
+                        continue;
+                    }
+                };
+                match self
+                    .send_request::<u32, _>(Method::POST, "/api/principal", Some(principal))
+                    .await
+                {
+                    Ok(Some(_)) => (),
+                    Ok(None) => {
+                        let err = "principal endpoint not found".to_string();
+                        eprintln!("Line {line}: {err}");
+                        result.failed.push((line, err));
+                        continue;
+                    }
+                    Err(err) => {
+                        eprintln!("Line {line}: {err}");
+                        result.failed.push((line, err));
+                        continue;
+                    }
+                }
+                if user.generated_password {
+                    result
+                        .generated_passwords
+                        .push((user.email.clone(), user.password));
+                }
+            }
+            result.created.push(user.email);
+        }
+
+        Ok(result)
+    }
+}
+
+impl UserRecord {
+    fn validate(self, generate_passwords: bool) -> Result<NewUser, String> {
+        if !is_valid_email(&self.email) {
+            return Err(format!("invalid e-mail address {:?}", self.email));
+        }
+        let quota = parse_quota(&self.quota)?;
+        let (password, generated_password) = if !self.password.is_empty() {
+            (self.password, false)
+        } else if generate_passwords {
+            (
+                rand::rng()
+                    .sample_iter(Alphanumeric)
+                    .take(GENERATED_PASSWORD_LEN)
+                    .map(char::from)
+                    .collect(),
+                true,
+            )
+        } else {
+            return Err("missing password, use --generate-passwords to create one".to_string());
+        };
+
+        Ok(NewUser {
+            email: self.email,
+            password,
+            generated_password,
+            quota,
+            display_name: Some(self.display_name).filter(|name| !name.is_empty()),
+        })
+    }
+}
+
+impl NewUser {
+    fn principal(&self) -> Result<Principal, String> {
+        Ok(Principal {
+            typ: Type::Individual.into(),
+            quota: self.quota,
+            name: self.email.clone().into(),
+            secrets: vec![
+                sha512_crypt::hash(&self.password)
+                    .map_err(|err| format!("failed to hash password: {err}"))?,
+            ],
+            emails: vec![self.email.clone()],
+            description: self.display_name.clone(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Print generated passwords to stdout as CSV so they can be distributed
+fn write_passwords(passwords: &[(String, String)]) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_writer(io::stdout());
+    writer.write_record(["email", "password"])?;
+    for (email, password) in passwords {
+        writer.write_record([email, password])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn is_valid_email(email: &str) -> bool {
+    email.rsplit_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty()
+            && !local.contains(|c: char| c == '@' || c == ',' || c.is_whitespace())
+            && is_valid_domain(domain)
+    })
+}
+
+/// Parse a quota in bytes, with an optional binary K, M, G or T suffix
+fn parse_quota(quota: &str) -> Result<Option<u64>, String> {
+    if quota.is_empty() {
+        return Ok(None);
+    }
+
+    let (amount, unit) = quota.split_at(
+        quota
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(quota.len()),
+    );
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        _ => return Err(format!("invalid quota {quota:?}")),
+    };
+    amount
+        .parse::<u64>()
+        .ok()
+        .and_then(|amount| amount.checked_mul(multiplier))
+        .map(Some)
+        .ok_or_else(|| format!("invalid quota {quota:?}"))
+}
+
 async fn import_mailboxes(
     client: &jmap_client::client::Client,
     path: &Path,
@@ -473,20 +750,19 @@ async fn import_mailboxes(
     for (path, mailbox) in build_mailbox_tree(&mailboxes) {
         let id = mailbox.id().unwrap_result("obtain mailbox id");
         // Find existing mailbox based on role
-        if !matches!(mailbox.role(), Role::None) {
-            if let Some(existing_mailbox) = existing_mailboxes
+        if !matches!(mailbox.role(), Role::None)
+            && let Some(existing_mailbox) = existing_mailboxes
                 .iter()
                 .find(|m| m.role() == mailbox.role())
-            {
-                id_mappings.insert(
-                    id.to_string(),
-                    existing_mailbox
-                        .id()
-                        .unwrap_result("obtain mailbox id")
-                        .to_string(),
-                );
-                continue;
-            }
+        {
+            id_mappings.insert(
+                id.to_string(),
+                existing_mailbox
+                    .id()
+                    .unwrap_result("obtain mailbox id")
+                    .to_string(),
+            );
+            continue;
         }
 
         // Find existing mailbox by name
@@ -529,7 +805,7 @@ async fn import_mailboxes(
                 create_request.sort_order(mailbox.sort_order());
             }
             if let Some(acls) = mailbox.acl() {
-                create_request.acls(acls.clone().into_iter());
+                create_request.acls(acls.clone());
             }
             if mailbox.is_subscribed() {
                 create_request.is_subscribed(true);
@@ -1038,3 +1314,131 @@ impl Iterator for Mailbox {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::modules::mock::MockServer;
+
+    fn created_principals(server: &MockServer) -> Vec<Value> {
+        server
+            .requests()
+            .into_iter()
+            .filter(|request| request.method == "POST" && request.path == "/api/principal")
+            .map(|request| serde_json::from_str(&request.body).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn import_valid_file() {
+        let server = MockServer::start(vec![("POST", "/api/principal", json!(1))]).await;
+        let csv = "email,password,quota,display_name\n\
+                   john@example.org,secret,1G,John Doe\n\
+                   jane@example.org,,,\n";
+
+        let result = server
+            .client()
+            .import_users(csv.as_bytes(), true, false)
+            .await
+            .unwrap();
+        assert_eq!(result.created, ["john@example.org", "jane@example.org"]);
+        assert!(result.skipped.is_empty());
+        assert!(result.failed.is_empty());
+        assert_eq!(result.generated_passwords.len(), 1);
+        assert_eq!(result.generated_passwords[0].0, "jane@example.org");
+        assert_eq!(
+            result.generated_passwords[0].1.len(),
+            GENERATED_PASSWORD_LEN
+        );
+
+        let principals = created_principals(&server);
+        assert_eq!(principals.len(), 2);
+        assert_eq!(principals[0]["name"], "john@example.org");
+        assert_eq!(principals[0]["emails"], json!(["john@example.org"]));
+        assert_eq!(principals[0]["quota"], 1u64 << 30);
+        assert_eq!(principals[0]["description"], "John Doe");
+        assert!(
+            principals[0]["secrets"][0]
+                .as_str()
+                .unwrap()
+                .starts_with("$6$")
+        );
+        assert!(principals[1].get("quota").is_none());
+    }
+
+    #[tokio::test]
+    async fn import_reports_malformed_rows() {
+        let server = MockServer::start(vec![("POST", "/api/principal", json!(1))]).await;
+        let csv = "email,password,quota,display_name\n\
+                   john@example.org,secret,,\n\
+                   not-an-email,secret,,\n\
+                   jane@example.org,secret,12X,\n\
+                   bob@example.org,secret\n\
+                   JOHN@example.org,secret,,\n\
+                   ann@example.org,,,\n\
+                   tom@example.org,secret,5M,Tom\n";
+
+        let result = server
+            .client()
+            .import_users(csv.as_bytes(), false, false)
+            .await
+            .unwrap();
+        assert_eq!(result.created, ["john@example.org", "tom@example.org"]);
+        assert_eq!(
+            result
+                .failed
+                .iter()
+                .map(|(line, _)| *line)
+                .collect::<Vec<_>>(),
+            [3, 4, 5, 6, 7]
+        );
+        assert_eq!(result.failed[3].1, "duplicate of line 2");
+        assert_eq!(created_principals(&server).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn import_dry_run_creates_nothing() {
+        let server = MockServer::start(vec![
+            ("POST", "/api/principal", json!(1)),
+            (
+                "GET",
+                "/api/principal/jane@example.org",
+                json!({"type": "individual", "name": "jane@example.org"}),
+            ),
+        ])
+        .await;
+        let csv = "email,password,quota,display_name\n\
+                   john@example.org,secret,100M,John\n\
+                   jane@example.org,secret,,\n";
+
+        let result = server
+            .client()
+            .import_users(csv.as_bytes(), false, true)
+            .await
+            .unwrap();
+        assert_eq!(result.created, ["john@example.org"]);
+        assert_eq!(result.skipped, ["jane@example.org"]);
+        assert!(result.failed.is_empty());
+        assert!(created_principals(&server).is_empty());
+        assert!(
+            server
+                .requests()
+                .iter()
+                .all(|request| request.method == "GET")
+        );
+    }
+
+    #[test]
+    fn parse_quotas() {
+        assert_eq!(parse_quota(""), Ok(None));
+        assert_eq!(parse_quota("1024"), Ok(Some(1024)));
+        assert_eq!(parse_quota("2K"), Ok(Some(2048)));
+        assert_eq!(parse_quota("5 MiB"), Ok(Some(5 << 20)));
+        assert_eq!(parse_quota("1g"), Ok(Some(1 << 30)));
+        assert!(parse_quota("12X").is_err());
+        assert!(parse_quota("-1").is_err());
+        assert!(parse_quota("99999999999T").is_err());
+    }
+}
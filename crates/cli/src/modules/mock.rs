@@ -0,0 +1,142 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! Minimal management API server for tests
+//!
+//! Serves canned JSON responses over plain HTTP/1.1 and records every
+//! request it receives so tests can assert on what the CLI sent.
+
+use std::sync::{Arc, Mutex};
+
+use jmap_client::client::Credentials;
+use serde_json::Value;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+use super::cli::Client;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: String,
+}
+
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Serve `routes` given as `(method, path, data)`
+    ///
+    /// Paths are matched without their query string and `data` is wrapped
+    /// in the `{"data": ...}` envelope of the management API. Unknown routes
+    /// return 404.
+    pub async fn start(routes: Vec<(&'static str, &'static str, Value)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let routes = Arc::new(routes);
+
+        let handle = tokio::spawn({
+            let requests = requests.clone();
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(handle_conn(stream, routes.clone(), requests.clone()));
+                }
+            }
+        });
+
+        MockServer {
+            url,
+            requests,
+            handle,
+        }
+    }
+
+    /// Client pointing at this server
+    pub fn client(&self) -> Client {
+        Client {
+            url: self.url.clone(),
+            credentials: Credentials::basic("admin", "secret"),
+            timeout: Some(5),
+        }
+    }
+
+    /// Requests received so far
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn handle_conn(
+    mut stream: TcpStream,
+    routes: Arc<Vec<(&'static str, &'static str, Value)>>,
+    requests: Arc<Mutex<Vec<Request>>>,
+) {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).into_owned();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while buf.len() < header_end + content_length {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+
+    let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+    let request = Request {
+        method: request_line.next().unwrap_or_default().to_string(),
+        path: request_line.next().unwrap_or_default().to_string(),
+        body: String::from_utf8_lossy(&buf[header_end..header_end + content_length]).into_owned(),
+    };
+
+    let path = request.path.split('?').next().unwrap_or_default();
+    let response = routes
+        .iter()
+        .find(|(method, route, _)| *method == request.method && *route == path)
+        .map(|(_, _, data)| serde_json::json!({ "data": data }).to_string());
+    requests.lock().unwrap().push(request);
+
+    let response = match response {
+        Some(body) => format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        ),
+        None => {
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+        }
+    };
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
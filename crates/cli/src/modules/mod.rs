@@ -22,6 +22,8 @@ pub mod export;
 pub mod group;
 pub mod import;
 pub mod list;
+#[cfg(test)]
+pub mod mock;
 pub mod queue;
 pub mod report;
 
@@ -36,7 +38,7 @@ pub struct Principal {
     pub typ: Option<Type>,
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub quota: Option<u32>,
+    pub quota: Option<u64>,
 
     #[serde(rename = "usedQuota")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -242,6 +244,15 @@ pub fn host(url: &str) -> Option<&str> {
         .map(|(_, url)| url.split_once('/').map_or(url, |(host, _)| host))
 }
 
+/// Append an encoded `query` to `path`, if it has any parameters
+pub fn with_query(path: String, query: String) -> String {
+    if query.is_empty() {
+        path
+    } else {
+        format!("{path}?{query}")
+    }
+}
+
 pub fn is_localhost(url: &str) -> bool {
     host(url).is_some_and(|host| {
         let host = host.rsplit_once(':').map_or(host, |(host, _)| host);
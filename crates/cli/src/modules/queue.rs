@@ -4,23 +4,29 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use super::{
-    List,
-    cli::{Client, QueueCommands},
+    List, UnwrapResult,
+    cli::{Client, QueueCommands, QueueStatus},
+    with_query,
 };
 use console::Term;
 use human_size::{Byte, SpecificSize};
 use mail_parser::DateTime;
 use prettytable::{Attr, Cell, Row, Table, format::Alignment};
 use reqwest::Method;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub struct Message {
     pub id: u64,
     pub return_path: String,
     pub recipients: Vec<Recipient>,
-    #[serde(deserialize_with = "deserialize_datetime")]
+    #[serde(
+        deserialize_with = "deserialize_datetime",
+        serialize_with = "serialize_datetime"
+    )]
     pub created: DateTime,
     pub size: usize,
     #[serde(default)]
@@ -30,23 +36,35 @@ pub struct Message {
     pub blob_hash: String,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub struct Recipient {
     pub address: String,
     pub status: Status,
     pub queue: String,
     pub retry_num: u32,
-    #[serde(deserialize_with = "deserialize_maybe_datetime", default)]
+    #[serde(
+        deserialize_with = "deserialize_maybe_datetime",
+        serialize_with = "serialize_maybe_datetime",
+        default
+    )]
     pub next_retry: Option<DateTime>,
-    #[serde(deserialize_with = "deserialize_maybe_datetime", default)]
+    #[serde(
+        deserialize_with = "deserialize_maybe_datetime",
+        serialize_with = "serialize_maybe_datetime",
+        default
+    )]
     pub next_notify: Option<DateTime>,
-    #[serde(deserialize_with = "deserialize_maybe_datetime", default)]
+    #[serde(
+        deserialize_with = "deserialize_maybe_datetime",
+        serialize_with = "serialize_maybe_datetime",
+        default
+    )]
     pub expires: Option<DateTime>,
     #[serde(default)]
     pub orcpt: Option<String>,
 }
 
-#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Status {
     #[serde(rename = "scheduled")]
     Scheduled,
@@ -58,6 +76,24 @@ pub enum Status {
     PermanentFailure(String),
 }
 
+/// Filters applied when listing queued messages
+#[derive(Debug, Default)]
+pub struct QueueFilter {
+    pub sender: Option<String>,
+    pub rcpt: Option<String>,
+    pub domain: Option<String>,
+    pub before: Option<DateTime>,
+    pub after: Option<DateTime>,
+    pub older_than: Option<Duration>,
+    pub status: Option<QueueStatus>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Outcome {
+    succeeded: Vec<String>,
+    failed: Vec<String>,
+}
+
 impl QueueCommands {
     pub async fn exec(self, client: Client) {
         match self {
@@ -66,14 +102,33 @@ impl QueueCommands {
                 rcpt,
                 before,
                 after,
+                domain,
+                older_than,
+                status,
                 page_size,
+                json,
             } => {
+                let messages = client
+                    .list_queued(&QueueFilter {
+                        sender,
+                        rcpt,
+                        domain,
+                        before,
+                        after,
+                        older_than,
+                        status,
+                    })
+                    .await;
+                if json {
+                    print_json(&messages);
+                    return;
+                }
+
                 let stdout = Term::buffered_stdout();
-                let ids = client.query_messages(&sender, &rcpt, &before, &after).await;
-                let ids_len = ids.len();
+                let messages_len = messages.len();
                 let page_size = page_size.map(|p| std::cmp::max(p, 1)).unwrap_or(20);
-                let pages_total = (ids_len as f64 / page_size as f64).ceil() as usize;
-                for (page_num, chunk) in ids.chunks(page_size).enumerate() {
+                let pages_total = (messages_len as f64 / page_size as f64).ceil() as usize;
+                for (page_num, chunk) in messages.chunks(page_size).enumerate() {
                     // Build table
                     let mut table = Table::new();
                     table.add_row(Row::new(
@@ -82,15 +137,7 @@ impl QueueCommands {
                             .map(|p| Cell::new(p).with_style(Attr::Bold))
                             .collect(),
                     ));
-                    for id in chunk {
-                        let message = client
-                            .http_request::<Message, String>(
-                                Method::GET,
-                                &format!("/api/queue/messages/{id}"),
-                                None,
-                            )
-                            .await;
-
+                    for message in chunk {
                         let mut rcpts = String::new();
                         let mut deliver_at = i64::MAX;
                         let mut deliver_pos = 0;
@@ -113,7 +160,7 @@ impl QueueCommands {
                         }
 
                         let mut cells = Vec::new();
-                        cells.push(Cell::new(&format!("{id:X}")));
+                        cells.push(Cell::new(&format!("{:X}", message.id)));
                         cells.push(if deliver_at != i64::MAX {
                             Cell::new(
                                 &message.recipients[deliver_pos]
@@ -149,17 +196,23 @@ impl QueueCommands {
                         }
                     }
                 }
-                eprintln!("\n{ids_len} queued message(s) found.")
+                eprintln!("\n{messages_len} queued message(s) found.")
             }
-            QueueCommands::Status { ids } => {
+            QueueCommands::Status { ids, json } => {
+                if json {
+                    let mut messages = Vec::with_capacity(ids.len());
+                    for (uid, id) in parse_ids(&ids).into_iter().zip(ids) {
+                        match client.get_queued(uid).await {
+                            Some(message) => messages.push(message),
+                            None => eprintln!("Message {id} not found."),
+                        }
+                    }
+                    print_json(&messages);
+                    return;
+                }
+
                 for (uid, id) in parse_ids(&ids).into_iter().zip(ids) {
-                    let message = client
-                        .try_http_request::<Message, String>(
-                            Method::GET,
-                            &format!("/api/queue/messages/{uid}"),
-                            None,
-                        )
-                        .await;
+                    let message = client.get_queued(uid).await;
                     let mut table = Table::new();
                     table.add_row(Row::new(vec![
                         Cell::new("ID").with_style(Attr::Bold),
@@ -256,6 +309,7 @@ impl QueueCommands {
                 before,
                 after,
                 time,
+                json,
                 ids,
             } => {
                 let (parsed_ids, ids) = if ids.is_empty() {
@@ -277,34 +331,31 @@ impl QueueCommands {
                     std::process::exit(1);
                 }
 
-                let mut success_count = 0;
-                let mut failed_list = vec![];
-
-                for id in parsed_ids {
-                    let mut query =
-                        form_urlencoded::Serializer::new(format!("/api/queue/messages/{id}"));
-
-                    if let Some(filter) = &domain {
-                        query.append_pair("filter", filter);
-                    }
-                    if let Some(at) = time {
-                        query.append_pair("at", &at.to_rfc3339());
-                    }
-
+                let mut outcome = Outcome::default();
+                for (id, hex_id) in parsed_ids.into_iter().zip(ids) {
                     if client
-                        .try_http_request::<bool, String>(Method::PATCH, &query.finish(), None)
+                        .retry_queued(id, domain.as_deref(), time.as_ref())
                         .await
-                        .unwrap_or(false)
                     {
-                        success_count += 1;
+                        outcome.succeeded.push(hex_id);
                     } else {
-                        failed_list.push(id.to_string());
+                        outcome.failed.push(hex_id);
                     }
                 }
 
-                eprint!("\nSuccessfully rescheduled {success_count} message(s).");
-                if !failed_list.is_empty() {
-                    eprint!(" Unable to reschedule id(s): {}.", failed_list.join(", "));
+                if json {
+                    print_json(&outcome);
+                    return;
+                }
+                eprint!(
+                    "\nSuccessfully rescheduled {} message(s).",
+                    outcome.succeeded.len()
+                );
+                if !outcome.failed.is_empty() {
+                    eprint!(
+                        " Unable to reschedule id(s): {}.",
+                        outcome.failed.join(", ")
+                    );
                 }
                 eprintln!();
             }
@@ -313,6 +364,7 @@ impl QueueCommands {
                 rcpt,
                 before,
                 after,
+                json,
                 ids,
             } => {
                 let (parsed_ids, ids) = if ids.is_empty() {
@@ -333,33 +385,27 @@ impl QueueCommands {
                     std::process::exit(1);
                 }
 
-                let mut success_count = 0;
-                let mut failed_list = vec![];
-
-                for id in parsed_ids {
-                    let mut query =
-                        form_urlencoded::Serializer::new(format!("/api/queue/messages/{id}"));
-
-                    if let Some(filter) = &rcpt {
-                        query.append_pair("filter", filter);
-                    }
-
-                    if client
-                        .try_http_request::<bool, String>(Method::DELETE, &query.finish(), None)
-                        .await
-                        .unwrap_or(false)
-                    {
-                        success_count += 1;
+                let mut outcome = Outcome::default();
+                for (id, hex_id) in parsed_ids.into_iter().zip(ids) {
+                    if client.cancel_queued(id, rcpt.as_deref()).await {
+                        outcome.succeeded.push(hex_id);
                     } else {
-                        failed_list.push(id.to_string());
+                        outcome.failed.push(hex_id);
                     }
                 }
 
-                eprint!("\nCancelled delivery of {success_count} message(s).");
-                if !failed_list.is_empty() {
+                if json {
+                    print_json(&outcome);
+                    return;
+                }
+                eprint!(
+                    "\nCancelled delivery of {} message(s).",
+                    outcome.succeeded.len()
+                );
+                if !outcome.failed.is_empty() {
                     eprint!(
                         " Unable to cancel delivery for id(s): {}.",
-                        failed_list.join(", ")
+                        outcome.failed.join(", ")
                     );
                 }
                 eprintln!();
@@ -369,6 +415,80 @@ impl QueueCommands {
 }
 
 impl Client {
+    /// Fetch the queued messages matching `filter`
+    ///
+    /// The server narrows the list by sender, recipient and due date; the
+    /// domain, age and status filters are applied to the fetched messages.
+    pub async fn list_queued(&self, filter: &QueueFilter) -> Vec<Message> {
+        let rcpt = filter.rcpt.clone().or_else(|| filter.domain.clone());
+        let ids = self
+            .query_messages(&filter.sender, &rcpt, &filter.before, &filter.after)
+            .await;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+
+        let mut messages = Vec::with_capacity(ids.len());
+        for id in ids {
+            // Messages may be delivered while they are being listed
+            if let Some(message) = self.get_queued(id).await
+                && filter.matches(&message, now)
+            {
+                messages.push(message);
+            }
+        }
+        messages
+    }
+
+    pub async fn get_queued(&self, id: u64) -> Option<Message> {
+        self.try_http_request::<Message, String>(
+            Method::GET,
+            &format!("/api/queue/messages/{id}"),
+            None,
+        )
+        .await
+    }
+
+    /// Reschedule delivery of message `id` at `time`, or immediately
+    pub async fn retry_queued(
+        &self,
+        id: u64,
+        domain: Option<&str>,
+        time: Option<&DateTime>,
+    ) -> bool {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        if let Some(filter) = domain {
+            query.append_pair("filter", filter);
+        }
+        if let Some(at) = time {
+            query.append_pair("at", &at.to_rfc3339());
+        }
+
+        self.try_http_request::<bool, String>(
+            Method::PATCH,
+            &with_query(format!("/api/queue/messages/{id}"), query.finish()),
+            None,
+        )
+        .await
+        .unwrap_or(false)
+    }
+
+    /// Cancel delivery of message `id`, deleting it once no recipients remain
+    pub async fn cancel_queued(&self, id: u64, rcpt: Option<&str>) -> bool {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        if let Some(filter) = rcpt {
+            query.append_pair("filter", filter);
+        }
+
+        self.try_http_request::<bool, String>(
+            Method::DELETE,
+            &with_query(format!("/api/queue/messages/{id}"), query.finish()),
+            None,
+        )
+        .await
+        .unwrap_or(false)
+    }
+
     async fn query_messages(
         &self,
         from: &Option<String>,
@@ -376,7 +496,7 @@ impl Client {
         before: &Option<DateTime>,
         after: &Option<DateTime>,
     ) -> Vec<u64> {
-        let mut query = form_urlencoded::Serializer::new("/api/queue/messages".to_string());
+        let mut query = form_urlencoded::Serializer::new(String::new());
 
         if let Some(sender) = from {
             query.append_pair("from", sender);
@@ -391,9 +511,60 @@ impl Client {
             query.append_pair("after", &after.to_rfc3339());
         }
 
-        self.http_request::<List<u64>, String>(Method::GET, &query.finish(), None)
-            .await
-            .items
+        self.http_request::<List<u64>, String>(
+            Method::GET,
+            &with_query("/api/queue/messages".to_string(), query.finish()),
+            None,
+        )
+        .await
+        .items
+    }
+}
+
+impl QueueFilter {
+    fn matches(&self, message: &Message, now: i64) -> bool {
+        let domain_matches = self.domain.as_ref().is_none_or(|domain| {
+            message.recipients.iter().any(|rcpt| {
+                rcpt.address
+                    .rsplit_once('@')
+                    .is_some_and(|(_, rcpt_domain)| rcpt_domain.eq_ignore_ascii_case(domain))
+            })
+        });
+        let age_matches = self
+            .older_than
+            .is_none_or(|age| now - message.created.to_timestamp() >= age.as_secs() as i64);
+        let status_matches = self.status.is_none_or(|status| {
+            message
+                .recipients
+                .iter()
+                .any(|rcpt| rcpt.status.matches(status))
+        });
+
+        domain_matches && age_matches && status_matches
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).unwrap_result("serialize JSON output")
+    );
+}
+
+fn serialize_datetime<S>(value: &DateTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&value.to_rfc3339())
+}
+
+fn serialize_maybe_datetime<S>(value: &Option<DateTime>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(value) => serialize_datetime(value, serializer),
+        None => serializer.serialize_none(),
     }
 }
 
@@ -444,6 +615,16 @@ fn parse_ids(ids: &[String]) -> Vec<u64> {
 }
 
 impl Status {
+    fn matches(&self, status: QueueStatus) -> bool {
+        matches!(
+            (self, status),
+            (Status::Scheduled, QueueStatus::Scheduled)
+                | (Status::Completed(_), QueueStatus::Delivered)
+                | (Status::TemporaryFailure(_), QueueStatus::Tempfail)
+                | (Status::PermanentFailure(_), QueueStatus::Permfail)
+        )
+    }
+
     fn status_short(&self) -> &str {
         match self {
             Status::Scheduled => "scheduled",
@@ -471,3 +652,94 @@ impl Status {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::modules::mock::MockServer;
+
+    fn message(id: u64, rcpt: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "return_path": "sender@example.com",
+            "recipients": [{
+                "address": rcpt,
+                "status": "scheduled",
+                "queue": "default",
+                "retry_num": 0,
+                "next_retry": "2024-01-01T00:00:00Z"
+            }],
+            "created": "2024-01-01T00:00:00Z",
+            "size": 1024,
+            "blob_hash": "abc"
+        })
+    }
+
+    #[tokio::test]
+    async fn list_filters_by_domain() {
+        let server = MockServer::start(vec![
+            (
+                "GET",
+                "/api/queue/messages",
+                json!({"items": [1, 2], "total": 2}),
+            ),
+            (
+                "GET",
+                "/api/queue/messages/1",
+                message(1, "john@example.org"),
+            ),
+            ("GET", "/api/queue/messages/2", message(2, "jane@other.net")),
+        ])
+        .await;
+
+        let messages = server
+            .client()
+            .list_queued(&QueueFilter {
+                domain: Some("EXAMPLE.org".to_string()),
+                ..Default::default()
+            })
+            .await;
+
+        assert_eq!(messages.iter().map(|m| m.id).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(
+            server.requests()[0].path,
+            "/api/queue/messages?to=EXAMPLE.org"
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_reschedules_immediately() {
+        let server =
+            MockServer::start(vec![("PATCH", "/api/queue/messages/16", json!(true))]).await;
+
+        assert!(server.client().retry_queued(0x10, None, None).await);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "PATCH");
+        assert_eq!(requests[0].path, "/api/queue/messages/16");
+    }
+
+    #[test]
+    fn filter_by_age_and_status() {
+        let message: Message =
+            serde_json::from_str(&message(1, "john@example.org").to_string()).unwrap();
+        let created = message.created.to_timestamp();
+        let filter = QueueFilter {
+            older_than: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        assert!(filter.matches(&message, created + 3600));
+        assert!(!filter.matches(&message, created + 60));
+
+        let mut filter = QueueFilter {
+            status: Some(QueueStatus::Scheduled),
+            ..Default::default()
+        };
+        assert!(filter.matches(&message, created));
+        filter.status = Some(QueueStatus::Permfail);
+        assert!(!filter.matches(&message, created));
+    }
+}
@@ -1,21 +1,112 @@
 //! Cluster configuration
 
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Cluster configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClusterConfig {
     pub node_id: String,
+    /// Address other nodes reach this node on
+    pub address: String,
     pub cluster_name: String,
     pub enabled: bool,
+    /// Ids of the configured cluster members, empty for a single-node cluster
+    pub members: Vec<String>,
+    pub leader_election: LeaderElectionConfig,
+    pub discovery: DiscoveryConfig,
+    pub rebalance: RebalanceConfig,
+    pub propagation: PropagationConfig,
 }
 
 impl Default for ClusterConfig {
     fn default() -> Self {
         Self {
             node_id: "node-1".to_string(),
+            address: "127.0.0.1:7911".to_string(),
             cluster_name: "stalwart-cluster".to_string(),
             enabled: false,
+            members: Vec::new(),
+            leader_election: LeaderElectionConfig::default(),
+            discovery: DiscoveryConfig::default(),
+            rebalance: RebalanceConfig::default(),
+            propagation: PropagationConfig::default(),
+        }
+    }
+}
+
+/// Service discovery configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveryConfig {
+    /// JSON file listing the cluster nodes, for clusters without external services
+    pub static_file: Option<PathBuf>,
+    /// Interval between node list refreshes
+    pub refresh_interval: Duration,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            static_file: None,
+            refresh_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Rebalancing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalanceConfig {
+    /// Fraction above the mean load a node may carry before work is moved off it
+    pub imbalance_tolerance: f64,
+    /// Maximum number of shard moves in one plan
+    pub max_moves: usize,
+}
+
+impl Default for RebalanceConfig {
+    fn default() -> Self {
+        Self {
+            imbalance_tolerance: 0.2,
+            max_moves: 64,
+        }
+    }
+}
+
+/// Configuration propagation settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropagationConfig {
+    /// Time to wait for a node to acknowledge a configuration push
+    pub ack_timeout: Duration,
+    /// Push configuration over HTTPS rather than plain HTTP
+    pub use_tls: bool,
+}
+
+impl Default for PropagationConfig {
+    fn default() -> Self {
+        Self {
+            ack_timeout: Duration::from_secs(5),
+            use_tls: true,
+        }
+    }
+}
+
+/// Leader election configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderElectionConfig {
+    /// Key of the leader lease in the shared state
+    pub lease_key: String,
+    /// Time a lease stays valid without renewal
+    pub lease_ttl: Duration,
+    /// Interval between renewal or acquisition attempts, well below the TTL
+    pub renew_interval: Duration,
+}
+
+impl Default for LeaderElectionConfig {
+    fn default() -> Self {
+        Self {
+            lease_key: "cluster/leader".to_string(),
+            lease_ttl: Duration::from_secs(15),
+            renew_interval: Duration::from_secs(5),
         }
     }
 }
@@ -1,20 +1,437 @@
 //! Discovery module
+//!
+//! Backends that tell a node which other nodes belong to the cluster. They
+//! also hold the leases used for leader election, since every node shares them.
 
-use crate::error::Result;
+use crate::config::ClusterConfig;
+use crate::error::{ClusterError, Result};
+use crate::leader::LeaseRecord;
+use crate::node::NodeInfo;
+use async_trait::async_trait;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// Time to wait for another process to release the node list lock
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Interval between attempts to take the node list lock
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+/// Age after which a lock left behind by a crashed process is broken
+const LOCK_STALE_AFTER: Duration = Duration::from_secs(30);
 
 /// Discovery placeholder
 pub struct Discovery;
 
 /// Service discovery trait
-pub trait ServiceDiscovery {
-    /// Discover services
-    async fn discover(&self) -> Result<Vec<String>>;
+#[async_trait]
+pub trait ServiceDiscovery: std::fmt::Debug + Send + Sync {
+    /// Discover the nodes of the cluster
+    async fn discover_nodes(&self) -> Result<Vec<NodeInfo>>;
+
+    /// Add or update a node
+    async fn register_node(&self, node: &NodeInfo) -> Result<()>;
+
+    /// Remove a node
+    async fn deregister_node(&self, node_id: &str) -> Result<()>;
+
+    /// Get a lease record
+    async fn read_lease(&self, key: &str) -> Result<Option<LeaseRecord>>;
+
+    /// Replace a lease record only if it still equals `current`
+    ///
+    /// Returns `false` without writing when another node changed it first.
+    async fn swap_lease(&self, key: &str, current: Option<&LeaseRecord>, new: &LeaseRecord) -> Result<bool>;
 }
 
 /// Discovery backend
-pub struct DiscoveryBackend;
+pub type DiscoveryBackend = Arc<dyn ServiceDiscovery>;
 
 /// Create discovery backend
-pub async fn create_backend(_config: &crate::ClusterConfig) -> Result<Discovery> {
-    Ok(Discovery)
+pub async fn create_backend(config: &ClusterConfig) -> Result<DiscoveryBackend> {
+    match &config.discovery.static_file {
+        Some(path) => Ok(Arc::new(
+            StaticFileDiscovery::new(path, config.discovery.refresh_interval).await?,
+        )),
+        None => Ok(Arc::new(MemoryDiscovery::default())),
+    }
+}
+
+/// Discovery of the nodes registered in this process only, for single-node setups
+#[derive(Debug, Default)]
+pub struct MemoryDiscovery {
+    nodes: RwLock<Vec<NodeInfo>>,
+    leases: RwLock<HashMap<String, LeaseRecord>>,
+}
+
+#[async_trait]
+impl ServiceDiscovery for MemoryDiscovery {
+    async fn discover_nodes(&self) -> Result<Vec<NodeInfo>> {
+        Ok(self.nodes.read().await.clone())
+    }
+
+    async fn register_node(&self, node: &NodeInfo) -> Result<()> {
+        upsert_node(&mut *self.nodes.write().await, node);
+        Ok(())
+    }
+
+    async fn deregister_node(&self, node_id: &str) -> Result<()> {
+        self.nodes.write().await.retain(|node| node.id != node_id);
+        Ok(())
+    }
+
+    async fn read_lease(&self, key: &str) -> Result<Option<LeaseRecord>> {
+        Ok(self.leases.read().await.get(key).cloned())
+    }
+
+    async fn swap_lease(&self, key: &str, current: Option<&LeaseRecord>, new: &LeaseRecord) -> Result<bool> {
+        let mut leases = self.leases.write().await;
+        if leases.get(key) != current {
+            return Ok(false);
+        }
+
+        leases.insert(key.to_string(), new.clone());
+        Ok(true)
+    }
+}
+
+/// Discovery from a JSON node list file, for clusters without external services
+///
+/// The file is polled for changes so nodes can be added or removed by
+/// editing it. A file that fails to parse is logged and the last good node
+/// list is kept. Registering and deregistering rewrite the file atomically.
+///
+/// Leases live in a `.leases` file next to the node list. Swaps hold a
+/// `.lock` file created exclusively, so nodes sharing the directory see each
+/// other's leases and only one swap of a given record succeeds.
+#[derive(Debug)]
+pub struct StaticFileDiscovery {
+    path: PathBuf,
+    nodes: Arc<RwLock<Vec<NodeInfo>>>,
+    write_lock: Mutex<()>,
+    refresh_task: JoinHandle<()>,
+}
+
+impl StaticFileDiscovery {
+    /// Load the node list and start watching the file for changes
+    pub async fn new(path: impl Into<PathBuf>, refresh_interval: Duration) -> Result<Self> {
+        let path = path.into();
+        info!("Using static node list: {}", path.display());
+
+        let contents = read_node_file(&path).await?;
+        let nodes = Arc::new(RwLock::new(parse_node_list(&path, &contents)?));
+
+        let refresh_task = tokio::spawn({
+            let path = path.clone();
+            let nodes = Arc::clone(&nodes);
+            let mut fingerprint = content_fingerprint(&contents);
+
+            async move {
+                let mut interval = tokio::time::interval(refresh_interval);
+                loop {
+                    interval.tick().await;
+
+                    let contents = match read_node_file(&path).await {
+                        Ok(contents) => contents,
+                        Err(e) => {
+                            warn!("Failed to read node list, keeping the last good one: {}", e);
+                            continue;
+                        }
+                    };
+
+                    let current = content_fingerprint(&contents);
+                    if current == fingerprint {
+                        continue;
+                    }
+                    fingerprint = current;
+
+                    match parse_node_list(&path, &contents) {
+                        Ok(list) => {
+                            debug!("Node list changed, {} nodes", list.len());
+                            *nodes.write().await = list;
+                        }
+                        Err(e) => warn!("{}, keeping the last good node list", e),
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            path,
+            nodes,
+            write_lock: Mutex::new(()),
+            refresh_task,
+        })
+    }
+
+    /// Apply a change to the node list in the file and in memory
+    async fn update_file(&self, update: impl FnOnce(&mut Vec<NodeInfo>)) -> Result<()> {
+        let _guard = self.write_lock.lock().await;
+
+        // Start from the file, not the cache, to keep concurrent manual edits
+        let mut list = parse_node_list(&self.path, &read_node_file(&self.path).await?)?;
+        update(&mut list);
+
+        let contents = serde_json::to_string_pretty(&list)
+            .map_err(|e| ClusterError::Generic(format!("Failed to serialize node list: {}", e)))?;
+        write_atomically(&self.path, contents).await?;
+
+        *self.nodes.write().await = list;
+        Ok(())
+    }
+
+    /// Read the leases, treating a missing file as no leases
+    async fn read_leases(&self) -> Result<HashMap<String, LeaseRecord>> {
+        let path = sibling_path(&self.path, ".leases");
+        let contents = read_node_file(&path).await?;
+        if contents.trim().is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        serde_json::from_str(&contents)
+            .map_err(|e| ClusterError::Configuration(format!("Invalid lease file {}: {}", path.display(), e)))
+    }
+}
+
+#[async_trait]
+impl ServiceDiscovery for StaticFileDiscovery {
+    async fn discover_nodes(&self) -> Result<Vec<NodeInfo>> {
+        Ok(self.nodes.read().await.clone())
+    }
+
+    async fn register_node(&self, node: &NodeInfo) -> Result<()> {
+        info!("Registering node {} in {}", node.id, self.path.display());
+        self.update_file(|list| upsert_node(list, node)).await
+    }
+
+    async fn deregister_node(&self, node_id: &str) -> Result<()> {
+        info!("Deregistering node {} from {}", node_id, self.path.display());
+        self.update_file(|list| list.retain(|node| node.id != node_id)).await
+    }
+
+    async fn read_lease(&self, key: &str) -> Result<Option<LeaseRecord>> {
+        Ok(self.read_leases().await?.remove(key))
+    }
+
+    async fn swap_lease(&self, key: &str, current: Option<&LeaseRecord>, new: &LeaseRecord) -> Result<bool> {
+        let _guard = self.write_lock.lock().await;
+        let _lock = FileLock::acquire(sibling_path(&self.path, ".lock")).await?;
+
+        let mut leases = self.read_leases().await?;
+        if leases.get(key) != current {
+            return Ok(false);
+        }
+        leases.insert(key.to_string(), new.clone());
+
+        let contents = serde_json::to_string_pretty(&leases)
+            .map_err(|e| ClusterError::Generic(format!("Failed to serialize leases: {}", e)))?;
+        write_atomically(&sibling_path(&self.path, ".leases"), contents).await?;
+        Ok(true)
+    }
+}
+
+impl Drop for StaticFileDiscovery {
+    fn drop(&mut self) {
+        self.refresh_task.abort();
+    }
+}
+
+/// Lock shared by every process using a node list, held while its file exists
+struct FileLock {
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Create the lock file, waiting while another process holds it
+    async fn acquire(path: PathBuf) -> Result<Self> {
+        let deadline = Instant::now() + LOCK_TIMEOUT;
+        loop {
+            match tokio::fs::OpenOptions::new().write(true).create_new(true).open(&path).await {
+                Ok(_) => return Ok(Self { path }),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if lock_age(&path).await.is_some_and(|age| age > LOCK_STALE_AFTER) {
+                        warn!("Breaking stale lock {}", path.display());
+                        let _ = tokio::fs::remove_file(&path).await;
+                        continue;
+                    }
+                    if Instant::now() >= deadline {
+                        return Err(ClusterError::Generic(format!("Timed out waiting for lock {}", path.display())));
+                    }
+                    tokio::time::sleep(LOCK_RETRY_INTERVAL).await;
+                }
+                Err(e) => return Err(file_error(&path, e)),
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+async fn lock_age(path: &Path) -> Option<Duration> {
+    let modified = tokio::fs::metadata(path).await.ok()?.modified().ok()?;
+    SystemTime::now().duration_since(modified).ok()
+}
+
+/// Path of a file next to `path`, named after it with a suffix
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Write a sibling file and rename it over the original so readers never see partial contents
+async fn write_atomically(path: &Path, contents: String) -> Result<()> {
+    let temp_path = sibling_path(path, ".tmp");
+    tokio::fs::write(&temp_path, contents).await.map_err(|e| file_error(&temp_path, e))?;
+    tokio::fs::rename(&temp_path, path).await.map_err(|e| file_error(path, e))
+}
+
+/// Replace the node with the same id, or append it
+fn upsert_node(list: &mut Vec<NodeInfo>, node: &NodeInfo) {
+    match list.iter_mut().find(|existing| existing.id == node.id) {
+        Some(existing) => *existing = node.clone(),
+        None => list.push(node.clone()),
+    }
+}
+
+/// Read the node list file, treating a missing file as an empty list
+async fn read_node_file(path: &Path) -> Result<String> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(file_error(path, e)),
+    }
+}
+
+/// Parse a node list
+fn parse_node_list(path: &Path, contents: &str) -> Result<Vec<NodeInfo>> {
+    if contents.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    serde_json::from_str(contents)
+        .map_err(|e| ClusterError::Configuration(format!("Invalid node list {}: {}", path.display(), e)))
+}
+
+fn content_fingerprint(contents: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn file_error(path: &Path, error: std::io::Error) -> ClusterError {
+    ClusterError::Configuration(format!("Failed to access node list {}: {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REFRESH_INTERVAL: Duration = Duration::from_millis(20);
+
+    fn node(id: &str, address: &str) -> NodeInfo {
+        NodeInfo {
+            id: id.to_string(),
+            address: address.to_string(),
+        }
+    }
+
+    fn node_file(name: &str, nodes: &[NodeInfo]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("a3mailer-discovery-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("nodes.json");
+        std::fs::write(&path, serde_json::to_string(nodes).unwrap()).unwrap();
+        path
+    }
+
+    /// Wait for the refresh loop to pick up a file change
+    async fn wait_for_nodes(discovery: &StaticFileDiscovery, expected: usize) -> Vec<NodeInfo> {
+        for _ in 0..100 {
+            let nodes = discovery.discover_nodes().await.unwrap();
+            if nodes.len() == expected {
+                return nodes;
+            }
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+        }
+        panic!("node list did not reach {} nodes", expected);
+    }
+
+    #[tokio::test]
+    async fn test_static_file_discovery() {
+        let path = node_file("discover", &[node("node-1", "10.0.0.1:7911"), node("node-2", "10.0.0.2:7911")]);
+        let discovery = StaticFileDiscovery::new(&path, REFRESH_INTERVAL).await.unwrap();
+
+        let nodes = discovery.discover_nodes().await.unwrap();
+        assert_eq!(nodes, vec![node("node-1", "10.0.0.1:7911"), node("node-2", "10.0.0.2:7911")]);
+
+        // Editing the file adds a node
+        std::fs::write(
+            &path,
+            serde_json::to_string(&[node("node-1", "10.0.0.1:7911"), node("node-2", "10.0.0.2:7911"), node("node-3", "10.0.0.3:7911")]).unwrap(),
+        ).unwrap();
+        let nodes = wait_for_nodes(&discovery, 3).await;
+        assert_eq!(nodes[2], node("node-3", "10.0.0.3:7911"));
+
+        // Registering and deregistering go through the file
+        discovery.register_node(&node("node-4", "10.0.0.4:7911")).await.unwrap();
+        discovery.deregister_node("node-1").await.unwrap();
+        let on_disk: Vec<NodeInfo> = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let ids = on_disk.iter().map(|node| node.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["node-2", "node-3", "node-4"]);
+        assert_eq!(discovery.discover_nodes().await.unwrap(), on_disk);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_static_file_discovery_tolerates_malformed_file() {
+        let path = node_file("malformed", &[node("node-1", "10.0.0.1:7911")]);
+        let discovery = StaticFileDiscovery::new(&path, REFRESH_INTERVAL).await.unwrap();
+
+        // A half-written file keeps the last good list
+        std::fs::write(&path, "[{\"id\": \"node-1\", \"addr").unwrap();
+        tokio::time::sleep(REFRESH_INTERVAL * 5).await;
+        assert_eq!(discovery.discover_nodes().await.unwrap(), vec![node("node-1", "10.0.0.1:7911")]);
+        assert!(discovery.register_node(&node("node-2", "10.0.0.2:7911")).await.is_err());
+
+        // The refresh loop is still running once the file is fixed
+        std::fs::write(&path, serde_json::to_string(&[node("node-1", "10.0.0.1:7911"), node("node-2", "10.0.0.2:7911")]).unwrap()).unwrap();
+        wait_for_nodes(&discovery, 2).await;
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_static_file_leases_are_shared() {
+        let path = node_file("leases", &[node("node-1", "10.0.0.1:7911"), node("node-2", "10.0.0.2:7911")]);
+
+        // Two instances on the same file stand in for two processes
+        let first = StaticFileDiscovery::new(&path, REFRESH_INTERVAL).await.unwrap();
+        let second = StaticFileDiscovery::new(&path, REFRESH_INTERVAL).await.unwrap();
+        let lease = |holder: &str, term| LeaseRecord {
+            holder: holder.to_string(),
+            term,
+            expires_at_ms: u64::MAX,
+        };
+
+        assert!(first.swap_lease("leader", None, &lease("node-1", 1)).await.unwrap());
+        assert!(!second.swap_lease("leader", None, &lease("node-2", 1)).await.unwrap());
+        assert_eq!(second.read_lease("leader").await.unwrap(), Some(lease("node-1", 1)));
+
+        assert!(second.swap_lease("leader", Some(&lease("node-1", 1)), &lease("node-2", 2)).await.unwrap());
+        assert_eq!(first.read_lease("leader").await.unwrap(), Some(lease("node-2", 2)));
+        assert!(!path.with_file_name("nodes.json.lock").exists());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
 }
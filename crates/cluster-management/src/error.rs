@@ -16,6 +16,18 @@ pub enum ClusterError {
     #[error("Network error: {0}")]
     Network(#[from] std::io::Error),
 
+    /// Operation requires this node to be the leader
+    #[error("Not the cluster leader")]
+    NotLeader,
+
+    /// Too few cluster members are visible to act safely
+    #[error("No quorum: {visible} of {required} required members visible")]
+    NoQuorum { visible: usize, required: usize },
+
+    /// Node is draining and takes no new work
+    #[error("Node is draining: {0}")]
+    NodeDraining(String),
+
     /// Generic error
     #[error("Cluster error: {0}")]
     Generic(String),
@@ -1,10 +1,19 @@
 //! Health module
+//!
+//! Health of the cluster nodes, as last reported by whoever checks them.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
 
 /// Health placeholder
 pub struct Health;
 
 /// Health monitor
-pub struct HealthMonitor;
+#[derive(Debug, Clone, Default)]
+pub struct HealthMonitor {
+    health: Arc<RwLock<HashMap<String, NodeHealth>>>,
+}
 
 /// Node health
 #[derive(Debug, Clone)]
@@ -13,3 +22,50 @@ pub struct NodeHealth {
     pub is_healthy: bool,
     pub last_check: std::time::Instant,
 }
+
+/// Number of nodes in each health state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HealthStatistics {
+    pub healthy: usize,
+    pub unhealthy: usize,
+}
+
+impl HealthMonitor {
+    /// Create a health monitor with no reports
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the result of a health check on a node
+    pub async fn record_health(&self, node_id: &str, is_healthy: bool) {
+        self.health.write().await.insert(
+            node_id.to_string(),
+            NodeHealth {
+                node_id: node_id.to_string(),
+                is_healthy,
+                last_check: std::time::Instant::now(),
+            },
+        );
+    }
+
+    /// Drop the health report of a removed node
+    pub async fn forget_node(&self, node_id: &str) {
+        self.health.write().await.remove(node_id);
+    }
+
+    /// Get the last health report of every node
+    pub async fn get_all_health(&self) -> HashMap<String, NodeHealth> {
+        self.health.read().await.clone()
+    }
+
+    /// Count the nodes by their last reported health
+    pub async fn get_statistics(&self) -> HealthStatistics {
+        let health = self.health.read().await;
+        let healthy = health.values().filter(|health| health.is_healthy).count();
+
+        HealthStatistics {
+            healthy,
+            unhealthy: health.len() - healthy,
+        }
+    }
+}
@@ -1,10 +1,173 @@
 //! Leader module
+//!
+//! Lease-based leader election. The leader renews a lease with a TTL in the
+//! discovery backend shared by all nodes; followers take over once the lease
+//! has expired.
+
+use crate::config::LeaderElectionConfig;
+use crate::discovery::{DiscoveryBackend, ServiceDiscovery};
+use crate::error::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
 
 /// Leader placeholder
 pub struct Leader;
 
+/// Lease held by a node
+#[derive(Debug, Clone)]
+pub struct Lease {
+    pub holder: String,
+    /// Incremented each time the lease changes hands
+    pub term: u64,
+    pub expires_at: Instant,
+}
+
+/// Lease as stored in the shared backend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaseRecord {
+    pub holder: String,
+    pub term: u64,
+    /// Wall-clock expiry in milliseconds since the Unix epoch
+    pub expires_at_ms: u64,
+}
+
+/// Shared storage for leases with a TTL
+#[async_trait]
+pub trait LeaseStore: Send + Sync {
+    /// Acquire a lease that is free, expired or already held by `holder`
+    async fn acquire_lease(&self, key: &str, holder: &str, ttl: Duration) -> Result<Option<Lease>>;
+
+    /// Extend a lease that `holder` still holds
+    async fn renew_lease(&self, key: &str, holder: &str, ttl: Duration) -> Result<Option<Lease>>;
+
+    /// Give up a lease held by `holder`
+    async fn release_lease(&self, key: &str, holder: &str) -> Result<()>;
+
+    /// Get the current unexpired lease
+    async fn get_lease(&self, key: &str) -> Result<Option<Lease>>;
+}
+
+/// Lease store kept in the discovery backend shared by all nodes
+///
+/// Every change is a compare-and-swap against the record that was read, so
+/// of two nodes racing for an expired lease only one gets the next term.
+/// Local time is the wall clock at creation advanced by the monotonic clock,
+/// which keeps expiry checks steady when the system clock is stepped.
+#[derive(Debug)]
+pub struct DiscoveryLeaseStore {
+    backend: DiscoveryBackend,
+    epoch_ms: u64,
+    epoch: Instant,
+}
+
+impl DiscoveryLeaseStore {
+    /// Create a lease store on a discovery backend
+    pub fn new(backend: DiscoveryBackend) -> Self {
+        Self {
+            backend,
+            epoch_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            epoch: Instant::now(),
+        }
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.epoch_ms + self.epoch.elapsed().as_millis() as u64
+    }
+
+    fn to_lease(&self, record: &LeaseRecord) -> Lease {
+        Lease {
+            holder: record.holder.clone(),
+            term: record.term,
+            expires_at: Instant::now() + Duration::from_millis(record.expires_at_ms.saturating_sub(self.now_ms())),
+        }
+    }
+
+    /// Write a record unless another node changed the lease since it was read
+    async fn swap(&self, key: &str, current: Option<&LeaseRecord>, record: LeaseRecord) -> Result<Option<Lease>> {
+        if self.backend.swap_lease(key, current, &record).await? {
+            Ok(Some(self.to_lease(&record)))
+        } else {
+            debug!("Lease {} changed while updating it", key);
+            Ok(None)
+        }
+    }
+}
+
+#[async_trait]
+impl LeaseStore for DiscoveryLeaseStore {
+    async fn acquire_lease(&self, key: &str, holder: &str, ttl: Duration) -> Result<Option<Lease>> {
+        let now = self.now_ms();
+        let current = self.backend.read_lease(key).await?;
+
+        let term = match &current {
+            Some(lease) if lease.expires_at_ms > now && lease.holder != holder => return Ok(None),
+            Some(lease) if lease.expires_at_ms > now => lease.term,
+            Some(lease) => lease.term + 1,
+            None => 1,
+        };
+
+        let record = LeaseRecord {
+            holder: holder.to_string(),
+            term,
+            expires_at_ms: now + ttl.as_millis() as u64,
+        };
+        self.swap(key, current.as_ref(), record).await
+    }
+
+    async fn renew_lease(&self, key: &str, holder: &str, ttl: Duration) -> Result<Option<Lease>> {
+        let now = self.now_ms();
+        let current = match self.backend.read_lease(key).await? {
+            Some(lease) if lease.holder == holder && lease.expires_at_ms > now => lease,
+            _ => return Ok(None),
+        };
+
+        let record = LeaseRecord {
+            expires_at_ms: now + ttl.as_millis() as u64,
+            ..current.clone()
+        };
+        self.swap(key, Some(&current), record).await
+    }
+
+    async fn release_lease(&self, key: &str, holder: &str) -> Result<()> {
+        // Expire rather than remove the lease so terms keep increasing
+        if let Some(current) = self.backend.read_lease(key).await?.filter(|lease| lease.holder == holder) {
+            let record = LeaseRecord {
+                expires_at_ms: self.now_ms(),
+                ..current.clone()
+            };
+            self.swap(key, Some(&current), record).await?;
+        }
+        Ok(())
+    }
+
+    async fn get_lease(&self, key: &str) -> Result<Option<Lease>> {
+        let now = self.now_ms();
+        Ok(self
+            .backend
+            .read_lease(key)
+            .await?
+            .filter(|lease| lease.expires_at_ms > now)
+            .map(|lease| self.to_lease(&lease)))
+    }
+}
+
 /// Leader election
-pub struct LeaderElection;
+pub struct LeaderElection {
+    config: LeaderElectionConfig,
+    node_id: String,
+    store: Arc<dyn LeaseStore>,
+    state: Arc<RwLock<ElectionState>>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
 
 /// Leadership state
 #[derive(Debug, Clone)]
@@ -13,3 +176,316 @@ pub struct LeadershipState {
     pub leader_id: Option<String>,
     pub term: u64,
 }
+
+/// Leadership along with the expiry of the lease backing it
+#[derive(Debug)]
+struct ElectionState {
+    leadership: LeadershipState,
+    lease_expires_at: Option<Instant>,
+}
+
+impl ElectionState {
+    /// Leadership is only trusted while the lease it was granted is unexpired
+    fn holds_lease(&self) -> bool {
+        self.leadership.is_leader && self.lease_expires_at.is_some_and(|expires_at| expires_at > Instant::now())
+    }
+}
+
+impl LeaderElection {
+    /// Create a new leader election for a node
+    pub async fn new(config: &LeaderElectionConfig, node_id: &str, store: Arc<dyn LeaseStore>) -> Result<Self> {
+        Ok(Self {
+            config: config.clone(),
+            node_id: node_id.to_string(),
+            store,
+            state: Arc::new(RwLock::new(ElectionState {
+                leadership: LeadershipState {
+                    is_leader: false,
+                    leader_id: None,
+                    term: 0,
+                },
+                lease_expires_at: None,
+            })),
+            task: Mutex::new(None),
+        })
+    }
+
+    /// Start taking part in elections
+    pub async fn start(&self) -> Result<()> {
+        info!("Starting leader election for node: {}", self.node_id);
+
+        let config = self.config.clone();
+        let node_id = self.node_id.clone();
+        let store = Arc::clone(&self.store);
+        let state = Arc::clone(&self.state);
+
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(config.renew_interval);
+            loop {
+                interval.tick().await;
+                Self::run_round(&config, &node_id, store.as_ref(), &state).await;
+            }
+        });
+
+        if let Some(previous) = self.task.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+
+        Ok(())
+    }
+
+    /// Stop taking part in elections, releasing the lease if held
+    pub async fn stop(&self) -> Result<()> {
+        info!("Stopping leader election for node: {}", self.node_id);
+
+        if let Some(handle) = self.task.lock().unwrap().take() {
+            handle.abort();
+        }
+
+        let mut state = self.state.write().await;
+        if state.leadership.is_leader {
+            state.leadership.is_leader = false;
+            state.leadership.leader_id = None;
+            state.lease_expires_at = None;
+            self.store.release_lease(&self.config.lease_key, &self.node_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Renew the lease when leading, otherwise try to acquire it
+    async fn run_round(config: &LeaderElectionConfig, node_id: &str, store: &dyn LeaseStore, state: &RwLock<ElectionState>) {
+        let was_leader = state.read().await.leadership.is_leader;
+
+        let result = if was_leader {
+            store.renew_lease(&config.lease_key, node_id, config.lease_ttl).await
+        } else {
+            store.acquire_lease(&config.lease_key, node_id, config.lease_ttl).await
+        };
+
+        // Observe the current holder when the lease is someone else's
+        let current = match &result {
+            Ok(None) => store.get_lease(&config.lease_key).await.ok().flatten(),
+            _ => None,
+        };
+
+        let mut state = state.write().await;
+        match result {
+            Ok(Some(lease)) => {
+                if !was_leader {
+                    info!("Node {} became leader for term {}", node_id, lease.term);
+                }
+                state.leadership = LeadershipState {
+                    is_leader: true,
+                    leader_id: Some(node_id.to_string()),
+                    term: lease.term,
+                };
+                state.lease_expires_at = Some(lease.expires_at);
+            }
+            Ok(None) => {
+                if was_leader {
+                    warn!("Node {} lost the leader lease", node_id);
+                }
+                state.leadership.is_leader = false;
+                state.lease_expires_at = None;
+                state.leadership.leader_id = current.as_ref().map(|lease| lease.holder.clone());
+                if let Some(lease) = current {
+                    state.leadership.term = lease.term;
+                }
+            }
+            Err(e) => {
+                // Step down right away, the lease cannot be confirmed
+                if was_leader {
+                    warn!("Node {} failed to renew the leader lease, stepping down: {}", node_id, e);
+                    state.leadership.leader_id = None;
+                } else {
+                    debug!("Node {} failed to check the leader lease: {}", node_id, e);
+                }
+                state.leadership.is_leader = false;
+                state.lease_expires_at = None;
+            }
+        }
+    }
+
+    /// Check if this node is the leader
+    pub async fn is_leader(&self) -> bool {
+        self.state.read().await.holds_lease()
+    }
+
+    /// Get the id of the current leader, if known
+    pub async fn get_leader(&self) -> Option<String> {
+        let state = self.state.read().await;
+        if state.leadership.is_leader && !state.holds_lease() {
+            // Our own lease lapsed before it could be renewed
+            return None;
+        }
+        state.leadership.leader_id.clone()
+    }
+
+    /// Get the leadership state as seen by this node
+    pub async fn get_state(&self) -> LeadershipState {
+        let state = self.state.read().await;
+        LeadershipState {
+            is_leader: state.holds_lease(),
+            ..state.leadership.clone()
+        }
+    }
+}
+
+impl std::fmt::Debug for LeaderElection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LeaderElection")
+            .field("config", &self.config)
+            .field("node_id", &self.node_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for LeaderElection {
+    fn drop(&mut self) {
+        if let Some(handle) = self.task.get_mut().unwrap().take() {
+            handle.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::MemoryDiscovery;
+    use crate::error::ClusterError;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Lease store that can be cut off from the shared state
+    struct PartitionedStore {
+        inner: DiscoveryLeaseStore,
+        partitioned: AtomicBool,
+    }
+
+    impl PartitionedStore {
+        fn check(&self) -> Result<()> {
+            if self.partitioned.load(Ordering::SeqCst) {
+                Err(ClusterError::Generic("partitioned".to_string()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LeaseStore for PartitionedStore {
+        async fn acquire_lease(&self, key: &str, holder: &str, ttl: Duration) -> Result<Option<Lease>> {
+            self.check()?;
+            self.inner.acquire_lease(key, holder, ttl).await
+        }
+
+        async fn renew_lease(&self, key: &str, holder: &str, ttl: Duration) -> Result<Option<Lease>> {
+            self.check()?;
+            self.inner.renew_lease(key, holder, ttl).await
+        }
+
+        async fn release_lease(&self, key: &str, holder: &str) -> Result<()> {
+            self.check()?;
+            self.inner.release_lease(key, holder).await
+        }
+
+        async fn get_lease(&self, key: &str) -> Result<Option<Lease>> {
+            self.check()?;
+            self.inner.get_lease(key).await
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failover_when_leader_stops_renewing() {
+        let config = LeaderElectionConfig {
+            lease_key: "leader".to_string(),
+            lease_ttl: Duration::from_secs(3),
+            renew_interval: Duration::from_secs(1),
+        };
+        let shared: DiscoveryBackend = Arc::new(MemoryDiscovery::default());
+        let store_a = Arc::new(PartitionedStore {
+            inner: DiscoveryLeaseStore::new(shared.clone()),
+            partitioned: AtomicBool::new(false),
+        });
+        let store_b = Arc::new(DiscoveryLeaseStore::new(shared.clone()));
+
+        let node_a = LeaderElection::new(&config, "node-a", store_a.clone()).await.unwrap();
+        node_a.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(node_a.is_leader().await);
+
+        let node_b = LeaderElection::new(&config, "node-b", store_b).await.unwrap();
+        node_b.start().await.unwrap();
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert!(node_a.is_leader().await);
+        assert!(!node_b.is_leader().await);
+        assert_eq!(node_b.get_leader().await.as_deref(), Some("node-a"));
+        let term_a = node_a.get_state().await.term;
+
+        // Node A can no longer reach the shared state and steps down on its next renewal
+        store_a.partitioned.store(true, Ordering::SeqCst);
+        let partitioned_at = Instant::now();
+        tokio::time::sleep(config.renew_interval + Duration::from_millis(100)).await;
+        assert!(!node_a.is_leader().await);
+        assert_eq!(node_a.get_leader().await, None);
+
+        // Node B takes over once the lease expires
+        let deadline = partitioned_at + config.lease_ttl + config.renew_interval * 2;
+        while !node_b.is_leader().await {
+            assert!(Instant::now() < deadline, "follower did not take over in time");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(node_b.get_leader().await.as_deref(), Some("node-b"));
+        assert!(node_b.get_state().await.term > term_a);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stop_releases_lease() {
+        let config = LeaderElectionConfig {
+            lease_key: "leader".to_string(),
+            lease_ttl: Duration::from_secs(30),
+            renew_interval: Duration::from_secs(1),
+        };
+        let shared: DiscoveryBackend = Arc::new(MemoryDiscovery::default());
+        let store_a = Arc::new(DiscoveryLeaseStore::new(shared.clone()));
+        let store_b = Arc::new(DiscoveryLeaseStore::new(shared.clone()));
+
+        let node_a = LeaderElection::new(&config, "node-a", store_a).await.unwrap();
+        node_a.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(node_a.is_leader().await);
+
+        let node_b = LeaderElection::new(&config, "node-b", store_b).await.unwrap();
+        node_b.start().await.unwrap();
+        node_a.stop().await.unwrap();
+
+        // Well before the 30s TTL would have run out
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert!(node_b.is_leader().await);
+        assert!(!node_a.is_leader().await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_expired_lease_goes_to_one_node() {
+        let shared: DiscoveryBackend = Arc::new(MemoryDiscovery::default());
+        let store_a = DiscoveryLeaseStore::new(shared.clone());
+        let store_b = DiscoveryLeaseStore::new(shared.clone());
+        let ttl = Duration::from_secs(3);
+
+        assert_eq!(store_a.acquire_lease("leader", "node-a", ttl).await.unwrap().unwrap().term, 1);
+        tokio::time::sleep(ttl).await;
+
+        // Both nodes read the expired lease, only the first swap gets the next term
+        let expired = shared.read_lease("leader").await.unwrap();
+        assert_eq!(store_b.acquire_lease("leader", "node-b", ttl).await.unwrap().unwrap().term, 2);
+        let racing = LeaseRecord {
+            holder: "node-c".to_string(),
+            term: 2,
+            expires_at_ms: u64::MAX,
+        };
+        assert!(!shared.swap_lease("leader", expired.as_ref(), &racing).await.unwrap());
+
+        assert_eq!(store_a.get_lease("leader").await.unwrap().unwrap().holder, "node-b");
+        assert!(store_a.renew_lease("leader", "node-a", ttl).await.unwrap().is_none());
+    }
+}
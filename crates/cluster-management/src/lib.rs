@@ -23,20 +23,26 @@ pub mod health;
 pub mod leader;
 pub mod metrics;
 pub mod node;
+pub mod quorum;
+pub mod rebalance;
 pub mod state;
 pub mod sync;
 
-pub use config::ClusterConfig;
+pub use config::{ClusterConfig, DiscoveryConfig, LeaderElectionConfig, PropagationConfig};
 pub use consensus::{ConsensusEngine, ConsensusState};
-pub use discovery::{ServiceDiscovery, DiscoveryBackend};
+pub use discovery::{ServiceDiscovery, DiscoveryBackend, MemoryDiscovery, StaticFileDiscovery};
 pub use error::{ClusterError, Result};
-pub use health::{HealthMonitor, NodeHealth};
-pub use leader::{LeaderElection, LeadershipState};
-pub use metrics::ClusterMetrics;
+pub use health::{HealthMonitor, HealthStatistics, NodeHealth};
+pub use leader::{DiscoveryLeaseStore, LeaderElection, LeadershipState, Lease, LeaseRecord, LeaseStore};
+pub use metrics::{ClusterMetrics, ShardLoadSource};
 pub use node::{Node, NodeInfo, NodeStatus};
+pub use quorum::QuorumPolicy;
+pub use rebalance::{RebalancePlan, ShardMove};
 pub use state::{ClusterState, ClusterStateManager};
+pub use sync::{ConfigAck, ConfigPropagator, ConfigStore, ConfigTransport, HttpConfigTransport, PropagationResult, VersionedConfig};
 
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use tracing::{info, warn, error};
 use uuid::Uuid;
@@ -55,7 +61,10 @@ struct ClusterManagerInner {
     service_discovery: DiscoveryBackend,
     health_monitor: HealthMonitor,
     leader_election: LeaderElection,
-    consensus_engine: Option<ConsensusEngine>,
+    quorum: QuorumPolicy,
+    config_store: ConfigStore,
+    config_propagator: RwLock<ConfigPropagator>,
+    shard_load_source: Arc<RwLock<Option<Arc<dyn ShardLoadSource>>>>,
     metrics: Arc<RwLock<ClusterMetrics>>,
 }
 
@@ -65,26 +74,32 @@ impl ClusterManager {
         info!("Initializing cluster manager");
 
         // Create node information
-        let node_info = NodeInfo::new(&config.node_id).await?;
+        let node_info = NodeInfo::new(&config.node_id, &config.address);
 
         // Create cluster state manager
-        let cluster_state = ClusterStateManager::new(&config.state).await?;
+        let cluster_state = ClusterStateManager::default();
 
         // Initialize service discovery
         let service_discovery = discovery::create_backend(&config).await?;
 
         // Create health monitor
-        let health_monitor = HealthMonitor::new(&config.health).await?;
+        let health_monitor = HealthMonitor::new();
 
-        // Create leader election
-        let leader_election = LeaderElection::new(&config.leader_election, node_info.clone()).await?;
+        // Create leader election, holding its lease in the discovery backend shared by all nodes
+        let leader_election = LeaderElection::new(
+            &config.leader_election,
+            &node_info.id,
+            Arc::new(DiscoveryLeaseStore::new(service_discovery.clone())),
+        ).await?;
 
-        // Create consensus engine if enabled
-        let consensus_engine = if config.consensus.enabled {
-            Some(ConsensusEngine::new(&config.consensus, node_info.clone()).await?)
-        } else {
-            None
-        };
+        // Leader-only operations need a majority of the configured members
+        let quorum = QuorumPolicy::new(&config.members, &node_info.id);
+
+        // Push configuration over HTTP until another transport is set
+        let config_propagator = ConfigPropagator::new(
+            &config.propagation,
+            Arc::new(HttpConfigTransport::new(&config.propagation, service_discovery.clone())?),
+        );
 
         // Create metrics collector
         let metrics = Arc::new(RwLock::new(ClusterMetrics::new()));
@@ -97,7 +112,10 @@ impl ClusterManager {
                 service_discovery,
                 health_monitor,
                 leader_election,
-                consensus_engine,
+                quorum,
+                config_store: ConfigStore::default(),
+                config_propagator: RwLock::new(config_propagator),
+                shard_load_source: Arc::new(RwLock::new(None)),
                 metrics,
             }),
         })
@@ -109,21 +127,11 @@ impl ClusterManager {
 
         // Register this node with service discovery
         self.inner.service_discovery.register_node(&self.inner.node_info).await?;
-
-        // Start health monitoring
-        self.inner.health_monitor.start().await?;
+        self.inner.cluster_state.add_node(self.inner.node_info.clone()).await?;
 
         // Start leader election
         self.inner.leader_election.start().await?;
 
-        // Start consensus engine if enabled
-        if let Some(ref consensus) = self.inner.consensus_engine {
-            consensus.start().await?;
-        }
-
-        // Start cluster state synchronization
-        self.inner.cluster_state.start().await?;
-
         // Start background tasks
         self.start_background_tasks().await;
 
@@ -135,20 +143,9 @@ impl ClusterManager {
     pub async fn stop(&self) -> Result<()> {
         info!("Stopping cluster manager");
 
-        // Stop consensus engine
-        if let Some(ref consensus) = self.inner.consensus_engine {
-            consensus.stop().await?;
-        }
-
         // Stop leader election
         self.inner.leader_election.stop().await?;
 
-        // Stop health monitoring
-        self.inner.health_monitor.stop().await?;
-
-        // Stop cluster state manager
-        self.inner.cluster_state.stop().await?;
-
         // Deregister from service discovery
         self.inner.service_discovery.deregister_node(&self.inner.node_info.id).await?;
 
@@ -158,7 +155,10 @@ impl ClusterManager {
 
     /// Get current cluster state
     pub async fn get_cluster_state(&self) -> ClusterState {
-        self.inner.cluster_state.get_state().await
+        ClusterState {
+            leader: self.inner.leader_election.get_leader().await,
+            ..self.inner.cluster_state.get_state().await
+        }
     }
 
     /// Get all nodes in the cluster
@@ -187,6 +187,52 @@ impl ClusterManager {
         }
     }
 
+    /// Check if this node can see a majority of the configured cluster members
+    ///
+    /// Nodes count as visible when discovery lists them and their last health
+    /// check passed.
+    pub async fn has_quorum(&self) -> bool {
+        match self.reachable_nodes().await {
+            Ok(reachable) => self.inner.quorum.has_quorum(reachable.iter().map(String::as_str)),
+            Err(e) => {
+                warn!("Failed to determine reachable nodes: {}", e);
+                self.inner.quorum.has_quorum(Vec::<&str>::new())
+            }
+        }
+    }
+
+    /// Get the ids of the nodes this node can currently reach
+    async fn reachable_nodes(&self) -> Result<Vec<String>> {
+        let health = self.inner.health_monitor.get_all_health().await;
+        Ok(self.inner.service_discovery.discover_nodes().await?
+            .into_iter()
+            .map(|node| node.id.to_string())
+            .filter(|id| health.get(id).map_or(false, |health| health.is_healthy))
+            .collect())
+    }
+
+    /// Fail unless this node is the leader and can see a majority of the cluster
+    ///
+    /// Guards against both sides of a partition acting as leader.
+    async fn require_leader_quorum(&self) -> Result<()> {
+        if !self.is_leader().await {
+            return Err(ClusterError::NotLeader);
+        }
+
+        let reachable = self.reachable_nodes().await?;
+        if let Err(e) = self.inner.quorum.require(reachable.iter().map(String::as_str)) {
+            warn!("Refusing leader operation: {}", e);
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Record the result of a health check on a node
+    pub async fn report_node_health(&self, node_id: &str, is_healthy: bool) {
+        self.inner.health_monitor.record_health(node_id, is_healthy).await;
+    }
+
     /// Get cluster metrics
     pub async fn get_metrics(&self) -> ClusterMetrics {
         (*self.inner.metrics.read().await).clone()
@@ -205,7 +251,32 @@ impl ClusterManager {
         Ok(())
     }
 
+    /// Drain a node and then remove it from the cluster
+    ///
+    /// The node is marked draining so it gets no new work, and is removed
+    /// once its active connections have finished or the timeout runs out.
+    pub async fn drain_node(&self, node_id: &Uuid, timeout: Duration) -> Result<bool> {
+        if !self.inner.cluster_state.drain_node(&node_id.to_string(), timeout).await {
+            warn!("Removing node {} before its connections finished", node_id);
+        }
+
+        self.remove_node(node_id).await
+    }
+
+    /// Record the number of connections a node is serving, which draining waits on
+    pub async fn report_active_connections(&self, node_id: &str, count: u64) {
+        self.inner.cluster_state.set_active_connections(node_id, count).await;
+    }
+
+    /// Get the status of a node, including whether it is draining
+    pub async fn get_node_status(&self, node_id: &Uuid) -> NodeStatus {
+        self.inner.cluster_state.get_node_status(&node_id.to_string()).await
+    }
+
     /// Remove a node from the cluster
+    ///
+    /// The node is removed right away and its shards are handed to the
+    /// remaining nodes, see [`Self::drain_node`] to let its connections finish.
     pub async fn remove_node(&self, node_id: &Uuid) -> Result<bool> {
         info!("Removing node from cluster: {}", node_id);
 
@@ -216,6 +287,9 @@ impl ClusterManager {
 
         // Update cluster state
         let removed = self.inner.cluster_state.remove_node(&node_id_str).await?;
+        self.inner.cluster_state.reassign_shards(&node_id_str).await;
+        self.inner.cluster_state.forget_node(&node_id_str).await;
+        self.inner.health_monitor.forget_node(&node_id_str).await;
 
         if removed {
             info!("Node removed successfully: {}", node_id);
@@ -242,29 +316,96 @@ impl ClusterManager {
     }
 
     /// Trigger cluster rebalancing
-    pub async fn rebalance_cluster(&self) -> Result<()> {
+    ///
+    /// Moves shards from over-loaded to under-loaded healthy nodes and
+    /// returns the applied plan, which is empty when the load is already
+    /// within the configured imbalance tolerance.
+    pub async fn rebalance_cluster(&self) -> Result<RebalancePlan> {
         info!("Triggering cluster rebalancing");
 
-        if !self.is_leader().await {
-            return Err(ClusterError::NotLeader);
+        self.require_leader_quorum().await?;
+
+        // Only healthy nodes take part, nodes without a health report are assumed healthy.
+        // Draining nodes are left out so no shards are moved onto them.
+        let health = self.inner.health_monitor.get_all_health().await;
+        let statuses = self.inner.cluster_state.get_node_statuses().await;
+        let nodes = self.inner.cluster_state.get_state().await.nodes
+            .into_iter()
+            .map(|node| node.id.to_string())
+            .filter(|id| health.get(id).map_or(true, |health| health.is_healthy))
+            .filter(|id| statuses.get(id) != Some(&NodeStatus::Draining))
+            .collect::<Vec<_>>();
+
+        // Plan with current loads rather than the last periodic sample
+        if let Err(e) = refresh_shard_loads(&self.inner.shard_load_source, &self.inner.cluster_state, &self.inner.metrics).await {
+            warn!("Failed to refresh shard loads, using the last known loads: {}", e);
+        }
+        let shard_loads = self.inner.metrics.read().await.shard_loads.clone();
+        let assignments = self.inner.cluster_state.get_assignments().await;
+        let plan = rebalance::plan_rebalance(&assignments, &shard_loads, &nodes, &self.inner.config.rebalance);
+
+        if plan.is_empty() {
+            info!("Cluster load is within tolerance, nothing to rebalance");
+        } else {
+            info!("Rebalancing {} shards", plan.moves.len());
+            self.inner.cluster_state.apply_rebalance(&plan).await?;
         }
 
-        // TODO: Implement rebalancing logic
+        Ok(plan)
+    }
 
-        Ok(())
+    /// Set the source of the shard loads that rebalancing evens out
+    pub async fn set_shard_load_source(&self, source: Arc<dyn ShardLoadSource>) {
+        *self.inner.shard_load_source.write().await = Some(source);
+    }
+
+    /// Replace the HTTP transport used to push configuration to other nodes
+    pub async fn set_config_transport(&self, transport: Arc<dyn ConfigTransport>) {
+        *self.inner.config_propagator.write().await =
+            ConfigPropagator::new(&self.inner.config.propagation, transport);
+    }
+
+    /// Get the configuration applied on this node
+    pub async fn get_config(&self) -> VersionedConfig {
+        self.inner.config_store.get().await
+    }
+
+    /// Apply configuration pushed by the leader, returning the version now held
+    ///
+    /// Versions older than the current one are ignored.
+    pub async fn apply_config(&self, config: &VersionedConfig) -> u64 {
+        self.inner.config_store.apply(config).await
     }
 
     /// Propagate configuration to all nodes
-    pub async fn propagate_config(&self, config_data: Vec<u8>) -> Result<()> {
+    ///
+    /// Stamps the configuration with the next version, applies it locally and
+    /// pushes it to every configured member and discovered node, reporting
+    /// which nodes acknowledged the new version and which lag behind.
+    /// Members that cannot be reached are reported as lagging.
+    pub async fn propagate_config(&self, config_data: Vec<u8>) -> Result<PropagationResult> {
         info!("Propagating configuration to cluster");
 
-        if !self.is_leader().await {
-            return Err(ClusterError::NotLeader);
-        }
+        self.require_leader_quorum().await?;
 
-        // TODO: Implement configuration propagation
+        let propagator = self.inner.config_propagator.read().await;
+        let mut nodes = self.inner.config.members.clone();
+        nodes.extend(self.inner.service_discovery.discover_nodes().await?.into_iter().map(|node| node.id));
+        nodes.sort();
+        nodes.dedup();
+        nodes.retain(|id| *id != self.inner.node_info.id);
 
-        Ok(())
+        let result = propagator.propagate(&self.inner.config_store, &nodes, config_data).await?;
+        if result.is_complete() {
+            info!("Configuration version {} applied on all {} nodes", result.version, result.applied.len());
+        } else {
+            warn!(
+                "Configuration version {} applied on {} nodes, {} lagging: {:?}",
+                result.version, result.applied.len(), result.lagged.len(), result.lagged
+            );
+        }
+
+        Ok(result)
     }
 
     /// Start background tasks
@@ -274,9 +415,6 @@ impl ClusterManager {
 
         // Start node discovery refresh
         self.start_discovery_refresh().await;
-
-        // Start cluster state sync
-        self.start_state_sync().await;
     }
 
     /// Start metrics collection background task
@@ -284,6 +422,7 @@ impl ClusterManager {
         let metrics = self.inner.metrics.clone();
         let cluster_state = self.inner.cluster_state.clone();
         let health_monitor = self.inner.health_monitor.clone();
+        let shard_load_source = self.inner.shard_load_source.clone();
 
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
@@ -301,6 +440,10 @@ impl ClusterManager {
                     metrics_guard.update_cluster_stats(&state);
                     metrics_guard.update_health_stats(&health_stats);
                 }
+
+                if let Err(e) = refresh_shard_loads(&shard_load_source, &cluster_state, &metrics).await {
+                    warn!("Failed to collect shard loads: {}", e);
+                }
             }
         });
     }
@@ -332,30 +475,58 @@ impl ClusterManager {
             }
         });
     }
+}
 
-    /// Start cluster state synchronization task
-    async fn start_state_sync(&self) {
-        let cluster_state = self.inner.cluster_state.clone();
-        let sync_interval = self.inner.config.state.sync_interval;
-
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(sync_interval);
-
-            loop {
-                interval.tick().await;
-
-                // Synchronize cluster state
-                if let Err(e) = cluster_state.sync_state().await {
-                    error!("Cluster state sync failed: {}", e);
-                }
-            }
-        });
-    }
+/// Sample the load of every assigned shard into the metrics
+///
+/// Does nothing until a shard load source is set.
+async fn refresh_shard_loads(
+    source: &RwLock<Option<Arc<dyn ShardLoadSource>>>,
+    cluster_state: &ClusterStateManager,
+    metrics: &RwLock<ClusterMetrics>,
+) -> Result<()> {
+    let source = match source.read().await.clone() {
+        Some(source) => source,
+        None => return Ok(()),
+    };
+
+    let shard_ids = cluster_state.get_assignments().await.into_keys().collect::<Vec<_>>();
+    let loads = source.shard_loads(&shard_ids).await?;
+    metrics.write().await.update_shard_loads(loads);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
+
+    /// Shard loads as the storage layer would report them
+    #[derive(Debug)]
+    struct FixedLoads(HashMap<String, f64>);
+
+    #[async_trait::async_trait]
+    impl ShardLoadSource for FixedLoads {
+        async fn shard_loads(&self, shard_ids: &[String]) -> Result<HashMap<String, f64>> {
+            Ok(shard_ids
+                .iter()
+                .filter_map(|id| Some((id.clone(), *self.0.get(id)?)))
+                .collect())
+        }
+    }
+
+    /// Transport reaching only the given in-process nodes
+    struct PartialTransport(HashMap<String, ConfigStore>);
+
+    #[async_trait::async_trait]
+    impl ConfigTransport for PartialTransport {
+        async fn push_config(&self, node_id: &str, config: &VersionedConfig) -> Result<u64> {
+            match self.0.get(node_id) {
+                Some(store) => Ok(store.apply(config).await),
+                None => Err(ClusterError::Generic(format!("unreachable node {}", node_id))),
+            }
+        }
+    }
 
     #[tokio::test]
     async fn test_cluster_manager_creation() {
@@ -371,10 +542,83 @@ mod tests {
 
         // Get initial node info
         let node_info = manager.get_node_info().clone();
-        assert!(!node_info.id.is_nil());
+        assert_eq!(node_info.id, "node-1");
 
         // Test cluster state
         let state = manager.get_cluster_state().await;
         assert!(state.nodes.is_empty()); // Initially empty
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rebalance_uses_reported_shard_loads() {
+        let manager = ClusterManager::new(ClusterConfig::default()).await.unwrap();
+        manager.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(manager.is_leader().await);
+
+        manager.add_node(NodeInfo::new("node-2", "10.0.0.2:7911")).await.unwrap();
+        manager.add_node(NodeInfo::new("node-3", "10.0.0.3:7911")).await.unwrap();
+        let shards = ["shard-1", "shard-2", "shard-3", "shard-4"];
+        for shard in shards {
+            manager.inner.cluster_state.assign_shard(shard, "node-1").await.unwrap();
+        }
+
+        // Without a load source every shard counts as idle
+        assert!(manager.rebalance_cluster().await.unwrap().is_empty());
+
+        let loads = shards.iter().map(|shard| (shard.to_string(), 10.0)).collect();
+        manager.set_shard_load_source(Arc::new(FixedLoads(loads))).await;
+        let plan = manager.rebalance_cluster().await.unwrap();
+        assert_eq!(plan.moves.len(), 2);
+        assert!(plan.moves.iter().all(|shard_move| shard_move.from_node == "node-1"));
+        assert_eq!(manager.get_metrics().await.shard_loads.len(), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_node_hands_over_shards() {
+        let manager = ClusterManager::new(ClusterConfig::default()).await.unwrap();
+        let (node_a, node_b) = (Uuid::new_v4(), Uuid::new_v4());
+        for node_id in [node_a, node_b] {
+            manager.add_node(NodeInfo::new(&node_id.to_string(), "10.0.0.2:7911")).await.unwrap();
+        }
+        manager.inner.cluster_state.assign_shard("shard-1", &node_a.to_string()).await.unwrap();
+        manager.report_active_connections(&node_a.to_string(), 2).await;
+
+        let drain = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.drain_node(&node_a, Duration::from_secs(60)).await }
+        });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(manager.get_node_status(&node_a).await, NodeStatus::Draining);
+        assert!(!drain.is_finished());
+
+        manager.report_active_connections(&node_a.to_string(), 0).await;
+        assert!(drain.await.unwrap().unwrap());
+        assert_eq!(manager.inner.cluster_state.get_assignments().await["shard-1"], node_b.to_string());
+        assert!(!manager.remove_node(&node_a).await.unwrap());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_propagate_config_reports_unreachable_members() {
+        let config = ClusterConfig {
+            members: vec!["node-1".to_string(), "node-2".to_string(), "node-3".to_string()],
+            ..Default::default()
+        };
+        let manager = ClusterManager::new(config).await.unwrap();
+        manager.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Node 3 is configured but was never discovered
+        manager.add_node(NodeInfo::new("node-2", "10.0.0.2:7911")).await.unwrap();
+        manager.report_node_health("node-2", true).await;
+        let node_2 = ConfigStore::default();
+        manager
+            .set_config_transport(Arc::new(PartialTransport(HashMap::from([("node-2".to_string(), node_2.clone())]))))
+            .await;
+
+        let result = manager.propagate_config(b"v1".to_vec()).await.unwrap();
+        assert_eq!(result.applied, ["node-2"]);
+        assert_eq!(result.lagged, ["node-3"]);
+        assert_eq!(node_2.version().await, result.version);
+    }
 }
@@ -1,7 +1,58 @@
 //! Metrics module
 
+use crate::error::Result;
+use crate::health::HealthStatistics;
+use crate::state::ClusterState;
+use async_trait::async_trait;
+use std::collections::HashMap;
+
 /// Metrics placeholder
 pub struct Metrics;
 
+/// Source of the load carried by each shard, such as the storage layer's request rates
+#[async_trait]
+pub trait ShardLoadSource: std::fmt::Debug + Send + Sync {
+    /// Get the current load of the given shards, shards without a value count as idle
+    async fn shard_loads(&self, shard_ids: &[String]) -> Result<HashMap<String, f64>>;
+}
+
 /// Cluster metrics
-pub struct ClusterMetrics;
+#[derive(Debug, Clone, Default)]
+pub struct ClusterMetrics {
+    /// Load carried by each shard, in arbitrary units comparable across shards
+    pub shard_loads: HashMap<String, f64>,
+    /// Nodes in the cluster state
+    pub node_count: usize,
+    /// Nodes whose last health check passed
+    pub healthy_nodes: usize,
+    /// Nodes whose last health check failed
+    pub unhealthy_nodes: usize,
+}
+
+impl ClusterMetrics {
+    /// Create empty cluster metrics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the node count from the cluster state
+    pub fn update_cluster_stats(&mut self, state: &ClusterState) {
+        self.node_count = state.nodes.len();
+    }
+
+    /// Update the node health counts
+    pub fn update_health_stats(&mut self, stats: &HealthStatistics) {
+        self.healthy_nodes = stats.healthy;
+        self.unhealthy_nodes = stats.unhealthy;
+    }
+
+    /// Record the current load of a shard
+    pub fn record_shard_load(&mut self, shard_id: &str, load: f64) {
+        self.shard_loads.insert(shard_id.to_string(), load);
+    }
+
+    /// Replace the shard loads, dropping shards that are no longer reported
+    pub fn update_shard_loads(&mut self, loads: HashMap<String, f64>) {
+        self.shard_loads = loads;
+    }
+}
@@ -1,19 +1,34 @@
 //! Node module
 
+use serde::{Deserialize, Serialize};
+
 /// Node placeholder
 pub struct Node;
 
 /// Node information
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NodeInfo {
     pub id: String,
     pub address: String,
 }
 
+impl NodeInfo {
+    /// Create node information
+    pub fn new(id: &str, address: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            address: address.to_string(),
+        }
+    }
+}
+
 /// Node status
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NodeStatus {
     Active,
     Inactive,
     Joining,
+    /// Finishing in-flight work before removal, receives no new work
+    Draining,
     Leaving,
 }
@@ -0,0 +1,97 @@
+//! Quorum module
+//!
+//! Majority checks that keep both sides of a network partition from acting
+//! as leader at the same time.
+
+use crate::error::{ClusterError, Result};
+use std::collections::HashSet;
+
+/// Majority requirement over the configured cluster members
+#[derive(Debug, Clone)]
+pub struct QuorumPolicy {
+    members: HashSet<String>,
+    local_node: String,
+}
+
+impl QuorumPolicy {
+    /// Create a policy for the configured members as seen from `local_node`
+    ///
+    /// The local node always counts as a member, so an empty member list
+    /// describes a single-node cluster.
+    pub fn new(members: &[String], local_node: &str) -> Self {
+        let mut members = members.iter().cloned().collect::<HashSet<_>>();
+        members.insert(local_node.to_string());
+
+        Self {
+            members,
+            local_node: local_node.to_string(),
+        }
+    }
+
+    /// Number of members that must be visible for a quorum
+    pub fn quorum_size(&self) -> usize {
+        self.members.len() / 2 + 1
+    }
+
+    /// Count the members visible from this node, including itself
+    pub fn visible_members<'a>(&self, reachable: impl IntoIterator<Item = &'a str>) -> usize {
+        reachable
+            .into_iter()
+            .chain(std::iter::once(self.local_node.as_str()))
+            .filter(|node| self.members.contains(*node))
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Check if the reachable nodes form a majority of the members
+    pub fn has_quorum<'a>(&self, reachable: impl IntoIterator<Item = &'a str>) -> bool {
+        self.visible_members(reachable) >= self.quorum_size()
+    }
+
+    /// Fail with [`ClusterError::NoQuorum`] unless the reachable nodes form a majority
+    pub fn require<'a>(&self, reachable: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        let visible = self.visible_members(reachable);
+        let required = self.quorum_size();
+
+        if visible >= required {
+            Ok(())
+        } else {
+            Err(ClusterError::NoQuorum { visible, required })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members() -> Vec<String> {
+        ["node-a", "node-b", "node-c", "node-d", "node-e"].iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_partition_quorum() {
+        // Partition {a, b} | {c, d, e}
+        let minority = QuorumPolicy::new(&members(), "node-a");
+        let majority = QuorumPolicy::new(&members(), "node-c");
+        assert_eq!(minority.quorum_size(), 3);
+
+        assert!(!minority.has_quorum(["node-b"]));
+        assert!(matches!(
+            minority.require(["node-b"]),
+            Err(ClusterError::NoQuorum { visible: 2, required: 3 })
+        ));
+
+        assert!(majority.has_quorum(["node-d", "node-e"]));
+        assert!(majority.require(["node-d", "node-e"]).is_ok());
+
+        // Unknown and repeated nodes do not count towards the majority
+        assert!(!minority.has_quorum(["node-b", "node-b", "node-x", "node-y"]));
+    }
+
+    #[test]
+    fn test_single_node_quorum() {
+        let policy = QuorumPolicy::new(&[], "node-a");
+        assert!(policy.has_quorum(Vec::<&str>::new()));
+    }
+}
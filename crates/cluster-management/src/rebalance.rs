@@ -0,0 +1,185 @@
+//! Rebalance module
+//!
+//! Plans shard moves that even out load across healthy nodes.
+
+use crate::config::RebalanceConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Move of one shard between nodes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShardMove {
+    pub shard_id: String,
+    pub from_node: String,
+    pub to_node: String,
+    /// Load carried by the shard
+    pub load: f64,
+}
+
+/// Ordered shard moves produced by a rebalance
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RebalancePlan {
+    pub moves: Vec<ShardMove>,
+}
+
+impl RebalancePlan {
+    /// Check if the plan moves nothing
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+}
+
+/// Plan shard moves from over-loaded to under-loaded nodes
+///
+/// A node's load is the sum of the loads of its shards. Moves are planned
+/// greedily from the hottest to the coldest node until every node is within
+/// the imbalance tolerance of the mean, so an already balanced cluster gets
+/// an empty plan. Shards on nodes missing from `nodes` are left alone.
+pub fn plan_rebalance(
+    assignments: &HashMap<String, String>,
+    shard_loads: &HashMap<String, f64>,
+    nodes: &[String],
+    config: &RebalanceConfig,
+) -> RebalancePlan {
+    let mut plan = RebalancePlan::default();
+    if nodes.len() < 2 {
+        return plan;
+    }
+
+    // Ordered maps keep the plan deterministic
+    let mut node_loads: BTreeMap<&str, f64> = nodes.iter().map(|node| (node.as_str(), 0.0)).collect();
+    let mut node_shards: BTreeMap<&str, Vec<(&str, f64)>> = BTreeMap::new();
+    let mut shards = assignments.iter().collect::<Vec<_>>();
+    shards.sort();
+    for (shard, node) in shards {
+        if let Some(load) = node_loads.get_mut(node.as_str()) {
+            let shard_load = shard_loads.get(shard).copied().unwrap_or(0.0);
+            *load += shard_load;
+            node_shards.entry(node.as_str()).or_default().push((shard.as_str(), shard_load));
+        }
+    }
+
+    let mean = node_loads.values().sum::<f64>() / node_loads.len() as f64;
+    if mean <= 0.0 {
+        return plan;
+    }
+    let limit = mean * (1.0 + config.imbalance_tolerance);
+
+    while plan.moves.len() < config.max_moves {
+        let (hottest, hottest_load) = node_loads
+            .iter()
+            .fold(None, |max: Option<(&str, f64)>, (node, load)| match max {
+                Some((_, max_load)) if max_load >= *load => max,
+                _ => Some((*node, *load)),
+            })
+            .unwrap();
+        let (coldest, coldest_load) = node_loads
+            .iter()
+            .fold(None, |min: Option<(&str, f64)>, (node, load)| match min {
+                Some((_, min_load)) if min_load <= *load => min,
+                _ => Some((*node, *load)),
+            })
+            .unwrap();
+
+        if hottest_load <= limit {
+            break;
+        }
+
+        // Pick the shard that leaves the two nodes closest to each other,
+        // skipping shards too large to reduce the gap
+        let gap = hottest_load - coldest_load;
+        let candidate = node_shards
+            .get(hottest)
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, (_, load))| *load > 0.0 && *load < gap)
+            .min_by(|(_, (_, a)), (_, (_, b))| (gap - 2.0 * a).abs().total_cmp(&(gap - 2.0 * b).abs()))
+            .map(|(index, _)| index);
+
+        let index = match candidate {
+            Some(index) => index,
+            None => break,
+        };
+
+        let (shard, load) = node_shards.get_mut(hottest).unwrap().remove(index);
+        node_shards.entry(coldest).or_default().push((shard, load));
+        *node_loads.get_mut(hottest).unwrap() -= load;
+        *node_loads.get_mut(coldest).unwrap() += load;
+
+        plan.moves.push(ShardMove {
+            shard_id: shard.to_string(),
+            from_node: hottest.to_string(),
+            to_node: coldest.to_string(),
+            load,
+        });
+    }
+
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ClusterStateManager;
+
+    fn cluster(shards: &[(&str, &str, f64)]) -> (HashMap<String, String>, HashMap<String, f64>) {
+        let assignments = shards.iter().map(|(shard, node, _)| (shard.to_string(), node.to_string())).collect();
+        let loads = shards.iter().map(|(shard, _, load)| (shard.to_string(), *load)).collect();
+        (assignments, loads)
+    }
+
+    fn nodes() -> Vec<String> {
+        vec!["node-a".to_string(), "node-b".to_string(), "node-c".to_string()]
+    }
+
+    #[tokio::test]
+    async fn test_rebalance_moves_work_from_hottest_node() {
+        let (assignments, loads) = cluster(&[
+            ("shard-1", "node-a", 30.0),
+            ("shard-2", "node-a", 25.0),
+            ("shard-3", "node-a", 20.0),
+            ("shard-4", "node-a", 15.0),
+            ("shard-5", "node-b", 10.0),
+            ("shard-6", "node-c", 5.0),
+        ]);
+        let config = RebalanceConfig::default();
+
+        let plan = plan_rebalance(&assignments, &loads, &nodes(), &config);
+        assert!(!plan.is_empty());
+        assert!(plan.moves.iter().all(|shard_move| shard_move.from_node == "node-a"), "{:?}", plan);
+
+        // Applying the plan brings every node within the tolerance of the mean
+        let state = ClusterStateManager::default();
+        for (shard, node) in &assignments {
+            state.assign_shard(shard, node).await.unwrap();
+        }
+        state.apply_rebalance(&plan).await.unwrap();
+
+        let applied = state.get_assignments().await;
+        let mean = loads.values().sum::<f64>() / 3.0;
+        for node in nodes() {
+            let load = applied
+                .iter()
+                .filter(|(_, assigned)| **assigned == node)
+                .map(|(shard, _)| loads[shard])
+                .sum::<f64>();
+            assert!(load <= mean * (1.0 + config.imbalance_tolerance), "{} has load {}", node, load);
+        }
+
+        // A balanced cluster needs no further moves
+        assert!(plan_rebalance(&applied, &loads, &nodes(), &config).is_empty());
+    }
+
+    #[test]
+    fn test_rebalance_within_tolerance_is_noop() {
+        let (assignments, loads) = cluster(&[
+            ("shard-1", "node-a", 11.0),
+            ("shard-2", "node-b", 10.0),
+            ("shard-3", "node-c", 9.0),
+        ]);
+
+        let plan = plan_rebalance(&assignments, &loads, &nodes(), &RebalanceConfig::default());
+        assert!(plan.is_empty());
+    }
+}
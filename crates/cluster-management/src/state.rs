@@ -1,5 +1,18 @@
 //! State module
 
+use crate::error::{ClusterError, Result};
+use crate::node::{NodeInfo, NodeStatus};
+use crate::rebalance::RebalancePlan;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// Interval between checks of a draining node's active connections
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// State placeholder
 pub struct State;
 
@@ -11,4 +24,270 @@ pub struct ClusterState {
 }
 
 /// Cluster state manager
-pub struct ClusterStateManager;
+#[derive(Debug, Clone, Default)]
+pub struct ClusterStateManager {
+    nodes: Arc<RwLock<Vec<NodeInfo>>>,
+    /// Node each shard is assigned to
+    assignments: Arc<RwLock<HashMap<String, String>>>,
+    /// Status of nodes that are not simply active
+    statuses: Arc<RwLock<HashMap<String, NodeStatus>>>,
+    /// Connections each node is currently serving
+    connections: Arc<RwLock<HashMap<String, u64>>>,
+}
+
+impl ClusterStateManager {
+    /// Get the nodes of the cluster
+    ///
+    /// The leader is not tracked here and is left unset.
+    pub async fn get_state(&self) -> ClusterState {
+        ClusterState {
+            nodes: self.nodes.read().await.clone(),
+            leader: None,
+        }
+    }
+
+    /// Add or update a node
+    pub async fn add_node(&self, node: NodeInfo) -> Result<()> {
+        let mut nodes = self.nodes.write().await;
+        match nodes.iter_mut().find(|existing| existing.id == node.id) {
+            Some(existing) => *existing = node,
+            None => nodes.push(node),
+        }
+        Ok(())
+    }
+
+    /// Remove a node, returning whether it was known
+    pub async fn remove_node(&self, node_id: &str) -> Result<bool> {
+        let mut nodes = self.nodes.write().await;
+        let before = nodes.len();
+        nodes.retain(|node| node.id != node_id);
+        Ok(nodes.len() != before)
+    }
+
+    /// Replace the node list with the nodes found by service discovery
+    pub async fn update_discovered_nodes(&self, nodes: Vec<NodeInfo>) -> Result<()> {
+        debug!("Discovered {} nodes", nodes.len());
+        *self.nodes.write().await = nodes;
+        Ok(())
+    }
+
+    /// Assign a shard to a node, refusing nodes that are draining
+    pub async fn assign_shard(&self, shard_id: &str, node_id: &str) -> Result<()> {
+        if self.get_node_status(node_id).await == NodeStatus::Draining {
+            return Err(ClusterError::NodeDraining(node_id.to_string()));
+        }
+
+        self.assignments.write().await.insert(shard_id.to_string(), node_id.to_string());
+        Ok(())
+    }
+
+    /// Get the status of a node, nodes without a recorded status are active
+    pub async fn get_node_status(&self, node_id: &str) -> NodeStatus {
+        self.statuses.read().await.get(node_id).copied().unwrap_or(NodeStatus::Active)
+    }
+
+    /// Get the status of every node with a recorded status
+    pub async fn get_node_statuses(&self) -> HashMap<String, NodeStatus> {
+        self.statuses.read().await.clone()
+    }
+
+    /// Set the status of a node
+    pub async fn set_node_status(&self, node_id: &str, status: NodeStatus) {
+        self.statuses.write().await.insert(node_id.to_string(), status);
+    }
+
+    /// Record the number of connections a node is serving
+    pub async fn set_active_connections(&self, node_id: &str, count: u64) {
+        self.connections.write().await.insert(node_id.to_string(), count);
+    }
+
+    /// Get the number of connections a node is serving
+    pub async fn get_active_connections(&self, node_id: &str) -> u64 {
+        self.connections.read().await.get(node_id).copied().unwrap_or(0)
+    }
+
+    /// Drop the status and connection count of a removed node
+    pub async fn forget_node(&self, node_id: &str) {
+        self.statuses.write().await.remove(node_id);
+        self.connections.write().await.remove(node_id);
+    }
+
+    /// Mark a node as draining and wait for its connections to finish
+    ///
+    /// Returns `true` once the node has no active connections, or `false`
+    /// when the timeout ran out first. The node stays draining either way.
+    pub async fn drain_node(&self, node_id: &str, timeout: Duration) -> bool {
+        info!("Draining node {}", node_id);
+        self.set_node_status(node_id, NodeStatus::Draining).await;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let active = self.get_active_connections(node_id).await;
+            if active == 0 {
+                info!("Node {} drained", node_id);
+                return true;
+            }
+            if Instant::now() >= deadline {
+                warn!("Node {} still has {} active connections after drain timeout", node_id, active);
+                return false;
+            }
+
+            debug!("Waiting for {} active connections on node {}", active, node_id);
+            tokio::time::sleep(DRAIN_POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now()))).await;
+        }
+    }
+
+    /// Hand the shards of a removed node to the remaining nodes
+    ///
+    /// Each shard goes to the non-draining node holding the fewest shards.
+    /// When no such node is left the assignments are dropped, so no shard
+    /// points at a node that is gone. Returns the number of shards affected.
+    pub async fn reassign_shards(&self, node_id: &str) -> usize {
+        let statuses = self.get_node_statuses().await;
+        let mut shard_counts = self
+            .nodes
+            .read()
+            .await
+            .iter()
+            .filter(|node| node.id != node_id && statuses.get(&node.id) != Some(&NodeStatus::Draining))
+            .map(|node| (node.id.clone(), 0usize))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut assignments = self.assignments.write().await;
+        for node in assignments.values() {
+            if let Some(count) = shard_counts.get_mut(node) {
+                *count += 1;
+            }
+        }
+
+        let mut orphaned = assignments
+            .iter()
+            .filter(|(_, node)| *node == node_id)
+            .map(|(shard, _)| shard.clone())
+            .collect::<Vec<_>>();
+        orphaned.sort();
+
+        for shard in &orphaned {
+            match shard_counts.iter_mut().min_by_key(|(_, count)| **count) {
+                Some((target, count)) => {
+                    info!("Reassigning shard {} from removed node {} to {}", shard, node_id, target);
+                    *count += 1;
+                    assignments.insert(shard.clone(), target.clone());
+                }
+                None => {
+                    warn!("No node left for shard {} of removed node {}, unassigning it", shard, node_id);
+                    assignments.remove(shard);
+                }
+            }
+        }
+
+        orphaned.len()
+    }
+
+    /// Get the node each shard is assigned to
+    pub async fn get_assignments(&self) -> HashMap<String, String> {
+        self.assignments.read().await.clone()
+    }
+
+    /// Apply the moves of a rebalance plan in order
+    ///
+    /// Moves whose shard is no longer on the expected node are skipped.
+    pub async fn apply_rebalance(&self, plan: &RebalancePlan) -> Result<()> {
+        let total = plan.moves.len();
+
+        for (index, shard_move) in plan.moves.iter().enumerate() {
+            if self.get_node_status(&shard_move.to_node).await == NodeStatus::Draining {
+                warn!(
+                    "Rebalance progress {}/{}: skipped shard {}, {} is draining",
+                    index + 1, total, shard_move.shard_id, shard_move.to_node
+                );
+                continue;
+            }
+
+            let mut assignments = self.assignments.write().await;
+            match assignments.get_mut(&shard_move.shard_id) {
+                Some(node) if *node == shard_move.from_node => {
+                    *node = shard_move.to_node.clone();
+                    info!(
+                        "Rebalance progress {}/{}: moved shard {} from {} to {}",
+                        index + 1, total, shard_move.shard_id, shard_move.from_node, shard_move.to_node
+                    );
+                }
+                _ => warn!(
+                    "Rebalance progress {}/{}: skipped shard {}, no longer on {}",
+                    index + 1, total, shard_move.shard_id, shard_move.from_node
+                ),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_node() {
+        let state = ClusterStateManager::default();
+        state.assign_shard("shard-1", "node-a").await.unwrap();
+        state.set_active_connections("node-a", 3).await;
+
+        let drain = tokio::spawn({
+            let state = state.clone();
+            async move { state.drain_node("node-a", Duration::from_secs(60)).await }
+        });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        // Draining nodes take no new assignments while in-flight work finishes
+        assert_eq!(state.get_node_status("node-a").await, NodeStatus::Draining);
+        assert!(matches!(
+            state.assign_shard("shard-2", "node-a").await,
+            Err(ClusterError::NodeDraining(_))
+        ));
+        state.assign_shard("shard-2", "node-b").await.unwrap();
+        assert!(!drain.is_finished());
+
+        state.set_active_connections("node-a", 0).await;
+        let started = Instant::now();
+        assert!(drain.await.unwrap());
+        assert!(started.elapsed() <= DRAIN_POLL_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn test_reassign_shards_of_removed_node() {
+        let state = ClusterStateManager::default();
+        for node_id in ["node-a", "node-b", "node-c"] {
+            state.add_node(NodeInfo::new(node_id, "127.0.0.1:7911")).await.unwrap();
+        }
+        state.assign_shard("shard-1", "node-a").await.unwrap();
+        state.assign_shard("shard-2", "node-a").await.unwrap();
+        state.assign_shard("shard-3", "node-b").await.unwrap();
+        state.set_node_status("node-c", NodeStatus::Draining).await;
+
+        // Draining nodes take no shards, so everything lands on node-b
+        assert!(state.remove_node("node-a").await.unwrap());
+        assert_eq!(state.reassign_shards("node-a").await, 2);
+        let assignments = state.get_assignments().await;
+        assert_eq!(assignments.len(), 3);
+        assert!(assignments.values().all(|node| node == "node-b"));
+
+        // Without any node left the assignments are dropped
+        state.remove_node("node-b").await.unwrap();
+        state.remove_node("node-c").await.unwrap();
+        assert_eq!(state.reassign_shards("node-b").await, 3);
+        assert!(state.get_assignments().await.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_node_timeout() {
+        let state = ClusterStateManager::default();
+        state.set_active_connections("node-a", 1).await;
+
+        let started = Instant::now();
+        assert!(!state.drain_node("node-a", Duration::from_secs(5)).await);
+        assert_eq!(started.elapsed(), Duration::from_secs(5));
+        assert_eq!(state.get_node_status("node-a").await, NodeStatus::Draining);
+    }
+}
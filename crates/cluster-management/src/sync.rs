@@ -1,4 +1,327 @@
 //! Sync module
+//!
+//! Versioned configuration propagation from the leader to the other nodes.
+
+use crate::config::PropagationConfig;
+use crate::discovery::DiscoveryBackend;
+use crate::error::{ClusterError, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use tokio::task::JoinSet;
+use tracing::{debug, info, warn};
+
+/// Path nodes accept configuration pushes on
+pub const CONFIG_PUSH_PATH: &str = "/cluster/config";
 
 /// Sync placeholder
 pub struct Sync;
+
+/// Configuration stamped with the version it was propagated as
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VersionedConfig {
+    pub version: u64,
+    pub data: Vec<u8>,
+}
+
+/// Transport pushing configuration to other nodes
+#[async_trait]
+pub trait ConfigTransport: Send + Sync {
+    /// Push a configuration to a node and return the version the node holds afterwards
+    async fn push_config(&self, node_id: &str, config: &VersionedConfig) -> Result<u64>;
+}
+
+/// Reply of a node to a configuration push
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConfigAck {
+    /// Version the node holds after applying the push
+    pub version: u64,
+}
+
+/// Transport pushing configuration over HTTP to the address each node registered in discovery
+///
+/// Nodes accept the push as a JSON [`VersionedConfig`] on [`CONFIG_PUSH_PATH`],
+/// pass it to `ClusterManager::apply_config` and reply with a JSON [`ConfigAck`].
+#[derive(Debug)]
+pub struct HttpConfigTransport {
+    client: reqwest::Client,
+    discovery: DiscoveryBackend,
+    scheme: &'static str,
+}
+
+impl HttpConfigTransport {
+    /// Create a transport resolving node addresses through discovery
+    pub fn new(config: &PropagationConfig, discovery: DiscoveryBackend) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(config.ack_timeout)
+            .build()
+            .map_err(|e| ClusterError::Configuration(format!("Failed to create configuration push client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            discovery,
+            scheme: if config.use_tls { "https" } else { "http" },
+        })
+    }
+}
+
+#[async_trait]
+impl ConfigTransport for HttpConfigTransport {
+    async fn push_config(&self, node_id: &str, config: &VersionedConfig) -> Result<u64> {
+        let address = self
+            .discovery
+            .discover_nodes()
+            .await?
+            .into_iter()
+            .find(|node| node.id == node_id)
+            .map(|node| node.address)
+            .ok_or_else(|| ClusterError::Generic(format!("No address known for node {}", node_id)))?;
+
+        let url = format!("{}://{}{}", self.scheme, address, CONFIG_PUSH_PATH);
+        let push_error = |e: reqwest::Error| ClusterError::Generic(format!("Configuration push to {} failed: {}", url, e));
+        let ack: ConfigAck = self
+            .client
+            .post(&url)
+            .json(config)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(push_error)?
+            .json()
+            .await
+            .map_err(push_error)?;
+
+        Ok(ack.version)
+    }
+}
+
+/// Outcome of a configuration propagation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PropagationResult {
+    pub version: u64,
+    /// Nodes that acknowledged the new version
+    pub applied: Vec<String>,
+    /// Nodes that were unreachable, failed, timed out or hold a different version
+    pub lagged: Vec<String>,
+}
+
+impl PropagationResult {
+    /// Check if every node applied the new version
+    pub fn is_complete(&self) -> bool {
+        self.lagged.is_empty()
+    }
+}
+
+/// Configuration held by a node
+#[derive(Debug, Clone, Default)]
+pub struct ConfigStore {
+    current: Arc<RwLock<VersionedConfig>>,
+}
+
+impl ConfigStore {
+    /// Apply a configuration unless it is older than the current one
+    ///
+    /// Returns the version held after the call, so a stale configuration
+    /// leaves the current version in place.
+    pub async fn apply(&self, config: &VersionedConfig) -> u64 {
+        let mut current = self.current.write().await;
+        if config.version < current.version {
+            warn!(
+                "Ignoring configuration version {}, already at version {}",
+                config.version, current.version
+            );
+        } else if config.version > current.version {
+            debug!("Applying configuration version {}", config.version);
+            *current = config.clone();
+        }
+        current.version
+    }
+
+    /// Get the current configuration version
+    pub async fn version(&self) -> u64 {
+        self.current.read().await.version
+    }
+
+    /// Get the current configuration
+    pub async fn get(&self) -> VersionedConfig {
+        self.current.read().await.clone()
+    }
+}
+
+/// Stamps configuration versions and pushes them to the cluster
+pub struct ConfigPropagator {
+    config: PropagationConfig,
+    transport: Arc<dyn ConfigTransport>,
+    last_version: Mutex<u64>,
+}
+
+impl ConfigPropagator {
+    /// Create a new propagator over a transport
+    pub fn new(config: &PropagationConfig, transport: Arc<dyn ConfigTransport>) -> Self {
+        Self {
+            config: config.clone(),
+            transport,
+            last_version: Mutex::new(0),
+        }
+    }
+
+    /// Stamp the next version, above both the last stamped and the local one
+    fn next_version(&self, local_version: u64) -> u64 {
+        let mut last_version = self.last_version.lock().unwrap();
+        *last_version = (*last_version).max(local_version) + 1;
+        *last_version
+    }
+
+    /// Apply a configuration locally and push it to `nodes`
+    ///
+    /// Pushes run concurrently and each waits at most the acknowledgement
+    /// timeout. Every node in `nodes` that does not acknowledge the new
+    /// version, including nodes that cannot be reached at all, is reported
+    /// as lagging.
+    pub async fn propagate(&self, local: &ConfigStore, nodes: &[String], data: Vec<u8>) -> Result<PropagationResult> {
+        let config = Arc::new(VersionedConfig {
+            version: self.next_version(local.version().await),
+            data,
+        });
+
+        if local.apply(&config).await != config.version {
+            return Err(ClusterError::Generic(format!(
+                "Local node rejected configuration version {}",
+                config.version
+            )));
+        }
+
+        info!("Propagating configuration version {} to {} nodes", config.version, nodes.len());
+
+        let mut pushes = JoinSet::new();
+        for node_id in nodes {
+            let node_id = node_id.clone();
+            let config = Arc::clone(&config);
+            let transport = Arc::clone(&self.transport);
+            let ack_timeout = self.config.ack_timeout;
+
+            pushes.spawn(async move {
+                let ack = tokio::time::timeout(ack_timeout, transport.push_config(&node_id, &config)).await;
+                (node_id, ack)
+            });
+        }
+
+        let mut result = PropagationResult {
+            version: config.version,
+            ..Default::default()
+        };
+        while let Some(joined) = pushes.join_next().await {
+            let (node_id, ack) = joined
+                .map_err(|e| ClusterError::Generic(format!("Configuration push task failed: {}", e)))?;
+
+            match ack {
+                Ok(Ok(version)) if version == config.version => result.applied.push(node_id),
+                Ok(Ok(version)) => {
+                    warn!("Node {} holds configuration version {} instead of {}", node_id, version, config.version);
+                    result.lagged.push(node_id);
+                }
+                Ok(Err(e)) => {
+                    warn!("Failed to push configuration to node {}: {}", node_id, e);
+                    result.lagged.push(node_id);
+                }
+                Err(_) => {
+                    warn!("Node {} did not acknowledge configuration version {} in time", node_id, config.version);
+                    result.lagged.push(node_id);
+                }
+            }
+        }
+
+        result.applied.sort();
+        result.lagged.sort();
+        Ok(result)
+    }
+}
+
+impl std::fmt::Debug for ConfigPropagator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigPropagator")
+            .field("config", &self.config)
+            .field("last_version", &self.last_version)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Transport delivering straight to in-process node stores
+    #[derive(Default)]
+    struct MockTransport {
+        nodes: HashMap<String, ConfigStore>,
+    }
+
+    #[async_trait]
+    impl ConfigTransport for MockTransport {
+        async fn push_config(&self, node_id: &str, config: &VersionedConfig) -> Result<u64> {
+            match self.nodes.get(node_id) {
+                Some(store) => Ok(store.apply(config).await),
+                None => Err(ClusterError::Generic(format!("unreachable node {}", node_id))),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_transport_unknown_node() {
+        let discovery: DiscoveryBackend = Arc::new(crate::discovery::MemoryDiscovery::default());
+        let transport = HttpConfigTransport::new(&PropagationConfig::default(), discovery).unwrap();
+
+        let err = transport.push_config("node-b", &VersionedConfig::default()).await.unwrap_err();
+        assert!(err.to_string().contains("No address known for node node-b"));
+    }
+
+    fn node_ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_propagate_config_to_all_nodes() {
+        let transport = Arc::new(MockTransport {
+            nodes: ["node-b", "node-c"].iter().map(|id| (id.to_string(), ConfigStore::default())).collect(),
+        });
+        let leader = ConfigStore::default();
+        let propagator = ConfigPropagator::new(&PropagationConfig::default(), transport.clone());
+
+        let result = propagator.propagate(&leader, &node_ids(&["node-b", "node-c"]), b"v1".to_vec()).await.unwrap();
+        assert_eq!(result.version, 1);
+        assert_eq!(result.applied, node_ids(&["node-b", "node-c"]));
+        assert!(result.is_complete());
+
+        // Versions keep increasing
+        let result = propagator.propagate(&leader, &node_ids(&["node-b", "node-c"]), b"v2".to_vec()).await.unwrap();
+        assert_eq!(result.version, 2);
+        for store in transport.nodes.values().chain([&leader]) {
+            assert_eq!(store.get().await, VersionedConfig { version: 2, data: b"v2".to_vec() });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stale_config_rejected() {
+        let store = ConfigStore::default();
+        assert_eq!(store.apply(&VersionedConfig { version: 5, data: b"new".to_vec() }).await, 5);
+        assert_eq!(store.apply(&VersionedConfig { version: 3, data: b"old".to_vec() }).await, 5);
+        assert_eq!(store.get().await.data, b"new");
+
+        // A node ahead of the leader and an unreachable node both lag
+        let transport = Arc::new(MockTransport {
+            nodes: [("node-b".to_string(), ConfigStore::default()), ("node-c".to_string(), store.clone())].into(),
+        });
+        let propagator = ConfigPropagator::new(&PropagationConfig::default(), transport);
+
+        let result = propagator
+            .propagate(&ConfigStore::default(), &node_ids(&["node-b", "node-c", "node-d"]), b"v1".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(result.version, 1);
+        assert_eq!(result.applied, node_ids(&["node-b"]));
+        assert_eq!(result.lagged, node_ids(&["node-c", "node-d"]));
+        assert_eq!(store.get().await.data, b"new");
+    }
+}
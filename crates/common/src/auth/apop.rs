@@ -0,0 +1,132 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use directory::backend::internal::SpecialSecrets;
+
+/// Compute the APOP digest, MD5(timestamp + password), as lowercase hex
+pub fn compute_apop_digest(timestamp: &str, password: &str) -> String {
+    let mut input = String::with_capacity(timestamp.len() + password.len());
+    input.push_str(timestamp);
+    input.push_str(password);
+    format!("{:x}", md5::compute(input.as_bytes()))
+}
+
+/// Check an APOP digest against the secrets of an account
+///
+/// APOP carries no second factor, so accounts with TOTP enabled are refused.
+/// Only account passwords stored in cleartext can produce a digest, hashed
+/// secrets and app passwords are skipped.
+pub fn verify_apop_digest<'x>(
+    timestamp: &str,
+    secrets: impl IntoIterator<Item = &'x str>,
+    digest: &str,
+) -> bool {
+    let digest = digest.to_ascii_lowercase();
+    let mut verified = false;
+    for secret in secrets {
+        if secret.is_otp_auth() {
+            return false;
+        } else if let Some(secret) = cleartext_secret(secret) {
+            verified |= constant_time_eq(
+                compute_apop_digest(timestamp, secret).as_bytes(),
+                digest.as_bytes(),
+            );
+        }
+    }
+    verified
+}
+
+/// Account password in cleartext, if it is not stored hashed
+fn cleartext_secret(secret: &str) -> Option<&str> {
+    if !secret.is_password() {
+        None
+    } else if let Some(secret) = secret.strip_prefix('{') {
+        let (algorithm, secret) = secret.split_once('}')?;
+        matches!(algorithm, "PLAIN" | "plain" | "CLEAR" | "clear").then_some(secret)
+    } else if secret.is_empty() || secret.starts_with(['$', '_']) {
+        None
+    } else {
+        Some(secret)
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMESTAMP: &str = "<1896.697170952@dbc.mtview.ca.us>";
+    const DIGEST: &str = "c4c9334bac560ecc979e58001b3e22fb";
+
+    #[test]
+    fn test_apop_digest_computation() {
+        // Test vector from RFC 1939
+        assert_eq!(compute_apop_digest(TIMESTAMP, "tanstaaf"), DIGEST);
+
+        let digest = compute_apop_digest("", "");
+        assert_eq!(digest.len(), 32);
+        assert!(digest.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(
+            compute_apop_digest(TIMESTAMP, "secret1"),
+            compute_apop_digest(TIMESTAMP, "secret2")
+        );
+    }
+
+    #[test]
+    fn test_apop_verification_accepts_correct_digest() {
+        assert!(verify_apop_digest(TIMESTAMP, ["tanstaaf"], DIGEST));
+        assert!(verify_apop_digest(
+            TIMESTAMP,
+            ["$app$phone$tanstaaf", "{PLAIN}tanstaaf"],
+            &DIGEST.to_uppercase()
+        ));
+    }
+
+    #[test]
+    fn test_apop_verification_rejects_wrong_digest() {
+        assert!(!verify_apop_digest(
+            TIMESTAMP,
+            ["tanstaaf"],
+            "00000000000000000000000000000000"
+        ));
+
+        // The digest of a hashed secret must not be accepted
+        let hashed = "{SHA}60cXn4M+9rlkPiIl1CM8mjjFKYE=";
+        assert!(!verify_apop_digest(
+            TIMESTAMP,
+            [hashed],
+            &compute_apop_digest(TIMESTAMP, hashed)
+        ));
+    }
+
+    #[test]
+    fn test_apop_refused_for_totp_accounts() {
+        assert!(!verify_apop_digest(
+            TIMESTAMP,
+            ["otpauth://totp/mrose", "{PLAIN}tanstaaf"],
+            DIGEST
+        ));
+        assert!(!verify_apop_digest(
+            TIMESTAMP,
+            ["tanstaaf", "otpauth://totp/mrose"],
+            DIGEST
+        ));
+    }
+
+    #[test]
+    fn test_apop_refused_for_app_password_only_accounts() {
+        let app_password = "$app$phone$tanstaaf";
+        assert!(!verify_apop_digest(TIMESTAMP, [app_password], DIGEST));
+        assert!(!verify_apop_digest(
+            TIMESTAMP,
+            [app_password],
+            &compute_apop_digest(TIMESTAMP, app_password)
+        ));
+    }
+}
@@ -21,6 +21,7 @@ use utils::{
 use crate::{Server, listener::limiter::ConcurrencyLimiter};
 
 pub mod access_token;
+pub mod apop;
 pub mod oauth;
 pub mod rate_limit;
 pub mod roles;
@@ -64,6 +65,7 @@ pub struct AuthRequest<'x> {
     remote_ip: IpAddr,
     return_member_of: bool,
     allow_api_access: bool,
+    apop_timestamp: Option<&'x str>,
     directory: Option<&'x Directory>,
 }
 
@@ -101,10 +103,26 @@ impl Server {
         directory: &Directory,
     ) -> trc::Result<Principal> {
         // First try to authenticate the user against the default directory
-        let result = match directory
-            .query(QueryBy::Credentials(&req.credentials), req.return_member_of)
-            .await
-        {
+        let lookup = match (&req.credentials, req.apop_timestamp) {
+            (Credentials::Plain { username, secret }, Some(timestamp)) => directory
+                .query(QueryBy::Name(username), req.return_member_of)
+                .await
+                .map(|principal| {
+                    principal.filter(|principal| {
+                        apop::verify_apop_digest(
+                            timestamp,
+                            principal.secrets.iter().map(String::as_str),
+                            secret,
+                        )
+                    })
+                }),
+            _ => {
+                directory
+                    .query(QueryBy::Credentials(&req.credentials), req.return_member_of)
+                    .await
+            }
+        };
+        let result = match lookup {
             Ok(Some(principal)) => {
                 trc::event!(
                     Auth(trc::AuthEvent::Success),
@@ -126,7 +144,9 @@ impl Server {
         };
 
         // Then check if the credentials match the fallback admin or master user
-        if let Credentials::Plain { username, secret } = &req.credentials {
+        if let (Credentials::Plain { username, secret }, None) =
+            (&req.credentials, req.apop_timestamp)
+        {
             match (&self.core.jmap.fallback_admin, &self.core.jmap.master_user) {
                 (Some((fallback_admin, fallback_pass)), _) if username == fallback_admin => {
                     if verify_secret_hash(fallback_pass, secret).await? {
@@ -222,6 +242,22 @@ impl<'x> AuthRequest<'x> {
             return_member_of: true,
             directory: None,
             allow_api_access: false,
+            apop_timestamp: None,
+        }
+    }
+
+    /// Authenticate with an APOP digest computed over the server's greeting
+    /// timestamp (RFC 1939, section 7)
+    pub fn from_apop(
+        user: impl Into<String>,
+        digest: impl Into<String>,
+        timestamp: &'x str,
+        session_id: u64,
+        remote_ip: IpAddr,
+    ) -> Self {
+        Self {
+            apop_timestamp: Some(timestamp),
+            ..Self::from_plain(user, digest, session_id, remote_ip)
         }
     }
 
@@ -9,15 +9,22 @@
 //! This module provides comprehensive alerting capabilities including threshold-based
 //! alerts, alert routing, notification channels, and alert management.
 
-use super::{SystemMetrics, ApplicationMetrics, HealthStatus, AlertThresholds};
+use super::{SystemMetrics, ApplicationMetrics, HealthStatus, AlertThresholds, MonitoringConfig, SloConfig};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     sync::{Arc, RwLock},
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use serde::{Serialize, Deserialize};
 use tracing::{debug, info, warn};
 
+/// Short window of the fast-burn SLO alert
+const FAST_BURN_SHORT_WINDOW: Duration = Duration::from_secs(5 * 60);
+/// Long window of the fast-burn SLO alert
+const FAST_BURN_LONG_WINDOW: Duration = Duration::from_secs(3600);
+/// Share of the error budget that may be consumed within the long window before alerting
+const FAST_BURN_BUDGET_SHARE: f64 = 0.02;
+
 /// Alert severity levels
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AlertSeverity {
@@ -81,6 +88,12 @@ pub enum AlertCondition {
         component: String,
         status: HealthStatus,
     },
+    /// Error budget burn rate exceeds the threshold over both windows
+    BurnRate {
+        short_window: Duration,
+        long_window: Duration,
+        threshold: f64,
+    },
     /// Custom condition function
     Custom {
         name: String,
@@ -172,6 +185,43 @@ impl Alert {
     }
 }
 
+impl AlertRule {
+    /// Create the multi-window fast-burn rule for an SLO
+    pub fn fast_burn(slo: &SloConfig) -> Self {
+        let threshold = FAST_BURN_BUDGET_SHARE * slo.window.as_secs_f64()
+            / FAST_BURN_LONG_WINDOW.as_secs_f64();
+
+        let mut annotations = HashMap::new();
+        annotations.insert(
+            "target_availability".to_string(),
+            slo.target_availability.to_string(),
+        );
+
+        Self {
+            name: "slo_fast_burn".to_string(),
+            description: "Error budget is being consumed too quickly".to_string(),
+            severity: AlertSeverity::Critical,
+            condition: AlertCondition::BurnRate {
+                short_window: FAST_BURN_SHORT_WINDOW,
+                long_window: FAST_BURN_LONG_WINDOW,
+                threshold,
+            },
+            duration: Duration::ZERO,
+            labels: HashMap::new(),
+            annotations,
+            enabled: true,
+        }
+    }
+}
+
+/// Cumulative request counters at a point in time
+#[derive(Debug, Clone, Copy)]
+struct RequestSample {
+    timestamp: u64,
+    successful: u64,
+    failed: u64,
+}
+
 /// Alert manager state for a rule
 #[derive(Debug)]
 struct AlertRuleState {
@@ -208,7 +258,9 @@ pub struct AlertManager {
     rules: Arc<RwLock<HashMap<String, AlertRuleState>>>,
     active_alerts: Arc<RwLock<HashMap<String, Alert>>>,
     alert_history: Arc<RwLock<Vec<Alert>>>,
+    request_samples: Arc<RwLock<VecDeque<RequestSample>>>,
     thresholds: AlertThresholds,
+    slo: Option<SloConfig>,
 }
 
 impl AlertManager {
@@ -219,10 +271,115 @@ impl AlertManager {
             rules: Arc::new(RwLock::new(HashMap::new())),
             active_alerts: Arc::new(RwLock::new(HashMap::new())),
             alert_history: Arc::new(RwLock::new(Vec::new())),
+            request_samples: Arc::new(RwLock::new(VecDeque::new())),
             thresholds,
+            slo: None,
+        }
+    }
+
+    /// Create an alert manager with the thresholds and SLO of a monitoring configuration
+    pub fn from_config(config: &MonitoringConfig) -> Self {
+        Self::new(config.alert_thresholds.clone()).with_slo(config.slo.clone())
+    }
+
+    /// Enable error budget burn-rate alerting for an SLO
+    pub fn with_slo(mut self, slo: SloConfig) -> Self {
+        info!("Enabling SLO alerting with target availability {}", slo.target_availability);
+        self.add_rule(AlertRule::fast_burn(&slo));
+        self.slo = Some(slo);
+        self
+    }
+
+    /// Record request counters used to compute rolling error rates
+    pub fn record_request_counts(&self, app_metrics: &ApplicationMetrics) {
+        let slo = match &self.slo {
+            Some(slo) => slo,
+            None => return,
+        };
+
+        let sample = RequestSample {
+            timestamp: app_metrics.timestamp,
+            successful: app_metrics.successful_requests,
+            failed: app_metrics.failed_requests,
+        };
+
+        let mut samples = self.request_samples.write().unwrap();
+        match samples.back().map(|last| last.timestamp) {
+            Some(timestamp) if timestamp > sample.timestamp => return,
+            Some(timestamp) if timestamp == sample.timestamp => {
+                samples.pop_back();
+                samples.push_back(sample);
+            }
+            _ => samples.push_back(sample),
+        }
+
+        // Keep only the samples covering the SLO window
+        let cutoff_time = sample.timestamp.saturating_sub(slo.window.as_secs());
+        while samples.len() > 1 && samples[1].timestamp <= cutoff_time {
+            samples.pop_front();
         }
     }
 
+    /// Get the error rate over a trailing window, if there was traffic
+    pub fn error_rate(&self, window: Duration) -> Option<f64> {
+        let samples = self.request_samples.read().unwrap();
+        let latest = samples.back()?;
+        let cutoff_time = latest.timestamp.saturating_sub(window.as_secs());
+
+        // Use the newest sample at or before the window start, or the oldest available
+        let baseline = samples
+            .iter()
+            .rev()
+            .find(|sample| sample.timestamp <= cutoff_time)
+            .or_else(|| samples.front())?;
+
+        if baseline.timestamp == latest.timestamp {
+            return None;
+        }
+
+        // Counters going backwards indicate a restart
+        let counter_delta = |from: u64, to: u64| if to >= from { to - from } else { to };
+        let successful = counter_delta(baseline.successful, latest.successful);
+        let failed = counter_delta(baseline.failed, latest.failed);
+        let total = successful + failed;
+
+        if total > 0 {
+            Some(failed as f64 / total as f64)
+        } else {
+            None
+        }
+    }
+
+    /// Get the error budget burn rate over a trailing window
+    pub fn burn_rate(&self, window: Duration) -> Option<f64> {
+        let slo = self.slo.as_ref()?;
+        self.error_rate(window).map(|rate| rate / slo.error_budget())
+    }
+
+    /// Get the projected time until the error budget is exhausted at the given burn rate
+    pub fn budget_exhaustion(&self, burn_rate: f64) -> Option<Duration> {
+        let slo = self.slo.as_ref()?;
+        if burn_rate <= 0.0 {
+            return None;
+        }
+
+        // Budget already consumed over the recorded part of the SLO window
+        let span = {
+            let samples = self.request_samples.read().unwrap();
+            match (samples.front(), samples.back()) {
+                (Some(first), Some(last)) => Duration::from_secs(last.timestamp - first.timestamp),
+                _ => Duration::ZERO,
+            }
+        };
+        let consumed = self.burn_rate(span).unwrap_or(0.0) * span.as_secs_f64()
+            / slo.window.as_secs_f64();
+
+        let remaining = (1.0 - consumed).max(0.0);
+        Some(Duration::from_secs_f64(
+            remaining * slo.window.as_secs_f64() / burn_rate,
+        ))
+    }
+
     /// Add an alert rule
     pub fn add_rule(&self, rule: AlertRule) {
         info!("Adding alert rule: {}", rule.name);
@@ -241,6 +398,8 @@ impl AlertManager {
     pub fn evaluate_rules(&self, system_metrics: &SystemMetrics, app_metrics: &ApplicationMetrics) {
         debug!("Evaluating alert rules");
 
+        self.record_request_counts(app_metrics);
+
         let rule_names: Vec<String> = {
             let rules = self.rules.read().unwrap();
             rules
@@ -279,7 +438,8 @@ impl AlertManager {
                     // Fire alert
                     if rule_state.active_alert.is_none() {
                         let message = self.generate_alert_message(&rule_state.rule, system_metrics, app_metrics);
-                        let alert = Alert::new(&rule_state.rule, message);
+                        let mut alert = Alert::new(&rule_state.rule, message);
+                        alert.annotations.extend(self.condition_annotations(&rule_state.rule.condition));
 
                         warn!("Alert fired: {} - {}", alert.rule_name, alert.message);
 
@@ -292,6 +452,7 @@ impl AlertManager {
                     } else if let Some(ref mut alert) = rule_state.active_alert {
                         // Update existing alert
                         alert.update();
+                        alert.annotations.extend(self.condition_annotations(&rule_state.rule.condition));
 
                         let mut active_alerts = self.active_alerts.write().unwrap();
                         active_alerts.insert(alert.id.clone(), alert.clone());
@@ -337,12 +498,49 @@ impl AlertManager {
 
                 current_status == *status
             }
+            AlertCondition::BurnRate { short_window, long_window, threshold } => {
+                match (self.burn_rate(*short_window), self.burn_rate(*long_window)) {
+                    (Some(short), Some(long)) => short > *threshold && long > *threshold,
+                    _ => false,
+                }
+            }
             AlertCondition::Custom { name: _, evaluator } => {
                 evaluator(system_metrics, app_metrics)
             }
         }
     }
 
+    /// Get condition-specific annotations for a firing alert
+    fn condition_annotations(&self, condition: &AlertCondition) -> HashMap<String, String> {
+        let mut annotations = HashMap::new();
+
+        if let AlertCondition::BurnRate { short_window, long_window, threshold } = condition {
+            let short = self.burn_rate(*short_window).unwrap_or(0.0);
+            let long = self.burn_rate(*long_window).unwrap_or(0.0);
+
+            annotations.insert("burn_rate".to_string(), format!("{:.2}", short));
+            annotations.insert("burn_rate_long_window".to_string(), format!("{:.2}", long));
+            annotations.insert("burn_rate_threshold".to_string(), format!("{:.2}", threshold));
+
+            if let Some(exhaustion) = self.budget_exhaustion(short) {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                annotations.insert(
+                    "budget_exhausted_in_seconds".to_string(),
+                    exhaustion.as_secs().to_string(),
+                );
+                annotations.insert(
+                    "budget_exhausted_at".to_string(),
+                    (now + exhaustion.as_secs()).to_string(),
+                );
+            }
+        }
+
+        annotations
+    }
+
     /// Get metric value by name
     fn get_metric_value(&self, metric: &str, system_metrics: &SystemMetrics, app_metrics: &ApplicationMetrics) -> f64 {
         match metric {
@@ -406,6 +604,11 @@ impl AlertManager {
             AlertCondition::HealthStatus { component, status } => {
                 format!("Component {} is {}", component, status)
             }
+            AlertCondition::BurnRate { short_window, long_window, threshold } => {
+                format!("Error budget burn rate is above {:.2} over {}s and {}s (current: {:.2})",
+                    threshold, short_window.as_secs(), long_window.as_secs(),
+                    self.burn_rate(*short_window).unwrap_or(0.0))
+            }
             AlertCondition::Custom { name, .. } => {
                 format!("Custom condition {} is met", name)
             }
@@ -558,4 +761,76 @@ mod tests {
         assert!(manager.get_active_alerts().len() <= 1);
         assert!(manager.get_alert_history(10).len() <= 1);
     }
+
+    /// Feed two hours of per-minute cumulative counters with a fixed failure ratio
+    fn feed_request_series(manager: &AlertManager, failure_ratio: f64) -> ApplicationMetrics {
+        let failures_per_minute = (10_000.0 * failure_ratio).round() as u64;
+        let sample = |minute: u64| ApplicationMetrics {
+            timestamp: 1_000_000 + minute * 60,
+            total_requests: minute * 10_000,
+            successful_requests: minute * (10_000 - failures_per_minute),
+            failed_requests: minute * failures_per_minute,
+            ..Default::default()
+        };
+
+        for minute in 0..120 {
+            manager.record_request_counts(&sample(minute));
+        }
+
+        let latest = sample(120);
+        manager.record_request_counts(&latest);
+        latest
+    }
+
+    #[test]
+    fn test_slo_fast_burn_alert() {
+        let manager = AlertManager::default().with_slo(SloConfig {
+            target_availability: 0.999,
+            window: Duration::from_secs(30 * 24 * 3600),
+        });
+
+        // 10% of requests failing burns a 99.9% budget 100 times too fast
+        let latest = feed_request_series(&manager, 0.1);
+        manager.evaluate_rules(&SystemMetrics::default(), &latest);
+
+        let active_alerts = manager.get_active_alerts();
+        assert_eq!(active_alerts.len(), 1);
+
+        let alert = &active_alerts[0];
+        assert_eq!(alert.rule_name, "slo_fast_burn");
+        assert_eq!(alert.severity, AlertSeverity::Critical);
+
+        let burn_rate: f64 = alert.annotations["burn_rate"].parse().unwrap();
+        assert!((burn_rate - 100.0).abs() < 0.01, "burn rate was {}", burn_rate);
+
+        // 2h at 100x consumed 2/720 * 100 of the budget, the rest lasts ~5.2h
+        let exhausted_in: u64 = alert.annotations["budget_exhausted_in_seconds"].parse().unwrap();
+        assert!((18_700..18_800).contains(&exhausted_in), "exhaustion in {}s", exhausted_in);
+        assert!(alert.annotations.contains_key("budget_exhausted_at"));
+    }
+
+    #[test]
+    fn test_slo_from_monitoring_config() {
+        let manager = AlertManager::from_config(&MonitoringConfig::default());
+
+        let latest = feed_request_series(&manager, 0.1);
+        manager.evaluate_rules(&SystemMetrics::default(), &latest);
+
+        let active_alerts = manager.get_active_alerts();
+        assert_eq!(active_alerts.len(), 1);
+        assert_eq!(active_alerts[0].rule_name, "slo_fast_burn");
+    }
+
+    #[test]
+    fn test_slo_within_budget() {
+        let manager = AlertManager::default().with_slo(SloConfig::default());
+
+        // 0.05% of requests failing stays within a 99.9% budget
+        let latest = feed_request_series(&manager, 0.0005);
+        manager.evaluate_rules(&SystemMetrics::default(), &latest);
+
+        let burn_rate = manager.burn_rate(Duration::from_secs(3600)).unwrap();
+        assert!(burn_rate < 1.0, "burn rate was {}", burn_rate);
+        assert!(manager.get_active_alerts().is_empty());
+    }
 }
@@ -24,6 +24,7 @@ use std::{
 };
 use serde::{Serialize, Deserialize};
 use tracing::{debug, info};
+use alerts::AlertManager;
 
 /// Monitoring configuration
 #[derive(Debug, Clone)]
@@ -46,6 +47,8 @@ pub struct MonitoringConfig {
     pub health_endpoint_path: String,
     /// Alert thresholds
     pub alert_thresholds: AlertThresholds,
+    /// Service level objective used for burn-rate alerting, see [`AlertManager::from_config`]
+    pub slo: SloConfig,
     /// Custom metrics configuration
     pub custom_metrics: Vec<CustomMetricConfig>,
 }
@@ -62,6 +65,7 @@ impl Default for MonitoringConfig {
             enable_health_endpoint: true,
             health_endpoint_path: "/health".to_string(),
             alert_thresholds: AlertThresholds::default(),
+            slo: SloConfig::default(),
             custom_metrics: Vec::new(),
         }
     }
@@ -100,6 +104,31 @@ impl Default for AlertThresholds {
     }
 }
 
+/// Service level objective configuration
+#[derive(Debug, Clone)]
+pub struct SloConfig {
+    /// Target availability as a ratio of successful requests (e.g. 0.999)
+    pub target_availability: f64,
+    /// Rolling window the error budget is measured over
+    pub window: Duration,
+}
+
+impl SloConfig {
+    /// Ratio of requests allowed to fail within the window
+    pub fn error_budget(&self) -> f64 {
+        (1.0 - self.target_availability).max(f64::EPSILON)
+    }
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        Self {
+            target_availability: 0.999,
+            window: Duration::from_secs(30 * 24 * 3600), // 30 days
+        }
+    }
+}
+
 /// Custom metric configuration
 #[derive(Debug, Clone)]
 pub struct CustomMetricConfig {
@@ -323,6 +352,7 @@ pub struct MonitoringManager {
     app_metrics: Arc<RwLock<Vec<ApplicationMetrics>>>,
     health_checks: Arc<RwLock<Vec<HealthCheck>>>,
     custom_metrics: Arc<RwLock<HashMap<String, f64>>>,
+    alert_manager: AlertManager,
     start_time: Instant,
 }
 
@@ -331,6 +361,7 @@ impl MonitoringManager {
     pub fn new(config: MonitoringConfig) -> Self {
         info!("Initializing monitoring manager with config: {:?}", config);
         Self {
+            alert_manager: AlertManager::from_config(&config),
             config,
             system_metrics: Arc::new(RwLock::new(Vec::new())),
             app_metrics: Arc::new(RwLock::new(Vec::new())),
@@ -370,6 +401,9 @@ impl MonitoringManager {
         debug!("Recording app metrics: RPS: {:.1}, Avg RT: {:.1}ms",
                metrics.requests_per_second, metrics.avg_response_time);
 
+        let latest_system = self.get_latest_system_metrics().unwrap_or_default();
+        self.alert_manager.evaluate_rules(&latest_system, &metrics);
+
         let mut app_metrics = self.app_metrics.write().unwrap();
         app_metrics.push(metrics);
 
@@ -414,6 +448,11 @@ impl MonitoringManager {
         custom_metrics.insert(name, value);
     }
 
+    /// Get custom metric value
+    pub fn get_custom_metric(&self, name: &str) -> Option<f64> {
+        self.custom_metrics.read().unwrap().get(name).copied()
+    }
+
     /// Get latest system metrics
     pub fn get_latest_system_metrics(&self) -> Option<SystemMetrics> {
         let system_metrics = self.system_metrics.read().unwrap();
@@ -475,6 +514,11 @@ impl MonitoringManager {
         self.start_time.elapsed().as_secs()
     }
 
+    /// Get the alert manager evaluated on every recorded application metrics sample
+    pub fn alert_manager(&self) -> &AlertManager {
+        &self.alert_manager
+    }
+
     /// Get configuration
     pub fn get_config(&self) -> &MonitoringConfig {
         &self.config
@@ -592,4 +636,29 @@ mod tests {
         let custom_metrics = manager.custom_metrics.read().unwrap();
         assert_eq!(custom_metrics.get("custom.counter"), Some(&42.0));
     }
+
+    #[test]
+    fn test_app_metrics_drive_slo_alerts() {
+        let manager = MonitoringManager::default();
+        let start = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            - 2 * 3600;
+
+        // 10% of requests failing for two hours burns the default 99.9% budget
+        for minute in 0..=120 {
+            manager.record_app_metrics(ApplicationMetrics {
+                timestamp: start + minute * 60,
+                total_requests: minute * 10_000,
+                successful_requests: minute * 9_000,
+                failed_requests: minute * 1_000,
+                ..Default::default()
+            });
+        }
+
+        let active_alerts = manager.alert_manager().get_active_alerts();
+        assert_eq!(active_alerts.len(), 1);
+        assert_eq!(active_alerts[0].rule_name, "slo_fast_burn");
+    }
 }
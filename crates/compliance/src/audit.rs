@@ -1,9 +1,14 @@
 //! Audit logging module
 
 use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::info;
 
 /// Audit logger
-pub struct AuditLogger;
+#[derive(Debug, Default)]
+pub struct AuditLogger {
+    events: RwLock<Vec<AuditEvent>>,
+}
 
 /// Audit event
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,25 +17,66 @@ pub struct AuditEvent {
     pub level: AuditLevel,
     pub message: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Data subject the event relates to
+    #[serde(default)]
+    pub subject_id: Option<String>,
 }
 
 /// Audit level
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AuditLevel {
     Info,
     Warning,
     Error,
     Critical,
+    /// Compliance-relevant action such as a data subject erasure
+    ComplianceViolation,
+}
+
+impl AuditEvent {
+    /// Create new audit event
+    pub fn new(level: AuditLevel, message: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            level,
+            message: message.into(),
+            timestamp: chrono::Utc::now(),
+            subject_id: None,
+        }
+    }
+
+    /// Attach the data subject this event relates to
+    pub fn with_subject(mut self, subject_id: impl Into<String>) -> Self {
+        self.subject_id = Some(subject_id.into());
+        self
+    }
 }
 
 impl AuditLogger {
     /// Create new audit logger
     pub fn new() -> Self {
-        Self
+        Self::default()
     }
 
     /// Log an audit event
-    pub async fn log(&self, _event: AuditEvent) {
-        // TODO: Implement audit logging
+    pub async fn log(&self, event: AuditEvent) {
+        info!("Audit [{:?}]: {}", event.level, event.message);
+        self.events.write().await.push(event);
+    }
+
+    /// Get all recorded events
+    pub async fn events(&self) -> Vec<AuditEvent> {
+        self.events.read().await.clone()
+    }
+
+    /// Get events relating to a data subject
+    pub async fn events_for_subject(&self, subject_id: &str) -> Vec<AuditEvent> {
+        self.events
+            .read()
+            .await
+            .iter()
+            .filter(|event| event.subject_id.as_deref() == Some(subject_id))
+            .cloned()
+            .collect()
     }
 }
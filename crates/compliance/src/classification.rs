@@ -1,10 +1,17 @@
 //! Data classification module
 
+use crate::error::{ComplianceError, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
 /// Data classifier
-pub struct DataClassifier;
+#[derive(Debug)]
+pub struct DataClassifier {
+    rules: Vec<ClassificationRule>,
+}
 
 /// Data sensitivity levels
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 pub enum DataSensitivity {
     Public,
     Internal,
@@ -17,20 +24,233 @@ pub enum DataSensitivity {
 pub struct ClassificationResult {
     pub sensitivity: DataSensitivity,
     pub confidence: f64,
+    /// Matches that determined the sensitivity
+    pub matches: Vec<ClassificationMatch>,
+}
+
+/// Kind of detected personal data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PiiKind {
+    EmailAddress,
+    PhoneNumber,
+    CreditCard,
+    Ssn,
+    NationalId,
+    Custom(String),
+}
+
+/// Span of text that matched a classification rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassificationMatch {
+    pub kind: PiiKind,
+    pub sensitivity: DataSensitivity,
+    /// Byte offset where the match starts
+    pub start: usize,
+    /// Byte offset where the match ends
+    pub end: usize,
+}
+
+/// Organization-defined classification pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomPattern {
+    pub name: String,
+    pub pattern: String,
+    pub sensitivity: DataSensitivity,
+}
+
+#[derive(Debug)]
+struct ClassificationRule {
+    kind: PiiKind,
+    regex: Regex,
+    sensitivity: DataSensitivity,
+    validator: Option<fn(&str) -> bool>,
 }
 
+const EMAIL_PATTERN: &str = r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b";
+const PHONE_PATTERN: &str = r"(?:\+\d{1,3}[\s.-]?)?\(?\b\d{3}\)?[\s.-]\d{3}[\s.-]\d{4}\b";
+const CREDIT_CARD_PATTERN: &str = r"\b(?:\d[ -]?){12,18}\d\b";
+const SSN_PATTERN: &str = r"\b\d{3}-\d{2}-\d{4}\b";
+const UK_NINO_PATTERN: &str = r"\b[A-CEGHJ-PR-TW-Z]{2} ?\d{2} ?\d{2} ?\d{2} ?[A-D]\b";
+
 impl DataClassifier {
-    /// Create new data classifier
+    /// Create new data classifier with the built-in PII patterns
     pub fn new() -> Self {
-        Self
+        let builtin = |kind, pattern: &str, sensitivity, validator| ClassificationRule {
+            kind,
+            regex: Regex::new(pattern).expect("built-in classification pattern"),
+            sensitivity,
+            validator,
+        };
+
+        Self {
+            rules: vec![
+                builtin(PiiKind::EmailAddress, EMAIL_PATTERN, DataSensitivity::Confidential, None),
+                builtin(PiiKind::PhoneNumber, PHONE_PATTERN, DataSensitivity::Confidential, None),
+                builtin(
+                    PiiKind::CreditCard,
+                    CREDIT_CARD_PATTERN,
+                    DataSensitivity::Restricted,
+                    Some(is_luhn_valid as fn(&str) -> bool),
+                ),
+                builtin(
+                    PiiKind::Ssn,
+                    SSN_PATTERN,
+                    DataSensitivity::Restricted,
+                    Some(is_valid_ssn as fn(&str) -> bool),
+                ),
+                builtin(PiiKind::NationalId, UK_NINO_PATTERN, DataSensitivity::Restricted, None),
+            ],
+        }
+    }
+
+    /// Create a classifier with additional organization-defined patterns
+    pub fn with_patterns(patterns: &[CustomPattern]) -> Result<Self> {
+        let mut classifier = Self::new();
+        for pattern in patterns {
+            classifier.rules.push(ClassificationRule {
+                kind: PiiKind::Custom(pattern.name.clone()),
+                regex: Regex::new(&pattern.pattern).map_err(|e| {
+                    ComplianceError::Configuration(format!(
+                        "Invalid classification pattern {}: {}",
+                        pattern.name, e
+                    ))
+                })?,
+                sensitivity: pattern.sensitivity,
+                validator: None,
+            });
+        }
+        Ok(classifier)
     }
 
     /// Classify data
-    pub async fn classify(&self, _data: &[u8]) -> ClassificationResult {
-        // TODO: Implement data classification
-        ClassificationResult {
-            sensitivity: DataSensitivity::Internal,
-            confidence: 0.5,
+    pub async fn classify(&self, data: &[u8]) -> ClassificationResult {
+        self.classify_text(&String::from_utf8_lossy(data))
+    }
+
+    /// Scan text for personal data and return the highest matched sensitivity
+    pub fn classify_text(&self, text: &str) -> ClassificationResult {
+        let mut matches = Vec::new();
+
+        for rule in &self.rules {
+            for found in rule.regex.find_iter(text) {
+                if rule.validator.is_none_or(|validate| validate(found.as_str())) {
+                    matches.push(ClassificationMatch {
+                        kind: rule.kind.clone(),
+                        sensitivity: rule.sensitivity,
+                        start: found.start(),
+                        end: found.end(),
+                    });
+                }
+            }
         }
+        matches.sort_by_key(|m| (m.start, m.end));
+
+        match matches.iter().map(|m| m.sensitivity).max() {
+            Some(sensitivity) => ClassificationResult {
+                sensitivity,
+                confidence: 0.9,
+                matches,
+            },
+            None => ClassificationResult {
+                sensitivity: DataSensitivity::Public,
+                confidence: 0.5,
+                matches,
+            },
+        }
+    }
+}
+
+impl Default for DataClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Check a card number with the Luhn algorithm
+fn is_luhn_valid(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+/// Reject SSNs with area, group or serial numbers that are never issued
+fn is_valid_ssn(candidate: &str) -> bool {
+    let mut parts = candidate.split('-');
+    let (Some(area), Some(group), Some(serial)) = (parts.next(), parts.next(), parts.next()) else {
+        return false;
+    };
+    area != "000" && area != "666" && !area.starts_with('9') && group != "00" && serial != "0000"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credit_card_luhn() {
+        let classifier = DataClassifier::new();
+
+        let result = classifier.classify_text("Card: 4111 1111 1111 1111 exp 12/29");
+        assert_eq!(result.sensitivity, DataSensitivity::Restricted);
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].kind, PiiKind::CreditCard);
+        assert_eq!(result.matches[0].start, 6);
+
+        let result = classifier.classify_text("Order 4111 1111 1111 1112 shipped");
+        assert_eq!(result.sensitivity, DataSensitivity::Public);
+        assert!(result.matches.is_empty());
+    }
+
+    #[test]
+    fn test_pii_patterns() {
+        let classifier = DataClassifier::new();
+
+        let result = classifier.classify_text("Contact jane@example.com or 555-123-4567");
+        assert_eq!(result.sensitivity, DataSensitivity::Confidential);
+        let kinds: Vec<_> = result.matches.iter().map(|m| m.kind.clone()).collect();
+        assert_eq!(kinds, vec![PiiKind::EmailAddress, PiiKind::PhoneNumber]);
+
+        let result = classifier.classify_text("SSN 123-45-6789, NINO AB 12 34 56 C");
+        assert_eq!(result.sensitivity, DataSensitivity::Restricted);
+        assert_eq!(result.matches.len(), 2);
+
+        assert!(classifier.classify_text("SSN 000-12-3456").matches.is_empty());
+    }
+
+    #[test]
+    fn test_custom_patterns() {
+        let classifier = DataClassifier::with_patterns(&[CustomPattern {
+            name: "employee_id".to_string(),
+            pattern: r"\bEMP-\d{6}\b".to_string(),
+            sensitivity: DataSensitivity::Internal,
+        }])
+        .unwrap();
+
+        let result = classifier.classify_text("Badge EMP-004217 issued");
+        assert_eq!(result.sensitivity, DataSensitivity::Internal);
+        assert_eq!(result.matches[0].kind, PiiKind::Custom("employee_id".to_string()));
+
+        assert!(DataClassifier::with_patterns(&[CustomPattern {
+            name: "broken".to_string(),
+            pattern: "(".to_string(),
+            sensitivity: DataSensitivity::Internal,
+        }])
+        .is_err());
     }
 }
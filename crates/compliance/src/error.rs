@@ -12,6 +12,10 @@ pub enum ComplianceError {
     #[error("Configuration error: {0}")]
     Configuration(String),
 
+    /// Records cannot be erased while a retention policy holds them
+    #[error("Retention conflict: records under legal hold: {}", .0.join(", "))]
+    RetentionConflict(Vec<String>),
+
     /// Generic error
     #[error("Compliance error: {0}")]
     Generic(String),
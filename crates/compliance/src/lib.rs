@@ -49,6 +49,7 @@ pub mod audit;
 pub mod classification;
 pub mod retention;
 pub mod privacy;
+pub mod records;
 pub mod gdpr;
 pub mod hipaa;
 pub mod ccpa;
@@ -58,9 +59,10 @@ pub mod error;
 pub use config::ComplianceConfig;
 pub use manager::ComplianceManager;
 pub use audit::{AuditLogger, AuditEvent, AuditLevel};
-pub use classification::{DataClassifier, DataSensitivity, ClassificationResult};
-pub use retention::{RetentionManager, RetentionPolicy, RetentionAction};
-pub use privacy::{PrivacyManager, PrivacyRequest, PrivacyRequestType};
+pub use classification::{DataClassifier, DataSensitivity, ClassificationResult, ClassificationMatch, CustomPattern, PiiKind};
+pub use retention::{RetentionManager, RetentionPolicy, RetentionAction, RetentionRunReport, PolicyRunReport};
+pub use privacy::{PrivacyManager, PrivacyRequest, PrivacyRequestType, DataExportBundle, ExportSection, ManifestEntry, ErasureReport};
+pub use records::{DataRecord, DataCategory, RecordState, RecordStore, InMemoryRecordStore};
 pub use error::{ComplianceError, Result};
 
 /// Compliance frameworks
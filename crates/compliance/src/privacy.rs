@@ -1,7 +1,20 @@
 //! Privacy management module
 
+use crate::audit::{AuditEvent, AuditLevel, AuditLogger};
+use crate::error::{ComplianceError, Result};
+use crate::records::{DataCategory, RecordStore};
+use crate::retention::RetentionManager;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+
 /// Privacy manager
-pub struct PrivacyManager;
+pub struct PrivacyManager {
+    store: Arc<dyn RecordStore>,
+    audit: Arc<AuditLogger>,
+    retention: Arc<RetentionManager>,
+}
 
 /// Privacy request
 #[derive(Debug, Clone)]
@@ -22,14 +35,348 @@ pub enum PrivacyRequestType {
     ConsentWithdrawal,
 }
 
+/// Name of the export section holding audit entries
+pub const AUDIT_SECTION: &str = "audit_entries";
+
+/// Section of a data export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSection {
+    pub name: String,
+    pub records: Vec<serde_json::Value>,
+}
+
+/// Manifest entry describing one export section
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub section: String,
+    pub record_count: usize,
+    /// Hex-encoded SHA-256 of the section's serialized records
+    pub sha256: String,
+}
+
+/// Portable bundle of all data held about a data subject
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataExportBundle {
+    pub export_id: String,
+    pub subject_id: String,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub manifest: Vec<ManifestEntry>,
+    pub sections: Vec<ExportSection>,
+}
+
+/// Outcome of a right-to-erasure request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErasureReport {
+    pub subject_id: String,
+    pub reason: String,
+    pub erased_at: chrono::DateTime<chrono::Utc>,
+    /// Number of deleted records per data category
+    pub deleted: BTreeMap<DataCategory, usize>,
+}
+
+impl ErasureReport {
+    /// Total number of deleted records
+    pub fn total_deleted(&self) -> usize {
+        self.deleted.values().sum()
+    }
+}
+
+impl DataExportBundle {
+    /// Get a section by name
+    pub fn section(&self, name: &str) -> Option<&ExportSection> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    /// Check that every section matches its manifest checksum
+    pub fn verify(&self) -> Result<bool> {
+        for entry in &self.manifest {
+            let Some(section) = self.section(&entry.section) else {
+                return Ok(false);
+            };
+            if section.records.len() != entry.record_count
+                || section_checksum(&section.records)? != entry.sha256
+            {
+                return Ok(false);
+            }
+        }
+        Ok(self.manifest.len() == self.sections.len())
+    }
+}
+
+fn section_checksum(records: &[serde_json::Value]) -> Result<String> {
+    let bytes = serde_json::to_vec(records)
+        .map_err(|e| ComplianceError::Generic(format!("Failed to serialize export: {}", e)))?;
+    Ok(ring::digest::digest(&ring::digest::SHA256, &bytes)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
 impl PrivacyManager {
     /// Create new privacy manager
-    pub fn new() -> Self {
-        Self
+    pub fn new(
+        store: Arc<dyn RecordStore>,
+        audit: Arc<AuditLogger>,
+        retention: Arc<RetentionManager>,
+    ) -> Self {
+        Self {
+            store,
+            audit,
+            retention,
+        }
     }
 
     /// Process privacy request
     pub async fn process_request(&self, _request: &PrivacyRequest) {
         // TODO: Implement privacy request processing
     }
+
+    /// Gather all data held about a data subject into a portable bundle
+    pub async fn export_subject_data(&self, subject_id: &str) -> Result<DataExportBundle> {
+        let records = self.store.records_for_subject(subject_id).await?;
+        let audit_entries = self.audit.events_for_subject(subject_id).await;
+
+        let mut sections = Vec::new();
+        for category in [DataCategory::MailMetadata, DataCategory::Profile] {
+            sections.push(ExportSection {
+                name: category.as_str().to_string(),
+                records: records
+                    .iter()
+                    .filter(|record| record.category == category)
+                    .map(serde_json::to_value)
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(|e| ComplianceError::Generic(e.to_string()))?,
+            });
+        }
+        sections.push(ExportSection {
+            name: AUDIT_SECTION.to_string(),
+            records: audit_entries
+                .iter()
+                .map(serde_json::to_value)
+                .collect::<std::result::Result<_, _>>()
+                .map_err(|e| ComplianceError::Generic(e.to_string()))?,
+        });
+
+        let mut manifest = Vec::with_capacity(sections.len());
+        for section in &sections {
+            manifest.push(ManifestEntry {
+                section: section.name.clone(),
+                record_count: section.records.len(),
+                sha256: section_checksum(&section.records)?,
+            });
+        }
+
+        let bundle = DataExportBundle {
+            export_id: uuid::Uuid::new_v4().to_string(),
+            subject_id: subject_id.to_string(),
+            generated_at: chrono::Utc::now(),
+            manifest,
+            sections,
+        };
+
+        info!(
+            "Exported {} records for data subject {}",
+            bundle.manifest.iter().map(|e| e.record_count).sum::<usize>(),
+            subject_id
+        );
+        self.audit
+            .log(
+                AuditEvent::new(
+                    AuditLevel::Info,
+                    format!("Data export {} generated", bundle.export_id),
+                )
+                .with_subject(subject_id),
+            )
+            .await;
+
+        Ok(bundle)
+    }
+
+    /// Erase all data held about a data subject.
+    ///
+    /// Nothing is deleted if any record is still under a legal-hold retention
+    /// policy; the blocked records are listed in the returned error instead.
+    pub async fn erase_subject_data(&self, subject_id: &str, reason: &str) -> Result<ErasureReport> {
+        let records = self.store.records_for_subject(subject_id).await?;
+
+        let mut blocked = Vec::new();
+        for record in &records {
+            if let Some(policy) = self.retention.legal_hold_for(record).await {
+                blocked.push(format!("{} ({})", record.id, policy));
+            }
+        }
+        if !blocked.is_empty() {
+            warn!(
+                "Erasure for data subject {} blocked by legal hold on {} records",
+                subject_id,
+                blocked.len()
+            );
+            return Err(ComplianceError::RetentionConflict(blocked));
+        }
+
+        let mut deleted: BTreeMap<DataCategory, usize> = BTreeMap::new();
+        for record in &records {
+            if self.store.delete(&record.id).await? {
+                *deleted.entry(record.category).or_default() += 1;
+            }
+        }
+
+        let report = ErasureReport {
+            subject_id: subject_id.to_string(),
+            reason: reason.to_string(),
+            erased_at: chrono::Utc::now(),
+            deleted,
+        };
+
+        self.audit
+            .log(
+                AuditEvent::new(
+                    AuditLevel::ComplianceViolation,
+                    format!(
+                        "GDPR erasure: deleted {} records ({})",
+                        report.total_deleted(),
+                        reason
+                    ),
+                )
+                .with_subject(subject_id),
+            )
+            .await;
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classification::DataSensitivity;
+    use crate::records::{DataRecord, InMemoryRecordStore, RecordState};
+    use crate::retention::{RetentionAction, RetentionPolicy};
+    use std::time::Duration;
+
+    fn record(id: &str, subject_id: &str, category: DataCategory) -> DataRecord {
+        DataRecord {
+            id: id.to_string(),
+            subject_id: subject_id.to_string(),
+            category,
+            sensitivity: DataSensitivity::Confidential,
+            state: RecordState::Active,
+            created_at: chrono::Utc::now(),
+            data: serde_json::json!({ "id": id }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_only_contains_subject_data() {
+        let store = Arc::new(InMemoryRecordStore::new());
+        let audit = Arc::new(AuditLogger::new());
+        for record in [
+            record("m1", "alice", DataCategory::MailMetadata),
+            record("p1", "alice", DataCategory::Profile),
+            record("m2", "bob", DataCategory::MailMetadata),
+            record("p2", "bob", DataCategory::Profile),
+        ] {
+            store.insert(record).await.unwrap();
+        }
+        audit
+            .log(AuditEvent::new(AuditLevel::Info, "alice logged in").with_subject("alice"))
+            .await;
+        audit
+            .log(AuditEvent::new(AuditLevel::Info, "bob logged in").with_subject("bob"))
+            .await;
+
+        let retention = Arc::new(RetentionManager::new(store.clone()));
+        let manager = PrivacyManager::new(store, audit.clone(), retention);
+        let bundle = manager.export_subject_data("alice").await.unwrap();
+
+        assert_eq!(bundle.subject_id, "alice");
+        assert!(bundle.verify().unwrap());
+        let mail = bundle.section("mail_metadata").unwrap();
+        assert_eq!(mail.records.len(), 1);
+        assert_eq!(mail.records[0]["id"], "m1");
+        assert_eq!(bundle.section("profile").unwrap().records[0]["id"], "p1");
+        let audit_section = bundle.section(AUDIT_SECTION).unwrap();
+        assert_eq!(audit_section.records.len(), 1);
+        assert_eq!(audit_section.records[0]["message"], "alice logged in");
+
+        // The export itself is audited
+        let events = audit.events_for_subject("alice").await;
+        assert_eq!(events.len(), 2);
+        assert!(events[1].message.contains(&bundle.export_id));
+    }
+
+    #[tokio::test]
+    async fn test_export_subject_without_data() {
+        let store = Arc::new(InMemoryRecordStore::new());
+        let manager = PrivacyManager::new(
+            store.clone(),
+            Arc::new(AuditLogger::new()),
+            Arc::new(RetentionManager::new(store)),
+        );
+
+        let bundle = manager.export_subject_data("nobody").await.unwrap();
+        assert_eq!(bundle.manifest.len(), 3);
+        assert!(bundle.manifest.iter().all(|entry| entry.record_count == 0));
+        assert!(bundle.verify().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_erasure_blocked_by_legal_hold() {
+        let store = Arc::new(InMemoryRecordStore::new());
+        let retention = Arc::new(RetentionManager::new(store.clone()));
+        retention
+            .register_policy(RetentionPolicy {
+                name: "litigation-hold".to_string(),
+                retention_period: Duration::from_secs(365 * 86400),
+                action: RetentionAction::Delete,
+                category: Some(DataCategory::MailMetadata),
+                legal_hold: true,
+            })
+            .await
+            .unwrap();
+        store.insert(record("m1", "alice", DataCategory::MailMetadata)).await.unwrap();
+        store.insert(record("p1", "alice", DataCategory::Profile)).await.unwrap();
+
+        let manager = PrivacyManager::new(store.clone(), Arc::new(AuditLogger::new()), retention);
+        match manager.erase_subject_data("alice", "user request").await {
+            Err(ComplianceError::RetentionConflict(blocked)) => {
+                assert_eq!(blocked, vec!["m1 (litigation-hold)".to_string()]);
+            }
+            other => panic!("expected retention conflict, got {:?}", other),
+        }
+
+        // Nothing was deleted
+        assert_eq!(store.records_for_subject("alice").await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_erasure_is_idempotent() {
+        let store = Arc::new(InMemoryRecordStore::new());
+        let audit = Arc::new(AuditLogger::new());
+        for record in [
+            record("m1", "alice", DataCategory::MailMetadata),
+            record("m2", "alice", DataCategory::MailMetadata),
+            record("p1", "alice", DataCategory::Profile),
+            record("p2", "bob", DataCategory::Profile),
+        ] {
+            store.insert(record).await.unwrap();
+        }
+
+        let retention = Arc::new(RetentionManager::new(store.clone()));
+        let manager = PrivacyManager::new(store.clone(), audit.clone(), retention);
+        let report = manager.erase_subject_data("alice", "user request").await.unwrap();
+        assert_eq!(report.deleted.get(&DataCategory::MailMetadata), Some(&2));
+        assert_eq!(report.deleted.get(&DataCategory::Profile), Some(&1));
+        assert!(store.records_for_subject("alice").await.unwrap().is_empty());
+        assert_eq!(store.records_for_subject("bob").await.unwrap().len(), 1);
+
+        let report = manager.erase_subject_data("alice", "user request").await.unwrap();
+        assert_eq!(report.total_deleted(), 0);
+        let events = audit.events_for_subject("alice").await;
+        assert_eq!(events.len(), 2);
+        assert!(events
+            .iter()
+            .all(|event| event.level == AuditLevel::ComplianceViolation));
+    }
 }
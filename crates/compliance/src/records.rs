@@ -0,0 +1,107 @@
+//! Personal data inventory
+
+use crate::error::Result;
+use crate::classification::DataSensitivity;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Category of a personal data record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DataCategory {
+    /// Message metadata (headers, sizes, folders)
+    MailMetadata,
+    /// Account profile information
+    Profile,
+}
+
+impl DataCategory {
+    /// Section name used in exports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataCategory::MailMetadata => "mail_metadata",
+            DataCategory::Profile => "profile",
+        }
+    }
+}
+
+/// Lifecycle state of a record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RecordState {
+    #[default]
+    Active,
+    Archived,
+    Anonymized,
+}
+
+/// Record holding personal data about a data subject
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataRecord {
+    pub id: String,
+    pub subject_id: String,
+    pub category: DataCategory,
+    pub sensitivity: DataSensitivity,
+    #[serde(default)]
+    pub state: RecordState,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub data: serde_json::Value,
+}
+
+/// Storage holding classified personal data records
+#[async_trait]
+pub trait RecordStore: Send + Sync {
+    /// Store or replace a record
+    async fn insert(&self, record: DataRecord) -> Result<()>;
+
+    /// Get all records belonging to a data subject
+    async fn records_for_subject(&self, subject_id: &str) -> Result<Vec<DataRecord>>;
+
+    /// Get all records
+    async fn all_records(&self) -> Result<Vec<DataRecord>>;
+
+    /// Delete a record, returning whether it existed
+    async fn delete(&self, record_id: &str) -> Result<bool>;
+}
+
+/// In-memory record store
+#[derive(Debug, Default)]
+pub struct InMemoryRecordStore {
+    records: RwLock<HashMap<String, DataRecord>>,
+}
+
+impl InMemoryRecordStore {
+    /// Create new in-memory record store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RecordStore for InMemoryRecordStore {
+    async fn insert(&self, record: DataRecord) -> Result<()> {
+        self.records.write().await.insert(record.id.clone(), record);
+        Ok(())
+    }
+
+    async fn records_for_subject(&self, subject_id: &str) -> Result<Vec<DataRecord>> {
+        let mut records: Vec<_> = self
+            .records
+            .read()
+            .await
+            .values()
+            .filter(|record| record.subject_id == subject_id)
+            .cloned()
+            .collect();
+        records.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(records)
+    }
+
+    async fn all_records(&self) -> Result<Vec<DataRecord>> {
+        Ok(self.records.read().await.values().cloned().collect())
+    }
+
+    async fn delete(&self, record_id: &str) -> Result<bool> {
+        Ok(self.records.write().await.remove(record_id).is_some())
+    }
+}
@@ -0,0 +1,32 @@
+//! Error types for the configuration system
+
+/// Result type for configuration operations
+pub type Result<T> = std::result::Result<T, ConfigError>;
+
+/// Configuration system errors
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// Configuration file does not exist
+    #[error("Configuration file not found: {0}")]
+    FileNotFound(String),
+
+    /// I/O error
+    #[error("I/O error: {0}")]
+    IoError(String),
+
+    /// Parse error
+    #[error("Parse error: {0}")]
+    ParseError(String),
+
+    /// Network error
+    #[error("Network error: {0}")]
+    NetworkError(String),
+
+    /// Unsupported configuration format
+    #[error("Unsupported format: {0}")]
+    UnsupportedFormat(String),
+
+    /// Validation failed, with every problem found
+    #[error("Configuration validation failed: {}", .0.join("; "))]
+    Validation(Vec<String>),
+}
@@ -4,357 +4,417 @@
 //! supporting TOML files, environment variables, command line arguments,
 //! and remote configuration sources.
 
-use crate::{A3MailerConfig, ConfigSource, Result, ConfigError};
-use std::collections::HashMap;
+use crate::{A3MailerConfig, Result, ConfigError};
 use std::path::Path;
-use tracing::{info, warn, error, debug};
+use tracing::{info, debug};
 use serde_json::Value;
 
-/// Load configuration from a TOML file
+/// Configuration file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Detect the format from a file extension
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path.extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("");
+        
+        match extension.to_lowercase().as_str() {
+            "toml" => Ok(ConfigFormat::Toml),
+            "json" => Ok(ConfigFormat::Json),
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            _ => Err(ConfigError::UnsupportedFormat(format!(
+                "Unsupported configuration file format: {}",
+                extension
+            ))),
+        }
+    }
+    
+    /// Parse configuration content into a generic value
+    pub fn parse(self, content: &str) -> Result<Value> {
+        match self {
+            ConfigFormat::Toml => toml::from_str(content)
+                .map_err(|e| ConfigError::ParseError(format!("TOML parse error: {}", e))),
+            ConfigFormat::Json => serde_json::from_str(content)
+                .map_err(|e| ConfigError::ParseError(format!("JSON parse error: {}", e))),
+            ConfigFormat::Yaml => serde_yaml::from_str(content)
+                .map_err(|e| ConfigError::ParseError(format!("YAML parse error: {}", e))),
+        }
+    }
+}
+
+/// Load configuration from a TOML, JSON or YAML file
+///
+/// Fields missing from the file keep their default values.
 pub async fn load_from_file(path: &Path) -> Result<A3MailerConfig> {
+    config_from_layers([load_layer_from_file(path).await?])
+}
+
+/// Load the fields set in a TOML, JSON or YAML file
+///
+/// The format is detected from the file extension. `${VAR}` and
+/// `${VAR:-default}` references in string values are replaced from the
+/// environment; `$${...}` is kept as a literal `${...}`.
+pub async fn load_layer_from_file(path: &Path) -> Result<Value> {
     info!("Loading configuration from file: {}", path.display());
     
     if !path.exists() {
         return Err(ConfigError::FileNotFound(path.to_string_lossy().to_string()));
     }
     
+    let format = ConfigFormat::from_path(path)?;
     let content = tokio::fs::read_to_string(path).await
         .map_err(|e| ConfigError::IoError(e.to_string()))?;
     
-    let config: A3MailerConfig = toml::from_str(&content)
-        .map_err(|e| ConfigError::ParseError(format!("TOML parse error: {}", e)))?;
+    let mut layer = format.parse(&content)?;
+    interpolate_env(&mut layer)?;
     
     info!("Successfully loaded configuration from file: {}", path.display());
-    Ok(config)
+    Ok(layer)
 }
 
-/// Load configuration from environment variables
-pub async fn load_from_environment() -> Result<A3MailerConfig> {
-    info!("Loading configuration from environment variables");
-    
-    let mut config = A3MailerConfig::default();
-    
-    // Server configuration
-    if let Ok(hostname) = std::env::var("A3MAILER_HOSTNAME") {
-        config.server.hostname = hostname;
-    }
-    
-    if let Ok(bind_addresses) = std::env::var("A3MAILER_BIND_ADDRESSES") {
-        config.server.bind_addresses = bind_addresses
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .collect();
+/// Expand environment variable references in all string values
+pub fn interpolate_env(value: &mut Value) -> Result<()> {
+    interpolate_with(value, |name| std::env::var(name).ok())
+}
+
+/// Expand variable references in all string values using `lookup`
+///
+/// Every unset variable without a default is reported in a single error.
+pub fn interpolate_with(value: &mut Value, lookup: impl Fn(&str) -> Option<String>) -> Result<()> {
+    let mut missing = Vec::new();
+    interpolate_value(value, &lookup, &mut missing)?;
+    
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        missing.sort();
+        missing.dedup();
+        Err(ConfigError::ParseError(format!(
+            "Environment variables not set and without default: {}",
+            missing.join(", ")
+        )))
     }
-    
-    if let Ok(max_connections) = std::env::var("A3MAILER_MAX_CONNECTIONS") {
-        config.server.max_connections = max_connections.parse()
-            .map_err(|e| ConfigError::ParseError(format!("Invalid max_connections: {}", e)))?;
+}
+
+fn interpolate_value(
+    value: &mut Value,
+    lookup: &impl Fn(&str) -> Option<String>,
+    missing: &mut Vec<String>,
+) -> Result<()> {
+    match value {
+        Value::String(text) if text.contains('$') => {
+            *text = interpolate_str(text, lookup, missing)?;
+        }
+        Value::Array(items) => {
+            for item in items {
+                interpolate_value(item, lookup, missing)?;
+            }
+        }
+        Value::Object(fields) => {
+            for field in fields.values_mut() {
+                interpolate_value(field, lookup, missing)?;
+            }
+        }
+        _ => {}
     }
     
-    if let Ok(worker_threads) = std::env::var("A3MAILER_WORKER_THREADS") {
-        config.server.worker_threads = Some(worker_threads.parse()
-            .map_err(|e| ConfigError::ParseError(format!("Invalid worker_threads: {}", e)))?);
+    Ok(())
+}
+
+fn interpolate_str(
+    text: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+    missing: &mut Vec<String>,
+) -> Result<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    
+    while let Some(position) = rest.find('$') {
+        result.push_str(&rest[..position]);
+        rest = &rest[position..];
+        
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            result.push_str("${");
+            rest = escaped;
+        } else if let Some(reference) = rest.strip_prefix("${") {
+            let end = reference.find('}').ok_or_else(|| {
+                ConfigError::ParseError(format!("Unterminated variable reference in: {}", text))
+            })?;
+            let (name, default) = match reference[..end].split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (&reference[..end], None),
+            };
+            if name.is_empty() {
+                return Err(ConfigError::ParseError(format!("Empty variable reference in: {}", text)));
+            }
+            
+            match (lookup(name).filter(|value| !value.is_empty() || default.is_none()), default) {
+                (Some(value), _) => result.push_str(&value),
+                (None, Some(default)) => result.push_str(default),
+                (None, None) => missing.push(name.to_string()),
+            }
+            rest = &reference[end + 1..];
+        } else {
+            result.push('$');
+            rest = &rest[1..];
+        }
     }
+    result.push_str(rest);
     
+    Ok(result)
+}
+
+/// Environment variables and the configuration fields they set
+const ENVIRONMENT_VARIABLES: &[(&str, &str, FieldKind)] = &[
+    // Server configuration
+    ("A3MAILER_HOSTNAME", "server.hostname", FieldKind::String),
+    ("A3MAILER_BIND_ADDRESSES", "server.bind_addresses", FieldKind::List),
+    ("A3MAILER_MAX_CONNECTIONS", "server.max_connections", FieldKind::Number),
+    ("A3MAILER_WORKER_THREADS", "server.worker_threads", FieldKind::Number),
     // AI configuration
-    if let Ok(ai_enabled) = std::env::var("A3MAILER_AI_ENABLED") {
-        config.ai.enabled = ai_enabled.parse()
-            .map_err(|e| ConfigError::ParseError(format!("Invalid ai_enabled: {}", e)))?;
-    }
-    
-    if let Ok(model_path) = std::env::var("A3MAILER_AI_MODEL_PATH") {
-        config.ai.model_path = model_path;
-    }
-    
-    if let Ok(threat_threshold) = std::env::var("A3MAILER_AI_THREAT_THRESHOLD") {
-        config.ai.threat_detection.confidence_threshold = threat_threshold.parse()
-            .map_err(|e| ConfigError::ParseError(format!("Invalid threat_threshold: {}", e)))?;
-    }
-    
+    ("A3MAILER_AI_ENABLED", "ai.enabled", FieldKind::Bool),
+    ("A3MAILER_AI_MODEL_PATH", "ai.model_path", FieldKind::String),
+    ("A3MAILER_AI_THREAT_THRESHOLD", "ai.threat_detection.confidence_threshold", FieldKind::Number),
     // Web3 configuration
-    if let Ok(web3_enabled) = std::env::var("A3MAILER_WEB3_ENABLED") {
-        config.web3.enabled = web3_enabled.parse()
-            .map_err(|e| ConfigError::ParseError(format!("Invalid web3_enabled: {}", e)))?;
-    }
-    
-    if let Ok(blockchain_network) = std::env::var("A3MAILER_BLOCKCHAIN_NETWORK") {
-        config.web3.blockchain_network = blockchain_network;
-    }
-    
-    if let Ok(rpc_url) = std::env::var("A3MAILER_RPC_URL") {
-        config.web3.rpc_url = rpc_url;
-    }
-    
-    if let Ok(did_resolver) = std::env::var("A3MAILER_DID_RESOLVER_URL") {
-        config.web3.did.resolver_url = did_resolver;
-    }
-    
-    if let Ok(ipfs_gateway) = std::env::var("A3MAILER_IPFS_GATEWAY") {
-        config.web3.ipfs.gateway_url = ipfs_gateway;
-    }
-    
+    ("A3MAILER_WEB3_ENABLED", "web3.enabled", FieldKind::Bool),
+    ("A3MAILER_BLOCKCHAIN_NETWORK", "web3.blockchain_network", FieldKind::String),
+    ("A3MAILER_RPC_URL", "web3.rpc_url", FieldKind::String),
+    ("A3MAILER_DID_RESOLVER_URL", "web3.did.resolver_url", FieldKind::String),
+    ("A3MAILER_IPFS_GATEWAY", "web3.ipfs.gateway_url", FieldKind::String),
     // Storage configuration
-    if let Ok(storage_backend) = std::env::var("A3MAILER_STORAGE_BACKEND") {
-        config.storage.backend = storage_backend;
-    }
-    
-    if let Ok(connection_string) = std::env::var("A3MAILER_DATABASE_URL") {
-        config.storage.connection_string = connection_string;
-    }
-    
-    if let Ok(max_connections) = std::env::var("A3MAILER_DB_MAX_CONNECTIONS") {
-        config.storage.max_connections = max_connections.parse()
-            .map_err(|e| ConfigError::ParseError(format!("Invalid db_max_connections: {}", e)))?;
-    }
-    
+    ("A3MAILER_STORAGE_BACKEND", "storage.backend", FieldKind::String),
+    ("A3MAILER_DATABASE_URL", "storage.connection_string", FieldKind::String),
+    ("A3MAILER_DB_MAX_CONNECTIONS", "storage.max_connections", FieldKind::Number),
     // Security configuration
-    if let Ok(tls_cert) = std::env::var("A3MAILER_TLS_CERT_FILE") {
-        config.server.tls.cert_file = tls_cert;
-    }
-    
-    if let Ok(tls_key) = std::env::var("A3MAILER_TLS_KEY_FILE") {
-        config.server.tls.key_file = tls_key;
-    }
-    
-    if let Ok(tls_enabled) = std::env::var("A3MAILER_TLS_ENABLED") {
-        config.server.tls.enabled = tls_enabled.parse()
-            .map_err(|e| ConfigError::ParseError(format!("Invalid tls_enabled: {}", e)))?;
-    }
-    
+    ("A3MAILER_TLS_CERT_FILE", "server.tls.cert_file", FieldKind::String),
+    ("A3MAILER_TLS_KEY_FILE", "server.tls.key_file", FieldKind::String),
+    ("A3MAILER_TLS_ENABLED", "server.tls.enabled", FieldKind::Bool),
     // Monitoring configuration
-    if let Ok(monitoring_enabled) = std::env::var("A3MAILER_MONITORING_ENABLED") {
-        config.monitoring.enabled = monitoring_enabled.parse()
-            .map_err(|e| ConfigError::ParseError(format!("Invalid monitoring_enabled: {}", e)))?;
-    }
-    
-    if let Ok(metrics_port) = std::env::var("A3MAILER_METRICS_PORT") {
-        config.monitoring.metrics_port = metrics_port.parse()
-            .map_err(|e| ConfigError::ParseError(format!("Invalid metrics_port: {}", e)))?;
-    }
-    
+    ("A3MAILER_MONITORING_ENABLED", "monitoring.enabled", FieldKind::Bool),
+    ("A3MAILER_METRICS_PORT", "monitoring.metrics_port", FieldKind::Number),
     // Logging configuration
-    if let Ok(log_level) = std::env::var("A3MAILER_LOG_LEVEL") {
-        config.logging.level = log_level;
+    ("A3MAILER_LOG_LEVEL", "logging.level", FieldKind::String),
+    ("A3MAILER_LOG_FORMAT", "logging.format", FieldKind::String),
+    // Enterprise configuration
+    ("A3MAILER_LICENSE_KEY", "enterprise.license_key", FieldKind::String),
+];
+
+/// How a textual override is converted into a configuration value
+#[derive(Debug, Clone, Copy)]
+enum FieldKind {
+    String,
+    Bool,
+    Number,
+    /// Comma separated list
+    List,
+}
+
+impl FieldKind {
+    fn parse(self, name: &str, text: &str) -> Result<Value> {
+        match self {
+            FieldKind::String => Ok(Value::String(text.to_string())),
+            FieldKind::Bool => text.trim().parse::<bool>()
+                .map(Value::Bool)
+                .map_err(|e| ConfigError::ParseError(format!("Invalid {}: {}", name, e))),
+            FieldKind::Number => text.trim().parse::<serde_json::Number>()
+                .map(Value::Number)
+                .map_err(|e| ConfigError::ParseError(format!("Invalid {}: {}", name, e))),
+            FieldKind::List => Ok(Value::Array(
+                text.split(',')
+                    .map(|s| Value::String(s.trim().to_string()))
+                    .collect(),
+            )),
+        }
     }
+}
+
+/// Load configuration from environment variables
+pub async fn load_from_environment() -> Result<A3MailerConfig> {
+    config_from_layers([load_layer_from_environment()?])
+}
+
+/// Load the fields set by environment variables
+pub fn load_layer_from_environment() -> Result<Value> {
+    info!("Loading configuration from environment variables");
     
-    if let Ok(log_format) = std::env::var("A3MAILER_LOG_FORMAT") {
-        config.logging.format = log_format;
-    }
+    let layer = environment_layer_with(|name| std::env::var(name).ok())?;
     
-    // Enterprise configuration
-    if let Ok(license_key) = std::env::var("A3MAILER_LICENSE_KEY") {
-        config.enterprise.license_key = Some(license_key);
+    info!("Successfully loaded configuration from environment variables");
+    Ok(layer)
+}
+
+/// Build the environment layer using `lookup` to read variables
+pub fn environment_layer_with(lookup: impl Fn(&str) -> Option<String>) -> Result<Value> {
+    let mut layer = Value::Object(Default::default());
+    
+    for (name, field, kind) in ENVIRONMENT_VARIABLES {
+        if let Some(text) = lookup(name) {
+            set_field(&mut layer, field, kind.parse(name, &text)?);
+        }
     }
     
-    info!("Successfully loaded configuration from environment variables");
-    Ok(config)
+    Ok(layer)
 }
 
+/// Command line options and the configuration fields they set
+const COMMAND_LINE_OPTIONS: &[(&str, &str, FieldKind)] = &[
+    ("--hostname", "server.hostname", FieldKind::String),
+    ("--max-connections", "server.max_connections", FieldKind::Number),
+    ("--ai-enabled", "ai.enabled", FieldKind::Bool),
+    ("--web3-enabled", "web3.enabled", FieldKind::Bool),
+    ("--database-url", "storage.connection_string", FieldKind::String),
+    ("--log-level", "logging.level", FieldKind::String),
+    ("--metrics-port", "monitoring.metrics_port", FieldKind::Number),
+    ("--license-key", "enterprise.license_key", FieldKind::String),
+];
+
 /// Load configuration from command line arguments
 pub async fn load_from_command_line(args: &[String]) -> Result<A3MailerConfig> {
+    config_from_layers([load_layer_from_command_line(args)?])
+}
+
+/// Load the fields set by command line arguments
+pub fn load_layer_from_command_line(args: &[String]) -> Result<Value> {
     info!("Loading configuration from command line arguments");
     
-    let mut config = A3MailerConfig::default();
+    let mut layer = Value::Object(Default::default());
     let mut i = 0;
     
     while i < args.len() {
         let arg = &args[i];
+        let value = args.get(i + 1);
         
-        match arg.as_str() {
-            "--hostname" => {
-                if i + 1 < args.len() {
-                    config.server.hostname = args[i + 1].clone();
-                    i += 1;
-                }
+        if arg == "--port" {
+            if let Some(value) = value {
+                let port: u16 = value.parse()
+                    .map_err(|e| ConfigError::ParseError(format!("Invalid port: {}", e)))?;
+                set_field(&mut layer, "server.bind_addresses", Value::from(vec![format!("0.0.0.0:{}", port)]));
+                i += 1;
             }
-            "--port" => {
-                if i + 1 < args.len() {
-                    let port: u16 = args[i + 1].parse()
-                        .map_err(|e| ConfigError::ParseError(format!("Invalid port: {}", e)))?;
-                    config.server.bind_addresses = vec![format!("0.0.0.0:{}", port)];
-                    i += 1;
-                }
-            }
-            "--max-connections" => {
-                if i + 1 < args.len() {
-                    config.server.max_connections = args[i + 1].parse()
-                        .map_err(|e| ConfigError::ParseError(format!("Invalid max-connections: {}", e)))?;
-                    i += 1;
-                }
-            }
-            "--ai-enabled" => {
-                if i + 1 < args.len() {
-                    config.ai.enabled = args[i + 1].parse()
-                        .map_err(|e| ConfigError::ParseError(format!("Invalid ai-enabled: {}", e)))?;
-                    i += 1;
-                }
-            }
-            "--web3-enabled" => {
-                if i + 1 < args.len() {
-                    config.web3.enabled = args[i + 1].parse()
-                        .map_err(|e| ConfigError::ParseError(format!("Invalid web3-enabled: {}", e)))?;
-                    i += 1;
-                }
-            }
-            "--database-url" => {
-                if i + 1 < args.len() {
-                    config.storage.connection_string = args[i + 1].clone();
-                    i += 1;
-                }
-            }
-            "--log-level" => {
-                if i + 1 < args.len() {
-                    config.logging.level = args[i + 1].clone();
-                    i += 1;
-                }
-            }
-            "--metrics-port" => {
-                if i + 1 < args.len() {
-                    config.monitoring.metrics_port = args[i + 1].parse()
-                        .map_err(|e| ConfigError::ParseError(format!("Invalid metrics-port: {}", e)))?;
-                    i += 1;
-                }
-            }
-            "--license-key" => {
-                if i + 1 < args.len() {
-                    config.enterprise.license_key = Some(args[i + 1].clone());
-                    i += 1;
-                }
-            }
-            _ => {
-                // Ignore unknown arguments
-                debug!("Ignoring unknown command line argument: {}", arg);
+        } else if let Some((option, field, kind)) = COMMAND_LINE_OPTIONS.iter().find(|(option, _, _)| *option == arg.as_str()) {
+            if let Some(value) = value {
+                set_field(&mut layer, field, kind.parse(option.trim_start_matches('-'), value)?);
+                i += 1;
             }
+        } else {
+            // Ignore unknown arguments
+            debug!("Ignoring unknown command line argument: {}", arg);
         }
         
         i += 1;
     }
     
     info!("Successfully loaded configuration from command line arguments");
-    Ok(config)
+    Ok(layer)
 }
 
 /// Load configuration from remote source
 pub async fn load_from_remote(url: &str) -> Result<A3MailerConfig> {
+    config_from_layers([load_layer_from_remote(url).await?])
+}
+
+/// Load the fields set by a remote source
+///
+/// See [`RemoteSource`](crate::remote::RemoteSource) for repeated fetches
+/// with ETag caching.
+pub async fn load_layer_from_remote(url: &str) -> Result<Value> {
     info!("Loading configuration from remote source: {}", url);
     
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .map_err(|e| ConfigError::NetworkError(e.to_string()))?;
-    
-    let response = client
-        .get(url)
-        .header("Accept", "application/toml, application/json")
-        .send()
-        .await
-        .map_err(|e| ConfigError::NetworkError(e.to_string()))?;
-    
-    if !response.status().is_success() {
-        return Err(ConfigError::NetworkError(format!(
-            "Remote config request failed with status: {}", 
-            response.status()
-        )));
+    crate::remote::RemoteSource::new(url)?.layer().await
+}
+
+/// Build a configuration from layers, later layers taking precedence
+///
+/// Layers are deep merged onto the default configuration: nested tables
+/// merge field by field, while any other value (including lists and
+/// optional fields) set by a later layer replaces the earlier one.
+pub fn config_from_layers(layers: impl IntoIterator<Item = Value>) -> Result<A3MailerConfig> {
+    let mut merged = serde_json::to_value(A3MailerConfig::default())
+        .map_err(|e| ConfigError::ParseError(format!("Default config conversion error: {}", e)))?;
+    for layer in layers {
+        merge_values(&mut merged, layer);
+    }
+    
+    serde_json::from_value(merged)
+        .map_err(|e| ConfigError::ParseError(format!("Config conversion error: {}", e)))
+}
+
+/// Deep merge `layer` into `base`
+pub fn merge_values(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
     }
-    
-    let content_type = response.headers()
-        .get("content-type")
-        .and_then(|ct| ct.to_str().ok())
-        .unwrap_or("application/toml");
-    
-    let content = response.text().await
-        .map_err(|e| ConfigError::NetworkError(e.to_string()))?;
-    
-    let config = if content_type.contains("json") {
-        // Parse as JSON first, then convert to config
-        let json_value: Value = serde_json::from_str(&content)
-            .map_err(|e| ConfigError::ParseError(format!("JSON parse error: {}", e)))?;
-        
-        serde_json::from_value(json_value)
-            .map_err(|e| ConfigError::ParseError(format!("JSON to config conversion error: {}", e)))?
-    } else {
-        // Parse as TOML
-        toml::from_str(&content)
-            .map_err(|e| ConfigError::ParseError(format!("TOML parse error: {}", e)))?
-    };
-    
-    info!("Successfully loaded configuration from remote source: {}", url);
-    Ok(config)
 }
 
 /// Merge two configurations, with the second one taking precedence
+///
+/// Complete configurations do not record which fields were set, so only
+/// the fields of `override_config` that differ from the defaults are
+/// applied. Prefer merging layers with [`config_from_layers`].
 pub fn merge_configs(base: A3MailerConfig, override_config: A3MailerConfig) -> Result<A3MailerConfig> {
     debug!("Merging configurations");
     
-    // For now, we'll do a simple override merge
-    // In a production system, this would be more sophisticated
-    let mut merged = base;
+    let to_value = |config: &A3MailerConfig| serde_json::to_value(config)
+        .map_err(|e| ConfigError::ParseError(format!("Config conversion error: {}", e)));
+    let defaults = to_value(&A3MailerConfig::default())?;
+    let mut merged = to_value(&base)?;
     
-    // Server config
-    if override_config.server.hostname != "localhost" {
-        merged.server.hostname = override_config.server.hostname;
-    }
-    if !override_config.server.bind_addresses.is_empty() {
-        merged.server.bind_addresses = override_config.server.bind_addresses;
-    }
-    if override_config.server.max_connections != 10000 {
-        merged.server.max_connections = override_config.server.max_connections;
-    }
-    if override_config.server.worker_threads.is_some() {
-        merged.server.worker_threads = override_config.server.worker_threads;
-    }
-    if override_config.server.timeout_seconds != 300 {
-        merged.server.timeout_seconds = override_config.server.timeout_seconds;
+    if let Some(layer) = changed_fields(to_value(&override_config)?, &defaults) {
+        merge_values(&mut merged, layer);
     }
     
-    // AI config
-    merged.ai.enabled = override_config.ai.enabled;
-    if override_config.ai.model_path != "models/" {
-        merged.ai.model_path = override_config.ai.model_path;
-    }
-    
-    // Web3 config
-    merged.web3.enabled = override_config.web3.enabled;
-    if override_config.web3.blockchain_network != "ethereum" {
-        merged.web3.blockchain_network = override_config.web3.blockchain_network;
-    }
-    if !override_config.web3.rpc_url.contains("YOUR_PROJECT_ID") {
-        merged.web3.rpc_url = override_config.web3.rpc_url;
-    }
-    
-    // Storage config
-    if override_config.storage.backend != "postgresql" {
-        merged.storage.backend = override_config.storage.backend;
-    }
-    if !override_config.storage.connection_string.contains("user:pass@localhost") {
-        merged.storage.connection_string = override_config.storage.connection_string;
-    }
-    if override_config.storage.max_connections != 100 {
-        merged.storage.max_connections = override_config.storage.max_connections;
-    }
-    
-    // Monitoring config
-    merged.monitoring.enabled = override_config.monitoring.enabled;
-    if override_config.monitoring.metrics_port != 9090 {
-        merged.monitoring.metrics_port = override_config.monitoring.metrics_port;
-    }
-    
-    // Logging config
-    if override_config.logging.level != "info" {
-        merged.logging.level = override_config.logging.level;
-    }
-    if override_config.logging.format != "json" {
-        merged.logging.format = override_config.logging.format;
+    debug!("Configuration merge completed");
+    serde_json::from_value(merged)
+        .map_err(|e| ConfigError::ParseError(format!("Config conversion error: {}", e)))
+}
+
+/// Fields of `value` that differ from `defaults`
+fn changed_fields(value: Value, defaults: &Value) -> Option<Value> {
+    match (value, defaults) {
+        (Value::Object(fields), Value::Object(defaults)) => {
+            let changed = fields
+                .into_iter()
+                .filter_map(|(key, value)| match defaults.get(&key) {
+                    Some(default) => changed_fields(value, default).map(|value| (key, value)),
+                    None => Some((key, value)),
+                })
+                .collect::<serde_json::Map<_, _>>();
+            (!changed.is_empty()).then_some(Value::Object(changed))
+        }
+        (value, default) => (value != *default).then_some(value),
     }
-    merged.logging.structured = override_config.logging.structured;
-    
-    // Enterprise config
-    if override_config.enterprise.license_key.is_some() {
-        merged.enterprise.license_key = override_config.enterprise.license_key;
+}
+
+/// Set a dotted field path, creating intermediate tables
+fn set_field(layer: &mut Value, field: &str, value: Value) {
+    let mut current = layer;
+    for key in field.split('.') {
+        if !current.is_object() {
+            *current = Value::Object(Default::default());
+        }
+        current = current
+            .as_object_mut()
+            .unwrap()
+            .entry(key)
+            .or_insert(Value::Null);
     }
-    
-    debug!("Configuration merge completed");
-    Ok(merged)
+    *current = value;
 }
 
 /// Validate configuration file format
@@ -365,35 +425,174 @@ pub fn validate_config_file(path: &Path) -> Result<()> {
         return Err(ConfigError::FileNotFound(path.to_string_lossy().to_string()));
     }
     
-    let extension = path.extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("");
+    let format = ConfigFormat::from_path(path)?;
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ConfigError::IoError(e.to_string()))?;
     
-    match extension.to_lowercase().as_str() {
-        "toml" => {
-            // Validate TOML syntax
-            let content = std::fs::read_to_string(path)
-                .map_err(|e| ConfigError::IoError(e.to_string()))?;
-            
-            toml::from_str::<Value>(&content)
-                .map_err(|e| ConfigError::ParseError(format!("Invalid TOML syntax: {}", e)))?;
-        }
-        "json" => {
-            // Validate JSON syntax
-            let content = std::fs::read_to_string(path)
-                .map_err(|e| ConfigError::IoError(e.to_string()))?;
-            
-            serde_json::from_str::<Value>(&content)
-                .map_err(|e| ConfigError::ParseError(format!("Invalid JSON syntax: {}", e)))?;
-        }
-        _ => {
-            return Err(ConfigError::UnsupportedFormat(format!(
-                "Unsupported configuration file format: {}", 
-                extension
-            )));
-        }
-    }
+    format.parse(&content)?;
     
     info!("Configuration file format validation successful: {}", path.display());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn expand(text: &str) -> Result<String> {
+        let vars = HashMap::from([
+            ("SMTP_PASSWORD", "s3cret"),
+            ("SMTP_HOST", "mail.example.org"),
+            ("EMPTY", ""),
+        ]);
+        let mut value = Value::String(text.to_string());
+        interpolate_with(&mut value, |name| vars.get(name).map(|value| value.to_string()))?;
+        Ok(value.as_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn test_interpolation() {
+        assert_eq!(expand("${SMTP_PASSWORD}").unwrap(), "s3cret");
+        assert_eq!(expand("smtp://${SMTP_HOST}:587").unwrap(), "smtp://mail.example.org:587");
+        assert_eq!(expand("price: $5").unwrap(), "price: $5");
+
+        // Defaults apply to unset and empty variables
+        assert_eq!(expand("${SMTP_PORT:-587}").unwrap(), "587");
+        assert_eq!(expand("${EMPTY:-fallback}").unwrap(), "fallback");
+        assert_eq!(expand("${SMTP_HOST:-localhost}").unwrap(), "mail.example.org");
+        assert_eq!(expand("${EMPTY}").unwrap(), "");
+
+        // Escaped references are kept literally
+        assert_eq!(expand("$${literal}").unwrap(), "${literal}");
+        assert_eq!(expand("$${SMTP_PASSWORD} is ${SMTP_PASSWORD}").unwrap(), "${SMTP_PASSWORD} is s3cret");
+    }
+
+    #[test]
+    fn test_interpolation_errors() {
+        let error = expand("${DB_PASSWORD}@${DB_HOST}").unwrap_err().to_string();
+        assert!(error.contains("DB_HOST, DB_PASSWORD"), "{}", error);
+        assert!(expand("${SMTP_PASSWORD").is_err());
+        assert!(expand("${}").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load_from_file_interpolates_env() {
+        let dir = std::env::temp_dir().join(format!("a3mailer-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("interpolate.toml");
+
+        let mut table: toml::Table = toml::from_str(&toml::to_string(&A3MailerConfig::default()).unwrap()).unwrap();
+        table["server"]["hostname"] = toml::Value::from("${A3MAILER_TEST_HOSTNAME}");
+        table["storage"]["connection_string"] = toml::Value::from("postgresql://mail:${A3MAILER_TEST_DB_PASSWORD}@db/a3mailer");
+        table["logging"]["level"] = toml::Value::from("${A3MAILER_TEST_LOG_LEVEL:-debug}");
+        table["web3"]["rpc_url"] = toml::Value::from("https://rpc.example.org/$${project}");
+        std::fs::write(&path, toml::to_string(&table).unwrap()).unwrap();
+
+        std::env::set_var("A3MAILER_TEST_HOSTNAME", "mx.example.org");
+        let error = load_from_file(&path).await.unwrap_err().to_string();
+        assert!(error.contains("A3MAILER_TEST_DB_PASSWORD"), "{}", error);
+
+        std::env::set_var("A3MAILER_TEST_DB_PASSWORD", "s3cret");
+        let config = load_from_file(&path).await.unwrap();
+        assert_eq!(config.server.hostname, "mx.example.org");
+        assert_eq!(config.storage.connection_string.expose(), "postgresql://mail:s3cret@db/a3mailer");
+        assert_eq!(config.logging.level, "debug");
+        assert_eq!(config.web3.rpc_url, "https://rpc.example.org/${project}");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_load_from_file_formats() {
+        let dir = std::env::temp_dir().join(format!("a3mailer-formats-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut config = A3MailerConfig::default();
+        config.server.hostname = "mx.example.org".to_string();
+        config.server.worker_threads = Some(8);
+        config.storage.max_connections = 25;
+        config.logging.level = "debug".to_string();
+        config.enterprise.license_key = Some("LICENSE-1234".into());
+
+        // YAML is written from the JSON value so enums use the same externally tagged maps
+        let expected = serde_json::to_value(&config).unwrap();
+        let files = [
+            ("config.toml", toml::to_string(&config).unwrap()),
+            ("config.json", serde_json::to_string_pretty(&expected).unwrap()),
+            ("config.yaml", serde_yaml::to_string(&expected).unwrap()),
+            ("config.yml", serde_yaml::to_string(&expected).unwrap()),
+        ];
+
+        for (name, content) in files {
+            let path = dir.join(name);
+            std::fs::write(&path, content).unwrap();
+            validate_config_file(&path).unwrap();
+
+            let loaded = load_from_file(&path).await.unwrap();
+            assert_eq!(serde_json::to_value(&loaded).unwrap(), expected, "{}", name);
+
+            let merged = merge_configs(A3MailerConfig::default(), loaded).unwrap();
+            assert_eq!(serde_json::to_value(&merged).unwrap(), expected, "{}", name);
+        }
+
+        let path = dir.join("config.ini");
+        std::fs::write(&path, "hostname = mx.example.org").unwrap();
+        assert!(matches!(load_from_file(&path).await, Err(ConfigError::UnsupportedFormat(_))));
+        assert!(matches!(validate_config_file(&path), Err(ConfigError::UnsupportedFormat(_))));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_deep_merge_precedence() {
+        let dir = std::env::temp_dir().join(format!("a3mailer-merge-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("base.toml");
+        std::fs::write(&path, r#"
+[server]
+hostname = "mx.example.org"
+bind_addresses = ["0.0.0.0:25", "0.0.0.0:587"]
+max_connections = 500
+worker_threads = 4
+
+[server.tls]
+enabled = true
+cert_file = "/etc/a3mailer/cert.pem"
+key_file = "/etc/a3mailer/key.pem"
+protocols = ["TLSv1.3"]
+ciphers = []
+
+[logging]
+level = "debug"
+"#).unwrap();
+
+        let vars = HashMap::from([
+            ("A3MAILER_MAX_CONNECTIONS", "2000"),
+            ("A3MAILER_LOG_LEVEL", "info"),
+        ]);
+        let file = load_layer_from_file(&path).await.unwrap();
+        let env = environment_layer_with(|name| vars.get(name).map(|value| value.to_string())).unwrap();
+        let cli = load_layer_from_command_line(&["--port".to_string(), "2525".to_string()]).unwrap();
+        let config = config_from_layers([file, env, cli]).unwrap();
+
+        // Only the overridden fields change
+        assert_eq!(config.server.max_connections, 2000);
+        assert_eq!(config.logging.level, "info");
+        assert_eq!(config.server.hostname, "mx.example.org");
+        assert_eq!(config.server.worker_threads, Some(4));
+        assert_eq!(config.server.timeout_seconds, 300);
+        assert!(config.server.tls.enabled);
+        assert_eq!(config.server.tls.cert_file, "/etc/a3mailer/cert.pem");
+        assert_eq!(config.server.tls.key_file, "/etc/a3mailer/key.pem");
+        assert_eq!(config.server.tls.protocols, vec!["TLSv1.3".to_string()]);
+
+        // Lists are replaced rather than appended to
+        assert_eq!(config.server.bind_addresses, vec!["0.0.0.0:2525".to_string()]);
+
+        let error = environment_layer_with(|name| (name == "A3MAILER_TLS_ENABLED").then(|| "maybe".to_string()));
+        assert!(error.is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
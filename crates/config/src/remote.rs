@@ -0,0 +1,302 @@
+//! Remote Configuration Source for A3Mailer
+//!
+//! This module fetches configuration layers over HTTPS. The ETag of the
+//! last response is sent back as `If-None-Match` so unchanged configuration
+//! is not downloaded again, transient failures are retried with exponential
+//! backoff, and the last good layer is kept when the source is unreachable.
+
+use crate::loader::ConfigFormat;
+use crate::{ConfigError, Result};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+use url::Url;
+
+/// Default number of retries after a transient failure
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// Default delay before the first retry, doubled for every further retry
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Result of fetching a remote configuration
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteFetch {
+    /// The configuration changed and was downloaded
+    Modified(Value),
+    /// The server reported the cached configuration is still current
+    NotModified,
+}
+
+/// Last good configuration received from a remote source
+#[derive(Debug, Clone)]
+struct CachedLayer {
+    etag: Option<String>,
+    layer: Value,
+}
+
+/// Remote configuration source
+#[derive(Debug)]
+pub struct RemoteSource {
+    url: String,
+    client: reqwest::Client,
+    retries: u32,
+    retry_delay: Duration,
+    cache: Mutex<Option<CachedLayer>>,
+}
+
+impl RemoteSource {
+    /// Create a remote source for an HTTPS URL
+    pub fn new(url: &str) -> Result<Self> {
+        Self::build(url, false)
+    }
+
+    /// Create a remote source that also accepts plain HTTP URLs
+    ///
+    /// Intended for local development and tests only.
+    pub fn new_insecure(url: &str) -> Result<Self> {
+        Self::build(url, true)
+    }
+
+    fn build(url: &str, allow_http: bool) -> Result<Self> {
+        let parsed = Url::parse(url)
+            .map_err(|e| ConfigError::NetworkError(format!("Invalid remote config URL {}: {}", url, e)))?;
+        match parsed.scheme() {
+            "https" => {}
+            "http" if allow_http => {}
+            scheme => {
+                return Err(ConfigError::NetworkError(format!(
+                    "Remote config URL must use https, got {}: {}",
+                    scheme, url
+                )));
+            }
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .https_only(!allow_http)
+            .build()
+            .map_err(|e| ConfigError::NetworkError(e.to_string()))?;
+
+        Ok(Self {
+            url: url.to_string(),
+            client,
+            retries: DEFAULT_RETRIES,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            cache: Mutex::new(None),
+        })
+    }
+
+    /// Set the number of retries and the initial backoff delay
+    pub fn with_retry(mut self, retries: u32, retry_delay: Duration) -> Self {
+        self.retries = retries;
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// URL of the remote source
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Fetch the configuration if it changed since the last fetch
+    pub async fn fetch(&self) -> Result<RemoteFetch> {
+        let mut cache = self.cache.lock().await;
+        let etag = cache.as_ref().and_then(|cached| cached.etag.clone());
+
+        let mut attempt = 0;
+        let response = loop {
+            match self.request(etag.as_deref()).await {
+                Ok(response) => break response,
+                Err((e, transient)) if transient && attempt < self.retries => {
+                    let delay = self.retry_delay * 2u32.saturating_pow(attempt);
+                    attempt += 1;
+                    warn!(
+                        "Remote config request to {} failed, retrying in {:?} ({}/{}): {}",
+                        self.url, delay, attempt, self.retries, e
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err((e, _)) => return Err(e),
+            }
+        };
+
+        if response.status() == StatusCode::NOT_MODIFIED && cache.is_some() {
+            debug!("Remote configuration not modified: {}", self.url);
+            return Ok(RemoteFetch::NotModified);
+        }
+
+        let etag = response.headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(|etag| etag.to_string());
+        let content_type = response.headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok())
+            .unwrap_or("application/toml")
+            .to_string();
+
+        let content = response.text().await
+            .map_err(|e| ConfigError::NetworkError(e.to_string()))?;
+
+        let layer = if content_type.contains("json") {
+            ConfigFormat::Json.parse(&content)?
+        } else {
+            ConfigFormat::Toml.parse(&content)?
+        };
+
+        info!("Successfully loaded configuration from remote source: {}", self.url);
+        *cache = Some(CachedLayer { etag, layer: layer.clone() });
+        Ok(RemoteFetch::Modified(layer))
+    }
+
+    /// Current configuration layer
+    ///
+    /// Falls back to the last good layer if the source cannot be reached.
+    pub async fn layer(&self) -> Result<Value> {
+        match self.fetch().await {
+            Ok(RemoteFetch::Modified(layer)) => Ok(layer),
+            Ok(RemoteFetch::NotModified) => self.cached().await.ok_or_else(|| {
+                ConfigError::NetworkError(format!("No cached configuration for {}", self.url))
+            }),
+            Err(e) => match self.cached().await {
+                Some(layer) => {
+                    warn!("Using last good configuration from {}: {}", self.url, e);
+                    Ok(layer)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    async fn cached(&self) -> Option<Value> {
+        self.cache.lock().await.as_ref().map(|cached| cached.layer.clone())
+    }
+
+    /// Send one request, flagging errors worth retrying
+    async fn request(&self, etag: Option<&str>) -> std::result::Result<reqwest::Response, (ConfigError, bool)> {
+        let mut request = self.client
+            .get(&self.url)
+            .header(reqwest::header::ACCEPT, "application/toml, application/json");
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+
+        let response = request.send().await
+            .map_err(|e| (ConfigError::NetworkError(e.to_string()), !e.is_builder()))?;
+
+        let status = response.status();
+        if status.is_success() || status == StatusCode::NOT_MODIFIED {
+            Ok(response)
+        } else {
+            let transient = status.is_server_error()
+                || status == StatusCode::REQUEST_TIMEOUT
+                || status == StatusCode::TOO_MANY_REQUESTS;
+            Err((
+                ConfigError::NetworkError(format!("Remote config request failed with status: {}", status)),
+                transient,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn config_response(hostname: &str, etag: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200)
+            .insert_header("ETag", etag)
+            .set_body_raw(
+                serde_json::json!({ "server": { "hostname": hostname } }).to_string(),
+                "application/json",
+            )
+    }
+
+    fn hostname(layer: &Value) -> &str {
+        layer["server"]["hostname"].as_str().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_remote_etag_caching() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/config.json"))
+            .and(header("If-None-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/config.json"))
+            .and(header("If-None-Match", "\"v1\""))
+            .respond_with(config_response("mx2.example.org", "\"v2\""))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/config.json"))
+            .respond_with(config_response("mx1.example.org", "\"v1\""))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let source = RemoteSource::new_insecure(&format!("{}/config.json", server.uri())).unwrap();
+
+        let RemoteFetch::Modified(layer) = source.fetch().await.unwrap() else {
+            panic!("expected the initial configuration");
+        };
+        assert_eq!(hostname(&layer), "mx1.example.org");
+
+        // Unchanged configuration is served from the cache
+        assert_eq!(source.fetch().await.unwrap(), RemoteFetch::NotModified);
+        assert_eq!(hostname(&source.cached().await.unwrap()), "mx1.example.org");
+
+        let layer = source.layer().await.unwrap();
+        assert_eq!(hostname(&layer), "mx2.example.org");
+    }
+
+    #[tokio::test]
+    async fn test_remote_retries_and_keeps_last_good() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(config_response("mx1.example.org", "\"v1\""))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let source = RemoteSource::new_insecure(&server.uri())
+            .unwrap()
+            .with_retry(2, Duration::from_millis(10));
+
+        // Two transient failures are retried before the configuration arrives
+        assert_eq!(hostname(&source.layer().await.unwrap()), "mx1.example.org");
+
+        // Once retries are exhausted the last good configuration is kept
+        assert!(source.fetch().await.is_err());
+        assert_eq!(hostname(&source.layer().await.unwrap()), "mx1.example.org");
+        assert_eq!(server.received_requests().await.unwrap().len(), 9);
+    }
+
+    #[test]
+    fn test_remote_requires_https() {
+        assert!(RemoteSource::new("http://config.example.org/a3mailer.toml").is_err());
+        assert!(RemoteSource::new("https://config.example.org/a3mailer.toml").is_ok());
+        assert!(RemoteSource::new_insecure("http://127.0.0.1:8080/a3mailer.toml").is_ok());
+    }
+}
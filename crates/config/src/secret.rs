@@ -0,0 +1,128 @@
+//! Secret Values for A3Mailer Configuration
+//!
+//! This module provides a wrapper for sensitive configuration values such as
+//! credentials and license keys. Secrets never appear in `Debug` or `Display`
+//! output; the inner value must be read explicitly with [`Secret::expose`].
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::Cell;
+use std::fmt;
+
+/// Placeholder rendered instead of a secret value
+pub const REDACTED: &str = "***redacted***";
+
+thread_local! {
+    static REDACT_ON_SERIALIZE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Sensitive configuration value
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret<T = String>(T);
+
+impl<T> Secret<T> {
+    /// Wrap a sensitive value
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Access the inner value
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    /// Consume the secret and return the inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// Serialize secrets as [`REDACTED`] while `f` runs on this thread
+///
+/// Outside of this scope secrets serialize to their real value, so
+/// configuration written to disk can be loaded back.
+pub fn redact_on_serialize<R>(f: impl FnOnce() -> R) -> R {
+    struct Reset(bool);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            REDACT_ON_SERIALIZE.with(|redact| redact.set(self.0));
+        }
+    }
+
+    let _reset = Reset(REDACT_ON_SERIALIZE.with(|redact| redact.replace(true)));
+    f()
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret<String> {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T: Serialize> Serialize for Secret<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if REDACT_ON_SERIALIZE.with(Cell::get) {
+            serializer.serialize_str(REDACTED)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Secret<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::A3MailerConfig;
+
+    #[test]
+    fn test_secrets_are_redacted() {
+        let mut config = A3MailerConfig::default();
+        config.storage.connection_string = "postgresql://mail:s3cret@db/a3mailer".into();
+        config.storage.cache.connection_string = "redis://:cache-s3cret@cache:6379".into();
+        config.enterprise.license_key = Some("LICENSE-0123456789abcdef0123456789".into());
+
+        let debug = format!("{:?}", config);
+        assert!(!debug.contains("s3cret"), "{}", debug);
+        assert!(!debug.contains("LICENSE-0123456789"), "{}", debug);
+        assert!(debug.contains(REDACTED));
+        assert_eq!(config.storage.connection_string.to_string(), REDACTED);
+
+        // The value is still available explicitly
+        assert_eq!(config.storage.connection_string.expose(), "postgresql://mail:s3cret@db/a3mailer");
+
+        // Serialization keeps the real value unless redaction is requested
+        let written = toml::to_string(&config).unwrap();
+        assert!(written.contains("postgresql://mail:s3cret@db/a3mailer"));
+        let reloaded: A3MailerConfig = toml::from_str(&written).unwrap();
+        assert_eq!(reloaded.enterprise.license_key, config.enterprise.license_key);
+
+        let redacted = redact_on_serialize(|| toml::to_string(&config).unwrap());
+        assert!(!redacted.contains("s3cret"), "{}", redacted);
+        assert!(!redacted.contains("LICENSE-0123456789"), "{}", redacted);
+        assert!(toml::to_string(&config).unwrap().contains("cache-s3cret"));
+    }
+}
@@ -0,0 +1,86 @@
+//! Secrets Manager for A3Mailer Configuration
+//!
+//! Secret values can be kept out of configuration files by referencing a
+//! file instead, as done with container and orchestrator secrets:
+//!
+//! ```toml
+//! [storage]
+//! connection_string = "file:/run/secrets/db-url"
+//! ```
+//!
+//! The manager replaces such references with the file contents, without the
+//! trailing newline, after the configuration is loaded and before it is
+//! published to subscribers.
+
+use std::path::Path;
+use tracing::debug;
+
+use crate::{A3MailerConfig, ConfigError, Result, Secret};
+
+/// Prefix of secret values read from a file
+pub const FILE_PREFIX: &str = "file:";
+
+/// Resolves secret references in loaded configurations
+#[derive(Debug, Default)]
+pub struct SecretsManager;
+
+impl SecretsManager {
+    /// Create a new secrets manager
+    pub async fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    /// Replace file references in the secrets of a configuration
+    pub async fn apply_secrets(&self, config: &mut A3MailerConfig) -> Result<()> {
+        resolve(&mut config.storage.connection_string).await?;
+        resolve(&mut config.storage.cache.connection_string).await?;
+        if let Some(license_key) = &mut config.enterprise.license_key {
+            resolve(license_key).await?;
+        }
+
+        Ok(())
+    }
+}
+
+async fn resolve(secret: &mut Secret) -> Result<()> {
+    let Some(path) = secret.expose().strip_prefix(FILE_PREFIX) else {
+        return Ok(());
+    };
+    let path = Path::new(path);
+
+    debug!("Reading secret from {}", path.display());
+    let contents = tokio::fs::read_to_string(path).await.map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ConfigError::FileNotFound(path.display().to_string()),
+        _ => ConfigError::IoError(format!("Failed to read secret {}: {}", path.display(), e)),
+    })?;
+    *secret = Secret::new(contents.trim_end_matches(['\r', '\n']).to_string());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db-url");
+        std::fs::write(&path, "postgresql://mail:s3cret@db/a3mailer\n").unwrap();
+
+        let mut config = A3MailerConfig::default();
+        config.storage.connection_string = format!("{FILE_PREFIX}{}", path.display()).into();
+        config.enterprise.license_key = Some("LICENSE-0123456789".into());
+        SecretsManager::new().await.unwrap().apply_secrets(&mut config).await.unwrap();
+
+        // File references are replaced, other values are kept as is
+        assert_eq!(config.storage.connection_string.expose(), "postgresql://mail:s3cret@db/a3mailer");
+        assert_eq!(config.enterprise.license_key.as_ref().unwrap().expose(), "LICENSE-0123456789");
+
+        config.storage.cache.connection_string = format!("{FILE_PREFIX}{}", dir.path().join("missing").display()).into();
+        assert!(matches!(
+            SecretsManager::new().await.unwrap().apply_secrets(&mut config).await,
+            Err(ConfigError::FileNotFound(_))
+        ));
+    }
+}
@@ -5,7 +5,7 @@
 
 use crate::{A3MailerConfig, Result, ConfigError};
 use std::collections::HashSet;
-use std::net::{IpAddr, SocketAddr};
+use std::net::SocketAddr;
 use std::path::Path;
 use tracing::{info, warn, error, debug};
 use url::Url;
@@ -67,6 +67,11 @@ impl ConfigValidator {
         self.add_result(ValidationSeverity::Info, field, message, None);
     }
 
+    /// Check if the validator runs in strict mode
+    pub fn is_strict(&self) -> bool {
+        self.strict_mode
+    }
+
     /// Get validation results
     pub fn get_results(&self) -> &[ValidationResult] {
         &self.results
@@ -123,10 +128,13 @@ pub async fn validate_config(config: &A3MailerConfig) -> Result<()> {
             }
         }
         
-        return Err(ConfigError::ValidationError(format!(
-            "Configuration validation failed with {} errors", 
-            error_count
-        )));
+        return Err(ConfigError::Validation(
+            validator.get_results()
+                .iter()
+                .filter(|result| result.severity == ValidationSeverity::Error)
+                .map(|result| format!("{}: {}", result.field, result.message))
+                .collect()
+        ));
     }
     
     if warning_count > 0 {
@@ -289,11 +297,7 @@ async fn validate_ai_config(validator: &mut ConfigValidator, config: &crate::AiC
         if config.model_path.is_empty() {
             validator.add_error("ai.model_path", "AI model path cannot be empty when AI is enabled");
         } else if !Path::new(&config.model_path).exists() {
-            validator.add_warning(
-                "ai.model_path",
-                &format!("AI model path does not exist: {}", config.model_path),
-                Some("Ensure the model directory exists and contains required models")
-            );
+            validator.add_error("ai.model_path", &format!("AI model path does not exist: {}", config.model_path));
         }
         
         // Validate threat detection config
@@ -415,9 +419,9 @@ async fn validate_storage_config(validator: &mut ConfigValidator, config: &crate
     }
     
     // Validate connection string
-    if config.connection_string.is_empty() {
+    if config.connection_string.expose().is_empty() {
         validator.add_error("storage.connection_string", "Database connection string cannot be empty");
-    } else if config.connection_string.contains("user:pass@localhost") {
+    } else if config.connection_string.expose().contains("user:pass@localhost") {
         validator.add_warning(
             "storage.connection_string",
             "Using default database credentials",
@@ -500,7 +504,7 @@ async fn validate_enterprise_config(validator: &mut ConfigValidator, config: &cr
     
     // Validate license key format if present
     if let Some(license_key) = &config.license_key {
-        if license_key.len() < 32 {
+        if license_key.expose().len() < 32 {
             validator.add_warning(
                 "enterprise.license_key",
                 "License key appears to be too short",
@@ -534,6 +538,15 @@ async fn validate_cross_dependencies(validator: &mut ConfigValidator, config: &A
         );
     }
     
+    // Check replication topology
+    let replication = &config.storage.replication;
+    if replication.enabled && replication.nodes.len() < 2 {
+        validator.add_error(
+            "storage.replication.nodes",
+            &format!("Replication requires at least 2 nodes, {} configured", replication.nodes.len())
+        );
+    }
+    
     // Check monitoring and logging
     if !config.monitoring.enabled {
         validator.add_warning(
@@ -543,3 +556,112 @@ async fn validate_cross_dependencies(validator: &mut ConfigValidator, config: &A
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A valid configuration with TLS and AI files created under `dir`
+    fn valid_config(dir: &Path) -> A3MailerConfig {
+        std::fs::create_dir_all(dir.join("models")).unwrap();
+        std::fs::write(dir.join("cert.pem"), "cert").unwrap();
+        std::fs::write(dir.join("key.pem"), "key").unwrap();
+
+        let mut config = A3MailerConfig::default();
+        config.server.bind_addresses = vec!["0.0.0.0:2525".to_string()];
+        config.server.tls.enabled = true;
+        config.server.tls.cert_file = dir.join("cert.pem").to_string_lossy().to_string();
+        config.server.tls.key_file = dir.join("key.pem").to_string_lossy().to_string();
+        config.server.tls.protocols = vec!["TLSv1.3".to_string()];
+        config.ai.enabled = true;
+        config.ai.model_path = dir.join("models").to_string_lossy().to_string();
+        config.ai.threat_detection.confidence_threshold = 0.8;
+        config.web3.enabled = true;
+        config.web3.rpc_url = "https://rpc.example.org".to_string();
+        config.web3.did.resolver_url = "https://resolver.example.org".to_string();
+        config.web3.ipfs.gateway_url = "https://ipfs.example.org".to_string();
+        config.web3.smart_contracts.gas_limit = 100_000;
+        config.storage.replication.enabled = true;
+        config.storage.replication.nodes = vec!["db1:5432".to_string(), "db2:5432".to_string()];
+        config.logging.level = "info".to_string();
+        config
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("a3mailer-validator-{}-{}", name, std::process::id()))
+    }
+
+    async fn problems(config: &A3MailerConfig) -> Vec<String> {
+        match validate_config(config).await {
+            Err(ConfigError::Validation(problems)) => problems,
+            other => panic!("expected validation errors, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_valid_config() {
+        let dir = temp_dir("valid");
+        validate_config(&valid_config(&dir)).await.unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tls_without_certificate() {
+        let dir = temp_dir("tls");
+        let mut config = valid_config(&dir);
+        config.server.tls.cert_file = String::new();
+
+        let problems = problems(&config).await;
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert!(problems[0].starts_with("server.tls.cert_file:"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replication_without_enough_nodes() {
+        let dir = temp_dir("replication");
+        let mut config = valid_config(&dir);
+        config.storage.replication.nodes.truncate(1);
+
+        let problems = problems(&config).await;
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert!(problems[0].starts_with("storage.replication.nodes:"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ai_with_missing_model_path() {
+        let dir = temp_dir("ai");
+        let mut config = valid_config(&dir);
+        config.ai.model_path = dir.join("missing").to_string_lossy().to_string();
+
+        let problems = problems(&config).await;
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert!(problems[0].starts_with("ai.model_path:"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_default_config_does_not_require_models() {
+        let config = A3MailerConfig::default();
+        assert!(!config.ai.enabled);
+
+        if let Err(ConfigError::Validation(problems)) = validate_config(&config).await {
+            assert!(problems.iter().all(|problem| !problem.starts_with("ai.")), "{:?}", problems);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_all_problems_are_reported() {
+        let dir = temp_dir("all");
+        let mut config = valid_config(&dir);
+        config.server.tls.cert_file = String::new();
+        config.storage.replication.nodes.clear();
+        config.ai.model_path = dir.join("missing").to_string_lossy().to_string();
+
+        let problems = problems(&config).await;
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,187 @@
+//! Configuration Watcher for A3Mailer
+//!
+//! This module polls file and remote configuration sources and reloads the
+//! configuration when they change. Remote sources are polled with
+//! conditional requests, so an unchanged remote configuration is a no-op. Reloads that fail to parse or
+//! validate keep the previous configuration and are reported as
+//! [`ConfigEvent::ReloadFailed`](crate::ConfigEvent) events.
+
+use crate::remote::RemoteFetch;
+use crate::{ConfigSource, ConfigState, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, warn};
+
+/// Default interval between checks for source changes
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Configuration watcher
+pub struct ConfigWatcher {
+    shutdown: oneshot::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl ConfigWatcher {
+    /// Start watching the sources of a configuration manager
+    pub(crate) async fn new(state: Arc<ConfigState>, interval: Duration) -> Result<Self> {
+        let paths = state.sources
+            .iter()
+            .filter_map(|source| match source {
+                ConfigSource::File(path) => Some(path.clone()),
+                _ => None,
+            })
+            .collect::<Vec<PathBuf>>();
+
+        let mut fingerprints = Vec::with_capacity(paths.len());
+        for path in &paths {
+            fingerprints.push(fingerprint(path).await);
+        }
+
+        let (shutdown, mut shutdown_rx) = oneshot::channel();
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = &mut shutdown_rx => break,
+                    _ = ticker.tick() => {}
+                }
+
+                let mut changed = false;
+                for (path, previous) in paths.iter().zip(fingerprints.iter_mut()) {
+                    let current = fingerprint(path).await;
+                    if current != *previous {
+                        debug!("Configuration file changed: {}", path.display());
+                        *previous = current;
+                        changed = true;
+                    }
+                }
+
+                for remote in state.remotes.values() {
+                    match remote.fetch().await {
+                        Ok(RemoteFetch::Modified(_)) => {
+                            debug!("Remote configuration changed: {}", remote.url());
+                            changed = true;
+                        }
+                        Ok(RemoteFetch::NotModified) => {}
+                        Err(e) => warn!("Failed to poll remote configuration {}: {}", remote.url(), e),
+                    }
+                }
+
+                if changed {
+                    if let Err(e) = state.reload().await {
+                        warn!("Ignoring configuration change: {}", e);
+                    }
+                }
+            }
+        });
+
+        Ok(Self { shutdown, handle })
+    }
+
+    /// Stop watching and wait for the watcher task to exit
+    pub async fn stop(self) -> Result<()> {
+        let _ = self.shutdown.send(());
+        let _ = self.handle.await;
+        Ok(())
+    }
+}
+
+/// Hash of a file's contents, `None` if it cannot be read
+async fn fingerprint(path: &Path) -> Option<u64> {
+    let contents = tokio::fs::read(path).await.ok()?;
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ConfigEvent, ConfigManager, ConfigSource};
+    use std::path::Path;
+    use std::time::Duration;
+
+    fn write_config(path: &Path, hostname: &str) {
+        std::fs::write(path, format!(r#"
+[server]
+hostname = "{}"
+
+[server.tls]
+enabled = false
+cert_file = ""
+key_file = ""
+protocols = []
+ciphers = []
+
+[ai]
+enabled = false
+
+[web3]
+enabled = false
+rpc_url = "https://rpc.example.org"
+"#, hostname)).unwrap();
+    }
+
+    async fn watched_manager(name: &str) -> (ConfigManager, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("a3mailer-watch-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        write_config(&path, "mx1.example.org");
+
+        let mut manager = ConfigManager::builder()
+            .add_source(ConfigSource::File(path.clone()))
+            .watch_interval(Duration::from_millis(20))
+            .build()
+            .await
+            .unwrap();
+        manager.start_watching().await.unwrap();
+
+        (manager, dir)
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_reloaded_config() {
+        let (mut manager, dir) = watched_manager("reload").await;
+        let mut updates = manager.subscribe();
+        assert_eq!(updates.borrow().server.hostname, "mx1.example.org");
+
+        write_config(&dir.join("config.toml"), "mx2.example.org");
+        tokio::time::timeout(Duration::from_secs(5), updates.changed())
+            .await
+            .expect("no configuration update")
+            .unwrap();
+
+        assert_eq!(updates.borrow_and_update().server.hostname, "mx2.example.org");
+        assert_eq!(manager.get_config().await.unwrap().server.hostname, "mx2.example.org");
+
+        manager.stop_watching().await.unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_change_is_not_published() {
+        let (mut manager, dir) = watched_manager("invalid").await;
+        let updates = manager.subscribe();
+        let mut events = manager.subscribe_events();
+
+        std::fs::write(dir.join("config.toml"), "[server\nhostname = ").unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("no reload event")
+            .unwrap();
+        assert!(matches!(event, ConfigEvent::ReloadFailed(_)), "{:?}", event);
+
+        assert!(!updates.has_changed().unwrap());
+        assert_eq!(manager.get_config().await.unwrap().server.hostname, "mx1.example.org");
+
+        manager.stop_watching().await.unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -401,6 +401,19 @@ impl TimeRange {
     }
 
     pub fn from_raw(raw: &RawElement<'_>) -> super::Result<Option<Self>> {
+        Self::from_raw_unclamped(raw).map(|range| {
+            range.map(|mut range| {
+                if range.end < range.start {
+                    range.end = i64::MAX;
+                }
+                range
+            })
+        })
+    }
+
+    /// Parse a time-range without replacing an end before the start, so
+    /// callers can reject inverted ranges instead of treating them as open
+    pub(crate) fn from_raw_unclamped(raw: &RawElement<'_>) -> super::Result<Option<Self>> {
         let mut range = TimeRange {
             start: i64::MIN,
             end: i64::MAX,
@@ -418,10 +431,6 @@ impl TimeRange {
             }
         }
 
-        if range.end < range.start {
-            range.end = i64::MAX;
-        }
-
         if range.start != i64::MIN || range.end != i64::MAX {
             Ok(Some(range))
         } else {
@@ -199,7 +199,7 @@ impl DavParser for CalendarQuery {
                         ns: Namespace::CalDav,
                         element: Element::TimeRange,
                     } => {
-                        let range = TimeRange::from_raw(&raw)?;
+                        let range = TimeRange::from_raw_unclamped(&raw)?;
                         stream.expect_element_end()?;
                         if let Some(filter) = range.and_then(|range| {
                             Filter::from_parts(
@@ -11,6 +11,11 @@ pub mod property;
 pub mod request;
 pub mod response;
 
+/// Namespace for A3Mailer extension elements, such as the error category
+/// reported in `DAV:error` bodies that carry no WebDAV precondition. It shares
+/// the `urn:a3mailer:` prefix of the lock and sync token URNs.
+pub const A3MAILER_NAMESPACE: &str = "urn:a3mailer:dav";
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(test, derive(serde::Serialize, serde::Deserialize))]
 pub struct NamedElement {
@@ -47,13 +47,15 @@ pub mod request;
 pub mod router;
 pub mod security;
 pub mod server;
+pub(crate) mod webdav;
 
 #[cfg(test)]
 mod test_utils;
 
 use dav_proto::schema::{
+    A3MAILER_NAMESPACE, Namespace,
     request::DavPropertyValue,
-    response::{Condition, List, Prop, PropStat, ResponseDescription, Status},
+    response::{Condition, ErrorResponse, List, Prop, PropStat, ResponseDescription, Status},
 };
 use groupware::{DavResourceName, RFC_3986};
 use hyper::{Method, StatusCode};
@@ -215,6 +217,12 @@ impl From<DavErrorCondition> for DavError {
     }
 }
 
+impl From<trc::Error> for DavError {
+    fn from(value: trc::Error) -> Self {
+        DavError::Internal(value)
+    }
+}
+
 impl From<Condition> for DavErrorCondition {
     fn from(value: Condition) -> Self {
         DavErrorCondition {
@@ -510,6 +518,41 @@ impl DavError {
         }
     }
 
+    /// Convert to an HTTP response with a `DAV:error` XML body
+    ///
+    /// Errors carrying a WebDAV condition report its precondition element,
+    /// any other error reports its category.
+    pub fn into_response(self) -> HttpResponse {
+        let status = self.status_code();
+        let category = self.category();
+        let condition = match self {
+            Self::Condition(cond) => Some(cond.condition),
+            Self::Conflict { condition, .. } => condition,
+            Self::Code(code) if code == StatusCode::NOT_MODIFIED => return HttpResponse::new(status),
+            _ => None,
+        };
+
+        let body = match condition {
+            Some(condition) => {
+                let namespace = match &condition {
+                    Condition::Base(_) => Namespace::Dav,
+                    Condition::Cal(_) => Namespace::CalDav,
+                    Condition::Card(_) => Namespace::CardDav,
+                };
+                ErrorResponse::new(condition)
+                    .with_namespace(namespace)
+                    .to_string()
+            }
+            None => format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+                 <D:error xmlns:D=\"DAV:\" xmlns:X=\"{A3MAILER_NAMESPACE}\">\
+                 <X:category>{category}</X:category></D:error>"
+            ),
+        };
+
+        HttpResponse::new(status).with_xml_body(body)
+    }
+
     /// Convert to a tracing event for logging
     pub fn to_event(&self) -> trc::Event<trc::EventType> {
         match self {
@@ -595,10 +638,6 @@ impl DavRequestHandler for Server {
         resource: DavResourceName,
         method: DavMethod,
     ) -> HttpResponse {
-        // For now, return a simple response indicating DAV support
-        // In a full implementation, this would route to specific handlers
-        // based on the resource type and method
-
         match (resource, method) {
             (_, DavMethod::OPTIONS) => {
                 HttpResponse::new(StatusCode::OK)
@@ -611,6 +650,10 @@ impl DavRequestHandler for Server {
                         "OPTIONS, GET, HEAD, POST, PUT, DELETE, COPY, MOVE, MKCALENDAR, MKCOL, PROPFIND, PROPPATCH, LOCK, UNLOCK, REPORT, ACL"
                     )
             }
+            (DavResourceName::File | DavResourceName::Cal | DavResourceName::Card, _) => {
+                webdav::handle_resource_request(self, req, access_token, session, resource, method)
+                    .await
+            }
             _ => {
                 // Return a basic "not implemented" response for now
                 HttpResponse::new(StatusCode::NOT_IMPLEMENTED)
@@ -16,7 +16,7 @@ mod tests {
     use dav_proto::schema::{
         property::{WebDavProperty, DavValue, ResourceType},
         request::DavPropertyValue,
-        response::{Condition, BaseCondition, CalCondition, Status},
+        response::{Condition, BaseCondition, CalCondition, Href, Status},
     };
     use hyper::{Method, StatusCode};
 
@@ -206,6 +206,55 @@ mod tests {
         assert_eq!(simple_condition.context, None);
     }
 
+    /// Test DAV:error response bodies
+    #[test]
+    fn test_dav_error_into_response() {
+        fn body(response: &HttpResponse) -> &str {
+            match response.body() {
+                http_proto::HttpResponseBody::Text(body) => body,
+                _ => panic!("expected a text body"),
+            }
+        }
+
+        let lock_conflict: DavError = DavErrorCondition::new(
+            StatusCode::LOCKED,
+            BaseCondition::NoConflictingLock(List(vec![Href("/dav/file/john/a.txt".into())])),
+        )
+        .into();
+        let response = lock_conflict.into_response();
+        assert_eq!(response.status(), StatusCode::LOCKED);
+        assert_eq!(
+            response.headers().unwrap().get("content-type").unwrap(),
+            "application/xml; charset=utf-8"
+        );
+        assert_eq!(
+            body(&response),
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?><D:error xmlns:D=\"DAV:\">",
+                "<D:no-conflicting-lock><D:href>/dav/file/john/a.txt</D:href>",
+                "</D:no-conflicting-lock></D:error>"
+            )
+        );
+
+        let conflict = DavError::conflict_with_condition(
+            "Calendar data is invalid",
+            CalCondition::ValidCalendarData,
+        )
+        .into_response();
+        assert_eq!(conflict.status(), StatusCode::CONFLICT);
+        assert!(body(&conflict).contains("xmlns:A=\"urn:ietf:params:xml:ns:caldav\""));
+        assert!(body(&conflict).contains("<A:valid-calendar-data/>"));
+
+        let not_found = DavError::not_found("file", "/dav/file/john/missing").into_response();
+        assert_eq!(not_found.status(), StatusCode::NOT_FOUND);
+        assert!(body(&not_found).contains("<X:category>not_found</X:category>"));
+        assert!(body(&not_found).contains(&format!("xmlns:X=\"{A3MAILER_NAMESPACE}\"")));
+        assert!(!body(&not_found).contains("/dav/file/john/missing"));
+
+        let not_modified = DavError::Code(StatusCode::NOT_MODIFIED).into_response();
+        assert!(matches!(not_modified.body(), http_proto::HttpResponseBody::Empty));
+    }
+
     /// Test PropStatBuilder functionality
     #[test]
     fn test_propstat_builder() {
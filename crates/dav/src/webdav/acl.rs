@@ -0,0 +1,410 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! WebDAV access control (RFC 3744)
+//!
+//! Grants are stored on each node and inherited by its descendants. The
+//! account owner implicitly holds every privilege. WebDAV privileges map to
+//! the [`Acl`] access model as follows:
+//!
+//! | Privilege   | Access rights                                           |
+//! |-------------|---------------------------------------------------------|
+//! | `read`      | `Read`, `ReadItems`                                     |
+//! | `write`     | `Modify`, `Delete`, `AddItems`, `ModifyItems`, `RemoveItems` |
+//! | `read-acl`  | `Read`, `ReadItems`                                     |
+//! | `write-acl` | `Administer`                                            |
+
+use dav_proto::schema::{
+    property::{DavValue, Privilege},
+    request::Acl as AclRequest,
+    response::{Ace, BaseCondition, GrantDeny, Href, List, Principal, Resource},
+};
+use groupware::DavResourceName;
+use hyper::StatusCode;
+use jmap_proto::types::acl::Acl;
+use utils::map::bitmap::Bitmap;
+
+use super::{ResourcePath, ResourceTree, normalize_path, parent_path, strip_origin};
+use crate::{DavError, DavErrorCondition, DavMethod};
+
+/// Access rights granted to a principal on a node and its descendants
+#[derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PrincipalGrant {
+    pub principal: String,
+    pub grants: Bitmap<Acl>,
+}
+
+/// Access rights held by `principal` on `path`, including inherited grants
+pub fn privileges(
+    tree: &ResourceTree,
+    resource: &ResourcePath,
+    path: &str,
+    principal: &str,
+) -> Bitmap<Acl> {
+    if principal == resource.account {
+        return Bitmap::all();
+    }
+
+    let mut acl = Bitmap::new();
+    let mut path = normalize_path(path);
+    loop {
+        if let Some(node) = tree.get(path) {
+            for grant in node.acl.iter().filter(|grant| grant.principal == principal) {
+                acl.union(&grant.grants);
+            }
+        }
+        if path.is_empty() {
+            break;
+        }
+        path = parent_path(path);
+    }
+    acl
+}
+
+/// Access right a method requires on the request URI
+pub fn required_privilege(method: DavMethod) -> (Acl, Privilege) {
+    match method {
+        DavMethod::GET
+        | DavMethod::HEAD
+        | DavMethod::OPTIONS
+        | DavMethod::PROPFIND
+        | DavMethod::REPORT => (Acl::Read, Privilege::Read),
+        DavMethod::ACL => (Acl::Administer, Privilege::WriteAcl),
+        _ => (Acl::Modify, Privilege::Write),
+    }
+}
+
+/// Check that `principal` holds `right` on `path`
+pub fn check_privilege(
+    tree: &ResourceTree,
+    resource: &ResourcePath,
+    path: &str,
+    principal: &str,
+    (right, privilege): (Acl, Privilege),
+) -> crate::Result<()> {
+    if privileges(tree, resource, path, principal).contains(right) {
+        Ok(())
+    } else {
+        let is_collection = tree.get(path).is_some_and(|node| node.is_collection);
+        Err(DavErrorCondition::new(
+            StatusCode::FORBIDDEN,
+            BaseCondition::NeedPrivileges(List(vec![Resource {
+                href: Href(resource.href(path, is_collection)),
+                privilege,
+            }])),
+        )
+        .into())
+    }
+}
+
+/// Value of the `acl` property of a node
+///
+/// The owner is reported as a protected ACE, followed by the grants of the
+/// node itself and the ones inherited from its ancestors.
+pub fn acl_property(tree: &ResourceTree, resource: &ResourcePath, path: &str) -> DavValue {
+    let mut aces = vec![
+        Ace::new(
+            Principal::Href(Href(principal_href(&resource.account))),
+            GrantDeny::grant(vec![Privilege::All]),
+        )
+        .with_protected(),
+    ];
+
+    let mut ancestor = normalize_path(path);
+    loop {
+        if let Some(node) = tree.get(ancestor) {
+            for grant in &node.acl {
+                let ace = Ace::new(
+                    Principal::Href(Href(principal_href(&grant.principal))),
+                    GrantDeny::grant(acl_to_privileges(grant.grants)),
+                );
+                aces.push(if ancestor != normalize_path(path) {
+                    ace.with_inherited(resource.href(ancestor, true))
+                } else {
+                    ace
+                });
+            }
+        }
+        if ancestor.is_empty() {
+            break;
+        }
+        ancestor = parent_path(ancestor);
+    }
+
+    DavValue::Acl(List(aces))
+}
+
+/// Replace the grants of a node with the ACEs of an `ACL` request
+///
+/// The caller needs `write-acl` on the resource and may only grant
+/// privileges it holds itself.
+pub fn set_acl(
+    tree: &mut ResourceTree,
+    resource: &ResourcePath,
+    principal: &str,
+    request: AclRequest,
+) -> crate::Result<()> {
+    check_privilege(
+        tree,
+        resource,
+        &resource.path,
+        principal,
+        required_privilege(DavMethod::ACL),
+    )?;
+    if !tree.contains(&resource.path) {
+        return Err(DavError::not_found(
+            "resource",
+            resource.href(&resource.path, false),
+        ));
+    }
+    let held = privileges(tree, resource, &resource.path, principal);
+
+    let mut grants: Vec<PrincipalGrant> = Vec::with_capacity(request.aces.len());
+    for ace in request.aces {
+        if ace.invert {
+            return Err(
+                DavErrorCondition::new(StatusCode::FORBIDDEN, BaseCondition::NoInvert).into(),
+            );
+        }
+        let requested = match ace.grant_deny {
+            GrantDeny::Grant(list) => list.0,
+            GrantDeny::Deny(_) => {
+                return Err(
+                    DavErrorCondition::new(StatusCode::FORBIDDEN, BaseCondition::GrantOnly).into(),
+                );
+            }
+        };
+        let grantee = principal_name(&ace.principal).ok_or_else(|| {
+            DavError::from(DavErrorCondition::new(
+                StatusCode::FORBIDDEN,
+                BaseCondition::AllowedPrincipal,
+            ))
+        })?;
+
+        let mut acl = Bitmap::<Acl>::new();
+        for privilege in requested {
+            match privilege {
+                Privilege::Read | Privilege::ReadAcl => {
+                    acl.insert(Acl::Read);
+                    acl.insert(Acl::ReadItems);
+                }
+                Privilege::Write => {
+                    acl.insert(Acl::Modify);
+                    acl.insert(Acl::Delete);
+                    acl.insert(Acl::AddItems);
+                    acl.insert(Acl::ModifyItems);
+                    acl.insert(Acl::RemoveItems);
+                }
+                Privilege::WriteAcl => {
+                    acl.insert(Acl::Administer);
+                }
+                _ => {
+                    return Err(DavErrorCondition::new(
+                        StatusCode::FORBIDDEN,
+                        BaseCondition::NotSupportedPrivilege,
+                    )
+                    .into());
+                }
+            }
+        }
+
+        // Privilege escalation
+        if acl.bitmap & !held.bitmap != 0 {
+            return Err(DavErrorCondition::new(
+                StatusCode::FORBIDDEN,
+                BaseCondition::NoAceConflict,
+            )
+            .with_details("Cannot grant privileges not held by the requester")
+            .into());
+        }
+
+        // The owner holds every privilege already
+        if acl.is_empty() || grantee == resource.account {
+            continue;
+        }
+        match grants.iter_mut().find(|grant| grant.principal == grantee) {
+            Some(grant) => grant.grants.union(&acl),
+            None => grants.push(PrincipalGrant {
+                principal: grantee.to_string(),
+                grants: acl,
+            }),
+        }
+    }
+
+    if let Some(node) = tree.get_mut(&resource.path) {
+        node.acl = grants;
+    }
+
+    Ok(())
+}
+
+fn acl_to_privileges(acl: Bitmap<Acl>) -> Vec<Privilege> {
+    let mut privileges = Vec::with_capacity(4);
+    if acl.contains(Acl::Read) {
+        privileges.push(Privilege::Read);
+        privileges.push(Privilege::ReadAcl);
+    }
+    if acl.contains(Acl::Modify) {
+        privileges.push(Privilege::Write);
+    }
+    if acl.contains(Acl::Administer) {
+        privileges.push(Privilege::WriteAcl);
+    }
+    privileges
+}
+
+fn principal_href(name: &str) -> String {
+    format!("{}/{name}/", DavResourceName::Principal.base_path())
+}
+
+fn principal_name(principal: &Principal) -> Option<&str> {
+    let Principal::Href(href) = principal else {
+        return None;
+    };
+    let name = normalize_path(
+        strip_origin(&href.0)?
+            .strip_prefix(DavResourceName::Principal.base_path())?
+            .strip_prefix('/')?,
+    );
+
+    (!name.is_empty() && !name.contains('/')).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webdav::{
+        parse_body,
+        tests::{resource_path, sample_tree},
+    };
+    use dav_proto::schema::response::Condition;
+
+    fn acl_request(principal: &str, privileges: &str) -> AclRequest {
+        parse_body(
+            format!(
+                r#"<D:acl xmlns:D="DAV:">
+                     <D:ace>
+                       <D:principal><D:href>/dav/pal/{principal}/</D:href></D:principal>
+                       <D:grant>{privileges}</D:grant>
+                     </D:ace>
+                   </D:acl>"#
+            )
+            .as_bytes(),
+        )
+        .unwrap()
+    }
+
+    fn aces(value: DavValue) -> Vec<Ace> {
+        match value {
+            DavValue::Acl(aces) => aces.0,
+            value => panic!("unexpected value {value:?}"),
+        }
+    }
+
+    #[test]
+    fn test_read_acl() {
+        let mut tree = sample_tree();
+        let docs = resource_path("docs");
+
+        set_acl(
+            &mut tree,
+            &docs,
+            "john",
+            acl_request(
+                "jane",
+                "<D:privilege><D:read/></D:privilege><D:privilege><D:write-acl/></D:privilege>",
+            ),
+        )
+        .unwrap();
+
+        // Grants are inherited by descendants
+        let jane = privileges(&tree, &docs, "docs/sub/b.txt", "jane");
+        assert!(jane.contains(Acl::Read));
+        assert!(jane.contains(Acl::Administer));
+        assert!(!jane.contains(Acl::Modify));
+        assert!(privileges(&tree, &docs, "readme.md", "jane").is_empty());
+
+        let own = aces(acl_property(&tree, &docs, "docs"));
+        assert_eq!(own.len(), 2);
+        assert!(own[0].protected);
+        assert_eq!(own[1].principal, Principal::Href(Href("/dav/pal/jane/".into())));
+        assert_eq!(
+            own[1].grant_deny,
+            GrantDeny::grant(vec![Privilege::Read, Privilege::ReadAcl, Privilege::WriteAcl])
+        );
+        assert_eq!(own[1].inherited, None);
+
+        let inherited = aces(acl_property(&tree, &docs, "docs/a.txt"));
+        assert_eq!(
+            inherited[1].inherited,
+            Some(Href("/dav/file/john/docs/".into()))
+        );
+    }
+
+    #[test]
+    fn test_privilege_escalation() {
+        let mut tree = sample_tree();
+        let docs = resource_path("docs");
+        set_acl(
+            &mut tree,
+            &docs,
+            "john",
+            acl_request(
+                "jane",
+                "<D:privilege><D:read/></D:privilege><D:privilege><D:write-acl/></D:privilege>",
+            ),
+        )
+        .unwrap();
+
+        // Jane administers the ACL but cannot hand out write access
+        let err = set_acl(
+            &mut tree,
+            &docs,
+            "jane",
+            acl_request("bob", "<D:privilege><D:write/></D:privilege>"),
+        )
+        .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+        assert!(matches!(
+            err,
+            DavError::Condition(DavErrorCondition {
+                condition: Condition::Base(BaseCondition::NoAceConflict),
+                ..
+            })
+        ));
+        assert!(privileges(&tree, &docs, "docs", "bob").is_empty());
+
+        // Privileges outside of the supported set
+        let err = set_acl(
+            &mut tree,
+            &docs,
+            "jane",
+            acl_request("bob", "<D:privilege><D:unbind/></D:privilege>"),
+        )
+        .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+
+        // Without write-acl the request is rejected outright
+        let err = set_acl(
+            &mut tree,
+            &docs,
+            "bob",
+            acl_request("bob", "<D:privilege><D:read/></D:privilege>"),
+        )
+        .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+
+        // Delegated grants within the held privileges succeed
+        set_acl(
+            &mut tree,
+            &docs,
+            "jane",
+            acl_request("bob", "<D:privilege><D:read/></D:privilege>"),
+        )
+        .unwrap();
+        assert!(privileges(&tree, &docs, "docs/a.txt", "bob").contains(Acl::Read));
+    }
+}
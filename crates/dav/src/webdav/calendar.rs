@@ -0,0 +1,368 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! CalDAV `calendar-query` REPORT handling
+//!
+//! Calendar object resources are stored as iCalendar text in the blob
+//! store. Recurring components are expanded up to [`MAX_EXPANSIONS`]
+//! instances to decide whether they overlap the queried time range.
+
+use calcard::{
+    common::timezone::Tz,
+    icalendar::{ICalendar, ICalendarComponentType, dates::TimeOrDelta},
+};
+use dav_proto::{
+    Depth,
+    schema::{
+        Namespace,
+        property::{CalDavProperty, DavProperty, DavValue, TimeRange},
+        request::{CalendarQuery, DavPropertyValue, Filter, FilterOp, PropFind},
+        response::{CalCondition, MultiStatus, Response},
+    },
+};
+use hyper::StatusCode;
+
+use super::{DavNode, ResourcePath, ResourceTree, propfind::insert_requested};
+use crate::{DavError, DavErrorCondition, PropStatBuilder};
+
+/// Maximum number of recurrence instances expanded per calendar object
+pub const MAX_EXPANSIONS: usize = 3000;
+
+type CalendarFilter = Filter<
+    Vec<ICalendarComponentType>,
+    calcard::icalendar::ICalendarProperty,
+    calcard::icalendar::ICalendarParameterName,
+>;
+
+/// Run a `calendar-query` REPORT against a calendar collection
+///
+/// Floating times are evaluated in UTC. A missing `Depth` header is treated
+/// as `1`, returning the objects of the addressed calendar.
+pub fn calendar_query(
+    tree: &ResourceTree,
+    resource: &ResourcePath,
+    depth: Depth,
+    query: &CalendarQuery,
+) -> crate::Result<MultiStatus> {
+    validate_filters(&query.filters)?;
+
+    if !tree.contains(&resource.path) {
+        return Err(DavError::not_found(
+            "calendar",
+            resource.href(&resource.path, true),
+        ));
+    }
+
+    let depth = match depth {
+        Depth::Zero => 0,
+        Depth::One | Depth::None => 1,
+        Depth::Infinity => usize::MAX,
+    };
+
+    let mut responses = Vec::new();
+    for (path, node) in tree.subtree(&resource.path, depth) {
+        if node.is_collection {
+            continue;
+        }
+
+        let Some((contents, ical)) = tree.body(node).and_then(|contents| {
+            std::str::from_utf8(contents)
+                .ok()
+                .and_then(|text| ICalendar::parse(text).ok())
+                .map(|ical| (contents, ical))
+        }) else {
+            continue;
+        };
+
+        if matches_filters(&ical, &query.filters) {
+            responses.push(Response::new_propstat(
+                resource.href(path, false),
+                calendar_propstats(path, node, contents, &query.properties).build(),
+            ));
+        }
+    }
+
+    Ok(MultiStatus::new(responses).with_namespace(Namespace::CalDav))
+}
+
+fn validate_filters(filters: &[CalendarFilter]) -> crate::Result<()> {
+    for filter in filters {
+        match filter {
+            Filter::Component {
+                op: FilterOp::TimeRange(range),
+                ..
+            } => {
+                if range.end <= range.start {
+                    return Err(DavError::validation_with_field(
+                        "Invalid time-range: end must be later than start",
+                        "time-range",
+                    ));
+                }
+            }
+            Filter::Component { .. } | Filter::AnyOf | Filter::AllOf => {}
+            Filter::Property { .. } | Filter::Parameter { .. } => {
+                return Err(DavErrorCondition::new(
+                    StatusCode::FORBIDDEN,
+                    CalCondition::SupportedFilter(vec![filter.clone()]),
+                )
+                .with_details("Only component filters are supported")
+                .into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn matches_filters(ical: &ICalendar, filters: &[CalendarFilter]) -> bool {
+    let mut is_all = true;
+    let mut matches_one = false;
+
+    for filter in filters {
+        let result = match filter {
+            Filter::AnyOf => {
+                is_all = false;
+                continue;
+            }
+            Filter::AllOf => {
+                is_all = true;
+                continue;
+            }
+            Filter::Component { comp, op } => matches_component(ical, comp, op),
+            Filter::Property { .. } | Filter::Parameter { .. } => false,
+        };
+
+        if result {
+            matches_one = true;
+        } else if is_all {
+            return false;
+        }
+    }
+
+    is_all || matches_one
+}
+
+fn matches_component(ical: &ICalendar, comp: &[ICalendarComponentType], op: &FilterOp) -> bool {
+    let Some(component_type) = comp.last() else {
+        return false;
+    };
+    let mut components = ical
+        .components
+        .iter()
+        .filter(|component| &component.component_type == component_type);
+
+    match op {
+        FilterOp::Exists => components.next().is_some(),
+        FilterOp::Undefined => components.next().is_none(),
+        FilterOp::TimeRange(range) => overlaps(ical, component_type, range),
+        FilterOp::TextMatch(_) => false,
+    }
+}
+
+/// Check whether any instance of a component type overlaps a time range
+fn overlaps(ical: &ICalendar, component_type: &ICalendarComponentType, range: &TimeRange) -> bool {
+    let is_todo = component_type == &ICalendarComponentType::VTodo;
+
+    ical.expand_dates(Tz::UTC, MAX_EXPANSIONS)
+        .events
+        .into_iter()
+        .filter(|event| {
+            ical.component_by_id(event.comp_id)
+                .is_some_and(|component| &component.component_type == component_type)
+        })
+        .any(|event| {
+            let start = event.start.timestamp();
+            let end = match event.end {
+                TimeOrDelta::Time(end) => end.timestamp(),
+                TimeOrDelta::Delta(delta) => start + delta.num_seconds(),
+            };
+
+            range.is_in_range(is_todo, start, end)
+        })
+}
+
+fn calendar_propstats(
+    path: &str,
+    node: &DavNode,
+    contents: &[u8],
+    request: &PropFind,
+) -> PropStatBuilder {
+    let mut builder = PropStatBuilder::default();
+    let properties = match request {
+        PropFind::Prop(properties) => properties.as_slice(),
+        PropFind::AllProp(_) | PropFind::PropName => &[],
+    };
+
+    for property in properties {
+        if let DavProperty::CalDav(CalDavProperty::CalendarData(_)) = property {
+            builder.insert_ok(DavPropertyValue::new(
+                property.clone(),
+                DavValue::CData(String::from_utf8_lossy(contents).into_owned()),
+            ));
+        } else {
+            insert_requested(&mut builder, path, node, property);
+        }
+    }
+
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webdav::{
+        parse_body,
+        tests::{T0, resource_path},
+    };
+    use dav_proto::schema::request::Report;
+    use groupware::DavResourceName;
+
+    fn event(uid: &str, start: &str, end: &str, rrule: Option<&str>) -> String {
+        let rrule = rrule.map(|rule| format!("RRULE:{rule}\r\n")).unwrap_or_default();
+        format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//A3Mailer//Test//EN\r\n\
+             BEGIN:VEVENT\r\nUID:{uid}\r\nDTSTAMP:20060101T000000Z\r\n\
+             DTSTART:{start}\r\nDTEND:{end}\r\n{rrule}SUMMARY:{uid}\r\n\
+             END:VEVENT\r\nEND:VCALENDAR\r\n"
+        )
+    }
+
+    fn calendar_tree() -> ResourceTree {
+        let mut tree = ResourceTree::new(T0);
+        tree.insert("work", DavNode::collection(T0)).unwrap();
+        // Inside the window
+        tree.insert_file(
+            "work/meeting.ics",
+            event("meeting", "20060103T100000Z", "20060103T110000Z", None),
+            Some("text/calendar"),
+            T0,
+        )
+        .unwrap();
+        // Before the window
+        tree.insert_file(
+            "work/past.ics",
+            event("past", "20060101T100000Z", "20060101T110000Z", None),
+            Some("text/calendar"),
+            T0,
+        )
+        .unwrap();
+        // After the window
+        tree.insert_file(
+            "work/future.ics",
+            event("future", "20060110T100000Z", "20060110T110000Z", None),
+            Some("text/calendar"),
+            T0,
+        )
+        .unwrap();
+        // Starts before the window, one instance falls inside it
+        tree.insert_file(
+            "work/weekly.ics",
+            event(
+                "weekly",
+                "20051229T090000Z",
+                "20051229T093000Z",
+                Some("FREQ=WEEKLY;COUNT=4"),
+            ),
+            Some("text/calendar"),
+            T0,
+        )
+        .unwrap();
+        // Recurs daily before the window but ends before it starts
+        tree.insert_file(
+            "work/ended.ics",
+            event(
+                "ended",
+                "20051220T090000Z",
+                "20051220T093000Z",
+                Some("FREQ=DAILY;UNTIL=20051225T000000Z"),
+            ),
+            Some("text/calendar"),
+            T0,
+        )
+        .unwrap();
+        tree
+    }
+
+    fn query(start: &str, end: &str) -> CalendarQuery {
+        let body = format!(
+            r#"<C:calendar-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+                 <D:prop><D:getetag/><C:calendar-data/></D:prop>
+                 <C:filter>
+                   <C:comp-filter name="VCALENDAR">
+                     <C:comp-filter name="VEVENT">
+                       <C:time-range start="{start}" end="{end}"/>
+                     </C:comp-filter>
+                   </C:comp-filter>
+                 </C:filter>
+               </C:calendar-query>"#
+        );
+        match parse_body::<Report>(body.as_bytes()).unwrap() {
+            Report::CalendarQuery(query) => query,
+            report => panic!("unexpected report {report:?}"),
+        }
+    }
+
+    fn calendar_path() -> ResourcePath {
+        ResourcePath {
+            resource: DavResourceName::Cal,
+            ..resource_path("work")
+        }
+    }
+
+    #[test]
+    fn test_calendar_query_time_range() {
+        let multistatus = calendar_query(
+            &calendar_tree(),
+            &calendar_path(),
+            Depth::One,
+            &query("20060102T000000Z", "20060106T000000Z"),
+        )
+        .unwrap();
+
+        let mut hrefs = multistatus
+            .response
+            .0
+            .iter()
+            .map(|response| response.href.0.as_str())
+            .collect::<Vec<_>>();
+        hrefs.sort_unstable();
+        assert_eq!(
+            hrefs,
+            vec!["/dav/cal/john/work/meeting.ics", "/dav/cal/john/work/weekly.ics"]
+        );
+
+        let xml = multistatus.to_string();
+        assert!(xml.contains("xmlns:A=\"urn:ietf:params:xml:ns:caldav\""));
+        assert!(xml.contains("UID:meeting"));
+        assert!(!xml.contains("UID:past"));
+    }
+
+    #[test]
+    fn test_calendar_query_no_match() {
+        let multistatus = calendar_query(
+            &calendar_tree(),
+            &calendar_path(),
+            Depth::One,
+            &query("20070101T000000Z", "20070201T000000Z"),
+        )
+        .unwrap();
+        assert!(multistatus.is_empty());
+    }
+
+    #[test]
+    fn test_calendar_query_invalid_range() {
+        let err = calendar_query(
+            &calendar_tree(),
+            &calendar_path(),
+            Depth::One,
+            &query("20060106T000000Z", "20060102T000000Z"),
+        )
+        .unwrap_err();
+
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert!(err.to_string().contains("time-range"));
+    }
+}
@@ -0,0 +1,223 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! CardDAV `addressbook-multiget` REPORT handling
+//!
+//! Address object resources are stored as vCard text in the blob store.
+
+use dav_proto::schema::{
+    Namespace,
+    property::{CardDavProperty, DavProperty, DavValue},
+    request::{DavPropertyValue, MultiGet, PropFind},
+    response::{MultiStatus, Response},
+};
+use hyper::StatusCode;
+
+use super::{
+    DavNode, ResourcePath, ResourceTree, normalize_path, propfind::insert_requested,
+    strip_origin,
+};
+use crate::PropStatBuilder;
+
+/// Run an `addressbook-multiget` REPORT
+///
+/// Every requested href gets its own response: existing contacts return
+/// their properties, unknown ones a `404` propstat and hrefs outside the
+/// account's address books a `400` propstat.
+pub fn addressbook_multiget(
+    tree: &ResourceTree,
+    resource: &ResourcePath,
+    multiget: &MultiGet,
+) -> MultiStatus {
+    let properties = match &multiget.properties {
+        PropFind::Prop(properties) => properties.as_slice(),
+        PropFind::AllProp(_) | PropFind::PropName => &[],
+    };
+
+    let responses = multiget
+        .hrefs
+        .iter()
+        .map(|href| {
+            let propstats = match resolve_href(resource, href) {
+                Some(path) => match tree.get(path) {
+                    Some(node) if !node.is_collection => match tree.body(node) {
+                        Some(contents) => contact_propstats(path, node, contents, properties),
+                        None => status_propstats(properties, StatusCode::INTERNAL_SERVER_ERROR),
+                    },
+                    _ => status_propstats(properties, StatusCode::NOT_FOUND),
+                },
+                None => status_propstats(properties, StatusCode::BAD_REQUEST),
+            };
+
+            Response::new_propstat(href.as_str(), propstats.build())
+        })
+        .collect();
+
+    MultiStatus::new(responses).with_namespace(Namespace::CardDav)
+}
+
+/// Map an href to a tree path, returning `None` for malformed hrefs
+pub(crate) fn resolve_href<'x>(resource: &ResourcePath, href: &'x str) -> Option<&'x str> {
+    let href = strip_origin(href)?;
+    let root = resource.root();
+    let path = normalize_path(href.strip_prefix(root.as_str())?.strip_prefix('/')?);
+
+    if path.is_empty()
+        || path
+            .split('/')
+            .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+        || percent_encoding::percent_decode_str(path).decode_utf8().is_err()
+    {
+        None
+    } else {
+        Some(path)
+    }
+}
+
+fn contact_propstats(
+    path: &str,
+    node: &DavNode,
+    contents: &[u8],
+    properties: &[DavProperty],
+) -> PropStatBuilder {
+    let mut builder = PropStatBuilder::default();
+
+    for property in properties {
+        if let DavProperty::CardDav(CardDavProperty::AddressData(_)) = property {
+            builder.insert_ok(DavPropertyValue::new(
+                property.clone(),
+                DavValue::CData(String::from_utf8_lossy(contents).into_owned()),
+            ));
+        } else {
+            insert_requested(&mut builder, path, node, property);
+        }
+    }
+
+    builder
+}
+
+fn status_propstats(properties: &[DavProperty], status: StatusCode) -> PropStatBuilder {
+    let mut builder = PropStatBuilder::default();
+    for property in properties {
+        builder.insert_with_status(DavPropertyValue::empty(property.clone()), status);
+    }
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webdav::{
+        parse_body,
+        tests::{T0, resource_path},
+    };
+    use dav_proto::schema::{
+        property::WebDavProperty,
+        request::Report,
+        response::{PropStat, ResponseType},
+    };
+    use groupware::DavResourceName;
+
+    fn contact(uid: &str, name: &str) -> String {
+        format!("BEGIN:VCARD\r\nVERSION:4.0\r\nUID:{uid}\r\nFN:{name}\r\nEND:VCARD\r\n")
+    }
+
+    fn card_tree() -> ResourceTree {
+        let mut tree = ResourceTree::new(T0);
+        tree.insert("default", DavNode::collection(T0)).unwrap();
+        for (path, uid, name) in [
+            ("default/jane.vcf", "jane", "Jane Doe"),
+            ("default/bob.vcf", "bob", "Bob Smith"),
+        ] {
+            tree.insert_file(path, contact(uid, name), Some("text/vcard"), T0)
+                .unwrap();
+        }
+        tree
+    }
+
+    fn multiget(hrefs: &[&str]) -> MultiGet {
+        let body = format!(
+            r#"<C:addressbook-multiget xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:carddav">
+                 <D:prop><D:getetag/><C:address-data/></D:prop>
+                 {}
+               </C:addressbook-multiget>"#,
+            hrefs
+                .iter()
+                .map(|href| format!("<D:href>{href}</D:href>"))
+                .collect::<String>()
+        );
+        match parse_body::<Report>(body.as_bytes()).unwrap() {
+            Report::AddressbookMultiGet(multiget) => multiget,
+            report => panic!("unexpected report {report:?}"),
+        }
+    }
+
+    fn propstats(response: &Response) -> &[PropStat] {
+        match &response.typ {
+            ResponseType::PropStat(propstats) => &propstats.0,
+            ResponseType::Status { .. } => &[],
+        }
+    }
+
+    #[test]
+    fn test_addressbook_multiget() {
+        let tree = card_tree();
+        let resource = ResourcePath {
+            resource: DavResourceName::Card,
+            ..resource_path("default")
+        };
+        let multistatus = addressbook_multiget(
+            &tree,
+            &resource,
+            &multiget(&[
+                "/dav/card/john/default/jane.vcf",
+                "/dav/card/john/default/missing.vcf",
+                "/dav/card/other/default/bob.vcf",
+            ]),
+        );
+        let responses = &multistatus.response.0;
+        assert_eq!(responses.len(), 3);
+
+        // Existing contact
+        assert_eq!(responses[0].href.0, "/dav/card/john/default/jane.vcf");
+        let found = propstats(&responses[0]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].status.0, StatusCode::OK);
+        assert!(found[0].prop.0.0.contains(&DavPropertyValue::new(
+            WebDavProperty::GetETag,
+            DavValue::String(tree.get("default/jane.vcf").unwrap().etag()),
+        )));
+        assert!(multistatus.to_string().contains("FN:Jane Doe"));
+
+        // Missing contact
+        let missing = propstats(&responses[1]);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].status.0, StatusCode::NOT_FOUND);
+        assert_eq!(missing[0].prop.0.0.len(), 2);
+
+        // Href outside the account
+        let malformed = propstats(&responses[2]);
+        assert_eq!(malformed.len(), 1);
+        assert_eq!(malformed[0].status.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_resolve_href() {
+        let resource = ResourcePath {
+            resource: DavResourceName::Card,
+            ..resource_path("")
+        };
+
+        assert_eq!(
+            resolve_href(&resource, "https://mail.example.org/dav/card/john/default/a.vcf"),
+            Some("default/a.vcf")
+        );
+        assert_eq!(resolve_href(&resource, "/dav/card/john/default/../a.vcf"), None);
+        assert_eq!(resolve_href(&resource, "/dav/card/john"), None);
+        assert_eq!(resolve_href(&resource, "/dav/card/johnny/a.vcf"), None);
+        assert_eq!(resolve_href(&resource, "/dav/card/john/%FF.vcf"), None);
+    }
+}
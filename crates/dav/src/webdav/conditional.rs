@@ -0,0 +1,190 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! HTTP conditional request evaluation (RFC 7232)
+//!
+//! Entity tags are the ones reported by PROPFIND's `getetag`, so a client
+//! can reuse a tag from a multistatus response in `If-Match`.
+
+use hyper::StatusCode;
+
+use super::DavNode;
+use crate::DavError;
+
+/// Entity tag list from an `If-Match` or `If-None-Match` header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityTags {
+    Any,
+    Tags(Vec<String>),
+}
+
+impl EntityTags {
+    fn parse(value: &str) -> Self {
+        if value.trim() == "*" {
+            EntityTags::Any
+        } else {
+            EntityTags::Tags(
+                value
+                    .split(',')
+                    .map(|tag| tag.trim().to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect(),
+            )
+        }
+    }
+
+    /// Strong comparison, used by `If-Match`
+    fn matches_strong(&self, etag: &str) -> bool {
+        match self {
+            EntityTags::Any => true,
+            EntityTags::Tags(tags) => tags.iter().any(|tag| !tag.starts_with("W/") && tag == etag),
+        }
+    }
+
+    /// Weak comparison, used by `If-None-Match`
+    fn matches_weak(&self, etag: &str) -> bool {
+        match self {
+            EntityTags::Any => true,
+            EntityTags::Tags(tags) => tags
+                .iter()
+                .any(|tag| tag.trim_start_matches("W/") == etag.trim_start_matches("W/")),
+        }
+    }
+}
+
+/// Conditional request headers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Preconditions {
+    pub if_match: Option<EntityTags>,
+    pub if_none_match: Option<EntityTags>,
+    pub if_unmodified_since: Option<i64>,
+}
+
+impl Preconditions {
+    /// Parse a request header, returning `false` when it is not a precondition
+    pub fn parse(&mut self, key: &str, value: &str) -> bool {
+        if key.eq_ignore_ascii_case("If-Match") {
+            self.if_match = Some(EntityTags::parse(value));
+        } else if key.eq_ignore_ascii_case("If-None-Match") {
+            self.if_none_match = Some(EntityTags::parse(value));
+        } else if key.eq_ignore_ascii_case("If-Unmodified-Since") {
+            // Invalid dates must be ignored (RFC 7232, section 3.4)
+            self.if_unmodified_since = chrono::DateTime::parse_from_rfc2822(value.trim())
+                .ok()
+                .map(|date| date.timestamp());
+        } else {
+            return false;
+        }
+
+        true
+    }
+
+    /// Evaluate the preconditions against the current state of a resource
+    ///
+    /// Fails with `412 Precondition Failed`, or with `304 Not Modified` when
+    /// `If-None-Match` matches on a safe method.
+    pub fn evaluate(&self, node: Option<&DavNode>, is_safe_method: bool) -> crate::Result<()> {
+        let etag = node.map(|node| node.etag());
+
+        if let Some(if_match) = &self.if_match {
+            if !etag.as_deref().is_some_and(|etag| if_match.matches_strong(etag)) {
+                return Err(DavError::Code(StatusCode::PRECONDITION_FAILED));
+            }
+        } else if let (Some(since), Some(node)) = (self.if_unmodified_since, node) {
+            if node.last_modified > since {
+                return Err(DavError::Code(StatusCode::PRECONDITION_FAILED));
+            }
+        }
+
+        if let Some(if_none_match) = &self.if_none_match {
+            if etag.as_deref().is_some_and(|etag| if_none_match.matches_weak(etag)) {
+                return Err(DavError::Code(if is_safe_method {
+                    StatusCode::NOT_MODIFIED
+                } else {
+                    StatusCode::PRECONDITION_FAILED
+                }));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webdav::tests::T0;
+
+    fn preconditions(headers: &[(&str, &str)]) -> Preconditions {
+        let mut preconditions = Preconditions::default();
+        for (key, value) in headers {
+            assert!(preconditions.parse(key, value));
+        }
+        preconditions
+    }
+
+    fn status(result: crate::Result<()>) -> Option<StatusCode> {
+        result.err().map(|err| err.status_code())
+    }
+
+    #[test]
+    fn test_if_match() {
+        let node = DavNode::file("hello", None, T0);
+        let etag = node.etag();
+
+        let matching = preconditions(&[("If-Match", etag.as_str())]);
+        assert_eq!(status(matching.evaluate(Some(&node), false)), None);
+
+        let stale = preconditions(&[("If-Match", "\"0000\"")]);
+        assert_eq!(
+            status(stale.evaluate(Some(&node), false)),
+            Some(StatusCode::PRECONDITION_FAILED)
+        );
+
+        let any = preconditions(&[("if-match", "*")]);
+        assert_eq!(status(any.evaluate(Some(&node), false)), None);
+        assert_eq!(
+            status(any.evaluate(None, false)),
+            Some(StatusCode::PRECONDITION_FAILED)
+        );
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let node = DavNode::file("hello", None, T0);
+        let weak = format!("W/{}", node.etag());
+        let if_none_match = preconditions(&[("If-None-Match", weak.as_str())]);
+
+        assert_eq!(
+            status(if_none_match.evaluate(Some(&node), true)),
+            Some(StatusCode::NOT_MODIFIED)
+        );
+        assert_eq!(
+            status(if_none_match.evaluate(Some(&node), false)),
+            Some(StatusCode::PRECONDITION_FAILED)
+        );
+
+        let create_only = preconditions(&[("If-None-Match", "*")]);
+        assert_eq!(status(create_only.evaluate(None, false)), None);
+    }
+
+    #[test]
+    fn test_if_unmodified_since() {
+        let node = DavNode::file("hello", None, T0);
+        // 2023-11-14T22:13:20Z
+        let before = preconditions(&[("If-Unmodified-Since", "Tue, 14 Nov 2023 22:13:19 GMT")]);
+        let after = preconditions(&[("If-Unmodified-Since", "Tue, 14 Nov 2023 22:13:20 GMT")]);
+
+        assert_eq!(
+            status(before.evaluate(Some(&node), false)),
+            Some(StatusCode::PRECONDITION_FAILED)
+        );
+        assert_eq!(status(after.evaluate(Some(&node), false)), None);
+
+        let invalid = preconditions(&[("If-Unmodified-Since", "yesterday")]);
+        assert_eq!(invalid.if_unmodified_since, None);
+    }
+}
@@ -0,0 +1,270 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! COPY and MOVE handling for WebDAV resources
+//!
+//! Locks are never carried over to the destination, and a MOVE drops the
+//! locks held on the source (RFC 4918, section 7.7).
+
+use dav_proto::{Depth, RequestHeaders};
+use hyper::StatusCode;
+
+use super::{
+    ResourcePath, ResourceTree, is_descendant_or_self, lock::check_lock, parent_path,
+    strip_origin,
+};
+use crate::{DavError, fix_percent_encoding};
+
+/// Copy or move a resource to the path named in the `Destination` header
+///
+/// Returns `201 Created` when the destination was unmapped and
+/// `204 No Content` when an existing resource was overwritten.
+pub fn copy_move(
+    tree: &mut ResourceTree,
+    resource: &ResourcePath,
+    principal: &str,
+    headers: &RequestHeaders<'_>,
+    is_move: bool,
+    now: i64,
+) -> crate::Result<StatusCode> {
+    let source_path = resource.path.as_str();
+    let source = tree
+        .get(source_path)
+        .ok_or_else(|| DavError::not_found("resource", resource.href(source_path, false)))?;
+    if source_path.is_empty() {
+        return Err(DavError::Code(StatusCode::FORBIDDEN));
+    }
+
+    let depth = if source.is_collection {
+        match headers.depth {
+            Depth::Zero if !is_move => 0,
+            Depth::Infinity | Depth::None => usize::MAX,
+            _ => {
+                return Err(DavError::validation_with_field(
+                    "Depth must be 0 or infinity for COPY and infinity for MOVE",
+                    "Depth",
+                ));
+            }
+        }
+    } else {
+        0
+    };
+
+    // Validate destination
+    let destination = destination_path(resource, headers)?;
+    if is_descendant_or_self(&destination, source_path)
+        || is_descendant_or_self(source_path, &destination)
+    {
+        return Err(DavError::Code(StatusCode::BAD_GATEWAY));
+    }
+
+    let overwrite = tree.contains(&destination);
+    if overwrite && headers.overwrite_fail {
+        return Err(DavError::Code(StatusCode::PRECONDITION_FAILED));
+    }
+    if !tree
+        .get(parent_path(&destination))
+        .is_some_and(|node| node.is_collection)
+    {
+        return Err(DavError::conflict(format!(
+            "Parent collection of '{destination}' does not exist"
+        )));
+    }
+
+    // Every modified path must be unlocked or have its lock token submitted
+    let mut modified = tree
+        .subtree(&destination, usize::MAX)
+        .map(|(path, _)| path.to_string())
+        .collect::<Vec<_>>();
+    if !overwrite {
+        modified.push(destination.clone());
+    }
+    if is_move {
+        modified.extend(
+            tree.subtree(source_path, usize::MAX)
+                .map(|(path, _)| path.to_string()),
+        );
+    }
+    for path in &modified {
+        check_lock(tree, resource, principal, path, headers, now)?;
+    }
+
+    // Parents sort before their children, so the nodes can be inserted in order
+    let nodes = tree
+        .subtree(source_path, depth)
+        .map(|(path, node)| {
+            let mut node = node.clone();
+            if !is_move {
+                node.created = now;
+                node.last_modified = now;
+                node.acl.clear();
+            }
+            (format!("{destination}{}", &path[source_path.len()..]), node)
+        })
+        .collect::<Vec<_>>();
+
+    tree.remove(&destination);
+    if is_move {
+        tree.remove(source_path);
+        tree.locks_mut().release(source_path);
+    }
+    for (path, node) in nodes {
+        tree.insert(&path, node)?;
+    }
+
+    Ok(if overwrite {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::CREATED
+    })
+}
+
+/// Resolve the `Destination` header to a path in the same account
+pub fn destination_path(resource: &ResourcePath, headers: &RequestHeaders<'_>) -> crate::Result<String> {
+    headers
+        .destination
+        .and_then(strip_origin)
+        .and_then(|uri| ResourcePath::parse(resource.resource, &fix_percent_encoding(uri)))
+        .filter(|destination| {
+            destination.account == resource.account && !destination.path.is_empty()
+        })
+        .map(|destination| destination.path)
+        .ok_or(DavError::Code(StatusCode::BAD_GATEWAY))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webdav::tests::{T0, resource_path, sample_tree};
+
+    fn headers<'x>(destination: &'x str, extra: &[(&str, &'x str)]) -> RequestHeaders<'x> {
+        let mut headers = RequestHeaders::new("/dav/file/john/");
+        headers.parse("Destination", destination);
+        for &(key, value) in extra {
+            headers.parse(key, value);
+        }
+        headers
+    }
+
+    #[test]
+    fn test_copy_create() {
+        let mut tree = sample_tree();
+
+        let status = copy_move(
+            &mut tree,
+            &resource_path("docs/a.txt"),
+            "john",
+            &headers("https://mail.example.org/dav/file/john/docs/a copy.txt", &[]),
+            false,
+            T0 + 100,
+        )
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(tree.contents("docs/a.txt"), Some(&b"hello"[..]));
+        let copy = tree.get("docs/a%20copy.txt").unwrap();
+        assert_eq!(tree.body(copy), Some(&b"hello"[..]));
+        assert_eq!(copy.created, T0 + 100);
+
+        // Depth 0 copies the collection without its members
+        let status = copy_move(
+            &mut tree,
+            &resource_path("docs"),
+            "john",
+            &headers("/dav/file/john/archive/", &[("Depth", "0")]),
+            false,
+            T0 + 100,
+        )
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(tree.get("archive").unwrap().is_collection);
+        assert_eq!(tree.subtree("archive", usize::MAX).count(), 1);
+
+        // Depth infinity copies the whole subtree
+        copy_move(
+            &mut tree,
+            &resource_path("docs"),
+            "john",
+            &headers("/dav/file/john/backup", &[]),
+            false,
+            T0 + 100,
+        )
+        .unwrap();
+        assert_eq!(tree.contents("backup/sub/b.txt"), Some(&b"world!"[..]));
+    }
+
+    #[test]
+    fn test_move_overwrite() {
+        let mut tree = sample_tree();
+
+        let status = copy_move(
+            &mut tree,
+            &resource_path("docs/a.txt"),
+            "john",
+            &headers("/dav/file/john/readme.md", &[("Overwrite", "T")]),
+            true,
+            T0 + 100,
+        )
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(!tree.contains("docs/a.txt"));
+        let moved = tree.get("readme.md").unwrap();
+        assert_eq!(tree.body(moved), Some(&b"hello"[..]));
+        assert_eq!(moved.last_modified, T0 + 10);
+
+        let status = copy_move(
+            &mut tree,
+            &resource_path("docs"),
+            "john",
+            &headers("/dav/file/john/archive", &[]),
+            true,
+            T0 + 100,
+        )
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(!tree.contains("docs"));
+        assert!(tree.contains("archive/sub/b.txt"));
+    }
+
+    #[test]
+    fn test_copy_no_overwrite() {
+        let mut tree = sample_tree();
+
+        let err = copy_move(
+            &mut tree,
+            &resource_path("docs/a.txt"),
+            "john",
+            &headers("/dav/file/john/readme.md", &[("Overwrite", "F")]),
+            false,
+            T0 + 100,
+        )
+        .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(tree.contents("readme.md"), Some(&b"# A3Mailer"[..]));
+
+        let err = copy_move(
+            &mut tree,
+            &resource_path("docs/a.txt"),
+            "john",
+            &headers("/dav/file/john/missing/a.txt", &[]),
+            false,
+            T0 + 100,
+        )
+        .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::CONFLICT);
+
+        let err = copy_move(
+            &mut tree,
+            &resource_path("docs"),
+            "john",
+            &headers("/dav/file/john/docs/sub/docs", &[]),
+            true,
+            T0 + 100,
+        )
+        .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::BAD_GATEWAY);
+        assert!(tree.contains("docs/a.txt"));
+    }
+}
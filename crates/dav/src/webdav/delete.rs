@@ -0,0 +1,130 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! DELETE handling for WebDAV resources
+//!
+//! Deleting a collection removes all its members (RFC 4918, section 9.6.1),
+//! so every locked member needs its lock token submitted.
+
+use dav_proto::RequestHeaders;
+use hyper::StatusCode;
+
+use super::{ResourcePath, ResourceTree, conditional::Preconditions, lock::check_lock};
+use crate::DavError;
+
+/// Delete a resource and, for collections, all of its members
+pub fn delete(
+    tree: &mut ResourceTree,
+    resource: &ResourcePath,
+    principal: &str,
+    headers: &RequestHeaders<'_>,
+    preconditions: &Preconditions,
+    now: i64,
+) -> crate::Result<StatusCode> {
+    let path = resource.path.as_str();
+    let node = tree
+        .get(path)
+        .ok_or_else(|| DavError::not_found("resource", resource.href(path, false)))?;
+    if path.is_empty() {
+        return Err(DavError::Code(StatusCode::FORBIDDEN));
+    }
+
+    preconditions.evaluate(Some(node), false)?;
+    for (member, _) in tree.subtree(path, usize::MAX) {
+        check_lock(tree, resource, principal, member, headers, now)?;
+    }
+
+    tree.remove(path);
+    tree.locks_mut().release(path);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webdav::{
+        lock::lock,
+        parse_body,
+        tests::{T0, resource_path, sample_tree},
+    };
+
+    #[test]
+    fn test_delete_collection() {
+        let mut tree = sample_tree();
+        let headers = RequestHeaders::new("/dav/file/john/docs");
+
+        let status = delete(
+            &mut tree,
+            &resource_path("docs"),
+            "john",
+            &headers,
+            &Preconditions::default(),
+            T0,
+        )
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(!tree.contains("docs"));
+        assert!(!tree.contains("docs/sub/b.txt"));
+        assert!(tree.contains("readme.md"));
+
+        let err = delete(
+            &mut tree,
+            &resource_path("docs"),
+            "john",
+            &headers,
+            &Preconditions::default(),
+            T0,
+        )
+        .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_delete_locked_member() {
+        let mut tree = sample_tree();
+        let lock_info = parse_body(
+            br#"<D:lockinfo xmlns:D="DAV:"><D:lockscope><D:exclusive/></D:lockscope><D:locktype><D:write/></D:locktype></D:lockinfo>"#,
+        )
+        .unwrap();
+        let token = lock(
+            &mut tree,
+            &resource_path("docs/sub/b.txt"),
+            "john",
+            &RequestHeaders::new("/dav/file/john/docs/sub/b.txt"),
+            Some(lock_info),
+            T0,
+        )
+        .unwrap()
+        .token;
+
+        let err = delete(
+            &mut tree,
+            &resource_path("docs"),
+            "john",
+            &RequestHeaders::new("/dav/file/john/docs"),
+            &Preconditions::default(),
+            T0,
+        )
+        .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::LOCKED);
+        assert!(tree.contains("docs/sub/b.txt"));
+
+        let if_header = format!("(<{token}>)");
+        let mut headers = RequestHeaders::new("/dav/file/john/docs");
+        headers.parse("If", &if_header);
+        delete(
+            &mut tree,
+            &resource_path("docs"),
+            "john",
+            &headers,
+            &Preconditions::default(),
+            T0,
+        )
+        .unwrap();
+        assert!(tree.locks().get(&token, T0).is_none());
+    }
+}
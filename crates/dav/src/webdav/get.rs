@@ -0,0 +1,99 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! GET and HEAD handling for WebDAV file resources
+
+use dav_proto::schema::property::Rfc1123DateTime;
+use http_proto::HttpResponse;
+use hyper::StatusCode;
+
+use super::{ResourcePath, ResourceTree, conditional::Preconditions};
+use crate::DavError;
+
+/// Fetch a file, honoring conditional request headers
+pub fn get(
+    tree: &ResourceTree,
+    resource: &ResourcePath,
+    preconditions: &Preconditions,
+    is_head: bool,
+) -> crate::Result<HttpResponse> {
+    let node = tree
+        .get(&resource.path)
+        .ok_or_else(|| DavError::not_found("file", resource.href(&resource.path, false)))?;
+
+    if node.is_collection {
+        return Err(DavError::Code(StatusCode::METHOD_NOT_ALLOWED));
+    }
+
+    match preconditions.evaluate(Some(node), true) {
+        Ok(()) => {}
+        Err(DavError::Code(code)) if code == StatusCode::NOT_MODIFIED => {
+            return Ok(HttpResponse::new(StatusCode::NOT_MODIFIED).with_etag(node.etag()));
+        }
+        Err(err) => return Err(err),
+    }
+
+    let response = HttpResponse::new(StatusCode::OK)
+        .with_content_type(
+            node.content_type
+                .clone()
+                .unwrap_or_else(|| "application/octet-stream".to_string()),
+        )
+        .with_etag(node.etag())
+        .with_last_modified(Rfc1123DateTime::new(node.last_modified).to_string());
+
+    Ok(if is_head {
+        response.with_content_length(node.size as usize)
+    } else {
+        let body = tree
+            .body(node)
+            .ok_or(DavError::Code(StatusCode::INTERNAL_SERVER_ERROR))?;
+        response.with_binary_body(body.to_vec())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webdav::tests::{resource_path, sample_tree};
+
+    #[test]
+    fn test_get_not_modified() {
+        let tree = sample_tree();
+        let etag = tree.get("docs/a.txt").unwrap().etag();
+
+        let mut preconditions = Preconditions::default();
+        preconditions.parse("If-None-Match", &etag);
+        let response = get(&tree, &resource_path("docs/a.txt"), &preconditions, false).unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let mut preconditions = Preconditions::default();
+        preconditions.parse("If-None-Match", "\"other\"");
+        let response = get(&tree, &resource_path("docs/a.txt"), &preconditions, false).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().unwrap().get("etag").unwrap().to_str().unwrap(),
+            etag
+        );
+    }
+
+    #[test]
+    fn test_get_errors() {
+        let tree = sample_tree();
+        let preconditions = Preconditions::default();
+
+        let err = get(&tree, &resource_path("docs"), &preconditions, false).unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::METHOD_NOT_ALLOWED);
+
+        let err = get(&tree, &resource_path("missing"), &preconditions, false).unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+
+        let mut stale = Preconditions::default();
+        stale.parse("If-Match", "\"stale\"");
+        let err = get(&tree, &resource_path("docs/a.txt"), &stale, false).unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::PRECONDITION_FAILED);
+    }
+}
@@ -0,0 +1,535 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! LOCK and UNLOCK handling for WebDAV file resources
+//!
+//! Only exclusive write locks are supported. Lock timeouts are capped at
+//! [`MAX_LOCK_TIMEOUT`] regardless of what the client requests. A lock belongs
+//! to the principal that created it: only that principal can refresh or
+//! release it, or submit its token to modify a locked resource.
+
+use dav_proto::{
+    Condition as IfCondition, Depth, RequestHeaders, Timeout,
+    schema::{
+        property::{ActiveLock, LockScope, LockType},
+        request::{DeadProperty, LockInfo},
+        response::{BaseCondition, Href, List},
+    },
+};
+use hyper::StatusCode;
+use std::collections::BTreeMap;
+use store::rand;
+
+use super::{ResourcePath, ResourceTree, is_descendant_or_self};
+use crate::{DavError, DavErrorCondition};
+
+/// Timeout applied when the client does not send a `Timeout` header
+pub const DEFAULT_LOCK_TIMEOUT: u64 = 600;
+
+/// Maximum lock lifetime granted by the server
+pub const MAX_LOCK_TIMEOUT: u64 = 3600;
+
+/// An active exclusive write lock
+#[derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DavLock {
+    pub token: String,
+    /// Name of the principal holding the lock
+    pub principal: String,
+    pub path: String,
+    pub depth_infinity: bool,
+    pub owner: Option<DeadProperty>,
+    pub timeout: u64,
+    pub expires: i64,
+}
+
+impl DavLock {
+    /// Check whether the lock applies to a path
+    pub fn covers(&self, path: &str) -> bool {
+        self.path == path || (self.depth_infinity && is_descendant(path, &self.path))
+    }
+
+    /// Build the `activelock` element describing this lock
+    pub fn to_active_lock(&self, resource: &ResourcePath, is_collection: bool) -> ActiveLock {
+        ActiveLock::new(resource.href(&self.path, is_collection), LockScope::Exclusive)
+            .with_depth(if self.depth_infinity {
+                Depth::Infinity
+            } else {
+                Depth::Zero
+            })
+            .with_timeout(self.timeout)
+            .with_owner_opt(self.owner.clone())
+            .with_lock_token(&self.token)
+    }
+}
+
+/// Active locks of a resource tree, keyed by lock token
+#[derive(
+    rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Clone, Default, PartialEq, Eq,
+)]
+pub struct LockTable {
+    locks: BTreeMap<String, DavLock>,
+}
+
+impl LockTable {
+    /// Get an active lock by token
+    pub fn get(&self, token: &str, now: i64) -> Option<&DavLock> {
+        self.locks.get(token).filter(|lock| lock.expires > now)
+    }
+
+    /// Active locks that would conflict with a new lock on `path`
+    pub fn conflicts(&self, path: &str, depth_infinity: bool, now: i64) -> Vec<&DavLock> {
+        self.locks
+            .values()
+            .filter(|lock| {
+                lock.expires > now
+                    && (lock.covers(path) || (depth_infinity && is_descendant(&lock.path, path)))
+            })
+            .collect()
+    }
+
+    /// Drop the locks held on a path or any of its descendants
+    pub fn release(&mut self, path: &str) {
+        self.locks
+            .retain(|_, lock| !is_descendant_or_self(&lock.path, path));
+    }
+
+    /// Drop locks whose timeout has elapsed
+    pub fn remove_expired(&mut self, now: i64) {
+        self.locks.retain(|_, lock| lock.expires > now);
+    }
+
+    fn insert(&mut self, lock: DavLock) {
+        self.locks.insert(lock.token.clone(), lock);
+    }
+
+    fn remove(&mut self, token: &str) -> Option<DavLock> {
+        self.locks.remove(token)
+    }
+}
+
+/// Result of a successful LOCK request
+#[derive(Debug, Clone)]
+pub struct LockResponse {
+    pub status: StatusCode,
+    pub token: String,
+    pub active_lock: ActiveLock,
+}
+
+/// Create or refresh a lock
+///
+/// A request without a body refreshes the lock named in the `If` header.
+/// Locking an unmapped URL creates an empty resource and returns `201`.
+pub fn lock(
+    tree: &mut ResourceTree,
+    resource: &ResourcePath,
+    principal: &str,
+    headers: &RequestHeaders<'_>,
+    request: Option<LockInfo>,
+    now: i64,
+) -> crate::Result<LockResponse> {
+    tree.locks_mut().remove_expired(now);
+    let timeout = lock_timeout(headers.timeout);
+
+    let Some(request) = request else {
+        return refresh(tree, resource, principal, headers, timeout, now);
+    };
+
+    if request.lock_scope != LockScope::Exclusive || request.lock_type != LockType::Write {
+        return Err(DavError::validation_with_field(
+            "Only exclusive write locks are supported",
+            "lockinfo",
+        ));
+    }
+
+    let depth_infinity = match headers.depth {
+        Depth::Zero => false,
+        Depth::Infinity | Depth::None => true,
+        Depth::One => {
+            return Err(DavError::validation_with_field(
+                "LOCK requires a Depth of 0 or infinity",
+                "Depth",
+            ));
+        }
+    };
+
+    let path = resource.path.as_str();
+    let conflicts = tree.locks().conflicts(path, depth_infinity, now);
+    if !conflicts.is_empty() {
+        let hrefs = conflicts
+            .iter()
+            .map(|lock| {
+                let is_collection = tree.get(&lock.path).is_some_and(|node| node.is_collection);
+                Href(resource.href(&lock.path, is_collection))
+            })
+            .collect();
+        return Err(DavErrorCondition::new(
+            StatusCode::LOCKED,
+            BaseCondition::NoConflictingLock(List(hrefs)),
+        )
+        .with_details(format!("Resource '{path}' is already locked"))
+        .into());
+    }
+
+    let status = if tree.contains(path) {
+        StatusCode::OK
+    } else {
+        tree.insert_file(path, Vec::new(), None, now)?;
+        StatusCode::CREATED
+    };
+
+    let lock = DavLock {
+        token: generate_lock_token(),
+        principal: principal.to_string(),
+        path: path.to_string(),
+        depth_infinity,
+        owner: request.owner,
+        timeout,
+        expires: now + timeout as i64,
+    };
+    let response = lock_response(tree, resource, &lock, status);
+    tree.locks_mut().insert(lock);
+
+    Ok(response)
+}
+
+/// Remove the lock named in the `Lock-Token` header
+///
+/// Fails with `403 Forbidden` when the lock is held by another principal.
+pub fn unlock(
+    tree: &mut ResourceTree,
+    resource: &ResourcePath,
+    principal: &str,
+    headers: &RequestHeaders<'_>,
+    now: i64,
+) -> crate::Result<()> {
+    let token = headers
+        .lock_token
+        .ok_or_else(|| DavError::validation_with_field("Missing Lock-Token header", "Lock-Token"))?;
+
+    match tree.locks().get(token, now) {
+        Some(lock) if lock.covers(&resource.path) => {
+            if lock.principal != principal {
+                return Err(DavError::Code(StatusCode::FORBIDDEN));
+            }
+            tree.locks_mut().remove(token);
+            Ok(())
+        }
+        _ => Err(DavErrorCondition::new(
+            StatusCode::CONFLICT,
+            BaseCondition::LockTokenMatchesRequestUri,
+        )
+        .with_details(format!("Lock token '{token}' does not apply to this resource"))
+        .into()),
+    }
+}
+
+/// Ensure the request submitted the token of every lock covering `path`
+///
+/// Fails with `423 Locked` and the `lock-token-submitted` condition when a
+/// lock applies and its token is missing from the `If` header, or the lock
+/// is held by a principal other than `principal`.
+pub fn check_lock(
+    tree: &ResourceTree,
+    resource: &ResourcePath,
+    principal: &str,
+    path: &str,
+    headers: &RequestHeaders<'_>,
+    now: i64,
+) -> crate::Result<()> {
+    let submitted = submitted_tokens(headers).collect::<Vec<_>>();
+    let missing = tree
+        .locks()
+        .conflicts(path, false, now)
+        .into_iter()
+        .filter(|lock| lock.principal != principal || !submitted.contains(&lock.token.as_str()))
+        .map(|lock| {
+            let is_collection = tree.get(&lock.path).is_some_and(|node| node.is_collection);
+            Href(resource.href(&lock.path, is_collection))
+        })
+        .collect::<Vec<_>>();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(DavErrorCondition::new(
+            StatusCode::LOCKED,
+            BaseCondition::LockTokenSubmitted(List(missing)),
+        )
+        .into())
+    }
+}
+
+fn submitted_tokens<'x>(headers: &'x RequestHeaders<'_>) -> impl Iterator<Item = &'x str> {
+    headers
+        .if_
+        .iter()
+        .flat_map(|if_| if_.list.iter())
+        .filter_map(|condition| match condition {
+            IfCondition::StateToken {
+                is_not: false,
+                token,
+            } => Some(*token),
+            _ => None,
+        })
+}
+
+fn refresh(
+    tree: &mut ResourceTree,
+    resource: &ResourcePath,
+    principal: &str,
+    headers: &RequestHeaders<'_>,
+    timeout: u64,
+    now: i64,
+) -> crate::Result<LockResponse> {
+    let token = submitted_tokens(headers)
+        .next()
+        .ok_or_else(|| {
+            DavError::validation_with_field("Lock refresh requires a lock token", "If")
+        })?;
+
+    let lock = match tree.locks().get(token, now) {
+        Some(lock) if lock.covers(&resource.path) && lock.principal != principal => {
+            return Err(DavError::Code(StatusCode::FORBIDDEN));
+        }
+        Some(lock) if lock.covers(&resource.path) => DavLock {
+            timeout,
+            expires: now + timeout as i64,
+            ..lock.clone()
+        },
+        _ => {
+            return Err(DavErrorCondition::new(
+                StatusCode::PRECONDITION_FAILED,
+                BaseCondition::LockTokenMatchesRequestUri,
+            )
+            .into());
+        }
+    };
+
+    let response = lock_response(tree, resource, &lock, StatusCode::OK);
+    tree.locks_mut().insert(lock);
+
+    Ok(response)
+}
+
+fn lock_response(
+    tree: &ResourceTree,
+    resource: &ResourcePath,
+    lock: &DavLock,
+    status: StatusCode,
+) -> LockResponse {
+    let is_collection = tree.get(&lock.path).is_some_and(|node| node.is_collection);
+    LockResponse {
+        status,
+        token: lock.token.clone(),
+        active_lock: lock.to_active_lock(resource, is_collection),
+    }
+}
+
+fn lock_timeout(timeout: Timeout) -> u64 {
+    match timeout {
+        Timeout::Second(seconds) => seconds.clamp(1, MAX_LOCK_TIMEOUT),
+        Timeout::Infinite => MAX_LOCK_TIMEOUT,
+        Timeout::None => DEFAULT_LOCK_TIMEOUT,
+    }
+}
+
+fn is_descendant(path: &str, ancestor: &str) -> bool {
+    path != ancestor && (ancestor.is_empty() || is_descendant_or_self(path, ancestor))
+}
+
+fn generate_lock_token() -> String {
+    let id = rand::random::<u128>().to_be_bytes();
+    format!(
+        "opaquelocktoken:{:02x}{:02x}{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+        id[0], id[1], id[2], id[3], id[4], id[5], id[6], id[7],
+        id[8], id[9], id[10], id[11], id[12], id[13], id[14], id[15]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webdav::{
+        parse_body,
+        tests::{T0, resource_path, sample_tree},
+    };
+    use dav_proto::schema::response::Condition;
+
+    const LOCK_BODY: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
+        <D:lockinfo xmlns:D="DAV:">
+          <D:lockscope><D:exclusive/></D:lockscope>
+          <D:locktype><D:write/></D:locktype>
+          <D:owner><D:href>mailto:john@example.org</D:href></D:owner>
+        </D:lockinfo>"#;
+
+    fn lock_info() -> Option<LockInfo> {
+        Some(parse_body(LOCK_BODY.as_bytes()).unwrap())
+    }
+
+    fn headers(uri: &str) -> RequestHeaders<'_> {
+        RequestHeaders::new(uri)
+    }
+
+    #[test]
+    fn test_lock_acquire() {
+        let mut tree = sample_tree();
+        let resource = resource_path("docs/a.txt");
+        let mut headers = headers("/dav/file/john/docs/a.txt");
+        headers.parse("Timeout", "Second-86400");
+
+        let response = lock(&mut tree, &resource, "john", &headers, lock_info(), T0).unwrap();
+        assert_eq!(response.status, StatusCode::OK);
+        assert!(response.token.starts_with("opaquelocktoken:"));
+        assert_eq!(response.active_lock.timeout, Timeout::Second(MAX_LOCK_TIMEOUT));
+        assert_eq!(
+            response.active_lock.lock_root,
+            Href("/dav/file/john/docs/a.txt".to_string())
+        );
+
+        let lock = tree.locks().get(&response.token, T0).unwrap();
+        assert!(lock.covers("docs/a.txt"));
+        assert!(tree.locks().get(&response.token, T0 + MAX_LOCK_TIMEOUT as i64).is_none());
+    }
+
+    #[test]
+    fn test_lock_unmapped_url_creates_resource() {
+        let mut tree = sample_tree();
+        let resource = resource_path("docs/new.txt");
+        let response = lock(
+            &mut tree,
+            &resource,
+            "john",
+            &headers("/dav/file/john/docs/new.txt"),
+            lock_info(),
+            T0,
+        )
+        .unwrap();
+
+        assert_eq!(response.status, StatusCode::CREATED);
+        assert!(tree.contains("docs/new.txt"));
+    }
+
+    #[test]
+    fn test_double_lock_conflict() {
+        let mut tree = sample_tree();
+        let headers = headers("/dav/file/john/docs");
+        lock(&mut tree, &resource_path("docs"), "john", &headers, lock_info(), T0).unwrap();
+
+        for path in ["docs", "docs/sub/b.txt"] {
+            let err = lock(&mut tree, &resource_path(path), "john", &headers, lock_info(), T0 + 1)
+                .unwrap_err();
+            assert_eq!(err.status_code(), StatusCode::LOCKED);
+            match err {
+                DavError::Condition(cond) => assert_eq!(
+                    cond.condition,
+                    Condition::Base(BaseCondition::NoConflictingLock(List(vec![Href(
+                        "/dav/file/john/docs/".to_string()
+                    )])))
+                ),
+                err => panic!("unexpected error {err:?}"),
+            }
+        }
+
+        // Expired locks no longer conflict
+        assert!(lock(
+            &mut tree,
+            &resource_path("docs"),
+            "john",
+            &headers,
+            lock_info(),
+            T0 + DEFAULT_LOCK_TIMEOUT as i64
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_unlock() {
+        let mut tree = sample_tree();
+        let resource = resource_path("readme.md");
+        let response = lock(
+            &mut tree,
+            &resource,
+            "john",
+            &headers("/dav/file/john/readme.md"),
+            lock_info(),
+            T0,
+        )
+        .unwrap();
+
+        let mut wrong = headers("/dav/file/john/readme.md");
+        wrong.parse("Lock-Token", "<opaquelocktoken:00000000-0000-0000-0000-000000000000>");
+        let err = unlock(&mut tree, &resource, "john", &wrong, T0).unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::CONFLICT);
+        assert!(tree.locks().get(&response.token, T0).is_some());
+
+        let err = unlock(&mut tree, &resource, "john", &headers("/dav/file/john/readme.md"), T0)
+            .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+
+        let lock_token = format!("<{}>", response.token);
+        let mut right = headers("/dav/file/john/readme.md");
+        right.parse("Lock-Token", &lock_token);
+        unlock(&mut tree, &resource, "john", &right, T0).unwrap();
+        assert!(tree.locks().get(&response.token, T0).is_none());
+    }
+
+    #[test]
+    fn test_lock_refresh() {
+        let mut tree = sample_tree();
+        let resource = resource_path("readme.md");
+        let response = lock(
+            &mut tree,
+            &resource,
+            "john",
+            &headers("/dav/file/john/readme.md"),
+            lock_info(),
+            T0,
+        )
+        .unwrap();
+
+        let if_header = format!("(<{}>)", response.token);
+        let mut refresh = headers("/dav/file/john/readme.md");
+        refresh.parse("If", &if_header);
+        refresh.parse("Timeout", "Second-120");
+        let refreshed = lock(&mut tree, &resource, "john", &refresh, None, T0 + 500).unwrap();
+
+        assert_eq!(refreshed.token, response.token);
+        assert_eq!(tree.locks().get(&response.token, T0 + 600).unwrap().expires, T0 + 620);
+    }
+
+    #[test]
+    fn test_lock_belongs_to_principal() {
+        let mut tree = sample_tree();
+        let resource = resource_path("readme.md");
+        let response = lock(
+            &mut tree,
+            &resource,
+            "john",
+            &headers("/dav/file/john/readme.md"),
+            lock_info(),
+            T0,
+        )
+        .unwrap();
+
+        // Knowing the token is not enough to release the lock of another principal
+        let lock_token = format!("<{}>", response.token);
+        let mut release = headers("/dav/file/john/readme.md");
+        release.parse("Lock-Token", &lock_token);
+        let err = unlock(&mut tree, &resource, "jane", &release, T0).unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+
+        // ... or to modify the locked resource
+        let if_header = format!("(<{}>)", response.token);
+        let mut submit = headers("/dav/file/john/readme.md");
+        submit.parse("If", &if_header);
+        assert!(check_lock(&tree, &resource, "john", "readme.md", &submit, T0).is_ok());
+        let err = check_lock(&tree, &resource, "jane", "readme.md", &submit, T0).unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::LOCKED);
+
+        let err = lock(&mut tree, &resource, "jane", &submit, None, T0).unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+    }
+}
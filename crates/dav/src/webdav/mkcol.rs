@@ -0,0 +1,74 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! MKCOL handling for WebDAV resources
+//!
+//! Request bodies, including extended MKCOL (RFC 5689), are not supported:
+//! a request announcing one with a `Content-Type` is refused.
+
+use dav_proto::RequestHeaders;
+use hyper::StatusCode;
+
+use super::{DavNode, ResourcePath, ResourceTree, lock::check_lock};
+use crate::DavError;
+
+/// Create a collection
+///
+/// Fails with `405 Method Not Allowed` when the URL is already mapped and
+/// with `409 Conflict` when the parent collection does not exist.
+pub fn mkcol(
+    tree: &mut ResourceTree,
+    resource: &ResourcePath,
+    principal: &str,
+    headers: &RequestHeaders<'_>,
+    now: i64,
+) -> crate::Result<StatusCode> {
+    if headers.content_type.is_some() {
+        return Err(DavError::Code(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+    }
+
+    let path = resource.path.as_str();
+    if tree.contains(path) {
+        return Err(DavError::Code(StatusCode::METHOD_NOT_ALLOWED));
+    }
+
+    check_lock(tree, resource, principal, path, headers, now)?;
+    tree.insert(path, DavNode::collection(now))?;
+
+    Ok(StatusCode::CREATED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webdav::tests::{T0, resource_path, sample_tree};
+
+    #[test]
+    fn test_mkcol() {
+        let mut tree = sample_tree();
+        let headers = RequestHeaders::new("/dav/file/john/docs/new");
+        let mut create = |path, headers: &RequestHeaders<'_>| {
+            mkcol(&mut tree, &resource_path(path), "john", headers, T0 + 5)
+                .map_err(|err| err.status_code())
+        };
+
+        assert_eq!(create("docs/new", &headers), Ok(StatusCode::CREATED));
+        assert_eq!(
+            create("docs/new", &headers),
+            Err(StatusCode::METHOD_NOT_ALLOWED)
+        );
+        assert_eq!(create("missing/new", &headers), Err(StatusCode::CONFLICT));
+
+        let mut with_body = RequestHeaders::new("/dav/file/john/docs/other");
+        with_body.parse("Content-Type", "application/xml");
+        assert_eq!(
+            create("docs/other", &with_body),
+            Err(StatusCode::UNSUPPORTED_MEDIA_TYPE)
+        );
+        assert!(tree.get("docs/new").unwrap().is_collection);
+        assert!(!tree.contains("docs/other"));
+    }
+}
@@ -0,0 +1,632 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! WebDAV resource handling
+//!
+//! This module implements the `file`, `cal` and `card` DAV resource types on
+//! top of a per-account resource tree, built for each request from the
+//! resources stored in the data store (see [`storage`]). Each method handler
+//! works on the tree and returns either a response or a [`DavError`], which
+//! keeps the protocol logic independent from request I/O and easy to test.
+//!
+//! Resource bodies live in the blob store. The tree only carries the bodies
+//! a request needs, loaded before its handler runs or written by it.
+
+pub mod acl;
+pub mod conditional;
+pub mod delete;
+pub mod get;
+pub mod lock;
+pub mod calendar;
+pub mod card;
+pub mod copy_move;
+pub mod mkcol;
+pub mod propfind;
+pub mod storage;
+pub mod update;
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use common::{Server, auth::AccessToken};
+use dav_proto::{
+    RequestHeaders,
+    parser::{DavParser, tokenizer::Tokenizer},
+    schema::{
+        property::{Privilege, WebDavProperty},
+        request::{DavPropertyValue, Report},
+        response::PropResponse,
+    },
+};
+use groupware::DavResourceName;
+use http_proto::{HttpRequest, HttpResponse, HttpSessionData, request::fetch_body};
+use hyper::StatusCode;
+use jmap_proto::types::{acl::Acl, collection::Collection};
+use store::{ahash::AHashMap, xxhash_rust::xxh3::Xxh3};
+use utils::BlobHash;
+
+use crate::{DavError, DavMethod};
+use acl::PrincipalGrant;
+use conditional::Preconditions;
+use lock::LockTable;
+use storage::{StoredTree, account_id};
+
+/// Maximum accepted size of a DAV request body
+pub const MAX_REQUEST_BODY: usize = 10 * 1024 * 1024;
+
+/// Attempts made to write changes back when other requests keep modifying
+/// the same resources
+const MAX_WRITE_ATTEMPTS: usize = 5;
+
+/// A single node in a resource tree
+///
+/// File nodes reference their body in the blob store by hash.
+#[derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DavNode {
+    pub is_collection: bool,
+    pub blob_hash: Option<BlobHash>,
+    pub size: u32,
+    pub content_type: Option<String>,
+    pub created: i64,
+    pub last_modified: i64,
+    pub acl: Vec<PrincipalGrant>,
+}
+
+impl DavNode {
+    /// Create new collection node
+    pub fn collection(timestamp: i64) -> Self {
+        Self {
+            is_collection: true,
+            blob_hash: None,
+            size: 0,
+            content_type: None,
+            created: timestamp,
+            last_modified: timestamp,
+            acl: Vec::new(),
+        }
+    }
+
+    /// Create new file node referencing the body `contents`
+    ///
+    /// The body itself needs to be added to the tree with
+    /// [`ResourceTree::add_body`].
+    pub fn file(contents: impl AsRef<[u8]>, content_type: Option<&str>, timestamp: i64) -> Self {
+        let contents = contents.as_ref();
+        Self {
+            is_collection: false,
+            blob_hash: Some(BlobHash::generate(contents)),
+            size: contents.len() as u32,
+            content_type: content_type.map(|ct| ct.to_string()),
+            created: timestamp,
+            last_modified: timestamp,
+            acl: Vec::new(),
+        }
+    }
+
+    /// Strong entity tag derived from the node body and modification time
+    ///
+    /// The hash is stable across builds and restarts, so tags handed out to
+    /// clients stay valid.
+    pub fn etag(&self) -> String {
+        let mut hasher = Xxh3::new();
+        hasher.update(&[self.is_collection as u8]);
+        hasher.update(&self.last_modified.to_be_bytes());
+        if let Some(blob_hash) = &self.blob_hash {
+            hasher.update(&blob_hash.0);
+        }
+        format!("\"{:016x}\"", hasher.digest())
+    }
+}
+
+/// Resource tree of a single account, keyed by normalized relative path
+///
+/// The root collection is stored under the empty path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceTree {
+    nodes: BTreeMap<String, DavNode>,
+    locks: LockTable,
+    bodies: AHashMap<BlobHash, Vec<u8>>,
+}
+
+impl Default for ResourceTree {
+    fn default() -> Self {
+        Self::new(now())
+    }
+}
+
+impl ResourceTree {
+    /// Create new tree containing only the root collection
+    pub fn new(timestamp: i64) -> Self {
+        let mut nodes = BTreeMap::new();
+        nodes.insert(String::new(), DavNode::collection(timestamp));
+        Self {
+            nodes,
+            locks: LockTable::default(),
+            bodies: AHashMap::new(),
+        }
+    }
+
+    /// Add a body referenced by a file node
+    pub fn add_body(&mut self, contents: Vec<u8>) {
+        self.bodies.insert(BlobHash::generate(&contents), contents);
+    }
+
+    /// Insert a file node along with its body
+    pub fn insert_file(
+        &mut self,
+        path: &str,
+        contents: impl Into<Vec<u8>>,
+        content_type: Option<&str>,
+        timestamp: i64,
+    ) -> crate::Result<()> {
+        let contents = contents.into();
+        self.insert(path, DavNode::file(&contents, content_type, timestamp))?;
+        self.add_body(contents);
+        Ok(())
+    }
+
+    /// Body of a file node, if it was loaded or written by this request
+    pub fn body(&self, node: &DavNode) -> Option<&[u8]> {
+        node.blob_hash
+            .as_ref()
+            .and_then(|hash| self.bodies.get(hash))
+            .map(Vec::as_slice)
+    }
+
+    /// Body of the file at `path`, if it was loaded or written by this request
+    pub fn contents(&self, path: &str) -> Option<&[u8]> {
+        self.get(path).and_then(|node| self.body(node))
+    }
+
+    /// Get the active locks of this tree
+    pub fn locks(&self) -> &LockTable {
+        &self.locks
+    }
+
+    /// Get the active locks of this tree for modification
+    pub fn locks_mut(&mut self) -> &mut LockTable {
+        &mut self.locks
+    }
+
+    /// Get a node by path
+    pub fn get(&self, path: &str) -> Option<&DavNode> {
+        self.nodes.get(normalize_path(path))
+    }
+
+    /// Get a node by path for modification
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut DavNode> {
+        self.nodes.get_mut(normalize_path(path))
+    }
+
+    /// Check whether a node exists
+    pub fn contains(&self, path: &str) -> bool {
+        self.nodes.contains_key(normalize_path(path))
+    }
+
+    /// Insert a node, failing with 409 when the parent collection is missing
+    pub fn insert(&mut self, path: &str, node: DavNode) -> crate::Result<()> {
+        let path = normalize_path(path);
+        if path.is_empty() {
+            return Err(DavError::conflict("Cannot replace the root collection"));
+        }
+
+        match self.nodes.get(parent_path(path)) {
+            Some(parent) if parent.is_collection => {
+                self.nodes.insert(path.to_string(), node);
+                Ok(())
+            }
+            _ => Err(DavError::conflict(format!(
+                "Parent collection of '{path}' does not exist"
+            ))),
+        }
+    }
+
+    /// Remove a node and all its descendants, returning the removed nodes
+    pub fn remove(&mut self, path: &str) -> Vec<(String, DavNode)> {
+        let path = normalize_path(path);
+        let keys = self
+            .subtree(path, usize::MAX)
+            .map(|(key, _)| key.to_string())
+            .collect::<Vec<_>>();
+
+        keys.into_iter()
+            .filter_map(|key| self.nodes.remove(&key).map(|node| (key, node)))
+            .collect()
+    }
+
+    /// Iterate over a node and its descendants up to the given depth
+    pub fn subtree<'x>(
+        &'x self,
+        path: &'x str,
+        depth: usize,
+    ) -> impl Iterator<Item = (&'x str, &'x DavNode)> + 'x {
+        let path = normalize_path(path);
+        self.nodes
+            .iter()
+            .filter(move |(key, _)| {
+                (path.is_empty() || is_descendant_or_self(key, path))
+                    && relative_depth(key, path) <= depth
+            })
+            .map(|(key, node)| (key.as_str(), node))
+    }
+}
+
+/// Handle a request for a tree-backed DAV resource type
+pub(crate) async fn handle_resource_request(
+    server: &Server,
+    mut req: HttpRequest,
+    access_token: Arc<AccessToken>,
+    session: &HttpSessionData,
+    resource: DavResourceName,
+    method: DavMethod,
+) -> HttpResponse {
+    let body = if method.has_body() {
+        match fetch_body(&mut req, MAX_REQUEST_BODY, session.session_id).await {
+            Some(body) => body,
+            None => return HttpResponse::new(StatusCode::PAYLOAD_TOO_LARGE),
+        }
+    } else {
+        Vec::new()
+    };
+
+    let uri = req.uri().path().to_string();
+    let mut headers = RequestHeaders::new(&uri);
+    let mut preconditions = Preconditions::default();
+    for (key, value) in req.headers() {
+        if let Ok(value) = value.to_str() {
+            headers.parse(key.as_str(), value);
+            preconditions.parse(key.as_str(), value);
+        }
+    }
+
+    let result = match ResourcePath::parse(resource, &uri) {
+        Some(path) => {
+            handle_request(
+                server,
+                &access_token,
+                &path,
+                &headers,
+                &preconditions,
+                &body,
+                method,
+            )
+            .await
+        }
+        None => Err(DavError::not_found(resource.name(), uri.as_str())),
+    };
+
+    result.unwrap_or_else(DavError::into_response)
+}
+
+async fn handle_request(
+    server: &Server,
+    access_token: &AccessToken,
+    path: &ResourcePath,
+    headers: &RequestHeaders<'_>,
+    preconditions: &Preconditions,
+    body: &[u8],
+    method: DavMethod,
+) -> crate::Result<HttpResponse> {
+    let account_id = account_id(server, access_token, path).await?;
+
+    // Members of the account hold every privilege, anyone else needs to be
+    // granted access through the resource ACL
+    let request = ResourceRequest {
+        path,
+        principal: if access_token.is_member(account_id) {
+            path.account.as_str()
+        } else {
+            access_token.name.as_str()
+        },
+        user: access_token.name.as_str(),
+        headers,
+        preconditions,
+    };
+
+    let mut attempt = 1;
+    loop {
+        let mut stored =
+            StoredTree::load(server, account_id, Collection::from(path.resource)).await?;
+        match method {
+            DavMethod::GET => stored.load_bodies(server, &path.path, 0).await?,
+            DavMethod::REPORT => match parse_body::<Report>(body)? {
+                // Multiget hrefs can address any collection of the account
+                Report::AddressbookMultiGet(multiget) => {
+                    for href in &multiget.hrefs {
+                        if let Some(href_path) = card::resolve_href(path, href) {
+                            stored.load_bodies(server, href_path, 0).await?;
+                        }
+                    }
+                }
+                _ => stored.load_bodies(server, &path.path, usize::MAX).await?,
+            },
+            _ => {}
+        }
+        let response = dispatch(&mut stored.tree, &request, body, method)?;
+        if is_read_only(method) {
+            return Ok(response);
+        }
+
+        match stored.save(server, access_token).await {
+            Ok(()) => return Ok(response),
+            Err(DavError::Internal(err))
+                if err.is_assertion_failure() && attempt < MAX_WRITE_ATTEMPTS =>
+            {
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Parsed state of a request shared by all method handlers
+struct ResourceRequest<'x> {
+    path: &'x ResourcePath,
+    /// Principal the resource ACL is evaluated for
+    principal: &'x str,
+    /// Authenticated user, which owns the locks it creates
+    user: &'x str,
+    headers: &'x RequestHeaders<'x>,
+    preconditions: &'x Preconditions,
+}
+
+fn is_read_only(method: DavMethod) -> bool {
+    matches!(
+        method,
+        DavMethod::GET | DavMethod::HEAD | DavMethod::PROPFIND | DavMethod::REPORT
+    )
+}
+
+fn dispatch(
+    tree: &mut ResourceTree,
+    request: &ResourceRequest<'_>,
+    body: &[u8],
+    method: DavMethod,
+) -> crate::Result<HttpResponse> {
+    let &ResourceRequest {
+        path,
+        principal,
+        user,
+        headers,
+        preconditions,
+    } = request;
+
+    // Other accounts need to be granted access through the resource ACL
+    let required = acl::required_privilege(method);
+    acl::check_privilege(tree, path, &path.path, principal, required)?;
+    if matches!(method, DavMethod::COPY | DavMethod::MOVE) {
+        let destination = copy_move::destination_path(path, headers)?;
+        acl::check_privilege(
+            tree,
+            path,
+            &destination,
+            principal,
+            (Acl::Modify, Privilege::Write),
+        )?;
+    }
+
+    match method {
+        DavMethod::GET | DavMethod::HEAD => {
+            get::get(tree, path, preconditions, method == DavMethod::HEAD)
+        }
+        DavMethod::PUT => update::put(
+            tree,
+            path,
+            user,
+            headers,
+            preconditions,
+            body.to_vec(),
+            now(),
+        )
+        .map(|response| HttpResponse::new(response.status).with_etag(response.etag)),
+        DavMethod::DELETE => {
+            delete::delete(tree, path, user, headers, preconditions, now()).map(HttpResponse::new)
+        }
+        DavMethod::MKCOL => mkcol::mkcol(tree, path, user, headers, now()).map(HttpResponse::new),
+        DavMethod::PROPFIND => {
+            let request = parse_body(body)?;
+            propfind::propfind(tree, path, headers.depth, &request).map(|multistatus| {
+                HttpResponse::new(StatusCode::MULTI_STATUS).with_xml_body(multistatus.to_string())
+            })
+        }
+        DavMethod::LOCK => {
+            let request = if body.is_empty() {
+                None
+            } else {
+                Some(parse_body(body)?)
+            };
+            lock::lock(tree, path, user, headers, request, now()).map(|response| {
+                HttpResponse::new(response.status)
+                    .with_lock_token(&response.token)
+                    .with_xml_body(
+                        PropResponse::new(vec![DavPropertyValue::new(
+                            WebDavProperty::LockDiscovery,
+                            vec![response.active_lock],
+                        )])
+                        .to_string(),
+                    )
+            })
+        }
+        DavMethod::COPY | DavMethod::MOVE => {
+            copy_move::copy_move(tree, path, user, headers, method == DavMethod::MOVE, now())
+                .map(HttpResponse::new)
+        }
+        DavMethod::ACL => {
+            let request = parse_body(body)?;
+            acl::set_acl(tree, path, principal, request).map(|_| HttpResponse::new(StatusCode::OK))
+        }
+        DavMethod::UNLOCK => lock::unlock(tree, path, user, headers, now())
+            .map(|_| HttpResponse::new(StatusCode::NO_CONTENT)),
+        DavMethod::REPORT => {
+            let report = parse_body::<Report>(body)?;
+            let multistatus = match (path.resource, report) {
+                (DavResourceName::Cal, Report::CalendarQuery(query)) => {
+                    calendar::calendar_query(tree, path, headers.depth, &query)?
+                }
+                (DavResourceName::Card, Report::AddressbookMultiGet(multiget)) => {
+                    card::addressbook_multiget(tree, path, &multiget)
+                }
+                _ => return Err(DavError::Code(StatusCode::FORBIDDEN)),
+            };
+
+            Ok(HttpResponse::new(StatusCode::MULTI_STATUS).with_xml_body(multistatus.to_string()))
+        }
+        _ => Err(DavError::Code(StatusCode::NOT_IMPLEMENTED)),
+    }
+}
+
+/// Parse an XML request body, treating an empty body as the default request
+pub(crate) fn parse_body<T: DavParser>(body: &[u8]) -> crate::Result<T> {
+    T::parse(&mut Tokenizer::new(body)).map_err(DavError::Parse)
+}
+
+/// Resource type, account and relative path addressed by a request URI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourcePath {
+    pub resource: DavResourceName,
+    pub account: String,
+    pub path: String,
+}
+
+impl ResourcePath {
+    /// Parse a request URI of the form `<base path>/<account>/<path>`
+    pub fn parse(resource: DavResourceName, uri: &str) -> Option<Self> {
+        let rest = uri.strip_prefix(resource.base_path())?;
+        let rest = rest.strip_prefix('/')?;
+        let (account, path) = rest.split_once('/').unwrap_or((rest, ""));
+        if account.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            resource,
+            account: account.to_string(),
+            path: normalize_path(path).to_string(),
+        })
+    }
+
+    /// Href of the account's root collection, without a trailing slash
+    pub fn root(&self) -> String {
+        format!("{}/{}", self.resource.base_path(), self.account)
+    }
+
+    /// Build the href of a node, adding a trailing slash to collections
+    pub fn href(&self, path: &str, is_collection: bool) -> String {
+        let mut href = format!("{}/", self.root());
+        if !path.is_empty() {
+            href.push_str(path);
+            if is_collection {
+                href.push('/');
+            }
+        }
+        href
+    }
+}
+
+/// Strip the scheme and authority from an absolute URL, keeping its path
+pub fn strip_origin(href: &str) -> Option<&str> {
+    match href.split_once("://") {
+        Some((_, rest)) => rest.find('/').map(|pos| &rest[pos..]),
+        None => Some(href),
+    }
+}
+
+/// Strip leading and trailing slashes from a path
+pub fn normalize_path(path: &str) -> &str {
+    path.trim_matches('/')
+}
+
+/// Get the parent of a normalized path
+pub fn parent_path(path: &str) -> &str {
+    path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or("")
+}
+
+/// Get the last segment of a normalized path
+pub fn node_name(path: &str) -> &str {
+    path.rsplit_once('/').map(|(_, name)| name).unwrap_or(path)
+}
+
+fn is_descendant_or_self(key: &str, path: &str) -> bool {
+    key == path || key.strip_prefix(path).is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn relative_depth(key: &str, path: &str) -> usize {
+    let rest = normalize_path(&key[path.len()..]);
+    if rest.is_empty() {
+        0
+    } else {
+        rest.split('/').count()
+    }
+}
+
+pub(crate) fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const T0: i64 = 1_700_000_000;
+
+    /// Small tree used across the WebDAV handler tests
+    pub(crate) fn sample_tree() -> ResourceTree {
+        let mut tree = ResourceTree::new(T0);
+        tree.insert("docs", DavNode::collection(T0)).unwrap();
+        tree.insert_file("docs/a.txt", "hello", Some("text/plain"), T0 + 10)
+            .unwrap();
+        tree.insert("docs/sub", DavNode::collection(T0)).unwrap();
+        tree.insert_file("docs/sub/b.txt", "world!", Some("text/plain"), T0 + 20)
+            .unwrap();
+        tree.insert_file("readme.md", "# A3Mailer", Some("text/markdown"), T0)
+            .unwrap();
+        tree
+    }
+
+    pub(crate) fn resource_path(path: &str) -> ResourcePath {
+        ResourcePath {
+            resource: DavResourceName::File,
+            account: "john".to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_resource_path_parse() {
+        let parse = |uri| ResourcePath::parse(DavResourceName::File, uri);
+        assert_eq!(parse("/dav/file/john/docs/a.txt"), Some(resource_path("docs/a.txt")));
+        assert_eq!(parse("/dav/file/john/"), Some(resource_path("")));
+        assert_eq!(parse("/dav/file/"), None);
+        assert_eq!(ResourcePath::parse(DavResourceName::Cal, "/dav/file/john/"), None);
+        assert_eq!(resource_path("").href("docs", true), "/dav/file/john/docs/");
+    }
+
+    #[test]
+    fn test_subtree_depth() {
+        let tree = sample_tree();
+        let paths = |depth| {
+            tree.subtree("docs", depth)
+                .map(|(path, _)| path.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(paths(0), vec!["docs"]);
+        assert_eq!(paths(1), vec!["docs", "docs/a.txt", "docs/sub"]);
+        assert_eq!(paths(usize::MAX).len(), 4);
+        assert_eq!(tree.subtree("", 1).count(), 3);
+    }
+
+    #[test]
+    fn test_insert_requires_parent() {
+        let mut tree = sample_tree();
+        assert!(tree.insert("missing/c.txt", DavNode::collection(T0)).is_err());
+        assert!(tree.insert("readme.md/c.txt", DavNode::collection(T0)).is_err());
+        assert_eq!(tree.remove("docs").len(), 4);
+        assert!(!tree.contains("docs/sub/b.txt"));
+    }
+}
@@ -0,0 +1,308 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! PROPFIND handling for WebDAV file resources
+
+use dav_proto::{
+    Depth,
+    schema::{
+        property::{DavProperty, DavValue, ResourceType, Rfc1123DateTime, WebDavProperty},
+        request::{DavPropertyValue, PropFind},
+        response::{MultiStatus, Response},
+    },
+};
+use hyper::StatusCode;
+
+use super::{DavNode, ResourcePath, ResourceTree, acl, node_name};
+use crate::{DavError, PropStatBuilder};
+
+/// Live properties reported for `allprop` and `propname` requests
+const FILE_PROPERTIES: [WebDavProperty; 7] = [
+    WebDavProperty::CreationDate,
+    WebDavProperty::DisplayName,
+    WebDavProperty::GetContentLength,
+    WebDavProperty::GetContentType,
+    WebDavProperty::GetETag,
+    WebDavProperty::GetLastModified,
+    WebDavProperty::ResourceType,
+];
+
+/// Run a PROPFIND request against a resource tree
+///
+/// A missing `Depth` header is treated as `infinity`, as required by RFC 4918.
+pub fn propfind(
+    tree: &ResourceTree,
+    path: &ResourcePath,
+    depth: Depth,
+    request: &PropFind,
+) -> crate::Result<MultiStatus> {
+    if !tree.contains(&path.path) {
+        return Err(DavError::not_found("file", path.href(&path.path, false)));
+    }
+
+    let depth = match depth {
+        Depth::Zero => 0,
+        Depth::One => 1,
+        Depth::Infinity | Depth::None => usize::MAX,
+    };
+
+    let responses = tree
+        .subtree(&path.path, depth)
+        .map(|(node_path, node)| {
+            Response::new_propstat(
+                path.href(node_path, node.is_collection),
+                node_propstats(tree, path, node_path, node, request).build(),
+            )
+        })
+        .collect();
+
+    Ok(MultiStatus::new(responses))
+}
+
+fn node_propstats(
+    tree: &ResourceTree,
+    resource: &ResourcePath,
+    path: &str,
+    node: &DavNode,
+    request: &PropFind,
+) -> PropStatBuilder {
+    let mut builder = PropStatBuilder::default();
+
+    match request {
+        PropFind::PropName => {
+            for property in FILE_PROPERTIES {
+                if property_value(path, node, &property).is_some() {
+                    builder.insert_ok(DavPropertyValue::empty(property));
+                }
+            }
+        }
+        PropFind::AllProp(include) => {
+            for property in FILE_PROPERTIES {
+                if let Some(value) = property_value(path, node, &property) {
+                    builder.insert_ok(DavPropertyValue::new(property, value));
+                }
+            }
+            for property in include {
+                insert_requested(&mut builder, path, node, property);
+            }
+        }
+        PropFind::Prop(properties) => {
+            for property in properties {
+                if let DavProperty::WebDav(WebDavProperty::Acl) = property {
+                    builder.insert_ok(DavPropertyValue::new(
+                        property.clone(),
+                        acl::acl_property(tree, resource, path),
+                    ));
+                } else {
+                    insert_requested(&mut builder, path, node, property);
+                }
+            }
+        }
+    }
+
+    builder
+}
+
+pub(super) fn insert_requested(
+    builder: &mut PropStatBuilder,
+    path: &str,
+    node: &DavNode,
+    property: &DavProperty,
+) {
+    let value = match property {
+        DavProperty::WebDav(webdav) if FILE_PROPERTIES.contains(webdav) => {
+            property_value(path, node, webdav)
+        }
+        _ => None,
+    };
+
+    match value {
+        Some(value) => {
+            builder.insert_ok(DavPropertyValue::new(property.clone(), value));
+        }
+        None => {
+            builder.insert_with_status(
+                DavPropertyValue::empty(property.clone()),
+                StatusCode::NOT_FOUND,
+            );
+        }
+    }
+}
+
+fn property_value(path: &str, node: &DavNode, property: &WebDavProperty) -> Option<DavValue> {
+    match property {
+        WebDavProperty::CreationDate => Some(DavValue::Timestamp(node.created)),
+        WebDavProperty::DisplayName if !path.is_empty() => {
+            Some(DavValue::String(node_name(path).to_string()))
+        }
+        WebDavProperty::GetContentLength if !node.is_collection => {
+            Some(DavValue::Uint64(node.size as u64))
+        }
+        WebDavProperty::GetContentType if !node.is_collection => {
+            node.content_type.clone().map(DavValue::String)
+        }
+        WebDavProperty::GetETag => Some(DavValue::String(node.etag())),
+        WebDavProperty::GetLastModified => {
+            Some(DavValue::Rfc1123Date(Rfc1123DateTime::new(node.last_modified)))
+        }
+        WebDavProperty::ResourceType => Some(DavValue::from(if node.is_collection {
+            vec![ResourceType::Collection]
+        } else {
+            vec![]
+        })),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webdav::{
+        parse_body,
+        tests::{resource_path, sample_tree},
+    };
+    use dav_proto::schema::response::{PropStat, ResponseType};
+
+    const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
+        <D:propfind xmlns:D="DAV:">
+          <D:prop>
+            <D:getcontentlength/>
+            <D:getlastmodified/>
+            <D:resourcetype/>
+            <D:getetag/>
+            <D:quota-used-bytes/>
+          </D:prop>
+        </D:propfind>"#;
+
+    fn run(path: &str, depth: Depth) -> MultiStatus {
+        let request: PropFind = parse_body(PROPFIND_BODY.as_bytes()).unwrap();
+        propfind(&sample_tree(), &resource_path(path), depth, &request).unwrap()
+    }
+
+    fn hrefs(multistatus: &MultiStatus) -> Vec<&str> {
+        multistatus
+            .response
+            .0
+            .iter()
+            .map(|response| response.href.0.as_str())
+            .collect()
+    }
+
+    fn propstat(response: &Response, status: StatusCode) -> Option<&PropStat> {
+        match &response.typ {
+            ResponseType::PropStat(propstats) => {
+                propstats.0.iter().find(|propstat| propstat.status.0 == status)
+            }
+            ResponseType::Status { .. } => None,
+        }
+    }
+
+    fn has_property(propstat: &PropStat, property: WebDavProperty) -> bool {
+        propstat
+            .prop
+            .0
+            .0
+            .iter()
+            .any(|value| value.property == DavProperty::WebDav(property.clone()))
+    }
+
+    #[test]
+    fn test_propfind_depth_zero() {
+        let multistatus = run("docs", Depth::Zero);
+        assert_eq!(hrefs(&multistatus), vec!["/dav/file/john/docs/"]);
+    }
+
+    #[test]
+    fn test_propfind_depth_one() {
+        let multistatus = run("docs", Depth::One);
+        assert_eq!(
+            hrefs(&multistatus),
+            vec![
+                "/dav/file/john/docs/",
+                "/dav/file/john/docs/a.txt",
+                "/dav/file/john/docs/sub/"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_propfind_depth_infinity() {
+        let multistatus = run("docs", Depth::Infinity);
+        assert_eq!(multistatus.response.0.len(), 4);
+        assert!(hrefs(&multistatus).contains(&"/dav/file/john/docs/sub/b.txt"));
+    }
+
+    #[test]
+    fn test_propfind_file_properties() {
+        let tree = sample_tree();
+        let multistatus = run("docs/a.txt", Depth::Zero);
+        let response = &multistatus.response.0[0];
+
+        let ok = propstat(response, StatusCode::OK).unwrap();
+        for property in [
+            WebDavProperty::GetContentLength,
+            WebDavProperty::GetLastModified,
+            WebDavProperty::ResourceType,
+            WebDavProperty::GetETag,
+        ] {
+            assert!(has_property(ok, property));
+        }
+        assert!(ok.prop.0.0.contains(&DavPropertyValue::new(
+            WebDavProperty::GetContentLength,
+            DavValue::Uint64(5)
+        )));
+        assert!(ok.prop.0.0.contains(&DavPropertyValue::new(
+            WebDavProperty::GetETag,
+            DavValue::String(tree.get("docs/a.txt").unwrap().etag())
+        )));
+
+        let not_found = propstat(response, StatusCode::NOT_FOUND).unwrap();
+        assert!(has_property(not_found, WebDavProperty::QuotaUsedBytes));
+        assert!(!has_property(not_found, WebDavProperty::GetETag));
+    }
+
+    #[test]
+    fn test_propfind_collection_has_no_content_length() {
+        let multistatus = run("docs", Depth::Zero);
+        let response = &multistatus.response.0[0];
+
+        let not_found = propstat(response, StatusCode::NOT_FOUND).unwrap();
+        assert!(has_property(not_found, WebDavProperty::GetContentLength));
+        assert!(has_property(
+            propstat(response, StatusCode::OK).unwrap(),
+            WebDavProperty::ResourceType
+        ));
+    }
+
+    #[test]
+    fn test_propfind_allprop_and_missing_resource() {
+        let request: PropFind = parse_body(b"").unwrap();
+        assert_eq!(request, PropFind::AllProp(vec![]));
+
+        let multistatus =
+            propfind(&sample_tree(), &resource_path("readme.md"), Depth::Zero, &request).unwrap();
+        let ok = propstat(&multistatus.response.0[0], StatusCode::OK).unwrap();
+        assert!(has_property(ok, WebDavProperty::GetContentType));
+        assert!(multistatus.to_string().contains("<D:multistatus"));
+
+        let err = propfind(&sample_tree(), &resource_path("nope"), Depth::Zero, &request)
+            .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_propfind_acl() {
+        let request: PropFind =
+            parse_body(br#"<D:propfind xmlns:D="DAV:"><D:prop><D:acl/></D:prop></D:propfind>"#)
+                .unwrap();
+        let multistatus =
+            propfind(&sample_tree(), &resource_path("docs"), Depth::Zero, &request).unwrap();
+
+        let ok = propstat(&multistatus.response.0[0], StatusCode::OK).unwrap();
+        assert!(has_property(ok, WebDavProperty::Acl));
+        assert!(multistatus.to_string().contains("/dav/pal/john/"));
+    }
+}
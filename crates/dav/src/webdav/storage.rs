@@ -0,0 +1,474 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! Persistence of resource trees
+//!
+//! Every resource is stored as its own document in the collection of its
+//! resource type, and file bodies live in the blob store, linked to and
+//! counted against the quota of the account through the object index. The
+//! locks of an account are stored in a separate document of the same
+//! collection.
+//!
+//! Nothing is cached between requests: a request loads the resources of the
+//! account, runs its handler and, when the method modifies the tree, writes
+//! back only the resources that changed, asserting that none of them was
+//! modified in the meantime.
+
+use common::{
+    Server,
+    auth::AccessToken,
+    storage::index::{
+        IndexValue, IndexableAndSerializableObject, IndexableObject, ObjectIndexBuilder,
+    },
+};
+use dav_proto::schema::response::BaseCondition;
+use directory::backend::internal::manage::ManageDirectory;
+use hyper::StatusCode;
+use jmap_proto::types::{collection::Collection, property::Property};
+use store::{
+    Serialize,
+    ahash::AHashMap,
+    write::{AlignedBytes, Archive, Archiver, BatchBuilder, assert::AssertValue},
+};
+use trc::AddContext;
+
+use super::{DavNode, ResourcePath, ResourceTree, lock::LockTable, now};
+use crate::{DavError, DavErrorCondition};
+
+/// Document id the locks of an account are stored under
+///
+/// Document ids are assigned from zero, so the last one is never used by
+/// the resources of the collection.
+pub const LOCKS_DOCUMENT_ID: u32 = u32::MAX;
+
+/// A resource as stored in the data store
+#[derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct StoredNode {
+    pub path: String,
+    pub node: DavNode,
+}
+
+/// Resource tree loaded from the store, along with the documents it was built
+/// from so that writes only touch changed resources and can detect concurrent
+/// modifications
+pub struct StoredTree {
+    pub account_id: u32,
+    pub collection: Collection,
+    pub tree: ResourceTree,
+    documents: AHashMap<String, StoredDocument>,
+    duplicates: Vec<(String, StoredDocument)>,
+    locks: Option<Archive<AlignedBytes>>,
+    stored_locks: LockTable,
+}
+
+struct StoredDocument {
+    document_id: u32,
+    node: DavNode,
+    archive: Archive<AlignedBytes>,
+}
+
+/// Resolve the account addressed by a request
+///
+/// Fails with `404 Not Found` for unknown accounts.
+pub async fn account_id(
+    server: &Server,
+    access_token: &AccessToken,
+    path: &ResourcePath,
+) -> crate::Result<u32> {
+    if access_token.name == path.account {
+        return Ok(access_token.primary_id);
+    }
+
+    server
+        .store()
+        .get_principal_id(&path.account)
+        .await
+        .caused_by(trc::location!())?
+        .ok_or_else(|| DavError::not_found("account", path.root()))
+}
+
+impl StoredTree {
+    /// Load the resources of an account, or an empty tree if none was written
+    ///
+    /// File bodies are not loaded, see [`StoredTree::load_bodies`].
+    pub async fn load(
+        server: &Server,
+        account_id: u32,
+        collection: Collection,
+    ) -> trc::Result<Self> {
+        let mut tree = ResourceTree::new(now());
+        let mut documents = AHashMap::new();
+        let mut duplicates = Vec::new();
+
+        if let Some(document_ids) = server
+            .get_document_ids(account_id, collection)
+            .await
+            .caused_by(trc::location!())?
+        {
+            server
+                .get_archives(
+                    account_id,
+                    collection,
+                    &document_ids,
+                    |document_id, archive| {
+                        let stored = archive
+                            .deserialize::<StoredNode>()
+                            .caused_by(trc::location!())?;
+                        tree.nodes.insert(stored.path.clone(), stored.node.clone());
+
+                        // Concurrent requests creating the same resource both
+                        // succeed, the most recent document wins
+                        if let Some(previous) = documents.insert(
+                            stored.path.clone(),
+                            StoredDocument {
+                                document_id,
+                                node: stored.node,
+                                archive,
+                            },
+                        ) {
+                            duplicates.push((stored.path, previous));
+                        }
+                        Ok(true)
+                    },
+                )
+                .await
+                .caused_by(trc::location!())?;
+        }
+
+        let locks = server
+            .get_archive(account_id, collection, LOCKS_DOCUMENT_ID)
+            .await
+            .caused_by(trc::location!())?;
+        if let Some(archive) = &locks {
+            tree.locks = archive
+                .deserialize::<LockTable>()
+                .caused_by(trc::location!())?;
+        }
+
+        Ok(Self {
+            account_id,
+            collection,
+            stored_locks: tree.locks.clone(),
+            tree,
+            documents,
+            duplicates,
+            locks,
+        })
+    }
+
+    /// Load the bodies of the files under `path` up to the given depth
+    ///
+    /// Bodies missing from the blob store are skipped.
+    pub async fn load_bodies(
+        &mut self,
+        server: &Server,
+        path: &str,
+        depth: usize,
+    ) -> trc::Result<()> {
+        let hashes = self
+            .tree
+            .subtree(path, depth)
+            .filter_map(|(_, node)| node.blob_hash.clone())
+            .collect::<Vec<_>>();
+
+        for hash in hashes {
+            if self.tree.bodies.contains_key(&hash) {
+                continue;
+            }
+            if let Some(contents) = server
+                .blob_store()
+                .get_blob(&hash.0, 0..usize::MAX)
+                .await
+                .caused_by(trc::location!())?
+            {
+                self.tree.bodies.insert(hash, contents);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the changed resources back to the store
+    ///
+    /// Fails with an assertion error when another request modified any of
+    /// them after they were loaded, and with `507 Insufficient Storage` when
+    /// the changes exceed the quota of the account.
+    pub async fn save(self, server: &Server, access_token: &AccessToken) -> crate::Result<()> {
+        let Self {
+            account_id,
+            collection,
+            tree,
+            documents,
+            duplicates,
+            locks,
+            stored_locks,
+        } = self;
+
+        let mut batch = BatchBuilder::new();
+        batch
+            .with_account_id(account_id)
+            .with_collection(collection);
+        let mut used_quota = 0i64;
+        let mut new_blobs = Vec::new();
+        let mut created = Vec::new();
+
+        // Update changed resources, a resource changing between file and
+        // collection is replaced by a new document
+        for (path, node) in &tree.nodes {
+            match documents.get(path) {
+                Some(document) if &document.node == node => {}
+                Some(document) if document.node.blob_hash.is_some() == node.blob_hash.is_some() => {
+                    used_quota += stored_size(path, node) - stored_size(path, &document.node);
+                    if node.blob_hash != document.node.blob_hash {
+                        new_blobs.extend(node.blob_hash.as_ref());
+                    }
+                    batch.update_document(document.document_id).custom(
+                        ObjectIndexBuilder::new()
+                            .with_current(
+                                document
+                                    .archive
+                                    .to_unarchived::<StoredNode>()
+                                    .caused_by(trc::location!())?,
+                            )
+                            .with_changes(StoredNode {
+                                path: path.clone(),
+                                node: node.clone(),
+                            })
+                            .with_tenant_id(access_token),
+                    )?;
+                }
+                _ => {
+                    used_quota += stored_size(path, node);
+                    new_blobs.extend(node.blob_hash.as_ref());
+                    created.push((path, node));
+                }
+            }
+        }
+
+        // Delete removed or replaced resources
+        let removed = documents
+            .iter()
+            .filter(|(path, document)| {
+                tree.nodes.get(*path).is_none_or(|node| {
+                    document.node.blob_hash.is_some() != node.blob_hash.is_some()
+                })
+            })
+            .map(|(path, document)| (path.as_str(), document))
+            .chain(
+                duplicates
+                    .iter()
+                    .map(|(path, document)| (path.as_str(), document)),
+            );
+        for (path, document) in removed {
+            used_quota -= stored_size(path, &document.node);
+            batch.delete_document(document.document_id).custom(
+                ObjectIndexBuilder::<_, ()>::new()
+                    .with_current(
+                        document
+                            .archive
+                            .to_unarchived::<StoredNode>()
+                            .caused_by(trc::location!())?,
+                    )
+                    .with_tenant_id(access_token),
+            )?;
+        }
+
+        // Create new resources
+        if !created.is_empty() {
+            let last_document_id = server
+                .store()
+                .assign_document_ids(account_id, collection, created.len() as u64)
+                .await
+                .caused_by(trc::location!())?;
+            for (document_id, (path, node)) in (0..=last_document_id).rev().zip(created) {
+                batch.create_document(document_id).custom(
+                    ObjectIndexBuilder::<(), _>::new()
+                        .with_changes(StoredNode {
+                            path: path.clone(),
+                            node: node.clone(),
+                        })
+                        .with_tenant_id(access_token),
+                )?;
+            }
+        }
+
+        // Locks
+        if tree.locks != stored_locks {
+            batch.update_document(LOCKS_DOCUMENT_ID);
+            match &locks {
+                Some(current) => batch.assert_value(Property::Value, current),
+                None => batch.assert_value(Property::Value, AssertValue::None),
+            };
+            batch.set(
+                Property::Value,
+                Archiver::new(tree.locks)
+                    .serialize()
+                    .caused_by(trc::location!())?,
+            );
+        }
+
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        // Validate quota
+        if used_quota > 0 {
+            server
+                .has_available_quota(
+                    &server.get_resource_token(access_token, account_id).await?,
+                    used_quota as u64,
+                )
+                .await
+                .map_err(|err| {
+                    if err.matches(trc::EventType::Limit(trc::LimitEvent::Quota))
+                        || err.matches(trc::EventType::Limit(trc::LimitEvent::TenantQuota))
+                    {
+                        DavError::from(DavErrorCondition::new(
+                            StatusCode::INSUFFICIENT_STORAGE,
+                            BaseCondition::QuotaNotExceeded,
+                        ))
+                    } else {
+                        DavError::Internal(err)
+                    }
+                })?;
+        }
+
+        // Write the bodies of new and replaced files, bodies of copied files
+        // are already in the blob store
+        for hash in new_blobs {
+            if let Some(contents) = tree.bodies.get(hash) {
+                server
+                    .put_blob(account_id, contents, false)
+                    .await
+                    .caused_by(trc::location!())?;
+            }
+        }
+
+        server
+            .commit_batch(batch)
+            .await
+            .map(|_| ())
+            .map_err(DavError::Internal)
+    }
+}
+
+/// Bytes a resource counts against the quota of its account
+fn stored_size(path: &str, node: &DavNode) -> i64 {
+    path.len() as i64 + node.size as i64
+}
+
+impl IndexableObject for StoredNode {
+    fn index_values(&self) -> impl Iterator<Item = IndexValue<'_>> {
+        let mut values = Vec::with_capacity(2);
+
+        values.push(IndexValue::Quota {
+            used: stored_size(&self.path, &self.node) as u32,
+        });
+        if let Some(blob_hash) = &self.node.blob_hash {
+            values.push(IndexValue::Blob {
+                value: blob_hash.clone(),
+            });
+        }
+
+        values.into_iter()
+    }
+}
+
+impl IndexableObject for &ArchivedStoredNode {
+    fn index_values(&self) -> impl Iterator<Item = IndexValue<'_>> {
+        let mut values = Vec::with_capacity(2);
+
+        values.push(IndexValue::Quota {
+            used: self.path.len() as u32 + u32::from(self.node.size),
+        });
+        if let Some(blob_hash) = self.node.blob_hash.as_ref() {
+            values.push(IndexValue::Blob {
+                value: blob_hash.into(),
+            });
+        }
+
+        values.into_iter()
+    }
+}
+
+impl IndexableAndSerializableObject for StoredNode {
+    fn is_versioned() -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webdav::{
+        acl::PrincipalGrant,
+        tests::{T0, sample_tree},
+    };
+    use jmap_proto::types::acl::Acl;
+    use store::Deserialize;
+    use utils::map::bitmap::Bitmap;
+
+    fn archive(stored: &StoredNode) -> Archive<AlignedBytes> {
+        let bytes = Archiver::new(stored.clone()).serialize().unwrap();
+        <Archive<AlignedBytes> as Deserialize>::deserialize(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_stored_node_archive_roundtrip() {
+        let tree = sample_tree();
+        let mut node = tree.get("docs/a.txt").unwrap().clone();
+        node.acl.push(PrincipalGrant {
+            principal: "jane".to_string(),
+            grants: Bitmap::from_iter([Acl::Read, Acl::ReadItems]),
+        });
+        let stored = StoredNode {
+            path: "docs/a.txt".to_string(),
+            node,
+        };
+
+        let archive = archive(&stored);
+        let restored = archive.deserialize::<StoredNode>().unwrap();
+        assert_eq!(restored, stored);
+        assert_eq!(restored.node.etag(), tree.get("docs/a.txt").unwrap().etag());
+        assert_eq!(
+            archive
+                .to_unarchived::<StoredNode>()
+                .unwrap()
+                .inner
+                .index_values()
+                .collect::<Vec<_>>(),
+            stored.index_values().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_index_values() {
+        let tree = sample_tree();
+        let file = StoredNode {
+            path: "docs/a.txt".to_string(),
+            node: tree.get("docs/a.txt").unwrap().clone(),
+        };
+        let collection = StoredNode {
+            path: "docs".to_string(),
+            node: DavNode::collection(T0),
+        };
+
+        // Quota covers the path and the body, bodies are linked to the file
+        assert_eq!(
+            file.index_values().collect::<Vec<_>>(),
+            vec![
+                IndexValue::Quota { used: 15 },
+                IndexValue::Blob {
+                    value: file.node.blob_hash.clone().unwrap()
+                }
+            ]
+        );
+        assert_eq!(
+            collection.index_values().collect::<Vec<_>>(),
+            vec![IndexValue::Quota { used: 4 }]
+        );
+    }
+}
@@ -0,0 +1,178 @@
+/*
+ * SPDX-FileCopyrightText: 2024 A3Mailer Project
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! PUT handling for WebDAV file resources
+
+use dav_proto::RequestHeaders;
+use hyper::StatusCode;
+
+use super::{DavNode, ResourcePath, ResourceTree, conditional::Preconditions, lock::check_lock};
+use crate::DavError;
+
+/// Result of a successful PUT request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PutResponse {
+    pub status: StatusCode,
+    pub etag: String,
+}
+
+/// Create or replace a file
+///
+/// Returns `201 Created` for new resources and `204 No Content` when an
+/// existing file was replaced.
+pub fn put(
+    tree: &mut ResourceTree,
+    resource: &ResourcePath,
+    principal: &str,
+    headers: &RequestHeaders<'_>,
+    preconditions: &Preconditions,
+    contents: Vec<u8>,
+    now: i64,
+) -> crate::Result<PutResponse> {
+    let existing = tree.get(&resource.path);
+    if existing.is_some_and(|node| node.is_collection) {
+        return Err(DavError::Code(StatusCode::METHOD_NOT_ALLOWED));
+    }
+
+    preconditions.evaluate(existing, false)?;
+    check_lock(tree, resource, principal, &resource.path, headers, now)?;
+
+    let (status, created, content_type, acl) = match tree.get(&resource.path) {
+        Some(node) => (
+            StatusCode::NO_CONTENT,
+            node.created,
+            headers
+                .content_type
+                .map(|ct| ct.to_string())
+                .or_else(|| node.content_type.clone()),
+            node.acl.clone(),
+        ),
+        None => (
+            StatusCode::CREATED,
+            now,
+            headers.content_type.map(|ct| ct.to_string()),
+            Vec::new(),
+        ),
+    };
+
+    let node = DavNode {
+        created,
+        acl,
+        ..DavNode::file(&contents, content_type.as_deref(), now)
+    };
+    let etag = node.etag();
+    tree.insert(&resource.path, node)?;
+    tree.add_body(contents);
+
+    Ok(PutResponse { status, etag })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webdav::{
+        lock::lock,
+        tests::{T0, resource_path, sample_tree},
+    };
+
+    fn preconditions(key: &str, value: &str) -> Preconditions {
+        let mut preconditions = Preconditions::default();
+        preconditions.parse(key, value);
+        preconditions
+    }
+
+    #[test]
+    fn test_conditional_put() {
+        let mut tree = sample_tree();
+        let resource = resource_path("docs/a.txt");
+        let headers = RequestHeaders::new("/dav/file/john/docs/a.txt");
+        let etag = tree.get("docs/a.txt").unwrap().etag();
+
+        let response = put(
+            &mut tree,
+            &resource,
+            "john",
+            &headers,
+            &preconditions("If-Match", &etag),
+            b"updated".to_vec(),
+            T0 + 100,
+        )
+        .unwrap();
+        assert_eq!(response.status, StatusCode::NO_CONTENT);
+        assert_ne!(response.etag, etag);
+
+        let node = tree.get("docs/a.txt").unwrap();
+        assert_eq!(tree.body(node), Some(&b"updated"[..]));
+        assert_eq!(node.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(node.etag(), response.etag);
+
+        // The previous ETag is now stale
+        let err = put(
+            &mut tree,
+            &resource,
+            "john",
+            &headers,
+            &preconditions("If-Match", &etag),
+            b"lost update".to_vec(),
+            T0 + 200,
+        )
+        .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(tree.contents("docs/a.txt"), Some(&b"updated"[..]));
+    }
+
+    #[test]
+    fn test_put_create() {
+        let mut tree = sample_tree();
+        let resource = resource_path("docs/new.txt");
+        let headers = RequestHeaders::new("/dav/file/john/docs/new.txt");
+
+        let err = put(
+            &mut tree,
+            &resource,
+            "john",
+            &headers,
+            &preconditions("If-Match", "*"),
+            b"new".to_vec(),
+            T0,
+        )
+        .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::PRECONDITION_FAILED);
+        assert!(!tree.contains("docs/new.txt"));
+
+        let create_only = preconditions("If-None-Match", "*");
+        let response = put(&mut tree, &resource, "john", &headers, &create_only, b"new".to_vec(), T0)
+            .unwrap();
+        assert_eq!(response.status, StatusCode::CREATED);
+
+        let err = put(&mut tree, &resource, "john", &headers, &create_only, b"again".to_vec(), T0)
+            .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[test]
+    fn test_put_locked_resource() {
+        let mut tree = sample_tree();
+        let resource = resource_path("readme.md");
+        let headers = RequestHeaders::new("/dav/file/john/readme.md");
+        let lock_info = crate::webdav::parse_body(
+            br#"<D:lockinfo xmlns:D="DAV:"><D:lockscope><D:exclusive/></D:lockscope><D:locktype><D:write/></D:locktype></D:lockinfo>"#,
+        )
+        .unwrap();
+        let token = lock(&mut tree, &resource, "john", &headers, Some(lock_info), T0)
+            .unwrap()
+            .token;
+
+        let err = put(&mut tree, &resource, "john", &headers, &Preconditions::default(), vec![], T0)
+            .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::LOCKED);
+
+        let if_header = format!("(<{token}>)");
+        let mut with_token = RequestHeaders::new("/dav/file/john/readme.md");
+        with_token.parse("If", &if_header);
+        put(&mut tree, &resource, "john", &with_token, &Preconditions::default(), vec![], T0).unwrap();
+    }
+}
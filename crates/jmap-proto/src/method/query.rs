@@ -55,7 +55,7 @@ pub struct QueryResponse {
     pub limit: Option<usize>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Filter {
     Email(String),
     Name(String),
@@ -108,6 +108,25 @@ pub enum Filter {
     Close,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterNode {
+    Operator(FilterOperator),
+    Condition(Filter),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterOperator {
+    pub operator: Operator,
+    pub conditions: Vec<FilterNode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    And,
+    Or,
+    Not,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Comparator {
     pub is_ascending: bool,
@@ -245,19 +264,26 @@ impl JsonObjectParser for QueryRequest<RequestArguments> {
 pub fn parse_filter(parser: &mut Parser) -> trc::Result<Vec<Filter>> {
     let mut filter = vec![Filter::Close];
     let mut pos_stack = vec![0];
+    let mut conditions_stack = vec![0usize];
 
     loop {
         match parser.next_token::<RequestProperty>()? {
             Token::String(property) => {
                 parser.next_token::<Ignore>()?.assert(Token::Colon)?;
-                filter[*pos_stack.last().unwrap()] = match &property.hash[0] {
+                let cond = match &property.hash[0] {
                     0x726f_7461_7265_706f => {
-                        match parser.next_token::<u64>()?.unwrap_string("operator")? {
-                            0x0044_4e41 => Filter::And,
-                            0x524f => Filter::Or,
-                            0x0054_4f4e => Filter::Not,
-                            _ => return Err(parser.error_value()),
-                        }
+                        filter[*pos_stack.last().unwrap()] =
+                            match parser.next_token::<String>()?.unwrap_string("operator")?.as_str() {
+                                "AND" => Filter::And,
+                                "OR" => Filter::Or,
+                                "NOT" => Filter::Not,
+                                other => {
+                                    return Err(trc::JmapEvent::UnsupportedFilter
+                                        .into_err()
+                                        .details(format_compact!("operator {other}")));
+                                }
+                            };
+                        continue;
                     }
                     0x736e_6f69_7469_646e_6f63 => {
                         parser.next_token::<Ignore>()?.assert(Token::ArrayStart)?;
@@ -441,12 +467,31 @@ pub fn parse_filter(parser: &mut Parser) -> trc::Result<Vec<Filter>> {
                         }
                     },
                 };
+
+                // A FilterCondition with several properties matches only
+                // when all of them match, so group them under an implicit AND
+                let pos = *pos_stack.last().unwrap();
+                let conditions = conditions_stack.last_mut().unwrap();
+                match *conditions {
+                    0 => filter[pos] = cond,
+                    1 => {
+                        let first = std::mem::replace(&mut filter[pos], Filter::And);
+                        filter.push(first);
+                        filter.push(cond);
+                    }
+                    _ => filter.push(cond),
+                }
+                *conditions += 1;
             }
             Token::DictStart => {
                 pos_stack.push(filter.len());
+                conditions_stack.push(0);
                 filter.push(Filter::Close);
             }
             Token::DictEnd => {
+                if conditions_stack.pop().unwrap() > 1 {
+                    filter.push(Filter::Close);
+                }
                 if !matches!(filter[pos_stack.pop().unwrap()], Filter::Close) {
                     if pos_stack.is_empty() {
                         break;
@@ -699,7 +744,95 @@ impl Filter {
     }
 }
 
+impl FilterNode {
+    /// Builds the typed filter tree from the flat representation produced by
+    /// `parse_filter`, rejecting conditions that were not recognised.
+    pub fn from_filters(filters: &[Filter]) -> trc::Result<Option<FilterNode>> {
+        let mut iter = filters.iter();
+        let node = match iter.next() {
+            Some(filter) => FilterNode::build(filter, &mut iter)?,
+            None => return Ok(None),
+        };
+
+        if iter.next().is_none() {
+            Ok(Some(node))
+        } else {
+            Err(trc::JmapEvent::InvalidArguments
+                .into_err()
+                .details("Malformed filter"))
+        }
+    }
+
+    fn build<'x>(
+        filter: &'x Filter,
+        iter: &mut impl Iterator<Item = &'x Filter>,
+    ) -> trc::Result<FilterNode> {
+        let operator = match filter {
+            Filter::And => Operator::And,
+            Filter::Or => Operator::Or,
+            Filter::Not => Operator::Not,
+            Filter::_T(property) => {
+                return Err(trc::JmapEvent::UnsupportedFilter
+                    .into_err()
+                    .details(property.clone()));
+            }
+            Filter::Close => {
+                return Err(trc::JmapEvent::InvalidArguments
+                    .into_err()
+                    .details("Malformed filter"));
+            }
+            condition => return Ok(FilterNode::Condition(condition.clone())),
+        };
+
+        let mut conditions = Vec::new();
+        loop {
+            match iter.next() {
+                Some(Filter::Close) => break,
+                Some(filter) => conditions.push(FilterNode::build(filter, iter)?),
+                None => {
+                    return Err(trc::JmapEvent::InvalidArguments
+                        .into_err()
+                        .details("Malformed filter"));
+                }
+            }
+        }
+
+        Ok(FilterNode::Operator(FilterOperator {
+            operator,
+            conditions,
+        }))
+    }
+}
+
 impl Comparator {
+    /// Rejects sort criteria that are unknown or missing required arguments.
+    pub fn validate(sort: &[Comparator]) -> trc::Result<()> {
+        for comparator in sort {
+            match &comparator.property {
+                SortProperty::_T(property) => {
+                    return Err(trc::JmapEvent::UnsupportedSort
+                        .into_err()
+                        .details(property.clone()));
+                }
+                SortProperty::HasKeyword
+                | SortProperty::AllInThreadHaveKeyword
+                | SortProperty::SomeInThreadHaveKeyword
+                    if comparator.keyword.is_none() =>
+                {
+                    return Err(trc::JmapEvent::InvalidArguments
+                        .into_err()
+                        .details(format_compact!(
+                            "Missing keyword for sort property {}",
+                            comparator.property
+                        )));
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn descending(property: SortProperty) -> Self {
         Self {
             property,
@@ -809,3 +942,118 @@ impl From<FilterType> for Filter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::{JsonObjectParser, json::Parser},
+        request::method::MethodObject,
+        types::{date::UTCDate, keyword::Keyword},
+    };
+
+    use super::{
+        Comparator, Filter, FilterNode, FilterOperator, Operator, QueryRequest, RequestArguments,
+        SortProperty,
+    };
+
+    fn parse_query(json: &str) -> trc::Result<QueryRequest<RequestArguments>> {
+        let mut parser = Parser::new(json.as_bytes());
+        parser.ctx = MethodObject::Email;
+        QueryRequest::parse(&mut parser)
+    }
+
+    #[test]
+    fn parse_nested_filter_and_sort() {
+        let request = parse_query(
+            r#"{
+                "accountId": "b",
+                "filter": {
+                    "operator": "AND",
+                    "conditions": [
+                        {"hasKeyword": "$flagged", "after": "2024-01-01T00:00:00Z"},
+                        {
+                            "operator": "OR",
+                            "conditions": [{"from": "alice"}, {"subject": "report"}]
+                        },
+                        {"operator": "NOT", "conditions": [{"text": "spam"}]}
+                    ]
+                },
+                "sort": [
+                    {"property": "receivedAt", "isAscending": false},
+                    {"property": "subject", "isAscending": true, "collation": "i;unicode-casemap"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let after = Parser::new(b"\"2024-01-01T00:00:00Z\"")
+            .next_token::<UTCDate>()
+            .unwrap()
+            .unwrap_string("")
+            .unwrap();
+        assert_eq!(
+            FilterNode::from_filters(&request.filter).unwrap(),
+            Some(FilterNode::Operator(FilterOperator {
+                operator: Operator::And,
+                conditions: vec![
+                    FilterNode::Operator(FilterOperator {
+                        operator: Operator::And,
+                        conditions: vec![
+                            FilterNode::Condition(Filter::HasKeyword(Keyword::Flagged)),
+                            FilterNode::Condition(Filter::After(after)),
+                        ],
+                    }),
+                    FilterNode::Operator(FilterOperator {
+                        operator: Operator::Or,
+                        conditions: vec![
+                            FilterNode::Condition(Filter::From("alice".to_string())),
+                            FilterNode::Condition(Filter::Subject("report".to_string())),
+                        ],
+                    }),
+                    FilterNode::Operator(FilterOperator {
+                        operator: Operator::Not,
+                        conditions: vec![FilterNode::Condition(Filter::Text("spam".to_string()))],
+                    }),
+                ],
+            }))
+        );
+
+        let sort = request.sort.unwrap();
+        Comparator::validate(&sort).unwrap();
+        assert_eq!(
+            sort,
+            vec![
+                Comparator::descending(SortProperty::ReceivedAt),
+                Comparator {
+                    is_ascending: true,
+                    collation: Some("i;unicode-casemap".to_string()),
+                    property: SortProperty::Subject,
+                    keyword: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_unsupported_filter_and_sort() {
+        let request = parse_query(r#"{"filter": {"inMailbox": "a", "priority": 1}}"#).unwrap();
+        assert!(
+            FilterNode::from_filters(&request.filter)
+                .unwrap_err()
+                .matches(trc::EventType::Jmap(trc::JmapEvent::UnsupportedFilter))
+        );
+
+        assert!(
+            parse_query(r#"{"filter": {"operator": "XOR", "conditions": []}}"#)
+                .unwrap_err()
+                .matches(trc::EventType::Jmap(trc::JmapEvent::UnsupportedFilter))
+        );
+
+        let request = parse_query(r#"{"sort": [{"property": "priority"}]}"#).unwrap();
+        assert!(
+            Comparator::validate(&request.sort.unwrap())
+                .unwrap_err()
+                .matches(trc::EventType::Jmap(trc::JmapEvent::UnsupportedSort))
+        );
+    }
+}
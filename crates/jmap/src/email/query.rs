@@ -38,6 +38,10 @@ impl EmailQuery for Server {
         mut request: QueryRequest<QueryArguments>,
         access_token: &AccessToken,
     ) -> trc::Result<QueryResponse> {
+        if let Some(sort) = &request.sort {
+            Comparator::validate(sort)?;
+        }
+
         let account_id = request.account_id.document_id();
         let mut filters = Vec::with_capacity(request.filter.len());
         let cached_messages = self
@@ -1,5 +1,6 @@
 //! Configuration for Kubernetes operator
 
+use crate::healing::SelfHealingConfig;
 use serde::{Deserialize, Serialize};
 
 /// Configuration for Kubernetes operator
@@ -9,6 +10,9 @@ pub struct OperatorConfig {
     pub namespace: String,
     /// Operator name
     pub name: String,
+    /// Automatic restart of unhealthy pods
+    #[serde(default)]
+    pub self_healing: SelfHealingConfig,
 }
 
 impl Default for OperatorConfig {
@@ -16,6 +20,7 @@ impl Default for OperatorConfig {
         Self {
             namespace: "default".to_string(),
             name: "stalwart-operator".to_string(),
+            self_healing: SelfHealingConfig::default(),
         }
     }
 }
@@ -1,14 +1,401 @@
 //! Kubernetes controllers
 
+use crate::crd::{ConditionStatus, ConditionType, StalwartMailServer, StalwartMailServerStatus};
+use crate::events::{EventReason, EventRecorder, OperatorEvent};
+use crate::error::Result;
+use crate::DeploymentPhase;
+use tracing::{info, warn};
+
 /// Mail server controller
 pub struct MailServerController;
 
 /// Controller context
 pub struct ControllerContext;
 
+/// State of the managed workload as observed in the cluster
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObservedState {
+    /// Replicas currently configured on the StatefulSet
+    pub replicas: i32,
+    /// Replicas passing readiness checks
+    pub ready_replicas: i32,
+    /// Replicas running the desired revision
+    pub updated_replicas: i32,
+    /// Version currently deployed
+    pub version: String,
+}
+
+/// Changes the reconcile wants applied to the workload
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReconcileActions {
+    pub scale_to: Option<i32>,
+    pub update_to: Option<String>,
+}
+
 impl MailServerController {
     /// Create new controller
     pub fn new() -> Self {
         Self
     }
+
+    /// Reconcile a mail server against the observed state, updating its
+    /// status conditions and recording events for significant actions
+    ///
+    /// Scale and update events are recorded when the action is first issued,
+    /// that is when the previous Progressing condition did not already cover
+    /// the same target, so a pending action is not reported on every pass.
+    /// Failing to publish an event is logged and does not fail the reconcile.
+    pub async fn reconcile(
+        &self,
+        server: &mut StalwartMailServer,
+        observed: &ObservedState,
+        recorder: &dyn EventRecorder,
+    ) -> Result<ReconcileActions> {
+        let spec = &server.spec;
+        let mut actions = ReconcileActions::default();
+        if observed.replicas != spec.replicas {
+            actions.scale_to = Some(spec.replicas);
+        }
+        if observed.version != spec.version {
+            actions.update_to = Some(spec.version.clone());
+        }
+
+        let status = server.status.get_or_insert_with(StalwartMailServerStatus::default);
+        let previous = status
+            .condition(ConditionType::Progressing)
+            .filter(|condition| condition.status == ConditionStatus::True)
+            .map(|condition| condition.message.clone())
+            .unwrap_or_default();
+        status.ready_replicas = observed.ready_replicas;
+
+        let rolling_out = actions.update_to.is_some() || observed.updated_replicas < spec.replicas;
+        let scaling = actions.scale_to.is_some();
+        let update_target = format!("Updating to {}", spec.version);
+        let scale_target = format!("to {} replicas", spec.replicas);
+
+        if rolling_out {
+            status.set_condition(
+                ConditionType::Progressing,
+                ConditionStatus::True,
+                "RollingUpdate",
+                if scaling {
+                    format!("{}, scaling {}", update_target, scale_target)
+                } else {
+                    update_target.clone()
+                },
+            );
+        } else if scaling {
+            status.set_condition(
+                ConditionType::Progressing,
+                ConditionStatus::True,
+                "Scaling",
+                format!("Scaling {}", scale_target),
+            );
+        } else {
+            status.set_condition(
+                ConditionType::Progressing,
+                ConditionStatus::False,
+                "RolloutComplete",
+                "All replicas are up to date",
+            );
+        }
+
+        let mut events = Vec::new();
+        if scaling && !previous.ends_with(&scale_target) {
+            events.push(OperatorEvent::new(
+                EventReason::Scaled,
+                "Scale",
+                format!("Scaling from {} to {} replicas", observed.replicas, spec.replicas),
+            ));
+        }
+        if actions.update_to.is_some()
+            && previous != update_target
+            && !previous.starts_with(&format!("{},", update_target))
+        {
+            events.push(OperatorEvent::new(
+                EventReason::Updated,
+                "Update",
+                format!("Updating from {} to {}", observed.version, spec.version),
+            ));
+        }
+        for event in events {
+            if let Err(err) = recorder.publish(event).await {
+                warn!("Failed to publish reconcile event: {}", err);
+            }
+        }
+
+        if observed.ready_replicas > 0 {
+            status.set_condition(
+                ConditionType::Available,
+                ConditionStatus::True,
+                "MinimumReplicasAvailable",
+                format!("{} replicas ready", observed.ready_replicas),
+            );
+        } else {
+            status.set_condition(
+                ConditionType::Available,
+                ConditionStatus::False,
+                "NoReplicasAvailable",
+                "No replicas are ready",
+            );
+        }
+
+        if observed.ready_replicas < spec.replicas && !rolling_out && !scaling {
+            status.set_condition(
+                ConditionType::Degraded,
+                ConditionStatus::True,
+                "InsufficientReplicas",
+                format!("{} of {} replicas ready", observed.ready_replicas, spec.replicas),
+            );
+        } else {
+            status.set_condition(
+                ConditionType::Degraded,
+                ConditionStatus::False,
+                "AsExpected",
+                "",
+            );
+        }
+
+        status.phase = format!(
+            "{:?}",
+            if rolling_out {
+                DeploymentPhase::Updating
+            } else if scaling {
+                DeploymentPhase::Scaling
+            } else {
+                DeploymentPhase::Running
+            }
+        );
+
+        Ok(actions)
+    }
+
+    /// Record that a backup was triggered for a mail server
+    pub async fn record_backup_triggered(
+        &self,
+        backup_name: &str,
+        recorder: &dyn EventRecorder,
+    ) -> Result<()> {
+        info!("Backup {} triggered", backup_name);
+        recorder
+            .publish(OperatorEvent::new(
+                EventReason::BackupTriggered,
+                "Backup",
+                format!("Triggered backup {}", backup_name),
+            ))
+            .await
+    }
+
+    /// Mark a reconcile as failed
+    pub async fn record_failure(
+        &self,
+        server: &mut StalwartMailServer,
+        error: &str,
+        recorder: &dyn EventRecorder,
+    ) -> Result<()> {
+        warn!("Reconcile failed: {}", error);
+        let status = server.status.get_or_insert_with(StalwartMailServerStatus::default);
+        status.set_condition(
+            ConditionType::Degraded,
+            ConditionStatus::True,
+            "ReconcileFailed",
+            error,
+        );
+        status.phase = format!("{:?}", DeploymentPhase::Failed);
+
+        recorder
+            .publish(OperatorEvent::new(EventReason::ReconcileFailed, "Reconcile", error))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crd::StalwartMailServerSpec;
+    use crate::events::EventType;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct FakeRecorder {
+        events: Mutex<Vec<OperatorEvent>>,
+    }
+
+    #[async_trait]
+    impl EventRecorder for FakeRecorder {
+        async fn publish(&self, event: OperatorEvent) -> Result<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    struct FailingRecorder;
+
+    #[async_trait]
+    impl EventRecorder for FailingRecorder {
+        async fn publish(&self, _event: OperatorEvent) -> Result<()> {
+            Err(crate::error::OperatorError::KubernetesClient(
+                "events API unavailable".to_string(),
+            ))
+        }
+    }
+
+    fn server(replicas: i32, version: &str) -> StalwartMailServer {
+        StalwartMailServer {
+            spec: StalwartMailServerSpec {
+                replicas,
+                image: "stalwartlabs/mail-server".to_string(),
+                version: version.to_string(),
+            },
+            status: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rollout_sets_progressing() {
+        let controller = MailServerController::new();
+        let recorder = FakeRecorder::default();
+        let mut server = server(3, "0.13.1");
+
+        let actions = controller
+            .reconcile(
+                &mut server,
+                &ObservedState {
+                    replicas: 3,
+                    ready_replicas: 3,
+                    updated_replicas: 0,
+                    version: "0.13.0".to_string(),
+                },
+                &recorder,
+            )
+            .await
+            .unwrap();
+        assert_eq!(actions.update_to.as_deref(), Some("0.13.1"));
+
+        let status = server.status.as_ref().unwrap();
+        let progressing = status.condition(ConditionType::Progressing).unwrap();
+        assert_eq!(progressing.status, ConditionStatus::True);
+        assert_eq!(progressing.reason, "RollingUpdate");
+        assert!(status.is_condition_true(ConditionType::Available));
+        assert!(!status.is_condition_true(ConditionType::Degraded));
+
+        let events = recorder.events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].reason, EventReason::Updated);
+        assert_eq!(events[0].event_type, EventType::Normal);
+    }
+
+    #[tokio::test]
+    async fn test_steady_state_and_degraded() {
+        let controller = MailServerController::new();
+        let recorder = FakeRecorder::default();
+        let mut server = server(3, "0.13.1");
+        let mut observed = ObservedState {
+            replicas: 3,
+            ready_replicas: 3,
+            updated_replicas: 3,
+            version: "0.13.1".to_string(),
+        };
+
+        controller.reconcile(&mut server, &observed, &recorder).await.unwrap();
+        let status = server.status.as_ref().unwrap();
+        assert!(!status.is_condition_true(ConditionType::Progressing));
+        assert!(!status.is_condition_true(ConditionType::Degraded));
+        assert!(recorder.events.lock().unwrap().is_empty());
+
+        observed.ready_replicas = 1;
+        controller.reconcile(&mut server, &observed, &recorder).await.unwrap();
+        assert!(server.status.as_ref().unwrap().is_condition_true(ConditionType::Degraded));
+    }
+
+    #[tokio::test]
+    async fn test_events_recorded_once_per_action() {
+        let controller = MailServerController::new();
+        let recorder = FakeRecorder::default();
+        let mut server = server(3, "0.13.1");
+        let observed = ObservedState {
+            replicas: 1,
+            ready_replicas: 1,
+            updated_replicas: 1,
+            version: "0.13.1".to_string(),
+        };
+
+        // The scale is still pending on the second pass and is not reported again
+        for _ in 0..2 {
+            let actions = controller.reconcile(&mut server, &observed, &recorder).await.unwrap();
+            assert_eq!(actions.scale_to, Some(3));
+        }
+        {
+            let events = recorder.events.lock().unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].reason, EventReason::Scaled);
+            assert_eq!(events[0].note, "Scaling from 1 to 3 replicas");
+        }
+
+        // A new target is a new action
+        server.spec.replicas = 5;
+        controller.reconcile(&mut server, &observed, &recorder).await.unwrap();
+        assert_eq!(recorder.events.lock().unwrap().len(), 2);
+
+        // Starting an update while scaling reports only the update
+        server.spec.version = "0.13.2".to_string();
+        controller.reconcile(&mut server, &observed, &recorder).await.unwrap();
+        controller.reconcile(&mut server, &observed, &recorder).await.unwrap();
+        let events = recorder.events.lock().unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[2].reason, EventReason::Updated);
+    }
+
+    #[tokio::test]
+    async fn test_publish_failure_keeps_status() {
+        let controller = MailServerController::new();
+        let mut server = server(3, "0.13.1");
+
+        let actions = controller
+            .reconcile(
+                &mut server,
+                &ObservedState {
+                    replicas: 1,
+                    ready_replicas: 1,
+                    updated_replicas: 1,
+                    version: "0.13.0".to_string(),
+                },
+                &FailingRecorder,
+            )
+            .await
+            .unwrap();
+        assert_eq!(actions.scale_to, Some(3));
+        assert_eq!(actions.update_to.as_deref(), Some("0.13.1"));
+        assert!(server
+            .status
+            .as_ref()
+            .unwrap()
+            .is_condition_true(ConditionType::Progressing));
+    }
+
+    #[tokio::test]
+    async fn test_failure_records_warning() {
+        let controller = MailServerController::new();
+        let recorder = FakeRecorder::default();
+        let mut server = server(1, "0.13.1");
+
+        controller
+            .record_failure(&mut server, "statefulset patch rejected", &recorder)
+            .await
+            .unwrap();
+
+        let degraded = server
+            .status
+            .as_ref()
+            .unwrap()
+            .condition(ConditionType::Degraded)
+            .cloned()
+            .unwrap();
+        assert_eq!(degraded.reason, "ReconcileFailed");
+        assert_eq!(
+            recorder.events.lock().unwrap()[0].event_type,
+            EventType::Warning
+        );
+    }
 }
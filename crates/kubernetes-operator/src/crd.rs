@@ -1,5 +1,6 @@
 //! Custom Resource Definitions
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Stalwart Mail Server CRD
@@ -18,8 +19,85 @@ pub struct StalwartMailServerSpec {
 }
 
 /// Stalwart Mail Server status
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StalwartMailServerStatus {
     pub ready_replicas: i32,
     pub phase: String,
+    /// Standard status conditions
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+}
+
+/// Condition type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConditionType {
+    /// At least one replica is serving traffic
+    Available,
+    /// A rollout or scaling operation is in progress
+    Progressing,
+    /// The deployment is running below its desired capacity or reconcile failed
+    Degraded,
+}
+
+/// Condition status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConditionStatus {
+    True,
+    False,
+    Unknown,
+}
+
+/// Status condition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Condition {
+    #[serde(rename = "type")]
+    pub type_: ConditionType,
+    pub status: ConditionStatus,
+    pub reason: String,
+    pub message: String,
+    pub last_transition_time: DateTime<Utc>,
+}
+
+impl StalwartMailServerStatus {
+    /// Get a condition by type
+    pub fn condition(&self, type_: ConditionType) -> Option<&Condition> {
+        self.conditions.iter().find(|c| c.type_ == type_)
+    }
+
+    /// Set a condition, keeping the transition time if the status did not change
+    pub fn set_condition(
+        &mut self,
+        type_: ConditionType,
+        status: ConditionStatus,
+        reason: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        let reason = reason.into();
+        let message = message.into();
+
+        match self.conditions.iter_mut().find(|c| c.type_ == type_) {
+            Some(condition) => {
+                if condition.status != status {
+                    condition.status = status;
+                    condition.last_transition_time = Utc::now();
+                }
+                condition.reason = reason;
+                condition.message = message;
+            }
+            None => self.conditions.push(Condition {
+                type_,
+                status,
+                reason,
+                message,
+                last_transition_time: Utc::now(),
+            }),
+        }
+    }
+
+    /// Check whether a condition is currently true
+    pub fn is_condition_true(&self, type_: ConditionType) -> bool {
+        self.condition(type_)
+            .is_some_and(|c| c.status == ConditionStatus::True)
+    }
 }
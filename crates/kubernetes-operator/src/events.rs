@@ -0,0 +1,110 @@
+//! Kubernetes event recording
+
+use crate::error::{OperatorError, Result};
+use async_trait::async_trait;
+use k8s_openapi::api::core::v1::ObjectReference;
+use kube::runtime::events::{Event, EventType as KubeEventType, Recorder, Reporter};
+
+/// Event type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventType {
+    Normal,
+    Warning,
+}
+
+/// Reason for a significant reconcile action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventReason {
+    Scaled,
+    Updated,
+    BackupTriggered,
+    PodRestarted,
+    ReconcileFailed,
+}
+
+impl EventReason {
+    /// Reason string shown by `kubectl describe`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventReason::Scaled => "Scaled",
+            EventReason::Updated => "Updated",
+            EventReason::BackupTriggered => "BackupTriggered",
+            EventReason::PodRestarted => "PodRestarted",
+            EventReason::ReconcileFailed => "ReconcileFailed",
+        }
+    }
+
+    /// Default event type for this reason
+    pub fn event_type(&self) -> EventType {
+        match self {
+            EventReason::ReconcileFailed | EventReason::PodRestarted => EventType::Warning,
+            _ => EventType::Normal,
+        }
+    }
+}
+
+/// Event emitted by the operator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperatorEvent {
+    pub event_type: EventType,
+    pub reason: EventReason,
+    pub action: String,
+    pub note: String,
+}
+
+impl OperatorEvent {
+    /// Create new event
+    pub fn new(reason: EventReason, action: impl Into<String>, note: impl Into<String>) -> Self {
+        Self {
+            event_type: reason.event_type(),
+            reason,
+            action: action.into(),
+            note: note.into(),
+        }
+    }
+}
+
+/// Sink for operator events
+#[async_trait]
+pub trait EventRecorder: Send + Sync {
+    /// Publish an event for the reconciled object
+    async fn publish(&self, event: OperatorEvent) -> Result<()>;
+}
+
+/// Event recorder backed by the Kubernetes events API
+pub struct KubeEventRecorder {
+    recorder: Recorder,
+}
+
+impl KubeEventRecorder {
+    /// Create new recorder for an object
+    pub fn new(client: kube::Client, controller: &str, reference: ObjectReference) -> Self {
+        let reporter = Reporter {
+            controller: controller.to_string(),
+            instance: std::env::var("POD_NAME").ok(),
+        };
+
+        Self {
+            recorder: Recorder::new(client, reporter, reference),
+        }
+    }
+}
+
+#[async_trait]
+impl EventRecorder for KubeEventRecorder {
+    async fn publish(&self, event: OperatorEvent) -> Result<()> {
+        self.recorder
+            .publish(Event {
+                type_: match event.event_type {
+                    EventType::Normal => KubeEventType::Normal,
+                    EventType::Warning => KubeEventType::Warning,
+                },
+                reason: event.reason.as_str().to_string(),
+                note: Some(event.note),
+                action: event.action,
+                secondary: None,
+            })
+            .await
+            .map_err(|e| OperatorError::KubernetesClient(e.to_string()))
+    }
+}
@@ -0,0 +1,398 @@
+//! Self-healing restart controller
+//!
+//! Pods that fail readiness for longer than the configured threshold are
+//! deleted so the StatefulSet recreates them. Restarts of the same pod back
+//! off exponentially, and a global cap bounds how many pods may be
+//! recovering from a restart at once. The backoff is only reset once a pod
+//! stays ready for [`STABILITY_THRESHOLDS`] times the unready threshold, so a
+//! flapping pod keeps backing off.
+
+use crate::crd::{ConditionStatus, ConditionType, StalwartMailServer, StalwartMailServerStatus};
+use crate::error::{OperatorError, Result};
+use crate::events::{EventReason, EventRecorder, OperatorEvent};
+use async_trait::async_trait;
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::{Api, DeleteParams};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+/// Multiple of the unready threshold a pod must stay ready for before its
+/// restart backoff is reset
+pub const STABILITY_THRESHOLDS: u32 = 3;
+
+/// Self-healing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfHealingConfig {
+    /// Enable automatic pod restarts
+    pub enabled: bool,
+    /// How long a pod must be unready before it is restarted
+    pub unready_threshold: Duration,
+    /// Minimum delay between restarts of the same pod
+    pub backoff_base: Duration,
+    /// Maximum delay between restarts of the same pod
+    pub backoff_max: Duration,
+    /// Maximum number of pods recovering from a restart at the same time
+    pub max_concurrent_restarts: usize,
+}
+
+impl Default for SelfHealingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            unready_threshold: Duration::from_secs(300),
+            backoff_base: Duration::from_secs(60),
+            backoff_max: Duration::from_secs(3600),
+            max_concurrent_restarts: 1,
+        }
+    }
+}
+
+/// Readiness of a managed pod
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PodHealth {
+    pub name: String,
+    pub ready: bool,
+}
+
+/// Deletes pods so their controller recreates them
+#[async_trait]
+pub trait PodRestarter: Send + Sync {
+    /// Delete a pod
+    async fn restart_pod(&self, name: &str) -> Result<()>;
+}
+
+/// Pod restarter backed by the Kubernetes API
+pub struct KubePodRestarter {
+    pods: Api<Pod>,
+}
+
+impl KubePodRestarter {
+    /// Create new pod restarter for a namespace
+    pub fn new(client: kube::Client, namespace: &str) -> Self {
+        Self {
+            pods: Api::namespaced(client, namespace),
+        }
+    }
+}
+
+#[async_trait]
+impl PodRestarter for KubePodRestarter {
+    async fn restart_pod(&self, name: &str) -> Result<()> {
+        self.pods
+            .delete(name, &DeleteParams::default())
+            .await
+            .map(|_| ())
+            .map_err(|e| OperatorError::KubernetesClient(e.to_string()))
+    }
+}
+
+#[derive(Debug, Default)]
+struct PodTracker {
+    unready_since: Option<Instant>,
+    ready_since: Option<Instant>,
+    last_restart: Option<Instant>,
+    restart_count: u32,
+}
+
+/// Restart controller
+pub struct RestartController {
+    config: SelfHealingConfig,
+    pods: RwLock<HashMap<String, PodTracker>>,
+}
+
+impl RestartController {
+    /// Create new restart controller
+    pub fn new(config: SelfHealingConfig) -> Self {
+        Self {
+            config,
+            pods: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Evaluate pod readiness and restart pods that stayed unready too long,
+    /// returning the names of the restarted pods
+    pub async fn evaluate(
+        &self,
+        server: &mut StalwartMailServer,
+        pods: &[PodHealth],
+        restarter: &dyn PodRestarter,
+        recorder: &dyn EventRecorder,
+    ) -> Result<Vec<String>> {
+        self.evaluate_at(Instant::now(), server, pods, restarter, recorder)
+            .await
+    }
+
+    async fn evaluate_at(
+        &self,
+        now: Instant,
+        server: &mut StalwartMailServer,
+        pods: &[PodHealth],
+        restarter: &dyn PodRestarter,
+        recorder: &dyn EventRecorder,
+    ) -> Result<Vec<String>> {
+        let mut restarted = Vec::new();
+        if !self.config.enabled {
+            return Ok(restarted);
+        }
+
+        let mut trackers = self.pods.write().await;
+        trackers.retain(|name, _| pods.iter().any(|pod| &pod.name == name));
+
+        // Pods restarted within the threshold are still recovering
+        let mut recovering = trackers
+            .values()
+            .filter(|tracker| {
+                tracker
+                    .last_restart
+                    .is_some_and(|at| now.duration_since(at) < self.config.unready_threshold)
+            })
+            .count();
+
+        for pod in pods {
+            let tracker = trackers.entry(pod.name.clone()).or_default();
+
+            if pod.ready {
+                tracker.unready_since = None;
+                let ready_since = *tracker.ready_since.get_or_insert(now);
+                if now.duration_since(ready_since)
+                    >= self.config.unready_threshold * STABILITY_THRESHOLDS
+                {
+                    tracker.restart_count = 0;
+                }
+                continue;
+            }
+
+            tracker.ready_since = None;
+            let unready_since = *tracker.unready_since.get_or_insert(now);
+            if now.duration_since(unready_since) < self.config.unready_threshold {
+                continue;
+            }
+
+            if let Some(last_restart) = tracker.last_restart {
+                if now.duration_since(last_restart) < self.backoff(tracker.restart_count) {
+                    continue;
+                }
+            }
+
+            if recovering >= self.config.max_concurrent_restarts {
+                warn!(
+                    "Not restarting pod {}: {} pods already recovering",
+                    pod.name, recovering
+                );
+                continue;
+            }
+
+            restarter.restart_pod(&pod.name).await?;
+            info!("Restarted unready pod {}", pod.name);
+
+            tracker.unready_since = None;
+            tracker.last_restart = Some(now);
+            tracker.restart_count += 1;
+            recovering += 1;
+            restarted.push(pod.name.clone());
+
+            recorder
+                .publish(OperatorEvent::new(
+                    EventReason::PodRestarted,
+                    "Restart",
+                    format!(
+                        "Restarted pod {} after failing readiness for {}s (restart #{})",
+                        pod.name,
+                        self.config.unready_threshold.as_secs(),
+                        tracker.restart_count
+                    ),
+                ))
+                .await?;
+        }
+
+        if !restarted.is_empty() {
+            server
+                .status
+                .get_or_insert_with(StalwartMailServerStatus::default)
+                .set_condition(
+                    ConditionType::Degraded,
+                    ConditionStatus::True,
+                    "PodRestarted",
+                    format!("Restarted unready pods: {}", restarted.join(", ")),
+                );
+        }
+
+        Ok(restarted)
+    }
+
+    fn backoff(&self, restart_count: u32) -> Duration {
+        let exponent = restart_count.saturating_sub(1).min(16);
+        self.config
+            .backoff_base
+            .saturating_mul(1 << exponent)
+            .min(self.config.backoff_max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crd::StalwartMailServerSpec;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct FakeCluster {
+        deleted: Mutex<Vec<String>>,
+        events: Mutex<Vec<OperatorEvent>>,
+    }
+
+    #[async_trait]
+    impl PodRestarter for FakeCluster {
+        async fn restart_pod(&self, name: &str) -> Result<()> {
+            self.deleted.lock().unwrap().push(name.to_string());
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl EventRecorder for FakeCluster {
+        async fn publish(&self, event: OperatorEvent) -> Result<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    fn server() -> StalwartMailServer {
+        StalwartMailServer {
+            spec: StalwartMailServerSpec {
+                replicas: 3,
+                image: "stalwartlabs/mail-server".to_string(),
+                version: "0.13.1".to_string(),
+            },
+            status: None,
+        }
+    }
+
+    fn config() -> SelfHealingConfig {
+        SelfHealingConfig {
+            enabled: true,
+            unready_threshold: Duration::from_secs(60),
+            backoff_base: Duration::from_secs(600),
+            backoff_max: Duration::from_secs(3600),
+            max_concurrent_restarts: 1,
+        }
+    }
+
+    fn pod(name: &str, ready: bool) -> PodHealth {
+        PodHealth {
+            name: name.to_string(),
+            ready,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restart_after_threshold_with_backoff() {
+        let controller = RestartController::new(config());
+        let cluster = FakeCluster::default();
+        let mut server = server();
+        let pods = [pod("mail-0", false), pod("mail-1", true)];
+        let start = Instant::now();
+
+        // Not yet past the threshold
+        for secs in [0, 30] {
+            let restarted = controller
+                .evaluate_at(start + Duration::from_secs(secs), &mut server, &pods, &cluster, &cluster)
+                .await
+                .unwrap();
+            assert!(restarted.is_empty());
+        }
+
+        let restarted = controller
+            .evaluate_at(start + Duration::from_secs(61), &mut server, &pods, &cluster, &cluster)
+            .await
+            .unwrap();
+        assert_eq!(restarted, vec!["mail-0".to_string()]);
+        assert!(server.status.as_ref().unwrap().is_condition_true(ConditionType::Degraded));
+        assert_eq!(cluster.events.lock().unwrap()[0].reason, EventReason::PodRestarted);
+
+        // Still unready past another threshold, but within the backoff window
+        for secs in [130, 300, 600] {
+            controller
+                .evaluate_at(start + Duration::from_secs(secs), &mut server, &pods, &cluster, &cluster)
+                .await
+                .unwrap();
+        }
+        assert_eq!(cluster.deleted.lock().unwrap().len(), 1);
+
+        // Backoff elapsed
+        let restarted = controller
+            .evaluate_at(start + Duration::from_secs(662), &mut server, &pods, &cluster, &cluster)
+            .await
+            .unwrap();
+        assert_eq!(restarted, vec!["mail-0".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_global_restart_cap() {
+        let controller = RestartController::new(config());
+        let cluster = FakeCluster::default();
+        let mut server = server();
+        let pods = [pod("mail-0", false), pod("mail-1", false), pod("mail-2", false)];
+        let start = Instant::now();
+
+        controller
+            .evaluate_at(start, &mut server, &pods, &cluster, &cluster)
+            .await
+            .unwrap();
+        let restarted = controller
+            .evaluate_at(start + Duration::from_secs(61), &mut server, &pods, &cluster, &cluster)
+            .await
+            .unwrap();
+        assert_eq!(restarted.len(), 1);
+
+        // The first restart has recovered window-wise, so one more may go
+        let restarted = controller
+            .evaluate_at(start + Duration::from_secs(122), &mut server, &pods, &cluster, &cluster)
+            .await
+            .unwrap();
+        assert_eq!(restarted.len(), 1);
+        assert_eq!(cluster.deleted.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_flapping_pod_keeps_backing_off() {
+        let controller = RestartController::new(config());
+        let cluster = FakeCluster::default();
+        let mut server = server();
+        let start = Instant::now();
+
+        for (secs, ready, restarts) in [
+            (0, false, 0),
+            (61, false, 1),
+            // Ready only briefly after each restart
+            (100, true, 0),
+            (110, false, 0),
+            (661, false, 1),
+            (700, true, 0),
+            (710, false, 0),
+            // The backoff doubled instead of starting over
+            (1400, false, 0),
+            (1861, false, 1),
+            // Staying ready for the stability window resets it
+            (1900, true, 0),
+            (2080, true, 0),
+            (2090, false, 0),
+            (2500, false, 1),
+        ] {
+            let restarted = controller
+                .evaluate_at(
+                    start + Duration::from_secs(secs),
+                    &mut server,
+                    &[pod("mail-0", ready)],
+                    &cluster,
+                    &cluster,
+                )
+                .await
+                .unwrap();
+            assert_eq!(restarted.len(), restarts, "at {}s", secs);
+        }
+    }
+}
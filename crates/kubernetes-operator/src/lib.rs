@@ -47,6 +47,8 @@ pub mod config;
 pub mod manager;
 pub mod crd;
 pub mod controllers;
+pub mod events;
+pub mod healing;
 pub mod scaling;
 pub mod backup;
 pub mod monitoring;
@@ -56,8 +58,10 @@ pub mod error;
 
 pub use config::OperatorConfig;
 pub use manager::OperatorManager;
-pub use crd::{StalwartMailServer, StalwartMailServerSpec, StalwartMailServerStatus};
-pub use controllers::{MailServerController, ControllerContext};
+pub use crd::{StalwartMailServer, StalwartMailServerSpec, StalwartMailServerStatus, Condition, ConditionType, ConditionStatus};
+pub use controllers::{MailServerController, ControllerContext, ObservedState, ReconcileActions};
+pub use healing::{RestartController, SelfHealingConfig, PodHealth, PodRestarter, KubePodRestarter};
+pub use events::{EventRecorder, KubeEventRecorder, OperatorEvent, EventReason, EventType};
 pub use scaling::{AutoScaler, ScalingPolicy, ScalingMetrics};
 pub use backup::{BackupController, BackupPolicy, RestoreOperation};
 pub use error::{OperatorError, Result};
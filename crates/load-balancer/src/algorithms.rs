@@ -2,8 +2,10 @@
 
 use crate::backend::Backend;
 use crate::error::{LoadBalancerError, Result};
+use crate::hash_ring::HashRing;
 use async_trait::async_trait;
 use std::sync::Arc;
+use tokio::sync::RwLock;
 
 /// Load balancing algorithm types
 #[derive(Debug, Clone)]
@@ -13,6 +15,7 @@ pub enum LoadBalancingAlgorithm {
     WeightedRoundRobin,
     IpHash,
     Random,
+    ConsistentHash { virtual_nodes: u32 },
 }
 
 /// Load balancer trait for selecting backends
@@ -21,6 +24,12 @@ pub trait LoadBalancer: Send + Sync {
     /// Select a backend for the request
     async fn select_backend(&self, backends: &[Arc<Backend>]) -> Result<Option<Arc<Backend>>>;
 
+    /// Select a backend for a request identified by a key, such as the
+    /// client IP or session affinity key
+    async fn select_backend_for_key(&self, backends: &[Arc<Backend>], _key: &str) -> Result<Option<Arc<Backend>>> {
+        self.select_backend(backends).await
+    }
+
     /// Update algorithm state after request completion
     async fn update_state(&self, backend: &Backend, success: bool) -> Result<()>;
 }
@@ -104,11 +113,65 @@ impl LoadBalancer for LeastConnectionsBalancer {
     }
 }
 
+/// Consistent-hash load balancer
+///
+/// Requests with the same key go to the same backend, and adding or removing
+/// a backend only moves the keys that backend gains or loses.
+#[derive(Debug, Clone)]
+pub struct ConsistentHashBalancer {
+    virtual_nodes: u32,
+    ring: Arc<RwLock<HashRing>>,
+    /// Used for requests without a key
+    fallback: RoundRobinBalancer,
+}
+
+impl ConsistentHashBalancer {
+    pub fn new(virtual_nodes: u32) -> Self {
+        Self {
+            virtual_nodes,
+            ring: Arc::new(RwLock::new(HashRing::new(virtual_nodes))),
+            fallback: RoundRobinBalancer::new(),
+        }
+    }
+
+    /// Get the fraction of keys that would remap if a backend were added
+    pub fn remap_fraction_on_add(&self, backends: &[Arc<Backend>], backend_id: &str, weight: u32) -> f64 {
+        HashRing::from_backends(backends, self.virtual_nodes).remap_fraction_on_add(backend_id, weight)
+    }
+}
+
+#[async_trait]
+impl LoadBalancer for ConsistentHashBalancer {
+    async fn select_backend(&self, backends: &[Arc<Backend>]) -> Result<Option<Arc<Backend>>> {
+        self.fallback.select_backend(backends).await
+    }
+
+    async fn select_backend_for_key(&self, backends: &[Arc<Backend>], key: &str) -> Result<Option<Arc<Backend>>> {
+        if backends.is_empty() {
+            return Ok(None);
+        }
+
+        // Rebuild the ring when backends or their weights changed
+        if !self.ring.read().await.is_built_from(backends) {
+            *self.ring.write().await = HashRing::from_backends(backends, self.virtual_nodes);
+        }
+
+        let ring = self.ring.read().await;
+        Ok(ring.get(key).and_then(|id| backends.iter().find(|backend| backend.id == id)).cloned())
+    }
+
+    async fn update_state(&self, _backend: &Backend, _success: bool) -> Result<()> {
+        // The ring only depends on the backends passed in
+        Ok(())
+    }
+}
+
 /// Load balancer implementation enum
 #[derive(Debug, Clone)]
 pub enum LoadBalancerImpl {
     RoundRobin(RoundRobinBalancer),
     LeastConnections(LeastConnectionsBalancer),
+    ConsistentHash(ConsistentHashBalancer),
 }
 
 impl LoadBalancerImpl {
@@ -117,6 +180,16 @@ impl LoadBalancerImpl {
         match self {
             Self::RoundRobin(balancer) => balancer.select_backend(backends).await,
             Self::LeastConnections(balancer) => balancer.select_backend(backends).await,
+            Self::ConsistentHash(balancer) => balancer.select_backend(backends).await,
+        }
+    }
+
+    /// Select a backend for a request identified by a key
+    pub async fn select_backend_for_key(&self, backends: &[Arc<Backend>], key: &str) -> Result<Option<Arc<Backend>>> {
+        match self {
+            Self::RoundRobin(balancer) => balancer.select_backend_for_key(backends, key).await,
+            Self::LeastConnections(balancer) => balancer.select_backend_for_key(backends, key).await,
+            Self::ConsistentHash(balancer) => balancer.select_backend_for_key(backends, key).await,
         }
     }
 
@@ -125,6 +198,7 @@ impl LoadBalancerImpl {
         match self {
             Self::RoundRobin(balancer) => balancer.update_state(backend, success).await,
             Self::LeastConnections(balancer) => balancer.update_state(backend, success).await,
+            Self::ConsistentHash(balancer) => balancer.update_state(backend, success).await,
         }
     }
 }
@@ -137,5 +211,90 @@ pub fn create_load_balancer(algorithm: LoadBalancingAlgorithm) -> LoadBalancerIm
         LoadBalancingAlgorithm::WeightedRoundRobin => LoadBalancerImpl::RoundRobin(RoundRobinBalancer::new()), // TODO: Implement weighted
         LoadBalancingAlgorithm::IpHash => LoadBalancerImpl::RoundRobin(RoundRobinBalancer::new()), // TODO: Implement IP hash
         LoadBalancingAlgorithm::Random => LoadBalancerImpl::RoundRobin(RoundRobinBalancer::new()), // TODO: Implement random
+        LoadBalancingAlgorithm::ConsistentHash { virtual_nodes } => LoadBalancerImpl::ConsistentHash(ConsistentHashBalancer::new(virtual_nodes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn backends(count: usize) -> Vec<Arc<Backend>> {
+        (1..=count)
+            .map(|i| Arc::new(Backend::new(format!("backend{}", i), "127.0.0.1".to_string(), 8000 + i as u16, 1)))
+            .collect()
+    }
+
+    async fn assignments(balancer: &LoadBalancerImpl, backends: &[Arc<Backend>], keys: &[String]) -> HashMap<String, String> {
+        let mut assignments = HashMap::new();
+        for key in keys {
+            let backend = balancer.select_backend_for_key(backends, key).await.unwrap().unwrap();
+            assignments.insert(key.clone(), backend.id.clone());
+        }
+        assignments
+    }
+
+    fn keys() -> Vec<String> {
+        (0..10000).map(|i| format!("user{}@example.com", i)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_consistent_hash_distribution() {
+        let balancer = create_load_balancer(LoadBalancingAlgorithm::ConsistentHash { virtual_nodes: 160 });
+        let pool = backends(4);
+        let keys = keys();
+
+        let assigned = assignments(&balancer, &pool, &keys).await;
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for backend_id in assigned.values() {
+            *counts.entry(backend_id).or_default() += 1;
+        }
+
+        // Every backend gets within 20% of an even share
+        let expected = keys.len() / pool.len();
+        assert_eq!(counts.len(), pool.len());
+        for (backend_id, count) in counts {
+            assert!(count > expected * 8 / 10 && count < expected * 12 / 10, "{} got {} keys", backend_id, count);
+        }
+
+        // The same key always maps to the same backend
+        assert_eq!(assignments(&balancer, &pool, &keys).await, assigned);
+    }
+
+    #[tokio::test]
+    async fn test_consistent_hash_remove_backend() {
+        let balancer = create_load_balancer(LoadBalancingAlgorithm::ConsistentHash { virtual_nodes: 160 });
+        let pool = backends(5);
+        let keys = keys();
+
+        let before = assignments(&balancer, &pool, &keys).await;
+        let remaining = pool.iter().filter(|backend| backend.id != "backend3").cloned().collect::<Vec<_>>();
+        let after = assignments(&balancer, &remaining, &keys).await;
+
+        // Only keys of the removed backend move, roughly 1/N of all keys
+        let moved = keys.iter().filter(|key| before[*key] != after[*key]).collect::<Vec<_>>();
+        assert!(moved.iter().all(|key| before[*key] == "backend3"));
+        let fraction = moved.len() as f64 / keys.len() as f64;
+        assert!(fraction > 0.5 / 5.0 && fraction < 1.5 / 5.0, "moved {}", fraction);
+    }
+
+    #[tokio::test]
+    async fn test_consistent_hash_remap_fraction() {
+        let balancer = ConsistentHashBalancer::new(160);
+        let pool = backends(4);
+        let keys = keys();
+
+        let fraction = balancer.remap_fraction_on_add(&pool[..3], "backend4", 1);
+        assert!(fraction > 0.5 / 4.0 && fraction < 1.5 / 4.0, "fraction {}", fraction);
+
+        // The prediction matches the keys that actually move
+        let before = assignments(&LoadBalancerImpl::ConsistentHash(balancer.clone()), &pool[..3], &keys).await;
+        let after = assignments(&LoadBalancerImpl::ConsistentHash(balancer.clone()), &pool, &keys).await;
+        let moved = keys.iter().filter(|key| before[*key] != after[*key]).count() as f64 / keys.len() as f64;
+        assert!((moved - fraction).abs() < 0.02, "predicted {}, moved {}", fraction, moved);
+
+        assert_eq!(balancer.remap_fraction_on_add(&[], "backend1", 1), 1.0);
+        assert_eq!(balancer.remap_fraction_on_add(&pool, "backend5", 0), 0.0);
     }
 }
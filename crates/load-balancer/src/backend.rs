@@ -4,7 +4,12 @@ use crate::error::Result;
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+/// How often a draining backend's connections are checked
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Backend server status
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -13,6 +18,8 @@ pub enum BackendStatus {
     Unhealthy,
     Draining,
     Disabled,
+    /// Temporarily out of rotation as an outlier
+    Ejected,
 }
 
 /// Backend server
@@ -121,17 +128,48 @@ impl BackendPool {
         Ok(backends.len() < initial_len)
     }
 
+    /// Stop routing new requests to a backend and wait for its in-flight
+    /// requests to finish
+    ///
+    /// Returns `false` if requests were still in flight when the timeout
+    /// passed or the backend does not exist.
+    pub async fn drain_backend(&self, backend_id: &str, timeout: Duration) -> Result<bool> {
+        if !self.set_status(backend_id, BackendStatus::Draining).await? {
+            return Ok(false);
+        }
+
+        let Some(backend) = self.get_backend(backend_id).await else {
+            return Ok(false);
+        };
+
+        info!("Draining backend {} ({} active connections)", backend_id, backend.get_active_connections().await);
+
+        let drained = tokio::time::timeout(timeout, async {
+            while backend.get_active_connections().await > 0 {
+                tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+            }
+        }).await.is_ok();
+
+        if !drained {
+            warn!("Backend {} still had {} active connections after {:?}",
+                  backend_id, backend.get_active_connections().await, timeout);
+        }
+        Ok(drained)
+    }
+
     /// Update backend weight
-    pub async fn update_weight(&self, backend_id: &str, weight: u32) -> Result<()> {
-        let backends = self.backends.read().await;
-        for backend in backends.iter() {
+    pub async fn update_weight(&self, backend_id: &str, weight: u32) -> Result<bool> {
+        let mut backends = self.backends.write().await;
+        for backend in backends.iter_mut() {
             if backend.id == backend_id {
-                // Note: This is a simplified implementation
-                // In a real implementation, we'd need interior mutability for weight
-                break;
+                // Counters are shared, so the replacement keeps the backend's statistics
+                let mut updated = (**backend).clone();
+                updated.weight = weight;
+                *backend = Arc::new(updated);
+                return Ok(true);
             }
         }
-        Ok(())
+        Ok(false)
     }
 
     /// Get all backend status
@@ -140,6 +178,35 @@ impl BackendPool {
         backends.iter().map(|b| ((**b).clone(), b.status.clone())).collect()
     }
 
+    /// Update backend status
+    pub async fn set_status(&self, backend_id: &str, status: BackendStatus) -> Result<bool> {
+        self.update_status(backend_id, None, status).await
+    }
+
+    /// Update backend status only if it is still `expected`, so a check that
+    /// started before a drain does not undo it
+    pub async fn compare_and_set_status(&self, backend_id: &str, expected: BackendStatus, status: BackendStatus) -> Result<bool> {
+        self.update_status(backend_id, Some(expected), status).await
+    }
+
+    async fn update_status(&self, backend_id: &str, expected: Option<BackendStatus>, status: BackendStatus) -> Result<bool> {
+        let mut backends = self.backends.write().await;
+        for backend in backends.iter_mut() {
+            if backend.id == backend_id && expected.as_ref().is_none_or(|expected| backend.status == *expected) {
+                let mut updated = (**backend).clone();
+                updated.status = status;
+                *backend = Arc::new(updated);
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Get all backends, including unavailable ones
+    pub async fn get_backends(&self) -> Vec<Arc<Backend>> {
+        self.backends.read().await.clone()
+    }
+
     /// Get healthy backends
     pub async fn get_healthy_backends(&self) -> Vec<Arc<Backend>> {
         let backends = self.backends.read().await;
@@ -184,3 +251,56 @@ impl BackendPool {
         stats
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{create_load_balancer, LoadBalancingAlgorithm};
+
+    async fn pool() -> BackendPool {
+        BackendPool::new(&[
+            Backend::new("backend1".to_string(), "127.0.0.1".to_string(), 8001, 1),
+            Backend::new("backend2".to_string(), "127.0.0.1".to_string(), 8002, 1),
+        ]).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_drain_waits_for_in_flight_requests() {
+        let pool = pool().await;
+        let backend = pool.get_backend("backend1").await.unwrap();
+        backend.increment_connections().await;
+
+        let drain = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.drain_backend("backend1", Duration::from_secs(5)).await.unwrap() }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // New requests only go to the remaining backend
+        assert_eq!(pool.get_backend("backend1").await.unwrap().status, BackendStatus::Draining);
+        let balancer = create_load_balancer(LoadBalancingAlgorithm::RoundRobin);
+        for _ in 0..10 {
+            let healthy = pool.get_healthy_backends().await;
+            assert_eq!(balancer.select_backend(&healthy).await.unwrap().unwrap().id, "backend2");
+        }
+        assert!(!drain.is_finished());
+
+        // The in-flight request completes through the handle it started with
+        backend.decrement_connections().await;
+        assert!(drain.await.unwrap());
+        assert!(pool.remove_backend("backend1").await.unwrap());
+        assert!(pool.get_backend("backend1").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_drain_timeout() {
+        let pool = pool().await;
+        pool.get_backend("backend1").await.unwrap().increment_connections().await;
+
+        let started = std::time::Instant::now();
+        assert!(!pool.drain_backend("backend1", Duration::from_millis(300)).await.unwrap());
+        assert!(started.elapsed() >= Duration::from_millis(300));
+
+        assert!(!pool.drain_backend("missing", Duration::from_millis(300)).await.unwrap());
+    }
+}
@@ -7,9 +7,16 @@ use serde::{Deserialize, Serialize};
 pub struct LoadBalancerConfig {
     pub enabled: bool,
     pub algorithm: String,
+    /// Points each backend owns on the hash ring per unit of weight, for `consistent_hash`
+    pub hash_virtual_nodes: u32,
     pub health_check_interval: u64,
+    /// Seconds a removed backend's in-flight requests may take to finish
+    pub drain_timeout: u64,
+    /// Seconds of recent requests per-backend latency and error metrics cover
+    pub metrics_window: u64,
     pub backends: Vec<crate::Backend>,
     pub health_check: HealthCheckConfig,
+    pub outlier_detection: OutlierDetectionConfig,
     pub session_affinity: Option<SessionAffinityConfig>,
     pub server: ServerConfig,
 }
@@ -18,13 +25,105 @@ pub struct LoadBalancerConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthCheckConfig {
     pub enabled: bool,
+    /// Seconds between checks
     pub interval: u64,
+    /// Seconds to wait for a response
+    pub timeout: u64,
+    pub path: String,
+    pub expected_status: u16,
+    /// Text the response body must contain
+    pub body_contains: Option<String>,
+    /// Slowest response still counted as healthy
+    pub max_latency_ms: Option<u64>,
+    /// Consecutive successes needed to mark an unhealthy backend healthy
+    pub healthy_threshold: u32,
+    /// Consecutive failures needed to mark a healthy backend unhealthy
+    pub unhealthy_threshold: u32,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: 30,
+            timeout: 5,
+            path: "/health".to_string(),
+            expected_status: 200,
+            body_contains: None,
+            max_latency_ms: None,
+            healthy_threshold: 2,
+            unhealthy_threshold: 3,
+        }
+    }
+}
+
+/// Outlier detection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlierDetectionConfig {
+    pub enabled: bool,
+    /// Seconds between evaluations
+    pub interval: u64,
+    /// Requests a backend needs in the metrics window to be evaluated
+    pub min_requests: u64,
+    /// Multiple of the pool's median p50 latency that makes a backend an outlier
+    pub latency_multiplier: f64,
+    /// Multiple of the pool's median error rate that makes a backend an outlier
+    pub error_rate_multiplier: f64,
+    /// Error rate never treated as an outlier, even when the rest of the pool has no errors
+    pub min_error_rate: f64,
+    /// Seconds of the first ejection, each repeated ejection adds as much again
+    pub base_ejection_time: u64,
+    pub max_ejection_time: u64,
+}
+
+impl Default for OutlierDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: 10,
+            min_requests: 20,
+            latency_multiplier: 3.0,
+            error_rate_multiplier: 3.0,
+            min_error_rate: 0.1,
+            base_ejection_time: 30,
+            max_ejection_time: 300,
+        }
+    }
 }
 
 /// Session affinity configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionAffinityConfig {
     pub enabled: bool,
+    pub mode: AffinityMode,
+    /// Header carrying the authenticated account
+    pub header_name: String,
+    /// Cookie carrying the authenticated account, checked when the header is absent
+    pub cookie_name: String,
+    /// Points each backend owns on the hash ring per unit of weight
+    pub virtual_nodes: u32,
+}
+
+/// What requests are kept on the same backend by
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AffinityMode {
+    /// Client IP address
+    ClientIp,
+    /// Authenticated JMAP/IMAP account
+    Account,
+}
+
+impl Default for SessionAffinityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mode: AffinityMode::Account,
+            header_name: "X-Account-Id".to_string(),
+            cookie_name: "a3mailer_account".to_string(),
+            virtual_nodes: 160,
+        }
+    }
 }
 
 /// Server configuration
@@ -39,12 +138,13 @@ impl Default for LoadBalancerConfig {
         Self {
             enabled: false,
             algorithm: "round_robin".to_string(),
+            hash_virtual_nodes: 160,
             health_check_interval: 30,
+            drain_timeout: 30,
+            metrics_window: 60,
             backends: vec![],
-            health_check: HealthCheckConfig {
-                enabled: true,
-                interval: 30,
-            },
+            health_check: HealthCheckConfig::default(),
+            outlier_detection: OutlierDetectionConfig::default(),
             session_affinity: None,
             server: ServerConfig {
                 listen_address: "0.0.0.0".to_string(),
@@ -0,0 +1,152 @@
+//! Consistent hash ring
+
+use crate::backend::Backend;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Hash ring mapping keys to backend IDs
+///
+/// Each backend owns `virtual_nodes * weight` points on the ring, named by
+/// backend and index, so changing a weight only adds or removes that
+/// backend's own points and leaves every other key where it was.
+///
+/// Points are placed with a fixed hash, so every node and every build puts
+/// them in the same place and routes a key to the same backend.
+#[derive(Debug, Clone)]
+pub struct HashRing {
+    virtual_nodes: u32,
+    ring: BTreeMap<u64, String>,
+    /// Backend IDs and weights on the ring
+    members: Vec<(String, u32)>,
+}
+
+impl HashRing {
+    /// Create an empty ring
+    pub fn new(virtual_nodes: u32) -> Self {
+        Self {
+            virtual_nodes: virtual_nodes.max(1),
+            ring: BTreeMap::new(),
+            members: Vec::new(),
+        }
+    }
+
+    /// Create a ring holding the given backends
+    pub fn from_backends(backends: &[Arc<Backend>], virtual_nodes: u32) -> Self {
+        let mut ring = Self::new(virtual_nodes);
+        for backend in backends {
+            ring.add(&backend.id, backend.weight);
+        }
+        ring
+    }
+
+    /// Check if the ring holds exactly the given backends with their current weights
+    pub fn is_built_from(&self, backends: &[Arc<Backend>]) -> bool {
+        self.members.len() == backends.len()
+            && self.members.iter().zip(backends).all(|((id, weight), backend)| *id == backend.id && *weight == backend.weight)
+    }
+
+    /// Add a backend with the given weight, a weight of 0 adds no points
+    ///
+    /// A point landing on one already taken is rehashed with a salt instead
+    /// of replacing it, so no backend silently loses part of its share.
+    pub fn add(&mut self, backend_id: &str, weight: u32) {
+        self.remove(backend_id);
+        for index in 0..self.virtual_nodes.saturating_mul(weight) {
+            let mut point = hash_key(&format!("{}#{}", backend_id, index));
+            let mut salt = 0u32;
+            while self.ring.contains_key(&point) {
+                salt += 1;
+                point = hash_key(&format!("{}#{}#{}", backend_id, index, salt));
+            }
+            self.ring.insert(point, backend_id.to_string());
+        }
+        self.members.push((backend_id.to_string(), weight));
+    }
+
+    /// Remove a backend
+    pub fn remove(&mut self, backend_id: &str) {
+        self.ring.retain(|_, id| id != backend_id);
+        self.members.retain(|(id, _)| id != backend_id);
+    }
+
+    /// Get the backend owning a key
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.get_matching(key, |_| true)
+    }
+
+    /// Get the first backend accepted by `accept`, walking clockwise from the
+    /// key's position so every key fails over to a stable successor
+    pub fn get_matching(&self, key: &str, accept: impl Fn(&str) -> bool) -> Option<&str> {
+        let hash = hash_key(key);
+        self.ring
+            .range(hash..)
+            .chain(self.ring.range(..hash))
+            .map(|(_, id)| id.as_str())
+            .find(|id| accept(id))
+    }
+
+    /// Get the fraction of keys that would move to a backend if it were added
+    ///
+    /// Keys only ever move to the added backend, so this is the share of the
+    /// ring its points would claim.
+    pub fn remap_fraction_on_add(&self, backend_id: &str, weight: u32) -> f64 {
+        let mut ring = self.clone();
+        ring.add(backend_id, weight);
+
+        if !ring.ring.values().any(|id| id == backend_id) {
+            return 0.0;
+        }
+        if ring.members.len() == 1 {
+            return 1.0;
+        }
+
+        // Each point owns the keys hashing between the previous point and itself
+        let mut claimed = 0u128;
+        let mut previous = ring.ring.keys().next_back().copied().unwrap_or_default();
+        for (&point, id) in &ring.ring {
+            if id == backend_id {
+                claimed += point.wrapping_sub(previous) as u128;
+            }
+            previous = point;
+        }
+
+        claimed as f64 / (u64::MAX as f64 + 1.0)
+    }
+
+    /// Check if the ring has no backends
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+}
+
+/// Hash a key onto the ring
+///
+/// XXH3 is specified independently of the Rust release, unlike `DefaultHasher`.
+pub(crate) fn hash_key(key: &str) -> u64 {
+    xxhash_rust::xxh3::xxh3_64(key.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colliding_points_are_rehashed() {
+        let mut ring = HashRing::new(4);
+        ring.add("backend-a", 1);
+
+        // Take the spot backend-b's first point would land on
+        let taken = hash_key("backend-b#0");
+        ring.ring.insert(taken, "backend-a".to_string());
+        ring.add("backend-b", 1);
+
+        assert_eq!(ring.ring[&taken], "backend-a");
+        let backend_b_points = ring.ring.values().filter(|id| *id == "backend-b").count();
+        assert_eq!(backend_b_points, 4);
+
+        // Removing backend-b leaves backend-a's points in place
+        ring.remove("backend-b");
+        assert_eq!(ring.ring.len(), 5);
+        assert!(ring.ring.values().all(|id| id == "backend-a"));
+    }
+}
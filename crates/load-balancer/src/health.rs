@@ -1,49 +1,80 @@
 //! Health checking
 
-use crate::error::Result;
+use crate::error::{LoadBalancerError, Result};
 use crate::config::HealthCheckConfig;
-use crate::backend::BackendPool;
+use crate::backend::{Backend, BackendPool, BackendStatus};
+use crate::metrics::MetricsCollector;
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
 use tokio::time::interval;
+use tracing::{debug, info, warn};
+
+/// Result of a single health check
+#[derive(Debug, Clone)]
+pub struct HealthCheckResult {
+    pub success: bool,
+    pub latency: Duration,
+    /// Why the check failed
+    pub reason: Option<String>,
+}
+
+/// Consecutive check results of a backend
+#[derive(Debug, Clone, Default)]
+struct HealthStreak {
+    successes: u32,
+    failures: u32,
+}
 
 /// Health checker
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct HealthChecker {
     config: HealthCheckConfig,
     backend_pool: Arc<BackendPool>,
+    metrics: Arc<MetricsCollector>,
+    client: reqwest::Client,
+    streaks: Arc<Mutex<HashMap<String, HealthStreak>>>,
     running: Arc<std::sync::atomic::AtomicBool>,
 }
 
 impl HealthChecker {
     /// Create new health checker
-    pub async fn new(config: &HealthCheckConfig, backend_pool: Arc<BackendPool>) -> Result<Self> {
+    pub async fn new(
+        config: &HealthCheckConfig,
+        backend_pool: Arc<BackendPool>,
+        metrics: Arc<MetricsCollector>,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout))
+            .build()
+            .map_err(|e| LoadBalancerError::Configuration(format!("Failed to create health check client: {}", e)))?;
+
         Ok(Self {
             config: config.clone(),
             backend_pool,
+            metrics,
+            client,
+            streaks: Arc::new(Mutex::new(HashMap::new())),
             running: Arc::new(std::sync::atomic::AtomicBool::new(false)),
         })
     }
 
     /// Start health checking
     pub async fn start(&self) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
         self.running.store(true, std::sync::atomic::Ordering::Relaxed);
 
-        let mut interval = interval(Duration::from_secs(self.config.interval_seconds));
-        let running = self.running.clone();
-        let backend_pool = self.backend_pool.clone();
-        let config = self.config.clone();
+        let mut interval = interval(Duration::from_secs(self.config.interval));
+        let checker = self.clone();
 
         tokio::spawn(async move {
-            while running.load(std::sync::atomic::Ordering::Relaxed) {
+            while checker.running.load(std::sync::atomic::Ordering::Relaxed) {
                 interval.tick().await;
-
-                // Perform health checks
-                let backends = backend_pool.get_all_status().await;
-                for backend in backends {
-                    // TODO: Implement actual health check logic
-                    let _health_check_result = Self::check_backend_health(&backend.0, &config).await;
-                }
+                checker.check_all().await;
             }
         });
 
@@ -56,12 +87,209 @@ impl HealthChecker {
         Ok(())
     }
 
+    /// Check every backend once and apply any status transitions
+    pub async fn check_all(&self) {
+        let backends = self.backend_pool.get_backends().await;
+        let results = futures::future::join_all(
+            backends.iter().map(|backend| self.check_backend_health(backend))
+        ).await;
+
+        for (backend, result) in backends.iter().zip(results) {
+            self.record_result(backend, &result).await;
+        }
+    }
+
+    /// Update a backend's streak and change its status once the streak
+    /// reaches the configured threshold
+    async fn record_result(&self, backend: &Backend, result: &HealthCheckResult) {
+        let new_status = {
+            let mut streaks = self.streaks.lock().await;
+            let streak = streaks.entry(backend.id.clone()).or_default();
+
+            if result.success {
+                streak.successes += 1;
+                streak.failures = 0;
+            } else {
+                streak.failures += 1;
+                streak.successes = 0;
+            }
+
+            // Draining and disabled backends are managed by the operator
+            match backend.status {
+                BackendStatus::Unhealthy if streak.successes >= self.config.healthy_threshold => Some(BackendStatus::Healthy),
+                BackendStatus::Healthy if streak.failures >= self.config.unhealthy_threshold => Some(BackendStatus::Unhealthy),
+                _ => None,
+            }
+        };
+
+        let Some(status) = new_status else {
+            if let Some(reason) = &result.reason {
+                debug!("Health check failed for backend {}: {}", backend.id, reason);
+            }
+            return;
+        };
+
+        if status == BackendStatus::Healthy {
+            info!("Backend {} is healthy again", backend.id);
+            self.metrics.record_health_transition(true);
+        } else {
+            warn!("Backend {} marked unhealthy: {}", backend.id, result.reason.as_deref().unwrap_or("check failed"));
+            self.metrics.record_health_transition(false);
+        }
+
+        let _ = self.backend_pool.compare_and_set_status(&backend.id, backend.status.clone(), status).await;
+    }
+
     /// Check health of a single backend
-    async fn check_backend_health(
-        _backend: &crate::backend::Backend,
-        _config: &HealthCheckConfig,
-    ) -> bool {
-        // TODO: Implement actual health check (HTTP request, TCP connection, etc.)
-        true
+    async fn check_backend_health(&self, backend: &Backend) -> HealthCheckResult {
+        let started = Instant::now();
+        let failure = |reason: String| HealthCheckResult {
+            success: false,
+            latency: started.elapsed(),
+            reason: Some(reason),
+        };
+
+        let response = match self.client.get(format!("{}{}", backend.url(), self.config.path)).send().await {
+            Ok(response) => response,
+            Err(e) => return failure(format!("request failed: {}", e)),
+        };
+
+        let status = response.status().as_u16();
+        if status != self.config.expected_status {
+            return failure(format!("expected status {}, got {}", self.config.expected_status, status));
+        }
+
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(e) => return failure(format!("failed to read body: {}", e)),
+        };
+
+        if let Some(expected) = &self.config.body_contains {
+            if !body.contains(expected.as_str()) {
+                return failure(format!("body does not contain {:?}", expected));
+            }
+        }
+
+        let latency = started.elapsed();
+        if let Some(max_latency_ms) = self.config.max_latency_ms {
+            if latency > Duration::from_millis(max_latency_ms) {
+                return failure(format!("latency {}ms over {}ms", latency.as_millis(), max_latency_ms));
+            }
+        }
+
+        HealthCheckResult {
+            success: true,
+            latency,
+            reason: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn health_checker(server: &MockServer, config: HealthCheckConfig) -> (HealthChecker, Arc<BackendPool>, Arc<MetricsCollector>) {
+        let backend = Backend::new("backend1".to_string(), "127.0.0.1".to_string(), server.address().port(), 1);
+        let pool = Arc::new(BackendPool::new(&[backend]).await.unwrap());
+        let metrics = Arc::new(MetricsCollector::new());
+        let checker = HealthChecker::new(&config, pool.clone(), metrics.clone()).await.unwrap();
+        (checker, pool, metrics)
+    }
+
+    async fn status(pool: &BackendPool) -> BackendStatus {
+        pool.get_backend("backend1").await.unwrap().status.clone()
+    }
+
+    fn config() -> HealthCheckConfig {
+        HealthCheckConfig {
+            body_contains: Some("OK".to_string()),
+            max_latency_ms: Some(200),
+            healthy_threshold: 2,
+            unhealthy_threshold: 2,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wrong_status_marks_unhealthy() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("OK"))
+            .mount(&server)
+            .await;
+
+        let (checker, pool, metrics) = health_checker(&server, config()).await;
+
+        // A single failure is not enough to take the backend out
+        checker.check_all().await;
+        assert_eq!(status(&pool).await, BackendStatus::Healthy);
+
+        checker.check_all().await;
+        assert_eq!(status(&pool).await, BackendStatus::Unhealthy);
+        assert_eq!(metrics.get_metrics(1, 0).backends_marked_unhealthy, 1);
+    }
+
+    #[tokio::test]
+    async fn test_body_and_latency_criteria() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("starting"))
+            .mount(&server)
+            .await;
+        let (checker, _, _) = health_checker(&server, config()).await;
+        let backend = checker.backend_pool.get_backend("backend1").await.unwrap();
+        let result = checker.check_backend_health(&backend).await;
+        assert!(!result.success);
+        assert!(result.reason.unwrap().contains("body"));
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("OK").set_delay(Duration::from_millis(400)))
+            .mount(&server)
+            .await;
+        let (checker, pool, _) = health_checker(&server, config()).await;
+        let backend = checker.backend_pool.get_backend("backend1").await.unwrap();
+        let result = checker.check_backend_health(&backend).await;
+        assert!(!result.success);
+        assert!(result.latency >= Duration::from_millis(400));
+        assert!(result.reason.unwrap().contains("latency"));
+
+        checker.check_all().await;
+        checker.check_all().await;
+        assert_eq!(status(&pool).await, BackendStatus::Unhealthy);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_is_debounced() {
+        let server = MockServer::start().await;
+        // Down, a single success while flapping, down again, then recovered
+        for (status, times) in [(503, 2), (200, 1), (503, 1), (200, 2)] {
+            Mock::given(method("GET"))
+                .and(path("/health"))
+                .respond_with(ResponseTemplate::new(status).set_body_string("OK"))
+                .up_to_n_times(times)
+                .mount(&server)
+                .await;
+        }
+
+        let (checker, pool, metrics) = health_checker(&server, config()).await;
+        let mut statuses = Vec::new();
+        for _ in 0..6 {
+            checker.check_all().await;
+            statuses.push(status(&pool).await);
+        }
+
+        use BackendStatus::{Healthy, Unhealthy};
+        assert_eq!(statuses, vec![Healthy, Unhealthy, Unhealthy, Unhealthy, Unhealthy, Healthy]);
+
+        let metrics = metrics.get_metrics(1, 1);
+        assert_eq!(metrics.backends_marked_unhealthy, 1);
+        assert_eq!(metrics.backends_marked_healthy, 1);
     }
 }
@@ -19,8 +19,10 @@ pub mod algorithms;
 pub mod backend;
 pub mod config;
 pub mod error;
+pub mod hash_ring;
 pub mod health;
 pub mod metrics;
+pub mod outlier;
 pub mod proxy;
 pub mod server;
 pub mod session;
@@ -28,13 +30,14 @@ pub mod tls;
 
 pub use algorithms::{LoadBalancingAlgorithm, LoadBalancer, LoadBalancerImpl};
 pub use backend::{Backend, BackendPool, BackendStatus};
-pub use config::{LoadBalancerConfig, HealthCheckConfig, SessionAffinityConfig, ServerConfig};
+pub use config::{LoadBalancerConfig, HealthCheckConfig, OutlierDetectionConfig, SessionAffinityConfig, AffinityMode, ServerConfig};
 pub use error::{LoadBalancerError, Result};
 pub use health::HealthChecker;
-pub use metrics::{LoadBalancerMetrics, MetricsCollector};
+pub use metrics::{BackendMetrics, LoadBalancerMetrics, MetricsCollector};
+pub use outlier::OutlierDetector;
 pub use proxy::ProxyService;
 pub use server::LoadBalancerServer;
-pub use session::SessionAffinity;
+pub use session::{SessionAffinity, AffinityRemap};
 
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -53,8 +56,10 @@ struct LoadBalancerServiceInner {
     backend_pool: BackendPool,
     load_balancer: LoadBalancerImpl,
     health_checker: HealthChecker,
-    session_affinity: Option<SessionAffinity>,
+    outlier_detector: OutlierDetector,
+    session_affinity: Option<Arc<SessionAffinity>>,
     metrics: Arc<RwLock<LoadBalancerMetrics>>,
+    metrics_collector: Arc<MetricsCollector>,
     server: Arc<RwLock<LoadBalancerServer>>,
 }
 
@@ -73,19 +78,35 @@ impl LoadBalancerService {
             "weighted_round_robin" => LoadBalancingAlgorithm::WeightedRoundRobin,
             "ip_hash" => LoadBalancingAlgorithm::IpHash,
             "random" => LoadBalancingAlgorithm::Random,
+            "consistent_hash" => LoadBalancingAlgorithm::ConsistentHash {
+                virtual_nodes: config.hash_virtual_nodes,
+            },
             _ => LoadBalancingAlgorithm::RoundRobin,
         };
         let load_balancer = algorithms::create_load_balancer(algorithm);
 
+        // Create metrics collector shared by the health checker and proxy
+        let metrics_collector = Arc::new(MetricsCollector::with_window(
+            std::time::Duration::from_secs(config.metrics_window),
+        ));
+
         // Create health checker
         let health_checker = HealthChecker::new(
             &config.health_check,
             Arc::new(backend_pool.clone()),
+            metrics_collector.clone(),
         ).await?;
 
+        // Create outlier detector
+        let outlier_detector = OutlierDetector::new(
+            &config.outlier_detection,
+            Arc::new(backend_pool.clone()),
+            metrics_collector.clone(),
+        );
+
         // Create session affinity if enabled
         let session_affinity = if let Some(ref affinity_config) = config.session_affinity {
-            Some(SessionAffinity::new(affinity_config.clone()))
+            Some(Arc::new(SessionAffinity::new(affinity_config.clone())))
         } else {
             None
         };
@@ -102,8 +123,10 @@ impl LoadBalancerService {
                 backend_pool,
                 load_balancer,
                 health_checker,
+                outlier_detector,
                 session_affinity,
                 metrics,
+                metrics_collector,
                 server: Arc::new(RwLock::new(server)),
             }),
         })
@@ -116,6 +139,9 @@ impl LoadBalancerService {
         // Start health checker
         self.inner.health_checker.start().await?;
 
+        // Start outlier detection
+        self.inner.outlier_detector.start().await?;
+
         // Start metrics collection
         self.start_metrics_collection().await;
 
@@ -134,6 +160,9 @@ impl LoadBalancerService {
         // Stop health checker
         self.inner.health_checker.stop().await?;
 
+        // Stop outlier detection
+        self.inner.outlier_detector.stop().await?;
+
         // Stop server
         self.inner.server.write().await.stop().await?;
 
@@ -160,8 +189,12 @@ impl LoadBalancerService {
     }
 
     /// Remove a backend
+    ///
+    /// The backend stops receiving new requests and is removed once its
+    /// in-flight requests finish or the drain timeout passes.
     pub async fn remove_backend(&self, backend_id: &str) -> Result<bool> {
         info!("Removing backend: {}", backend_id);
+        self.drain_backend(backend_id).await?;
         let removed = self.inner.backend_pool.remove_backend(backend_id).await?;
         if removed {
             info!("Backend removed successfully: {}", backend_id);
@@ -171,11 +204,29 @@ impl LoadBalancerService {
         Ok(removed)
     }
 
+    /// Stop routing new requests to a backend and wait for its in-flight
+    /// requests to finish, up to the drain timeout
+    pub async fn drain_backend(&self, backend_id: &str) -> Result<bool> {
+        let timeout = std::time::Duration::from_secs(self.inner.config.drain_timeout);
+        self.inner.backend_pool.drain_backend(backend_id, timeout).await
+    }
+
     /// Update backend weight
     pub async fn update_backend_weight(&self, backend_id: &str, weight: u32) -> Result<bool> {
         info!("Updating backend weight: {} -> {}", backend_id, weight);
-        self.inner.backend_pool.update_weight(backend_id, weight).await?;
-        Ok(true)
+        let updated = self.inner.backend_pool.update_weight(backend_id, weight).await?;
+        if !updated {
+            warn!("Backend not found: {}", backend_id);
+        }
+        Ok(updated)
+    }
+
+    /// Get affinity keys currently served away from their own backend
+    pub async fn get_affinity_remaps(&self) -> Vec<AffinityRemap> {
+        match &self.inner.session_affinity {
+            Some(session_affinity) => session_affinity.get_remaps().await,
+            None => Vec::new(),
+        }
     }
 
     /// Create proxy service for handling requests
@@ -183,13 +234,15 @@ impl LoadBalancerService {
         ProxyService::new(
             Arc::new(self.inner.backend_pool.clone()),
             self.inner.load_balancer.clone(),
-            Arc::new(MetricsCollector::new()),
+            self.inner.session_affinity.clone(),
+            self.inner.metrics_collector.clone(),
         )
     }
 
     /// Start metrics collection background task
     async fn start_metrics_collection(&self) {
         let metrics = self.inner.metrics.clone();
+        let metrics_collector = self.inner.metrics_collector.clone();
         let backend_pool = self.inner.backend_pool.clone();
 
         tokio::spawn(async move {
@@ -201,9 +254,14 @@ impl LoadBalancerService {
                 // Collect backend statistics
                 let backend_stats = backend_pool.get_statistics().await;
 
+                let healthy_count = backend_stats.iter()
+                    .filter(|stats| stats.status == BackendStatus::Healthy)
+                    .count();
+
                 // Update metrics
                 {
                     let mut metrics_guard = metrics.write().await;
+                    *metrics_guard = metrics_collector.get_metrics(backend_stats.len() as u64, healthy_count as u64);
                     metrics_guard.update_backend_stats(backend_stats);
                 }
             }
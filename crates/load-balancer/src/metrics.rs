@@ -1,8 +1,14 @@
 //! Metrics collection
 
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Ratio between consecutive histogram bucket bounds, bounding the percentile error to 5%
+const BUCKET_GROWTH: f64 = 1.05;
 
 /// Load balancer metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,9 +20,137 @@ pub struct LoadBalancerMetrics {
     pub active_connections: u64,
     pub backend_count: u64,
     pub healthy_backend_count: u64,
+    pub backends_marked_healthy: u64,
+    pub backends_marked_unhealthy: u64,
+    pub backends_ejected: u64,
+    /// Recent request metrics by backend ID
+    pub backend_metrics: HashMap<String, BackendMetrics>,
+}
+
+/// Recent request metrics of a backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendMetrics {
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub latency_p50_ms: f64,
+    pub latency_p95_ms: f64,
+    pub latency_p99_ms: f64,
+}
+
+/// Streaming latency histogram with exponentially growing buckets
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    buckets: Vec<u64>,
+    count: u64,
+    sum_micros: u64,
 }
 
+impl LatencyHistogram {
+    /// Record a latency
+    pub fn record(&mut self, latency: Duration) {
+        let micros = latency.as_micros() as u64;
+        let bucket = if micros <= 1 {
+            0
+        } else {
+            ((micros as f64).ln() / BUCKET_GROWTH.ln()).ceil() as usize
+        };
+
+        if bucket >= self.buckets.len() {
+            self.buckets.resize(bucket + 1, 0);
+        }
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum_micros += micros;
+    }
+
+    /// Add the latencies recorded in another histogram
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        if other.buckets.len() > self.buckets.len() {
+            self.buckets.resize(other.buckets.len(), 0);
+        }
+        for (bucket, count) in self.buckets.iter_mut().zip(&other.buckets) {
+            *bucket += count;
+        }
+        self.count += other.count;
+        self.sum_micros += other.sum_micros;
+    }
+
+    /// Get the latency at a quantile between 0 and 1
+    pub fn percentile(&self, quantile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let rank = ((quantile * self.count as f64).ceil() as u64).clamp(1, self.count);
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(Duration::from_micros(BUCKET_GROWTH.powi(bucket as i32).round() as u64));
+            }
+        }
+        None
+    }
+
+    /// Get the number of recorded latencies
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Get the mean latency
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_micros(self.sum_micros / self.count))
+    }
+}
+
+/// Requests recorded during one metrics window
+#[derive(Debug, Clone, Default)]
+struct WindowStats {
+    latency: LatencyHistogram,
+    errors: u64,
+}
+
+/// Request metrics of a backend over the current and previous window
+#[derive(Debug, Clone)]
+struct BackendWindow {
+    started: Instant,
+    current: WindowStats,
+    previous: WindowStats,
+}
+
+impl BackendWindow {
+    fn new(now: Instant) -> Self {
+        Self {
+            started: now,
+            current: WindowStats::default(),
+            previous: WindowStats::default(),
+        }
+    }
+
+    /// Start a new window once the current one has elapsed
+    fn rotate(&mut self, now: Instant, window: Duration) {
+        let elapsed = now.duration_since(self.started);
+        if elapsed < window {
+            return;
+        }
+
+        // Nothing from a window that ended more than a window ago is recent
+        self.previous = if elapsed < window * 2 {
+            std::mem::take(&mut self.current)
+        } else {
+            WindowStats::default()
+        };
+        self.current = WindowStats::default();
+        self.started = now;
+    }
 
+    fn snapshot(&self) -> (LatencyHistogram, u64) {
+        let mut latency = self.previous.latency.clone();
+        latency.merge(&self.current.latency);
+        (latency, self.previous.errors + self.current.errors)
+    }
+}
 
 /// Metrics collector
 #[derive(Debug)]
@@ -25,16 +159,32 @@ pub struct MetricsCollector {
     successful_requests: Arc<AtomicU64>,
     failed_requests: Arc<AtomicU64>,
     active_connections: Arc<AtomicU64>,
+    backends_marked_healthy: Arc<AtomicU64>,
+    backends_marked_unhealthy: Arc<AtomicU64>,
+    backends_ejected: Arc<AtomicU64>,
+    /// Percentiles cover between one and two windows of recent requests
+    window: Duration,
+    backend_windows: Mutex<HashMap<String, BackendWindow>>,
 }
 
 impl MetricsCollector {
     /// Create new metrics collector
     pub fn new() -> Self {
+        Self::with_window(Duration::from_secs(60))
+    }
+
+    /// Create new metrics collector with the given per-backend metrics window
+    pub fn with_window(window: Duration) -> Self {
         Self {
             total_requests: Arc::new(AtomicU64::new(0)),
             successful_requests: Arc::new(AtomicU64::new(0)),
             failed_requests: Arc::new(AtomicU64::new(0)),
             active_connections: Arc::new(AtomicU64::new(0)),
+            backends_marked_healthy: Arc::new(AtomicU64::new(0)),
+            backends_marked_unhealthy: Arc::new(AtomicU64::new(0)),
+            backends_ejected: Arc::new(AtomicU64::new(0)),
+            window,
+            backend_windows: Mutex::new(HashMap::new()),
         }
     }
 
@@ -48,6 +198,56 @@ impl MetricsCollector {
         }
     }
 
+    /// Record the latency and outcome of a request to a backend
+    pub fn record_backend_request(&self, backend_id: &str, latency: Duration, success: bool) {
+        let now = Instant::now();
+        let mut windows = self.backend_windows.lock();
+        let window = windows
+            .entry(backend_id.to_string())
+            .or_insert_with(|| BackendWindow::new(now));
+
+        window.rotate(now, self.window);
+        window.current.latency.record(latency);
+        if !success {
+            window.current.errors += 1;
+        }
+    }
+
+    /// Forget the recent requests of a backend
+    pub fn reset_backend(&self, backend_id: &str) {
+        self.backend_windows.lock().remove(backend_id);
+    }
+
+    /// Record an outlier ejection
+    pub fn record_ejection(&self) {
+        self.backends_ejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get recent request metrics by backend ID
+    pub fn get_backend_metrics(&self) -> HashMap<String, BackendMetrics> {
+        let now = Instant::now();
+        let mut windows = self.backend_windows.lock();
+        let as_ms = |latency: Option<Duration>| latency.map_or(0.0, |latency| latency.as_secs_f64() * 1000.0);
+
+        windows
+            .iter_mut()
+            .map(|(backend_id, window)| {
+                window.rotate(now, self.window);
+                let (latency, errors) = window.snapshot();
+                let requests = latency.count();
+
+                (backend_id.clone(), BackendMetrics {
+                    requests,
+                    errors,
+                    error_rate: if requests > 0 { errors as f64 / requests as f64 } else { 0.0 },
+                    latency_p50_ms: as_ms(latency.percentile(0.50)),
+                    latency_p95_ms: as_ms(latency.percentile(0.95)),
+                    latency_p99_ms: as_ms(latency.percentile(0.99)),
+                })
+            })
+            .collect()
+    }
+
     /// Increment active connections
     pub fn increment_connections(&self) {
         self.active_connections.fetch_add(1, Ordering::Relaxed);
@@ -58,20 +258,39 @@ impl MetricsCollector {
         self.active_connections.fetch_sub(1, Ordering::Relaxed);
     }
 
+    /// Record a backend health status change
+    pub fn record_health_transition(&self, healthy: bool) {
+        if healthy {
+            self.backends_marked_healthy.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.backends_marked_unhealthy.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
     /// Get current metrics
     pub fn get_metrics(&self, backend_count: u64, healthy_backend_count: u64) -> LoadBalancerMetrics {
         let total = self.total_requests.load(Ordering::Relaxed);
         let successful = self.successful_requests.load(Ordering::Relaxed);
         let failed = self.failed_requests.load(Ordering::Relaxed);
 
+        // Mean over the recent window of every backend
+        let mut recent = LatencyHistogram::default();
+        for window in self.backend_windows.lock().values() {
+            recent.merge(&window.snapshot().0);
+        }
+
         LoadBalancerMetrics {
             total_requests: total,
             successful_requests: successful,
             failed_requests: failed,
-            average_response_time_ms: 0.0, // TODO: Implement response time tracking
+            average_response_time_ms: recent.mean().map_or(0.0, |mean| mean.as_secs_f64() * 1000.0),
             active_connections: self.active_connections.load(Ordering::Relaxed),
             backend_count,
             healthy_backend_count,
+            backends_marked_healthy: self.backends_marked_healthy.load(Ordering::Relaxed),
+            backends_marked_unhealthy: self.backends_marked_unhealthy.load(Ordering::Relaxed),
+            backends_ejected: self.backends_ejected.load(Ordering::Relaxed),
+            backend_metrics: self.get_backend_metrics(),
         }
     }
 }
@@ -93,6 +312,10 @@ impl LoadBalancerMetrics {
             active_connections: 0,
             backend_count: 0,
             healthy_backend_count: 0,
+            backends_marked_healthy: 0,
+            backends_marked_unhealthy: 0,
+            backends_ejected: 0,
+            backend_metrics: HashMap::new(),
         }
     }
 
@@ -107,3 +330,54 @@ impl Default for LoadBalancerMetrics {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() <= expected * 0.05, "expected {} within 5%, got {}", expected, actual);
+    }
+
+    #[test]
+    fn test_backend_latency_percentiles() {
+        let collector = MetricsCollector::new();
+        for ms in 1..=1000 {
+            collector.record_backend_request("backend1", Duration::from_millis(ms), ms % 10 != 0);
+        }
+        collector.record_backend_request("backend2", Duration::from_millis(5), true);
+
+        let metrics = collector.get_metrics(2, 2);
+        let backend1 = &metrics.backend_metrics["backend1"];
+        assert_eq!(backend1.requests, 1000);
+        assert_eq!(backend1.errors, 100);
+        assert_close(backend1.error_rate, 0.1);
+        assert_close(backend1.latency_p50_ms, 500.0);
+        assert_close(backend1.latency_p95_ms, 950.0);
+        assert_close(backend1.latency_p99_ms, 990.0);
+
+        let backend2 = &metrics.backend_metrics["backend2"];
+        assert_eq!(backend2.error_rate, 0.0);
+        assert_close(backend2.latency_p99_ms, 5.0);
+        assert_close(metrics.average_response_time_ms, 500_505.0 / 1001.0);
+    }
+
+    #[test]
+    fn test_backend_metrics_window() {
+        let collector = MetricsCollector::with_window(Duration::from_millis(100));
+        for _ in 0..100 {
+            collector.record_backend_request("backend1", Duration::from_millis(800), false);
+        }
+
+        // Requests from more than a window ago no longer count
+        std::thread::sleep(Duration::from_millis(250));
+        for _ in 0..10 {
+            collector.record_backend_request("backend1", Duration::from_millis(20), true);
+        }
+
+        let metrics = &collector.get_backend_metrics()["backend1"];
+        assert_eq!(metrics.requests, 10);
+        assert_eq!(metrics.error_rate, 0.0);
+        assert_close(metrics.latency_p99_ms, 20.0);
+    }
+}
@@ -0,0 +1,274 @@
+//! Outlier detection
+
+use crate::backend::{BackendPool, BackendStatus};
+use crate::config::OutlierDetectionConfig;
+use crate::error::Result;
+use crate::metrics::MetricsCollector;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tokio::time::interval;
+use tracing::{debug, info, warn};
+
+/// Ejection history of a backend
+#[derive(Debug, Clone, Default)]
+struct EjectionState {
+    /// Ejections since the backend last stayed in rotation for the maximum ejection time
+    count: u32,
+    ejected_until: Option<Instant>,
+    reinstated_at: Option<Instant>,
+}
+
+/// Outlier detector ejecting backends much slower or more error-prone than
+/// the rest of the pool
+#[derive(Debug, Clone)]
+pub struct OutlierDetector {
+    config: OutlierDetectionConfig,
+    backend_pool: Arc<BackendPool>,
+    metrics: Arc<MetricsCollector>,
+    ejections: Arc<Mutex<HashMap<String, EjectionState>>>,
+    running: Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl OutlierDetector {
+    /// Create new outlier detector
+    pub fn new(config: &OutlierDetectionConfig, backend_pool: Arc<BackendPool>, metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            config: config.clone(),
+            backend_pool,
+            metrics,
+            ejections: Arc::new(Mutex::new(HashMap::new())),
+            running: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        }
+    }
+
+    /// Start outlier detection
+    pub async fn start(&self) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        self.running.store(true, std::sync::atomic::Ordering::Relaxed);
+
+        let mut interval = interval(Duration::from_secs(self.config.interval));
+        let detector = self.clone();
+
+        tokio::spawn(async move {
+            while detector.running.load(std::sync::atomic::Ordering::Relaxed) {
+                interval.tick().await;
+                detector.evaluate().await;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Stop outlier detection
+    pub async fn stop(&self) -> Result<()> {
+        self.running.store(false, std::sync::atomic::Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Reinstate backends whose ejection ended and eject new outliers
+    pub async fn evaluate(&self) {
+        self.evaluate_at(Instant::now()).await;
+    }
+
+    async fn evaluate_at(&self, now: Instant) {
+        let mut ejections = self.ejections.lock().await;
+        let max_ejection_time = Duration::from_secs(self.config.max_ejection_time);
+
+        // Put backends back in rotation once their ejection ends, starting
+        // their metrics afresh so they are judged on new requests only
+        for (backend_id, state) in ejections.iter_mut() {
+            if state.ejected_until.is_some_and(|until| until <= now) {
+                state.ejected_until = None;
+                state.reinstated_at = Some(now);
+                self.metrics.reset_backend(backend_id);
+                if let Ok(true) = self.backend_pool.compare_and_set_status(backend_id, BackendStatus::Ejected, BackendStatus::Healthy).await {
+                    info!("Backend {} reinstated after ejection", backend_id);
+                }
+            }
+        }
+
+        let backends = self.backend_pool.get_backends().await;
+        let backend_metrics = self.metrics.get_backend_metrics();
+
+        let candidates = backends
+            .iter()
+            .filter_map(|backend| {
+                backend_metrics
+                    .get(&backend.id)
+                    .filter(|metrics| metrics.requests >= self.config.min_requests)
+                    .map(|metrics| (backend, metrics))
+            })
+            .collect::<Vec<_>>();
+
+        if candidates.len() < 2 {
+            return;
+        }
+
+        let median_latency = median(candidates.iter().map(|(_, metrics)| metrics.latency_p50_ms).collect());
+        let median_error_rate = median(candidates.iter().map(|(_, metrics)| metrics.error_rate).collect());
+        let latency_limit = median_latency * self.config.latency_multiplier;
+        let error_rate_limit = (median_error_rate * self.config.error_rate_multiplier).max(self.config.min_error_rate);
+
+        let mut healthy_count = backends
+            .iter()
+            .filter(|backend| backend.status == BackendStatus::Healthy)
+            .count();
+
+        for (backend, metrics) in candidates {
+            let state = ejections.entry(backend.id.clone()).or_default();
+            let is_outlier = metrics.latency_p50_ms > latency_limit || metrics.error_rate > error_rate_limit;
+
+            if !is_outlier {
+                // Backends that stay in rotation long enough start over at the base ejection time
+                if state.reinstated_at.is_some_and(|reinstated| now.duration_since(reinstated) >= max_ejection_time) {
+                    state.count = 0;
+                    state.reinstated_at = None;
+                }
+                continue;
+            }
+
+            if backend.status != BackendStatus::Healthy {
+                continue;
+            }
+
+            if healthy_count <= 1 {
+                warn!("Backend {} is an outlier but is the last healthy backend, not ejecting", backend.id);
+                continue;
+            }
+
+            state.count += 1;
+            let ejection_time = (Duration::from_secs(self.config.base_ejection_time) * state.count).min(max_ejection_time);
+
+            if let Ok(true) = self.backend_pool.compare_and_set_status(&backend.id, BackendStatus::Healthy, BackendStatus::Ejected).await {
+                warn!(
+                    "Ejecting backend {} for {:?}: p50 latency {:.1}ms (limit {:.1}ms), error rate {:.3} (limit {:.3})",
+                    backend.id, ejection_time, metrics.latency_p50_ms, latency_limit, metrics.error_rate, error_rate_limit
+                );
+                state.ejected_until = Some(now + ejection_time);
+                healthy_count -= 1;
+                self.metrics.record_ejection();
+            } else {
+                debug!("Backend {} changed status before it could be ejected", backend.id);
+                state.count -= 1;
+            }
+        }
+    }
+}
+
+/// Median of a list of values
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let middle = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Backend;
+
+    fn config() -> OutlierDetectionConfig {
+        OutlierDetectionConfig {
+            min_requests: 20,
+            base_ejection_time: 30,
+            max_ejection_time: 300,
+            ..Default::default()
+        }
+    }
+
+    async fn detector(count: usize) -> (OutlierDetector, Arc<BackendPool>, Arc<MetricsCollector>) {
+        let backends = (1..=count)
+            .map(|i| Backend::new(format!("backend{}", i), "127.0.0.1".to_string(), 8000 + i as u16, 1))
+            .collect::<Vec<_>>();
+        let pool = Arc::new(BackendPool::new(&backends).await.unwrap());
+        let metrics = Arc::new(MetricsCollector::new());
+        (OutlierDetector::new(&config(), pool.clone(), metrics.clone()), pool, metrics)
+    }
+
+    fn record(metrics: &MetricsCollector, backend_id: &str, latency_ms: u64) {
+        for _ in 0..50 {
+            metrics.record_backend_request(backend_id, Duration::from_millis(latency_ms), true);
+        }
+    }
+
+    async fn status(pool: &BackendPool, backend_id: &str) -> BackendStatus {
+        pool.get_backend(backend_id).await.unwrap().status.clone()
+    }
+
+    #[tokio::test]
+    async fn test_slow_backend_ejected_and_reinstated() {
+        let (detector, pool, metrics) = detector(3).await;
+        record(&metrics, "backend1", 10);
+        record(&metrics, "backend2", 12);
+        record(&metrics, "backend3", 200);
+
+        let start = Instant::now();
+        detector.evaluate_at(start).await;
+        assert_eq!(status(&pool, "backend3").await, BackendStatus::Ejected);
+
+        // The other backends keep serving
+        let healthy = pool.get_healthy_backends().await;
+        assert_eq!(healthy.iter().map(|backend| backend.id.as_str()).collect::<Vec<_>>(), vec!["backend1", "backend2"]);
+        assert_eq!(metrics.get_metrics(3, 2).backends_ejected, 1);
+
+        detector.evaluate_at(start + Duration::from_secs(29)).await;
+        assert_eq!(status(&pool, "backend3").await, BackendStatus::Ejected);
+
+        // Reinstated after the ejection, judged only on new requests
+        detector.evaluate_at(start + Duration::from_secs(30)).await;
+        assert_eq!(status(&pool, "backend3").await, BackendStatus::Healthy);
+        assert!(!metrics.get_backend_metrics().contains_key("backend3"));
+
+        // Ejected again for longer if it is still slow
+        record(&metrics, "backend3", 200);
+        detector.evaluate_at(start + Duration::from_secs(31)).await;
+        assert_eq!(status(&pool, "backend3").await, BackendStatus::Ejected);
+        detector.evaluate_at(start + Duration::from_secs(61)).await;
+        assert_eq!(status(&pool, "backend3").await, BackendStatus::Ejected);
+        detector.evaluate_at(start + Duration::from_secs(91)).await;
+        assert_eq!(status(&pool, "backend3").await, BackendStatus::Healthy);
+    }
+
+    #[tokio::test]
+    async fn test_last_healthy_backend_not_ejected() {
+        let (detector, pool, metrics) = detector(3).await;
+        record(&metrics, "backend1", 200);
+        record(&metrics, "backend2", 10);
+        record(&metrics, "backend3", 10);
+        pool.set_status("backend2", BackendStatus::Unhealthy).await.unwrap();
+        pool.set_status("backend3", BackendStatus::Draining).await.unwrap();
+
+        detector.evaluate_at(Instant::now()).await;
+        assert_eq!(status(&pool, "backend1").await, BackendStatus::Healthy);
+        assert_eq!(metrics.get_metrics(3, 1).backends_ejected, 0);
+    }
+
+    #[tokio::test]
+    async fn test_error_rate_outlier() {
+        let (detector, pool, metrics) = detector(3).await;
+        record(&metrics, "backend1", 10);
+        record(&metrics, "backend2", 10);
+        for i in 0..50 {
+            metrics.record_backend_request("backend3", Duration::from_millis(10), i % 2 == 0);
+        }
+
+        detector.evaluate_at(Instant::now()).await;
+        assert_eq!(status(&pool, "backend3").await, BackendStatus::Ejected);
+        assert_eq!(status(&pool, "backend1").await, BackendStatus::Healthy);
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(vec![3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median(vec![4.0, 1.0, 2.0, 3.0]), 2.5);
+    }
+}
@@ -4,6 +4,8 @@ use crate::backend::{Backend, BackendPool};
 use crate::algorithms::LoadBalancerImpl;
 use crate::error::{LoadBalancerError, Result};
 use crate::metrics::MetricsCollector;
+use crate::session::SessionAffinity;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use hyper::{body::Incoming, Request, Response, StatusCode};
 use http_body_util::Full;
@@ -14,6 +16,7 @@ use bytes::Bytes;
 pub struct ProxyService {
     backend_pool: Arc<BackendPool>,
     load_balancer: LoadBalancerImpl,
+    session_affinity: Option<Arc<SessionAffinity>>,
     metrics: Arc<MetricsCollector>,
 }
 
@@ -22,17 +25,33 @@ impl ProxyService {
     pub fn new(
         backend_pool: Arc<BackendPool>,
         load_balancer: LoadBalancerImpl,
+        session_affinity: Option<Arc<SessionAffinity>>,
         metrics: Arc<MetricsCollector>,
     ) -> Self {
         Self {
             backend_pool,
             load_balancer,
+            session_affinity,
             metrics,
         }
     }
 
     /// Handle incoming request
-    pub async fn handle_request(&self, req: Request<Incoming>) -> Result<Response<Full<Bytes>>> {
+    pub async fn handle_request(&self, req: Request<Incoming>, client_addr: Option<SocketAddr>) -> Result<Response<Full<Bytes>>> {
+        // Keep requests with the same affinity key on the same backend
+        let affinity_key = match &self.session_affinity {
+            Some(session_affinity) if session_affinity.is_enabled() => {
+                session_affinity.extract_key(req.headers(), client_addr.map(|addr| addr.ip()))
+            }
+            _ => None,
+        };
+        let affine_backend = match (&self.session_affinity, &affinity_key) {
+            (Some(session_affinity), Some(key)) => {
+                session_affinity.select_backend(key, &self.backend_pool.get_backends().await).await
+            }
+            _ => None,
+        };
+
         // Get healthy backends
         let backends = self.backend_pool.get_healthy_backends().await;
 
@@ -44,7 +63,13 @@ impl ProxyService {
         }
 
         // Select backend using load balancing algorithm
-        let backend = match self.load_balancer.select_backend(&backends).await? {
+        let request_key = affinity_key.or_else(|| client_addr.map(|addr| addr.ip().to_string()));
+        let selected = match (affine_backend, request_key) {
+            (Some(backend), _) => Some(backend),
+            (None, Some(key)) => self.load_balancer.select_backend_for_key(&backends, &key).await?,
+            (None, None) => self.load_balancer.select_backend(&backends).await?,
+        };
+        let backend = match selected {
             Some(backend) => backend,
             None => {
                 return Ok(Response::builder()
@@ -59,7 +84,9 @@ impl ProxyService {
         self.metrics.increment_connections();
 
         // Forward request to backend
+        let started = std::time::Instant::now();
         let result = self.forward_request(req, &backend).await;
+        let latency = started.elapsed();
 
         // Decrement connection count
         backend.decrement_connections().await;
@@ -68,6 +95,7 @@ impl ProxyService {
         // Record metrics
         let success = result.is_ok();
         self.metrics.record_request(success);
+        self.metrics.record_backend_request(&backend.id, latency, success);
 
         if !success {
             backend.record_failure().await;
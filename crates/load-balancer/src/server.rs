@@ -2,6 +2,7 @@
 
 use crate::{config::ServerConfig, error::Result, proxy::ProxyService};
 use hyper::{Body, Request, Response, Server};
+use hyper::server::conn::AddrStream;
 use hyper::service::{make_service_fn, service_fn};
 use std::convert::Infallible;
 use std::net::SocketAddr;
@@ -33,13 +34,14 @@ impl LoadBalancerServer {
         let (shutdown_tx, shutdown_rx) = oneshot::channel();
         self.shutdown_tx = Some(shutdown_tx);
 
-        let make_svc = make_service_fn(move |_conn| {
+        let make_svc = make_service_fn(move |conn: &AddrStream| {
             let proxy_service = proxy_service.clone();
+            let client_addr = conn.remote_addr();
             async move {
                 Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
                     let proxy_service = proxy_service.clone();
                     async move {
-                        match proxy_service.handle_request(req).await {
+                        match proxy_service.handle_request(req, Some(client_addr)).await {
                             Ok(response) => Ok::<Response<Body>, Infallible>(response),
                             Err(_) => Ok(Response::builder()
                                 .status(500)
@@ -1,4 +1,248 @@
 //! Session management
 
+use crate::backend::Backend;
+use crate::config::{AffinityMode, SessionAffinityConfig};
+use crate::hash_ring::HashRing;
+use chrono::{DateTime, Utc};
+use http::header::{HeaderMap, COOKIE};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
 /// Session manager
 pub struct SessionManager;
+
+/// Affinity key served away from its own backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AffinityRemap {
+    pub key: String,
+    pub from_backend: String,
+    pub to_backend: String,
+    pub remapped_at: DateTime<Utc>,
+}
+
+/// Session affinity keeping requests with the same key on the same backend
+#[derive(Debug)]
+pub struct SessionAffinity {
+    config: SessionAffinityConfig,
+    ring: RwLock<HashRing>,
+    /// Keys currently failed over, by key
+    remaps: RwLock<HashMap<String, AffinityRemap>>,
+    total_remaps: AtomicU64,
+}
+
+impl SessionAffinity {
+    /// Create new session affinity
+    pub fn new(config: SessionAffinityConfig) -> Self {
+        let ring = HashRing::new(config.virtual_nodes);
+        Self {
+            config,
+            ring: RwLock::new(ring),
+            remaps: RwLock::new(HashMap::new()),
+            total_remaps: AtomicU64::new(0),
+        }
+    }
+
+    /// Check if session affinity is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Extract the affinity key of a request
+    ///
+    /// In account mode the account is read from the configured header, set
+    /// by the auth layer, or from the cookie set after login.
+    pub fn extract_key(&self, headers: &HeaderMap, client_ip: Option<IpAddr>) -> Option<String> {
+        match self.config.mode {
+            AffinityMode::ClientIp => client_ip.map(|ip| ip.to_string()),
+            AffinityMode::Account => headers
+                .get(&self.config.header_name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+                .or_else(|| find_cookie(headers, &self.config.cookie_name))
+                .map(|account| account.trim().to_lowercase())
+                .filter(|account| !account.is_empty()),
+        }
+    }
+
+    /// Select the backend for an affinity key
+    ///
+    /// `backends` must include unavailable backends so keys keep their
+    /// position on the ring. When the key's backend is unavailable the next
+    /// available backend on the ring serves it and the remap is recorded.
+    pub async fn select_backend(&self, key: &str, backends: &[Arc<Backend>]) -> Option<Arc<Backend>> {
+        self.sync_ring(backends).await;
+
+        let find = |id: &str| backends.iter().find(|backend| backend.id == id);
+        let (owner, selected) = {
+            let ring = self.ring.read().await;
+            let owner = ring.get(key)?.to_string();
+            let selected = ring
+                .get_matching(key, |id| find(id).is_some_and(|backend| backend.is_available()))
+                .and_then(find)?
+                .clone();
+            (owner, selected)
+        };
+
+        let mut remaps = self.remaps.write().await;
+        if selected.id == owner {
+            if remaps.remove(key).is_some() {
+                debug!("Affinity key {} returned to backend {}", key, owner);
+            }
+        } else if !matches!(remaps.get(key), Some(remap) if remap.to_backend == selected.id) {
+            warn!("Backend {} unavailable, remapping affinity key {} to {}", owner, key, selected.id);
+            self.total_remaps.fetch_add(1, Ordering::Relaxed);
+            remaps.insert(key.to_string(), AffinityRemap {
+                key: key.to_string(),
+                from_backend: owner,
+                to_backend: selected.id.clone(),
+                remapped_at: Utc::now(),
+            });
+        }
+
+        Some(selected)
+    }
+
+    /// Rebuild the ring when backends or their weights changed
+    async fn sync_ring(&self, backends: &[Arc<Backend>]) {
+        if !self.ring.read().await.is_built_from(backends) {
+            *self.ring.write().await = HashRing::from_backends(backends, self.config.virtual_nodes);
+        }
+    }
+
+    /// Get the keys currently served away from their own backend
+    pub async fn get_remaps(&self) -> Vec<AffinityRemap> {
+        self.remaps.read().await.values().cloned().collect()
+    }
+
+    /// Get the number of remaps since startup
+    pub fn get_remap_count(&self) -> u64 {
+        self.total_remaps.load(Ordering::Relaxed)
+    }
+}
+
+/// Find a cookie value in the request headers
+fn find_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(cookie_name, _)| *cookie_name == name)
+        .map(|(_, value)| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::BackendStatus;
+    use http::HeaderValue;
+
+    fn backends(ids: &[&str]) -> Vec<Arc<Backend>> {
+        ids.iter()
+            .enumerate()
+            .map(|(i, id)| Arc::new(Backend::new(id.to_string(), "127.0.0.1".to_string(), 8000 + i as u16, 1)))
+            .collect()
+    }
+
+    fn with_status(backends: &[Arc<Backend>], backend_id: &str, status: BackendStatus) -> Vec<Arc<Backend>> {
+        backends.iter()
+            .map(|backend| {
+                let mut backend = (**backend).clone();
+                if backend.id == backend_id {
+                    backend.status = status.clone();
+                }
+                Arc::new(backend)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_account_key() {
+        let affinity = SessionAffinity::new(SessionAffinityConfig::default());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(COOKIE, HeaderValue::from_static("theme=dark; a3mailer_account=Bob@Example.com"));
+        assert_eq!(affinity.extract_key(&headers, None).as_deref(), Some("bob@example.com"));
+
+        headers.insert("x-account-id", HeaderValue::from_static("alice@example.com"));
+        assert_eq!(affinity.extract_key(&headers, None).as_deref(), Some("alice@example.com"));
+
+        assert_eq!(affinity.extract_key(&HeaderMap::new(), "10.0.0.1".parse().ok()), None);
+    }
+
+    #[tokio::test]
+    async fn test_sticky_routing_and_failover() {
+        let affinity = SessionAffinity::new(SessionAffinityConfig::default());
+        let pool = backends(&["a", "b", "c"]);
+
+        let owner = affinity.select_backend("alice@example.com", &pool).await.unwrap();
+        for _ in 0..10 {
+            assert_eq!(affinity.select_backend("alice@example.com", &pool).await.unwrap().id, owner.id);
+        }
+
+        // Keys owned by other backends stay put while the owner is down
+        let accounts = (0..100).map(|i| format!("user{}@example.com", i)).collect::<Vec<_>>();
+        let mut before = HashMap::new();
+        for account in &accounts {
+            before.insert(account.clone(), affinity.select_backend(account, &pool).await.unwrap().id.clone());
+        }
+
+        let degraded = with_status(&pool, &owner.id, BackendStatus::Unhealthy);
+        let failover = affinity.select_backend("alice@example.com", &degraded).await.unwrap();
+        assert_ne!(failover.id, owner.id);
+        for _ in 0..10 {
+            assert_eq!(affinity.select_backend("alice@example.com", &degraded).await.unwrap().id, failover.id);
+        }
+
+        for account in &accounts {
+            let selected = affinity.select_backend(account, &degraded).await.unwrap();
+            if before[account] != owner.id {
+                assert_eq!(selected.id, before[account]);
+            }
+        }
+
+        let remaps = affinity.get_remaps().await;
+        let remap = remaps.iter().find(|remap| remap.key == "alice@example.com").unwrap();
+        assert_eq!(remap.from_backend, owner.id);
+        assert_eq!(remap.to_backend, failover.id);
+        assert_eq!(affinity.get_remap_count() as usize, remaps.len());
+
+        // The key returns once its backend recovers
+        assert_eq!(affinity.select_backend("alice@example.com", &pool).await.unwrap().id, owner.id);
+        assert!(affinity.get_remaps().await.iter().all(|remap| remap.key != "alice@example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_weight_change_remaps_minimally() {
+        let affinity = SessionAffinity::new(SessionAffinityConfig::default());
+        let pool = backends(&["a", "b", "c"]);
+
+        let accounts = (0..1000).map(|i| format!("user{}@example.com", i)).collect::<Vec<_>>();
+        let mut before = HashMap::new();
+        for account in &accounts {
+            before.insert(account.clone(), affinity.select_backend(account, &pool).await.unwrap().id.clone());
+        }
+
+        let mut heavier = (*pool[0]).clone();
+        heavier.weight = 2;
+        let reweighted = vec![Arc::new(heavier), pool[1].clone(), pool[2].clone()];
+
+        let mut moved = 0;
+        for account in &accounts {
+            let selected = affinity.select_backend(account, &reweighted).await.unwrap();
+            if selected.id != before[account] {
+                // Keys only move onto the backend that gained weight
+                assert_eq!(selected.id, "a");
+                moved += 1;
+            }
+        }
+        assert!(moved > 0 && moved < accounts.len() / 2, "moved {} keys", moved);
+        assert_eq!(affinity.get_remap_count(), 0);
+    }
+}
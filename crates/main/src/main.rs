@@ -9,14 +9,17 @@
 #![warn(clippy::cast_possible_wrap)]
 #![warn(clippy::cast_sign_loss)]
 
-use common::{config::server::ServerProtocol, core::BuildServer, manager::boot::BootManager};
+use common::{
+    config::server::ServerProtocol, core::BuildServer, manager::boot::BootManager,
+    monitoring::MonitoringManager,
+};
 use http::HttpSessionManager;
 use imap::core::ImapSessionManager;
 use managesieve::core::ManageSieveSessionManager;
 use pop3::Pop3SessionManager;
 use services::{StartServices, broadcast::subscriber::spawn_broadcast_subscriber};
 use smtp::{StartQueueManager, core::SmtpSessionManager};
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 use trc::Collector;
 use utils::wait_for_shutdown;
 
@@ -46,6 +49,12 @@ async fn main() -> std::io::Result<()> {
     init.start_services().await;
     init.start_queue_manager();
 
+    // Metrics shared by the protocol session managers
+    let monitoring = Arc::new(MonitoringManager::default());
+
+    // Parse protocol settings
+    let pop3 = Pop3SessionManager::new(init.inner.clone(), &mut init.config, monitoring);
+
     // Log configuration errors
     init.config.log_errors();
     init.config.log_warnings();
@@ -79,12 +88,9 @@ async fn main() -> std::io::Result<()> {
                 acceptor,
                 shutdown_rx,
             ),
-            ServerProtocol::Pop3 => server.spawn(
-                Pop3SessionManager::new(init.inner.clone()),
-                init.inner.clone(),
-                acceptor,
-                shutdown_rx,
-            ),
+            ServerProtocol::Pop3 => {
+                server.spawn(pop3.clone(), init.inner.clone(), acceptor, shutdown_rx)
+            }
             ServerProtocol::ManageSieve => server.spawn(
                 ManageSieveSessionManager::new(init.inner.clone()),
                 init.inner.clone(),
@@ -3,12 +3,12 @@
 //! This module provides comprehensive alerting capabilities with multiple
 //! notification channels, alert rules, and escalation policies.
 
-use crate::{MonitoringConfig, Result, MonitoringError};
+use crate::{MonitoringConfig, Result};
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use tokio::sync::RwLock;
-use tracing::{info, warn, error, debug};
+use tracing::{info, warn, debug};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
@@ -118,6 +118,7 @@ pub struct Alert {
     pub rule_id: String,
     pub name: String,
     pub description: String,
+    pub metric_name: String,
     pub severity: AlertSeverity,
     pub status: AlertStatus,
     pub current_value: f64,
@@ -140,6 +141,7 @@ impl Alert {
             rule_id: rule.id.clone(),
             name: rule.name.clone(),
             description: rule.description.clone(),
+            metric_name: rule.metric_name.clone(),
             severity: rule.severity.clone(),
             status: AlertStatus::Firing,
             current_value,
@@ -176,6 +178,29 @@ impl Alert {
     }
 }
 
+impl From<&Alert> for crate::Alert {
+    fn from(alert: &Alert) -> Self {
+        Self {
+            id: alert.id.clone(),
+            severity: match alert.severity {
+                AlertSeverity::Critical => crate::AlertSeverity::Critical,
+                AlertSeverity::High | AlertSeverity::Medium => crate::AlertSeverity::Warning,
+                AlertSeverity::Low | AlertSeverity::Info => crate::AlertSeverity::Info,
+            },
+            title: alert.name.clone(),
+            description: alert.description.clone(),
+            component: alert.labels.get("component").cloned().unwrap_or_default(),
+            metric_name: alert.metric_name.clone(),
+            current_value: alert.current_value,
+            threshold_value: alert.threshold,
+            baseline_value: None,
+            deviation: None,
+            created_at: alert.fired_at,
+            resolved_at: alert.resolved_at,
+        }
+    }
+}
+
 /// Notification channel types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NotificationChannel {
@@ -245,7 +270,6 @@ pub enum EscalationCondition {
 
 /// Alert manager
 pub struct AlertManager {
-    config: MonitoringConfig,
     rules: Arc<RwLock<HashMap<String, AlertRule>>>,
     active_alerts: Arc<RwLock<HashMap<String, Alert>>>,
     alert_history: Arc<RwLock<Vec<Alert>>>,
@@ -269,11 +293,10 @@ pub struct AlertMetrics {
 
 impl AlertManager {
     /// Create a new alert manager
-    pub async fn new(config: &MonitoringConfig) -> Result<Self> {
+    pub async fn new(_config: &MonitoringConfig) -> Result<Self> {
         info!("Initializing alert manager");
 
         let alert_manager = Self {
-            config: config.clone(),
             rules: Arc::new(RwLock::new(HashMap::new())),
             active_alerts: Arc::new(RwLock::new(HashMap::new())),
             alert_history: Arc::new(RwLock::new(Vec::new())),
@@ -520,6 +543,7 @@ impl AlertManager {
             self.send_alert_notifications(&alert).await?;
             
             active_alerts.insert(rule.id.clone(), alert);
+            drop(active_alerts);
             
             // Update metrics
             self.update_alert_metrics().await;
@@ -533,6 +557,7 @@ impl AlertManager {
         let mut active_alerts = self.active_alerts.write().await;
         
         if let Some(mut alert) = active_alerts.remove(&rule.id) {
+            drop(active_alerts);
             alert.resolve();
             info!("Resolved alert: {} (duration: {:?})", alert.name, alert.duration());
             
@@ -540,8 +565,7 @@ impl AlertManager {
             self.send_resolution_notification(&alert).await?;
             
             // Move to history
-            let mut history = self.alert_history.write().await;
-            history.push(alert);
+            self.alert_history.write().await.push(alert);
             
             // Update metrics
             self.update_alert_metrics().await;
@@ -661,6 +685,74 @@ impl AlertManager {
         for alert in active_alerts.values() {
             *metrics.alerts_by_severity.entry(alert.severity.clone()).or_insert(0) += 1;
         }
+
+        metrics.acknowledged_alerts = active_alerts.values()
+            .filter(|alert| alert.status == AlertStatus::Acknowledged)
+            .count() as u64;
+        metrics.escalation_rate = if active_alerts.is_empty() {
+            0.0
+        } else {
+            let escalated = active_alerts.values().filter(|alert| alert.escalation_level > 1).count();
+            escalated as f64 * 100.0 / active_alerts.len() as f64
+        };
+    }
+
+    /// Escalate active alerts according to their escalation policy
+    ///
+    /// Critical alerts follow the `critical` policy and all others the
+    /// `standard` one. An alert moves to the next level once the level's delay
+    /// has elapsed since it fired and all of the level's conditions hold, and
+    /// the level's channels are then notified.
+    pub async fn process_alerts(&self) -> Result<()> {
+        let policies = self.escalation_policies.read().await;
+        let channels = self.notification_channels.read().await;
+        let mut active_alerts = self.active_alerts.write().await;
+        let now = Utc::now();
+
+        for alert in active_alerts.values_mut() {
+            if alert.status != AlertStatus::Firing && alert.status != AlertStatus::Acknowledged {
+                continue;
+            }
+
+            let policy_id = if alert.severity == AlertSeverity::Critical { "critical" } else { "standard" };
+            let Some(policy) = policies.get(policy_id) else {
+                continue;
+            };
+            if alert.escalation_level >= policy.max_escalations {
+                continue;
+            }
+
+            let elapsed_minutes = now.signed_duration_since(alert.fired_at).num_minutes().max(0) as u64;
+            let Some(level) = policy.levels.iter()
+                .filter(|level| level.level > alert.escalation_level && elapsed_minutes >= level.delay_minutes)
+                .find(|level| level.conditions.iter().all(|condition| match condition {
+                    EscalationCondition::TimeElapsed(minutes) => elapsed_minutes >= *minutes,
+                    EscalationCondition::SeverityLevel(severity) => alert.severity.priority() >= severity.priority(),
+                    EscalationCondition::NotAcknowledged => alert.status != AlertStatus::Acknowledged,
+                    EscalationCondition::RepeatCount(count) => alert.notification_count >= *count,
+                }))
+            else {
+                continue;
+            };
+
+            info!("Escalating alert {} to level {} of policy {}", alert.name, level.level, policy.id);
+            for channel_id in &level.channels {
+                match channels.get(channel_id) {
+                    Some(channel) => match self.send_notification(channel, alert, false).await {
+                        Ok(_) => debug!("Sent escalation notification via channel: {}", channel_id),
+                        Err(e) => warn!("Failed to send escalation notification via {}: {}", channel_id, e),
+                    },
+                    None => warn!("Escalation policy {} references unknown channel {}", policy.id, channel_id),
+                }
+            }
+            alert.escalation_level = level.level;
+            alert.notification_count += 1;
+        }
+
+        drop(active_alerts);
+        self.update_alert_metrics().await;
+
+        Ok(())
     }
 
     /// Get alert statistics
@@ -669,13 +761,30 @@ impl AlertManager {
         metrics.clone()
     }
 
+    /// Get alert statistics as key-value pairs
+    pub async fn get_stats(&self) -> Result<HashMap<String, String>> {
+        let metrics = self.metrics.read().await;
+        let mut stats = HashMap::new();
+
+        stats.insert("alerts_total".to_string(), metrics.total_alerts.to_string());
+        stats.insert("alerts_active".to_string(), metrics.active_alerts.to_string());
+        stats.insert("alerts_resolved".to_string(), metrics.resolved_alerts.to_string());
+        stats.insert("alerts_acknowledged".to_string(), metrics.acknowledged_alerts.to_string());
+        stats.insert("alerts_average_resolution_minutes".to_string(), format!("{:.2}", metrics.average_resolution_time_minutes));
+        stats.insert("alerts_escalation_rate".to_string(), format!("{:.2}", metrics.escalation_rate));
+
+        Ok(stats)
+    }
+
     /// Acknowledge alert
     pub async fn acknowledge_alert(&self, alert_id: &str, acknowledged_by: String) -> Result<bool> {
         let mut active_alerts = self.active_alerts.write().await;
         
-        if let Some(alert) = active_alerts.get_mut(alert_id) {
+        if let Some(alert) = active_alerts.values_mut().find(|alert| alert.id == alert_id) {
             alert.acknowledge(acknowledged_by);
             info!("Alert acknowledged: {}", alert.name);
+            drop(active_alerts);
+            self.update_alert_metrics().await;
             Ok(true)
         } else {
             Ok(false)
@@ -703,3 +812,44 @@ impl AlertManager {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_alert_lifecycle_and_escalation() {
+        let manager = AlertManager::new(&MonitoringConfig::default()).await.unwrap();
+
+        // Firing, escalating to the first level of the critical policy and resolving
+        manager.evaluate_metric("error_rate_percent", 12.0, None).await.unwrap();
+        let alerts = manager.get_active_alerts().await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].escalation_level, 0);
+
+        manager.process_alerts().await.unwrap();
+        let alert = manager.get_active_alerts().await.remove(0);
+        assert_eq!(alert.escalation_level, 1);
+        assert_eq!(alert.notification_count, 1);
+
+        // Later levels wait for their delay
+        manager.process_alerts().await.unwrap();
+        assert_eq!(manager.get_active_alerts().await[0].escalation_level, 1);
+
+        let converted = crate::Alert::from(&alert);
+        assert_eq!(converted.severity, crate::AlertSeverity::Critical);
+        assert_eq!(converted.component, "application");
+        assert_eq!(converted.metric_name, "error_rate_percent");
+
+        assert!(manager.acknowledge_alert(&alert.id, "ops".to_string()).await.unwrap());
+        let stats = manager.get_stats().await.unwrap();
+        assert_eq!(stats["alerts_active"], "1");
+        assert_eq!(stats["alerts_acknowledged"], "1");
+
+        manager.evaluate_metric("error_rate_percent", 1.0, None).await.unwrap();
+        assert!(manager.get_active_alerts().await.is_empty());
+        let stats = manager.get_stats().await.unwrap();
+        assert_eq!(stats["alerts_active"], "0");
+        assert_eq!(stats["alerts_resolved"], "1");
+    }
+}
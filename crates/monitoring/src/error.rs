@@ -0,0 +1,16 @@
+//! Error types for the monitoring system
+
+/// Result type for monitoring operations
+pub type Result<T> = std::result::Result<T, MonitoringError>;
+
+/// Monitoring system errors
+#[derive(Debug, thiserror::Error)]
+pub enum MonitoringError {
+    /// Failed to serialize metrics or status
+    #[error("Serialization error: {0}")]
+    SerializationError(String),
+
+    /// Failed to set up trace export
+    #[error("Tracing error: {0}")]
+    TracingError(String),
+}
@@ -3,14 +3,19 @@
 //! This module provides comprehensive health checks for all system components
 //! including AI services, Web3 integration, and core email functionality.
 
-use crate::{MonitoringConfig, HealthStatus, HealthState, ComponentHealth, Result, MonitoringError};
-use std::collections::HashMap;
+use crate::{MonitoringConfig, HealthStatus, HealthState, ComponentHealth, Result};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{info, warn, error, debug};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
+/// Components that must be reachable before the node reports ready
+pub const READINESS_DEPENDENCIES: &[&str] = &["storage", "cache", "redis"];
+
 /// Health check configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthCheckConfig {
@@ -44,17 +49,56 @@ pub struct HealthCheckResult {
     pub checked_at: DateTime<Utc>,
 }
 
+/// Health check contributed by a subsystem
+#[async_trait]
+pub trait HealthCheckFn: Send + Sync {
+    /// Check the component and report its health
+    async fn check(&self) -> ComponentHealth;
+}
+
+/// Startup tasks a node waits for before it reports ready
+#[derive(Debug, Default)]
+pub struct StartupTracker {
+    pending: Mutex<HashSet<String>>,
+}
+
+impl StartupTracker {
+    /// Register a task that must report ready before startup completes
+    pub fn register(&self, task: &str) {
+        self.pending.lock().unwrap().insert(task.to_string());
+    }
+
+    /// Mark a task as ready
+    pub fn report_ready(&self, task: &str) {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.remove(task) {
+            debug!("Startup task ready: {}", task);
+            if pending.is_empty() {
+                info!("All startup tasks ready");
+            }
+        }
+    }
+
+    /// Get the tasks that have not reported ready yet, sorted by name
+    pub fn pending(&self) -> Vec<String> {
+        let mut pending = self.pending.lock().unwrap().iter().cloned().collect::<Vec<_>>();
+        pending.sort();
+        pending
+    }
+}
+
 /// Health monitor for system components
 pub struct HealthMonitor {
-    config: MonitoringConfig,
     health_checks: HashMap<String, HealthCheckConfig>,
+    registered_checks: RwLock<HashMap<String, Arc<dyn HealthCheckFn>>>,
+    registered_check_timeout: Duration,
     component_status: RwLock<HashMap<String, ComponentHealth>>,
     last_check_time: RwLock<DateTime<Utc>>,
 }
 
 impl HealthMonitor {
     /// Create a new health monitor
-    pub async fn new(config: &MonitoringConfig) -> Result<Self> {
+    pub async fn new(_config: &MonitoringConfig) -> Result<Self> {
         info!("Initializing health monitor");
         
         let mut health_checks = HashMap::new();
@@ -75,8 +119,9 @@ impl HealthMonitor {
         health_checks.insert("storage".to_string(), HealthCheckConfig::default());
         
         let monitor = Self {
-            config: config.clone(),
             health_checks,
+            registered_checks: RwLock::new(HashMap::new()),
+            registered_check_timeout: Duration::from_secs(HealthCheckConfig::default().timeout_seconds),
             component_status: RwLock::new(HashMap::new()),
             last_check_time: RwLock::new(Utc::now()),
         };
@@ -88,6 +133,80 @@ impl HealthMonitor {
         Ok(monitor)
     }
 
+    /// Register a health check contributed by a subsystem
+    ///
+    /// Registering a check under an existing name replaces it.
+    pub async fn register_health_check(&self, name: &str, check: Arc<dyn HealthCheckFn>) {
+        info!("Registering health check: {}", name);
+        
+        self.registered_checks.write().await.insert(name.to_string(), check);
+        self.component_status.write().await
+            .entry(name.to_string())
+            .or_insert_with(|| ComponentHealth {
+                status: HealthState::Unknown,
+                message: "Not checked yet".to_string(),
+                last_check: Utc::now(),
+                response_time_ms: 0,
+            });
+    }
+
+    /// Record the health of a component reported by its owner
+    pub async fn set_component_health(&self, name: &str, status: HealthState, message: &str) {
+        debug!("Component {} reported {:?}: {}", name, status, message);
+
+        self.component_status.write().await.insert(name.to_string(), ComponentHealth {
+            status,
+            message: message.to_string(),
+            last_check: Utc::now(),
+            response_time_ms: 0,
+        });
+    }
+
+    /// Get readiness to serve traffic
+    ///
+    /// The node is ready once no startup task is pending and every readiness
+    /// dependency is healthy or degraded. Dependencies that have not been
+    /// checked yet count as not ready.
+    pub async fn readiness(&self, pending_startup: &[String]) -> HealthStatus {
+        let component_status = self.component_status.read().await;
+        let mut components = HashMap::new();
+
+        for dependency in READINESS_DEPENDENCIES {
+            let health = component_status.get(*dependency).cloned().unwrap_or_else(|| ComponentHealth {
+                status: HealthState::Unknown,
+                message: "Not reported yet".to_string(),
+                last_check: Utc::now(),
+                response_time_ms: 0,
+            });
+            components.insert(dependency.to_string(), health);
+        }
+
+        components.insert("startup".to_string(), ComponentHealth {
+            status: if pending_startup.is_empty() { HealthState::Healthy } else { HealthState::Unhealthy },
+            message: if pending_startup.is_empty() {
+                "Startup complete".to_string()
+            } else {
+                format!("Waiting for: {}", pending_startup.join(", "))
+            },
+            last_check: Utc::now(),
+            response_time_ms: 0,
+        });
+
+        let overall_status = if components.values().any(|c| matches!(c.status, HealthState::Unhealthy | HealthState::Unknown)) {
+            HealthState::Unhealthy
+        } else if components.values().any(|c| c.status == HealthState::Degraded) {
+            HealthState::Degraded
+        } else {
+            HealthState::Healthy
+        };
+
+        HealthStatus {
+            overall_status,
+            components,
+            last_updated: Utc::now(),
+        }
+    }
+
     /// Run health checks for all components
     pub async fn run_health_checks(&self) -> Result<()> {
         debug!("Running health checks for all components");
@@ -107,8 +226,17 @@ impl HealthMonitor {
             }
         }
         
+        let registered_checks = self.registered_checks.read().await
+            .iter()
+            .map(|(name, check)| self.run_registered_check(name.clone(), Arc::clone(check)))
+            .collect::<Vec<_>>();
+        
         // Wait for all health checks to complete
-        let results = futures::future::join_all(check_futures).await;
+        let (results, registered_results) = futures::future::join(
+            futures::future::join_all(check_futures),
+            futures::future::join_all(registered_checks),
+        ).await;
+        let results = results.into_iter().chain(registered_results.into_iter().map(Ok));
         
         // Update component status
         let mut component_status = self.component_status.write().await;
@@ -206,6 +334,40 @@ impl HealthMonitor {
         })
     }
 
+    /// Run a registered check, recording panics and timeouts as unhealthy
+    async fn run_registered_check(&self, component: String, check: Arc<dyn HealthCheckFn>) -> HealthCheckResult {
+        debug!("Running registered health check: {}", component);
+        
+        let start_time = Instant::now();
+        let mut handle = tokio::spawn(async move { check.check().await });
+        
+        let (status, message) = match tokio::time::timeout(self.registered_check_timeout, &mut handle).await {
+            Ok(Ok(health)) => (health.status, health.message),
+            Ok(Err(e)) if e.is_panic() => {
+                error!("Health check {} panicked", component);
+                (HealthState::Unhealthy, "Health check panicked".to_string())
+            }
+            Ok(Err(e)) => (HealthState::Unhealthy, format!("Health check failed: {}", e)),
+            Err(_) => {
+                handle.abort();
+                warn!("Health check {} timed out", component);
+                (
+                    HealthState::Unhealthy,
+                    format!("Health check timed out after {}s", self.registered_check_timeout.as_secs()),
+                )
+            }
+        };
+        
+        HealthCheckResult {
+            component,
+            status,
+            message,
+            response_time_ms: start_time.elapsed().as_millis() as u64,
+            details: HashMap::new(),
+            checked_at: Utc::now(),
+        }
+    }
+
     /// Perform actual health check for a component
     async fn perform_health_check(&self, component: &str, config: &HealthCheckConfig) -> Result<(HealthState, String, HashMap<String, String>)> {
         let timeout = Duration::from_secs(config.timeout_seconds);
@@ -378,14 +540,13 @@ impl HealthMonitor {
             return HealthState::Unknown;
         }
         
-        let mut healthy_count = 0;
         let mut degraded_count = 0;
         let mut unhealthy_count = 0;
         let mut unknown_count = 0;
         
         for component in components.values() {
             match component.status {
-                HealthState::Healthy => healthy_count += 1,
+                HealthState::Healthy => {}
                 HealthState::Degraded => degraded_count += 1,
                 HealthState::Unhealthy => unhealthy_count += 1,
                 HealthState::Unknown => unknown_count += 1,
@@ -460,3 +621,87 @@ impl HealthMonitor {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StaticCheck(HealthState);
+
+    #[async_trait]
+    impl HealthCheckFn for StaticCheck {
+        async fn check(&self) -> ComponentHealth {
+            ComponentHealth {
+                status: self.0.clone(),
+                message: format!("{:?}", self.0),
+                last_check: Utc::now(),
+                response_time_ms: 0,
+            }
+        }
+    }
+
+    struct HangingCheck;
+
+    #[async_trait]
+    impl HealthCheckFn for HangingCheck {
+        async fn check(&self) -> ComponentHealth {
+            std::future::pending().await
+        }
+    }
+
+    struct PanickingCheck;
+
+    #[async_trait]
+    impl HealthCheckFn for PanickingCheck {
+        async fn check(&self) -> ComponentHealth {
+            panic!("check failed")
+        }
+    }
+
+    async fn component(monitor: &HealthMonitor, name: &str) -> ComponentHealth {
+        monitor.get_health_status().await.unwrap().components[name].clone()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_registered_health_checks() {
+        let monitor = HealthMonitor::new(&MonitoringConfig::default()).await.unwrap();
+        monitor.register_health_check("queue", Arc::new(StaticCheck(HealthState::Healthy))).await;
+        assert_eq!(component(&monitor, "queue").await.status, HealthState::Unknown);
+
+        monitor.run_health_checks().await.unwrap();
+        assert_eq!(component(&monitor, "queue").await.status, HealthState::Healthy);
+        assert_eq!(monitor.get_health_status().await.unwrap().overall_status, HealthState::Healthy);
+
+        // Degraded sits between healthy and unhealthy
+        monitor.register_health_check("spam_filter", Arc::new(StaticCheck(HealthState::Degraded))).await;
+        monitor.run_health_checks().await.unwrap();
+        assert_eq!(component(&monitor, "spam_filter").await.status, HealthState::Degraded);
+        assert_eq!(monitor.get_health_status().await.unwrap().overall_status, HealthState::Degraded);
+
+        monitor.register_health_check("directory", Arc::new(StaticCheck(HealthState::Unhealthy))).await;
+        monitor.run_health_checks().await.unwrap();
+        assert_eq!(monitor.get_health_status().await.unwrap().overall_status, HealthState::Unhealthy);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failing_health_checks_are_unhealthy() {
+        let monitor = HealthMonitor::new(&MonitoringConfig::default()).await.unwrap();
+        monitor.register_health_check("ldap", Arc::new(HangingCheck)).await;
+        monitor.register_health_check("queue", Arc::new(PanickingCheck)).await;
+
+        monitor.run_health_checks().await.unwrap();
+
+        let ldap = component(&monitor, "ldap").await;
+        assert_eq!(ldap.status, HealthState::Unhealthy);
+        assert!(ldap.message.contains("timed out"), "{}", ldap.message);
+
+        let queue = component(&monitor, "queue").await;
+        assert_eq!(queue.status, HealthState::Unhealthy);
+        assert!(queue.message.contains("panicked"), "{}", queue.message);
+
+        assert_eq!(monitor.get_health_status().await.unwrap().overall_status, HealthState::Unhealthy);
+
+        // The monitor keeps running checks afterwards
+        monitor.run_health_checks().await.unwrap();
+    }
+}
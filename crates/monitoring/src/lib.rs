@@ -7,7 +7,7 @@
 //!
 //! - **Prometheus Metrics**: Comprehensive metrics collection
 //! - **Distributed Tracing**: Request tracing across services
-//! - **Health Checks**: System health monitoring
+//! - **Health Checks**: System health monitoring with liveness and readiness probes
 //! - **Performance Monitoring**: Real-time performance metrics
 //! - **AI/ML Metrics**: Machine learning model performance
 //! - **Web3 Metrics**: Blockchain and DID operation metrics
@@ -53,8 +53,11 @@ pub mod health;
 pub mod performance;
 pub mod alerts;
 pub mod error;
+pub mod telemetry;
 
 pub use error::{MonitoringError, Result};
+pub use health::HealthCheckFn;
+pub use telemetry::{request_span, OtlpProtocol, TracingConfig};
 
 /// Monitoring configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +69,7 @@ pub struct MonitoringConfig {
     pub alert_thresholds: AlertThresholds,
     pub prometheus_endpoint: String,
     pub grafana_endpoint: String,
+    pub tracing: TracingConfig,
 }
 
 /// Alert threshold configuration
@@ -90,6 +94,7 @@ impl Default for MonitoringConfig {
             alert_thresholds: AlertThresholds::default(),
             prometheus_endpoint: "http://localhost:9090".to_string(),
             grafana_endpoint: "http://localhost:3000".to_string(),
+            tracing: TracingConfig::default(),
         }
     }
 }
@@ -145,6 +150,8 @@ pub struct PerformanceMetrics {
     pub network_rx_bytes: u64,
     pub network_tx_bytes: u64,
     pub active_connections: u64,
+    pub average_response_time_ms: f64,
+    pub requests_per_second: f64,
     pub emails_processed_per_second: f64,
     pub ai_inference_latency_ms: f64,
     pub web3_operation_latency_ms: f64,
@@ -162,6 +169,12 @@ pub struct Alert {
     pub metric_name: String,
     pub current_value: f64,
     pub threshold_value: f64,
+    /// Recent baseline the current value is compared against, for trend alerts
+    #[serde(default)]
+    pub baseline_value: Option<f64>,
+    /// Ratio of the current value to the baseline, for trend alerts
+    #[serde(default)]
+    pub deviation: Option<f64>,
     pub created_at: DateTime<Utc>,
     pub resolved_at: Option<DateTime<Utc>>,
 }
@@ -174,6 +187,9 @@ pub enum AlertSeverity {
     Critical,
 }
 
+/// Time allowed for the liveness probe to acquire the core monitoring locks
+const LIVENESS_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Main monitoring manager
 pub struct MonitoringManager {
     config: MonitoringConfig,
@@ -181,6 +197,8 @@ pub struct MonitoringManager {
     health_monitor: Arc<RwLock<health::HealthMonitor>>,
     performance_tracker: Arc<RwLock<performance::PerformanceTracker>>,
     alert_manager: Arc<RwLock<alerts::AlertManager>>,
+    tracer_provider: Arc<RwLock<Option<opentelemetry_sdk::trace::SdkTracerProvider>>>,
+    startup: Arc<health::StartupTracker>,
     start_time: Instant,
 }
 
@@ -220,6 +238,8 @@ impl MonitoringManager {
             health_monitor,
             performance_tracker,
             alert_manager,
+            tracer_provider: Arc::new(RwLock::new(None)),
+            startup: Arc::new(health::StartupTracker::default()),
             start_time,
         };
 
@@ -233,39 +253,50 @@ impl MonitoringManager {
     async fn start_background_tasks(&self) -> Result<()> {
         info!("Starting background monitoring tasks");
 
+        // Each task reports ready once it has completed a first run
+        for task in ["health_checks", "performance_metrics", "alert_processing"] {
+            self.startup.register(task);
+        }
+
         // Health check task
         let health_monitor = Arc::clone(&self.health_monitor);
         let health_interval = self.config.health_check_interval;
+        let startup = Arc::clone(&self.startup);
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(health_interval));
             loop {
                 interval.tick().await;
-                if let Err(e) = health_monitor.write().await.run_health_checks().await {
-                    error!("Health check failed: {}", e);
+                match health_monitor.read().await.run_health_checks().await {
+                    Ok(_) => startup.report_ready("health_checks"),
+                    Err(e) => error!("Health check failed: {}", e),
                 }
             }
         });
 
         // Performance monitoring task
         let performance_tracker = Arc::clone(&self.performance_tracker);
+        let startup = Arc::clone(&self.startup);
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(10));
             loop {
                 interval.tick().await;
-                if let Err(e) = performance_tracker.write().await.collect_metrics().await {
-                    error!("Performance metrics collection failed: {}", e);
+                match performance_tracker.write().await.collect_metrics().await {
+                    Ok(_) => startup.report_ready("performance_metrics"),
+                    Err(e) => error!("Performance metrics collection failed: {}", e),
                 }
             }
         });
 
         // Alert processing task
         let alert_manager = Arc::clone(&self.alert_manager);
+        let startup = Arc::clone(&self.startup);
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(5));
             loop {
                 interval.tick().await;
-                if let Err(e) = alert_manager.write().await.process_alerts().await {
-                    error!("Alert processing failed: {}", e);
+                match alert_manager.read().await.process_alerts().await {
+                    Ok(_) => startup.report_ready("alert_processing"),
+                    Err(e) => error!("Alert processing failed: {}", e),
                 }
             }
         });
@@ -274,17 +305,44 @@ impl MonitoringManager {
         Ok(())
     }
 
+    /// Initialize OpenTelemetry trace export
+    ///
+    /// When a tracing endpoint is configured, installs an OTLP span exporter
+    /// sampling at `performance_sampling_rate` and routes `tracing` spans to it.
+    pub async fn init_tracing(&self) -> Result<()> {
+        let mut tracer_provider = self.tracer_provider.write().await;
+        if tracer_provider.is_some() {
+            debug!("Trace export already initialized");
+            return Ok(());
+        }
+
+        match telemetry::init_tracer_provider(&self.config)? {
+            Some(provider) => {
+                info!("Exporting traces to {}", self.config.tracing.endpoint.as_deref().unwrap_or_default());
+                *tracer_provider = Some(provider);
+            }
+            None => debug!("No tracing endpoint configured, trace export disabled"),
+        }
+
+        Ok(())
+    }
+
     /// Record email processed metric
     pub async fn record_email_processed(&self, protocol: &str) -> Result<()> {
         let metrics_collector = self.metrics_collector.read().await;
-        metrics_collector.increment_counter("emails_processed_total", &[("protocol", protocol)]).await?;
+        metrics_collector.increment_counter("a3mailer_emails_processed_total", &[("protocol", protocol)]).await?;
         Ok(())
     }
 
+    /// Record the latency of a handled request
+    pub async fn record_request_latency(&self, latency_ms: u64) {
+        self.performance_tracker.read().await.record_request(latency_ms).await;
+    }
+
     /// Record AI inference metric
     pub async fn record_ai_inference(&self, model: &str, latency_ms: u64) -> Result<()> {
         let metrics_collector = self.metrics_collector.read().await;
-        metrics_collector.record_histogram("ai_inference_duration_ms", latency_ms as f64, &[("model", model)]).await?;
+        metrics_collector.record_histogram("a3mailer_ai_inference_duration_ms", latency_ms as f64, &[("model", model)]).await?;
         Ok(())
     }
 
@@ -292,16 +350,81 @@ impl MonitoringManager {
     pub async fn record_web3_operation(&self, operation: &str, latency_ms: u64, success: bool) -> Result<()> {
         let metrics_collector = self.metrics_collector.read().await;
         let status = if success { "success" } else { "failure" };
-        metrics_collector.record_histogram("web3_operation_duration_ms", latency_ms as f64, &[("operation", operation), ("status", status)]).await?;
+        metrics_collector.record_histogram("a3mailer_web3_operation_duration_ms", latency_ms as f64, &[("operation", operation), ("status", status)]).await?;
         Ok(())
     }
 
+    /// Render all metrics in Prometheus text exposition format
+    ///
+    /// Includes the collected counters, gauges and histograms along with the
+    /// latest performance sample.
+    pub async fn export_prometheus(&self) -> String {
+        let metrics_collector = self.metrics_collector.read().await;
+        
+        match self.get_performance_metrics().await {
+            Ok(performance) => {
+                if let Err(e) = metrics_collector.record_performance_metrics(&performance).await {
+                    warn!("Failed to record performance metrics: {}", e);
+                }
+            }
+            Err(e) => debug!("No performance metrics to export: {}", e),
+        }
+        
+        metrics_collector.get_prometheus_metrics().await.unwrap_or_else(|e| {
+            error!("Failed to render Prometheus metrics: {}", e);
+            String::new()
+        })
+    }
+
+    /// Register a health check contributed by a subsystem
+    ///
+    /// The check runs with the periodic health checks and its result is
+    /// folded into the overall health status.
+    pub async fn register_health_check(&self, name: &str, check: Arc<dyn health::HealthCheckFn>) {
+        self.health_monitor.read().await.register_health_check(name, check).await;
+    }
+
     /// Get system health status
     pub async fn get_health_status(&self) -> Result<HealthStatus> {
         let health_monitor = self.health_monitor.read().await;
         health_monitor.get_health_status().await
     }
 
+    /// Get liveness of the process
+    ///
+    /// Reports `Unhealthy` when the core monitoring state cannot be locked in
+    /// time, which indicates a deadlock or a stalled runtime.
+    pub async fn liveness(&self) -> HealthState {
+        let probe = async {
+            drop(self.health_monitor.read().await);
+            drop(self.metrics_collector.read().await);
+            drop(self.performance_tracker.read().await);
+            drop(self.alert_manager.read().await);
+        };
+
+        match tokio::time::timeout(LIVENESS_TIMEOUT, probe).await {
+            Ok(()) => HealthState::Healthy,
+            Err(_) => {
+                error!("Liveness probe timed out after {}s", LIVENESS_TIMEOUT.as_secs());
+                HealthState::Unhealthy
+            }
+        }
+    }
+
+    /// Get readiness to serve traffic
+    ///
+    /// Stays `Unhealthy` until the background tasks have completed their
+    /// first run and storage, cache and Redis are reachable.
+    pub async fn readiness(&self) -> HealthStatus {
+        let pending_startup = self.startup.pending();
+        self.health_monitor.read().await.readiness(&pending_startup).await
+    }
+
+    /// Record the health of a component reported by its owner
+    pub async fn set_component_health(&self, name: &str, status: HealthState, message: &str) {
+        self.health_monitor.read().await.set_component_health(name, status, message).await;
+    }
+
     /// Get performance metrics
     pub async fn get_performance_metrics(&self) -> Result<PerformanceMetrics> {
         let performance_tracker = self.performance_tracker.read().await;
@@ -310,8 +433,13 @@ impl MonitoringManager {
 
     /// Get active alerts
     pub async fn get_active_alerts(&self) -> Result<Vec<Alert>> {
-        let alert_manager = self.alert_manager.read().await;
-        alert_manager.get_active_alerts().await
+        let mut alerts: Vec<Alert> = self.alert_manager.read().await
+            .get_active_alerts().await
+            .iter()
+            .map(Alert::from)
+            .collect();
+        alerts.extend(self.performance_tracker.read().await.get_trend_alerts().await);
+        Ok(alerts)
     }
 
     /// Get system uptime
@@ -349,6 +477,10 @@ impl MonitoringManager {
         self.health_monitor.write().await.shutdown().await?;
         self.performance_tracker.write().await.shutdown().await?;
         self.alert_manager.write().await.shutdown().await?;
+
+        if let Some(provider) = self.tracer_provider.write().await.take() {
+            provider.shutdown().map_err(|e| MonitoringError::TracingError(format!("Failed to flush traces: {}", e)))?;
+        }
         
         info!("Monitoring system shutdown complete");
         Ok(())
@@ -364,3 +496,127 @@ pub async fn init_monitoring(config: MonitoringConfig) -> Result<MonitoringManag
     info!("A3Mailer monitoring system initialized successfully");
     Ok(manager)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Parse Prometheus text exposition output into metric families and
+    /// the names of their samples, panicking on malformed lines
+    fn parse_exposition(output: &str) -> HashMap<String, (String, HashSet<String>)> {
+        fn valid_name(name: &str) -> bool {
+            let mut chars = name.chars();
+            chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+        }
+
+        let mut families: HashMap<String, (String, HashSet<String>)> = HashMap::new();
+        let mut helps = HashSet::new();
+
+        for line in output.lines() {
+            if let Some(help) = line.strip_prefix("# HELP ") {
+                let name = help.split(' ').next().unwrap();
+                assert!(valid_name(name), "invalid name: {}", line);
+                assert!(helps.insert(name.to_string()), "duplicate HELP: {}", line);
+            } else if let Some(type_line) = line.strip_prefix("# TYPE ") {
+                let (name, metric_type) = type_line.split_once(' ').unwrap();
+                assert!(valid_name(name), "invalid name: {}", line);
+                assert!(["counter", "gauge", "histogram", "summary", "untyped"].contains(&metric_type), "{}", line);
+                assert!(
+                    families.insert(name.to_string(), (metric_type.to_string(), HashSet::new())).is_none(),
+                    "duplicate TYPE: {}",
+                    line
+                );
+            } else {
+                let (series, value) = line.rsplit_once(' ').unwrap();
+                assert!(value == "+Inf" || value == "-Inf" || value == "NaN" || value.parse::<f64>().is_ok(), "{}", line);
+
+                let name = match series.split_once('{') {
+                    Some((name, labels)) => {
+                        let labels = labels.strip_suffix('}').unwrap_or_else(|| panic!("unterminated labels: {}", line));
+                        for label in labels.split("\",").filter(|label| !label.is_empty()) {
+                            let (key, _) = label.split_once("=\"").unwrap_or_else(|| panic!("invalid label: {}", line));
+                            assert!(valid_name(key), "invalid label name: {}", line);
+                        }
+                        name
+                    }
+                    None => series,
+                };
+                assert!(valid_name(name), "invalid name: {}", line);
+
+                let family = ["_bucket", "_sum", "_count"]
+                    .iter()
+                    .filter_map(|suffix| name.strip_suffix(suffix))
+                    .find(|family| families.get(*family).is_some_and(|(t, _)| t == "histogram"))
+                    .unwrap_or(name);
+                let (_, samples) = families
+                    .get_mut(family)
+                    .unwrap_or_else(|| panic!("sample without TYPE: {}", line));
+                samples.insert(name.to_string());
+            }
+        }
+
+        families
+    }
+
+    #[tokio::test]
+    async fn test_export_prometheus() {
+        let monitoring = MonitoringManager::new(MonitoringConfig::default()).await.unwrap();
+        monitoring.record_email_processed("smtp").await.unwrap();
+        monitoring.record_email_processed("imap").await.unwrap();
+        monitoring.record_ai_inference("threat_detection", 4).await.unwrap();
+        monitoring.record_ai_inference("threat_detection", 250).await.unwrap();
+        monitoring.record_web3_operation("did_resolution", 1200, true).await.unwrap();
+        monitoring.record_web3_operation("ipfs_storage", 30, false).await.unwrap();
+
+        let output = monitoring.export_prometheus().await;
+        let families = parse_exposition(&output);
+
+        assert_eq!(families["a3mailer_emails_processed_total"].0, "counter");
+        assert!(output.contains("a3mailer_emails_processed_total{protocol=\"smtp\"} 2\n"), "{}", output);
+        assert_eq!(families["a3mailer_active_connections"].0, "gauge");
+
+        for histogram in ["a3mailer_ai_inference_duration_ms", "a3mailer_web3_operation_duration_ms"] {
+            let (metric_type, samples) = &families[histogram];
+            assert_eq!(metric_type, "histogram");
+            for suffix in ["_bucket", "_sum", "_count"] {
+                assert!(samples.contains(&format!("{}{}", histogram, suffix)), "{} missing {}", histogram, suffix);
+            }
+        }
+
+        // Observations of 0 (initial), 4 and 250 for the threat detection model
+        let series = "{model=\"threat_detection\"}";
+        assert!(output.contains(&format!("a3mailer_ai_inference_duration_ms_count{} 3\n", series)), "{}", output);
+        assert!(output.contains(&format!("a3mailer_ai_inference_duration_ms_sum{} 254\n", series)), "{}", output);
+        assert!(output.contains("a3mailer_ai_inference_duration_ms_bucket{le=\"+Inf\",model=\"threat_detection\"} 3\n"), "{}", output);
+        assert!(output.contains("a3mailer_ai_inference_duration_ms_bucket{le=\"10\",model=\"threat_detection\"} 2\n"), "{}", output);
+        assert!(output.contains("a3mailer_web3_operation_duration_ms_count{operation=\"ipfs_storage\",status=\"failure\"} 1\n"), "{}", output);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_readiness_after_startup_and_dependencies() {
+        let monitoring = MonitoringManager::new(MonitoringConfig::default()).await.unwrap();
+        assert_eq!(monitoring.liveness().await, HealthState::Healthy);
+
+        // Background tasks have not run yet
+        let readiness = monitoring.readiness().await;
+        assert_eq!(readiness.overall_status, HealthState::Unhealthy);
+        assert_eq!(readiness.components["startup"].status, HealthState::Unhealthy);
+
+        // First runs complete, but the cache has not reported in
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let readiness = monitoring.readiness().await;
+        assert_eq!(readiness.components["startup"].status, HealthState::Healthy);
+        assert_eq!(readiness.components["cache"].status, HealthState::Unknown);
+        assert_eq!(readiness.overall_status, HealthState::Unhealthy);
+
+        monitoring.set_component_health("cache", HealthState::Healthy, "Cache is reachable").await;
+        assert_eq!(monitoring.readiness().await.overall_status, HealthState::Healthy);
+
+        // Losing a dependency makes the node not ready while it stays alive
+        monitoring.set_component_health("redis", HealthState::Unhealthy, "Connection refused").await;
+        assert_eq!(monitoring.readiness().await.overall_status, HealthState::Unhealthy);
+        assert_eq!(monitoring.liveness().await, HealthState::Healthy);
+    }
+}
@@ -3,12 +3,12 @@
 //! This module provides comprehensive metrics collection compatible with
 //! Prometheus and other monitoring systems.
 
-use crate::{MonitoringConfig, Result, MonitoringError};
-use std::collections::HashMap;
+use crate::{MonitoringConfig, PerformanceMetrics, Result, MonitoringError};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
-use tracing::{info, warn, error, debug};
+use tracing::{info, debug};
 use serde::{Deserialize, Serialize};
 
 /// Metric types supported by the system
@@ -20,6 +20,60 @@ pub enum MetricType {
     Summary,
 }
 
+impl MetricType {
+    /// Prometheus type name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+            MetricType::Histogram => "histogram",
+            MetricType::Summary => "summary",
+        }
+    }
+}
+
+/// Help text for well-known metrics
+const METRIC_HELP: &[(&str, &str)] = &[
+    ("a3mailer_emails_processed_total", "Total number of emails processed"),
+    ("a3mailer_ai_inference_duration_ms", "AI model inference latency in milliseconds"),
+    ("a3mailer_web3_operation_duration_ms", "Web3 operation latency in milliseconds"),
+    ("a3mailer_uptime_seconds", "Time since the metrics collector started in seconds"),
+    ("a3mailer_active_connections", "Number of active connections"),
+    ("a3mailer_cpu_usage_percent", "CPU usage percentage"),
+    ("a3mailer_memory_usage_bytes", "Memory usage in bytes"),
+    ("a3mailer_memory_usage_percent", "Memory usage percentage"),
+    ("a3mailer_disk_usage_bytes", "Disk usage in bytes"),
+    ("a3mailer_disk_usage_percent", "Disk usage percentage"),
+    ("a3mailer_network_rx_bytes", "Network bytes received"),
+    ("a3mailer_network_tx_bytes", "Network bytes transmitted"),
+    ("a3mailer_average_response_time_ms", "Average request response time in milliseconds"),
+    ("a3mailer_requests_per_second", "Request rate"),
+    ("a3mailer_emails_processed_per_second", "Email processing rate"),
+    ("a3mailer_ai_inference_latency_ms", "Latest average AI inference latency in milliseconds"),
+    ("a3mailer_web3_operation_latency_ms", "Latest average Web3 operation latency in milliseconds"),
+];
+
+/// Series of one metric name in the Prometheus output
+struct MetricFamily {
+    metric_type: MetricType,
+    help: String,
+    samples: Vec<(&'static str, String, f64)>,
+}
+
+impl MetricFamily {
+    fn new(metric_type: MetricType, help: &str) -> Self {
+        Self {
+            metric_type,
+            help: help.to_string(),
+            samples: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, suffix: &'static str, labels: String, value: f64) {
+        self.samples.push((suffix, labels, value));
+    }
+}
+
 /// Metric data point
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricPoint {
@@ -68,7 +122,6 @@ pub struct GaugeMetric {
 
 /// Metrics collector for Prometheus-compatible metrics
 pub struct MetricsCollector {
-    config: MonitoringConfig,
     counters: Arc<RwLock<HashMap<String, CounterMetric>>>,
     gauges: Arc<RwLock<HashMap<String, GaugeMetric>>>,
     histograms: Arc<RwLock<HashMap<String, HistogramMetric>>>,
@@ -77,11 +130,10 @@ pub struct MetricsCollector {
 
 impl MetricsCollector {
     /// Create a new metrics collector
-    pub async fn new(config: &MonitoringConfig) -> Result<Self> {
+    pub async fn new(_config: &MonitoringConfig) -> Result<Self> {
         info!("Initializing metrics collector");
         
         let collector = Self {
-            config: config.clone(),
             counters: Arc::new(RwLock::new(HashMap::new())),
             gauges: Arc::new(RwLock::new(HashMap::new())),
             histograms: Arc::new(RwLock::new(HashMap::new())),
@@ -201,58 +253,91 @@ impl MetricsCollector {
         Ok(())
     }
 
-    /// Get all metrics in Prometheus format
+    /// Get all metrics in Prometheus text exposition format
+    ///
+    /// Series are grouped into one family per metric name with a single
+    /// `# HELP` and `# TYPE` line, sorted by name and label set.
     pub async fn get_prometheus_metrics(&self) -> Result<String> {
-        let mut output = String::new();
+        let mut families: BTreeMap<String, MetricFamily> = BTreeMap::new();
         
         // Add counters
         let counters = self.counters.read().await;
-        for counter in counters.values() {
-            output.push_str(&format!("# HELP {} {}\n", counter.name, counter.help));
-            output.push_str(&format!("# TYPE {} counter\n", counter.name));
-            
+        for (_, counter) in sorted_by_key(&counters) {
             let value = *counter.value.read().await;
-            let labels_str = self.format_labels(&counter.labels);
-            output.push_str(&format!("{}{} {}\n", counter.name, labels_str, value));
+            families.entry(counter.name.clone())
+                .or_insert_with(|| MetricFamily::new(MetricType::Counter, &counter.help))
+                .push("", format_labels(&counter.labels, None), value);
         }
         
         // Add gauges
         let gauges = self.gauges.read().await;
-        for gauge in gauges.values() {
-            output.push_str(&format!("# HELP {} {}\n", gauge.name, gauge.help));
-            output.push_str(&format!("# TYPE {} gauge\n", gauge.name));
-            
+        for (_, gauge) in sorted_by_key(&gauges) {
             let value = *gauge.value.read().await;
-            let labels_str = self.format_labels(&gauge.labels);
-            output.push_str(&format!("{}{} {}\n", gauge.name, labels_str, value));
+            families.entry(gauge.name.clone())
+                .or_insert_with(|| MetricFamily::new(MetricType::Gauge, &gauge.help))
+                .push("", format_labels(&gauge.labels, None), value);
         }
         
         // Add histograms
         let histograms = self.histograms.read().await;
-        for histogram in histograms.values() {
-            output.push_str(&format!("# HELP {} Histogram metric\n", histogram.name));
-            output.push_str(&format!("# TYPE {} histogram\n", histogram.name));
-            
-            let labels_str = self.format_labels(&histogram.labels);
+        for (_, histogram) in sorted_by_key(&histograms) {
+            let family = families.entry(histogram.name.clone())
+                .or_insert_with(|| MetricFamily::new(MetricType::Histogram, "Histogram metric"));
             
             // Histogram buckets
             for bucket in &histogram.buckets {
-                let mut bucket_labels = histogram.labels.clone();
-                bucket_labels.insert("le".to_string(), bucket.upper_bound.to_string());
-                let bucket_labels_str = self.format_labels(&bucket_labels);
-                output.push_str(&format!("{}_bucket{} {}\n", 
-                                        histogram.name, bucket_labels_str, bucket.count));
+                let bucket_labels = format_labels(&histogram.labels, Some(("le", format_value(bucket.upper_bound))));
+                family.push("_bucket", bucket_labels, bucket.count as f64);
             }
             
-            // Histogram count and sum
-            output.push_str(&format!("{}_count{} {}\n", histogram.name, labels_str, histogram.count));
-            output.push_str(&format!("{}_sum{} {}\n", histogram.name, labels_str, histogram.sum));
+            // Histogram sum and count
+            let labels_str = format_labels(&histogram.labels, None);
+            family.push("_sum", labels_str.clone(), histogram.sum);
+            family.push("_count", labels_str, histogram.count as f64);
+        }
+        
+        let mut output = String::new();
+        for (name, family) in families {
+            let help = METRIC_HELP.iter()
+                .find(|(metric, _)| *metric == name)
+                .map(|(_, help)| help.to_string())
+                .unwrap_or(family.help);
+            output.push_str(&format!("# HELP {} {}\n", name, help.replace('\\', "\\\\").replace('\n', "\\n")));
+            output.push_str(&format!("# TYPE {} {}\n", name, family.metric_type.as_str()));
+            for (suffix, labels, value) in family.samples {
+                output.push_str(&format!("{}{}{} {}\n", name, suffix, labels, format_value(value)));
+            }
         }
         
         debug!("Generated Prometheus metrics ({} bytes)", output.len());
         Ok(output)
     }
 
+    /// Record a performance sample as gauges
+    pub async fn record_performance_metrics(&self, metrics: &PerformanceMetrics) -> Result<()> {
+        let gauges = [
+            ("a3mailer_cpu_usage_percent", metrics.cpu_usage_percent),
+            ("a3mailer_memory_usage_bytes", metrics.memory_usage_bytes as f64),
+            ("a3mailer_memory_usage_percent", metrics.memory_usage_percent),
+            ("a3mailer_disk_usage_bytes", metrics.disk_usage_bytes as f64),
+            ("a3mailer_disk_usage_percent", metrics.disk_usage_percent),
+            ("a3mailer_network_rx_bytes", metrics.network_rx_bytes as f64),
+            ("a3mailer_network_tx_bytes", metrics.network_tx_bytes as f64),
+            ("a3mailer_active_connections", metrics.active_connections as f64),
+            ("a3mailer_average_response_time_ms", metrics.average_response_time_ms),
+            ("a3mailer_requests_per_second", metrics.requests_per_second),
+            ("a3mailer_emails_processed_per_second", metrics.emails_processed_per_second),
+            ("a3mailer_ai_inference_latency_ms", metrics.ai_inference_latency_ms),
+            ("a3mailer_web3_operation_latency_ms", metrics.web3_operation_latency_ms),
+        ];
+        
+        for (name, value) in gauges {
+            self.set_gauge(name, value, &[]).await?;
+        }
+        
+        Ok(())
+    }
+
     /// Get metrics as JSON
     pub async fn get_json_metrics(&self) -> Result<String> {
         let mut metrics = Vec::new();
@@ -358,25 +443,6 @@ impl MetricsCollector {
         key
     }
 
-    /// Format labels for Prometheus output
-    fn format_labels(&self, labels: &HashMap<String, String>) -> String {
-        if labels.is_empty() {
-            return String::new();
-        }
-        
-        let mut formatted = String::from("{");
-        let mut first = true;
-        for (key, value) in labels {
-            if !first {
-                formatted.push(',');
-            }
-            formatted.push_str(&format!("{}=\"{}\"", key, value));
-            first = false;
-        }
-        formatted.push('}');
-        formatted
-    }
-
     /// Create default histogram buckets
     fn create_default_buckets(&self, initial_value: f64) -> Vec<HistogramBucket> {
         let bounds = vec![0.001, 0.01, 0.1, 1.0, 10.0, 100.0, 1000.0, 10000.0, f64::INFINITY];
@@ -450,3 +516,43 @@ impl MetricsCollector {
         Ok(())
     }
 }
+
+/// Map entries sorted by key
+fn sorted_by_key<V>(map: &HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// Format labels for Prometheus output, sorted by name with escaped values
+fn format_labels(labels: &HashMap<String, String>, extra: Option<(&str, String)>) -> String {
+    let mut labels = labels.iter()
+        .map(|(key, value)| (key.as_str(), value.clone()))
+        .chain(extra)
+        .collect::<Vec<_>>();
+    if labels.is_empty() {
+        return String::new();
+    }
+    labels.sort();
+    
+    let formatted = labels.iter()
+        .map(|(key, value)| {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            format!("{}=\"{}\"", key, value)
+        })
+        .collect::<Vec<_>>();
+    format!("{{{}}}", formatted.join(","))
+}
+
+/// Format a sample value for Prometheus output
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,352 @@
+//! Performance Tracking for A3Mailer
+//!
+//! This module samples request performance and detects gradual degradation
+//! by comparing each sample against a rolling baseline, so slow drifts are
+//! reported well before the absolute alert thresholds are reached.
+
+use crate::{MonitoringConfig, PerformanceMetrics, Alert, AlertSeverity, Result};
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+use tokio::sync::RwLock;
+use tracing::{info, warn, debug};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+
+/// Metrics checked for trends, with the direction that indicates degradation
+const TREND_METRICS: &[(&str, TrendDirection)] = &[
+    ("average_response_time_ms", TrendDirection::Rising),
+    ("requests_per_second", TrendDirection::Falling),
+];
+
+/// Trend detection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendConfig {
+    pub enabled: bool,
+    /// Length of the rolling baseline window in seconds
+    pub window_seconds: u64,
+    /// Samples required in the window before comparing against the baseline
+    pub min_samples: usize,
+    /// Factor by which a value must move away from the baseline to alert
+    pub deviation_ratio: f64,
+}
+
+impl Default for TrendConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_seconds: 600,
+            min_samples: 5,
+            deviation_ratio: 2.0,
+        }
+    }
+}
+
+/// Direction in which a metric degrades
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TrendDirection {
+    Rising,
+    Falling,
+}
+
+/// Requests handled since the last sample
+struct RequestWindow {
+    count: u64,
+    total_latency_ms: u64,
+    started: Instant,
+}
+
+impl RequestWindow {
+    fn new() -> Self {
+        Self {
+            count: 0,
+            total_latency_ms: 0,
+            started: Instant::now(),
+        }
+    }
+}
+
+/// Performance tracker with trend-based alerting
+pub struct PerformanceTracker {
+    config: MonitoringConfig,
+    trend_config: TrendConfig,
+    current: RwLock<PerformanceMetrics>,
+    history: RwLock<VecDeque<PerformanceMetrics>>,
+    requests: RwLock<RequestWindow>,
+    trend_alerts: RwLock<HashMap<String, Alert>>,
+}
+
+impl PerformanceTracker {
+    /// Create a new performance tracker
+    pub async fn new(config: &MonitoringConfig) -> Result<Self> {
+        info!("Initializing performance tracker");
+
+        Ok(Self {
+            config: config.clone(),
+            trend_config: TrendConfig::default(),
+            current: RwLock::new(empty_sample(Utc::now())),
+            history: RwLock::new(VecDeque::new()),
+            requests: RwLock::new(RequestWindow::new()),
+            trend_alerts: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Use a custom trend detection configuration
+    pub fn with_trend_config(mut self, trend_config: TrendConfig) -> Self {
+        self.trend_config = trend_config;
+        self
+    }
+
+    /// Record the latency of a handled request
+    pub async fn record_request(&self, latency_ms: u64) {
+        let mut requests = self.requests.write().await;
+        requests.count += 1;
+        requests.total_latency_ms += latency_ms;
+    }
+
+    /// Take a sample of the requests handled since the previous one
+    pub async fn collect_metrics(&self) -> Result<()> {
+        debug!("Collecting performance metrics");
+
+        let (average_response_time_ms, requests_per_second) = {
+            let mut requests = self.requests.write().await;
+            let elapsed = requests.started.elapsed().as_secs_f64();
+            let average = if requests.count > 0 {
+                requests.total_latency_ms as f64 / requests.count as f64
+            } else {
+                0.0
+            };
+            let rate = if elapsed > 0.0 { requests.count as f64 / elapsed } else { 0.0 };
+            *requests = RequestWindow::new();
+            (average, rate)
+        };
+
+        // System resource figures are carried over from the previous sample
+        let sample = PerformanceMetrics {
+            average_response_time_ms,
+            requests_per_second,
+            timestamp: Utc::now(),
+            ..self.current.read().await.clone()
+        };
+
+        for alert in self.record_sample(sample).await {
+            warn!("Performance trend alert: {}", alert.description);
+        }
+
+        Ok(())
+    }
+
+    /// Record a sample and return the trend alerts it fired
+    pub async fn record_sample(&self, sample: PerformanceMetrics) -> Vec<Alert> {
+        let mut history = self.history.write().await;
+        let window_start = sample.timestamp - ChronoDuration::seconds(self.trend_config.window_seconds as i64);
+        history.retain(|previous| previous.timestamp >= window_start);
+
+        let fired = if self.trend_config.enabled {
+            self.detect_trends(&history, &sample).await
+        } else {
+            Vec::new()
+        };
+
+        history.push_back(sample.clone());
+        *self.current.write().await = sample;
+
+        fired
+    }
+
+    /// Compare a sample against the median of the window before it
+    async fn detect_trends(&self, history: &VecDeque<PerformanceMetrics>, sample: &PerformanceMetrics) -> Vec<Alert> {
+        let mut fired = Vec::new();
+
+        if history.len() < self.trend_config.min_samples {
+            return fired;
+        }
+
+        let mut trend_alerts = self.trend_alerts.write().await;
+        let ratio = self.trend_config.deviation_ratio;
+
+        for (metric, direction) in TREND_METRICS {
+            let baseline = median(history.iter().map(|previous| metric_value(previous, metric)).collect());
+            if baseline <= 0.0 {
+                continue;
+            }
+
+            let current = metric_value(sample, metric);
+            let deviation = current / baseline;
+            let (degraded, threshold) = match direction {
+                TrendDirection::Rising => (deviation >= ratio, baseline * ratio),
+                TrendDirection::Falling => (deviation <= 1.0 / ratio, baseline / ratio),
+            };
+
+            if !degraded {
+                if let Some(alert) = trend_alerts.remove(*metric) {
+                    info!("Performance trend resolved: {}", alert.title);
+                }
+                continue;
+            }
+
+            let description = format!(
+                "{} is {:.2}, {:.2}x the baseline of {:.2} over the last {}s",
+                metric, current, deviation, baseline, self.trend_config.window_seconds
+            );
+
+            match trend_alerts.get_mut(*metric) {
+                Some(alert) => {
+                    alert.description = description;
+                    alert.current_value = current;
+                    alert.threshold_value = threshold;
+                    alert.baseline_value = Some(baseline);
+                    alert.deviation = Some(deviation);
+                }
+                None => {
+                    let alert = Alert {
+                        id: format!("trend_{}", uuid::Uuid::new_v4()),
+                        severity: AlertSeverity::Warning,
+                        title: format!("Performance trend anomaly in {}", metric),
+                        description,
+                        component: "performance".to_string(),
+                        metric_name: metric.to_string(),
+                        current_value: current,
+                        threshold_value: threshold,
+                        baseline_value: Some(baseline),
+                        deviation: Some(deviation),
+                        created_at: sample.timestamp,
+                        resolved_at: None,
+                    };
+                    trend_alerts.insert(metric.to_string(), alert.clone());
+                    fired.push(alert);
+                }
+            }
+        }
+
+        fired
+    }
+
+    /// Get current performance metrics
+    pub async fn get_current_metrics(&self) -> Result<PerformanceMetrics> {
+        Ok(self.current.read().await.clone())
+    }
+
+    /// Get active trend alerts
+    pub async fn get_trend_alerts(&self) -> Vec<Alert> {
+        self.trend_alerts.read().await.values().cloned().collect()
+    }
+
+    /// Get performance tracker statistics
+    pub async fn get_stats(&self) -> Result<HashMap<String, String>> {
+        let mut stats = HashMap::new();
+
+        stats.insert("performance_samples".to_string(), self.history.read().await.len().to_string());
+        stats.insert("performance_trend_alerts".to_string(), self.trend_alerts.read().await.len().to_string());
+        stats.insert("performance_sampling_rate".to_string(), self.config.performance_sampling_rate.to_string());
+
+        Ok(stats)
+    }
+
+    /// Shutdown performance tracker
+    pub async fn shutdown(&self) -> Result<()> {
+        info!("Shutting down performance tracker");
+
+        self.history.write().await.clear();
+        self.trend_alerts.write().await.clear();
+
+        info!("Performance tracker shutdown complete");
+        Ok(())
+    }
+}
+
+/// Value of a trend metric in a sample
+fn metric_value(metrics: &PerformanceMetrics, metric: &str) -> f64 {
+    match metric {
+        "average_response_time_ms" => metrics.average_response_time_ms,
+        "requests_per_second" => metrics.requests_per_second,
+        _ => 0.0,
+    }
+}
+
+/// Median of a set of values
+fn median(mut values: Vec<f64>) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    }
+}
+
+/// Sample with every figure zeroed
+fn empty_sample(timestamp: DateTime<Utc>) -> PerformanceMetrics {
+    PerformanceMetrics {
+        cpu_usage_percent: 0.0,
+        memory_usage_bytes: 0,
+        memory_usage_percent: 0.0,
+        disk_usage_bytes: 0,
+        disk_usage_percent: 0.0,
+        network_rx_bytes: 0,
+        network_tx_bytes: 0,
+        active_connections: 0,
+        average_response_time_ms: 0.0,
+        requests_per_second: 0.0,
+        emails_processed_per_second: 0.0,
+        ai_inference_latency_ms: 0.0,
+        web3_operation_latency_ms: 0.0,
+        timestamp,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(minute: i64, average_response_time_ms: f64) -> PerformanceMetrics {
+        PerformanceMetrics {
+            average_response_time_ms,
+            requests_per_second: 100.0,
+            ..empty_sample(DateTime::<Utc>::UNIX_EPOCH + ChronoDuration::minutes(minute))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trend_alert_before_threshold() {
+        let config = MonitoringConfig::default();
+        let absolute_threshold = config.alert_thresholds.email_processing_latency_ms as f64;
+        let tracker = PerformanceTracker::new(&config).await.unwrap();
+
+        // A flat series never deviates from its baseline
+        for minute in 0..20 {
+            assert!(tracker.record_sample(sample(minute, 50.0)).await.is_empty());
+        }
+
+        // Latency grows 15% a minute and eventually crosses the threshold
+        let mut latency = 50.0;
+        let mut trend_alert = None;
+        for minute in 20..60 {
+            latency *= 1.15;
+            let fired = tracker.record_sample(sample(minute, latency)).await;
+            if let Some(alert) = fired.into_iter().next() {
+                trend_alert = Some((minute, alert));
+                break;
+            }
+        }
+
+        let (minute, alert) = trend_alert.expect("no trend alert fired");
+        assert_eq!(alert.severity, AlertSeverity::Warning);
+        assert_eq!(alert.metric_name, "average_response_time_ms");
+        assert!(alert.current_value < absolute_threshold, "fired at {}", alert.current_value);
+
+        let baseline = alert.baseline_value.unwrap();
+        let deviation = alert.deviation.unwrap();
+        assert!(baseline >= 50.0 && baseline < alert.current_value);
+        assert!(deviation >= 2.0);
+        assert!((deviation - alert.current_value / baseline).abs() < 1e-9);
+
+        // The alert stays active without firing again while latency keeps rising
+        latency *= 1.15;
+        assert!(tracker.record_sample(sample(minute + 1, latency)).await.is_empty());
+        assert_eq!(tracker.get_trend_alerts().await.len(), 1);
+    }
+}
@@ -0,0 +1,201 @@
+//! OpenTelemetry trace export
+//!
+//! Bridges `tracing` spans to an OTLP collector so a message can be followed
+//! across the SMTP, IMAP and JMAP handlers that touch it.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use opentelemetry::global;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider, TracerProviderBuilder};
+use opentelemetry_sdk::Resource;
+use serde::{Deserialize, Serialize};
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::{MonitoringConfig, MonitoringError, Result};
+
+/// Name of the tracer used for all exported spans
+const TRACER_NAME: &str = "a3mailer";
+
+/// Trace export configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TracingConfig {
+    /// OTLP collector endpoint, trace export is disabled when unset
+    pub endpoint: Option<String>,
+    pub protocol: OtlpProtocol,
+    pub service_name: String,
+    pub export_timeout_ms: u64,
+}
+
+/// OTLP transport protocol
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum OtlpProtocol {
+    Grpc,
+    Http,
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            protocol: OtlpProtocol::Grpc,
+            service_name: "a3mailer".to_string(),
+            export_timeout_ms: 10000,
+        }
+    }
+}
+
+/// Create a tracer provider builder with the configured sampler and resource
+///
+/// Sampling follows the parent span when there is one, so a trace started by
+/// one protocol handler is kept or dropped as a whole.
+pub(crate) fn tracer_provider_builder(config: &MonitoringConfig) -> TracerProviderBuilder {
+    let sampling_rate = config.performance_sampling_rate.clamp(0.0, 1.0);
+
+    SdkTracerProvider::builder()
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(sampling_rate))))
+        .with_resource(
+            Resource::builder()
+                .with_service_name(config.tracing.service_name.clone())
+                .build(),
+        )
+}
+
+/// Build the OTLP span exporter for an endpoint
+fn otlp_exporter(config: &TracingConfig, endpoint: &str) -> Result<opentelemetry_otlp::SpanExporter> {
+    let timeout = Duration::from_millis(config.export_timeout_ms);
+
+    let exporter = match config.protocol {
+        OtlpProtocol::Grpc => opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .with_timeout(timeout)
+            .build(),
+        OtlpProtocol::Http => opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .with_endpoint(endpoint)
+            .with_timeout(timeout)
+            .build(),
+    };
+
+    exporter.map_err(|e| MonitoringError::TracingError(format!("Failed to build OTLP span exporter: {}", e)))
+}
+
+/// Install the OTLP exporter and tracing subscriber
+///
+/// Returns `None` when no endpoint is configured.
+pub(crate) fn init_tracer_provider(config: &MonitoringConfig) -> Result<Option<SdkTracerProvider>> {
+    let endpoint = match &config.tracing.endpoint {
+        Some(endpoint) => endpoint,
+        None => return Ok(None),
+    };
+
+    let provider = tracer_provider_builder(config)
+        .with_batch_exporter(otlp_exporter(&config.tracing, endpoint)?)
+        .build();
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(provider.clone());
+
+    tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer(TRACER_NAME)))
+        .with(tracing_subscriber::fmt::layer())
+        .try_init()
+        .map_err(|e| MonitoringError::TracingError(format!("Failed to install tracing subscriber: {}", e)))?;
+
+    Ok(Some(provider))
+}
+
+/// Start a span for an incoming email request
+///
+/// The span carries the standard request attributes and can be entered
+/// directly, attached with `Instrument::instrument`, or used as the parent
+/// of an instrumented handler via `#[instrument(parent = &span)]`. The
+/// `mail.message_id` and `otel.status_code` fields are recorded once known.
+pub fn request_span(protocol: &str, operation: &str, client_address: &str) -> Span {
+    tracing::info_span!(
+        "email.request",
+        otel.kind = "server",
+        otel.status_code = tracing::field::Empty,
+        mail.protocol = protocol,
+        mail.operation = operation,
+        mail.message_id = tracing::field::Empty,
+        client.address = client_address,
+    )
+}
+
+/// Serialize the trace context of a span into W3C trace context headers
+pub fn inject_context(span: &Span) -> HashMap<String, String> {
+    let mut carrier = HashMap::new();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&span.context(), &mut carrier)
+    });
+    carrier
+}
+
+/// Continue a trace propagated from another handler
+pub fn set_parent_context(span: &Span, carrier: &HashMap<String, String>) {
+    let parent = global::get_text_map_propagator(|propagator| propagator.extract(carrier));
+    span.set_parent(parent);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::Value;
+    use opentelemetry_sdk::trace::InMemorySpanExporter;
+
+    fn run_request(sampling_rate: f64) -> Vec<opentelemetry_sdk::trace::SpanData> {
+        let config = MonitoringConfig {
+            performance_sampling_rate: sampling_rate,
+            ..Default::default()
+        };
+        let exporter = InMemorySpanExporter::default();
+        let provider = tracer_provider_builder(&config)
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer(TRACER_NAME)));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = request_span("smtp", "DATA", "192.0.2.1");
+            let _guard = span.enter();
+            span.record("mail.message_id", "<test@example.org>");
+            span.record("otel.status_code", "OK");
+        });
+
+        provider.force_flush().unwrap();
+        exporter.get_finished_spans().unwrap()
+    }
+
+    #[test]
+    fn test_request_span_exported() {
+        let spans = run_request(1.0);
+        assert_eq!(spans.len(), 1);
+
+        let span = &spans[0];
+        assert_eq!(span.name, "email.request");
+        assert_eq!(span.span_kind, opentelemetry::trace::SpanKind::Server);
+
+        let attributes: HashMap<&str, &Value> = span
+            .attributes
+            .iter()
+            .map(|kv| (kv.key.as_str(), &kv.value))
+            .collect();
+        assert_eq!(attributes["mail.protocol"].as_str(), "smtp");
+        assert_eq!(attributes["mail.operation"].as_str(), "DATA");
+        assert_eq!(attributes["mail.message_id"].as_str(), "<test@example.org>");
+        assert_eq!(attributes["client.address"].as_str(), "192.0.2.1");
+    }
+
+    #[test]
+    fn test_sampling_rate_honored() {
+        assert!(run_request(0.0).is_empty());
+    }
+}
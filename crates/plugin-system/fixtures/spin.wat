@@ -0,0 +1,27 @@
+;; Misbehaving plugin used by the test suite. It implements the A3Mailer
+;; plugin ABI but never returns from a hook.
+(module
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 1024))
+
+  (data (i32.const 0) "{\"name\":\"spin\",\"version\":\"1.0.0\",\"description\":\"Never returns from a hook\",\"author\":\"A3Mailer\",\"api_version\":\"1.0\",\"dependencies\":[]}")
+
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+    (local.get $ptr))
+
+  (func (export "metadata") (result i64)
+    (i64.const 133))
+
+  (func (export "initialize") (result i32)
+    (i32.const 0))
+
+  (func (export "execute_hook")
+    (param $hook_ptr i32) (param $hook_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i64)
+    (loop $forever
+      (br $forever))
+    (i64.const -1))
+)
@@ -0,0 +1,50 @@
+;; Minimal plugin used by the test suite. It implements the A3Mailer plugin
+;; ABI and replaces the hook payload with an uppercased copy.
+(module
+  (import "a3mailer" "log" (func $log (param i32 i32 i32)))
+
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 1024))
+
+  (data (i32.const 0) "{\"name\":\"uppercase\",\"version\":\"1.0.0\",\"description\":\"Uppercases ASCII payloads\",\"author\":\"A3Mailer\",\"api_version\":\"1.0\",\"dependencies\":[]}")
+  (data (i32.const 512) "uppercase plugin initialized")
+
+  (func $alloc (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+    (local.get $ptr))
+
+  (func (export "metadata") (result i64)
+    (i64.const 138))
+
+  (func (export "initialize") (result i32)
+    (call $log (i32.const 2) (i32.const 512) (i32.const 28))
+    (i32.const 0))
+
+  ;; Returns a "replace" outcome: tag byte 1 followed by the uppercased payload
+  (func (export "execute_hook")
+    (param $hook_ptr i32) (param $hook_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i64)
+    (local $out i32)
+    (local $i i32)
+    (local $c i32)
+    (local.set $out (call $alloc (i32.add (local.get $data_len) (i32.const 1))))
+    (i32.store8 (local.get $out) (i32.const 1))
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (local.get $data_len)))
+        (local.set $c (i32.load8_u (i32.add (local.get $data_ptr) (local.get $i))))
+        (if (i32.and
+              (i32.ge_u (local.get $c) (i32.const 97))
+              (i32.le_u (local.get $c) (i32.const 122)))
+          (then (local.set $c (i32.sub (local.get $c) (i32.const 32)))))
+        (i32.store8
+          (i32.add (i32.add (local.get $out) (i32.const 1)) (local.get $i))
+          (local.get $c))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $out)) (i64.const 32))
+      (i64.extend_i32_u (i32.add (local.get $data_len) (i32.const 1)))))
+)
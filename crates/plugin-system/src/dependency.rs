@@ -0,0 +1,99 @@
+//! Plugin dependency resolution and API version checks
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use semver::{Version, VersionReq};
+
+use crate::{PluginError, PluginMetadata};
+
+/// Plugin API versions the host is able to drive
+pub const SUPPORTED_API_VERSIONS: &str = "^1.0";
+
+/// Verify that the plugin targets an API version supported by the host
+pub fn check_api_version(metadata: &PluginMetadata) -> Result<(), PluginError> {
+    let mismatch = || PluginError::ApiVersionMismatch {
+        expected: SUPPORTED_API_VERSIONS.to_string(),
+        actual: metadata.api_version.clone(),
+    };
+    let supported = VersionReq::parse(SUPPORTED_API_VERSIONS).expect("valid version requirement");
+    let version = parse_lenient(&metadata.api_version).ok_or_else(mismatch)?;
+
+    if supported.matches(&version) {
+        Ok(())
+    } else {
+        Err(mismatch())
+    }
+}
+
+/// Accept `1`, `1.2` and `1.2.3` style versions
+fn parse_lenient(version: &str) -> Option<Version> {
+    let version = version.trim().trim_start_matches('v');
+    match version.split('.').count() {
+        1 => Version::parse(&format!("{}.0.0", version)).ok(),
+        2 => Version::parse(&format!("{}.0", version)).ok(),
+        _ => Version::parse(version).ok(),
+    }
+}
+
+/// Order `candidates` so that every plugin comes after its dependencies.
+///
+/// Dependencies may be satisfied either by another candidate or by a plugin
+/// in `loaded`. Returns indices into `candidates`; plugins without ordering
+/// constraints between them are sorted by name to keep loading deterministic.
+pub fn resolve_order(
+    candidates: &[PluginMetadata],
+    loaded: &HashSet<String>,
+) -> Result<Vec<usize>, PluginError> {
+    let by_name = candidates
+        .iter()
+        .enumerate()
+        .map(|(idx, metadata)| (metadata.name.as_str(), idx))
+        .collect::<HashMap<_, _>>();
+
+    let mut pending = vec![0usize; candidates.len()];
+    let mut dependents = vec![Vec::new(); candidates.len()];
+    for (idx, metadata) in candidates.iter().enumerate() {
+        for dependency in &metadata.dependencies {
+            if let Some(&dep_idx) = by_name.get(dependency.as_str()) {
+                pending[idx] += 1;
+                dependents[dep_idx].push(idx);
+            } else if !loaded.contains(dependency) {
+                return Err(PluginError::DependencyNotSatisfied(format!(
+                    "{} requires {}",
+                    metadata.name, dependency
+                )));
+            }
+        }
+    }
+
+    let mut ready = candidates
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| pending[*idx] == 0)
+        .map(|(idx, metadata)| (metadata.name.as_str(), idx))
+        .collect::<BTreeSet<_>>();
+    let mut order = Vec::with_capacity(candidates.len());
+
+    while let Some((_, idx)) = ready.pop_first() {
+        order.push(idx);
+        for &dependent in &dependents[idx] {
+            pending[dependent] -= 1;
+            if pending[dependent] == 0 {
+                ready.insert((candidates[dependent].name.as_str(), dependent));
+            }
+        }
+    }
+
+    if order.len() != candidates.len() {
+        let mut cycle = candidates
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| pending[*idx] > 0)
+            .map(|(_, metadata)| metadata.name.as_str())
+            .collect::<Vec<_>>();
+        cycle.sort_unstable();
+        return Err(PluginError::DependencyCycle(cycle.join(", ")));
+    }
+
+    Ok(order)
+}
@@ -0,0 +1,77 @@
+//! Fault isolation for plugin calls
+//!
+//! Plugin code must never take the host down with it. Every call into a
+//! plugin goes through [`catch_panic`], and hook executions additionally run
+//! on the blocking pool under a deadline so a stuck plugin only costs its own
+//! result.
+//!
+//! Only WASM plugins can actually be stopped at that deadline: their store
+//! carries an epoch deadline derived from the same hook timeout, so wasmtime
+//! traps the call and the blocking thread is released. Native plugins run
+//! arbitrary machine code and cannot be preempted; a timed-out native call
+//! keeps its blocking thread until it returns on its own.
+
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{HookOutcome, Plugin, PluginError};
+
+/// Shared handle to a loaded plugin
+pub(crate) type PluginHandle = Arc<parking_lot::RwLock<Box<dyn Plugin>>>;
+
+/// Run `f`, converting a panic raised by the plugin into [`PluginError::ExecutionFailed`]
+pub(crate) fn catch_panic<T>(
+    plugin: &str,
+    f: impl FnOnce() -> Result<T, PluginError>,
+) -> Result<T, PluginError> {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        Err(PluginError::ExecutionFailed(format!(
+            "plugin {} panicked: {}",
+            plugin,
+            panic_message(payload.as_ref())
+        )))
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// Execute `hook` on the blocking pool, giving up once `timeout` elapses
+///
+/// WASM plugins are interrupted by their epoch deadline at about the same
+/// time. A native plugin that times out keeps running in the background until
+/// it returns, and its result is discarded.
+pub(crate) async fn execute_with_timeout(
+    name: &str,
+    plugin: PluginHandle,
+    hook: &str,
+    data: Vec<u8>,
+    timeout: Duration,
+) -> Result<HookOutcome, PluginError> {
+    let task = {
+        let name = name.to_string();
+        let hook = hook.to_string();
+        tokio::task::spawn_blocking(move || {
+            catch_panic(&name, || plugin.read().execute_hook(&hook, &data))
+        })
+    };
+
+    match tokio::time::timeout(timeout, task).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => Err(PluginError::ExecutionFailed(format!(
+            "plugin {} aborted: {}",
+            name, e
+        ))),
+        Err(_) => Err(PluginError::ExecutionFailed(format!(
+            "plugin {} exceeded the {:?} hook timeout",
+            name, timeout
+        ))),
+    }
+}
@@ -29,15 +29,28 @@
 //! ```
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 use tracing::{info, warn, error, debug};
 
+pub mod dependency;
+mod isolation;
+mod reload;
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm;
+
+pub use dependency::SUPPORTED_API_VERSIONS;
+
 /// Plugin manager for handling dynamic plugin loading and execution
 pub struct PluginManager {
-    plugins: Arc<RwLock<HashMap<String, Box<dyn Plugin>>>>,
+    plugins: Arc<RwLock<HashMap<String, isolation::PluginHandle>>>,
+    sources: Arc<RwLock<HashMap<PathBuf, reload::PluginSource>>>,
     config: PluginConfig,
+    #[cfg(feature = "wasm-plugins")]
+    wasm: wasm::WasmRuntime,
 }
 
 /// Configuration for the plugin system
@@ -46,7 +59,17 @@ pub struct PluginConfig {
     pub plugin_dir: String,
     pub max_plugins: usize,
     pub enable_hot_reload: bool,
+    /// How often plugin artifacts are checked for changes when hot reload is enabled
+    pub hot_reload_interval: Duration,
+    /// Maximum time a single plugin may spend handling a hook
+    pub hook_timeout: Duration,
     pub security_level: SecurityLevel,
+    /// Largest linear memory, in bytes, a WASM plugin may allocate
+    pub wasm_memory_limit: usize,
+    /// Fuel granted to a WASM plugin for each call into it, roughly one unit per instruction
+    pub wasm_fuel: u64,
+    /// Per-plugin key/value settings, keyed by plugin name
+    pub plugin_settings: HashMap<String, HashMap<String, String>>,
 }
 
 /// Security levels for plugin execution
@@ -69,21 +92,55 @@ pub trait Plugin: Send + Sync {
     fn initialize(&mut self) -> Result<(), PluginError>;
     
     /// Execute a plugin hook
-    fn execute_hook(&self, hook: &str, data: &[u8]) -> Result<Vec<u8>, PluginError>;
+    fn execute_hook(&self, hook: &str, data: &[u8]) -> Result<HookOutcome, PluginError>;
     
     /// Cleanup plugin resources
     fn cleanup(&mut self) -> Result<(), PluginError>;
 }
 
+/// Result of a single plugin handling a hook
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookOutcome {
+    /// Leave the payload untouched; the bytes are the plugin's own output
+    Continue(Vec<u8>),
+    /// Rewrite the payload seen by the following plugins and the caller
+    Replace(Vec<u8>),
+    /// Halt the pipeline, skipping every remaining plugin
+    Reject(String),
+}
+
+/// Combined result of running a hook through every loaded plugin
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HookResult {
+    /// Payload after all `Replace` outcomes were applied
+    pub payload: Vec<u8>,
+    /// `Continue` outputs in execution order, tagged with the producing plugin
+    pub outputs: Vec<(String, Vec<u8>)>,
+    /// Plugin name and reason when the payload was rejected
+    pub rejection: Option<(String, String)>,
+}
+
+impl HookResult {
+    pub fn is_rejected(&self) -> bool {
+        self.rejection.is_some()
+    }
+}
+
 /// Plugin metadata information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginMetadata {
     pub name: String,
     pub version: String,
+    #[serde(default)]
     pub description: String,
+    #[serde(default)]
     pub author: String,
     pub api_version: String,
+    #[serde(default)]
     pub dependencies: Vec<String>,
+    /// Hook execution order: lower values run first, ties are broken by name
+    #[serde(default)]
+    pub priority: i32,
 }
 
 /// Plugin system errors
@@ -106,6 +163,9 @@ pub enum PluginError {
     
     #[error("Dependency not satisfied: {0}")]
     DependencyNotSatisfied(String),
+
+    #[error("Dependency cycle detected: {0}")]
+    DependencyCycle(String),
 }
 
 impl Default for PluginConfig {
@@ -114,7 +174,12 @@ impl Default for PluginConfig {
             plugin_dir: "plugins".to_string(),
             max_plugins: 100,
             enable_hot_reload: false,
+            hot_reload_interval: Duration::from_secs(2),
+            hook_timeout: Duration::from_secs(5),
             security_level: SecurityLevel::Standard,
+            wasm_memory_limit: 64 * 1024 * 1024,
+            wasm_fuel: 500_000_000,
+            plugin_settings: HashMap::new(),
         }
     }
 }
@@ -131,7 +196,10 @@ impl PluginManager {
         
         Self {
             plugins: Arc::new(RwLock::new(HashMap::new())),
+            sources: Arc::new(RwLock::new(HashMap::new())),
             config,
+            #[cfg(feature = "wasm-plugins")]
+            wasm: wasm::WasmRuntime::new(),
         }
     }
     
@@ -139,28 +207,145 @@ impl PluginManager {
     pub async fn load_plugin<P: AsRef<Path>>(&self, path: P) -> Result<String, PluginError> {
         let path = path.as_ref();
         info!("Loading plugin from: {:?}", path);
-        
-        // TODO: Implement actual plugin loading logic
-        // This is a placeholder implementation
-        
-        let plugin_name = path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-            
-        warn!("Plugin loading not yet implemented: {}", plugin_name);
-        
-        Ok(plugin_name)
+
+        if self.plugins.read().await.len() >= self.config.max_plugins {
+            warn!("Refusing to load {:?}: plugin limit reached", path);
+            return Err(PluginError::LoadingFailed(format!(
+                "maximum number of plugins ({}) reached",
+                self.config.max_plugins
+            )));
+        }
+
+        let plugin = self.instantiate(path)?;
+        let name = self.register_plugin(plugin).await?;
+        self.track_source(path, &name).await;
+
+        Ok(name)
     }
-    
+
+    /// Load several plugins, initializing each one after its dependencies
+    pub async fn load_plugins<I, P>(&self, paths: I) -> Result<Vec<String>, PluginError>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut sources = HashMap::new();
+        let mut plugins = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            let plugin = self.instantiate(path)?;
+            sources.insert(plugin.metadata().name, path.to_path_buf());
+            plugins.push(plugin);
+        }
+
+        let names = self.register_plugins(plugins).await?;
+        for name in &names {
+            if let Some(path) = sources.get(name) {
+                self.track_source(path, name).await;
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// Register several plugins in dependency order
+    ///
+    /// Dependencies may be provided by plugins in the same batch or by plugins
+    /// that are already loaded. Nothing is registered when the batch cannot be
+    /// ordered.
+    pub async fn register_plugins(
+        &self,
+        plugins: Vec<Box<dyn Plugin>>,
+    ) -> Result<Vec<String>, PluginError> {
+        let metadata = plugins.iter().map(|p| p.metadata()).collect::<Vec<_>>();
+        for metadata in &metadata {
+            dependency::check_api_version(metadata)?;
+        }
+
+        let loaded = self.plugins.read().await.keys().cloned().collect();
+        let order = dependency::resolve_order(&metadata, &loaded)?;
+
+        let mut plugins = plugins.into_iter().map(Some).collect::<Vec<_>>();
+        let mut names = Vec::with_capacity(order.len());
+        for idx in order {
+            if let Some(plugin) = plugins[idx].take() {
+                names.push(self.register_plugin(plugin).await?);
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// Initialize an already constructed plugin and add it to the manager
+    pub async fn register_plugin(&self, mut plugin: Box<dyn Plugin>) -> Result<String, PluginError> {
+        let metadata = plugin.metadata();
+        dependency::check_api_version(&metadata)?;
+        {
+            let plugins = self.plugins.read().await;
+            if let Some(missing) = metadata
+                .dependencies
+                .iter()
+                .find(|dependency| !plugins.contains_key(*dependency))
+            {
+                return Err(PluginError::DependencyNotSatisfied(format!(
+                    "{} requires {}",
+                    metadata.name, missing
+                )));
+            }
+        }
+        isolation::catch_panic(&metadata.name, || plugin.initialize())?;
+
+        let mut plugins = self.plugins.write().await;
+        if plugins.len() >= self.config.max_plugins {
+            let _ = isolation::catch_panic(&metadata.name, || plugin.cleanup());
+            return Err(PluginError::LoadingFailed(format!(
+                "maximum number of plugins ({}) reached",
+                self.config.max_plugins
+            )));
+        }
+        if plugins.contains_key(&metadata.name) {
+            let _ = isolation::catch_panic(&metadata.name, || plugin.cleanup());
+            return Err(PluginError::LoadingFailed(format!(
+                "plugin {} is already loaded",
+                metadata.name
+            )));
+        }
+        plugins.insert(
+            metadata.name.clone(),
+            Arc::new(parking_lot::RwLock::new(plugin)),
+        );
+
+        info!("Plugin {} v{} loaded successfully", metadata.name, metadata.version);
+        Ok(metadata.name)
+    }
+
+    /// Create a plugin instance for the artifact at `path` based on its extension
+    fn instantiate(&self, path: &Path) -> Result<Box<dyn Plugin>, PluginError> {
+        match path.extension().and_then(|e| e.to_str()) {
+            #[cfg(feature = "wasm-plugins")]
+            Some("wasm" | "wat") => Ok(Box::new(self.wasm.load(path, &self.config)?)),
+            Some(ext) => Err(PluginError::LoadingFailed(format!(
+                "unsupported plugin format: .{}",
+                ext
+            ))),
+            None => Err(PluginError::LoadingFailed(format!(
+                "cannot determine plugin format of {:?}",
+                path
+            ))),
+        }
+    }
+
     /// Unload a plugin by name
     pub async fn unload_plugin(&self, name: &str) -> Result<(), PluginError> {
         info!("Unloading plugin: {}", name);
         
         let mut plugins = self.plugins.write().await;
-        if let Some(mut plugin) = plugins.remove(name) {
-            plugin.cleanup()?;
+        if let Some(plugin) = plugins.remove(name) {
+            self.sources
+                .write()
+                .await
+                .retain(|_, source| source.name != name);
+            isolation::catch_panic(name, || plugin.write().cleanup())?;
             info!("Plugin {} unloaded successfully", name);
             Ok(())
         } else {
@@ -168,18 +353,50 @@ impl PluginManager {
         }
     }
     
-    /// Execute a hook across all loaded plugins
-    pub async fn execute_hook(&self, hook: &str, data: &[u8]) -> Result<Vec<Vec<u8>>, PluginError> {
+    /// Execute a hook across all loaded plugins in priority order
+    ///
+    /// Each plugin sees the payload as rewritten by the plugins before it.
+    /// Execution stops at the first plugin that rejects the payload. A plugin
+    /// that fails, panics or exceeds `hook_timeout` is skipped.
+    pub async fn execute_hook(&self, hook: &str, data: &[u8]) -> Result<HookResult, PluginError> {
         debug!("Executing hook '{}' across all plugins", hook);
-        
-        let plugins = self.plugins.read().await;
-        let mut results = Vec::new();
-        
-        for (name, plugin) in plugins.iter() {
-            match plugin.execute_hook(hook, data) {
-                Ok(result) => {
+
+        let mut ordered = self
+            .plugins
+            .read()
+            .await
+            .iter()
+            .map(|(name, plugin)| (plugin.read().metadata().priority, name.clone(), plugin.clone()))
+            .collect::<Vec<_>>();
+        ordered.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+        let mut result = HookResult {
+            payload: data.to_vec(),
+            ..Default::default()
+        };
+
+        for (_, name, plugin) in ordered {
+            let outcome = isolation::execute_with_timeout(
+                &name,
+                plugin,
+                hook,
+                result.payload.clone(),
+                self.config.hook_timeout,
+            )
+            .await;
+            match outcome {
+                Ok(HookOutcome::Continue(output)) => {
                     debug!("Plugin {} executed hook '{}' successfully", name, hook);
-                    results.push(result);
+                    result.outputs.push((name, output));
+                }
+                Ok(HookOutcome::Replace(payload)) => {
+                    debug!("Plugin {} replaced the payload of hook '{}'", name, hook);
+                    result.payload = payload;
+                }
+                Ok(HookOutcome::Reject(reason)) => {
+                    info!("Plugin {} rejected hook '{}': {}", name, hook, reason);
+                    result.rejection = Some((name, reason));
+                    break;
                 }
                 Err(e) => {
                     error!("Plugin {} failed to execute hook '{}': {}", name, hook, e);
@@ -187,10 +404,10 @@ impl PluginManager {
                 }
             }
         }
-        
-        Ok(results)
+
+        Ok(result)
     }
-    
+
     /// Get list of loaded plugins
     pub async fn list_plugins(&self) -> Vec<String> {
         let plugins = self.plugins.read().await;
@@ -201,7 +418,7 @@ impl PluginManager {
     pub async fn get_plugin_metadata(&self, name: &str) -> Result<PluginMetadata, PluginError> {
         let plugins = self.plugins.read().await;
         if let Some(plugin) = plugins.get(name) {
-            Ok(plugin.metadata())
+            Ok(plugin.read().metadata())
         } else {
             Err(PluginError::NotFound(name.to_string()))
         }
@@ -232,6 +449,7 @@ mod tests {
             max_plugins: 50,
             enable_hot_reload: true,
             security_level: SecurityLevel::High,
+            ..PluginConfig::default()
         };
         
         let manager = PluginManager::with_config(config.clone());
@@ -258,7 +476,411 @@ mod tests {
     #[tokio::test]
     async fn test_execute_hook_empty_plugins() {
         let manager = PluginManager::new();
-        let results = manager.execute_hook("test_hook", b"test_data").await.unwrap();
-        assert!(results.is_empty());
+        let result = manager.execute_hook("test_hook", b"test_data").await.unwrap();
+        assert!(result.outputs.is_empty());
+        assert!(!result.is_rejected());
+        assert_eq!(result.payload, b"test_data");
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    const UPPERCASE_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/uppercase.wat");
+
+    #[cfg(feature = "wasm-plugins")]
+    #[tokio::test]
+    async fn test_load_wasm_plugin_and_execute_hook() {
+        let manager = PluginManager::new();
+        let name = manager.load_plugin(UPPERCASE_FIXTURE).await.unwrap();
+        assert_eq!(name, "uppercase");
+
+        let metadata = manager.get_plugin_metadata("uppercase").await.unwrap();
+        assert_eq!(metadata.version, "1.0.0");
+        assert_eq!(metadata.api_version, "1.0");
+        assert!(metadata.dependencies.is_empty());
+
+        let result = manager.execute_hook("on_email_received", b"Subject: hello").await.unwrap();
+        assert_eq!(result.payload, b"SUBJECT: HELLO");
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[tokio::test]
+    async fn test_high_security_rejects_wasi_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wasi.wat");
+        std::fs::write(
+            &path,
+            r#"(module
+                (import "wasi_snapshot_preview1" "fd_write"
+                    (func (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1))"#,
+        )
+        .unwrap();
+
+        let manager = PluginManager::with_config(PluginConfig {
+            security_level: SecurityLevel::High,
+            ..PluginConfig::default()
+        });
+        let result = manager.load_plugin(&path).await;
+        assert!(matches!(result, Err(PluginError::SecurityViolation(_))));
+        assert!(manager.list_plugins().await.is_empty());
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[test]
+    fn test_wasm_plugin_runs_out_of_fuel() {
+        let config = PluginConfig {
+            wasm_fuel: 1_000_000,
+            ..PluginConfig::default()
+        };
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/spin.wat");
+        let mut plugin = wasm::WasmRuntime::new().load(Path::new(path), &config).unwrap();
+        plugin.initialize().unwrap();
+
+        // Every call gets a fresh budget, so the plugin fails the same way twice
+        for _ in 0..2 {
+            let result = plugin.execute_hook("on_email_received", b"message");
+            assert!(
+                matches!(result, Err(PluginError::ExecutionFailed(ref e)) if e.contains("fuel")),
+                "{:?}",
+                result
+            );
+        }
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[tokio::test]
+    async fn test_wasm_plugin_interrupted_at_hook_timeout() {
+        let manager = PluginManager::with_config(PluginConfig {
+            hook_timeout: Duration::from_millis(100),
+            wasm_fuel: u64::MAX,
+            ..PluginConfig::default()
+        });
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/spin.wat");
+        manager.load_plugin(path).await.unwrap();
+        let plugin = manager.plugins.read().await.get("spin").unwrap().clone();
+
+        // The epoch deadline stops the call itself, not only the caller's wait
+        let started = std::time::Instant::now();
+        let result = tokio::task::spawn_blocking(move || {
+            plugin.read().execute_hook("on_email_received", b"message")
+        })
+        .await
+        .unwrap();
+        assert!(
+            matches!(result, Err(PluginError::ExecutionFailed(ref e)) if e.contains("deadline")),
+            "{:?}",
+            result
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[tokio::test]
+    async fn test_wasm_memory_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("uppercase.wat");
+        let fixture = std::fs::read_to_string(UPPERCASE_FIXTURE).unwrap();
+        std::fs::write(
+            &path,
+            fixture.replace(r#"(memory (export "memory") 1)"#, r#"(memory (export "memory") 32)"#),
+        )
+        .unwrap();
+
+        let manager = PluginManager::with_config(PluginConfig {
+            wasm_memory_limit: 16 * 64 * 1024,
+            ..PluginConfig::default()
+        });
+        let result = manager.load_plugin(&path).await;
+        assert!(matches!(result, Err(PluginError::LoadingFailed(_))));
+
+        let manager = PluginManager::with_config(PluginConfig {
+            wasm_memory_limit: 32 * 64 * 1024,
+            ..PluginConfig::default()
+        });
+        manager.load_plugin(&path).await.unwrap();
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[tokio::test]
+    async fn test_max_plugins_enforced() {
+        let manager = PluginManager::with_config(PluginConfig {
+            max_plugins: 1,
+            ..PluginConfig::default()
+        });
+        manager.load_plugin(UPPERCASE_FIXTURE).await.unwrap();
+
+        let result = manager.load_plugin(UPPERCASE_FIXTURE).await;
+        assert!(matches!(result, Err(PluginError::LoadingFailed(_))));
+        assert_eq!(manager.list_plugins().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_load_unsupported_format() {
+        let manager = PluginManager::new();
+        let result = manager.load_plugin("plugins/readme.txt").await;
+        assert!(matches!(result, Err(PluginError::LoadingFailed(_))));
+    }
+
+    /// In-process plugin returning a fixed outcome and recording what it saw
+    struct StaticPlugin {
+        name: &'static str,
+        priority: i32,
+        outcome: HookOutcome,
+        dependencies: Vec<String>,
+        cleaned_up: Arc<std::sync::atomic::AtomicBool>,
+        delay: Duration,
+        panics: bool,
+        api_version: &'static str,
+        seen: Arc<parking_lot::Mutex<Vec<Vec<u8>>>>,
+    }
+
+    impl StaticPlugin {
+        fn new(name: &'static str, priority: i32, outcome: HookOutcome) -> Self {
+            Self {
+                name,
+                priority,
+                outcome,
+                dependencies: Vec::new(),
+                cleaned_up: Default::default(),
+                delay: Duration::ZERO,
+                panics: false,
+                api_version: "1.0",
+                seen: Default::default(),
+            }
+        }
+
+        fn depending_on(name: &'static str, dependencies: &[&str]) -> Box<dyn Plugin> {
+            let mut plugin = Self::new(name, 0, HookOutcome::Continue(Vec::new()));
+            plugin.dependencies = dependencies.iter().map(|d| d.to_string()).collect();
+            Box::new(plugin)
+        }
+    }
+
+    impl Plugin for StaticPlugin {
+        fn metadata(&self) -> PluginMetadata {
+            PluginMetadata {
+                name: self.name.to_string(),
+                version: "1.0.0".to_string(),
+                description: String::new(),
+                author: String::new(),
+                api_version: self.api_version.to_string(),
+                dependencies: self.dependencies.clone(),
+                priority: self.priority,
+            }
+        }
+
+        fn initialize(&mut self) -> Result<(), PluginError> {
+            Ok(())
+        }
+
+        fn execute_hook(&self, _hook: &str, data: &[u8]) -> Result<HookOutcome, PluginError> {
+            if self.panics {
+                panic!("{} exploded", self.name);
+            }
+            std::thread::sleep(self.delay);
+            self.seen.lock().push(data.to_vec());
+            Ok(self.outcome.clone())
+        }
+
+        fn cleanup(&mut self) -> Result<(), PluginError> {
+            self.cleaned_up
+                .store(true, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replace_is_seen_by_later_plugins() {
+        let manager = PluginManager::new();
+        let rewriter = StaticPlugin::new("rewriter", 10, HookOutcome::Replace(b"rewritten".to_vec()));
+        let observer = StaticPlugin::new("observer", 20, HookOutcome::Continue(b"ok".to_vec()));
+        let seen = observer.seen.clone();
+        manager.register_plugin(Box::new(observer)).await.unwrap();
+        manager.register_plugin(Box::new(rewriter)).await.unwrap();
+
+        let result = manager.execute_hook("on_email_received", b"original").await.unwrap();
+        assert_eq!(seen.lock().as_slice(), &[b"rewritten".to_vec()]);
+        assert_eq!(result.payload, b"rewritten");
+        assert_eq!(result.outputs, vec![("observer".to_string(), b"ok".to_vec())]);
+        assert!(!result.is_rejected());
+    }
+
+    #[tokio::test]
+    async fn test_reject_skips_later_plugins() {
+        let manager = PluginManager::new();
+        let filter = StaticPlugin::new("filter", 0, HookOutcome::Reject("spam".to_string()));
+        let archiver = StaticPlugin::new("archiver", 5, HookOutcome::Continue(Vec::new()));
+        let seen = archiver.seen.clone();
+        manager.register_plugin(Box::new(archiver)).await.unwrap();
+        manager.register_plugin(Box::new(filter)).await.unwrap();
+
+        let result = manager.execute_hook("on_email_received", b"message").await.unwrap();
+        assert_eq!(result.rejection, Some(("filter".to_string(), "spam".to_string())));
+        assert!(seen.lock().is_empty());
+        assert_eq!(result.payload, b"message");
+    }
+
+    #[tokio::test]
+    async fn test_dependency_chain_loads_in_order() {
+        let manager = PluginManager::new();
+        let names = manager
+            .register_plugins(vec![
+                StaticPlugin::depending_on("archive", &["dkim", "storage"]),
+                StaticPlugin::depending_on("dkim", &["storage"]),
+                StaticPlugin::depending_on("storage", &[]),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(names, vec!["storage", "dkim", "archive"]);
+
+        // Dependencies already loaded satisfy later registrations
+        manager
+            .register_plugin(StaticPlugin::depending_on("audit", &["archive"]))
+            .await
+            .unwrap();
+        assert_eq!(manager.list_plugins().await.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_missing_dependency() {
+        let manager = PluginManager::new();
+        let result = manager
+            .register_plugins(vec![
+                StaticPlugin::depending_on("dkim", &["storage"]),
+                StaticPlugin::depending_on("archive", &[]),
+            ])
+            .await;
+        assert!(matches!(result, Err(PluginError::DependencyNotSatisfied(_))));
+        assert!(manager.list_plugins().await.is_empty());
+
+        let result = manager
+            .register_plugin(StaticPlugin::depending_on("dkim", &["storage"]))
+            .await;
+        assert!(matches!(result, Err(PluginError::DependencyNotSatisfied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_dependency_cycle() {
+        let manager = PluginManager::new();
+        let result = manager
+            .register_plugins(vec![
+                StaticPlugin::depending_on("a", &["c"]),
+                StaticPlugin::depending_on("b", &["a"]),
+                StaticPlugin::depending_on("c", &["b"]),
+                StaticPlugin::depending_on("d", &[]),
+            ])
+            .await;
+        match result {
+            Err(PluginError::DependencyCycle(cycle)) => assert_eq!(cycle, "a, b, c"),
+            other => panic!("expected a dependency cycle, got {:?}", other),
+        }
+        assert!(manager.list_plugins().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_api_version_mismatch() {
+        let manager = PluginManager::new();
+        let mut plugin = StaticPlugin::new("legacy", 0, HookOutcome::Continue(Vec::new()));
+        plugin.api_version = "2.1";
+
+        let result = manager.register_plugin(Box::new(plugin)).await;
+        assert!(matches!(
+            result,
+            Err(PluginError::ApiVersionMismatch { ref actual, .. }) if actual == "2.1"
+        ));
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    fn hot_reload_manager(dir: &Path) -> (PluginManager, PathBuf) {
+        let path = dir.join("uppercase.wat");
+        std::fs::copy(UPPERCASE_FIXTURE, &path).unwrap();
+        let manager = PluginManager::with_config(PluginConfig {
+            plugin_dir: dir.to_string_lossy().into_owned(),
+            enable_hot_reload: true,
+            ..PluginConfig::default()
+        });
+        (manager, path)
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[tokio::test]
+    async fn test_hot_reload_swaps_changed_plugin() {
+        let dir = tempfile::tempdir().unwrap();
+        let (manager, path) = hot_reload_manager(dir.path());
+        manager.load_plugin(&path).await.unwrap();
+        let sibling = StaticPlugin::new("sibling", 0, HookOutcome::Continue(Vec::new()));
+        let sibling_cleaned_up = sibling.cleaned_up.clone();
+        manager.register_plugin(Box::new(sibling)).await.unwrap();
+
+        assert!(manager.check_for_changes().await.is_empty());
+
+        let updated = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace(r#"\"version\":\"1.0.0\""#, r#"\"version\":\"2.0.0\""#);
+        std::fs::write(&path, updated).unwrap();
+
+        assert_eq!(manager.check_for_changes().await, vec!["uppercase"]);
+        let metadata = manager.get_plugin_metadata("uppercase").await.unwrap();
+        assert_eq!(metadata.version, "2.0.0");
+
+        let result = manager.execute_hook("on_email_received", b"hello").await.unwrap();
+        assert_eq!(result.payload, b"HELLO");
+        assert!(manager.get_plugin_metadata("sibling").await.is_ok());
+        assert!(!sibling_cleaned_up.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[tokio::test]
+    async fn test_failed_hot_reload_keeps_previous_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let (manager, path) = hot_reload_manager(dir.path());
+        manager.load_plugin(&path).await.unwrap();
+
+        std::fs::write(&path, "(module (func $broken").unwrap();
+
+        assert!(manager.check_for_changes().await.is_empty());
+        let metadata = manager.get_plugin_metadata("uppercase").await.unwrap();
+        assert_eq!(metadata.version, "1.0.0");
+        let result = manager.execute_hook("on_email_received", b"still here").await.unwrap();
+        assert_eq!(result.payload, b"STILL HERE");
+    }
+
+    #[tokio::test]
+    async fn test_slow_plugin_times_out_and_siblings_run() {
+        let manager = PluginManager::with_config(PluginConfig {
+            hook_timeout: Duration::from_millis(50),
+            ..PluginConfig::default()
+        });
+        let mut slow = StaticPlugin::new("slow", 0, HookOutcome::Replace(b"late".to_vec()));
+        slow.delay = Duration::from_millis(500);
+        let sibling = StaticPlugin::new("sibling", 10, HookOutcome::Continue(b"ok".to_vec()));
+        let seen = sibling.seen.clone();
+        manager.register_plugin(Box::new(slow)).await.unwrap();
+        manager.register_plugin(Box::new(sibling)).await.unwrap();
+
+        let started = std::time::Instant::now();
+        let result = manager.execute_hook("on_email_received", b"message").await.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(500));
+        assert_eq!(result.payload, b"message");
+        assert_eq!(result.outputs, vec![("sibling".to_string(), b"ok".to_vec())]);
+        assert_eq!(seen.lock().as_slice(), &[b"message".to_vec()]);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_plugin_is_isolated() {
+        let manager = PluginManager::new();
+        let mut faulty = StaticPlugin::new("faulty", 0, HookOutcome::Reject("never".to_string()));
+        faulty.panics = true;
+        let sibling = StaticPlugin::new("sibling", 10, HookOutcome::Replace(b"rewritten".to_vec()));
+        manager.register_plugin(Box::new(faulty)).await.unwrap();
+        manager.register_plugin(Box::new(sibling)).await.unwrap();
+
+        let result = manager.execute_hook("on_email_received", b"message").await.unwrap();
+        assert!(!result.is_rejected());
+        assert_eq!(result.payload, b"rewritten");
+
+        // The faulty plugin stays loaded and keeps being isolated
+        let result = manager.execute_hook("on_email_received", b"again").await.unwrap();
+        assert_eq!(result.payload, b"rewritten");
+        assert_eq!(manager.list_plugins().await.len(), 2);
     }
 }
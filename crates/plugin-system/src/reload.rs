@@ -0,0 +1,172 @@
+//! Hot reloading of plugin artifacts
+//!
+//! Plugins loaded from disk are tracked together with a digest of their
+//! artifact. When `enable_hot_reload` is set, a background task polls the
+//! tracked artifacts inside `plugin_dir` and swaps in a fresh instance for
+//! every artifact whose content changed.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+use crate::{dependency, isolation, PluginError, PluginManager};
+
+/// Artifact a loaded plugin was instantiated from
+#[derive(Debug, Clone)]
+pub(crate) struct PluginSource {
+    pub name: String,
+    pub digest: blake3::Hash,
+}
+
+async fn digest(path: &Path) -> Result<blake3::Hash, PluginError> {
+    tokio::fs::read(path)
+        .await
+        .map(|bytes| blake3::hash(&bytes))
+        .map_err(|e| PluginError::LoadingFailed(format!("{}: {}", path.display(), e)))
+}
+
+impl PluginManager {
+    /// Remember the artifact `name` was loaded from so it can be hot reloaded
+    pub(crate) async fn track_source(&self, path: &Path, name: &str) {
+        match digest(path).await {
+            Ok(digest) => {
+                self.sources.write().await.insert(
+                    path.to_path_buf(),
+                    PluginSource {
+                        name: name.to_string(),
+                        digest,
+                    },
+                );
+            }
+            Err(e) => warn!("Plugin {} will not be hot reloaded: {}", name, e),
+        }
+    }
+
+    /// Spawn the background watcher when hot reloading is enabled
+    ///
+    /// The watcher stops once the manager is dropped.
+    pub fn start_hot_reload(self: &Arc<Self>) -> Option<JoinHandle<()>> {
+        if !self.config.enable_hot_reload {
+            return None;
+        }
+
+        info!(
+            "Watching {} for plugin changes every {:?}",
+            self.config.plugin_dir, self.config.hot_reload_interval
+        );
+        let manager: Weak<Self> = Arc::downgrade(self);
+        let period = self.config.hot_reload_interval;
+
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                manager.check_for_changes().await;
+            }
+        }))
+    }
+
+    /// Reload every tracked artifact in `plugin_dir` whose content changed
+    ///
+    /// Returns the names of the plugins that were reloaded.
+    pub async fn check_for_changes(&self) -> Vec<String> {
+        let plugin_dir = Path::new(&self.config.plugin_dir);
+        let tracked = self
+            .sources
+            .read()
+            .await
+            .iter()
+            .filter(|(path, _)| path.starts_with(plugin_dir))
+            .map(|(path, source)| (path.clone(), source.digest))
+            .collect::<Vec<(PathBuf, _)>>();
+
+        let mut reloaded = Vec::new();
+        for (path, previous) in tracked {
+            match digest(&path).await {
+                Ok(current) if current != previous => match self.reload_plugin(&path).await {
+                    Ok(name) => reloaded.push(name),
+                    Err(e) => {
+                        error!("Failed to reload plugin from {:?}: {}", path, e);
+                        // Don't retry the same broken artifact on every poll
+                        if let Some(source) = self.sources.write().await.get_mut(&path) {
+                            source.digest = current;
+                        }
+                    }
+                },
+                Ok(_) => {}
+                Err(e) => debug!("Skipping plugin artifact {:?}: {}", path, e),
+            }
+        }
+
+        reloaded
+    }
+
+    /// Replace the plugin loaded from `path` with a fresh instance
+    ///
+    /// The new instance is validated and initialized before the old one is
+    /// cleaned up, so a failed reload leaves the previous version running.
+    pub async fn reload_plugin<P: AsRef<Path>>(&self, path: P) -> Result<String, PluginError> {
+        let path = path.as_ref();
+        let name = self
+            .sources
+            .read()
+            .await
+            .get(path)
+            .map(|source| source.name.clone())
+            .ok_or_else(|| PluginError::NotFound(path.display().to_string()))?;
+        let digest = digest(path).await?;
+
+        let mut plugin = self.instantiate(path)?;
+        let metadata = plugin.metadata();
+        if metadata.name != name {
+            return Err(PluginError::LoadingFailed(format!(
+                "artifact now declares plugin {} instead of {}",
+                metadata.name, name
+            )));
+        }
+        dependency::check_api_version(&metadata)?;
+        {
+            let plugins = self.plugins.read().await;
+            if let Some(missing) = metadata
+                .dependencies
+                .iter()
+                .find(|dependency| *dependency != &name && !plugins.contains_key(*dependency))
+            {
+                return Err(PluginError::DependencyNotSatisfied(format!(
+                    "{} requires {}",
+                    name, missing
+                )));
+            }
+        }
+        isolation::catch_panic(&name, || plugin.initialize())?;
+
+        let previous = self
+            .plugins
+            .write()
+            .await
+            .insert(name.clone(), Arc::new(parking_lot::RwLock::new(plugin)));
+        if let Some(previous) = previous {
+            if let Err(e) = isolation::catch_panic(&name, || previous.write().cleanup()) {
+                warn!(
+                    "Cleanup of previous instance of plugin {} failed: {}",
+                    name, e
+                );
+            }
+        }
+        self.sources.write().await.insert(
+            path.to_path_buf(),
+            PluginSource {
+                name: name.clone(),
+                digest,
+            },
+        );
+
+        info!("Plugin {} reloaded at version {}", name, metadata.version);
+        Ok(name)
+    }
+}
@@ -0,0 +1,418 @@
+//! WebAssembly plugin loader
+//!
+//! WASM plugins talk to the host through a small, stable ABI. A plugin module
+//! must export:
+//!
+//! - `memory`: the linear memory used to exchange buffers with the host
+//! - `alloc(len: i32) -> i32`: reserve `len` bytes and return their offset
+//! - `metadata() -> i64`: packed pointer/length of a JSON [`PluginMetadata`]
+//! - `initialize() -> i32`: returns `0` on success, any other value is an error
+//! - `execute_hook(hook_ptr, hook_len, data_ptr, data_len) -> i64`: packed
+//!   pointer/length of the hook outcome, or a negative value on failure
+//!
+//! Packed values carry the pointer in the upper 32 bits and the length in the
+//! lower 32 bits. A hook outcome starts with a tag byte selecting the
+//! [`HookOutcome`]: `0` continue, `1` replace and `2` reject, followed by the
+//! output bytes, the new payload or the UTF-8 rejection reason respectively. The host provides the following imports in the `a3mailer`
+//! namespace:
+//!
+//! - `log(level: i32, ptr: i32, len: i32)`: levels are `0` error, `1` warn,
+//!   `2` info, `3` debug and anything else trace
+//! - `config_get(key_ptr, key_len, out_ptr, out_cap) -> i32`: copies the
+//!   plugin setting into `out_ptr` when it fits and returns its length, or
+//!   `-1` when the key is not configured
+//!
+//! Under [`SecurityLevel::High`] no other imports are linked, so modules that
+//! ask for WASI (filesystem, sockets, clocks) are refused at load time. At
+//! lower levels WASI is linked without inheriting the host's stdio; plugins
+//! report through `log` instead.
+//!
+//! Every instance is metered: each call into the plugin is granted
+//! [`PluginConfig::wasm_fuel`] units of fuel and traps once it runs out, and
+//! its linear memory cannot grow past [`PluginConfig::wasm_memory_limit`].
+//! Calls are also interrupted through wasmtime's epoch mechanism once
+//! [`PluginConfig::hook_timeout`] elapses, so a hook that outlives its
+//! deadline gives its blocking thread back instead of spinning on.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tracing::{debug, error, info, trace, warn};
+use wasi_common::sync::WasiCtxBuilder;
+use wasi_common::WasiCtx;
+use wasmtime::{
+    Caller, Config, Engine, Extern, Instance, Linker, Module, Store, StoreLimits,
+    StoreLimitsBuilder, Trap,
+};
+
+use crate::{HookOutcome, Plugin, PluginConfig, PluginError, PluginMetadata, SecurityLevel};
+
+/// Namespace of the host functions exposed to plugins
+pub const HOST_MODULE: &str = "a3mailer";
+
+/// Interval at which the engine epoch advances, the granularity of call deadlines
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Per-instance state reachable from host functions
+struct HostState {
+    plugin: String,
+    settings: HashMap<String, String>,
+    wasi: Option<WasiCtx>,
+    limits: StoreLimits,
+}
+
+/// Compiles and instantiates WASM plugins
+pub(crate) struct WasmRuntime {
+    engine: Engine,
+    stop_ticker: Arc<AtomicBool>,
+}
+
+/// A loaded WebAssembly plugin instance
+pub struct WasmPlugin {
+    metadata: PluginMetadata,
+    instance: Mutex<WasmInstance>,
+}
+
+struct WasmInstance {
+    store: Store<HostState>,
+    instance: Instance,
+    fuel: u64,
+    deadline: u64,
+}
+
+impl WasmRuntime {
+    pub(crate) fn new() -> Self {
+        let mut config = Config::new();
+        config.consume_fuel(true).epoch_interruption(true);
+        let engine = Engine::new(&config).expect("fuel and epochs are supported on every target");
+
+        let stop_ticker = Arc::new(AtomicBool::new(false));
+        {
+            let engine = engine.clone();
+            let stop_ticker = stop_ticker.clone();
+            std::thread::Builder::new()
+                .name("wasm-epoch".to_string())
+                .spawn(move || {
+                    while !stop_ticker.load(Ordering::Relaxed) {
+                        std::thread::sleep(EPOCH_TICK);
+                        engine.increment_epoch();
+                    }
+                })
+                .expect("failed to spawn the WASM epoch thread");
+        }
+
+        Self {
+            engine,
+            stop_ticker,
+        }
+    }
+
+    /// Compile the module at `path` and instantiate it under the configured security level
+    pub(crate) fn load(
+        &self,
+        path: &Path,
+        config: &PluginConfig,
+    ) -> Result<WasmPlugin, PluginError> {
+        let module = Module::from_file(&self.engine, path)
+            .map_err(|e| PluginError::LoadingFailed(format!("{}: {}", path.display(), e)))?;
+
+        let sandboxed = config.security_level == SecurityLevel::High;
+        for import in module.imports() {
+            if import.module() != HOST_MODULE && sandboxed {
+                return Err(PluginError::SecurityViolation(format!(
+                    "import {}::{} is not available under high security",
+                    import.module(),
+                    import.name()
+                )));
+            }
+        }
+
+        let mut linker = Linker::new(&self.engine);
+        add_host_functions(&mut linker)?;
+        let wasi = if sandboxed {
+            None
+        } else {
+            wasi_common::sync::add_to_linker(&mut linker, |state: &mut HostState| {
+                state.wasi.as_mut().expect("WASI context is configured")
+            })
+            .map_err(|e| PluginError::LoadingFailed(e.to_string()))?;
+            Some(WasiCtxBuilder::new().build())
+        };
+
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let mut store = Store::new(
+            &self.engine,
+            HostState {
+                plugin: stem,
+                settings: HashMap::new(),
+                wasi,
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(config.wasm_memory_limit)
+                    .instances(1)
+                    .build(),
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        let deadline = config
+            .hook_timeout
+            .as_millis()
+            .div_ceil(EPOCH_TICK.as_millis())
+            .max(1) as u64;
+        reset_budget(&mut store, config.wasm_fuel, deadline).map_err(PluginError::LoadingFailed)?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(|e| PluginError::LoadingFailed(call_error("instantiate", e)))?;
+
+        let mut instance = WasmInstance {
+            store,
+            instance,
+            fuel: config.wasm_fuel,
+            deadline,
+        };
+        let metadata = instance.read_metadata()?;
+
+        // Now that the plugin has identified itself, expose its settings
+        let state = instance.store.data_mut();
+        state.plugin = metadata.name.clone();
+        state.settings = config
+            .plugin_settings
+            .get(&metadata.name)
+            .cloned()
+            .unwrap_or_default();
+
+        debug!("Instantiated WASM plugin {} from {:?}", metadata.name, path);
+
+        Ok(WasmPlugin {
+            metadata,
+            instance: Mutex::new(instance),
+        })
+    }
+}
+
+impl Drop for WasmRuntime {
+    fn drop(&mut self) {
+        self.stop_ticker.store(true, Ordering::Relaxed);
+    }
+}
+
+fn add_host_functions(linker: &mut Linker<HostState>) -> Result<(), PluginError> {
+    linker
+        .func_wrap(
+            HOST_MODULE,
+            "log",
+            |mut caller: Caller<'_, HostState>, level: i32, ptr: i32, len: i32| {
+                let message = read_guest(&mut caller, ptr, len)
+                    .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                    .unwrap_or_default();
+                let plugin = &caller.data().plugin;
+                match level {
+                    0 => error!(plugin = %plugin, "{}", message),
+                    1 => warn!(plugin = %plugin, "{}", message),
+                    2 => info!(plugin = %plugin, "{}", message),
+                    3 => debug!(plugin = %plugin, "{}", message),
+                    _ => trace!(plugin = %plugin, "{}", message),
+                }
+            },
+        )
+        .map_err(|e| PluginError::LoadingFailed(e.to_string()))?;
+
+    linker
+        .func_wrap(
+            HOST_MODULE,
+            "config_get",
+            |mut caller: Caller<'_, HostState>,
+             key_ptr: i32,
+             key_len: i32,
+             out_ptr: i32,
+             out_cap: i32|
+             -> i32 {
+                let Some(key) = read_guest(&mut caller, key_ptr, key_len) else {
+                    return -1;
+                };
+                let key = String::from_utf8_lossy(&key);
+                let Some(value) = caller.data().settings.get(key.as_ref()).cloned() else {
+                    return -1;
+                };
+                if value.len() <= out_cap.max(0) as usize {
+                    if let Some(memory) = guest_memory(&mut caller) {
+                        if memory
+                            .write(&mut caller, out_ptr as u32 as usize, value.as_bytes())
+                            .is_err()
+                        {
+                            return -1;
+                        }
+                    }
+                }
+                value.len() as i32
+            },
+        )
+        .map_err(|e| PluginError::LoadingFailed(e.to_string()))?;
+
+    Ok(())
+}
+
+fn guest_memory(caller: &mut Caller<'_, HostState>) -> Option<wasmtime::Memory> {
+    caller.get_export("memory").and_then(Extern::into_memory)
+}
+
+fn read_guest(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> Option<Vec<u8>> {
+    let memory = guest_memory(caller)?;
+    let start = ptr as u32 as usize;
+    let end = start.checked_add(len as u32 as usize)?;
+    memory.data(&caller).get(start..end).map(<[u8]>::to_vec)
+}
+
+/// Grant a fresh fuel budget and deadline ahead of a call into the plugin
+fn reset_budget(store: &mut Store<HostState>, fuel: u64, deadline: u64) -> Result<(), String> {
+    store.set_epoch_deadline(deadline);
+    store.set_fuel(fuel).map_err(|e| e.to_string())
+}
+
+/// Describe a failed call, calling out traps caused by the resource limits
+fn call_error(context: &str, error: wasmtime::Error) -> String {
+    match error.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => format!("{}: plugin exhausted its fuel budget", context),
+        Some(Trap::Interrupt) => format!("{}: plugin interrupted after its deadline", context),
+        _ => format!("{}: {}", context, error),
+    }
+}
+
+fn unpack(packed: i64) -> (usize, usize) {
+    let packed = packed as u64;
+    ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize)
+}
+
+impl WasmInstance {
+    fn read_metadata(&mut self) -> Result<PluginMetadata, PluginError> {
+        reset_budget(&mut self.store, self.fuel, self.deadline)
+            .map_err(PluginError::LoadingFailed)?;
+        let packed = self
+            .instance
+            .get_typed_func::<(), i64>(&mut self.store, "metadata")
+            .and_then(|f| f.call(&mut self.store, ()))
+            .map_err(|e| PluginError::LoadingFailed(call_error("metadata", e)))?;
+        let bytes = self.read(packed)?;
+
+        serde_json::from_slice(&bytes)
+            .map_err(|e| PluginError::LoadingFailed(format!("invalid plugin metadata: {}", e)))
+    }
+
+    fn memory(&mut self) -> Result<wasmtime::Memory, PluginError> {
+        self.instance
+            .get_memory(&mut self.store, "memory")
+            .ok_or_else(|| PluginError::LoadingFailed("plugin does not export memory".to_string()))
+    }
+
+    fn read(&mut self, packed: i64) -> Result<Vec<u8>, PluginError> {
+        let (ptr, len) = unpack(packed);
+        let memory = self.memory()?;
+        memory
+            .data(&self.store)
+            .get(ptr..ptr + len)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| {
+                PluginError::ExecutionFailed("plugin returned an out-of-bounds buffer".to_string())
+            })
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(i32, i32), PluginError> {
+        let len = i32::try_from(bytes.len())
+            .map_err(|_| PluginError::ExecutionFailed("payload too large".to_string()))?;
+        let ptr = self
+            .instance
+            .get_typed_func::<i32, i32>(&mut self.store, "alloc")
+            .and_then(|f| f.call(&mut self.store, len))
+            .map_err(|e| PluginError::ExecutionFailed(call_error("alloc", e)))?;
+        let memory = self.memory()?;
+        memory
+            .write(&mut self.store, ptr as u32 as usize, bytes)
+            .map_err(|e| PluginError::ExecutionFailed(format!("alloc: {}", e)))?;
+
+        Ok((ptr, len))
+    }
+}
+
+impl Plugin for WasmPlugin {
+    fn metadata(&self) -> PluginMetadata {
+        self.metadata.clone()
+    }
+
+    fn initialize(&mut self) -> Result<(), PluginError> {
+        let instance = self.instance.get_mut();
+        reset_budget(&mut instance.store, instance.fuel, instance.deadline)
+            .map_err(PluginError::LoadingFailed)?;
+        let code = instance
+            .instance
+            .get_typed_func::<(), i32>(&mut instance.store, "initialize")
+            .and_then(|f| f.call(&mut instance.store, ()))
+            .map_err(|e| PluginError::LoadingFailed(call_error("initialize", e)))?;
+
+        if code == 0 {
+            Ok(())
+        } else {
+            Err(PluginError::LoadingFailed(format!(
+                "initialize returned error code {}",
+                code
+            )))
+        }
+    }
+
+    fn execute_hook(&self, hook: &str, data: &[u8]) -> Result<HookOutcome, PluginError> {
+        let mut guard = self.instance.lock();
+        let instance = &mut *guard;
+        reset_budget(&mut instance.store, instance.fuel, instance.deadline)
+            .map_err(PluginError::ExecutionFailed)?;
+        let (hook_ptr, hook_len) = instance.write(hook.as_bytes())?;
+        let (data_ptr, data_len) = instance.write(data)?;
+
+        let packed = instance
+            .instance
+            .get_typed_func::<(i32, i32, i32, i32), i64>(&mut instance.store, "execute_hook")
+            .map_err(|e| PluginError::ExecutionFailed(e.to_string()))?;
+        let packed = packed
+            .call(
+                &mut instance.store,
+                (hook_ptr, hook_len, data_ptr, data_len),
+            )
+            .map_err(|e| PluginError::ExecutionFailed(call_error(hook, e)))?;
+
+        if packed < 0 {
+            return Err(PluginError::ExecutionFailed(format!(
+                "hook '{}' returned error code {}",
+                hook, packed
+            )));
+        }
+
+        let mut output = instance.read(packed)?;
+        if output.is_empty() {
+            return Err(PluginError::ExecutionFailed(format!(
+                "hook '{}' returned an empty outcome",
+                hook
+            )));
+        }
+        let body = output.split_off(1);
+        match output[0] {
+            0 => Ok(HookOutcome::Continue(body)),
+            1 => Ok(HookOutcome::Replace(body)),
+            2 => Ok(HookOutcome::Reject(
+                String::from_utf8_lossy(&body).into_owned(),
+            )),
+            tag => Err(PluginError::ExecutionFailed(format!(
+                "hook '{}' returned unknown outcome tag {}",
+                hook, tag
+            ))),
+        }
+    }
+
+    fn cleanup(&mut self) -> Result<(), PluginError> {
+        Ok(())
+    }
+}
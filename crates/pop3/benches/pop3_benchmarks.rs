@@ -125,7 +125,7 @@ fn bench_response_serialization(c: &mut Criterion) {
         let message_data = vec![b'A'; size];
         let response = Response::Message::<u32> {
             bytes: message_data,
-            lines: 0,
+            lines: None,
         };
         
         group.bench_with_input(
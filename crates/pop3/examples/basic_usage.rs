@@ -153,6 +153,10 @@ fn demonstrate_response_generation() -> Result<(), Box<dyn std::error::Error>> {
     let capa_response = Response::Capability {
         mechanisms: vec![Mechanism::Plain, Mechanism::CramMd5, Mechanism::OAuthBearer],
         stls: true,
+        top: true,
+        uidl: true,
+        pipelining: true,
+        utf8: true,
     };
     let serialized = String::from_utf8(capa_response.serialize())?;
     println!("  CAPA Response:\n{}", indent_lines(&serialized));
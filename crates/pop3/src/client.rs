@@ -4,6 +4,8 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+use std::time::Instant;
+
 use common::{
     KV_RATE_LIMIT_IMAP,
     listener::{SessionResult, SessionStream},
@@ -82,11 +84,16 @@ impl<T: SessionStream> Session<T> {
         for request in requests {
             let result = match request {
                 Ok(command) => {
-                    // Check command rate limiting
+                    let name = command.name();
+                    let op_start = Instant::now();
+
+                    // Check command rate limiting, rejected commands are
+                    // only counted as rate limited
                     if let Err(err) = self.security.check_command_rate(self.session_id) {
+                        self.stats.record_rate_limited();
                         Err(trc::Error::from(err))
                     } else {
-                        match self.validate_request(command).await {
+                        let result = match self.validate_request(command).await {
                             Ok(command) => match command {
                         Command::User { name } => {
                             if let State::NotAuthenticated { username, apop_timestamp, .. } = &mut self.state {
@@ -163,7 +170,10 @@ impl<T: SessionStream> Session<T> {
                         }
                             },
                             Err(err) => Err(err),
-                        }
+                        };
+
+                        self.stats.record_command(name, op_start.elapsed(), result.is_ok());
+                        result
                     }
                 },
                 Err(err) => Err(err),
@@ -189,8 +199,18 @@ impl<T: SessionStream> Session<T> {
     ) -> trc::Result<Command<String, Mechanism>> {
         match &command {
             Command::Capa | Command::Quit | Command::Noop => Ok(command),
+            Command::Auth { .. }
+            | Command::User { .. }
+            | Command::Pass { .. }
+            | Command::Apop { .. }
+                if !self.security.is_auth_allowed(self.stream.is_tls()) =>
+            {
+                Err(trc::Pop3Event::Error
+                    .into_err()
+                    .details("Authentication requires TLS, use STLS first."))
+            }
             Command::Auth {
-                mechanism: Mechanism::Plain,
+                mechanism: Mechanism::Plain | Mechanism::Login,
                 ..
             }
             | Command::User { .. }
@@ -199,8 +219,16 @@ impl<T: SessionStream> Session<T> {
                 if let State::NotAuthenticated { username, .. } = &self.state {
                     match &command {
                         Command::Apop { .. } => {
-                            // APOP is always allowed regardless of TLS status
-                            Ok(command)
+                            // APOP never sends the password, so it is allowed
+                            // without plain-text auth once the TLS policy above
+                            // is satisfied
+                            if self.config.server.enable_apop {
+                                Ok(command)
+                            } else {
+                                Err(trc::Pop3Event::Error
+                                    .into_err()
+                                    .details("APOP is disabled."))
+                            }
                         }
                         _ => {
                             if self.stream.is_tls() || self.server.core.imap.allow_plain_auth {
@@ -234,15 +262,32 @@ impl<T: SessionStream> Session<T> {
                 }
             }
             Command::Stls => {
-                if !self.stream.is_tls() {
-                    Ok(command)
-                } else {
+                if self.stream.is_tls() {
                     Err(trc::Pop3Event::Error
                         .into_err()
                         .details("Already in TLS mode."))
+                } else if !matches!(self.state, State::NotAuthenticated { .. }) {
+                    Err(trc::Pop3Event::Error
+                        .into_err()
+                        .details("Already authenticated."))
+                } else if !self.instance.acceptor.is_tls() || !self.config.server.enable_stls {
+                    Err(trc::Pop3Event::Error
+                        .into_err()
+                        .details("TLS is not available."))
+                } else {
+                    Ok(command)
                 }
             }
 
+            Command::Top { .. } if !self.config.protocol.enable_top => {
+                Err(trc::Pop3Event::Error.into_err().details("TOP is disabled."))
+            }
+            Command::Uidl { .. } if !self.config.protocol.enable_uidl => Err(trc::Pop3Event::Error
+                .into_err()
+                .details("UIDL is disabled.")),
+            Command::Utf8 if !self.config.server.enable_utf8 => {
+                Err(trc::Pop3Event::Error.into_err().details("UTF8 is disabled."))
+            }
             Command::List { .. }
             | Command::Retr { .. }
             | Command::Dele { .. }
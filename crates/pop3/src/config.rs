@@ -389,6 +389,72 @@ impl Pop3Config {
         Ok(())
     }
 
+    /// Load the `pop3.*` settings from the server configuration
+    ///
+    /// Settings that are not present keep their default values.
+    pub fn parse(config: &mut utils::config::Config) -> Self {
+        let mut pop3 = Self::default();
+
+        let server = &mut pop3.server;
+        server.session_timeout = config
+            .property("pop3.timeout.authenticated")
+            .unwrap_or(server.session_timeout);
+        server.unauth_timeout = config
+            .property("pop3.timeout.anonymous")
+            .unwrap_or(server.unauth_timeout);
+        server.enable_apop = config
+            .property("pop3.auth.apop")
+            .unwrap_or(server.enable_apop);
+        server.enable_utf8 = config.property("pop3.utf8").unwrap_or(server.enable_utf8);
+        server.enable_stls = config.property("pop3.stls").unwrap_or(server.enable_stls);
+
+        let protocol = &mut pop3.protocol;
+        protocol.enable_pipelining = config
+            .property("pop3.pipelining")
+            .unwrap_or(protocol.enable_pipelining);
+        protocol.enable_top = config
+            .property("pop3.command.top")
+            .unwrap_or(protocol.enable_top);
+        protocol.enable_uidl = config
+            .property("pop3.command.uidl")
+            .unwrap_or(protocol.enable_uidl);
+
+        let security = &mut pop3.security;
+        security.require_tls_for_auth = config
+            .property("pop3.auth.require-tls")
+            .unwrap_or(security.require_tls_for_auth);
+        security.max_auth_attempts = config
+            .property("pop3.auth.max-attempts")
+            .unwrap_or(security.max_auth_attempts);
+        security.auth_window = config
+            .property("pop3.auth.window")
+            .unwrap_or(security.auth_window);
+        security.auto_block_duration = config
+            .property("pop3.auth.block-duration")
+            .unwrap_or(security.auto_block_duration);
+        security.max_connections_per_ip = config
+            .property("pop3.limits.connections-per-ip")
+            .unwrap_or(security.max_connections_per_ip);
+        security.max_connections = config
+            .property("pop3.limits.connections")
+            .unwrap_or(security.max_connections);
+        security.max_commands_per_minute = config
+            .property("pop3.limits.commands-per-minute")
+            .unwrap_or(security.max_commands_per_minute);
+        security.min_command_delay = config
+            .property::<Option<Duration>>("pop3.limits.command-delay")
+            .map_or(security.min_command_delay, Option::unwrap_or_default);
+        security.max_session_duration = config
+            .property("pop3.limits.session-duration")
+            .unwrap_or(security.max_session_duration);
+
+        if let Err(err) = pop3.validate() {
+            config.new_build_error("pop3", err.to_string());
+        }
+
+        pop3
+    }
+
     /// Creates a configuration for the specified environment
     pub fn for_environment(environment: Environment) -> Self {
         let mut config = match environment {
@@ -568,6 +634,7 @@ impl Pop3Config {
                 max_auth_attempts: 3,
                 auth_window: Duration::from_secs(900), // 15 minutes
                 max_connections_per_ip: 5,
+                max_connections: 200,
                 max_commands_per_minute: 30,
                 min_command_delay: Duration::from_millis(50),
                 enable_security_logging: true,
@@ -634,6 +701,7 @@ impl Pop3Config {
                 max_auth_attempts: 10,
                 auth_window: Duration::from_secs(300), // 5 minutes
                 max_connections_per_ip: 50,
+                max_connections: 1000,
                 max_commands_per_minute: 120,
                 min_command_delay: Duration::from_millis(10),
                 enable_security_logging: true,
@@ -1069,6 +1137,67 @@ mod config_tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_parse_server_config() {
+        let mut config = utils::config::Config::new(
+            r#"
+[pop3]
+stls = false
+utf8 = false
+command.top = false
+
+[pop3.auth]
+apop = false
+require-tls = true
+max-attempts = 5
+window = "10m"
+
+[pop3.timeout]
+authenticated = "10m"
+anonymous = "1m"
+
+[pop3.limits]
+connections-per-ip = 4
+connections = 50
+command-delay = "20ms"
+session-duration = "2h"
+"#,
+        )
+        .unwrap();
+        let pop3 = Pop3Config::parse(&mut config);
+
+        assert!(!pop3.server.enable_stls);
+        assert!(!pop3.server.enable_utf8);
+        assert!(!pop3.server.enable_apop);
+        assert_eq!(pop3.server.session_timeout, Duration::from_secs(600));
+        assert_eq!(pop3.server.unauth_timeout, Duration::from_secs(60));
+        assert!(!pop3.protocol.enable_top);
+        assert!(pop3.protocol.enable_uidl);
+        assert!(pop3.security.require_tls_for_auth);
+        assert_eq!(pop3.security.max_auth_attempts, 5);
+        assert_eq!(pop3.security.auth_window, Duration::from_secs(600));
+        assert_eq!(pop3.security.max_connections_per_ip, 4);
+        assert_eq!(pop3.security.max_connections, 50);
+        assert_eq!(pop3.security.min_command_delay, Duration::from_millis(20));
+        assert_eq!(pop3.security.max_session_duration, Duration::from_secs(7200));
+        assert!(config.errors.is_empty());
+
+        // Missing settings keep their defaults
+        let default = Pop3Config::default();
+        let pop3 = Pop3Config::parse(&mut utils::config::Config::new("").unwrap());
+        assert_eq!(pop3.server.enable_stls, default.server.enable_stls);
+        assert_eq!(
+            pop3.security.max_connections,
+            default.security.max_connections
+        );
+
+        // The delay between commands can be turned off
+        let pop3 = Pop3Config::parse(
+            &mut utils::config::Config::new("[pop3.limits]\ncommand-delay = false").unwrap(),
+        );
+        assert_eq!(pop3.security.min_command_delay, Duration::ZERO);
+    }
+
     #[test]
     fn test_apply_env_overrides() {
         use std::env;
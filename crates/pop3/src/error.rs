@@ -86,6 +86,8 @@ pub enum Pop3Error {
     InternalError(String),
     /// Rate limit exceeded
     RateLimitExceeded,
+    /// Concurrent connection limit reached
+    TooManyConnections,
     /// Connection timeout
     Timeout,
     /// Invalid argument
@@ -104,6 +106,7 @@ impl fmt::Display for Pop3Error {
             Pop3Error::MailboxLocked => write!(f, "Mailbox is locked by another session"),
             Pop3Error::InternalError(msg) => write!(f, "Internal server error: {}", msg),
             Pop3Error::RateLimitExceeded => write!(f, "Rate limit exceeded"),
+            Pop3Error::TooManyConnections => write!(f, "Too many connections"),
             Pop3Error::Timeout => write!(f, "Connection timeout"),
             Pop3Error::InvalidArgument(arg) => write!(f, "Invalid argument: {}", arg),
             Pop3Error::ProtocolViolation(msg) => write!(f, "Protocol violation: {}", msg),
@@ -137,6 +140,9 @@ impl From<Pop3Error> for trc::Error {
             Pop3Error::RateLimitExceeded => {
                 trc::LimitEvent::TooManyRequests.into_err()
             }
+            Pop3Error::TooManyConnections => {
+                trc::LimitEvent::ConcurrentConnection.into_err()
+            }
             Pop3Error::Timeout => {
                 trc::NetworkEvent::Timeout.into_err()
             }
@@ -4,16 +4,20 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use std::{net::IpAddr, sync::Arc};
+use std::{net::IpAddr, sync::Arc, time::Instant};
 
 use common::{
     Inner, Server,
     auth::AccessToken,
     listener::{ServerInstance, SessionStream, limiter::InFlight},
+    monitoring::MonitoringManager,
 };
+use config::Pop3Config;
 use mailbox::Mailbox;
+use monitoring::CommandStats;
 use protocol::request::Parser;
 use security::{SecurityManager, SecurityConfig};
+use utils::config::Config;
 
 pub mod client;
 pub mod config;
@@ -30,22 +34,51 @@ static SERVER_GREETING: &str = "+OK Stalwart POP3 at your service.\r\n";
 #[derive(Clone)]
 pub struct Pop3SessionManager {
     pub inner: Arc<Inner>,
+    pub config: Arc<Pop3Config>,
     pub security: Arc<SecurityManager>,
+    pub stats: Arc<CommandStats>,
+    pub monitoring: Arc<MonitoringManager>,
 }
 
 impl Pop3SessionManager {
-    pub fn new(inner: Arc<Inner>) -> Self {
-        let security_config = SecurityConfig::default(); // TODO: Load from config
-        Self {
+    /// Create a session manager from the `pop3.*` settings
+    ///
+    /// Command counters are published to `monitoring`, which is shared with
+    /// the rest of the server.
+    pub fn new(
+        inner: Arc<Inner>,
+        config: &mut Config,
+        monitoring: Arc<MonitoringManager>,
+    ) -> Self {
+        Self::with_config(inner, Pop3Config::parse(config), monitoring)
+    }
+
+    pub fn with_security_config(
+        inner: Arc<Inner>,
+        security_config: SecurityConfig,
+        monitoring: Arc<MonitoringManager>,
+    ) -> Self {
+        Self::with_config(
             inner,
-            security: Arc::new(SecurityManager::new(security_config)),
-        }
+            Pop3Config {
+                security: security_config,
+                ..Default::default()
+            },
+            monitoring,
+        )
     }
 
-    pub fn with_security_config(inner: Arc<Inner>, security_config: SecurityConfig) -> Self {
+    pub fn with_config(
+        inner: Arc<Inner>,
+        config: Pop3Config,
+        monitoring: Arc<MonitoringManager>,
+    ) -> Self {
         Self {
             inner,
-            security: Arc::new(SecurityManager::new(security_config)),
+            security: Arc::new(SecurityManager::new(config.security.clone())),
+            config: Arc::new(config),
+            stats: Arc::new(CommandStats::new()),
+            monitoring,
         }
     }
 }
@@ -59,7 +92,10 @@ pub struct Session<T: SessionStream> {
     pub in_flight: InFlight,
     pub remote_addr: IpAddr,
     pub session_id: u64,
+    pub started: Instant,
+    pub config: Arc<Pop3Config>,
     pub security: Arc<SecurityManager>,
+    pub stats: Arc<CommandStats>,
 }
 
 pub enum State {
@@ -96,4 +132,18 @@ impl State {
             _ => unreachable!(),
         }
     }
+
+    /// Count a failed login attempt
+    ///
+    /// Returns `false` once `max_failures` attempts have already failed, in
+    /// which case the connection should be dropped.
+    pub fn record_auth_failure(&mut self, max_failures: u32) -> bool {
+        match self {
+            State::NotAuthenticated { auth_failures, .. } if *auth_failures < max_failures => {
+                *auth_failures += 1;
+                true
+            }
+            _ => false,
+        }
+    }
 }
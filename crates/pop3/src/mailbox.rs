@@ -614,6 +614,26 @@ impl Mailbox {
         self.messages.get(index)
     }
 
+    /// Gets the unique identifier reported for a message by UIDL
+    ///
+    /// The identifier combines the UID validity with the message UID, both of
+    /// which persist across sessions, so a message keeps its identifier even
+    /// when its message number changes. Clients remember these identifiers to
+    /// tell which messages they already downloaded, so the format must not
+    /// change.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pop3::mailbox::{Mailbox, Message};
+    ///
+    /// let mailbox = Mailbox::new(123, 456);
+    /// assert_eq!(mailbox.unique_id(&Message::new(1, 7, 1024)), "4567");
+    /// ```
+    pub fn unique_id(&self, message: &Message) -> String {
+        format!("{}{}", self.uid_validity, message.uid)
+    }
+
     /// Gets a mutable reference to a message by its sequence number
     ///
     /// # Arguments
@@ -68,6 +68,8 @@ use tokio::{
 };
 use tracing::{debug, error, info, trace, warn};
 
+use common::monitoring::MonitoringManager;
+
 use crate::{
     security::SecurityStatsSnapshot,
     mailbox::MailboxStats,
@@ -408,6 +410,12 @@ pub struct CommandMetrics {
 
     /// Rate limited commands
     pub rate_limited_commands: u64,
+
+    /// Average execution time by type
+    pub latency_by_type: HashMap<String, Duration>,
+
+    /// Message bytes served by RETR
+    pub retr_bytes: u64,
 }
 
 /// Authentication metrics
@@ -602,6 +610,8 @@ impl Default for CommandMetrics {
             avg_execution_time: Duration::default(),
             errors_by_type: HashMap::new(),
             rate_limited_commands: 0,
+            latency_by_type: HashMap::new(),
+            retr_bytes: 0,
         }
     }
 }
@@ -863,6 +873,173 @@ impl Alert {
     }
 }
 
+/// Live command and authentication counters shared by all POP3 sessions
+///
+/// Sessions record every command as it completes. The counters can be
+/// read back as [`CommandMetrics`] and [`AuthenticationMetrics`] or
+/// published to the common [`MonitoringManager`].
+#[derive(Debug, Default)]
+pub struct CommandStats {
+    commands: RwLock<HashMap<&'static str, CommandCounter>>,
+    rate_limited: AtomicU64,
+    retr_bytes: AtomicU64,
+    auth_successes: AtomicU64,
+    auth_failures: AtomicU64,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct CommandCounter {
+    count: u64,
+    errors: u64,
+    total_time: Duration,
+}
+
+impl CommandCounter {
+    fn avg_time(&self) -> Duration {
+        if self.count > 0 {
+            Duration::from_nanos((self.total_time.as_nanos() / self.count as u128) as u64)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+impl CommandStats {
+    /// Creates empty counters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a completed command and how long it took to process
+    pub fn record_command(&self, command: &'static str, elapsed: Duration, success: bool) {
+        let mut commands = self.commands.write().unwrap();
+        let counter = commands.entry(command).or_default();
+        counter.count += 1;
+        counter.total_time += elapsed;
+        if !success {
+            counter.errors += 1;
+        }
+    }
+
+    /// Records a command rejected by the rate limiter
+    pub fn record_rate_limited(&self) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records the size of a message sent in full by RETR
+    pub fn record_retr_bytes(&self, bytes: u64) {
+        self.retr_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Records the outcome of an authentication attempt
+    pub fn record_auth(&self, success: bool) {
+        if success {
+            self.auth_successes.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.auth_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Number of times `command` was processed
+    pub fn command_count(&self, command: &str) -> u64 {
+        self.commands
+            .read()
+            .unwrap()
+            .get(command)
+            .map_or(0, |counter| counter.count)
+    }
+
+    /// Message bytes served by RETR
+    pub fn retr_bytes(&self) -> u64 {
+        self.retr_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Snapshot of the command counters
+    pub fn command_metrics(&self) -> CommandMetrics {
+        let commands = self.commands.read().unwrap();
+        let total = commands
+            .values()
+            .fold(CommandCounter::default(), |mut total, counter| {
+                total.count += counter.count;
+                total.errors += counter.errors;
+                total.total_time += counter.total_time;
+                total
+            });
+
+        CommandMetrics {
+            total_commands: total.count,
+            commands_per_second: 0.0,
+            commands_by_type: commands
+                .iter()
+                .map(|(command, counter)| (command.to_string(), counter.count))
+                .collect(),
+            success_rate: if total.count > 0 {
+                (total.count - total.errors) as f64 / total.count as f64 * 100.0
+            } else {
+                100.0
+            },
+            avg_execution_time: total.avg_time(),
+            errors_by_type: commands
+                .iter()
+                .filter(|(_, counter)| counter.errors > 0)
+                .map(|(command, counter)| (command.to_string(), counter.errors))
+                .collect(),
+            rate_limited_commands: self.rate_limited.load(Ordering::Relaxed),
+            latency_by_type: commands
+                .iter()
+                .map(|(command, counter)| (command.to_string(), counter.avg_time()))
+                .collect(),
+            retr_bytes: self.retr_bytes(),
+        }
+    }
+
+    /// Snapshot of the authentication outcomes
+    pub fn authentication_metrics(&self) -> AuthenticationMetrics {
+        let successful_attempts = self.auth_successes.load(Ordering::Relaxed);
+        let failed_attempts = self.auth_failures.load(Ordering::Relaxed);
+        let total_attempts = successful_attempts + failed_attempts;
+
+        AuthenticationMetrics {
+            total_attempts,
+            successful_attempts,
+            failed_attempts,
+            success_rate: if total_attempts > 0 {
+                successful_attempts as f64 / total_attempts as f64 * 100.0
+            } else {
+                100.0
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Publishes the counters as custom metrics of the common monitoring manager
+    ///
+    /// Per-command values are named `pop3.command.<name>.{count,errors,avg_ms}`.
+    pub fn export(&self, manager: &MonitoringManager) {
+        for (command, counter) in self.commands.read().unwrap().iter() {
+            let command = command.to_ascii_lowercase();
+            for (name, value) in [
+                ("count", counter.count as f64),
+                ("errors", counter.errors as f64),
+                ("avg_ms", counter.avg_time().as_micros() as f64 / 1000.0),
+            ] {
+                manager.set_custom_metric(format!("pop3.command.{command}.{name}"), value);
+            }
+        }
+
+        let auth = self.authentication_metrics();
+        let rate_limited = self.rate_limited.load(Ordering::Relaxed);
+        for (name, value) in [
+            ("pop3.command.rate_limited", rate_limited),
+            ("pop3.retr.bytes", self.retr_bytes()),
+            ("pop3.auth.success", auth.successful_attempts),
+            ("pop3.auth.failure", auth.failed_attempts),
+        ] {
+            manager.set_custom_metric(name.to_string(), value as f64);
+        }
+    }
+}
+
 /// Central POP3 monitoring system
 ///
 /// Coordinates all monitoring activities including metrics collection,
@@ -1992,6 +2169,52 @@ mod tests {
         assert!(!monitor.is_running());
     }
 
+    #[test]
+    fn test_command_stats_record_session() {
+        let stats = CommandStats::new();
+
+        // USER, a failed PASS, a retry, two RETRs and a DELE
+        stats.record_command("USER", Duration::from_millis(1), true);
+        stats.record_command("PASS", Duration::from_millis(20), false);
+        stats.record_auth(false);
+        stats.record_command("USER", Duration::from_millis(1), true);
+        stats.record_command("PASS", Duration::from_millis(10), true);
+        stats.record_auth(true);
+        for size in [1200, 800] {
+            stats.record_command("RETR", Duration::from_millis(4), true);
+            stats.record_retr_bytes(size);
+        }
+        stats.record_command("DELE", Duration::from_millis(2), true);
+        stats.record_rate_limited();
+
+        assert_eq!(stats.command_count("USER"), 2);
+        assert_eq!(stats.command_count("RETR"), 2);
+        assert_eq!(stats.command_count("TOP"), 0);
+        assert_eq!(stats.retr_bytes(), 2000);
+
+        let metrics = stats.command_metrics();
+        assert_eq!(metrics.total_commands, 7);
+        assert_eq!(metrics.errors_by_type.get("PASS"), Some(&1));
+        assert_eq!(metrics.latency_by_type["PASS"], Duration::from_millis(15));
+        assert_eq!(metrics.rate_limited_commands, 1);
+
+        let auth = stats.authentication_metrics();
+        assert_eq!((auth.successful_attempts, auth.failed_attempts), (1, 1));
+
+        let manager = MonitoringManager::default();
+        stats.export(&manager);
+        assert_eq!(
+            manager.get_custom_metric("pop3.command.retr.count"),
+            Some(2.0)
+        );
+        assert_eq!(
+            manager.get_custom_metric("pop3.command.pass.avg_ms"),
+            Some(15.0)
+        );
+        assert_eq!(manager.get_custom_metric("pop3.retr.bytes"), Some(2000.0));
+        assert_eq!(manager.get_custom_metric("pop3.auth.failure"), Some(1.0));
+    }
+
     #[test]
     fn test_performance_profiler_creation() {
         let config = ProfilingConfig::default();
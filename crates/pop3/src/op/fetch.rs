@@ -65,14 +65,14 @@ impl<T: SessionStream> Session<T> {
                         Elapsed = op_start.elapsed()
                     );
 
-                    self.write_bytes(
-                        Response::Message {
-                            bytes,
-                            lines: lines.unwrap_or(0),
-                        }
-                        .serialize(),
-                    )
-                    .await
+                    let size = bytes.len() as u64;
+                    self.write_bytes(Response::Message { bytes, lines }.serialize())
+                        .await?;
+
+                    if lines.is_none() {
+                        self.stats.record_retr_bytes(size);
+                    }
+                    Ok(())
                 } else {
                     Err(Pop3Error::InternalError(
                         "Failed to fetch message blob. Perhaps another session deleted it?".to_string()
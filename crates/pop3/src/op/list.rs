@@ -93,7 +93,7 @@ impl<T: SessionStream> Session<T> {
                     Elapsed = op_start.elapsed()
                 );
 
-                self.write_ok(format!("{} {}{}", msg, mailbox.uid_validity, message.uid))
+                self.write_ok(format!("{} {}", msg, mailbox.unique_id(message)))
                     .await
             } else {
                 Err(Pop3Error::MessageNotFound(msg).into())
@@ -115,7 +115,7 @@ impl<T: SessionStream> Session<T> {
                         .filter(|(_, m)| !m.flags.deleted)
                         .map(|(i, m)| ListItem::Uidl {
                             number: i + 1,
-                            uid: format!("{}{}", mailbox.uid_validity, m.uid)
+                            uid: mailbox.unique_id(m),
                         })
                         .collect::<Vec<_>>(),
                 )
@@ -154,3 +154,32 @@ impl<T: SessionStream> Session<T> {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::mailbox::{Mailbox, Message};
+
+    fn session(uids: &[u32]) -> Mailbox {
+        let mut mailbox = Mailbox::new(1, 1700000000);
+        for &uid in uids {
+            mailbox.add_message(Message::new(uid, uid, 100));
+        }
+        mailbox
+    }
+
+    #[test]
+    fn test_uidl_stable_across_sessions() {
+        let first = session(&[10, 11, 12]);
+        // Message 10 was deleted before the second session
+        let second = session(&[11, 12]);
+
+        // Identifiers keep the format clients already stored
+        let uid = first.unique_id(first.get_message(2).unwrap());
+        assert_eq!(uid, "170000000011");
+        assert_eq!(uid, second.unique_id(second.get_message(1).unwrap()));
+        assert_ne!(
+            first.unique_id(first.get_message(1).unwrap()),
+            second.unique_id(second.get_message(1).unwrap())
+        );
+    }
+}
@@ -8,6 +8,7 @@ use common::listener::SessionStream;
 
 use crate::{
     Session,
+    config::Pop3Config,
     protocol::{Mechanism, response::{Response, SerializeResponse}},
 };
 
@@ -18,25 +19,23 @@ pub mod list;
 
 impl<T: SessionStream> Session<T> {
     pub async fn handle_capa(&mut self) -> trc::Result<()> {
-        let mechanisms = if self.stream.is_tls() || self.server.core.imap.allow_plain_auth {
-            vec![Mechanism::Plain, Mechanism::OAuthBearer, Mechanism::XOauth2]
-        } else {
-            vec![Mechanism::OAuthBearer, Mechanism::XOauth2]
-        };
+        let is_tls = self.stream.is_tls();
 
         trc::event!(
             Pop3(trc::Pop3Event::Capabilities),
             SpanId = self.session_id,
-            Tls = self.stream.is_tls(),
+            Tls = is_tls,
             Strict = !self.server.core.imap.allow_plain_auth,
             Elapsed = trc::Value::Duration(0)
         );
 
         self.write_bytes(
-            Response::Capability {
-                mechanisms,
-                stls: !self.stream.is_tls(),
-            }
+            capabilities(
+                &self.config,
+                is_tls,
+                self.instance.acceptor.is_tls(),
+                self.server.core.imap.allow_plain_auth,
+            )
             .serialize(),
         )
         .await
@@ -62,3 +61,87 @@ impl<T: SessionStream> Session<T> {
         self.write_ok("UTF8 enabled").await
     }
 }
+
+/// Capabilities to advertise in CAPA for the current connection state
+///
+/// `can_upgrade` is whether the listener has a certificate for STLS. When
+/// the security policy requires TLS for authentication, no mechanisms are
+/// listed until the connection is encrypted.
+pub fn capabilities(
+    config: &Pop3Config,
+    is_tls: bool,
+    can_upgrade: bool,
+    allow_plain_auth: bool,
+) -> Response {
+    let mechanisms = if !is_tls && config.security.require_tls_for_auth {
+        vec![]
+    } else if is_tls || allow_plain_auth {
+        vec![
+            Mechanism::Plain,
+            Mechanism::Login,
+            Mechanism::OAuthBearer,
+            Mechanism::XOauth2,
+        ]
+    } else {
+        vec![Mechanism::OAuthBearer, Mechanism::XOauth2]
+    };
+
+    Response::Capability {
+        mechanisms,
+        stls: !is_tls && can_upgrade && config.server.enable_stls,
+        top: config.protocol.enable_top,
+        uidl: config.protocol.enable_uidl,
+        pipelining: config.protocol.enable_pipelining,
+        utf8: config.server.enable_utf8,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capa(config: &Pop3Config, is_tls: bool, can_upgrade: bool) -> String {
+        String::from_utf8(capabilities(config, is_tls, can_upgrade, false).serialize()).unwrap()
+    }
+
+    #[test]
+    fn test_capabilities_change_after_stls() {
+        let mut config = Pop3Config::default();
+        config.security.require_tls_for_auth = true;
+
+        let plaintext = capa(&config, false, true);
+        assert!(plaintext.contains("\r\nSTLS\r\n"));
+        assert!(!plaintext.contains("SASL"));
+        assert!(!plaintext.contains("USER"));
+
+        let tls = capa(&config, true, true);
+        assert!(!tls.contains("STLS"));
+        assert!(tls.contains("\r\nUSER\r\n"));
+        assert!(tls.contains("\r\nSASL PLAIN LOGIN OAUTHBEARER XOAUTH2\r\n"));
+
+        // Without the policy OAuth is offered before the upgrade
+        config.security.require_tls_for_auth = false;
+        assert!(capa(&config, false, true).contains("\r\nSASL OAUTHBEARER XOAUTH2\r\n"));
+    }
+
+    #[test]
+    fn test_capabilities_follow_config() {
+        let mut config = Pop3Config::default();
+        let all = capa(&config, false, true);
+        for capability in ["STLS", "TOP", "UIDL", "PIPELINING", "UTF8"] {
+            assert!(all.contains(&format!("\r\n{capability}\r\n")));
+        }
+
+        config.server.enable_stls = false;
+        config.protocol.enable_top = false;
+        config.protocol.enable_uidl = false;
+        config.protocol.enable_pipelining = false;
+        let none = capa(&config, false, true);
+        for capability in ["STLS", "TOP", "UIDL", "PIPELINING"] {
+            assert!(!none.contains(&format!("\r\n{capability}\r\n")));
+        }
+
+        // STLS is never offered without a certificate to upgrade with
+        assert!(!capa(&Pop3Config::default(), false, false).contains("STLS"));
+    }
+}
@@ -57,9 +57,34 @@ pub enum Command<T, M> {
     },
 }
 
+impl<T, M> Command<T, M> {
+    /// Command keyword, used to label per-command metrics
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::User { .. } => "USER",
+            Command::Pass { .. } => "PASS",
+            Command::Apop { .. } => "APOP",
+            Command::Quit => "QUIT",
+            Command::Stat => "STAT",
+            Command::List { .. } => "LIST",
+            Command::Retr { .. } => "RETR",
+            Command::Dele { .. } | Command::DeleMany { .. } => "DELE",
+            Command::Noop => "NOOP",
+            Command::Rset => "RSET",
+            Command::Top { .. } => "TOP",
+            Command::Uidl { .. } => "UIDL",
+            Command::Capa => "CAPA",
+            Command::Stls => "STLS",
+            Command::Utf8 => "UTF8",
+            Command::Auth { .. } => "AUTH",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Mechanism {
     Plain,
+    Login,
     CramMd5,
     DigestMd5,
     ScramSha1,
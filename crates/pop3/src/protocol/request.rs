@@ -287,6 +287,8 @@ impl Mechanism {
     pub fn parse(value: &[u8]) -> Result<Self, Error> {
         if value.eq_ignore_ascii_case(b"PLAIN") {
             Ok(Self::Plain)
+        } else if value.eq_ignore_ascii_case(b"LOGIN") {
+            Ok(Self::Login)
         } else if value.eq_ignore_ascii_case(b"CRAM-MD5") {
             Ok(Self::CramMd5)
         } else if value.eq_ignore_ascii_case(b"DIGEST-MD5") {
@@ -500,6 +502,7 @@ mod tests {
         let mechanisms = [
             ("PLAIN", Mechanism::Plain),
             ("plain", Mechanism::Plain),
+            ("LOGIN", Mechanism::Login),
             ("CRAM-MD5", Mechanism::CramMd5),
             ("cram-md5", Mechanism::CramMd5),
             ("DIGEST-MD5", Mechanism::DigestMd5),
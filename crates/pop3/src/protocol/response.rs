@@ -40,6 +40,10 @@
 //! let response = Response::Capability {
 //!     mechanisms: vec![Mechanism::Plain],
 //!     stls: true,
+//!     top: true,
+//!     uidl: true,
+//!     pipelining: true,
+//!     utf8: true,
 //! };
 //! let serialized = String::from_utf8(response.serialize()).unwrap();
 //! assert!(serialized.contains("STLS"));
@@ -202,8 +206,8 @@ pub enum Response {
     Message {
         /// Raw message bytes
         bytes: Vec<u8>,
-        /// Number of lines (used for TOP command)
-        lines: u32,
+        /// Body lines to send after the headers (TOP), `None` for the whole message
+        lines: Option<u32>,
     },
 
     /// Server capability response
@@ -215,6 +219,14 @@ pub enum Response {
         mechanisms: Vec<Mechanism>,
         /// Whether STLS (StartTLS) is supported
         stls: bool,
+        /// Whether the TOP command is enabled
+        top: bool,
+        /// Whether the UIDL command is enabled
+        uidl: bool,
+        /// Whether commands may be pipelined
+        pipelining: bool,
+        /// Whether the UTF8 command is enabled
+        utf8: bool,
     },
 }
 
@@ -357,7 +369,7 @@ impl SerializeResponse for Response {
             Response::Message { bytes, lines } => {
                 trace!(
                     message_size = bytes.len(),
-                    line_limit = lines,
+                    line_limit = ?lines,
                     "Serializing MESSAGE response"
                 );
 
@@ -370,13 +382,16 @@ impl SerializeResponse for Response {
                 buf.extend_from_slice(bytes.len().to_string().as_bytes());
                 buf.extend_from_slice(b" octets\r\n");
 
-                let mut line_count = 0;
+                let content = match lines {
+                    Some(lines) => top_lines(&bytes, lines),
+                    None => &bytes[..],
+                };
                 let mut last_byte = 0;
                 let mut dot_stuffed_count = 0;
                 let mut crlf_added_count = 0;
 
                 // POP3 transparency procedure with detailed logging
-                for (pos, &byte) in bytes.iter().enumerate() {
+                for (pos, &byte) in content.iter().enumerate() {
                     // Ensure lines end with CRLF (RFC 1939 requirement)
                     if byte == b'\n' && last_byte != b'\r' {
                         buf.push(b'\r');
@@ -392,19 +407,6 @@ impl SerializeResponse for Response {
 
                     buf.push(byte);
                     last_byte = byte;
-
-                    // Handle line limit for TOP command
-                    if lines > 0 && byte == b'\n' {
-                        line_count += 1;
-                        if line_count == lines {
-                            debug!(
-                                lines_processed = line_count,
-                                line_limit = lines,
-                                "Reached line limit for TOP command"
-                            );
-                            break;
-                        }
-                    }
                 }
 
                 // Ensure message ends with CRLF
@@ -419,8 +421,8 @@ impl SerializeResponse for Response {
                 debug!(
                     message_size = bytes.len(),
                     serialized_size = buf.len(),
-                    lines_processed = if lines > 0 { line_count } else { 0 },
-                    line_limit = lines,
+                    bytes_sent = content.len(),
+                    line_limit = ?lines,
                     dot_stuffed_count = dot_stuffed_count,
                     crlf_added_count = crlf_added_count,
                     estimated_size = estimated_size,
@@ -429,7 +431,14 @@ impl SerializeResponse for Response {
 
                 buf
             }
-            Response::Capability { mechanisms, stls } => {
+            Response::Capability {
+                mechanisms,
+                stls,
+                top,
+                uidl,
+                pipelining,
+                utf8,
+            } => {
                 trace!(
                     mechanism_count = mechanisms.len(),
                     stls_enabled = stls,
@@ -463,24 +472,27 @@ impl SerializeResponse for Response {
                     debug!("Added STLS capability");
                 }
 
-                // Standard POP3 capabilities
-                const STANDARD_CAPABILITIES: &[&str] = &[
-                    "TOP",           // TOP command support
-                    "RESP-CODES",    // Response codes extension
-                    "PIPELINING",    // Command pipelining
-                    "EXPIRE NEVER",  // Messages never expire
-                    "UIDL",          // Unique ID listing
-                    "UTF8",          // UTF-8 support
-                    "IMPLEMENTATION A3Mailer Server", // Server identification
+                // Standard POP3 capabilities, some of which can be disabled
+                let capabilities = [
+                    ("TOP", top),                   // TOP command support
+                    ("RESP-CODES", true),           // Response codes extension
+                    ("PIPELINING", pipelining),     // Command pipelining
+                    ("EXPIRE NEVER", true),         // Messages never expire
+                    ("UIDL", uidl),                 // Unique ID listing
+                    ("UTF8", utf8),                 // UTF-8 support
+                    ("IMPLEMENTATION A3Mailer Server", true), // Server identification
                 ];
 
-                for &capability in STANDARD_CAPABILITIES {
+                for (capability, _) in capabilities.iter().filter(|(_, enabled)| *enabled) {
                     buf.extend_from_slice(capability.as_bytes());
                     buf.extend_from_slice(b"\r\n");
                 }
 
                 debug!(
-                    standard_capabilities = STANDARD_CAPABILITIES.len(),
+                    top_enabled = top,
+                    uidl_enabled = uidl,
+                    pipelining_enabled = pipelining,
+                    utf8_enabled = utf8,
                     "Added standard capabilities"
                 );
 
@@ -515,10 +527,35 @@ impl SerializeResponse for Response {
     }
 }
 
+/// Headers of `message` followed by at most `lines` lines of its body
+fn top_lines(message: &[u8], lines: u32) -> &[u8] {
+    let body_start = message
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map(|pos| pos + 4)
+        .or_else(|| {
+            message
+                .windows(2)
+                .position(|window| window == b"\n\n")
+                .map(|pos| pos + 2)
+        })
+        .unwrap_or(message.len());
+
+    let mut end = body_start;
+    for _ in 0..lines {
+        match message[end..].iter().position(|&byte| byte == b'\n') {
+            Some(pos) => end += pos + 1,
+            None => return message,
+        }
+    }
+    &message[..end]
+}
+
 impl Mechanism {
     pub fn as_str(&self) -> &'static str {
         match self {
             Mechanism::Plain => "PLAIN",
+            Mechanism::Login => "LOGIN",
             Mechanism::CramMd5 => "CRAM-MD5",
             Mechanism::DigestMd5 => "DIGEST-MD5",
             Mechanism::ScramSha1 => "SCRAM-SHA-1",
@@ -641,6 +678,10 @@ mod tests {
                 Response::Capability {
                     mechanisms: vec![Mechanism::Plain, Mechanism::CramMd5],
                     stls: true,
+                    top: true,
+                    uidl: true,
+                    pipelining: true,
+                    utf8: true,
                 },
                 concat!(
                     "+OK Capability list follows\r\n",
@@ -661,7 +702,7 @@ mod tests {
                     bytes: "Subject: test\r\n\r\n.\r\ntest.\r\n.test\r\na"
                         .as_bytes()
                         .to_vec(),
-                    lines: 0,
+                    lines: None,
                 },
                 "+OK 35 octets\r\nSubject: test\r\n\r\n..\r\ntest.\r\n..test\r\na\r\n.\r\n",
             ),
@@ -702,7 +743,7 @@ mod tests {
         // Test dot stuffing
         let response = Response::Message {
             bytes: b"Line 1\r\n.Line starting with dot\r\n..Double dot line\r\nLast line".to_vec(),
-            lines: 0,
+            lines: None,
         };
         let serialized = String::from_utf8(response.serialize()).unwrap();
         assert!(serialized.contains("..Line starting with dot"));
@@ -711,7 +752,7 @@ mod tests {
         // Test line ending normalization
         let response = Response::Message {
             bytes: b"Line 1\nLine 2\r\nLine 3\n".to_vec(),
-            lines: 0,
+            lines: None,
         };
         let serialized = String::from_utf8(response.serialize()).unwrap();
         assert!(serialized.contains("Line 1\r\n"));
@@ -724,6 +765,10 @@ mod tests {
         let response = Response::Capability {
             mechanisms: vec![Mechanism::Plain, Mechanism::CramMd5, Mechanism::OAuthBearer],
             stls: true,
+            top: true,
+            uidl: true,
+            pipelining: true,
+            utf8: true,
         };
         let serialized = String::from_utf8(response.serialize()).unwrap();
 
@@ -739,6 +784,10 @@ mod tests {
         let response = Response::Capability {
             mechanisms: vec![Mechanism::Plain],
             stls: false,
+            top: true,
+            uidl: true,
+            pipelining: true,
+            utf8: true,
         };
         let serialized = String::from_utf8(response.serialize()).unwrap();
         assert!(!serialized.contains("STLS"));
@@ -846,7 +895,7 @@ mod tests {
         assert_eq!(serialized, "+OK 0 messages\r\n.\r\n");
 
         // Empty message content
-        let response = Response::Message { bytes: vec![], lines: 0 };
+        let response = Response::Message { bytes: vec![], lines: None };
         let serialized = String::from_utf8(response.serialize()).unwrap();
         assert!(serialized.starts_with("+OK 0 octets\r\n"));
         assert!(serialized.ends_with(".\r\n"));
@@ -863,7 +912,7 @@ mod tests {
         // Message starting with dot
         let response = Response::Message {
             bytes: b".This starts with a dot".to_vec(),
-            lines: 0
+            lines: None
         };
         let serialized = String::from_utf8(response.serialize()).unwrap();
         assert!(serialized.contains("..This starts with a dot"));
@@ -871,7 +920,7 @@ mod tests {
         // Multiple consecutive dots
         let response = Response::Message {
             bytes: b"Line 1\r\n..Multiple dots\r\n...Three dots".to_vec(),
-            lines: 0
+            lines: None
         };
         let serialized = String::from_utf8(response.serialize()).unwrap();
         assert!(serialized.contains("...Multiple dots"));
@@ -880,7 +929,7 @@ mod tests {
         // Dot at end of message
         let response = Response::Message {
             bytes: b"Message ending with dot.".to_vec(),
-            lines: 0
+            lines: None
         };
         let serialized = String::from_utf8(response.serialize()).unwrap();
         assert!(serialized.contains("Message ending with dot."));
@@ -889,27 +938,41 @@ mod tests {
     /// Test line limit functionality for TOP command
     #[test]
     fn test_line_limit() {
-        let message = b"Line 1\r\nLine 2\r\nLine 3\r\nLine 4\r\nLine 5\r\n".to_vec();
+        let message =
+            b"Subject: test\r\nFrom: a@b.c\r\n\r\nLine 1\r\nLine 2\r\nLine 3\r\n".to_vec();
 
-        // Test with line limit
-        let response = Response::Message { bytes: message.clone(), lines: 3 };
+        // TOP 1 0 returns only the headers
+        let response = Response::Message {
+            bytes: message.clone(),
+            lines: Some(0),
+        };
         let serialized = String::from_utf8(response.serialize()).unwrap();
+        assert!(serialized.ends_with("Subject: test\r\nFrom: a@b.c\r\n\r\n.\r\n"));
+        assert!(!serialized.contains("Line 1"));
 
-        // Should contain first 3 lines
-        assert!(serialized.contains("Line 1"));
-        assert!(serialized.contains("Line 2"));
-        assert!(serialized.contains("Line 3"));
+        // TOP 1 2 adds the first two body lines
+        let response = Response::Message {
+            bytes: message.clone(),
+            lines: Some(2),
+        };
+        let serialized = String::from_utf8(response.serialize()).unwrap();
+        assert!(serialized.ends_with("\r\n\r\nLine 1\r\nLine 2\r\n.\r\n"));
 
-        // Should not contain lines 4 and 5
-        assert!(!serialized.contains("Line 4"));
-        assert!(!serialized.contains("Line 5"));
+        // Asking for more lines than the body has returns the whole message
+        let response = Response::Message {
+            bytes: message.clone(),
+            lines: Some(10),
+        };
+        let serialized = String::from_utf8(response.serialize()).unwrap();
+        assert!(serialized.contains("Line 3"));
 
         // Test without line limit
-        let response = Response::Message { bytes: message, lines: 0 };
+        let response = Response::Message {
+            bytes: message,
+            lines: None,
+        };
         let serialized = String::from_utf8(response.serialize()).unwrap();
-
-        // Should contain all lines
-        assert!(serialized.contains("Line 5"));
+        assert!(serialized.contains("Line 3"));
     }
 
     /// Test capability response completeness
@@ -922,6 +985,10 @@ mod tests {
                 Mechanism::OAuthBearer
             ],
             stls: true,
+            top: true,
+            uidl: true,
+            pipelining: true,
+            utf8: true,
         };
         let serialized = String::from_utf8(response.serialize()).unwrap();
 
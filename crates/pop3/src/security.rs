@@ -63,6 +63,8 @@ use std::{
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, trace, warn};
 
+use common::listener::limiter::{ConcurrencyLimiter, InFlight, LimiterResult};
+
 use crate::error::Pop3Error;
 
 /// Suspicious activity flag constants
@@ -163,6 +165,12 @@ pub struct SecurityConfig {
     /// from a single IP. Recommended: 5-20 for production.
     pub max_connections_per_ip: u32,
 
+    /// Maximum concurrent connections across all IP addresses
+    ///
+    /// Caps the total number of open POP3 sessions on this server.
+    #[serde(default = "SecurityConfig::default_max_connections")]
+    pub max_connections: u32,
+
     /// Maximum commands per minute per session
     ///
     /// Prevents command flooding attacks. Recommended: 30-120 for
@@ -241,6 +249,7 @@ impl Default for SecurityConfig {
             max_auth_attempts: 3,
             auth_window: Duration::from_secs(300), // 5 minutes
             max_connections_per_ip: 10,
+            max_connections: Self::default_max_connections(),
             max_commands_per_minute: 60,
             min_command_delay: Duration::from_millis(100),
             enable_security_logging: true,
@@ -256,6 +265,10 @@ impl Default for SecurityConfig {
 }
 
 impl SecurityConfig {
+    fn default_max_connections() -> u32 {
+        1000
+    }
+
     /// Creates a high-security configuration for sensitive environments
     ///
     /// This configuration prioritizes security over convenience and is
@@ -284,6 +297,7 @@ impl SecurityConfig {
             max_auth_attempts: 2,
             auth_window: Duration::from_secs(900), // 15 minutes
             max_connections_per_ip: 3,
+            max_connections: 500,
             max_commands_per_minute: 20,
             min_command_delay: Duration::from_millis(250),
             enable_security_logging: true,
@@ -322,6 +336,7 @@ impl SecurityConfig {
             max_auth_attempts: 100,
             auth_window: Duration::from_secs(60),
             max_connections_per_ip: 100,
+            max_connections: 10000,
             max_commands_per_minute: 1000,
             min_command_delay: Duration::from_millis(1),
             enable_security_logging: false,
@@ -366,6 +381,10 @@ impl SecurityConfig {
             return Err("max_connections_per_ip must be greater than 0".to_string());
         }
 
+        if self.max_connections == 0 {
+            return Err("max_connections must be greater than 0".to_string());
+        }
+
         if self.max_commands_per_minute == 0 {
             return Err("max_commands_per_minute must be greater than 0".to_string());
         }
@@ -454,11 +473,57 @@ struct CommandRate {
     last_command: Instant,
 }
 
-/// Connection tracking
-#[derive(Debug, Clone)]
-struct ConnectionInfo {
-    count: u32,
-    first_connection: Instant,
+/// Concurrent session limits shared by all accepted connections
+///
+/// Built on the listener's `ConcurrencyLimiter`: every open session holds an
+/// in-flight slot on the global limiter and on the limiter of its remote IP.
+#[derive(Debug)]
+struct ConnectionLimiter {
+    global: ConcurrencyLimiter,
+    per_ip: Mutex<HashMap<IpAddr, ConcurrencyLimiter>>,
+    max_per_ip: u64,
+}
+
+/// Connection slots held by an open session
+///
+/// Dropping it when the session ends frees the slots for new connections.
+pub struct ConnectionSlot {
+    _global: InFlight,
+    _per_ip: InFlight,
+}
+
+impl ConnectionLimiter {
+    fn new(max_per_ip: u32, max_total: u32) -> Self {
+        Self {
+            global: ConcurrencyLimiter::new(max_total as u64),
+            per_ip: Mutex::new(HashMap::new()),
+            max_per_ip: max_per_ip as u64,
+        }
+    }
+
+    fn acquire(&self, ip: IpAddr) -> Result<ConnectionSlot, Pop3Error> {
+        let mut per_ip = self.per_ip.lock().unwrap();
+        if !per_ip.contains_key(&ip) {
+            // Forget addresses without open sessions
+            per_ip.retain(|_, limiter| limiter.is_active());
+        }
+
+        let ip_slot = match per_ip
+            .entry(ip)
+            .or_insert_with(|| ConcurrencyLimiter::new(self.max_per_ip))
+            .is_allowed()
+        {
+            LimiterResult::Allowed(in_flight) => in_flight,
+            _ => return Err(Pop3Error::TooManyConnections),
+        };
+        match self.global.is_allowed() {
+            LimiterResult::Allowed(in_flight) => Ok(ConnectionSlot {
+                _global: in_flight,
+                _per_ip: ip_slot,
+            }),
+            _ => Err(Pop3Error::TooManyConnections),
+        }
+    }
 }
 
 /// Security statistics for monitoring and alerting
@@ -594,8 +659,8 @@ pub struct SecurityManager {
     /// Command rate limiting per session
     command_rates: Arc<Mutex<HashMap<u64, CommandRate>>>,
 
-    /// Connection tracking per IP
-    connections: Arc<Mutex<HashMap<IpAddr, ConnectionInfo>>>,
+    /// Concurrent connections, globally and per IP
+    connections: Arc<ConnectionLimiter>,
 
     /// Security statistics for monitoring
     stats: Arc<SecurityStats>,
@@ -635,7 +700,10 @@ impl SecurityManager {
             config,
             auth_attempts: Arc::new(Mutex::new(HashMap::new())),
             command_rates: Arc::new(Mutex::new(HashMap::new())),
-            connections: Arc::new(Mutex::new(HashMap::new())),
+            connections: Arc::new(ConnectionLimiter::new(
+                config.max_connections_per_ip,
+                config.max_connections,
+            )),
             stats: Arc::new(SecurityStats::new()),
             blocked_ips: Arc::new(Mutex::new(HashMap::new())),
             suspicious_ips: Arc::new(Mutex::new(HashMap::new())),
@@ -976,38 +1044,24 @@ impl SecurityManager {
         }
     }
 
-    /// Check if new connection is allowed
-    pub fn check_connection_allowed(&self, ip: IpAddr) -> Result<(), Pop3Error> {
-        let mut connections = self.connections.lock().unwrap();
-        let now = Instant::now();
-
-        let conn_info = connections.entry(ip).or_insert(ConnectionInfo {
-            count: 0,
-            first_connection: now,
-        });
-
-        // Reset counter every hour
-        if now.duration_since(conn_info.first_connection) > Duration::from_secs(3600) {
-            conn_info.count = 0;
-            conn_info.first_connection = now;
-        }
-
-        if conn_info.count >= self.config.max_connections_per_ip {
-            return Err(Pop3Error::RateLimitExceeded);
-        }
-
-        conn_info.count += 1;
-        Ok(())
+    /// Reserve a connection slot for a new session from `ip`
+    ///
+    /// Fails once either the per-IP or the global limit of concurrent
+    /// sessions is reached. The slot is released when it is dropped.
+    pub fn acquire_connection(&self, ip: IpAddr) -> Result<ConnectionSlot, Pop3Error> {
+        self.connections.acquire(ip).inspect_err(|_| {
+            self.stats
+                .rejected_connections
+                .fetch_add(1, Ordering::Relaxed);
+        })
     }
 
-    /// Record connection close
-    pub fn record_connection_close(&self, ip: IpAddr) {
-        let mut connections = self.connections.lock().unwrap();
-        if let Some(conn_info) = connections.get_mut(&ip) {
-            if conn_info.count > 0 {
-                conn_info.count -= 1;
-            }
-        }
+    /// Whether authentication may be attempted on a connection
+    ///
+    /// When `require_tls_for_auth` is set, clients on a plaintext connection
+    /// must upgrade with STLS before any authentication command is accepted.
+    pub fn is_auth_allowed(&self, is_tls: bool) -> bool {
+        is_tls || !self.config.require_tls_for_auth
     }
 
     /// Check command rate limit
@@ -1165,4 +1219,65 @@ mod tests {
         // Fourth command should be blocked due to rate limit
         assert!(manager.check_command_rate(1).is_err(), "Fourth command should be blocked");
     }
+
+    #[test]
+    fn test_connection_limit_per_ip() {
+        let config = SecurityConfig {
+            max_connections_per_ip: 2,
+            ..Default::default()
+        };
+        let manager = SecurityManager::new(config);
+        let ip = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 3));
+        let other_ip = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 4));
+
+        let first = manager.acquire_connection(ip).unwrap();
+        let _second = manager.acquire_connection(ip).unwrap();
+        assert_eq!(
+            manager.acquire_connection(ip).err(),
+            Some(Pop3Error::TooManyConnections)
+        );
+
+        // Other addresses are unaffected
+        assert!(manager.acquire_connection(other_ip).is_ok());
+
+        // Closing a session frees its slot
+        drop(first);
+        assert!(manager.acquire_connection(ip).is_ok());
+    }
+
+    #[test]
+    fn test_connection_limit_global() {
+        let config = SecurityConfig {
+            max_connections_per_ip: 2,
+            max_connections: 2,
+            ..Default::default()
+        };
+        let manager = SecurityManager::new(config);
+
+        let _slots = (1..=2)
+            .map(|i| {
+                manager
+                    .acquire_connection(IpAddr::V4(Ipv4Addr::new(10, 0, 0, i)))
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert!(
+            manager
+                .acquire_connection(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 3)))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_auth_requires_tls() {
+        let manager = SecurityManager::new(SecurityConfig {
+            require_tls_for_auth: true,
+            ..Default::default()
+        });
+        assert!(!manager.is_auth_allowed(false));
+        assert!(manager.is_auth_allowed(true));
+
+        let manager = SecurityManager::new(SecurityConfig::default());
+        assert!(manager.is_auth_allowed(false));
+    }
 }
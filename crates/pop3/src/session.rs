@@ -5,7 +5,7 @@
  */
 
 use std::borrow::Cow;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use common::{
     core::BuildServer,
@@ -30,22 +30,33 @@ impl SessionManager for Pop3SessionManager {
         session: SessionData<T>,
     ) -> impl std::future::Future<Output = ()> + Send {
         async move {
-            // Generate APOP timestamp
-            let timestamp = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
-            let apop_timestamp = format!("<{}.{}@stalwart>", timestamp, session.session_id);
-
-            // Check connection limits
-            if let Err(_) = self.security.check_connection_allowed(session.remote_ip) {
-                trc::event!(
-                    Pop3(trc::Pop3Event::Error),
-                    RemoteIp = session.remote_ip,
-                    Reason = "Connection limit exceeded",
-                );
-                return;
-            }
+            // Generate APOP timestamp, only announced when APOP is enabled
+            let apop_timestamp = self.config.server.enable_apop.then(|| {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs();
+                format!("<{}.{}@stalwart>", timestamp, session.session_id)
+            });
+
+            // Check connection limits, the slot is held until the session ends
+            let _connection = match self.security.acquire_connection(session.remote_ip) {
+                Ok(slot) => slot,
+                Err(err) => {
+                    trc::event!(
+                        Pop3(trc::Pop3Event::Error),
+                        RemoteIp = session.remote_ip,
+                        Reason = err.to_string(),
+                    );
+
+                    let mut stream = session.stream;
+                    let _ = stream
+                        .write_all(b"-ERR Too many connections, please try again later.\r\n")
+                        .await;
+                    let _ = stream.shutdown().await;
+                    return;
+                }
+            };
 
             let mut session = Session {
                 server: self.inner.build_server(),
@@ -54,18 +65,26 @@ impl SessionManager for Pop3SessionManager {
                 state: State::NotAuthenticated {
                     auth_failures: 0,
                     username: None,
-                    apop_timestamp: Some(apop_timestamp.clone()),
+                    apop_timestamp: apop_timestamp.clone(),
                 },
                 stream: session.stream,
                 in_flight: session.in_flight,
                 remote_addr: session.remote_ip,
                 session_id: session.session_id,
+                started: Instant::now(),
+                config: self.config.clone(),
                 security: self.security.clone(),
+                stats: self.stats.clone(),
             };
 
             // Send greeting with APOP timestamp
-            let greeting = format!("+OK Stalwart POP3 at your service {}\r\n", apop_timestamp);
-            let remote_addr = session.remote_addr;
+            let greeting = match &apop_timestamp {
+                Some(apop_timestamp) => Cow::Owned(format!(
+                    "+OK Stalwart POP3 at your service {}\r\n",
+                    apop_timestamp
+                )),
+                None => Cow::Borrowed(SERVER_GREETING),
+            };
 
             if session
                 .write_bytes(greeting.as_bytes())
@@ -79,8 +98,8 @@ impl SessionManager for Pop3SessionManager {
                 }
             }
 
-            // Record connection close
-            self.security.record_connection_close(remote_addr);
+            // Publish the counters updated by this session
+            self.stats.export(&self.monitoring);
         }
     }
 
@@ -98,11 +117,7 @@ impl<T: SessionStream> Session<T> {
         loop {
             tokio::select! {
                 result = tokio::time::timeout(
-                    if !matches!(self.state, State::NotAuthenticated {..}) {
-                        self.server.core.imap.timeout_auth
-                    } else {
-                        self.server.core.imap.timeout_unauth
-                    },
+                    self.read_timeout(),
                     self.stream.read(&mut buf)) => {
                     match result {
                         Ok(Ok(bytes_read)) => {
@@ -141,7 +156,11 @@ impl<T: SessionStream> Session<T> {
                                 CausedBy = trc::location!()
                             );
 
-                            self.write_bytes(&b"-ERR Connection timed out.\r\n"[..]).await.ok();
+                            if self.started.elapsed() >= self.config.security.max_session_duration {
+                                self.write_bytes(&b"-ERR Session time limit exceeded.\r\n"[..]).await.ok();
+                            } else {
+                                self.write_bytes(&b"-ERR Connection timed out.\r\n"[..]).await.ok();
+                            }
                             break;
                         }
                     }
@@ -163,7 +182,43 @@ impl<T: SessionStream> Session<T> {
         false
     }
 
+    /// Time to wait for the next command
+    ///
+    /// The idle timeout depends on whether the client has authenticated, and
+    /// is cut short when the session reaches its maximum duration.
+    fn read_timeout(&self) -> Duration {
+        let idle = if !matches!(self.state, State::NotAuthenticated { .. }) {
+            self.config.server.session_timeout
+        } else {
+            self.config.server.unauth_timeout
+        };
+
+        idle.min(
+            self.config
+                .security
+                .max_session_duration
+                .saturating_sub(self.started.elapsed()),
+        )
+    }
+
+    /// Upgrade the connection after STLS
+    ///
+    /// Anything the client sent in plaintext, including a pending USER name
+    /// and pipelined input, is discarded so it cannot leak into the TLS session.
     pub async fn into_tls(self) -> Result<Session<TlsStream<T>>, ()> {
+        let state = match self.state {
+            State::NotAuthenticated {
+                auth_failures,
+                apop_timestamp,
+                ..
+            } => State::NotAuthenticated {
+                auth_failures,
+                username: None,
+                apop_timestamp,
+            },
+            state => state,
+        };
+
         Ok(Session {
             stream: self
                 .instance
@@ -171,12 +226,15 @@ impl<T: SessionStream> Session<T> {
                 .await?,
             server: self.server,
             instance: self.instance,
-            receiver: self.receiver,
-            state: self.state,
+            receiver: Parser::default(),
+            state,
             session_id: self.session_id,
+            started: self.started,
             in_flight: self.in_flight,
             remote_addr: self.remote_addr,
+            config: self.config,
             security: self.security,
+            stats: self.stats,
         })
     }
 }
@@ -162,6 +162,10 @@ fn test_capability_negotiation() {
     let capability_response = Response::Capability {
         mechanisms: vec![Mechanism::Plain, Mechanism::CramMd5, Mechanism::OAuthBearer],
         stls: true,
+        top: true,
+        uidl: true,
+        pipelining: true,
+        utf8: true,
     };
 
     let serialized = String::from_utf8(capability_response.serialize()).unwrap();
@@ -182,7 +186,7 @@ fn test_message_transparency() {
     // Test dot stuffing in message content
     let message_response = Response::Message {
         bytes: b"Subject: Test\r\n\r\n.This line starts with a dot\r\n..This line starts with two dots\r\nNormal line\r\n".to_vec(),
-        lines: 0,
+        lines: None,
     };
 
     let serialized = String::from_utf8(message_response.serialize()).unwrap();
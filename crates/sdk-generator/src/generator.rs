@@ -1,21 +1,148 @@
 //! SDK generator implementation
 
-use crate::{GeneratorConfig, error::Result};
+use crate::{
+    error::{GeneratorError, Result},
+    languages::{generator_for, GenerationContext},
+    ApiSpec, GenerationOptions, GeneratorConfig, Language, SdkArtifact, SdkMetadata,
+    TemplateEngine,
+};
+use std::path::{Path, PathBuf};
 
 /// SDK generator
 pub struct SdkGenerator {
     config: GeneratorConfig,
+    api_spec: Option<ApiSpec>,
 }
 
 impl SdkGenerator {
     /// Create new SDK generator
     pub async fn new(config: GeneratorConfig) -> Result<Self> {
-        Ok(Self { config })
+        Ok(Self {
+            config,
+            api_spec: None,
+        })
+    }
+
+    /// Set the API specification SDKs are generated from
+    pub fn with_api_spec(mut self, api_spec: ApiSpec) -> Self {
+        self.api_spec = Some(api_spec);
+        self
+    }
+
+    /// Generate an SDK in memory
+    pub fn generate(&self, spec: &ApiSpec, options: &GenerationOptions) -> Result<SdkArtifact> {
+        let generator = generator_for(&options.language).ok_or_else(|| {
+            GeneratorError::Configuration(format!("Unsupported language: {}", options.language))
+        })?;
+
+        let mut templates = TemplateEngine::new();
+        if let Some(dir) = &options.custom_templates {
+            templates.load_overrides(dir, &options.language, generator.template_names())?;
+        }
+        let files = generator.generate(&GenerationContext {
+            spec,
+            options,
+            templates: &templates,
+        })?;
+
+        Ok(SdkArtifact {
+            language: options.language.clone(),
+            files,
+            metadata: SdkMetadata {
+                name: options.package_name.clone(),
+                version: options.package_version.clone(),
+                language: options.language.clone(),
+                api_version: spec.version.clone(),
+                generator_version: env!("CARGO_PKG_VERSION").to_string(),
+                dependencies: generator.dependencies(),
+                build_instructions: None,
+                installation_instructions: None,
+            },
+            generated_at: chrono::Utc::now(),
+        })
     }
 
     /// Generate SDK
-    pub async fn generate_sdk(&self, _language: crate::Language, _output_dir: &str) -> Result<()> {
-        // TODO: Implement SDK generation
+    pub async fn generate_sdk(&self, language: Language, output_dir: &str) -> Result<()> {
+        let spec = self
+            .api_spec
+            .as_ref()
+            .ok_or_else(|| GeneratorError::Configuration("No API specification set".to_string()))?;
+        let output_dir = if output_dir.is_empty() {
+            Path::new(&self.config.output_dir)
+        } else {
+            Path::new(output_dir)
+        };
+        let options = GenerationOptions {
+            language,
+            output_dir: output_dir.to_path_buf(),
+            custom_templates: self.config.template_dir.as_ref().map(PathBuf::from),
+            ..Default::default()
+        };
+
+        let artifact = self.generate(spec, &options)?;
+        for file in &artifact.files {
+            let path = output_dir.join(&file.path);
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&path, &file.content).await?;
+        }
+
+        tracing::info!(
+            "Generated {} SDK with {} files in {}",
+            artifact.language,
+            artifact.files.len(),
+            output_dir.display()
+        );
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_custom_templates() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("python")).unwrap();
+        std::fs::write(
+            dir.path().join("python").join("README.md.hbs"),
+            "# {{spec.title}} {{spec.version}}\n",
+        )
+        .unwrap();
+
+        let generator = SdkGenerator::new(GeneratorConfig::default()).await.unwrap();
+        let spec = ApiSpec {
+            title: "Mail".to_string(),
+            version: "2.0".to_string(),
+            ..Default::default()
+        };
+        let options = GenerationOptions {
+            language: Language::Python,
+            custom_templates: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+
+        let artifact = generator.generate(&spec, &options).unwrap();
+        let readme = artifact
+            .files
+            .iter()
+            .find(|file| file.path == Path::new("README.md"))
+            .unwrap();
+        assert_eq!(readme.content, "# Mail 2.0\n");
+        // Files without a template keep their built-in content
+        assert!(artifact
+            .files
+            .iter()
+            .any(|file| file.path == Path::new("pyproject.toml")
+                && file.content.contains("requests")));
+
+        std::fs::write(dir.path().join("python").join("client.py.hbs"), "{{#each}}").unwrap();
+        assert!(matches!(
+            generator.generate(&spec, &options),
+            Err(GeneratorError::Template(message)) if message.contains("client.py.hbs")
+        ));
+    }
+}
@@ -1,10 +1,188 @@
 //! API introspection
 
+use crate::error::Result;
+use crate::openapi::Openapi;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
 /// API introspector
 pub struct ApiIntrospector;
 
+impl ApiIntrospector {
+    /// Load an API specification from an OpenAPI 3.x JSON or YAML file
+    pub fn from_openapi_file(path: &Path) -> Result<ApiSpec> {
+        Self::from_openapi_str(&std::fs::read_to_string(path)?)
+    }
+
+    /// Load an API specification from an OpenAPI 3.x JSON or YAML document
+    pub fn from_openapi_str(content: &str) -> Result<ApiSpec> {
+        Openapi::parse(content)
+    }
+}
+
 /// API specification
-pub struct ApiSpec;
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApiSpec {
+    pub title: String,
+    pub version: String,
+    pub description: Option<String>,
+    /// Default server URL
+    pub base_url: Option<String>,
+    pub endpoints: Vec<EndpointSpec>,
+    /// Named schemas endpoints refer to with `SchemaSpec::Ref`
+    #[serde(default)]
+    pub schemas: BTreeMap<String, SchemaSpec>,
+    /// Security schemes by name
+    #[serde(default)]
+    pub security_schemes: BTreeMap<String, SecuritySchemeSpec>,
+    /// Features of the source document that could not be represented
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
 
 /// Endpoint specification
-pub struct EndpointSpec;
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EndpointSpec {
+    pub operation_id: Option<String>,
+    /// Uppercase HTTP method
+    pub method: String,
+    /// Path with `{name}` placeholders for path parameters
+    pub path: String,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub parameters: Vec<ParameterSpec>,
+    pub request_body: Option<SchemaSpec>,
+    #[serde(default)]
+    pub responses: Vec<ResponseSpec>,
+    /// Names of the security schemes accepted, empty for public endpoints
+    #[serde(default)]
+    pub security: Vec<String>,
+}
+
+/// Endpoint parameter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterSpec {
+    pub name: String,
+    pub location: ParameterLocation,
+    pub required: bool,
+    pub schema: SchemaSpec,
+    pub description: Option<String>,
+}
+
+/// Where a parameter is sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParameterLocation {
+    Path,
+    Query,
+    Header,
+}
+
+/// Endpoint response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseSpec {
+    /// Status code, or `default`
+    pub status: String,
+    pub description: Option<String>,
+    pub schema: Option<SchemaSpec>,
+}
+
+/// Value schema
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaSpec {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    /// String limited to the given values
+    Enum(Vec<String>),
+    Array(Box<SchemaSpec>),
+    Object(ObjectSchema),
+    /// Named schema from `ApiSpec::schemas`
+    Ref(String),
+    /// Value that may also be null
+    Nullable(Box<SchemaSpec>),
+    /// Any JSON value
+    Any,
+}
+
+/// Object schema
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ObjectSchema {
+    pub properties: BTreeMap<String, SchemaSpec>,
+    /// Properties that must be present
+    #[serde(default)]
+    pub required: Vec<String>,
+}
+
+/// Security scheme
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecuritySchemeSpec {
+    /// `Authorization: Bearer` token
+    Bearer,
+    /// `Authorization: Basic` credentials
+    Basic,
+    /// API key sent in a header or query parameter
+    ApiKey {
+        name: String,
+        location: ParameterLocation,
+    },
+}
+
+impl EndpointSpec {
+    /// Name of the operation, derived from the method and path when the
+    /// spec does not name it
+    pub fn name(&self) -> String {
+        if let Some(operation_id) = self.operation_id.as_ref().filter(|id| !id.is_empty()) {
+            return operation_id.clone();
+        }
+
+        let mut name = self.method.to_ascii_lowercase();
+        for segment in self.path.split('/').filter(|segment| !segment.is_empty()) {
+            match segment.strip_prefix('{').and_then(|segment| segment.strip_suffix('}')) {
+                Some(parameter) => {
+                    name.push_str("_by_");
+                    name.push_str(parameter);
+                }
+                None => {
+                    name.push('_');
+                    name.push_str(segment);
+                }
+            }
+        }
+        name
+    }
+
+    /// Schema of the first successful response
+    pub fn success_schema(&self) -> Option<&SchemaSpec> {
+        self.responses
+            .iter()
+            .filter(|response| response.status.starts_with('2'))
+            .find_map(|response| response.schema.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_name() {
+        let endpoint = EndpointSpec {
+            method: "GET".to_string(),
+            path: "/mailboxes/{mailboxId}/messages".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(endpoint.name(), "get_mailboxes_by_mailboxId_messages");
+
+        let endpoint = EndpointSpec {
+            operation_id: Some("listMessages".to_string()),
+            ..endpoint
+        };
+        assert_eq!(endpoint.name(), "listMessages");
+    }
+}
@@ -0,0 +1,173 @@
+//! Language generators
+
+#[cfg(feature = "python-sdk")]
+pub mod python;
+#[cfg(feature = "typescript-sdk")]
+pub mod typescript;
+
+use crate::error::Result;
+use crate::introspection::ApiSpec;
+use crate::templates::{TemplateContext, TemplateEngine};
+use crate::{Dependency, GeneratedFile, GenerationOptions};
+use std::collections::HashSet;
+
+/// Supported programming languages
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Language {
+    /// Rust programming language
+    Rust,
+    /// Python programming language
+    Python,
+    /// JavaScript programming language
+    JavaScript,
+    /// TypeScript programming language
+    TypeScript,
+    /// Go programming language
+    Go,
+    /// Java programming language
+    Java,
+    /// C# programming language
+    CSharp,
+    /// PHP programming language
+    PHP,
+    /// Custom language
+    Custom(String),
+}
+
+/// Language generator trait
+pub trait LanguageGenerator {
+    /// Language the generator emits
+    fn language(&self) -> Language;
+
+    /// Packages the generated SDK depends on
+    fn dependencies(&self) -> Vec<Dependency>;
+
+    /// Names of the generated files user templates can override
+    fn template_names(&self) -> &'static [&'static str];
+
+    /// Generate the SDK's files
+    fn generate(&self, context: &GenerationContext) -> Result<Vec<GeneratedFile>>;
+}
+
+/// Generation context
+pub struct GenerationContext<'a> {
+    pub spec: &'a ApiSpec,
+    pub options: &'a GenerationOptions,
+    /// User templates overriding built-in files
+    pub templates: &'a TemplateEngine,
+}
+
+impl GenerationContext<'_> {
+    /// Content of the file `name`, rendered from the user's template if one
+    /// overrides it
+    pub fn render(&self, name: &str, builtin: impl FnOnce() -> String) -> Result<String> {
+        if self.templates.get_override(name).is_none() {
+            return Ok(builtin());
+        }
+        let context = TemplateContext::new(self.spec, self.options);
+        self.templates.render_or(name, &context, builtin)
+    }
+}
+
+/// Get the generator for a language, if it is implemented
+pub fn generator_for(language: &Language) -> Option<Box<dyn LanguageGenerator>> {
+    match language {
+        #[cfg(feature = "python-sdk")]
+        Language::Python => Some(Box::new(python::PythonGenerator)),
+        #[cfg(feature = "typescript-sdk")]
+        Language::TypeScript => Some(Box::new(typescript::TypeScriptGenerator)),
+        _ => None,
+    }
+}
+
+/// Split an identifier in any casing into lowercase words
+pub(crate) fn split_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let chars = name.chars().collect::<Vec<_>>();
+
+    for (i, &ch) in chars.iter().enumerate() {
+        if !ch.is_ascii_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+
+        // Start a word at `aB` and at the last capital of an acronym in `ABc`
+        let previous = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1);
+        let boundary = ch.is_ascii_uppercase()
+            && match previous {
+                Some(previous) if previous.is_ascii_lowercase() || previous.is_ascii_digit() => true,
+                Some(previous) if previous.is_ascii_uppercase() => {
+                    next.is_some_and(|next| next.is_ascii_lowercase())
+                }
+                _ => false,
+            };
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(ch.to_ascii_lowercase());
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Convert an identifier to `snake_case`
+pub(crate) fn to_snake_case(name: &str) -> String {
+    split_words(name).join("_")
+}
+
+/// Convert an identifier to `PascalCase`
+pub(crate) fn to_pascal_case(name: &str) -> String {
+    split_words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Convert an identifier to `camelCase`
+pub(crate) fn to_camel_case(name: &str) -> String {
+    let pascal = to_pascal_case(name);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Make a name unique among those already used, appending `_2`, `_3`, ...
+pub(crate) fn unique(name: String, used: &mut HashSet<String>) -> String {
+    let mut candidate = name.clone();
+    let mut suffix = 2;
+    while !used.insert(candidate.clone()) {
+        candidate = format!("{}_{}", name, suffix);
+        suffix += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifier_casing() {
+        assert_eq!(to_snake_case("listMessages"), "list_messages");
+        assert_eq!(to_snake_case("get_mailboxes_by_mailboxId"), "get_mailboxes_by_mailbox_id");
+        assert_eq!(to_snake_case("parseHTTPResponse2"), "parse_http_response2");
+        assert_eq!(to_snake_case("X-Request-ID"), "x_request_id");
+        assert_eq!(to_pascal_case("mailbox_summary"), "MailboxSummary");
+        assert_eq!(to_camel_case("get_mailboxes_by_id"), "getMailboxesById");
+    }
+}
@@ -0,0 +1,695 @@
+//! Python SDK generator
+
+use super::{to_snake_case, unique, GenerationContext, Language, LanguageGenerator};
+use crate::error::Result;
+use crate::introspection::{
+    EndpointSpec, ParameterLocation, ParameterSpec, SchemaSpec, SecuritySchemeSpec,
+};
+use crate::{Dependency, FileType, GeneratedFile};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+const REQUESTS_VERSION: &str = ">=2.28";
+const PYTEST_VERSION: &str = ">=7.0";
+
+/// Python SDK generator
+///
+/// Emits a package with a `requests` based `Client` exposing one typed
+/// method per endpoint.
+pub struct PythonGenerator;
+
+impl LanguageGenerator for PythonGenerator {
+    fn language(&self) -> Language {
+        Language::Python
+    }
+
+    fn dependencies(&self) -> Vec<Dependency> {
+        vec![
+            Dependency {
+                name: "requests".to_string(),
+                version: REQUESTS_VERSION.to_string(),
+                optional: false,
+                dev_only: false,
+            },
+            Dependency {
+                name: "pytest".to_string(),
+                version: PYTEST_VERSION.to_string(),
+                optional: true,
+                dev_only: true,
+            },
+        ]
+    }
+
+    fn template_names(&self) -> &'static [&'static str] {
+        &[
+            "pyproject.toml",
+            "__init__.py",
+            "client.py",
+            "test_client.py",
+            "README.md",
+        ]
+    }
+
+    fn generate(&self, context: &GenerationContext) -> Result<Vec<GeneratedFile>> {
+        let module = module_name(&context.options.package_name);
+        let methods = method_names(&context.spec.endpoints);
+
+        let mut files = vec![
+            GeneratedFile {
+                path: PathBuf::from("pyproject.toml"),
+                content: context.render("pyproject.toml", || pyproject(context))?,
+                file_type: FileType::Build,
+            },
+            GeneratedFile {
+                path: PathBuf::from(&module).join("__init__.py"),
+                content: context.render("__init__.py", || {
+                    format!(
+                        "\"\"\"{} client.\"\"\"\n\nfrom .client import ApiError, Client\n\n__all__ = [\"ApiError\", \"Client\"]\n__version__ = {}\n",
+                        docstring(&context.spec.title),
+                        string(&context.options.package_version)
+                    )
+                })?,
+                file_type: FileType::Source,
+            },
+            GeneratedFile {
+                path: PathBuf::from(&module).join("client.py"),
+                content: context.render("client.py", || client(context, &methods))?,
+                file_type: FileType::Source,
+            },
+        ];
+
+        if context.options.include_tests {
+            files.push(GeneratedFile {
+                path: PathBuf::from("tests").join("test_client.py"),
+                content: context.render("test_client.py", || tests(&module, &methods))?,
+                file_type: FileType::Test,
+            });
+        }
+
+        if context.options.include_docs {
+            files.push(GeneratedFile {
+                path: PathBuf::from("README.md"),
+                content: context.render("README.md", || readme(context, &module, &methods))?,
+                file_type: FileType::Documentation,
+            });
+        }
+
+        Ok(files)
+    }
+}
+
+fn pyproject(context: &GenerationContext) -> String {
+    let description = format!("Client for the {} API", context.spec.title);
+    format!(
+        r#"[build-system]
+requires = ["setuptools>=61"]
+build-backend = "setuptools.build_meta"
+
+[project]
+name = {name}
+version = {version}
+description = {description}
+requires-python = ">=3.8"
+dependencies = ["requests{requests}"]
+
+[project.optional-dependencies]
+test = ["pytest{pytest}"]
+"#,
+        name = string(&context.options.package_name),
+        version = string(&context.options.package_version),
+        description = string(&description),
+        requests = REQUESTS_VERSION,
+        pytest = PYTEST_VERSION,
+    )
+}
+
+fn client(context: &GenerationContext, methods: &[String]) -> String {
+    let spec = context.spec;
+    let bearer = spec
+        .security_schemes
+        .values()
+        .any(|scheme| *scheme == SecuritySchemeSpec::Bearer);
+    let api_key = spec
+        .security_schemes
+        .values()
+        .find_map(|scheme| match scheme {
+            SecuritySchemeSpec::ApiKey { name, location } => Some((name, location)),
+            _ => None,
+        });
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        r#""""{title} client.
+
+Generated by stalwart-sdk-generator, do not edit.
+"""
+
+from typing import Any, Dict, List, Optional
+from urllib.parse import quote
+
+import requests
+
+
+class ApiError(Exception):
+    """Error returned by the API or raised while calling it."""
+
+    def __init__(self, status_code: int, message: str, body: Any = None) -> None:
+        super().__init__(f"{{status_code}}: {{message}}")
+        self.status_code = status_code
+        self.message = message
+        self.body = body
+
+
+class Client:
+    """Client for the {title} API."""
+
+    def __init__(
+        self,
+        base_url: str = {base_url},
+"#,
+        title = docstring(&spec.title),
+        base_url = string(spec.base_url.as_deref().unwrap_or("http://localhost:8080")),
+    );
+    if bearer {
+        out.push_str("        token: Optional[str] = None,\n");
+    }
+    if api_key.is_some() {
+        out.push_str("        api_key: Optional[str] = None,\n");
+    }
+    out.push_str(
+        r#"        headers: Optional[Dict[str, str]] = None,
+        timeout: float = 30.0,
+        session: Optional[requests.Session] = None,
+    ) -> None:
+        self.base_url = base_url.rstrip("/")
+        self.timeout = timeout
+        self.session = session or requests.Session()
+        self.session.headers.update(headers or {})
+        self._auth_params: Dict[str, str] = {}
+"#,
+    );
+    if bearer {
+        out.push_str(
+            "        if token is not None:\n            self.session.headers[\"Authorization\"] = f\"Bearer {token}\"\n",
+        );
+    }
+    if let Some((name, location)) = api_key {
+        let target = match location {
+            ParameterLocation::Query => "self._auth_params",
+            _ => "self.session.headers",
+        };
+        let _ = write!(
+            out,
+            "        if api_key is not None:\n            {}[{}] = api_key\n",
+            target,
+            string(name)
+        );
+    }
+
+    out.push_str(
+        r#"
+    def _request(
+        self,
+        method: str,
+        path: str,
+        params: Optional[Dict[str, Any]] = None,
+        headers: Optional[Dict[str, Any]] = None,
+        json: Any = None,
+    ) -> Any:
+        query = dict(self._auth_params)
+        query.update({key: value for key, value in (params or {}).items() if value is not None})
+        extra_headers = {key: str(value) for key, value in (headers or {}).items() if value is not None}
+        try:
+            response = self.session.request(
+                method,
+                self.base_url + path,
+                params=query,
+                headers=extra_headers,
+                json=json,
+                timeout=self.timeout,
+            )
+        except requests.RequestException as err:
+            raise ApiError(0, str(err)) from err
+
+        if not response.ok:
+            try:
+                body = response.json()
+            except ValueError:
+                body = response.text
+            raise ApiError(response.status_code, response.reason or "request failed", body)
+
+        if not response.content:
+            return None
+        try:
+            return response.json()
+        except ValueError:
+            return response.text
+"#,
+    );
+
+    for (endpoint, name) in spec.endpoints.iter().zip(methods) {
+        out.push('\n');
+        method(&mut out, endpoint, name, context.options.include_docs);
+    }
+
+    out
+}
+
+/// Python parameter of a generated method
+struct Argument<'a> {
+    name: String,
+    parameter: &'a ParameterSpec,
+}
+
+fn method(out: &mut String, endpoint: &EndpointSpec, name: &str, include_docs: bool) {
+    // Required arguments come first, then the optional ones defaulting to None
+    let mut used = HashSet::from(["self".to_string(), "body".to_string()]);
+    let mut arguments = endpoint
+        .parameters
+        .iter()
+        .map(|parameter| Argument {
+            name: unique(identifier(&parameter.name), &mut used),
+            parameter,
+        })
+        .collect::<Vec<_>>();
+    arguments.sort_by_key(|argument| !argument.parameter.required);
+
+    let _ = writeln!(out, "    def {}(", name);
+    out.push_str("        self,\n");
+    let mut optional_started = false;
+    for argument in &arguments {
+        if !argument.parameter.required && !optional_started {
+            optional_started = true;
+            if let Some(body) = &endpoint.request_body {
+                let _ = writeln!(out, "        body: {},", python_type(body));
+            }
+        }
+        let _ = if argument.parameter.required {
+            writeln!(
+                out,
+                "        {}: {},",
+                argument.name,
+                python_type(&argument.parameter.schema)
+            )
+        } else {
+            writeln!(
+                out,
+                "        {}: {} = None,",
+                argument.name,
+                optional_type(&argument.parameter.schema)
+            )
+        };
+    }
+    if !optional_started {
+        if let Some(body) = &endpoint.request_body {
+            let _ = writeln!(out, "        body: {},", python_type(body));
+        }
+    }
+    let _ = writeln!(
+        out,
+        "    ) -> {}:",
+        endpoint
+            .success_schema()
+            .map_or("Any".to_string(), python_type)
+    );
+
+    if include_docs {
+        let summary = endpoint
+            .summary
+            .as_deref()
+            .or(endpoint.description.as_deref())
+            .map(str::trim)
+            .filter(|summary| !summary.is_empty())
+            .map_or_else(
+                || format!("{} {}", endpoint.method, endpoint.path),
+                str::to_string,
+            );
+        let _ = write!(out, "        \"\"\"{}", docstring(&summary));
+        let documented = arguments
+            .iter()
+            .filter_map(|argument| {
+                Some((&argument.name, argument.parameter.description.as_deref()?))
+            })
+            .collect::<Vec<_>>();
+        if !documented.is_empty() {
+            out.push_str("\n\n        Args:\n");
+            for (name, description) in documented {
+                let _ = writeln!(
+                    out,
+                    "            {}: {}",
+                    name,
+                    docstring(description.trim())
+                );
+            }
+            out.push_str("        ");
+        }
+        out.push_str("\"\"\"\n");
+    }
+
+    let argument_names = arguments
+        .iter()
+        .map(|argument| (argument.parameter.name.as_str(), argument.name.as_str()))
+        .collect::<Vec<_>>();
+    let _ = writeln!(out, "        return self._request(");
+    let _ = writeln!(
+        out,
+        "            {},",
+        string(&endpoint.method.to_ascii_uppercase())
+    );
+    let _ = writeln!(
+        out,
+        "            {},",
+        path_expression(&endpoint.path, &argument_names)
+    );
+    for (keyword, location) in [
+        ("params", ParameterLocation::Query),
+        ("headers", ParameterLocation::Header),
+    ] {
+        let entries = arguments
+            .iter()
+            .filter(|argument| argument.parameter.location == location)
+            .map(|argument| format!("{}: {}", string(&argument.parameter.name), argument.name))
+            .collect::<Vec<_>>();
+        if !entries.is_empty() {
+            let _ = writeln!(out, "            {}={{{}}},", keyword, entries.join(", "));
+        }
+    }
+    if endpoint.request_body.is_some() {
+        out.push_str("            json=body,\n");
+    }
+    out.push_str("        )\n");
+}
+
+fn tests(module: &str, methods: &[String]) -> String {
+    let mut out = format!(
+        "import pytest\n\nfrom {} import Client\n\n\n@pytest.fixture\ndef client():\n    return Client(base_url=\"http://localhost\")\n",
+        module
+    );
+    for name in methods {
+        let _ = write!(
+            out,
+            "\n\ndef test_{name}(client):\n    assert callable(client.{name})\n    pytest.skip(\"TODO: exercise {name} against a test server\")\n",
+        );
+    }
+    out
+}
+
+fn readme(context: &GenerationContext, module: &str, methods: &[String]) -> String {
+    let mut out = format!(
+        "# {}\n\nPython client for the {} API.\n\n```sh\npip install {}\n```\n\n```python\nfrom {} import Client\n\nclient = Client(base_url=\"{}\")\n```\n\n## Methods\n\n",
+        context.options.package_name,
+        context.spec.title,
+        context.options.package_name,
+        module,
+        context.spec.base_url.as_deref().unwrap_or("http://localhost:8080"),
+    );
+    for (endpoint, name) in context.spec.endpoints.iter().zip(methods) {
+        let _ = writeln!(out, "- `{}`: `{} {}`", name, endpoint.method, endpoint.path);
+    }
+    out
+}
+
+/// Unique method name per endpoint
+fn method_names(endpoints: &[EndpointSpec]) -> Vec<String> {
+    // Client attributes would shadow methods of the same name
+    let mut used = ["_request", "_auth_params", "base_url", "session", "timeout"]
+        .into_iter()
+        .map(str::to_string)
+        .collect::<HashSet<_>>();
+    endpoints
+        .iter()
+        .map(|endpoint| unique(identifier(&endpoint.name()), &mut used))
+        .collect()
+}
+
+/// Python module name for a package name
+fn module_name(package_name: &str) -> String {
+    identifier(package_name)
+}
+
+/// Convert a name into a Python identifier
+fn identifier(name: &str) -> String {
+    let mut identifier = to_snake_case(name);
+    if identifier.is_empty() {
+        identifier = "value".to_string();
+    }
+    if identifier.starts_with(|ch: char| ch.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    if KEYWORDS.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+    identifier
+}
+
+fn python_type(schema: &SchemaSpec) -> String {
+    match schema {
+        SchemaSpec::String | SchemaSpec::Enum(_) => "str".to_string(),
+        SchemaSpec::Integer => "int".to_string(),
+        SchemaSpec::Number => "float".to_string(),
+        SchemaSpec::Boolean => "bool".to_string(),
+        SchemaSpec::Array(items) => format!("List[{}]", python_type(items)),
+        SchemaSpec::Object(_) | SchemaSpec::Ref(_) => "Dict[str, Any]".to_string(),
+        SchemaSpec::Nullable(inner) => optional_type(inner),
+        SchemaSpec::Any => "Any".to_string(),
+    }
+}
+
+fn optional_type(schema: &SchemaSpec) -> String {
+    match schema {
+        SchemaSpec::Nullable(_) | SchemaSpec::Any => python_type(schema),
+        _ => format!("Optional[{}]", python_type(schema)),
+    }
+}
+
+/// Python expression for a request path, substituting path parameters
+fn path_expression(path: &str, arguments: &[(&str, &str)]) -> String {
+    let mut expression = String::new();
+    let mut interpolated = false;
+    let mut rest = path;
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        let parameter = &rest[start + 1..end];
+        expression.push_str(&f_string_literal(&rest[..start]));
+        match arguments.iter().find(|(name, _)| *name == parameter) {
+            Some((_, argument)) => {
+                interpolated = true;
+                let _ = write!(expression, "{{quote(str({}), safe='')}}", argument);
+            }
+            None => expression.push_str(&f_string_literal(&rest[start..=end])),
+        }
+        rest = &rest[end + 1..];
+    }
+    expression.push_str(&f_string_literal(rest));
+
+    if interpolated {
+        format!("f\"{}\"", expression)
+    } else {
+        string(path)
+    }
+}
+
+fn f_string_literal(text: &str) -> String {
+    escape(text).replace('{', "{{").replace('}', "}}")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Double-quoted Python (and TOML) string literal
+fn string(text: &str) -> String {
+    format!("\"{}\"", escape(text))
+}
+
+/// Text safe to place inside a triple-quoted docstring
+fn docstring(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::{ApiSpec, ObjectSchema, ResponseSpec};
+    use crate::{GenerationOptions, TemplateEngine};
+    use std::collections::BTreeMap;
+
+    fn spec() -> ApiSpec {
+        let mailbox = SchemaSpec::Object(ObjectSchema {
+            properties: BTreeMap::from([
+                ("id".to_string(), SchemaSpec::String),
+                ("name".to_string(), SchemaSpec::String),
+                ("unread".to_string(), SchemaSpec::Integer),
+            ]),
+            required: vec!["id".to_string(), "name".to_string()],
+        });
+
+        ApiSpec {
+            title: "Mail".to_string(),
+            version: "1.0.0".to_string(),
+            base_url: Some("https://mail.example.com/api".to_string()),
+            endpoints: vec![
+                EndpointSpec {
+                    operation_id: Some("listMailboxes".to_string()),
+                    method: "GET".to_string(),
+                    path: "/mailboxes".to_string(),
+                    summary: Some("List \"\"\"mailboxes\"\"\"".to_string()),
+                    parameters: vec![ParameterSpec {
+                        name: "limit".to_string(),
+                        location: ParameterLocation::Query,
+                        required: false,
+                        schema: SchemaSpec::Integer,
+                        description: Some("Maximum number of mailboxes".to_string()),
+                    }],
+                    responses: vec![ResponseSpec {
+                        status: "200".to_string(),
+                        description: None,
+                        schema: Some(SchemaSpec::Array(Box::new(SchemaSpec::Ref(
+                            "Mailbox".to_string(),
+                        )))),
+                    }],
+                    security: vec!["bearer".to_string()],
+                    ..Default::default()
+                },
+                EndpointSpec {
+                    method: "PUT".to_string(),
+                    path: "/mailboxes/{mailboxId}".to_string(),
+                    parameters: vec![
+                        ParameterSpec {
+                            name: "X-Request-ID".to_string(),
+                            location: ParameterLocation::Header,
+                            required: false,
+                            schema: SchemaSpec::String,
+                            description: None,
+                        },
+                        ParameterSpec {
+                            name: "mailboxId".to_string(),
+                            location: ParameterLocation::Path,
+                            required: true,
+                            schema: SchemaSpec::String,
+                            description: None,
+                        },
+                    ],
+                    request_body: Some(SchemaSpec::Ref("Mailbox".to_string())),
+                    ..Default::default()
+                },
+                EndpointSpec {
+                    operation_id: Some("import".to_string()),
+                    method: "POST".to_string(),
+                    path: "/import/{from}".to_string(),
+                    parameters: vec![ParameterSpec {
+                        name: "from".to_string(),
+                        location: ParameterLocation::Path,
+                        required: true,
+                        schema: SchemaSpec::Enum(vec!["mbox".to_string(), "maildir".to_string()]),
+                        description: None,
+                    }],
+                    ..Default::default()
+                },
+            ],
+            schemas: BTreeMap::from([("Mailbox".to_string(), mailbox)]),
+            security_schemes: BTreeMap::from([
+                ("bearer".to_string(), SecuritySchemeSpec::Bearer),
+                (
+                    "apiKey".to_string(),
+                    SecuritySchemeSpec::ApiKey {
+                        name: "X-API-Key".to_string(),
+                        location: ParameterLocation::Header,
+                    },
+                ),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_generated_python_compiles() {
+        let spec = spec();
+        let options = GenerationOptions {
+            language: Language::Python,
+            ..Default::default()
+        };
+        let files = PythonGenerator
+            .generate(&GenerationContext {
+                spec: &spec,
+                options: &options,
+                templates: &TemplateEngine::new(),
+            })
+            .unwrap();
+
+        let client = files
+            .iter()
+            .find(|file| file.path == PathBuf::from("stalwart_client/client.py"))
+            .unwrap();
+        for method in [
+            "def list_mailboxes(",
+            "def put_mailboxes_by_mailbox_id(",
+            "def import_(",
+        ] {
+            assert!(client.content.contains(method), "missing {}", method);
+        }
+        assert!(client
+            .content
+            .contains("f\"/mailboxes/{quote(str(mailbox_id), safe='')}\""));
+        assert!(client
+            .content
+            .contains("headers={\"X-Request-ID\": x_request_id}"));
+        assert!(client.content.contains("-> List[Dict[str, Any]]:"));
+        assert!(files
+            .iter()
+            .any(|file| file.path == PathBuf::from("pyproject.toml")
+                && file.content.contains("\"requests>=2.28\"")));
+
+        let tests = files
+            .iter()
+            .find(|file| file.file_type == FileType::Test)
+            .unwrap();
+        assert_eq!(tests.content.matches("\ndef test_").count(), 3);
+
+        // Every generated Python file must be valid syntax
+        let dir = tempfile::tempdir().unwrap();
+        let mut paths = Vec::new();
+        for file in files
+            .iter()
+            .filter(|file| file.path.extension().is_some_and(|ext| ext == "py"))
+        {
+            let path = dir.path().join(&file.path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &file.content).unwrap();
+            paths.push(path);
+        }
+
+        let output = match std::process::Command::new("python3")
+            .arg("-c")
+            .arg("import sys\nfor path in sys.argv[1:]:\n    compile(open(path).read(), path, 'exec')")
+            .args(&paths)
+            .output()
+        {
+            Ok(output) => output,
+            Err(err) => {
+                eprintln!("Skipping Python syntax check, python3 unavailable: {}", err);
+                return;
+            }
+        };
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
@@ -0,0 +1,813 @@
+//! TypeScript SDK generator
+
+use super::{
+    to_camel_case, to_pascal_case, unique, GenerationContext, Language, LanguageGenerator,
+};
+use crate::error::Result;
+use crate::introspection::{
+    EndpointSpec, ObjectSchema, ParameterLocation, ParameterSpec, SchemaSpec, SecuritySchemeSpec,
+};
+use crate::{Dependency, FileType, GeneratedFile};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// Words that cannot name a parameter
+const RESERVED: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+const TYPESCRIPT_VERSION: &str = "^5.0.0";
+
+/// TypeScript SDK generator
+///
+/// Emits an interface per named schema and a `fetch` based `Client` exposing
+/// one typed method per endpoint.
+pub struct TypeScriptGenerator;
+
+impl LanguageGenerator for TypeScriptGenerator {
+    fn language(&self) -> Language {
+        Language::TypeScript
+    }
+
+    fn dependencies(&self) -> Vec<Dependency> {
+        vec![Dependency {
+            name: "typescript".to_string(),
+            version: TYPESCRIPT_VERSION.to_string(),
+            optional: false,
+            dev_only: true,
+        }]
+    }
+
+    fn template_names(&self) -> &'static [&'static str] {
+        &[
+            "package.json",
+            "tsconfig.json",
+            "models.ts",
+            "client.ts",
+            "index.ts",
+            "README.md",
+        ]
+    }
+
+    fn generate(&self, context: &GenerationContext) -> Result<Vec<GeneratedFile>> {
+        let methods = method_names(&context.spec.endpoints);
+
+        let mut files = vec![
+            GeneratedFile {
+                path: PathBuf::from("package.json"),
+                content: context.render("package.json", || package_json(context))?,
+                file_type: FileType::Build,
+            },
+            GeneratedFile {
+                path: PathBuf::from("tsconfig.json"),
+                content: context.render("tsconfig.json", tsconfig_json)?,
+                file_type: FileType::Config,
+            },
+            GeneratedFile {
+                path: PathBuf::from("src").join("models.ts"),
+                content: context.render("models.ts", || models(context))?,
+                file_type: FileType::Source,
+            },
+            GeneratedFile {
+                path: PathBuf::from("src").join("client.ts"),
+                content: context.render("client.ts", || client(context, &methods))?,
+                file_type: FileType::Source,
+            },
+            GeneratedFile {
+                path: PathBuf::from("src").join("index.ts"),
+                content: context.render("index.ts", || {
+                    "export * from \"./client\";\nexport * from \"./models\";\n".to_string()
+                })?,
+                file_type: FileType::Source,
+            },
+        ];
+
+        if context.options.include_docs {
+            files.push(GeneratedFile {
+                path: PathBuf::from("README.md"),
+                content: context.render("README.md", || readme(context, &methods))?,
+                file_type: FileType::Documentation,
+            });
+        }
+
+        Ok(files)
+    }
+}
+
+fn package_json(context: &GenerationContext) -> String {
+    let package = serde_json::json!({
+        "name": context.options.package_name,
+        "version": context.options.package_version,
+        "description": format!("Client for the {} API", context.spec.title),
+        "main": "dist/index.js",
+        "types": "dist/index.d.ts",
+        "files": ["dist"],
+        "scripts": {
+            "build": "tsc",
+        },
+        "devDependencies": {
+            "typescript": TYPESCRIPT_VERSION,
+        },
+    });
+    serde_json::to_string_pretty(&package).unwrap_or_default() + "\n"
+}
+
+fn tsconfig_json() -> String {
+    let tsconfig = serde_json::json!({
+        "compilerOptions": {
+            "target": "ES2020",
+            "module": "commonjs",
+            "lib": ["ES2020", "DOM"],
+            "declaration": true,
+            "strict": true,
+            "outDir": "dist",
+            "rootDir": "src",
+        },
+        "include": ["src"],
+    });
+    serde_json::to_string_pretty(&tsconfig).unwrap_or_default() + "\n"
+}
+
+fn models(context: &GenerationContext) -> String {
+    let mut out = "// Generated by stalwart-sdk-generator, do not edit.\n".to_string();
+    for (name, schema) in &context.spec.schemas {
+        out.push('\n');
+        match schema {
+            SchemaSpec::Object(object) if !object.properties.is_empty() => {
+                let _ = writeln!(out, "export interface {} {{", type_name(name));
+                for (property, schema) in &object.properties {
+                    let _ = writeln!(
+                        out,
+                        "  {}{}: {};",
+                        property_name(property),
+                        if object.required.contains(property) {
+                            ""
+                        } else {
+                            "?"
+                        },
+                        ts_type(schema)
+                    );
+                }
+                out.push_str("}\n");
+            }
+            schema => {
+                let _ = writeln!(
+                    out,
+                    "export type {} = {};",
+                    type_name(name),
+                    ts_type(schema)
+                );
+            }
+        }
+    }
+    out
+}
+
+fn client(context: &GenerationContext, methods: &[String]) -> String {
+    let spec = context.spec;
+    let bearer = spec
+        .security_schemes
+        .values()
+        .any(|scheme| *scheme == SecuritySchemeSpec::Bearer);
+    let api_key = spec
+        .security_schemes
+        .values()
+        .find_map(|scheme| match scheme {
+            SecuritySchemeSpec::ApiKey { name, location } => Some((name, location)),
+            _ => None,
+        });
+    let mut models = spec
+        .schemas
+        .keys()
+        .map(|name| type_name(name))
+        .collect::<Vec<_>>();
+    models.sort();
+
+    let mut out = "// Generated by stalwart-sdk-generator, do not edit.\n\n".to_string();
+    if !models.is_empty() {
+        let _ = writeln!(
+            out,
+            "import type {{ {} }} from \"./models\";\n",
+            models.join(", ")
+        );
+    }
+    out.push_str(
+        r#"/** Error returned by the API or raised while calling it */
+export class ApiError extends Error {
+  constructor(
+    public readonly status: number,
+    message: string,
+    public readonly body?: unknown,
+  ) {
+    super(`${status}: ${message}`);
+    this.name = "ApiError";
+  }
+}
+
+/** Client options */
+export interface ClientOptions {
+  baseUrl?: string;
+"#,
+    );
+    if bearer {
+        out.push_str("  token?: string;\n");
+    }
+    if api_key.is_some() {
+        out.push_str("  apiKey?: string;\n");
+    }
+    let _ = write!(
+        out,
+        r#"  headers?: Record<string, string>;
+  fetch?: typeof fetch;
+}}
+
+/** Client for the {title} API */
+export class Client {{
+  private readonly baseUrl: string;
+  private readonly headers: Record<string, string>;
+  private readonly query: Record<string, string> = {{}};
+  private readonly fetchImpl: typeof fetch;
+
+  constructor(options: ClientOptions = {{}}) {{
+    this.baseUrl = (options.baseUrl ?? {base_url}).replace(/\/+$/, "");
+    this.headers = {{ ...options.headers }};
+    this.fetchImpl = options.fetch ?? globalThis.fetch.bind(globalThis);
+"#,
+        title = comment(&spec.title),
+        base_url = string(spec.base_url.as_deref().unwrap_or("http://localhost:8080")),
+    );
+    if bearer {
+        out.push_str(
+            "    if (options.token !== undefined) {\n      this.headers[\"Authorization\"] = `Bearer ${options.token}`;\n    }\n",
+        );
+    }
+    if let Some((name, location)) = api_key {
+        let target = match location {
+            ParameterLocation::Query => "this.query",
+            _ => "this.headers",
+        };
+        let _ = write!(
+            out,
+            "    if (options.apiKey !== undefined) {{\n      {}[{}] = options.apiKey;\n    }}\n",
+            target,
+            string(name)
+        );
+    }
+
+    out.push_str(
+        r#"  }
+
+  private async request<T>(
+    method: string,
+    path: string,
+    query: Record<string, unknown> = {},
+    headers: Record<string, unknown> = {},
+    body?: unknown,
+  ): Promise<T> {
+    const url = new URL(this.baseUrl + path);
+    for (const [key, value] of Object.entries({ ...this.query, ...query })) {
+      for (const item of Array.isArray(value) ? value : [value]) {
+        if (item !== undefined && item !== null) {
+          url.searchParams.append(key, String(item));
+        }
+      }
+    }
+
+    const requestHeaders: Record<string, string> = { ...this.headers };
+    for (const [key, value] of Object.entries(headers)) {
+      if (value !== undefined && value !== null) {
+        requestHeaders[key] = String(value);
+      }
+    }
+    if (body !== undefined) {
+      requestHeaders["Content-Type"] = "application/json";
+    }
+
+    let response: Response;
+    try {
+      response = await this.fetchImpl(url.toString(), {
+        method,
+        headers: requestHeaders,
+        body: body === undefined ? undefined : JSON.stringify(body),
+      });
+    } catch (err) {
+      throw new ApiError(0, String(err));
+    }
+
+    const text = await response.text();
+    let data: unknown = text;
+    if (text && (response.headers.get("Content-Type") ?? "").includes("json")) {
+      try {
+        data = JSON.parse(text);
+      } catch {
+        data = text;
+      }
+    }
+    if (!response.ok) {
+      throw new ApiError(response.status, response.statusText || "request failed", data);
+    }
+    return (text ? data : undefined) as T;
+  }
+"#,
+    );
+
+    for (endpoint, name) in spec.endpoints.iter().zip(methods) {
+        out.push('\n');
+        method(&mut out, endpoint, name, context.options.include_docs);
+    }
+    out.push_str("}\n");
+
+    out
+}
+
+/// TypeScript parameter of a generated method
+struct Argument<'a> {
+    name: String,
+    parameter: &'a ParameterSpec,
+}
+
+fn method(out: &mut String, endpoint: &EndpointSpec, name: &str, include_docs: bool) {
+    // Required arguments come first, then the body and the optional ones
+    let mut used = HashSet::from(["body".to_string()]);
+    let mut arguments = endpoint
+        .parameters
+        .iter()
+        .map(|parameter| Argument {
+            name: unique(identifier(&parameter.name), &mut used),
+            parameter,
+        })
+        .collect::<Vec<_>>();
+    arguments.sort_by_key(|argument| !argument.parameter.required);
+
+    let mut signature = arguments
+        .iter()
+        .filter(|argument| argument.parameter.required)
+        .map(|argument| format!("{}: {}", argument.name, ts_type(&argument.parameter.schema)))
+        .collect::<Vec<_>>();
+    if let Some(body) = &endpoint.request_body {
+        signature.push(format!("body: {}", ts_type(body)));
+    }
+    signature.extend(
+        arguments
+            .iter()
+            .filter(|argument| !argument.parameter.required)
+            .map(|argument| {
+                format!(
+                    "{}?: {}",
+                    argument.name,
+                    ts_type(&argument.parameter.schema)
+                )
+            }),
+    );
+
+    if include_docs {
+        // Summary and description paragraphs, then the parameter tags
+        let mut paragraphs = Vec::new();
+        for text in [&endpoint.summary, &endpoint.description]
+            .into_iter()
+            .flatten()
+        {
+            let text = text.trim();
+            if !text.is_empty() && !paragraphs.contains(&text) {
+                paragraphs.push(text);
+            }
+        }
+        let fallback = format!("{} {}", endpoint.method, endpoint.path);
+        if paragraphs.is_empty() {
+            paragraphs.push(&fallback);
+        }
+        let tags = arguments
+            .iter()
+            .filter_map(|argument| {
+                let description = argument.parameter.description.as_deref()?.trim();
+                Some(format!("@param {} {}", argument.name, description))
+            })
+            .collect::<Vec<_>>();
+        let tags = tags.join("\n");
+        if !tags.is_empty() {
+            paragraphs.push(&tags);
+        }
+
+        out.push_str("  /**\n");
+        for line in paragraphs.join("\n\n").lines() {
+            let _ = writeln!(
+                out,
+                "   *{}{}",
+                if line.is_empty() { "" } else { " " },
+                comment(line)
+            );
+        }
+        out.push_str("   */\n");
+    }
+
+    let return_type = endpoint
+        .success_schema()
+        .map_or("unknown".to_string(), ts_type);
+    let _ = writeln!(
+        out,
+        "  async {}({}): Promise<{}> {{",
+        name,
+        signature.join(", "),
+        return_type
+    );
+
+    let argument_names = arguments
+        .iter()
+        .map(|argument| (argument.parameter.name.as_str(), argument.name.as_str()))
+        .collect::<Vec<_>>();
+    let mut call = vec![
+        string(&endpoint.method.to_ascii_uppercase()),
+        path_expression(&endpoint.path, &argument_names),
+    ];
+    let mut trailing = Vec::new();
+    for location in [ParameterLocation::Query, ParameterLocation::Header] {
+        let entries = arguments
+            .iter()
+            .filter(|argument| argument.parameter.location == location)
+            .map(|argument| format!("{}: {}", string(&argument.parameter.name), argument.name))
+            .collect::<Vec<_>>();
+        trailing.push(if entries.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{ {} }}", entries.join(", "))
+        });
+    }
+    if endpoint.request_body.is_some() {
+        trailing.push("body".to_string());
+    }
+    // Drop trailing arguments left at their defaults
+    while trailing.last().is_some_and(|argument| argument == "{}") {
+        trailing.pop();
+    }
+    call.extend(trailing);
+
+    let _ = writeln!(
+        out,
+        "    return this.request<{}>({});",
+        return_type,
+        call.join(", ")
+    );
+    out.push_str("  }\n");
+}
+
+fn readme(context: &GenerationContext, methods: &[String]) -> String {
+    let mut out = format!(
+        "# {name}\n\nTypeScript client for the {title} API.\n\n```sh\nnpm install {name}\n```\n\n```typescript\nimport {{ Client }} from \"{name}\";\n\nconst client = new Client({{ baseUrl: {base_url} }});\n```\n\n## Methods\n\n",
+        name = context.options.package_name,
+        title = context.spec.title,
+        base_url = string(context.spec.base_url.as_deref().unwrap_or("http://localhost:8080")),
+    );
+    for (endpoint, name) in context.spec.endpoints.iter().zip(methods) {
+        let _ = writeln!(out, "- `{}`: `{} {}`", name, endpoint.method, endpoint.path);
+    }
+    out
+}
+
+/// Unique method name per endpoint
+fn method_names(endpoints: &[EndpointSpec]) -> Vec<String> {
+    // Client members would clash with methods of the same name
+    let mut used = [
+        "constructor",
+        "request",
+        "baseUrl",
+        "headers",
+        "query",
+        "fetchImpl",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect::<HashSet<_>>();
+    endpoints
+        .iter()
+        .map(|endpoint| unique(identifier(&endpoint.name()), &mut used))
+        .collect()
+}
+
+/// Convert a name into a TypeScript identifier
+fn identifier(name: &str) -> String {
+    let mut identifier = to_camel_case(name);
+    if identifier.is_empty() {
+        identifier = "value".to_string();
+    }
+    if identifier.starts_with(|ch: char| ch.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    if RESERVED.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+    identifier
+}
+
+/// Type name of a named schema
+fn type_name(name: &str) -> String {
+    let mut type_name = to_pascal_case(name);
+    if type_name.is_empty() || type_name.starts_with(|ch: char| ch.is_ascii_digit()) {
+        type_name.insert(0, 'T');
+    }
+    type_name
+}
+
+/// Property name, quoted unless it is a valid identifier
+fn property_name(name: &str) -> String {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_' || ch == '$')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '$');
+    if valid {
+        name.to_string()
+    } else {
+        string(name)
+    }
+}
+
+fn ts_type(schema: &SchemaSpec) -> String {
+    match schema {
+        SchemaSpec::String => "string".to_string(),
+        SchemaSpec::Integer | SchemaSpec::Number => "number".to_string(),
+        SchemaSpec::Boolean => "boolean".to_string(),
+        SchemaSpec::Enum(values) if values.is_empty() => "string".to_string(),
+        SchemaSpec::Enum(values) => values
+            .iter()
+            .map(|value| string(value))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        SchemaSpec::Array(items) => match items.as_ref() {
+            SchemaSpec::Enum(values) if values.len() > 1 => format!("({})[]", ts_type(items)),
+            SchemaSpec::Nullable(_) => format!("({})[]", ts_type(items)),
+            _ => format!("{}[]", ts_type(items)),
+        },
+        SchemaSpec::Object(object) => inline_object(object),
+        SchemaSpec::Ref(name) => type_name(name),
+        SchemaSpec::Nullable(inner) => match inner.as_ref() {
+            SchemaSpec::Any | SchemaSpec::Nullable(_) => ts_type(inner),
+            _ => format!("{} | null", ts_type(inner)),
+        },
+        SchemaSpec::Any => "unknown".to_string(),
+    }
+}
+
+fn inline_object(object: &ObjectSchema) -> String {
+    if object.properties.is_empty() {
+        return "Record<string, unknown>".to_string();
+    }
+
+    let properties = object
+        .properties
+        .iter()
+        .map(|(name, schema)| {
+            format!(
+                "{}{}: {}",
+                property_name(name),
+                if object.required.contains(name) {
+                    ""
+                } else {
+                    "?"
+                },
+                ts_type(schema)
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("{{ {} }}", properties.join("; "))
+}
+
+/// TypeScript expression for a request path, substituting path parameters
+fn path_expression(path: &str, arguments: &[(&str, &str)]) -> String {
+    let mut expression = String::new();
+    let mut interpolated = false;
+    let mut rest = path;
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        let parameter = &rest[start + 1..end];
+        expression.push_str(&template_literal(&rest[..start]));
+        match arguments.iter().find(|(name, _)| *name == parameter) {
+            Some((_, argument)) => {
+                interpolated = true;
+                let _ = write!(expression, "${{encodeURIComponent(String({}))}}", argument);
+            }
+            None => expression.push_str(&template_literal(&rest[start..=end])),
+        }
+        rest = &rest[end + 1..];
+    }
+    expression.push_str(&template_literal(rest));
+
+    if interpolated {
+        format!("`{}`", expression)
+    } else {
+        string(path)
+    }
+}
+
+fn template_literal(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('`', "\\`")
+        .replace("${", "\\${")
+}
+
+/// Double-quoted string literal
+fn string(text: &str) -> String {
+    serde_json::to_string(text).unwrap_or_default()
+}
+
+/// Text safe to place inside a block comment
+fn comment(text: &str) -> String {
+    text.replace("*/", "*\\/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::{ApiSpec, ResponseSpec};
+    use crate::{GenerationOptions, TemplateEngine};
+    use std::collections::BTreeMap;
+
+    fn spec() -> ApiSpec {
+        let message = SchemaSpec::Object(ObjectSchema {
+            properties: BTreeMap::from([
+                ("id".to_string(), SchemaSpec::String),
+                (
+                    "state".to_string(),
+                    SchemaSpec::Enum(vec!["draft".to_string(), "sent".to_string()]),
+                ),
+                (
+                    "snippet".to_string(),
+                    SchemaSpec::Nullable(Box::new(SchemaSpec::String)),
+                ),
+                ("x-spam-score".to_string(), SchemaSpec::Number),
+                (
+                    "labels".to_string(),
+                    SchemaSpec::Array(Box::new(SchemaSpec::Ref("Label".to_string()))),
+                ),
+            ]),
+            required: vec!["id".to_string(), "state".to_string()],
+        });
+
+        ApiSpec {
+            title: "Mail".to_string(),
+            version: "1.0.0".to_string(),
+            endpoints: vec![
+                EndpointSpec {
+                    operation_id: Some("listMessages".to_string()),
+                    method: "GET".to_string(),
+                    path: "/mailboxes/{mailbox_id}/messages".to_string(),
+                    summary: Some("List messages".to_string()),
+                    description: Some("Messages are sorted by date */".to_string()),
+                    parameters: vec![
+                        ParameterSpec {
+                            name: "limit".to_string(),
+                            location: ParameterLocation::Query,
+                            required: false,
+                            schema: SchemaSpec::Integer,
+                            description: Some("Maximum number of messages".to_string()),
+                        },
+                        ParameterSpec {
+                            name: "mailbox_id".to_string(),
+                            location: ParameterLocation::Path,
+                            required: true,
+                            schema: SchemaSpec::String,
+                            description: None,
+                        },
+                    ],
+                    responses: vec![ResponseSpec {
+                        status: "200".to_string(),
+                        description: None,
+                        schema: Some(SchemaSpec::Array(Box::new(SchemaSpec::Ref(
+                            "Message".to_string(),
+                        )))),
+                    }],
+                    ..Default::default()
+                },
+                EndpointSpec {
+                    method: "POST".to_string(),
+                    path: "/messages".to_string(),
+                    request_body: Some(SchemaSpec::Ref("Message".to_string())),
+                    ..Default::default()
+                },
+            ],
+            schemas: BTreeMap::from([
+                ("Message".to_string(), message),
+                (
+                    "label".to_string(),
+                    SchemaSpec::Nullable(Box::new(SchemaSpec::Enum(vec![
+                        "inbox".to_string(),
+                        "spam".to_string(),
+                    ]))),
+                ),
+            ]),
+            security_schemes: BTreeMap::from([("bearer".to_string(), SecuritySchemeSpec::Bearer)]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_generate_typescript() {
+        let spec = spec();
+        let options = GenerationOptions {
+            language: Language::TypeScript,
+            ..Default::default()
+        };
+        let files = TypeScriptGenerator
+            .generate(&GenerationContext {
+                spec: &spec,
+                options: &options,
+                templates: &TemplateEngine::new(),
+            })
+            .unwrap();
+        let file = |path: &str| {
+            &files
+                .iter()
+                .find(|file| file.path == PathBuf::from(path))
+                .unwrap_or_else(|| panic!("missing {}", path))
+                .content
+        };
+
+        let models = file("src/models.ts");
+        for declaration in [
+            "export interface Message {\n",
+            "  id: string;\n",
+            "  labels?: Label[];\n",
+            "  snippet?: string | null;\n",
+            "  state: \"draft\" | \"sent\";\n",
+            "  \"x-spam-score\"?: number;\n",
+            "export type Label = \"inbox\" | \"spam\" | null;\n",
+        ] {
+            assert!(
+                models.contains(declaration),
+                "missing {:?} in\n{}",
+                declaration,
+                models
+            );
+        }
+
+        let client = file("src/client.ts");
+        for method in [
+            "  async listMessages(mailboxId: string, limit?: number): Promise<Message[]> {\n",
+            "    return this.request<Message[]>(\"GET\", `/mailboxes/${encodeURIComponent(String(mailboxId))}/messages`, { \"limit\": limit });\n",
+            "  async postMessages(body: Message): Promise<unknown> {\n",
+            "    return this.request<unknown>(\"POST\", \"/messages\", {}, {}, body);\n",
+            "   * Messages are sorted by date *\\/\n",
+            "   * @param limit Maximum number of messages\n",
+        ] {
+            assert!(client.contains(method), "missing {:?} in\n{}", method, client);
+        }
+        assert_eq!(client.matches("  async ").count(), 2);
+        assert!(client.contains("import type { Label, Message } from \"./models\";"));
+
+        let package: serde_json::Value = serde_json::from_str(file("package.json")).unwrap();
+        assert_eq!(package["name"], "stalwart-client");
+        assert_eq!(package["devDependencies"]["typescript"], TYPESCRIPT_VERSION);
+        let tsconfig: serde_json::Value = serde_json::from_str(file("tsconfig.json")).unwrap();
+        assert_eq!(tsconfig["compilerOptions"]["strict"], true);
+    }
+}
@@ -27,12 +27,14 @@
 //! ## Example
 //!
 //! ```rust,no_run
-//! use stalwart_sdk_generator::{SdkGenerator, GeneratorConfig, Language};
+//! use stalwart_sdk_generator::{ApiIntrospector, SdkGenerator, GeneratorConfig, Language};
+//! use std::path::Path;
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     let config = GeneratorConfig::default();
-//!     let generator = SdkGenerator::new(config).await?;
+//!     let spec = ApiIntrospector::from_openapi_file(Path::new("openapi.yaml"))?;
+//!     let generator = SdkGenerator::new(config).await?.with_api_spec(spec);
 //!
 //!     // Generate Python SDK
 //!     generator.generate_sdk(Language::Python, "output/python").await?;
@@ -59,7 +61,10 @@ pub use config::GeneratorConfig;
 pub use generator::SdkGenerator;
 pub use languages::{Language, LanguageGenerator, GenerationContext};
 pub use templates::{TemplateEngine, Template, TemplateContext};
-pub use introspection::{ApiIntrospector, ApiSpec, EndpointSpec};
+pub use introspection::{
+    ApiIntrospector, ApiSpec, EndpointSpec, ObjectSchema, ParameterLocation, ParameterSpec,
+    ResponseSpec, SchemaSpec, SecuritySchemeSpec,
+};
 pub use documentation::{DocumentationGenerator, DocFormat};
 pub use error::{GeneratorError, Result};
 
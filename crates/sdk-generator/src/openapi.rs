@@ -1,4 +1,708 @@
 //! OpenAPI integration
 
+use crate::error::{GeneratorError, Result};
+use crate::introspection::{
+    ApiSpec, EndpointSpec, ObjectSchema, ParameterLocation, ParameterSpec, ResponseSpec,
+    SchemaSpec, SecuritySchemeSpec,
+};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// HTTP methods an OpenAPI path item may describe
+const METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Path item fields that are not operations
+const PATH_ITEM_FIELDS: &[&str] = &["summary", "description", "parameters", "servers"];
+
+/// Maximum number of `$ref` indirections followed, guarding against cycles
+const MAX_REF_DEPTH: usize = 16;
+
 /// OpenAPI handler
 pub struct Openapi;
+
+impl Openapi {
+    /// Parse an OpenAPI 3.x document in JSON or YAML
+    ///
+    /// Features the API model cannot represent are skipped and reported in
+    /// `ApiSpec::warnings`.
+    pub fn parse(content: &str) -> Result<ApiSpec> {
+        let document: Value = if content.trim_start().starts_with('{') {
+            serde_json::from_str(content).map_err(|err| {
+                GeneratorError::Configuration(format!("Invalid OpenAPI document: {}", err))
+            })?
+        } else {
+            serde_yaml::from_str(content).map_err(|err| {
+                GeneratorError::Configuration(format!("Invalid OpenAPI document: {}", err))
+            })?
+        };
+
+        match string_field(Some(&document), "openapi") {
+            Some(version) if version.starts_with("3.") => {}
+            Some(version) => {
+                return Err(GeneratorError::Configuration(format!(
+                    "Unsupported OpenAPI version: {}",
+                    version
+                )))
+            }
+            None => {
+                return Err(GeneratorError::Configuration(
+                    "Missing `openapi` version field".to_string(),
+                ))
+            }
+        }
+
+        Ok(Parser {
+            document: &document,
+            warnings: Vec::new(),
+        }
+        .parse())
+    }
+}
+
+struct Parser<'a> {
+    document: &'a Value,
+    warnings: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(mut self) -> ApiSpec {
+        let info = self.document.get("info");
+        let mut spec = ApiSpec {
+            title: string_field(info, "title").unwrap_or_default(),
+            version: string_field(info, "version").unwrap_or_default(),
+            description: string_field(info, "description"),
+            base_url: self
+                .document
+                .get("servers")
+                .and_then(Value::as_array)
+                .and_then(|servers| servers.first())
+                .and_then(|server| string_field(Some(server), "url")),
+            ..Default::default()
+        };
+
+        let components = self.document.get("components");
+        if let Some(schemas) = object_field(components, "schemas") {
+            for (name, schema) in schemas {
+                let schema = self.schema(schema, &format!("schema `{}`", name));
+                spec.schemas.insert(name.clone(), schema);
+            }
+        }
+        if let Some(schemes) = object_field(components, "securitySchemes") {
+            for (name, scheme) in schemes {
+                if let Some(scheme) = self.security_scheme(name, scheme) {
+                    spec.security_schemes.insert(name.clone(), scheme);
+                }
+            }
+        }
+
+        let default_security = self.security(self.document.get("security"));
+        if let Some(paths) = object_field(Some(self.document), "paths") {
+            for (path, item) in paths {
+                let Some(item) = self.resolve(item, path) else {
+                    continue;
+                };
+                for (field, operation) in item.as_object().into_iter().flatten() {
+                    if METHODS.contains(&field.as_str()) {
+                        let endpoint =
+                            self.endpoint(path, field, item, operation, &default_security);
+                        spec.endpoints.push(endpoint);
+                    } else if !PATH_ITEM_FIELDS.contains(&field.as_str())
+                        && !field.starts_with("x-")
+                    {
+                        self.warn(format!("{}: `{}` is not supported", path, field));
+                    }
+                }
+            }
+        }
+
+        spec.warnings = self.warnings;
+        spec
+    }
+
+    fn endpoint(
+        &mut self,
+        path: &str,
+        method: &str,
+        item: &'a Value,
+        operation: &'a Value,
+        default_security: &[String],
+    ) -> EndpointSpec {
+        let method = method.to_ascii_uppercase();
+        let location = format!("{} {}", method, path);
+
+        // Operation parameters override path item ones with the same name and location
+        let mut parameters: Vec<ParameterSpec> = Vec::new();
+        for parameter in
+            array_field(Some(item), "parameters").chain(array_field(Some(operation), "parameters"))
+        {
+            if let Some(parameter) = self.parameter(parameter, &location) {
+                parameters.retain(|existing| {
+                    existing.name != parameter.name || existing.location != parameter.location
+                });
+                parameters.push(parameter);
+            }
+        }
+
+        let request_body = operation
+            .get("requestBody")
+            .and_then(|body| self.resolve(body, &location))
+            .and_then(|body| self.content_schema(body, &format!("{} request body", location)));
+
+        let mut responses = Vec::new();
+        for (status, response) in object_field(Some(operation), "responses")
+            .into_iter()
+            .flatten()
+        {
+            let Some(response) = self.resolve(response, &location) else {
+                continue;
+            };
+            responses.push(ResponseSpec {
+                status: status.clone(),
+                description: string_field(Some(response), "description"),
+                schema: self.content_schema(response, &format!("{} response {}", location, status)),
+            });
+        }
+
+        let security = match operation.get("security") {
+            Some(security) => self.security(Some(security)),
+            None => default_security.to_vec(),
+        };
+
+        EndpointSpec {
+            operation_id: string_field(Some(operation), "operationId"),
+            method,
+            path: path.to_string(),
+            summary: string_field(Some(operation), "summary"),
+            description: string_field(Some(operation), "description"),
+            parameters,
+            request_body,
+            responses,
+            security,
+        }
+    }
+
+    fn parameter(&mut self, parameter: &'a Value, location: &str) -> Option<ParameterSpec> {
+        let parameter = self.resolve(parameter, location)?;
+        let name = string_field(Some(parameter), "name")?;
+        let context = format!("{}: parameter `{}`", location, name);
+
+        let parameter_location = match parameter.get("in").and_then(Value::as_str) {
+            Some("path") => ParameterLocation::Path,
+            Some("query") => ParameterLocation::Query,
+            Some("header") => ParameterLocation::Header,
+            Some(other) => {
+                self.warn(format!("{} in `{}` is not supported", context, other));
+                return None;
+            }
+            None => {
+                self.warn(format!("{} has no location", context));
+                return None;
+            }
+        };
+
+        let schema = match parameter.get("schema") {
+            Some(schema) => self.schema(schema, &context),
+            None => {
+                self.warn(format!(
+                    "{} has no schema, treating it as a string",
+                    context
+                ));
+                SchemaSpec::String
+            }
+        };
+
+        Some(ParameterSpec {
+            name,
+            location: parameter_location,
+            required: parameter_location == ParameterLocation::Path
+                || parameter
+                    .get("required")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+            schema,
+            description: string_field(Some(parameter), "description"),
+        })
+    }
+
+    /// Schema of a request body or response, preferring JSON content
+    fn content_schema(&mut self, value: &Value, context: &str) -> Option<SchemaSpec> {
+        let content = value.get("content")?.as_object()?;
+        let (media_type, media) = content
+            .iter()
+            .find(|(media_type, _)| is_json(media_type))
+            .or_else(|| content.iter().next())?;
+        if !is_json(media_type) {
+            self.warn(format!(
+                "{}: `{}` content is not supported, treating it as any value",
+                context, media_type
+            ));
+            return Some(SchemaSpec::Any);
+        }
+
+        media
+            .get("schema")
+            .map(|schema| self.schema(schema, context))
+    }
+
+    fn schema(&mut self, schema: &Value, context: &str) -> SchemaSpec {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            return match reference.strip_prefix("#/components/schemas/") {
+                Some(name) => SchemaSpec::Ref(name.replace("~1", "/").replace("~0", "~")),
+                None => {
+                    self.warn(format!(
+                        "{}: reference `{}` is not supported",
+                        context, reference
+                    ));
+                    SchemaSpec::Any
+                }
+            };
+        }
+
+        // OpenAPI 3.0 marks nullable values with a flag, 3.1 with a `null` type
+        let mut nullable = schema
+            .get("nullable")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let schema_type = match schema.get("type") {
+            Some(Value::Array(types)) => {
+                let types = types
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .filter(|schema_type| {
+                        if *schema_type == "null" {
+                            nullable = true;
+                            false
+                        } else {
+                            true
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                if types.len() > 1 {
+                    self.warn(format!(
+                        "{}: multiple types are not supported, treating it as any value",
+                        context
+                    ));
+                    None
+                } else {
+                    types.first().copied()
+                }
+            }
+            Some(schema_type) => schema_type.as_str(),
+            None => None,
+        };
+
+        let spec = if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            let strings = values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            nullable |= values.iter().any(Value::is_null);
+            if strings.len() == values.iter().filter(|value| !value.is_null()).count() {
+                SchemaSpec::Enum(strings)
+            } else {
+                self.warn(format!("{}: non-string enums are not supported", context));
+                self.typed_schema(schema, schema_type, context)
+            }
+        } else if let Some(alternatives) = ["allOf", "oneOf", "anyOf"]
+            .into_iter()
+            .find_map(|keyword| Some((keyword, schema.get(keyword)?.as_array()?)))
+        {
+            self.composed_schema(alternatives, &mut nullable, context)
+        } else {
+            self.typed_schema(schema, schema_type, context)
+        };
+
+        if nullable {
+            SchemaSpec::Nullable(Box::new(spec))
+        } else {
+            spec
+        }
+    }
+
+    fn typed_schema(
+        &mut self,
+        schema: &Value,
+        schema_type: Option<&str>,
+        context: &str,
+    ) -> SchemaSpec {
+        match schema_type {
+            Some("string") => SchemaSpec::String,
+            Some("integer") => SchemaSpec::Integer,
+            Some("number") => SchemaSpec::Number,
+            Some("boolean") => SchemaSpec::Boolean,
+            Some("array") => SchemaSpec::Array(Box::new(match schema.get("items") {
+                Some(items) => self.schema(items, context),
+                None => SchemaSpec::Any,
+            })),
+            Some("object") => self.object_schema(schema, context),
+            None if schema.get("properties").is_some() => self.object_schema(schema, context),
+            None => SchemaSpec::Any,
+            Some(other) => {
+                self.warn(format!("{}: type `{}` is not supported", context, other));
+                SchemaSpec::Any
+            }
+        }
+    }
+
+    fn object_schema(&mut self, schema: &Value, context: &str) -> SchemaSpec {
+        let mut properties = BTreeMap::new();
+        for (name, property) in object_field(Some(schema), "properties")
+            .into_iter()
+            .flatten()
+        {
+            let property = self.schema(property, &format!("{}.{}", context, name));
+            properties.insert(name.clone(), property);
+        }
+
+        SchemaSpec::Object(ObjectSchema {
+            properties,
+            required: array_field(Some(schema), "required")
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+        })
+    }
+
+    /// Convert `allOf`, `oneOf` or `anyOf`, only a single alternative
+    /// besides `null` can be represented
+    fn composed_schema(
+        &mut self,
+        (keyword, alternatives): (&str, &Vec<Value>),
+        nullable: &mut bool,
+        context: &str,
+    ) -> SchemaSpec {
+        let alternatives = alternatives
+            .iter()
+            .filter(|alternative| {
+                let is_null = alternative.get("type").and_then(Value::as_str) == Some("null");
+                *nullable |= is_null;
+                !is_null
+            })
+            .collect::<Vec<_>>();
+
+        match alternatives.as_slice() {
+            [alternative] => self.schema(alternative, context),
+            _ => {
+                self.warn(format!(
+                    "{}: `{}` with several schemas is not supported, treating it as any value",
+                    context, keyword
+                ));
+                SchemaSpec::Any
+            }
+        }
+    }
+
+    fn security_scheme(&mut self, name: &str, scheme: &'a Value) -> Option<SecuritySchemeSpec> {
+        let context = format!("security scheme `{}`", name);
+        let scheme = self.resolve(scheme, &context)?;
+
+        match scheme.get("type").and_then(Value::as_str) {
+            Some("http") => match scheme
+                .get("scheme")
+                .and_then(Value::as_str)
+                .map(str::to_ascii_lowercase)
+                .as_deref()
+            {
+                Some("bearer") => Some(SecuritySchemeSpec::Bearer),
+                Some("basic") => Some(SecuritySchemeSpec::Basic),
+                other => {
+                    self.warn(format!(
+                        "{}: HTTP scheme `{}` is not supported",
+                        context,
+                        other.unwrap_or_default()
+                    ));
+                    None
+                }
+            },
+            Some("apiKey") => {
+                let location = match scheme.get("in").and_then(Value::as_str) {
+                    Some("header") => ParameterLocation::Header,
+                    Some("query") => ParameterLocation::Query,
+                    other => {
+                        self.warn(format!(
+                            "{}: API keys in `{}` are not supported",
+                            context,
+                            other.unwrap_or_default()
+                        ));
+                        return None;
+                    }
+                };
+                Some(SecuritySchemeSpec::ApiKey {
+                    name: string_field(Some(scheme), "name")?,
+                    location,
+                })
+            }
+            other => {
+                self.warn(format!(
+                    "{}: type `{}` is not supported",
+                    context,
+                    other.unwrap_or_default()
+                ));
+                None
+            }
+        }
+    }
+
+    /// Names of the security schemes in a list of security requirements
+    fn security(&self, requirements: Option<&Value>) -> Vec<String> {
+        let mut names = Vec::new();
+        for requirement in requirements.and_then(Value::as_array).into_iter().flatten() {
+            for name in requirement.as_object().into_iter().flat_map(Map::keys) {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        names
+    }
+
+    /// Follow local `$ref`s to the referenced value
+    fn resolve(&mut self, mut value: &'a Value, context: &str) -> Option<&'a Value> {
+        for _ in 0..MAX_REF_DEPTH {
+            let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+                return Some(value);
+            };
+            match reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.document.pointer(pointer))
+            {
+                Some(target) => value = target,
+                None => {
+                    self.warn(format!(
+                        "{}: reference `{}` cannot be resolved",
+                        context, reference
+                    ));
+                    return None;
+                }
+            }
+        }
+
+        self.warn(format!("{}: too many nested references", context));
+        None
+    }
+
+    fn warn(&mut self, warning: String) {
+        tracing::warn!("OpenAPI: {}", warning);
+        self.warnings.push(warning);
+    }
+}
+
+fn is_json(media_type: &str) -> bool {
+    let media_type = media_type.split(';').next().unwrap_or_default().trim();
+    media_type == "application/json" || media_type.ends_with("+json")
+}
+
+/// String field, YAML documents may also give versions as numbers
+fn string_field(value: Option<&Value>, field: &str) -> Option<String> {
+    match value?.get(field)? {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn object_field<'a>(value: Option<&'a Value>, field: &str) -> Option<&'a Map<String, Value>> {
+    value?.get(field)?.as_object()
+}
+
+fn array_field<'a>(value: Option<&'a Value>, field: &str) -> impl Iterator<Item = &'a Value> {
+    value
+        .and_then(|value| value.get(field))
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::introspection::*;
+
+    const SPEC: &str = r##"
+openapi: 3.0.3
+info:
+  title: Mail API
+  version: 1.2.0
+servers:
+  - url: https://mail.example.com/api
+security:
+  - bearerAuth: []
+paths:
+  /mailboxes/{mailboxId}/messages:
+    parameters:
+      - $ref: "#/components/parameters/MailboxId"
+    get:
+      operationId: listMessages
+      parameters:
+        - name: limit
+          in: query
+          schema:
+            type: integer
+        - name: session
+          in: cookie
+          schema:
+            type: string
+      responses:
+        "200":
+          description: Messages in the mailbox
+          content:
+            application/json:
+              schema:
+                type: array
+                items:
+                  $ref: "#/components/schemas/Message"
+    post:
+      security: []
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: "#/components/schemas/Message"
+      responses:
+        "201":
+          description: Created
+components:
+  parameters:
+    MailboxId:
+      name: mailboxId
+      in: path
+      required: true
+      schema:
+        type: string
+  schemas:
+    Message:
+      type: object
+      required: [id]
+      properties:
+        id:
+          type: string
+        flags:
+          type: array
+          items:
+            type: string
+            enum: [seen, flagged]
+        snippet:
+          type: string
+          nullable: true
+        body:
+          oneOf:
+            - type: string
+            - type: object
+  securitySchemes:
+    bearerAuth:
+      type: http
+      scheme: bearer
+    oauth:
+      type: oauth2
+"##;
+
+    #[test]
+    fn test_parse_openapi() {
+        let spec = ApiIntrospector::from_openapi_str(SPEC).unwrap();
+        assert_eq!(spec.title, "Mail API");
+        assert_eq!(spec.version, "1.2.0");
+        assert_eq!(
+            spec.base_url.as_deref(),
+            Some("https://mail.example.com/api")
+        );
+        assert_eq!(spec.endpoints.len(), 2);
+        assert_eq!(
+            spec.security_schemes.into_iter().collect::<Vec<_>>(),
+            vec![("bearerAuth".to_string(), SecuritySchemeSpec::Bearer)]
+        );
+
+        let list = &spec.endpoints[0];
+        assert_eq!(list.name(), "listMessages");
+        assert_eq!(list.method, "GET");
+        assert_eq!(
+            list.parameters,
+            vec![
+                ParameterSpec {
+                    name: "mailboxId".to_string(),
+                    location: ParameterLocation::Path,
+                    required: true,
+                    schema: SchemaSpec::String,
+                    description: None,
+                },
+                ParameterSpec {
+                    name: "limit".to_string(),
+                    location: ParameterLocation::Query,
+                    required: false,
+                    schema: SchemaSpec::Integer,
+                    description: None,
+                },
+            ]
+        );
+        assert_eq!(
+            list.success_schema(),
+            Some(&SchemaSpec::Array(Box::new(SchemaSpec::Ref(
+                "Message".to_string()
+            ))))
+        );
+        assert_eq!(list.security, vec!["bearerAuth"]);
+
+        let create = &spec.endpoints[1];
+        assert_eq!(create.method, "POST");
+        assert_eq!(create.parameters.len(), 1);
+        assert_eq!(
+            create.request_body,
+            Some(SchemaSpec::Ref("Message".to_string()))
+        );
+        assert!(create.security.is_empty());
+
+        let SchemaSpec::Object(message) = &spec.schemas["Message"] else {
+            panic!("expected an object schema");
+        };
+        assert_eq!(message.required, vec!["id"]);
+        assert_eq!(
+            message.properties["flags"],
+            SchemaSpec::Array(Box::new(SchemaSpec::Enum(vec![
+                "seen".to_string(),
+                "flagged".to_string()
+            ])))
+        );
+        assert_eq!(
+            message.properties["snippet"],
+            SchemaSpec::Nullable(Box::new(SchemaSpec::String))
+        );
+        assert_eq!(message.properties["body"], SchemaSpec::Any);
+
+        // Unsupported features are reported instead of failing the parse
+        assert_eq!(spec.warnings.len(), 3, "{:?}", spec.warnings);
+        assert!(spec
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("cookie")));
+        assert!(spec
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("oauth2")));
+        assert!(spec
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("oneOf")));
+    }
+
+    #[test]
+    fn test_parse_openapi_json() {
+        let spec = ApiIntrospector::from_openapi_str(
+            r#"{"openapi": "3.1.0", "info": {"title": "Status", "version": "1"},
+                "paths": {"/status": {"get": {"responses": {"200": {
+                    "description": "OK",
+                    "content": {"application/json": {"schema": {"type": ["string", "null"]}}}
+                }}}}}}"#,
+        )
+        .unwrap();
+        assert_eq!(spec.endpoints.len(), 1);
+        assert_eq!(spec.endpoints[0].name(), "get_status");
+        assert_eq!(
+            spec.endpoints[0].success_schema(),
+            Some(&SchemaSpec::Nullable(Box::new(SchemaSpec::String)))
+        );
+        assert!(spec.warnings.is_empty());
+
+        assert!(ApiIntrospector::from_openapi_str(r#"{"swagger": "2.0"}"#).is_err());
+    }
+}
@@ -1,17 +1,224 @@
 //! Template engine
+//!
+//! Generated files are built in, but each one can be replaced by a
+//! Handlebars template. When `GenerationOptions::custom_templates` names a
+//! directory, `<dir>/<language>/<name>.hbs` overrides the built-in file
+//! `<name>`, for example `python/client.py.hbs`. The names each language
+//! supports are listed by `LanguageGenerator::template_names`.
+//!
+//! Templates are rendered with the API specification under `spec` and the
+//! generation options under `options`.
+
+use crate::error::{GeneratorError, Result};
+use crate::{ApiSpec, GenerationOptions, Language};
+use handlebars::Handlebars;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Extension of template files
+pub const TEMPLATE_EXTENSION: &str = "hbs";
 
 /// Template engine
-pub struct TemplateEngine;
+pub struct TemplateEngine {
+    registry: Handlebars<'static>,
+    overrides: HashMap<String, Template>,
+}
 
-/// Template
-pub struct Template;
+/// User template overriding a built-in file
+#[derive(Debug, Clone)]
+pub struct Template {
+    /// Name of the file it replaces
+    pub name: String,
+    /// File the template was loaded from
+    pub path: PathBuf,
+}
 
 /// Template context
-pub struct TemplateContext;
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    data: serde_json::Value,
+}
 
 impl TemplateEngine {
     /// Create new template engine
     pub fn new() -> Self {
-        Self
+        let mut registry = Handlebars::new();
+        // Templates produce source code, not HTML
+        registry.register_escape_fn(handlebars::no_escape);
+
+        Self {
+            registry,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Load the templates in `dir` overriding the given built-in files
+    ///
+    /// Fails on the first template that does not parse, naming its file
+    /// and line.
+    pub fn load_overrides(
+        &mut self,
+        dir: &Path,
+        language: &Language,
+        names: &[&str],
+    ) -> Result<()> {
+        let dir = dir.join(language.to_string());
+        if !dir.is_dir() {
+            return Ok(());
+        }
+
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(&format!(".{}", TEMPLATE_EXTENSION)))
+            else {
+                continue;
+            };
+            if !names.contains(&name) {
+                tracing::warn!(
+                    "Ignoring template {}, {} SDKs have no file named {}",
+                    path.display(),
+                    language,
+                    name
+                );
+                continue;
+            }
+
+            let content = std::fs::read_to_string(&path)?;
+            self.registry
+                .register_template_string(name, content)
+                .map_err(|err| {
+                    let location = match err.pos() {
+                        Some((line, column)) => {
+                            format!("{}, line {}, column {}", path.display(), line, column)
+                        }
+                        None => path.display().to_string(),
+                    };
+                    GeneratorError::Template(format!("{}: {}", location, err.reason()))
+                })?;
+            self.overrides.insert(
+                name.to_string(),
+                Template {
+                    name: name.to_string(),
+                    path,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Get the user template overriding a built-in file
+    pub fn get_override(&self, name: &str) -> Option<&Template> {
+        self.overrides.get(name)
+    }
+
+    /// Render the user template overriding `name`, or the built-in content
+    pub fn render_or(
+        &self,
+        name: &str,
+        context: &TemplateContext,
+        builtin: impl FnOnce() -> String,
+    ) -> Result<String> {
+        let Some(template) = self.overrides.get(name) else {
+            return Ok(builtin());
+        };
+
+        self.registry.render(name, &context.data).map_err(|err| {
+            GeneratorError::Template(format!("{}: {}", template.path.display(), err))
+        })
+    }
+}
+
+impl Default for TemplateEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TemplateContext {
+    /// Create the context templates are rendered with
+    pub fn new(spec: &ApiSpec, options: &GenerationOptions) -> Self {
+        Self {
+            data: serde_json::json!({
+                "spec": spec,
+                "options": options,
+            }),
+        }
+    }
+
+    /// Add a value to the context
+    pub fn insert(&mut self, key: &str, value: impl serde::Serialize) {
+        if let (Some(data), Ok(value)) = (self.data.as_object_mut(), serde_json::to_value(value)) {
+            data.insert(key.to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine(templates: &[(&str, &str)]) -> (tempfile::TempDir, Result<TemplateEngine>) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("python")).unwrap();
+        for (name, content) in templates {
+            std::fs::write(dir.path().join("python").join(name), content).unwrap();
+        }
+
+        let mut engine = TemplateEngine::new();
+        let result = engine
+            .load_overrides(dir.path(), &Language::Python, &["README.md", "client.py"])
+            .map(|_| engine);
+        (dir, result)
+    }
+
+    #[test]
+    fn test_override_precedence() {
+        let (_dir, engine) = engine(&[
+            (
+                "README.md.hbs",
+                "# {{options.package_name}} for <{{spec.title}}>\n",
+            ),
+            ("setup.py.hbs", "unknown templates are ignored"),
+        ]);
+        let engine = engine.unwrap();
+        let context = TemplateContext::new(
+            &ApiSpec {
+                title: "Mail".to_string(),
+                ..Default::default()
+            },
+            &GenerationOptions::default(),
+        );
+
+        assert!(engine.get_override("README.md").is_some());
+        assert!(engine.get_override("setup.py").is_none());
+        assert_eq!(
+            engine
+                .render_or("README.md", &context, || "built-in".to_string())
+                .unwrap(),
+            "# stalwart-client for <Mail>\n"
+        );
+        assert_eq!(
+            engine
+                .render_or("client.py", &context, || "built-in".to_string())
+                .unwrap(),
+            "built-in"
+        );
+    }
+
+    #[test]
+    fn test_invalid_template() {
+        let (_dir, engine) = engine(&[(
+            "client.py.hbs",
+            "import requests\n\n{{#if spec.title}}class Client: pass{{/each}}\n",
+        )]);
+        let Err(GeneratorError::Template(message)) = engine else {
+            panic!("expected a template error");
+        };
+        assert!(message.contains("client.py.hbs"), "{}", message);
+        assert!(message.contains("line 3"), "{}", message);
     }
 }
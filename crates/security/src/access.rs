@@ -0,0 +1,168 @@
+//! Role-based access control
+//!
+//! Users hold one or more roles, and roles are granted actions on resources.
+//! A `*` resource or action in a grant matches anything. Users without an
+//! explicit role assignment get the configured default role, and the
+//! configured admin roles are allowed everything.
+
+use crate::{AuthorizationConfig, Result};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+/// Evaluates permission checks against role grants
+pub struct AccessControl {
+    config: AuthorizationConfig,
+    user_roles: RwLock<HashMap<String, HashSet<String>>>,
+    grants: RwLock<HashMap<String, HashSet<(String, String)>>>,
+}
+
+impl AccessControl {
+    /// Create a new access control manager
+    pub async fn new(config: &AuthorizationConfig) -> Result<Self> {
+        info!(
+            "Initializing access control (RBAC {})",
+            if config.rbac_enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        );
+
+        Ok(Self {
+            config: config.clone(),
+            user_roles: RwLock::new(HashMap::new()),
+            grants: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Give `user_id` the role `role`
+    pub async fn assign_role(&self, user_id: &str, role: &str) {
+        self.user_roles
+            .write()
+            .await
+            .entry(user_id.to_string())
+            .or_default()
+            .insert(role.to_string());
+    }
+
+    /// Allow holders of `role` to perform `action` on `resource`
+    pub async fn grant(&self, role: &str, resource: &str, action: &str) {
+        self.grants
+            .write()
+            .await
+            .entry(role.to_string())
+            .or_default()
+            .insert((resource.to_string(), action.to_string()));
+    }
+
+    /// Whether `user_id` may perform `action` on `resource`
+    pub async fn check_permission(
+        &self,
+        user_id: &str,
+        resource: &str,
+        action: &str,
+    ) -> Result<bool> {
+        if !self.config.rbac_enabled {
+            return Ok(true);
+        }
+
+        let roles = self
+            .user_roles
+            .read()
+            .await
+            .get(user_id)
+            .cloned()
+            .unwrap_or_else(|| HashSet::from([self.config.default_role.clone()]));
+        if roles
+            .iter()
+            .any(|role| self.config.admin_roles.contains(role))
+        {
+            return Ok(true);
+        }
+
+        let grants = self.grants.read().await;
+        let granted = roles
+            .iter()
+            .filter_map(|role| grants.get(role))
+            .flatten()
+            .any(|(granted_resource, granted_action)| {
+                matches(granted_resource, resource) && matches(granted_action, action)
+            });
+
+        debug!(
+            "Access to {} {} for {}: {}",
+            action,
+            resource,
+            user_id,
+            if granted { "granted" } else { "denied" }
+        );
+        Ok(granted)
+    }
+
+    /// Short human readable status
+    pub async fn get_status(&self) -> Result<String> {
+        Ok(format!(
+            "active (roles: {})",
+            self.grants.read().await.len()
+        ))
+    }
+
+    pub async fn shutdown(&self) -> Result<()> {
+        info!("Access control shut down");
+        Ok(())
+    }
+}
+
+fn matches(pattern: &str, value: &str) -> bool {
+    pattern == "*" || pattern == value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SecurityConfig;
+
+    #[tokio::test]
+    async fn test_role_grants() {
+        let access = AccessControl::new(&SecurityConfig::default().authorization)
+            .await
+            .unwrap();
+        access.grant("user", "mailbox", "read").await;
+        access.grant("auditor", "*", "read").await;
+        access.assign_role("bob", "auditor").await;
+        access.assign_role("root", "admin").await;
+
+        // Users without an assignment get the default role
+        assert!(
+            access
+                .check_permission("alice", "mailbox", "read")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !access
+                .check_permission("alice", "mailbox", "delete")
+                .await
+                .unwrap()
+        );
+        assert!(
+            access
+                .check_permission("bob", "logs", "read")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !access
+                .check_permission("bob", "mailbox", "delete")
+                .await
+                .unwrap()
+        );
+        assert!(
+            access
+                .check_permission("root", "mailbox", "delete")
+                .await
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Security audit log
+//!
+//! Events are written to the tracing log and kept in a bounded in-memory
+//! buffer. The background sweep drains the buffer and, with real-time alerts
+//! enabled, raises a warning for every security violation it finds.
+
+use crate::{AuditConfig, Result, SecurityEvent};
+use std::collections::VecDeque;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Events kept between two sweeps; older events are dropped first
+const MAX_PENDING_EVENTS: usize = 10_000;
+
+/// Records security events
+pub struct AuditLogger {
+    config: AuditConfig,
+    pending: Mutex<VecDeque<SecurityEvent>>,
+}
+
+impl AuditLogger {
+    /// Create a new audit logger
+    pub async fn new(config: &AuditConfig) -> Result<Self> {
+        info!(
+            "Initializing audit logger (retention {} days)",
+            config.retention_days
+        );
+
+        Ok(Self {
+            config: config.clone(),
+            pending: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    /// Record a security event
+    pub async fn log_event(&self, event: SecurityEvent) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        info!(target: "security::audit", event = ?event, "Security event");
+        let mut pending = self.pending.lock().await;
+        if pending.len() == MAX_PENDING_EVENTS {
+            pending.pop_front();
+        }
+        pending.push_back(event);
+        Ok(())
+    }
+
+    /// Drain recorded events, alerting on security violations
+    pub async fn process_security_events(&self) -> Result<()> {
+        let events = std::mem::take(&mut *self.pending.lock().await);
+        if !self.config.real_time_alerts {
+            return Ok(());
+        }
+
+        for event in events {
+            if let SecurityEvent::SecurityViolation {
+                violation_type,
+                severity,
+                source_ip,
+                ..
+            } = event
+            {
+                warn!(
+                    "Security alert: {} ({}) from {}",
+                    violation_type, severity, source_ip
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn shutdown(&self) -> Result<()> {
+        self.process_security_events().await?;
+        info!("Audit logger shut down");
+        Ok(())
+    }
+}
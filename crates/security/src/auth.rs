@@ -0,0 +1,385 @@
+//! Authentication and session management
+//!
+//! Password verification is timing-safe: digests are compared with
+//! `subtle::ConstantTimeEq`, and lookups for unknown users still run a full
+//! hash verification against a dummy credential so that response times do
+//! not reveal whether an account exists.
+
+use crate::{AuthenticationConfig, Result, SecurityError};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use subtle::ConstantTimeEq;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+/// PBKDF2 rounds used for password hashes
+pub const PASSWORD_HASH_ROUNDS: u32 = 10_000;
+
+/// Usernames with failed logins tracked at once; the stalest entry is
+/// evicted when a new username would exceed this
+pub const MAX_TRACKED_USERNAMES: usize = 10_000;
+
+const SALT_LEN: usize = 16;
+const DIGEST_LEN: usize = 32;
+
+/// Authentication token issued after a successful login
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthToken {
+    pub token: String,
+    pub user_id: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Salted password hash
+#[derive(Debug, Clone)]
+pub struct PasswordHash {
+    salt: [u8; SALT_LEN],
+    digest: [u8; DIGEST_LEN],
+}
+
+impl PasswordHash {
+    /// Hash a password with a fresh random salt
+    pub fn new(password: &str) -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Self {
+            digest: derive(password, &salt),
+            salt,
+        }
+    }
+
+    /// Verify a password against this hash in constant time
+    pub fn verify(&self, password: &str) -> bool {
+        constant_time_eq(&derive(password, &self.salt), &self.digest)
+    }
+}
+
+/// Compare two byte strings without exiting early on the first mismatch
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Key derivations run on this thread, to check both failure paths do the same work
+    static DERIVATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+fn derive(password: &str, salt: &[u8]) -> [u8; DIGEST_LEN] {
+    #[cfg(test)]
+    DERIVATIONS.with(|count| count.set(count.get() + 1));
+
+    let mut digest = [0u8; DIGEST_LEN];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, PASSWORD_HASH_ROUNDS, &mut digest);
+    digest
+}
+
+#[derive(Debug)]
+struct LoginAttempts {
+    failures: u32,
+    last_failure: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+impl LoginAttempts {
+    /// Failures are forgotten once they, and any lockout, are older than the
+    /// lockout duration
+    fn is_expired(&self, now: DateTime<Utc>, lockout: ChronoDuration) -> bool {
+        self.locked_until.is_none_or(|until| until <= now) && self.last_failure + lockout <= now
+    }
+}
+
+/// Authentication manager
+pub struct AuthManager {
+    config: AuthenticationConfig,
+    users: RwLock<HashMap<String, PasswordHash>>,
+    sessions: RwLock<HashMap<String, AuthToken>>,
+    attempts: RwLock<HashMap<String, LoginAttempts>>,
+    dummy_hash: PasswordHash,
+}
+
+impl AuthManager {
+    /// Create a new auth manager
+    pub async fn new(config: &AuthenticationConfig) -> Result<Self> {
+        Ok(Self {
+            config: config.clone(),
+            users: RwLock::new(HashMap::new()),
+            sessions: RwLock::new(HashMap::new()),
+            attempts: RwLock::new(HashMap::new()),
+            dummy_hash: PasswordHash::new("a3mailer-dummy-credential"),
+        })
+    }
+
+    /// Register or replace a user's password
+    pub async fn set_password(&self, username: &str, password: &str) -> Result<()> {
+        let hash = PasswordHash::new(password);
+        self.users.write().await.insert(username.to_string(), hash);
+        Ok(())
+    }
+
+    /// Authenticate a user and open a session
+    pub async fn authenticate(&self, username: &str, password: &str) -> Result<AuthToken> {
+        if self.is_locked(username).await {
+            return Err(SecurityError::AccountLocked(username.to_string()));
+        }
+
+        // Always run a full verification, falling back to the dummy hash for
+        // unknown users so both failure paths cost the same
+        let hash = self.users.read().await.get(username).cloned();
+        let user_exists = hash.is_some();
+        let password_matches = hash.as_ref().unwrap_or(&self.dummy_hash).verify(password);
+
+        if !(user_exists & password_matches) {
+            self.record_failure(username).await;
+            debug!("Authentication failed for {}", username);
+            return Err(SecurityError::AuthenticationFailed(
+                "invalid username or password".to_string(),
+            ));
+        }
+
+        self.attempts.write().await.remove(username);
+
+        let issued_at = Utc::now();
+        let token = AuthToken {
+            token: new_session_token(),
+            user_id: username.to_string(),
+            issued_at,
+            expires_at: issued_at
+                + ChronoDuration::minutes(self.config.session_timeout_minutes as i64),
+        };
+        self.sessions
+            .write()
+            .await
+            .insert(token.token.clone(), token.clone());
+
+        Ok(token)
+    }
+
+    /// Look up an active session
+    pub async fn validate_session(&self, token: &str) -> Option<AuthToken> {
+        self.sessions
+            .read()
+            .await
+            .get(token)
+            .filter(|session| session.expires_at > Utc::now())
+            .cloned()
+    }
+
+    /// Remove expired sessions and failed login records
+    pub async fn cleanup_expired_sessions(&self) -> Result<()> {
+        let now = Utc::now();
+        let mut sessions = self.sessions.write().await;
+        let before = sessions.len();
+        sessions.retain(|_, session| session.expires_at > now);
+
+        let removed = before - sessions.len();
+        if removed > 0 {
+            debug!("Removed {} expired sessions", removed);
+        }
+        drop(sessions);
+
+        let lockout = self.lockout_duration();
+        self.attempts
+            .write()
+            .await
+            .retain(|_, attempts| !attempts.is_expired(now, lockout));
+        Ok(())
+    }
+
+    /// Get auth manager status
+    pub async fn get_status(&self) -> Result<String> {
+        Ok(format!(
+            "active (sessions: {})",
+            self.sessions.read().await.len()
+        ))
+    }
+
+    /// Shutdown auth manager
+    pub async fn shutdown(&self) -> Result<()> {
+        info!("Shutting down auth manager");
+        self.sessions.write().await.clear();
+        Ok(())
+    }
+
+    async fn is_locked(&self, username: &str) -> bool {
+        self.attempts
+            .read()
+            .await
+            .get(username)
+            .and_then(|attempts| attempts.locked_until)
+            .is_some_and(|until| until > Utc::now())
+    }
+
+    async fn record_failure(&self, username: &str) {
+        if self.config.max_login_attempts == 0 {
+            return;
+        }
+
+        let now = Utc::now();
+        let lockout = self.lockout_duration();
+        let mut attempts = self.attempts.write().await;
+        if !attempts.contains_key(username) && attempts.len() >= MAX_TRACKED_USERNAMES {
+            attempts.retain(|_, attempts| !attempts.is_expired(now, lockout));
+            if attempts.len() >= MAX_TRACKED_USERNAMES {
+                if let Some(stalest) = attempts
+                    .iter()
+                    .min_by_key(|(_, attempts)| (attempts.locked_until, attempts.last_failure))
+                    .map(|(username, _)| username.clone())
+                {
+                    attempts.remove(&stalest);
+                }
+            }
+        }
+
+        let entry = attempts
+            .entry(username.to_string())
+            .or_insert_with(|| LoginAttempts {
+                failures: 0,
+                last_failure: now,
+                locked_until: None,
+            });
+        if entry.is_expired(now, lockout) {
+            entry.failures = 0;
+            entry.locked_until = None;
+        }
+        entry.failures += 1;
+        entry.last_failure = now;
+        if entry.failures >= self.config.max_login_attempts {
+            warn!(
+                "Locking account {} after {} failed logins",
+                username, entry.failures
+            );
+            entry.failures = 0;
+            entry.locked_until = Some(now + lockout);
+        }
+    }
+
+    fn lockout_duration(&self) -> ChronoDuration {
+        ChronoDuration::minutes(self.config.lockout_duration_minutes as i64)
+    }
+}
+
+fn new_session_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SecurityConfig;
+
+    async fn manager() -> AuthManager {
+        let mut config = SecurityConfig::default().authentication;
+        config.max_login_attempts = 0;
+        let manager = AuthManager::new(&config).await.unwrap();
+        manager
+            .set_password("alice", "correct horse")
+            .await
+            .unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret-digest", b"secret-digest"));
+        assert!(!constant_time_eq(b"secret-digest", b"Secret-digest"));
+        assert!(!constant_time_eq(b"secret-digest", b"secret-digesT"));
+        assert!(!constant_time_eq(b"secret-digest", b"secret-diges"));
+        assert!(!constant_time_eq(b"", b"x"));
+    }
+
+    #[test]
+    fn test_password_hash_verify() {
+        let hash = PasswordHash::new("correct horse");
+        assert!(hash.verify("correct horse"));
+        assert!(!hash.verify("correct horsE"));
+        assert!(!hash.verify(""));
+    }
+
+    #[tokio::test]
+    async fn test_authenticate() {
+        let manager = manager().await;
+
+        let token = manager
+            .authenticate("alice", "correct horse")
+            .await
+            .unwrap();
+        assert_eq!(token.user_id, "alice");
+        assert!(manager.validate_session(&token.token).await.is_some());
+
+        assert!(matches!(
+            manager.authenticate("alice", "wrong").await,
+            Err(SecurityError::AuthenticationFailed(_))
+        ));
+        assert!(matches!(
+            manager.authenticate("mallory", "wrong").await,
+            Err(SecurityError::AuthenticationFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_user_runs_full_verification() {
+        let manager = manager().await;
+        let derivations = |username: &'static str| {
+            let manager = &manager;
+            async move {
+                DERIVATIONS.with(|count| count.set(0));
+                let result = manager.authenticate(username, "wrong password").await;
+                assert!(matches!(
+                    result,
+                    Err(SecurityError::AuthenticationFailed(_))
+                ));
+                DERIVATIONS.with(|count| count.get())
+            }
+        };
+
+        // Unknown users are checked against the dummy hash, so both failure
+        // paths derive exactly one key
+        assert_eq!(derivations("mallory").await, 1);
+        assert_eq!(derivations("alice").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_lockout() {
+        let mut config = SecurityConfig::default().authentication;
+        config.max_login_attempts = 2;
+        let manager = AuthManager::new(&config).await.unwrap();
+        manager
+            .set_password("alice", "correct horse")
+            .await
+            .unwrap();
+
+        let _ = manager.authenticate("alice", "wrong").await;
+        let _ = manager.authenticate("alice", "wrong").await;
+        assert!(matches!(
+            manager.authenticate("alice", "correct horse").await,
+            Err(SecurityError::AccountLocked(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_logins_are_bounded() {
+        let mut config = SecurityConfig::default().authentication;
+        config.max_login_attempts = 3;
+        let manager = AuthManager::new(&config).await.unwrap();
+
+        for user in 0..MAX_TRACKED_USERNAMES + 10 {
+            manager.record_failure(&format!("user{user}")).await;
+        }
+        assert_eq!(manager.attempts.read().await.len(), MAX_TRACKED_USERNAMES);
+
+        // Records older than the lockout duration are swept
+        let expired = Utc::now() - ChronoDuration::minutes(config.lockout_duration_minutes as i64);
+        for attempts in manager.attempts.write().await.values_mut() {
+            attempts.last_failure = expired;
+        }
+        manager.cleanup_expired_sessions().await.unwrap();
+        assert!(manager.attempts.read().await.is_empty());
+    }
+}
@@ -0,0 +1,43 @@
+//! Security error types and handling
+
+use std::fmt;
+
+/// Security result type
+pub type Result<T> = std::result::Result<T, SecurityError>;
+
+/// Security errors
+#[derive(Debug)]
+pub enum SecurityError {
+    /// Configuration errors
+    ConfigError(String),
+
+    /// Authentication errors
+    AuthenticationFailed(String),
+
+    /// Account is temporarily locked
+    AccountLocked(String),
+
+    /// Key management errors
+    KeyError(String),
+
+    /// Encryption/decryption errors
+    CryptoError(String),
+
+    /// Generic errors
+    Other(String),
+}
+
+impl fmt::Display for SecurityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecurityError::ConfigError(msg) => write!(f, "Configuration error: {}", msg),
+            SecurityError::AuthenticationFailed(msg) => write!(f, "Authentication failed: {}", msg),
+            SecurityError::AccountLocked(msg) => write!(f, "Account locked: {}", msg),
+            SecurityError::KeyError(msg) => write!(f, "Key management error: {}", msg),
+            SecurityError::CryptoError(msg) => write!(f, "Crypto error: {}", msg),
+            SecurityError::Other(msg) => write!(f, "Security error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SecurityError {}
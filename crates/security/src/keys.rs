@@ -0,0 +1,342 @@
+//! Key lifecycle management
+//!
+//! Keys are grouped by type (for example `encryption` or `backup`). Each type
+//! has exactly one active key used for new data; rotating a type retires the
+//! active key, which stays available for decrypting existing data until it is
+//! revoked. Revoked keys can no longer be retrieved at all.
+//!
+//! The key manager also serves backup encryption keys: it implements the
+//! backup-restore [`KeyProvider`] over keys of type [`BACKUP_KEY_TYPE`].
+
+use crate::{KeyManagementConfig, Result, SecurityError};
+use backup_restore::{BackupError, KeyProvider};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+/// Size of generated key material in bytes (256 bits)
+pub const KEY_SIZE: usize = 32;
+
+/// Key type backups are encrypted with
+pub const BACKUP_KEY_TYPE: &str = "backup";
+
+/// Keys older than this are replaced by `check_and_rotate_keys`
+const MAX_KEY_AGE_DAYS: i64 = 90;
+
+/// Lifecycle state of a managed key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyStatus {
+    /// Used to protect new data
+    Active,
+    /// Superseded by a newer key but still usable for existing data
+    Retired,
+    /// Withdrawn, must not be used for anything
+    Revoked,
+}
+
+/// A key held by the key manager
+#[derive(Clone)]
+pub struct ManagedKey {
+    pub id: String,
+    pub key_type: String,
+    pub status: KeyStatus,
+    pub created_at: DateTime<Utc>,
+    material: [u8; KEY_SIZE],
+}
+
+impl ManagedKey {
+    fn generate(key_type: &str) -> Self {
+        let mut material = [0u8; KEY_SIZE];
+        rand::thread_rng().fill_bytes(&mut material);
+        let mut suffix = [0u8; 8];
+        rand::thread_rng().fill_bytes(&mut suffix);
+
+        Self {
+            id: format!(
+                "{}-{}",
+                key_type,
+                suffix
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<String>()
+            ),
+            key_type: key_type.to_string(),
+            status: KeyStatus::Active,
+            created_at: Utc::now(),
+            material,
+        }
+    }
+
+    /// Raw key material
+    pub fn material(&self) -> &[u8; KEY_SIZE] {
+        &self.material
+    }
+}
+
+impl std::fmt::Debug for ManagedKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManagedKey")
+            .field("id", &self.id)
+            .field("key_type", &self.key_type)
+            .field("status", &self.status)
+            .field("created_at", &self.created_at)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Generates, rotates and revokes keys
+#[derive(Debug)]
+pub struct KeyManager {
+    config: KeyManagementConfig,
+    keys: RwLock<HashMap<String, ManagedKey>>,
+}
+
+impl KeyManager {
+    /// Create a new key manager
+    pub async fn new(config: &KeyManagementConfig) -> Result<Self> {
+        info!(
+            "Initializing key manager with {} key store",
+            config.key_store_type
+        );
+
+        Ok(Self {
+            config: config.clone(),
+            keys: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Generate a new active key of `key_type`, retiring the previous one
+    pub async fn generate_key(&self, key_type: &str) -> Result<String> {
+        let key = ManagedKey::generate(key_type);
+        let key_id = key.id.clone();
+
+        let mut keys = self.keys.write().await;
+        for existing in keys.values_mut() {
+            if existing.key_type == key_type && existing.status == KeyStatus::Active {
+                existing.status = KeyStatus::Retired;
+            }
+        }
+        keys.insert(key_id.clone(), key);
+        self.prune_retired(&mut keys, key_type);
+
+        debug!("Generated key {}", key_id);
+        Ok(key_id)
+    }
+
+    /// Active key of `key_type`, generating one on first use
+    pub async fn active_key(&self, key_type: &str) -> Result<ManagedKey> {
+        if let Some(key) = self
+            .keys
+            .read()
+            .await
+            .values()
+            .find(|key| key.key_type == key_type && key.status == KeyStatus::Active)
+        {
+            return Ok(key.clone());
+        }
+
+        let key_id = self.generate_key(key_type).await?;
+        self.get_key(&key_id).await
+    }
+
+    /// Look up a key by ID; revoked keys are refused
+    pub async fn get_key(&self, key_id: &str) -> Result<ManagedKey> {
+        match self.keys.read().await.get(key_id) {
+            Some(key) if key.status == KeyStatus::Revoked => Err(SecurityError::KeyError(format!(
+                "key {} has been revoked",
+                key_id
+            ))),
+            Some(key) => Ok(key.clone()),
+            None => Err(SecurityError::KeyError(format!("key {} not found", key_id))),
+        }
+    }
+
+    /// Revoke a key so it can no longer be retrieved
+    pub async fn revoke_key(&self, key_id: &str) -> Result<()> {
+        let mut keys = self.keys.write().await;
+        let key = keys
+            .get_mut(key_id)
+            .ok_or_else(|| SecurityError::KeyError(format!("key {} not found", key_id)))?;
+
+        warn!("Revoking key {}", key_id);
+        key.status = KeyStatus::Revoked;
+        Ok(())
+    }
+
+    /// Replace every active key with a freshly generated one
+    ///
+    /// Returns the IDs of the new keys.
+    pub async fn rotate_keys(&self) -> Result<Vec<String>> {
+        let mut key_types = self
+            .keys
+            .read()
+            .await
+            .values()
+            .filter(|key| key.status == KeyStatus::Active)
+            .map(|key| key.key_type.clone())
+            .collect::<Vec<_>>();
+        key_types.sort();
+        key_types.dedup();
+
+        let mut rotated = Vec::with_capacity(key_types.len());
+        for key_type in key_types {
+            rotated.push(self.generate_key(&key_type).await?);
+        }
+        Ok(rotated)
+    }
+
+    /// Rotate active keys that exceeded their maximum age
+    pub async fn check_and_rotate_keys(&self) -> Result<Vec<String>> {
+        if !self.config.auto_rotation_enabled {
+            return Ok(Vec::new());
+        }
+
+        let cutoff = Utc::now() - ChronoDuration::days(MAX_KEY_AGE_DAYS);
+        let expired = self
+            .keys
+            .read()
+            .await
+            .values()
+            .filter(|key| key.status == KeyStatus::Active && key.created_at < cutoff)
+            .map(|key| key.key_type.clone())
+            .collect::<Vec<_>>();
+
+        let mut rotated = Vec::with_capacity(expired.len());
+        for key_type in expired {
+            rotated.push(self.generate_key(&key_type).await?);
+        }
+        Ok(rotated)
+    }
+
+    /// Short human readable status
+    pub async fn get_status(&self) -> Result<String> {
+        let keys = self.keys.read().await;
+        let count = |status| keys.values().filter(|key| key.status == status).count();
+
+        Ok(format!(
+            "{} active, {} retired, {} revoked",
+            count(KeyStatus::Active),
+            count(KeyStatus::Retired),
+            count(KeyStatus::Revoked)
+        ))
+    }
+
+    pub async fn shutdown(&self) -> Result<()> {
+        info!("Key manager shut down");
+        Ok(())
+    }
+
+    /// Keep at most `backup_keys_count` retired keys of `key_type`, dropping the oldest
+    fn prune_retired(&self, keys: &mut HashMap<String, ManagedKey>, key_type: &str) {
+        let mut retired = keys
+            .values()
+            .filter(|key| key.key_type == key_type && key.status == KeyStatus::Retired)
+            .map(|key| (key.created_at, key.id.clone()))
+            .collect::<Vec<_>>();
+        let limit = self.config.backup_keys_count as usize;
+        if retired.len() <= limit {
+            return;
+        }
+
+        retired.sort();
+        for (_, key_id) in &retired[..retired.len() - limit] {
+            debug!("Dropping retired key {}", key_id);
+            keys.remove(key_id);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl KeyProvider for KeyManager {
+    async fn current_key(&self) -> backup_restore::Result<(String, Vec<u8>)> {
+        let key =
+            self.active_key(BACKUP_KEY_TYPE)
+                .await
+                .map_err(|e| BackupError::KeyUnavailable {
+                    key_id: BACKUP_KEY_TYPE.to_string(),
+                    reason: e.to_string(),
+                })?;
+        Ok((key.id, key.material.to_vec()))
+    }
+
+    async fn key(&self, key_id: &str) -> backup_restore::Result<Vec<u8>> {
+        let unavailable = |reason: String| BackupError::KeyUnavailable {
+            key_id: key_id.to_string(),
+            reason,
+        };
+        let key = self
+            .get_key(key_id)
+            .await
+            .map_err(|e| unavailable(e.to_string()))?;
+        if key.key_type != BACKUP_KEY_TYPE {
+            return Err(unavailable(format!("not a {} key", BACKUP_KEY_TYPE)));
+        }
+        Ok(key.material.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SecurityConfig;
+
+    async fn manager() -> KeyManager {
+        KeyManager::new(&SecurityConfig::default().key_management)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rotation_keeps_retired_keys_readable() {
+        let manager = manager().await;
+        let first = manager.active_key("backup").await.unwrap();
+        assert_eq!(manager.active_key("backup").await.unwrap().id, first.id);
+
+        let rotated = manager.rotate_keys().await.unwrap();
+        assert_eq!(rotated.len(), 1);
+        let second = manager.active_key("backup").await.unwrap();
+        assert_ne!(second.id, first.id);
+        assert_ne!(second.material(), first.material());
+
+        let retired = manager.get_key(&first.id).await.unwrap();
+        assert_eq!(retired.status, KeyStatus::Retired);
+        assert_eq!(retired.material(), first.material());
+    }
+
+    #[tokio::test]
+    async fn test_revoked_key_is_refused() {
+        let manager = manager().await;
+        let key = manager.active_key("backup").await.unwrap();
+        manager.revoke_key(&key.id).await.unwrap();
+
+        assert!(matches!(
+            manager.get_key(&key.id).await,
+            Err(SecurityError::KeyError(_))
+        ));
+        // A revoked active key is replaced on next use
+        assert_ne!(manager.active_key("backup").await.unwrap().id, key.id);
+    }
+
+    #[tokio::test]
+    async fn test_serves_backup_keys() {
+        let manager = manager().await;
+        let (key_id, material) = manager.current_key().await.unwrap();
+        assert_eq!(KeyProvider::key(&manager, &key_id).await.unwrap(), material);
+
+        // Keys of other types are never handed out for backups
+        let other = manager.active_key("encryption").await.unwrap();
+        assert!(matches!(
+            KeyProvider::key(&manager, &other.id).await,
+            Err(BackupError::KeyUnavailable { .. })
+        ));
+
+        manager.revoke_key(&key_id).await.unwrap();
+        assert!(matches!(
+            KeyProvider::key(&manager, &key_id).await,
+            Err(BackupError::KeyUnavailable { key_id: ref id, .. }) if *id == key_id
+        ));
+    }
+}
@@ -42,11 +42,10 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use tokio::sync::RwLock;
-use tracing::{info, warn, error, debug};
+use std::time::Duration;
+use tokio::sync::{RwLock, broadcast};
+use tracing::{info, warn, error};
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
 
 pub mod keys;
 pub mod crypto;
@@ -210,6 +209,7 @@ pub struct SecurityManager {
     access_control: Arc<access::AccessControl>,
     audit_logger: Arc<audit::AuditLogger>,
     metrics: Arc<RwLock<SecurityMetrics>>,
+    events: broadcast::Sender<SecurityEvent>,
 }
 
 impl SecurityManager {
@@ -244,6 +244,7 @@ impl SecurityManager {
             access_control,
             audit_logger,
             metrics,
+            events: broadcast::channel(1024).0,
         };
 
         // Start background security tasks
@@ -297,20 +298,21 @@ impl SecurityManager {
 
     /// Authenticate user
     pub async fn authenticate(&self, username: &str, password: &str, ip_address: &str, user_agent: &str) -> Result<auth::AuthToken> {
-        let start_time = std::time::Instant::now();
-        
         let result = self.auth_manager.authenticate(username, password).await;
         
         let success = result.is_ok();
         
         // Log authentication event
-        self.audit_logger.log_event(SecurityEvent::Authentication {
+        let event = SecurityEvent::Authentication {
             user_id: username.to_string(),
             success,
             method: "password".to_string(),
             ip_address: ip_address.to_string(),
             user_agent: user_agent.to_string(),
-        }).await?;
+        };
+        self.audit_logger.log_event(event.clone()).await?;
+        // Sending only fails when nobody is subscribed
+        let _ = self.events.send(event);
 
         // Update metrics
         {
@@ -325,6 +327,15 @@ impl SecurityManager {
         result
     }
 
+    /// Subscribe to authentication events, e.g. to feed the threat detector's
+    /// brute-force window through `ThreatDetector::watch_logins`
+    ///
+    /// Subscribers that fall behind miss the oldest events rather than
+    /// blocking authentication.
+    pub fn subscribe(&self) -> broadcast::Receiver<SecurityEvent> {
+        self.events.subscribe()
+    }
+
     /// Check authorization
     pub async fn authorize(&self, user_id: &str, resource: &str, action: &str) -> Result<bool> {
         let result = self.access_control.check_permission(user_id, resource, action).await?;
@@ -347,6 +358,11 @@ impl SecurityManager {
         Ok(result)
     }
 
+    /// Key provider backups should be encrypted with
+    pub fn backup_key_provider(&self) -> Arc<dyn backup_restore::KeyProvider> {
+        self.key_manager.clone()
+    }
+
     /// Generate new encryption key
     pub async fn generate_key(&self, key_type: &str) -> Result<String> {
         let key_id = self.key_manager.generate_key(key_type).await?;
@@ -427,8 +443,19 @@ impl SecurityManager {
                 loop {
                     interval.tick().await;
                     
-                    if let Err(e) = key_manager.check_and_rotate_keys().await {
-                        error!("Failed to check/rotate keys: {}", e);
+                    match key_manager.check_and_rotate_keys().await {
+                        Ok(rotated) => {
+                            for key_id in rotated {
+                                if let Err(e) = audit_logger.log_event(SecurityEvent::KeyManagement {
+                                    operation: "rotate".to_string(),
+                                    key_id,
+                                    key_type: "encryption".to_string(),
+                                }).await {
+                                    error!("Failed to log key rotation: {}", e);
+                                }
+                            }
+                        }
+                        Err(e) => error!("Failed to check/rotate keys: {}", e),
                     }
                 }
             });
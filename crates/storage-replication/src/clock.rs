@@ -0,0 +1,95 @@
+//! Vector clocks for causality tracking
+
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+use crate::node::NodeId;
+
+/// Causal relation between two vector clocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ordering {
+    /// Happened before the other clock
+    Less,
+    /// Happened after the other clock
+    Greater,
+    /// Same causal history
+    Equal,
+    /// Neither happened before the other
+    Concurrent,
+}
+
+/// Per-node write counters
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorClock {
+    counters: BTreeMap<NodeId, u64>,
+}
+
+impl VectorClock {
+    /// Create an empty clock
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counter of `node_id`
+    pub fn get(&self, node_id: &str) -> u64 {
+        self.counters.get(node_id).copied().unwrap_or(0)
+    }
+
+    /// Record a write on `node_id`
+    pub fn increment(&mut self, node_id: &str) {
+        *self.counters.entry(node_id.to_string()).or_default() += 1;
+    }
+
+    /// Take the element-wise maximum with `other`
+    pub fn merge(&mut self, other: &VectorClock) {
+        for (node_id, counter) in &other.counters {
+            let local = self.counters.entry(node_id.clone()).or_default();
+            *local = (*local).max(*counter);
+        }
+    }
+
+    /// Causal relation of `self` to `other`
+    pub fn compare(&self, other: &VectorClock) -> Ordering {
+        let mut less = false;
+        let mut greater = false;
+        for node_id in self.counters.keys().chain(other.counters.keys()) {
+            match self.get(node_id).cmp(&other.get(node_id)) {
+                std::cmp::Ordering::Less => less = true,
+                std::cmp::Ordering::Greater => greater = true,
+                std::cmp::Ordering::Equal => {}
+            }
+        }
+
+        match (less, greater) {
+            (false, false) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (true, true) => Ordering::Concurrent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let mut a = VectorClock::new();
+        let b = a.clone();
+        assert_eq!(a.compare(&b), Ordering::Equal);
+
+        a.increment("node1");
+        assert_eq!(a.compare(&b), Ordering::Greater);
+        assert_eq!(b.compare(&a), Ordering::Less);
+
+        let mut c = b.clone();
+        c.increment("node2");
+        assert_eq!(a.compare(&c), Ordering::Concurrent);
+
+        c.merge(&a);
+        assert_eq!(c.get("node1"), 1);
+        assert_eq!(c.get("node2"), 1);
+        assert_eq!(c.compare(&a), Ordering::Greater);
+    }
+}
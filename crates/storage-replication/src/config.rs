@@ -32,6 +32,63 @@ pub struct ReplicationConfig {
     
     /// Maximum retry attempts
     pub max_retries: u32,
+
+    /// Interval between anti-entropy passes
+    #[serde(default = "ReplicationConfig::default_anti_entropy_interval")]
+    pub anti_entropy_interval: Duration,
+
+    /// Depth of the Merkle trees compared during anti-entropy
+    #[serde(default = "ReplicationConfig::default_merkle_depth")]
+    pub merkle_depth: u32,
+
+    /// Outbound queue kept for every peer
+    #[serde(default)]
+    pub outbound_queue: OutboundQueueConfig,
+
+    /// Number of replicas consulted on reads
+    #[serde(default)]
+    pub read_consistency: ReadConsistency,
+}
+
+/// Outbound replication queue configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboundQueueConfig {
+    /// Maximum number of operations waiting for a peer
+    pub capacity: usize,
+
+    /// What to do when a peer's queue is full
+    pub policy: QueueFullPolicy,
+}
+
+/// Behaviour when an outbound queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QueueFullPolicy {
+    /// Wait for the peer to catch up, up to the replication timeout
+    Block,
+    /// Discard the oldest queued operation to make room
+    DropOldest,
+    /// Reject the new operation
+    Fail,
+}
+
+/// Replicas consulted on reads
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReadConsistency {
+    /// Read from the first reachable replica
+    #[default]
+    One,
+    /// Read from every reachable replica, requiring a majority, and repair stale ones
+    Quorum,
+}
+
+impl ReplicationConfig {
+    fn default_anti_entropy_interval() -> Duration {
+        Duration::from_secs(300)
+    }
+
+    fn default_merkle_depth() -> u32 {
+        8
+    }
 }
 
 /// Replication mode
@@ -133,6 +190,19 @@ impl Default for ReplicationConfig {
             conflict_resolution: ConflictResolutionStrategy::LastWriteWins,
             health_check_interval: Duration::from_secs(10),
             max_retries: 3,
+            anti_entropy_interval: Self::default_anti_entropy_interval(),
+            merkle_depth: Self::default_merkle_depth(),
+            outbound_queue: OutboundQueueConfig::default(),
+            read_consistency: ReadConsistency::default(),
+        }
+    }
+}
+
+impl Default for OutboundQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            policy: QueueFullPolicy::Block,
         }
     }
 }
@@ -1,8 +1,18 @@
 //! Conflict resolution for replication
+//!
+//! Every node resolves a conflict on its own, seeing its own write as the
+//! local side. Strategies therefore only look at properties both nodes
+//! agree on (timestamps, then node IDs as a tie-breaker) so that they pick
+//! the same winner and converge.
+
+use std::{collections::HashMap, sync::Arc};
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use crate::{error::Result, config::ConflictResolutionStrategy};
+use crate::{
+    error::{ReplicationError, Result},
+    config::ConflictResolutionStrategy,
+};
 
 /// Conflict resolution trait
 #[async_trait]
@@ -36,15 +46,51 @@ pub enum ConflictResolution {
     RequireManual,
 }
 
+impl DataConflict {
+    /// Whether the local write is ordered after the remote one
+    ///
+    /// Writes are ordered by timestamp, ties are broken by node ID.
+    pub fn local_is_newer(&self) -> bool {
+        (self.local_timestamp, &self.local_node_id) > (self.remote_timestamp, &self.remote_node_id)
+    }
+}
+
+/// Merges concurrently written values of a mergeable type
+///
+/// Merging must be commutative: the two nodes involved in a conflict call
+/// the merger with local and remote swapped and have to reach the same value.
+pub trait ConflictMerger: Send + Sync {
+    fn merge(&self, conflict: &DataConflict) -> Result<Vec<u8>>;
+}
+
+impl<F> ConflictMerger for F
+where
+    F: Fn(&DataConflict) -> Result<Vec<u8>> + Send + Sync,
+{
+    fn merge(&self, conflict: &DataConflict) -> Result<Vec<u8>> {
+        self(conflict)
+    }
+}
+
 /// Default conflict resolver implementation
 pub struct DefaultConflictResolver {
     strategy: ConflictResolutionStrategy,
+    mergers: HashMap<String, Arc<dyn ConflictMerger>>,
 }
 
 impl DefaultConflictResolver {
     /// Create a new default conflict resolver
     pub fn new(strategy: ConflictResolutionStrategy) -> Self {
-        Self { strategy }
+        Self {
+            strategy,
+            mergers: HashMap::new(),
+        }
+    }
+
+    /// Register the merger used by `ConflictResolutionStrategy::Custom(name)`
+    pub fn with_merger(mut self, name: impl Into<String>, merger: Arc<dyn ConflictMerger>) -> Self {
+        self.mergers.insert(name.into(), merger);
+        self
     }
 }
 
@@ -53,25 +99,29 @@ impl ConflictResolver for DefaultConflictResolver {
     async fn resolve_conflict(&self, conflict: DataConflict) -> Result<ConflictResolution> {
         match &self.strategy {
             ConflictResolutionStrategy::LastWriteWins => {
-                if conflict.local_timestamp > conflict.remote_timestamp {
+                if conflict.local_is_newer() {
                     Ok(ConflictResolution::UseLocal)
                 } else {
                     Ok(ConflictResolution::UseRemote)
                 }
             }
             ConflictResolutionStrategy::FirstWriteWins => {
-                if conflict.local_timestamp < conflict.remote_timestamp {
-                    Ok(ConflictResolution::UseLocal)
-                } else {
+                if conflict.local_is_newer() {
                     Ok(ConflictResolution::UseRemote)
+                } else {
+                    Ok(ConflictResolution::UseLocal)
                 }
             }
             ConflictResolutionStrategy::Manual => {
                 Ok(ConflictResolution::RequireManual)
             }
-            ConflictResolutionStrategy::Custom(_) => {
-                // TODO: Implement custom conflict resolution
-                todo!("Custom conflict resolution implementation")
+            ConflictResolutionStrategy::Custom(name) => {
+                let merger = self.mergers.get(name).ok_or_else(|| {
+                    ReplicationError::ConflictResolution {
+                        reason: format!("no merger registered as {}", name),
+                    }
+                })?;
+                merger.merge(&conflict).map(ConflictResolution::UseMerged)
             }
         }
     }
@@ -80,7 +130,12 @@ impl ConflictResolver for DefaultConflictResolver {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        node::{ApplyOutcome, ReplicatedWrite, ReplicationNode},
+        ReplicationOperation,
+    };
     use chrono::Utc;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
     #[tokio::test]
     async fn test_last_write_wins() {
@@ -123,4 +178,94 @@ mod tests {
         let resolution = resolver.resolve_conflict(conflict).await.unwrap();
         assert!(matches!(resolution, ConflictResolution::UseRemote));
     }
+
+    fn conflicting_writes() -> (ReplicationNode, ReplicationNode, ReplicatedWrite, ReplicatedWrite) {
+        let mut node1 = ReplicationNode::new("node1".into(), "127.0.0.1".into(), 8080, 1, vec![]);
+        let mut node2 = ReplicationNode::new("node2".into(), "127.0.0.2".into(), 8080, 1, vec![]);
+
+        // Both nodes update the same key before hearing from each other
+        let write1 = node1
+            .apply_local(ReplicationOperation::Update {
+                key: b"flags".to_vec(),
+                value: b"seen".to_vec(),
+            })
+            .remove(0);
+        let write2 = node2
+            .apply_local(ReplicationOperation::Update {
+                key: b"flags".to_vec(),
+                value: b"flagged".to_vec(),
+            })
+            .remove(0);
+
+        (node1, node2, write1, write2)
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_updates_converge_under_last_write_wins() {
+        let resolver = DefaultConflictResolver::new(ConflictResolutionStrategy::LastWriteWins);
+        let (mut node1, mut node2, write1, write2) = conflicting_writes();
+        let winner = if (write1.version.timestamp, &write1.version.node_id)
+            > (write2.version.timestamp, &write2.version.node_id)
+        {
+            write1.version.value.clone()
+        } else {
+            write2.version.value.clone()
+        };
+
+        node1.apply_remote(write2, &resolver).await.unwrap();
+        node2.apply_remote(write1, &resolver).await.unwrap();
+
+        assert_eq!(node1.get(b"flags"), winner.as_deref());
+        assert_eq!(node2.get(b"flags"), winner.as_deref());
+        assert_eq!(node1.key_version(b"flags"), node2.key_version(b"flags"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_merger_is_invoked() {
+        // Values are comma separated flag sets, merged by union
+        let calls = Arc::new(AtomicUsize::new(0));
+        let merger = {
+            let calls = calls.clone();
+            move |conflict: &DataConflict| -> Result<Vec<u8>> {
+                calls.fetch_add(1, AtomicOrdering::SeqCst);
+                let flags = [&conflict.local_value, &conflict.remote_value]
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|value| value.split(|b| *b == b','))
+                    .map(|flag| String::from_utf8_lossy(flag).into_owned())
+                    .collect::<std::collections::BTreeSet<_>>();
+                Ok(flags.into_iter().collect::<Vec<_>>().join(",").into_bytes())
+            }
+        };
+        let resolver = DefaultConflictResolver::new(ConflictResolutionStrategy::Custom(
+            "flag-set".to_string(),
+        ))
+        .with_merger("flag-set", Arc::new(merger));
+        let (mut node1, mut node2, write1, write2) = conflicting_writes();
+
+        let outcome = node1.apply_remote(write2, &resolver).await.unwrap();
+        assert!(matches!(
+            outcome,
+            ApplyOutcome::Resolved(ConflictResolution::UseMerged(_))
+        ));
+        node2.apply_remote(write1, &resolver).await.unwrap();
+
+        assert_eq!(calls.load(AtomicOrdering::SeqCst), 2);
+        assert_eq!(node1.get(b"flags"), Some(&b"flagged,seen"[..]));
+        assert_eq!(node2.get(b"flags"), Some(&b"flagged,seen"[..]));
+        assert_eq!(node1.key_version(b"flags"), node2.key_version(b"flags"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_custom_merger_fails() {
+        let resolver = DefaultConflictResolver::new(ConflictResolutionStrategy::Custom(
+            "unknown".to_string(),
+        ));
+        let (mut node1, _, _, write2) = conflicting_writes();
+
+        assert!(matches!(
+            node1.apply_remote(write2, &resolver).await,
+            Err(ReplicationError::ConflictResolution { .. })
+        ));
+    }
 }
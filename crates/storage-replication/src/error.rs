@@ -60,6 +60,17 @@ pub enum ReplicationError {
     #[error("Split brain scenario detected")]
     SplitBrain,
 
+    /// Outbound queue of a peer is full
+    #[error("Outbound queue for {node_id} is full ({capacity} operations)")]
+    QueueFull { node_id: String, capacity: usize },
+
+    /// Some peers refused an operation broadcast to them
+    #[error("Broadcast refused by {} of {attempted} peers", failed.len())]
+    Broadcast {
+        attempted: usize,
+        failed: Vec<(String, ReplicationError)>,
+    },
+
     /// Quorum not available
     #[error("Quorum not available: {available}/{required}")]
     NoQuorum { available: usize, required: usize },
@@ -81,7 +92,11 @@ impl ReplicationError {
             ReplicationError::Timeout => true,
             ReplicationError::NodeUnreachable { .. } => true,
             ReplicationError::NoQuorum { .. } => true,
+            ReplicationError::QueueFull { .. } => true,
             ReplicationError::HighLag { .. } => true,
+            ReplicationError::Broadcast { failed, .. } => {
+                failed.iter().all(|(_, error)| error.is_retryable())
+            }
             _ => false,
         }
     }
@@ -113,6 +128,8 @@ impl ReplicationError {
             ReplicationError::InvalidOperation(_) => "invalid_operation",
             ReplicationError::HighLag { .. } => "high_lag",
             ReplicationError::SplitBrain => "split_brain",
+            ReplicationError::QueueFull { .. } => "queue_full",
+            ReplicationError::Broadcast { .. } => "broadcast",
             ReplicationError::NoQuorum { .. } => "no_quorum",
             ReplicationError::VersionMismatch { .. } => "version_mismatch",
             ReplicationError::Generic(_) => "generic",
@@ -40,19 +40,22 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{info, warn, error};
 
+pub mod clock;
 pub mod config;
 pub mod manager;
+pub mod merkle;
 pub mod node;
 pub mod transport;
 pub mod conflict;
 pub mod metrics;
 pub mod error;
 
-pub use config::ReplicationConfig;
-pub use manager::ReplicationManager;
-pub use node::{ReplicationNode, NodeId, NodeStatus};
-pub use transport::{ReplicationTransport, SecureTransport};
-pub use conflict::{ConflictResolver, ConflictResolution};
+pub use clock::VectorClock;
+pub use config::{OutboundQueueConfig, QueueFullPolicy, ReadConsistency, ReplicationConfig};
+pub use manager::{ReplicationManager, SyncReport};
+pub use node::{ReplicationNode, NodeId, NodeStatus, VersionedValue, ReplicatedWrite, ApplyOutcome};
+pub use transport::{BoundedTransport, Delivery, ReplicationTransport, SecureTransport};
+pub use conflict::{ConflictResolver, ConflictResolution, ConflictMerger};
 pub use error::{ReplicationError, Result};
 
 /// Replication operation types
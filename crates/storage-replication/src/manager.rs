@@ -1,14 +1,16 @@
 //! Replication manager implementation
 
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{info, warn, error};
+use std::sync::{Arc, Weak};
+use tokio::{sync::RwLock, task::JoinHandle};
+use tracing::{debug, info, warn, error};
 
 use crate::{
-    config::ReplicationConfig,
-    node::{ReplicationNode, NodeId},
-    transport::ReplicationTransport,
-    conflict::ConflictResolver,
+    clock::Ordering,
+    config::{ReadConsistency, ReplicationConfig},
+    node::{resolve_concurrent, ReplicationNode, NodeId, ReplicatedWrite, VersionedValue},
+    metrics::{ReplicationMetrics, SharedMetrics},
+    transport::{BoundedTransport, ReplicationTransport},
+    conflict::{ConflictResolver, DefaultConflictResolver},
     error::{ReplicationError, Result},
     ReplicationStatus, ReplicationOperation,
 };
@@ -17,9 +19,11 @@ use crate::{
 pub struct ReplicationManager {
     config: ReplicationConfig,
     nodes: Arc<RwLock<Vec<ReplicationNode>>>,
-    transport: Arc<dyn ReplicationTransport>,
+    /// Outbound queues in front of the transport, once one is configured
+    transport: Option<Arc<BoundedTransport>>,
     conflict_resolver: Arc<dyn ConflictResolver>,
     status: Arc<RwLock<ReplicationStatus>>,
+    metrics: SharedMetrics,
 }
 
 impl ReplicationManager {
@@ -27,11 +31,56 @@ impl ReplicationManager {
     pub async fn new(config: ReplicationConfig) -> Result<Self> {
         info!("Creating replication manager");
 
-        // TODO: Initialize transport layer based on config
-        // TODO: Initialize conflict resolver based on config
-        // TODO: Initialize nodes from config
+        let nodes = config
+            .nodes
+            .iter()
+            .map(|node| {
+                ReplicationNode::new(
+                    node.id.clone(),
+                    node.address.clone(),
+                    node.port,
+                    node.priority,
+                    node.tags.clone(),
+                )
+            })
+            .collect();
+        let conflict_resolver = Arc::new(DefaultConflictResolver::new(
+            config.conflict_resolution.clone(),
+        ));
+        let metrics: SharedMetrics = Arc::new(ReplicationMetrics::new());
 
-        todo!("ReplicationManager::new implementation")
+        Ok(Self {
+            config,
+            nodes: Arc::new(RwLock::new(nodes)),
+            transport: None,
+            conflict_resolver,
+            status: Arc::new(RwLock::new(ReplicationStatus::Paused)),
+            metrics,
+        })
+    }
+
+    /// Use `conflict_resolver` instead of the one derived from the configuration
+    pub fn with_conflict_resolver(mut self, conflict_resolver: Arc<dyn ConflictResolver>) -> Self {
+        self.conflict_resolver = conflict_resolver;
+        self
+    }
+
+    /// Replicate to peers through `transport`
+    ///
+    /// The transport is wrapped in bounded per-peer queues sized by the
+    /// configuration, and every configured node is registered as a peer.
+    pub fn with_transport(mut self, transport: Arc<dyn ReplicationTransport>) -> Self {
+        let transport = BoundedTransport::new(
+            transport,
+            self.config.outbound_queue.clone(),
+            self.config.timeout,
+            self.metrics.clone(),
+        );
+        for node in &self.config.nodes {
+            transport.add_peer(&node.id);
+        }
+        self.transport = Some(Arc::new(transport));
+        self
     }
 
     /// Start replication
@@ -65,6 +114,117 @@ impl ReplicationManager {
         todo!("ReplicationManager::replicate_operation implementation")
     }
 
+    /// Read `key` with the configured read consistency
+    ///
+    /// Quorum reads compare the versions held by every reachable replica and
+    /// return the freshest one; concurrent versions are settled by the
+    /// conflict resolver, so a merge is returned as the merged value. Replicas
+    /// found to be stale are brought up to date in the background, so the
+    /// caller does not wait for the repair.
+    pub async fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let nodes = self.nodes.read().await;
+        let mut replicas = nodes.iter().filter(|node| node.is_reachable());
+
+        if self.config.read_consistency == ReadConsistency::One {
+            let node = replicas.next().ok_or(ReplicationError::NoQuorum {
+                available: 0,
+                required: 1,
+            })?;
+            return Ok(node.get(key).map(<[u8]>::to_vec));
+        }
+
+        let responses = replicas
+            .map(|node| (node.id().clone(), node.key_version(key).cloned()))
+            .collect::<Vec<_>>();
+        let required = nodes.len() / 2 + 1;
+        if responses.len() < required {
+            return Err(ReplicationError::NoQuorum {
+                available: responses.len(),
+                required,
+            });
+        }
+        drop(nodes);
+
+        let mut freshest: Option<VersionedValue> = None;
+        for version in responses.iter().filter_map(|(_, version)| version.clone()) {
+            freshest = Some(match freshest {
+                Some(current) => self.reconcile(key, current, version).await?,
+                None => version,
+            });
+        }
+        let Some(freshest) = freshest else {
+            return Ok(None);
+        };
+
+        let stale = responses
+            .into_iter()
+            .filter(|(_, version)| {
+                version.as_ref().map_or(true, |version| {
+                    version.clock.compare(&freshest.clock) != Ordering::Equal
+                })
+            })
+            .map(|(node_id, _)| node_id)
+            .collect::<Vec<_>>();
+        if !stale.is_empty() {
+            self.spawn_read_repair(
+                stale,
+                ReplicatedWrite {
+                    key: key.to_vec(),
+                    version: freshest.clone(),
+                },
+            );
+        }
+
+        Ok(freshest.value)
+    }
+
+    /// The version of `key` to keep out of `current` and `other`
+    ///
+    /// A version that causally follows the other one wins, concurrent ones
+    /// are settled by the conflict resolver. When the resolver asks for
+    /// manual resolution `current` is kept, as replicas keep their local value.
+    async fn reconcile(
+        &self,
+        key: &[u8],
+        current: VersionedValue,
+        other: VersionedValue,
+    ) -> Result<VersionedValue> {
+        match other.clock.compare(&current.clock) {
+            Ordering::Greater => Ok(other),
+            Ordering::Less | Ordering::Equal => Ok(current),
+            Ordering::Concurrent => {
+                let (_, resolved) =
+                    resolve_concurrent(key, &current, other, self.conflict_resolver.as_ref())
+                        .await?;
+                Ok(resolved.unwrap_or(current))
+            }
+        }
+    }
+
+    /// Write `write` back to the `stale` replicas without blocking the reader
+    fn spawn_read_repair(&self, stale: Vec<NodeId>, write: ReplicatedWrite) {
+        let nodes = self.nodes.clone();
+        let conflict_resolver = self.conflict_resolver.clone();
+
+        tokio::spawn(async move {
+            let mut nodes = nodes.write().await;
+            for node in nodes.iter_mut().filter(|node| stale.contains(node.id())) {
+                debug!("Read-repairing {:?} on node {}", write.key, node.id());
+                if let Err(e) = node
+                    .apply_remote(write.clone(), conflict_resolver.as_ref())
+                    .await
+                {
+                    warn!("Read repair on node {} failed: {}", node.id(), e);
+                }
+            }
+        });
+    }
+
+    /// Replication metrics, including outbound queue depth
+    pub fn metrics(&self) -> SharedMetrics {
+        self.metrics.clone()
+    }
+
     /// Get replication status
     pub async fn status(&self) -> ReplicationStatus {
         self.status.read().await.clone()
@@ -74,22 +234,110 @@ impl ReplicationManager {
     pub async fn add_node(&self, node: ReplicationNode) -> Result<()> {
         info!("Adding node to replication cluster: {}", node.id());
 
-        // TODO: Validate node
-        // TODO: Establish connection
-        // TODO: Add to node list
-
-        todo!("ReplicationManager::add_node implementation")
+        let mut nodes = self.nodes.write().await;
+        if nodes.iter().any(|existing| existing.id() == node.id()) {
+            return Err(ReplicationError::InvalidOperation(format!(
+                "node {} is already part of the cluster",
+                node.id()
+            )));
+        }
+        if let Some(transport) = &self.transport {
+            transport.add_peer(node.id());
+        }
+        nodes.push(node);
+        Ok(())
     }
 
     /// Remove a node from the replication cluster
     pub async fn remove_node(&self, node_id: &NodeId) -> Result<()> {
         info!("Removing node from replication cluster: {}", node_id);
 
-        // TODO: Close connections
-        // TODO: Remove from node list
-        // TODO: Update cluster topology
+        let mut nodes = self.nodes.write().await;
+        let position = nodes
+            .iter()
+            .position(|node| node.id() == node_id)
+            .ok_or_else(|| ReplicationError::NodeNotFound {
+                node_id: node_id.clone(),
+            })?;
+        nodes.remove(position);
+        if let Some(transport) = &self.transport {
+            transport.remove_peer(node_id);
+        }
+        Ok(())
+    }
+
+    /// Run an anti-entropy pass between every pair of nodes
+    ///
+    /// Each pair compares Merkle trees of their keyspaces and exchanges only
+    /// the writes in ranges whose hashes differ; conflicting writes are
+    /// settled by the conflict resolver.
+    pub async fn sync_now(&self) -> Result<SyncReport> {
+        let depth = self.config.merkle_depth;
+        let mut nodes = self.nodes.write().await;
+        let mut report = SyncReport::default();
+
+        for i in 0..nodes.len() {
+            for j in i + 1..nodes.len() {
+                let (head, tail) = nodes.split_at_mut(j);
+                let (a, b) = (&mut head[i], &mut tail[0]);
+                let (ranges, compared) = a.merkle_tree(depth).diff(&b.merkle_tree(depth));
+                report.hashes_compared += compared;
+                if ranges.is_empty() {
+                    continue;
+                }
+                debug!(
+                    "Nodes {} and {} diverge in {} range(s)",
+                    a.id(),
+                    b.id(),
+                    ranges.len()
+                );
+
+                for range in ranges {
+                    let from_a = a.writes_in_range(range, depth);
+                    let from_b = b.writes_in_range(range, depth);
+                    report.ranges_repaired += 1;
+                    report.writes_sent += from_a.len() + from_b.len();
+
+                    for write in from_a {
+                        b.apply_remote(write, self.conflict_resolver.as_ref())
+                            .await?;
+                    }
+                    for write in from_b {
+                        a.apply_remote(write, self.conflict_resolver.as_ref())
+                            .await?;
+                    }
+                }
+            }
+        }
 
-        todo!("ReplicationManager::remove_node implementation")
+        if report.ranges_repaired > 0 {
+            info!(
+                "Anti-entropy repaired {} range(s) with {} write(s)",
+                report.ranges_repaired, report.writes_sent
+            );
+        }
+        Ok(report)
+    }
+
+    /// Spawn the periodic anti-entropy task
+    ///
+    /// The task stops once the manager is dropped.
+    pub fn start_anti_entropy(self: &Arc<Self>) -> JoinHandle<()> {
+        let manager: Weak<Self> = Arc::downgrade(self);
+        let period = self.config.anti_entropy_interval;
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                if let Err(e) = manager.sync_now().await {
+                    error!("Anti-entropy pass failed: {}", e);
+                }
+            }
+        })
     }
 
     /// Get cluster health status
@@ -102,6 +350,17 @@ impl ReplicationManager {
     }
 }
 
+/// Outcome of an anti-entropy pass
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Merkle tree hashes compared across all node pairs
+    pub hashes_compared: usize,
+    /// Key ranges found to diverge and re-synchronized
+    pub ranges_repaired: usize,
+    /// Writes exchanged to repair those ranges
+    pub writes_sent: usize,
+}
+
 /// Cluster health information
 #[derive(Debug, Clone)]
 pub struct ClusterHealth {
@@ -115,7 +374,12 @@ pub struct ClusterHealth {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::ReplicationConfig;
+    use crate::{
+        config::{ConflictResolutionStrategy, ReplicationConfig},
+        conflict::DataConflict,
+        merkle,
+        node::NodeStatus,
+    };
 
     #[tokio::test]
     async fn test_manager_creation() {
@@ -123,4 +387,266 @@ mod tests {
         // Note: This will panic with todo! until implementation is complete
         // let manager = ReplicationManager::new(config).await.unwrap();
     }
+
+    /// Transport recording the nodes operations were delivered to
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent: std::sync::Mutex<Vec<NodeId>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ReplicationTransport for RecordingTransport {
+        async fn send_operation(
+            &self,
+            node_id: &NodeId,
+            _operation: ReplicationOperation,
+        ) -> Result<()> {
+            self.sent.lock().unwrap().push(node_id.clone());
+            Ok(())
+        }
+
+        async fn broadcast_operation(&self, _operation: ReplicationOperation) -> Result<()> {
+            Ok(())
+        }
+
+        async fn connect(&self, _node_id: &NodeId, _address: &str, _port: u16) -> Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&self, _node_id: &NodeId) -> Result<()> {
+            Ok(())
+        }
+
+        async fn is_connected(&self, _node_id: &NodeId) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transport_peers_follow_membership() {
+        let transport = Arc::new(RecordingTransport::default());
+        let manager = ReplicationManager::new(ReplicationConfig::default())
+            .await
+            .unwrap()
+            .with_transport(transport.clone());
+        for i in 1..=2 {
+            let node = ReplicationNode::new(
+                format!("node{}", i),
+                "127.0.0.1".to_string(),
+                8080,
+                1,
+                vec![],
+            );
+            manager.add_node(node).await.unwrap();
+        }
+        manager.remove_node(&"node1".to_string()).await.unwrap();
+
+        let queues = manager.transport.clone().unwrap();
+        queues
+            .broadcast_operation(ReplicationOperation::Delete { key: b"k".to_vec() })
+            .await
+            .unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while transport.sent.lock().unwrap().is_empty() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(*transport.sent.lock().unwrap(), vec!["node2".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_anti_entropy_resyncs_only_diverging_ranges() {
+        let config = ReplicationConfig {
+            merkle_depth: 3,
+            ..ReplicationConfig::default()
+        };
+        let depth = config.merkle_depth;
+        let mut node1 = ReplicationNode::new("node1".into(), "127.0.0.1".into(), 8080, 1, vec![]);
+        let mut node2 = ReplicationNode::new("node2".into(), "127.0.0.2".into(), 8080, 1, vec![]);
+
+        let writes = node1.apply_local(ReplicationOperation::Batch {
+            operations: (0..64)
+                .map(|i| ReplicationOperation::Insert {
+                    key: format!("message/{}", i).into_bytes(),
+                    value: format!("content {}", i).into_bytes(),
+                })
+                .collect(),
+        });
+
+        // node2 was partitioned while the writes of one range were replicated
+        let missing_range = merkle::range_of(&writes[0].key, depth);
+        let resolver = DefaultConflictResolver::new(config.conflict_resolution.clone());
+        let mut missing = 0;
+        for write in writes.iter().cloned() {
+            if merkle::range_of(&write.key, depth) == missing_range {
+                missing += 1;
+            } else {
+                node2.apply_remote(write, &resolver).await.unwrap();
+            }
+        }
+        assert!(missing > 0 && missing < writes.len());
+
+        let manager = ReplicationManager::new(config).await.unwrap();
+        manager.add_node(node1).await.unwrap();
+        manager.add_node(node2).await.unwrap();
+
+        let report = manager.sync_now().await.unwrap();
+        assert_eq!(report.ranges_repaired, 1);
+        assert_eq!(report.writes_sent, missing);
+
+        let nodes = manager.nodes.read().await;
+        assert_eq!(nodes[0].merkle_tree(depth), nodes[1].merkle_tree(depth));
+        for write in &writes {
+            assert_eq!(nodes[1].get(&write.key), write.version.value.as_deref());
+        }
+        drop(nodes);
+
+        // Converged nodes only compare their roots
+        let report = manager.sync_now().await.unwrap();
+        assert_eq!(
+            report,
+            SyncReport {
+                hashes_compared: 1,
+                ..SyncReport::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quorum_read_repairs_stale_replica() {
+        let config = ReplicationConfig {
+            read_consistency: ReadConsistency::Quorum,
+            ..ReplicationConfig::default()
+        };
+        let resolver = DefaultConflictResolver::new(config.conflict_resolution.clone());
+        let mut nodes = (1..=3)
+            .map(|i| {
+                let mut node = ReplicationNode::new(
+                    format!("node{}", i),
+                    format!("127.0.0.{}", i),
+                    8080,
+                    1,
+                    vec![],
+                );
+                node.set_status(NodeStatus::Healthy);
+                node
+            })
+            .collect::<Vec<_>>();
+        let key = b"mailbox/inbox".to_vec();
+
+        let first = nodes[0].apply_local(ReplicationOperation::Insert {
+            key: key.clone(),
+            value: b"v1".to_vec(),
+        });
+        let second = nodes[0].apply_local(ReplicationOperation::Update {
+            key: key.clone(),
+            value: b"v2".to_vec(),
+        });
+        // node3 missed the update
+        for (i, write) in [(1, &first[0]), (1, &second[0]), (2, &first[0])] {
+            nodes[i]
+                .apply_remote(write.clone(), &resolver)
+                .await
+                .unwrap();
+        }
+
+        let manager = ReplicationManager::new(config).await.unwrap();
+        for node in nodes {
+            manager.add_node(node).await.unwrap();
+        }
+
+        assert_eq!(manager.read(&key).await.unwrap(), Some(b"v2".to_vec()));
+
+        let repaired = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            loop {
+                if manager.nodes.read().await[2].get(&key) == Some(b"v2".as_slice()) {
+                    break;
+                }
+                tokio::task::yield_now().await;
+            }
+        })
+        .await;
+        assert!(repaired.is_ok(), "stale replica was not repaired");
+        assert_eq!(
+            manager.nodes.read().await[2].key_version(&key),
+            Some(&second[0].version)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quorum_read_merges_concurrent_versions() {
+        let config = ReplicationConfig {
+            read_consistency: ReadConsistency::Quorum,
+            conflict_resolution: ConflictResolutionStrategy::Custom("union".to_string()),
+            ..ReplicationConfig::default()
+        };
+        let union = |conflict: &DataConflict| -> Result<Vec<u8>> {
+            let mut flags = [&conflict.local_value, &conflict.remote_value]
+                .into_iter()
+                .flatten()
+                .map(|value| String::from_utf8_lossy(value).into_owned())
+                .collect::<Vec<_>>();
+            flags.sort();
+            Ok(flags.join(",").into_bytes())
+        };
+        let resolver: Arc<dyn ConflictResolver> = Arc::new(
+            DefaultConflictResolver::new(config.conflict_resolution.clone())
+                .with_merger("union", Arc::new(union)),
+        );
+        let key = b"flags".to_vec();
+        let mut nodes = (1..=2)
+            .map(|i| {
+                let mut node = ReplicationNode::new(
+                    format!("node{}", i),
+                    format!("127.0.0.{}", i),
+                    8080,
+                    1,
+                    vec![],
+                );
+                node.set_status(NodeStatus::Healthy);
+                node
+            })
+            .collect::<Vec<_>>();
+        // Both replicas accepted a write without seeing the other one
+        nodes[0].apply_local(ReplicationOperation::Insert {
+            key: key.clone(),
+            value: b"seen".to_vec(),
+        });
+        nodes[1].apply_local(ReplicationOperation::Insert {
+            key: key.clone(),
+            value: b"flagged".to_vec(),
+        });
+
+        let manager = ReplicationManager::new(config)
+            .await
+            .unwrap()
+            .with_conflict_resolver(resolver);
+        for node in nodes {
+            manager.add_node(node).await.unwrap();
+        }
+
+        assert_eq!(
+            manager.read(&key).await.unwrap(),
+            Some(b"flagged,seen".to_vec())
+        );
+
+        // Both replicas converge on the merged version
+        let repaired = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            loop {
+                let nodes = manager.nodes.read().await;
+                if nodes
+                    .iter()
+                    .all(|node| node.get(&key) == Some(b"flagged,seen".as_slice()))
+                {
+                    break;
+                }
+                drop(nodes);
+                tokio::task::yield_now().await;
+            }
+        })
+        .await;
+        assert!(repaired.is_ok(), "replicas did not converge on the merge");
+    }
 }
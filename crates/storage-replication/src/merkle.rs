@@ -0,0 +1,152 @@
+//! Merkle trees over a node's keyspace for anti-entropy
+//!
+//! Keys are assigned to `2^depth` ranges by the leading bits of their hash.
+//! Each leaf hashes the entries of one range and inner nodes hash their
+//! children, so two nodes can locate the ranges they disagree on by walking
+//! down only the subtrees whose hashes differ.
+
+use ring::digest::{digest, Context, SHA256};
+
+use crate::node::VersionedValue;
+
+/// Hash of a tree node
+pub type Hash = [u8; 32];
+
+/// Range of the keyspace `key` belongs to in a tree of the given depth
+pub fn range_of(key: &[u8], depth: u32) -> usize {
+    if depth == 0 {
+        return 0;
+    }
+    let hash = digest(&SHA256, key);
+    let prefix = u32::from_be_bytes(hash.as_ref()[..4].try_into().expect("4 bytes"));
+    (prefix >> (32 - depth)) as usize
+}
+
+/// Complete binary hash tree stored level by level, root first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleTree {
+    depth: u32,
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    /// Build a tree of `depth` levels below the root from `(key, version)` entries
+    pub fn build<'a>(
+        entries: impl IntoIterator<Item = (&'a [u8], &'a VersionedValue)>,
+        depth: u32,
+    ) -> Self {
+        let mut ranges = vec![Vec::new(); 1 << depth];
+        for (key, version) in entries {
+            ranges[range_of(key, depth)].push((key, version));
+        }
+
+        let leaves = ranges
+            .into_iter()
+            .map(|mut entries| {
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                let mut context = Context::new(&SHA256);
+                for (key, version) in entries {
+                    context.update(&(key.len() as u64).to_be_bytes());
+                    context.update(key);
+                    let version = serde_json::to_vec(version).expect("serializable version");
+                    context.update(&(version.len() as u64).to_be_bytes());
+                    context.update(&version);
+                }
+                to_hash(context.finish().as_ref())
+            })
+            .collect::<Vec<_>>();
+
+        let mut levels = vec![leaves];
+        while levels[0].len() > 1 {
+            let parents = levels[0]
+                .chunks(2)
+                .map(|pair| {
+                    let mut context = Context::new(&SHA256);
+                    context.update(&pair[0]);
+                    context.update(&pair[1]);
+                    to_hash(context.finish().as_ref())
+                })
+                .collect();
+            levels.insert(0, parents);
+        }
+
+        Self { depth, levels }
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    pub fn root(&self) -> Hash {
+        self.levels[0][0]
+    }
+
+    /// Hash of the node at `index` on `level`, the root being level 0
+    pub fn hash(&self, level: u32, index: usize) -> Option<Hash> {
+        self.levels.get(level as usize)?.get(index).copied()
+    }
+
+    /// Ranges whose content differs from `other`
+    ///
+    /// Only subtrees with differing hashes are descended into. Returns the
+    /// diverging ranges together with the number of hashes compared.
+    pub fn diff(&self, other: &MerkleTree) -> (Vec<usize>, usize) {
+        debug_assert_eq!(self.depth, other.depth);
+        let mut compared = 1;
+        if self.root() == other.root() {
+            return (Vec::new(), compared);
+        }
+
+        let mut diverging = vec![0];
+        for level in 1..=self.depth {
+            diverging = diverging
+                .into_iter()
+                .flat_map(|index| [index * 2, index * 2 + 1])
+                .filter(|&index| {
+                    compared += 1;
+                    self.hash(level, index) != other.hash(level, index)
+                })
+                .collect();
+        }
+        (diverging, compared)
+    }
+}
+
+fn to_hash(bytes: &[u8]) -> Hash {
+    bytes.try_into().expect("SHA-256 digest")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::VectorClock;
+    use chrono::Utc;
+
+    fn version(value: &str) -> VersionedValue {
+        VersionedValue {
+            value: Some(value.as_bytes().to_vec()),
+            timestamp: Utc::now(),
+            node_id: "node1".to_string(),
+            clock: VectorClock::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_locates_changed_range() {
+        let entries = (0..64)
+            .map(|i| (format!("key{}", i).into_bytes(), version("v")))
+            .collect::<Vec<_>>();
+        let tree = |entries: &[(Vec<u8>, VersionedValue)]| {
+            MerkleTree::build(entries.iter().map(|(k, v)| (k.as_slice(), v)), 3)
+        };
+
+        let a = tree(&entries);
+        assert_eq!(a.diff(&tree(&entries)), (Vec::new(), 1));
+
+        let mut changed = entries.clone();
+        changed[5].1 = version("other");
+        let (ranges, compared) = a.diff(&tree(&changed));
+        assert_eq!(ranges, vec![range_of(b"key5", 3)]);
+        assert_eq!(compared, 1 + 2 * 3);
+    }
+}
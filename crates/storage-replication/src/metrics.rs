@@ -14,6 +14,8 @@ pub struct ReplicationMetrics {
     replication_lag_ms: AtomicU64,
     nodes_healthy: AtomicU64,
     nodes_total: AtomicU64,
+    outbound_queue_depth: AtomicU64,
+    operations_dropped: AtomicU64,
 }
 
 impl ReplicationMetrics {
@@ -27,6 +29,8 @@ impl ReplicationMetrics {
             replication_lag_ms: AtomicU64::new(0),
             nodes_healthy: AtomicU64::new(0),
             nodes_total: AtomicU64::new(0),
+            outbound_queue_depth: AtomicU64::new(0),
+            operations_dropped: AtomicU64::new(0),
         }
     }
 
@@ -61,6 +65,20 @@ impl ReplicationMetrics {
         self.nodes_total.store(total, Ordering::Relaxed);
     }
 
+    /// Record operations entering (positive) or leaving (negative) outbound queues
+    pub fn adjust_outbound_queue_depth(&self, delta: i64) {
+        if delta >= 0 {
+            self.outbound_queue_depth.fetch_add(delta as u64, Ordering::Relaxed);
+        } else {
+            self.outbound_queue_depth.fetch_sub(delta.unsigned_abs(), Ordering::Relaxed);
+        }
+    }
+
+    /// Increment operations dropped from full outbound queues
+    pub fn increment_operations_dropped(&self) {
+        self.operations_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Get current metrics snapshot
     pub fn snapshot(&self) -> MetricsSnapshot {
         MetricsSnapshot {
@@ -71,6 +89,8 @@ impl ReplicationMetrics {
             replication_lag_ms: self.replication_lag_ms.load(Ordering::Relaxed),
             nodes_healthy: self.nodes_healthy.load(Ordering::Relaxed),
             nodes_total: self.nodes_total.load(Ordering::Relaxed),
+            outbound_queue_depth: self.outbound_queue_depth.load(Ordering::Relaxed),
+            operations_dropped: self.operations_dropped.load(Ordering::Relaxed),
         }
     }
 
@@ -83,6 +103,7 @@ impl ReplicationMetrics {
         self.replication_lag_ms.store(0, Ordering::Relaxed);
         self.nodes_healthy.store(0, Ordering::Relaxed);
         self.nodes_total.store(0, Ordering::Relaxed);
+        self.operations_dropped.store(0, Ordering::Relaxed);
     }
 }
 
@@ -102,6 +123,11 @@ pub struct MetricsSnapshot {
     pub replication_lag_ms: u64,
     pub nodes_healthy: u64,
     pub nodes_total: u64,
+    /// Operations waiting in outbound queues across all peers
+    #[serde(default)]
+    pub outbound_queue_depth: u64,
+    #[serde(default)]
+    pub operations_dropped: u64,
 }
 
 impl MetricsSnapshot {
@@ -163,6 +189,8 @@ mod tests {
             replication_lag_ms: 0,
             nodes_healthy: 0,
             nodes_total: 0,
+            outbound_queue_depth: 0,
+            operations_dropped: 0,
         };
         
         assert_eq!(snapshot.success_rate(), 0.95);
@@ -178,6 +206,8 @@ mod tests {
             replication_lag_ms: 500,
             nodes_healthy: 3,
             nodes_total: 3,
+            outbound_queue_depth: 0,
+            operations_dropped: 0,
         };
         
         assert!(healthy_snapshot.is_healthy());
@@ -1,8 +1,17 @@
 //! Replication node implementation
 
-use std::fmt;
+use std::{collections::BTreeMap, fmt};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use tracing::{debug, warn};
+
+use crate::{
+    clock::{Ordering, VectorClock},
+    conflict::{ConflictResolution, ConflictResolver, DataConflict},
+    error::Result,
+    merkle::{self, MerkleTree},
+    ReplicationOperation,
+};
 
 /// Node identifier
 pub type NodeId = String;
@@ -18,6 +27,39 @@ pub struct ReplicationNode {
     priority: u8,
     tags: Vec<String>,
     version: String,
+    /// Local copy of the replicated keyspace
+    #[serde(skip)]
+    data: BTreeMap<Vec<u8>, VersionedValue>,
+}
+
+/// A stored value together with the write that produced it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionedValue {
+    /// `None` marks a deleted key
+    pub value: Option<Vec<u8>>,
+    pub timestamp: DateTime<Utc>,
+    /// Node that originated the write
+    pub node_id: NodeId,
+    /// Causal history of the value
+    pub clock: VectorClock,
+}
+
+/// A single key write exchanged between nodes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicatedWrite {
+    pub key: Vec<u8>,
+    pub version: VersionedValue,
+}
+
+/// What applying a remote write did to the local keyspace
+#[derive(Debug, Clone)]
+pub enum ApplyOutcome {
+    /// The write superseded the local value
+    Applied,
+    /// The write was already known or older than the local value
+    Ignored,
+    /// The write conflicted with a local write and was resolved
+    Resolved(ConflictResolution),
 }
 
 /// Node status
@@ -53,6 +95,7 @@ impl ReplicationNode {
             priority,
             tags,
             version: env!("CARGO_PKG_VERSION").to_string(),
+            data: BTreeMap::new(),
         }
     }
 
@@ -126,6 +169,166 @@ impl ReplicationNode {
     pub fn has_tag(&self, tag: &str) -> bool {
         self.tags.contains(&tag.to_string())
     }
+
+    /// Current value of `key`, `None` if missing or deleted
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.data.get(key).and_then(|entry| entry.value.as_deref())
+    }
+
+    /// Version of `key`, including deletions
+    pub fn key_version(&self, key: &[u8]) -> Option<&VersionedValue> {
+        self.data.get(key)
+    }
+
+    /// Merkle tree over the local keyspace, deleted keys included
+    pub fn merkle_tree(&self, depth: u32) -> MerkleTree {
+        MerkleTree::build(
+            self.data.iter().map(|(key, version)| (key.as_slice(), version)),
+            depth,
+        )
+    }
+
+    /// Every stored write whose key falls into `range` of a tree of `depth`
+    pub fn writes_in_range(&self, range: usize, depth: u32) -> Vec<ReplicatedWrite> {
+        self.data
+            .iter()
+            .filter(|(key, _)| merkle::range_of(key, depth) == range)
+            .map(|(key, version)| ReplicatedWrite {
+                key: key.clone(),
+                version: version.clone(),
+            })
+            .collect()
+    }
+
+    /// Apply an operation originating on this node
+    ///
+    /// Returns the writes to replicate to the other nodes.
+    pub fn apply_local(&mut self, operation: ReplicationOperation) -> Vec<ReplicatedWrite> {
+        let mut writes = Vec::new();
+        self.collect_local(operation, &mut writes);
+        writes
+    }
+
+    fn collect_local(
+        &mut self,
+        operation: ReplicationOperation,
+        writes: &mut Vec<ReplicatedWrite>,
+    ) {
+        let (key, value) = match operation {
+            ReplicationOperation::Insert { key, value }
+            | ReplicationOperation::Update { key, value } => (key, Some(value)),
+            ReplicationOperation::Delete { key } => (key, None),
+            ReplicationOperation::Batch { operations } => {
+                for operation in operations {
+                    self.collect_local(operation, writes);
+                }
+                return;
+            }
+        };
+
+        let mut clock = self
+            .data
+            .get(&key)
+            .map(|local| local.clock.clone())
+            .unwrap_or_default();
+        clock.increment(&self.id);
+        let version = VersionedValue {
+            value,
+            timestamp: Utc::now(),
+            node_id: self.id.clone(),
+            clock,
+        };
+        self.data.insert(key.clone(), version.clone());
+        writes.push(ReplicatedWrite { key, version });
+    }
+
+    /// Apply a write received from another node
+    ///
+    /// Writes that causally follow the local value replace it and writes that
+    /// precede it are ignored. Only concurrent writes are settled by
+    /// `resolver`, after which the stored clock covers both writes.
+    pub async fn apply_remote(
+        &mut self,
+        write: ReplicatedWrite,
+        resolver: &dyn ConflictResolver,
+    ) -> Result<ApplyOutcome> {
+        let ReplicatedWrite { key, version } = write;
+        let Some(local) = self.data.get(&key) else {
+            self.data.insert(key, version);
+            return Ok(ApplyOutcome::Applied);
+        };
+
+        match version.clock.compare(&local.clock) {
+            Ordering::Greater => {
+                self.data.insert(key, version);
+                return Ok(ApplyOutcome::Applied);
+            }
+            Ordering::Less | Ordering::Equal => return Ok(ApplyOutcome::Ignored),
+            Ordering::Concurrent => {}
+        }
+
+        let (resolution, resolved) = resolve_concurrent(&key, local, version, resolver).await?;
+        if let Some(resolved) = resolved {
+            self.data.insert(key, resolved);
+        }
+
+        Ok(ApplyOutcome::Resolved(resolution))
+    }
+}
+
+/// Settle the concurrent writes `local` and `remote` of `key` with `resolver`
+///
+/// Returns the resolution together with the version to store, whose clock
+/// covers both writes, or `None` when the conflict needs manual resolution.
+pub(crate) async fn resolve_concurrent(
+    key: &[u8],
+    local: &VersionedValue,
+    remote: VersionedValue,
+    resolver: &dyn ConflictResolver,
+) -> Result<(ConflictResolution, Option<VersionedValue>)> {
+    let conflict = DataConflict {
+        key: key.to_vec(),
+        local_value: local.value.clone(),
+        remote_value: remote.value.clone(),
+        local_timestamp: local.timestamp,
+        remote_timestamp: remote.timestamp,
+        local_node_id: local.node_id.clone(),
+        remote_node_id: remote.node_id.clone(),
+    };
+    let local_is_newer = conflict.local_is_newer();
+    let resolution = resolver.resolve_conflict(conflict).await?;
+    debug!("Resolved conflict on {:?} with {:?}", key, resolution);
+
+    let mut clock = local.clock.clone();
+    clock.merge(&remote.clock);
+    let resolved = match &resolution {
+        ConflictResolution::UseLocal => Some(local.clone()),
+        ConflictResolution::UseRemote => Some(remote),
+        ConflictResolution::UseMerged(value) => {
+            // Stamp the merge with the newer write so both sides store the same version
+            let newer = if local_is_newer {
+                local.clone()
+            } else {
+                remote
+            };
+            Some(VersionedValue {
+                value: Some(value.clone()),
+                ..newer
+            })
+        }
+        ConflictResolution::RequireManual => {
+            warn!(
+                "Conflict on {:?} requires manual resolution, keeping local value",
+                key
+            );
+            None
+        }
+    };
+
+    Ok((
+        resolution,
+        resolved.map(|resolved| VersionedValue { clock, ..resolved }),
+    ))
 }
 
 impl fmt::Display for ReplicationNode {
@@ -153,6 +356,7 @@ impl fmt::Display for NodeStatus {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{config::ConflictResolutionStrategy, conflict::DefaultConflictResolver};
 
     #[test]
     fn test_node_creation() {
@@ -200,4 +404,68 @@ mod tests {
 
         assert_eq!(node.full_address(), "127.0.0.1:8080");
     }
+
+    #[tokio::test]
+    async fn test_causal_updates_do_not_conflict() {
+        // Any conflict reaching the resolver would be left unresolved
+        let resolver = DefaultConflictResolver::new(ConflictResolutionStrategy::Manual);
+        let mut node1 = ReplicationNode::new("node1".into(), "127.0.0.1".into(), 8080, 1, vec![]);
+        let mut node2 = ReplicationNode::new("node2".into(), "127.0.0.2".into(), 8080, 1, vec![]);
+
+        let first = node1.apply_local(ReplicationOperation::Insert {
+            key: b"mailbox".to_vec(),
+            value: b"v1".to_vec(),
+        });
+        for write in first {
+            let outcome = node2.apply_remote(write, &resolver).await.unwrap();
+            assert!(matches!(outcome, ApplyOutcome::Applied));
+        }
+
+        // node2 updates after having seen node1's write
+        let second = node2.apply_local(ReplicationOperation::Update {
+            key: b"mailbox".to_vec(),
+            value: b"v2".to_vec(),
+        });
+        assert_eq!(
+            second[0].version.clock.compare(&node1.key_version(b"mailbox").unwrap().clock),
+            Ordering::Greater
+        );
+        for write in second.clone() {
+            let outcome = node1.apply_remote(write, &resolver).await.unwrap();
+            assert!(matches!(outcome, ApplyOutcome::Applied));
+        }
+        assert_eq!(node1.get(b"mailbox"), Some(&b"v2"[..]));
+
+        // Redelivering the same write is a no-op
+        let outcome = node1.apply_remote(second[0].clone(), &resolver).await.unwrap();
+        assert!(matches!(outcome, ApplyOutcome::Ignored));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_updates_are_flagged() {
+        let resolver = DefaultConflictResolver::new(ConflictResolutionStrategy::Manual);
+        let mut node1 = ReplicationNode::new("node1".into(), "127.0.0.1".into(), 8080, 1, vec![]);
+        let mut node2 = ReplicationNode::new("node2".into(), "127.0.0.2".into(), 8080, 1, vec![]);
+
+        let write1 = node1
+            .apply_local(ReplicationOperation::Insert {
+                key: b"mailbox".to_vec(),
+                value: b"from node1".to_vec(),
+            })
+            .remove(0);
+        let write2 = node2
+            .apply_local(ReplicationOperation::Insert {
+                key: b"mailbox".to_vec(),
+                value: b"from node2".to_vec(),
+            })
+            .remove(0);
+        assert_eq!(write1.version.clock.compare(&write2.version.clock), Ordering::Concurrent);
+
+        let outcome = node1.apply_remote(write2, &resolver).await.unwrap();
+        assert!(matches!(
+            outcome,
+            ApplyOutcome::Resolved(ConflictResolution::RequireManual)
+        ));
+        assert_eq!(node1.get(b"mailbox"), Some(&b"from node1"[..]));
+    }
 }
@@ -1,7 +1,24 @@
 //! Transport layer for replication
 
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use async_trait::async_trait;
-use crate::{ReplicationOperation, error::Result, node::NodeId};
+use tokio::{
+    sync::{oneshot, Notify},
+    task::JoinHandle,
+};
+use tracing::{debug, warn};
+
+use crate::{
+    config::{OutboundQueueConfig, QueueFullPolicy},
+    error::{ReplicationError, Result},
+    metrics::SharedMetrics,
+    node::NodeId,
+    ReplicationOperation,
+};
 
 /// Transport layer for replication communication
 #[async_trait]
@@ -63,3 +80,515 @@ impl ReplicationTransport for SecureTransport {
         todo!("SecureTransport::is_connected implementation")
     }
 }
+
+/// An operation waiting in a peer queue and whoever awaits its delivery
+struct Queued {
+    operation: ReplicationOperation,
+    delivered: oneshot::Sender<Result<()>>,
+}
+
+/// Operations waiting to be delivered to a single peer
+#[derive(Default)]
+struct PeerQueue {
+    operations: Mutex<VecDeque<Queued>>,
+    /// Signaled when an operation is queued
+    queued: Notify,
+    /// Signaled when an operation leaves the queue
+    dequeued: Notify,
+}
+
+/// Outcome of an operation handed to [`BoundedTransport::submit`]
+///
+/// Resolves once the peer accepted or refused the operation, or once it was
+/// dropped from a full queue. Dropping the handle does not cancel delivery.
+pub struct Delivery(oneshot::Receiver<Result<()>>);
+
+impl Delivery {
+    /// Wait for the operation to be delivered
+    pub async fn wait(self) -> Result<()> {
+        self.0.await.unwrap_or_else(|_| {
+            Err(ReplicationError::Generic(
+                "operation was discarded before delivery".to_string(),
+            ))
+        })
+    }
+}
+
+/// Transport wrapper keeping a bounded outbound queue per peer
+///
+/// Operations are handed to the wrapped transport by one worker per peer,
+/// so a slow peer only fills its own queue. Once a queue reaches its
+/// capacity the configured [`QueueFullPolicy`] applies.
+///
+/// [`ReplicationTransport::send_operation`] returns as soon as the operation
+/// is queued; delivery failures are counted in the replication metrics, and
+/// callers that need the outcome use [`BoundedTransport::submit`] instead.
+pub struct BoundedTransport {
+    inner: Arc<dyn ReplicationTransport>,
+    config: OutboundQueueConfig,
+    block_timeout: Duration,
+    metrics: SharedMetrics,
+    /// Peers that broadcasts are delivered to, whether or not they have a queue yet
+    peers: Mutex<HashSet<NodeId>>,
+    queues: Mutex<HashMap<NodeId, (Arc<PeerQueue>, JoinHandle<()>)>>,
+}
+
+impl BoundedTransport {
+    /// Wrap `inner`, waiting at most `block_timeout` under [`QueueFullPolicy::Block`]
+    pub fn new(
+        inner: Arc<dyn ReplicationTransport>,
+        config: OutboundQueueConfig,
+        block_timeout: Duration,
+        metrics: SharedMetrics,
+    ) -> Self {
+        Self {
+            inner,
+            config,
+            block_timeout,
+            metrics,
+            peers: Mutex::new(HashSet::new()),
+            queues: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Include `node_id` in broadcasts
+    pub fn add_peer(&self, node_id: &NodeId) {
+        self.peers.lock().unwrap().insert(node_id.clone());
+    }
+
+    /// Stop broadcasting to `node_id` and discard the operations queued for it
+    pub fn remove_peer(&self, node_id: &NodeId) {
+        self.peers.lock().unwrap().remove(node_id);
+        self.discard_queue(node_id);
+    }
+
+    /// Number of operations waiting for `node_id`
+    pub fn queue_depth(&self, node_id: &NodeId) -> usize {
+        self.queues
+            .lock()
+            .unwrap()
+            .get(node_id)
+            .map_or(0, |(queue, _)| queue.operations.lock().unwrap().len())
+    }
+
+    /// Queue `operation` for `node_id`, returning a handle on its delivery
+    pub async fn submit(
+        &self,
+        node_id: &NodeId,
+        operation: ReplicationOperation,
+    ) -> Result<Delivery> {
+        let (delivered, delivery) = oneshot::channel();
+        self.enqueue(
+            node_id,
+            Queued {
+                operation,
+                delivered,
+            },
+        )
+        .await?;
+        Ok(Delivery(delivery))
+    }
+
+    /// Queue of `node_id`, starting its delivery worker on first use
+    fn queue(&self, node_id: &NodeId) -> Arc<PeerQueue> {
+        let mut queues = self.queues.lock().unwrap();
+        if let Some((queue, _)) = queues.get(node_id) {
+            return queue.clone();
+        }
+
+        let queue = Arc::new(PeerQueue::default());
+        let worker = tokio::spawn(deliver(
+            node_id.clone(),
+            queue.clone(),
+            self.inner.clone(),
+            self.metrics.clone(),
+        ));
+        queues.insert(node_id.clone(), (queue.clone(), worker));
+        queue
+    }
+
+    /// Stop the worker of `node_id`, failing the deliveries still queued
+    fn discard_queue(&self, node_id: &NodeId) {
+        if let Some((queue, worker)) = self.queues.lock().unwrap().remove(node_id) {
+            worker.abort();
+            let pending = std::mem::take(&mut *queue.operations.lock().unwrap());
+            self.metrics
+                .adjust_outbound_queue_depth(-(pending.len() as i64));
+        }
+    }
+
+    async fn enqueue(&self, node_id: &NodeId, queued: Queued) -> Result<()> {
+        let queue = self.queue(node_id);
+        let deadline = tokio::time::Instant::now() + self.block_timeout;
+
+        loop {
+            {
+                let mut operations = queue.operations.lock().unwrap();
+                if operations.len() < self.config.capacity {
+                    operations.push_back(queued);
+                    self.metrics.adjust_outbound_queue_depth(1);
+                    queue.queued.notify_one();
+                    return Ok(());
+                }
+
+                match self.config.policy {
+                    QueueFullPolicy::DropOldest => {
+                        if let Some(dropped) = operations.pop_front() {
+                            let _ = dropped.delivered.send(Err(ReplicationError::QueueFull {
+                                node_id: node_id.clone(),
+                                capacity: self.config.capacity,
+                            }));
+                        }
+                        operations.push_back(queued);
+                        self.metrics.increment_operations_dropped();
+                        warn!(
+                            "Outbound queue for {} is full, dropped oldest operation",
+                            node_id
+                        );
+                        return Ok(());
+                    }
+                    QueueFullPolicy::Fail => {
+                        return Err(ReplicationError::QueueFull {
+                            node_id: node_id.clone(),
+                            capacity: self.config.capacity,
+                        });
+                    }
+                    QueueFullPolicy::Block => {}
+                }
+            }
+
+            debug!("Outbound queue for {} is full, waiting", node_id);
+            if tokio::time::timeout_at(deadline, queue.dequeued.notified())
+                .await
+                .is_err()
+            {
+                return Err(ReplicationError::Timeout);
+            }
+        }
+    }
+}
+
+/// Hand queued operations for `node_id` to `inner` one at a time
+async fn deliver(
+    node_id: NodeId,
+    queue: Arc<PeerQueue>,
+    inner: Arc<dyn ReplicationTransport>,
+    metrics: SharedMetrics,
+) {
+    loop {
+        let next = queue.operations.lock().unwrap().pop_front();
+        let Some(Queued {
+            operation,
+            delivered,
+        }) = next
+        else {
+            queue.queued.notified().await;
+            continue;
+        };
+        metrics.adjust_outbound_queue_depth(-1);
+        queue.dequeued.notify_one();
+
+        let result = inner.send_operation(&node_id, operation).await;
+        match &result {
+            Ok(()) => metrics.increment_operations_replicated(),
+            Err(e) => {
+                warn!("Failed to replicate operation to {}: {}", node_id, e);
+                metrics.increment_operations_failed();
+            }
+        }
+        let _ = delivered.send(result);
+    }
+}
+
+impl Drop for BoundedTransport {
+    fn drop(&mut self) {
+        for (queue, worker) in self.queues.get_mut().unwrap().values() {
+            worker.abort();
+            let pending = queue.operations.lock().unwrap().len();
+            self.metrics.adjust_outbound_queue_depth(-(pending as i64));
+        }
+    }
+}
+
+#[async_trait]
+impl ReplicationTransport for BoundedTransport {
+    async fn send_operation(
+        &self,
+        node_id: &NodeId,
+        operation: ReplicationOperation,
+    ) -> Result<()> {
+        self.submit(node_id, operation).await.map(drop)
+    }
+
+    /// Queue `operation` for every peer, including peers not sent to before
+    ///
+    /// A peer whose queue refuses the operation does not stop the others
+    /// from receiving it; the refusals are reported together.
+    async fn broadcast_operation(&self, operation: ReplicationOperation) -> Result<()> {
+        let mut peers = self
+            .peers
+            .lock()
+            .unwrap()
+            .iter()
+            .chain(self.queues.lock().unwrap().keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        peers.sort();
+        peers.dedup();
+
+        let attempted = peers.len();
+        let mut failed = Vec::new();
+        for node_id in peers {
+            if let Err(e) = self.send_operation(&node_id, operation.clone()).await {
+                failed.push((node_id, e));
+            }
+        }
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(ReplicationError::Broadcast { attempted, failed })
+        }
+    }
+
+    async fn connect(&self, node_id: &NodeId, address: &str, port: u16) -> Result<()> {
+        self.inner.connect(node_id, address, port).await?;
+        self.add_peer(node_id);
+        self.queue(node_id);
+        Ok(())
+    }
+
+    async fn disconnect(&self, node_id: &NodeId) -> Result<()> {
+        self.discard_queue(node_id);
+        self.inner.disconnect(node_id).await
+    }
+
+    async fn is_connected(&self, node_id: &NodeId) -> bool {
+        self.inner.is_connected(node_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::ReplicationMetrics;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use tokio::sync::Semaphore;
+
+    /// Peer that only accepts an operation once a permit is released
+    #[derive(Default)]
+    struct SlowPeer {
+        permits: Semaphore,
+        attempts: AtomicUsize,
+        delivered: Mutex<Vec<u8>>,
+        refuse: AtomicBool,
+    }
+
+    #[async_trait]
+    impl ReplicationTransport for SlowPeer {
+        async fn send_operation(
+            &self,
+            node_id: &NodeId,
+            operation: ReplicationOperation,
+        ) -> Result<()> {
+            self.attempts.fetch_add(1, Ordering::SeqCst);
+            self.permits.acquire().await.unwrap().forget();
+            if self.refuse.load(Ordering::SeqCst) {
+                return Err(ReplicationError::NodeUnreachable {
+                    node_id: node_id.clone(),
+                });
+            }
+            if let ReplicationOperation::Delete { key } = operation {
+                self.delivered.lock().unwrap().push(key[0]);
+            }
+            Ok(())
+        }
+
+        async fn broadcast_operation(&self, _operation: ReplicationOperation) -> Result<()> {
+            Ok(())
+        }
+
+        async fn connect(&self, _node_id: &NodeId, _address: &str, _port: u16) -> Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&self, _node_id: &NodeId) -> Result<()> {
+            Ok(())
+        }
+
+        async fn is_connected(&self, _node_id: &NodeId) -> bool {
+            true
+        }
+    }
+
+    fn operation(id: u8) -> ReplicationOperation {
+        ReplicationOperation::Delete { key: vec![id] }
+    }
+
+    /// Transport with a queue of 2 in front of a stalled peer that already holds operation 1
+    async fn stalled(
+        policy: QueueFullPolicy,
+    ) -> (Arc<BoundedTransport>, Arc<SlowPeer>, SharedMetrics) {
+        let peer = Arc::new(SlowPeer::default());
+        let metrics: SharedMetrics = Arc::new(ReplicationMetrics::new());
+        let transport = Arc::new(BoundedTransport::new(
+            peer.clone(),
+            OutboundQueueConfig {
+                capacity: 2,
+                policy,
+            },
+            Duration::from_secs(5),
+            metrics.clone(),
+        ));
+        let node_id = "node2".to_string();
+
+        transport
+            .send_operation(&node_id, operation(1))
+            .await
+            .unwrap();
+        while peer.attempts.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        transport
+            .send_operation(&node_id, operation(2))
+            .await
+            .unwrap();
+        transport
+            .send_operation(&node_id, operation(3))
+            .await
+            .unwrap();
+        assert_eq!(transport.queue_depth(&node_id), 2);
+
+        (transport, peer, metrics)
+    }
+
+    async fn drain(peer: &SlowPeer, count: usize) {
+        peer.permits.add_permits(count);
+        while peer.delivered.lock().unwrap().len() < count {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fail_policy_rejects_when_full() {
+        let (transport, peer, _) = stalled(QueueFullPolicy::Fail).await;
+        let node_id = "node2".to_string();
+
+        assert!(matches!(
+            transport.send_operation(&node_id, operation(4)).await,
+            Err(ReplicationError::QueueFull { capacity: 2, .. })
+        ));
+        drain(&peer, 3).await;
+        assert_eq!(*peer.delivered.lock().unwrap(), vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_policy_discards_oldest() {
+        let (transport, peer, metrics) = stalled(QueueFullPolicy::DropOldest).await;
+        let node_id = "node2".to_string();
+
+        transport
+            .send_operation(&node_id, operation(4))
+            .await
+            .unwrap();
+        assert_eq!(transport.queue_depth(&node_id), 2);
+        assert_eq!(metrics.snapshot().operations_dropped, 1);
+
+        drain(&peer, 3).await;
+        assert_eq!(*peer.delivered.lock().unwrap(), vec![1, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_operation_reports_queue_full() {
+        let (transport, peer, _) = stalled(QueueFullPolicy::DropOldest).await;
+        let node_id = "node2".to_string();
+
+        // Queue holds [2, 3]: each submission pushes out the oldest entry
+        let fourth = transport.submit(&node_id, operation(4)).await.unwrap();
+        transport.submit(&node_id, operation(5)).await.unwrap();
+        let sixth = transport.submit(&node_id, operation(6)).await.unwrap();
+        assert!(matches!(
+            fourth.wait().await,
+            Err(ReplicationError::QueueFull { capacity: 2, .. })
+        ));
+
+        drain(&peer, 3).await;
+        sixth.wait().await.unwrap();
+        assert_eq!(*peer.delivered.lock().unwrap(), vec![1, 5, 6]);
+    }
+
+    #[tokio::test]
+    async fn test_submit_reports_delivery_failure() {
+        let (transport, peer, metrics) = stalled(QueueFullPolicy::Fail).await;
+        let node_id = "node2".to_string();
+        peer.refuse.store(true, Ordering::SeqCst);
+        peer.permits.add_permits(3);
+        while transport.queue_depth(&node_id) > 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let delivery = transport.submit(&node_id, operation(4)).await.unwrap();
+        peer.permits.add_permits(1);
+        assert!(matches!(
+            delivery.wait().await,
+            Err(ReplicationError::NodeUnreachable { ref node_id }) if node_id == "node2"
+        ));
+        assert_eq!(metrics.snapshot().operations_failed, 4);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_reaches_every_peer() {
+        let (transport, peer, _) = stalled(QueueFullPolicy::Fail).await;
+        // node3 has never been sent anything, so it has no queue yet
+        transport.add_peer(&"node3".to_string());
+
+        match transport.broadcast_operation(operation(4)).await {
+            Err(ReplicationError::Broadcast { attempted, failed }) => {
+                assert_eq!(attempted, 2);
+                assert_eq!(failed.len(), 1);
+                assert_eq!(failed[0].0, "node2");
+                assert!(matches!(failed[0].1, ReplicationError::QueueFull { .. }));
+            }
+            result => panic!("unexpected broadcast result: {:?}", result),
+        }
+
+        drain(&peer, 4).await;
+        let mut delivered = peer.delivered.lock().unwrap().clone();
+        delivered.sort();
+        assert_eq!(delivered, vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_block_policy_waits_for_room() {
+        let (transport, peer, _) = stalled(QueueFullPolicy::Block).await;
+
+        let blocked = tokio::spawn({
+            let transport = transport.clone();
+            async move {
+                transport
+                    .send_operation(&"node2".to_string(), operation(4))
+                    .await
+            }
+        });
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert!(!blocked.is_finished());
+
+        // Delivering operation 1 lets the worker pick up 2, freeing a slot
+        drain(&peer, 1).await;
+        blocked.await.unwrap().unwrap();
+        drain(&peer, 4).await;
+        assert_eq!(*peer.delivered.lock().unwrap(), vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_queue_depth_metrics_track_slow_peer() {
+        let (_transport, peer, metrics) = stalled(QueueFullPolicy::Fail).await;
+        assert_eq!(metrics.snapshot().outbound_queue_depth, 2);
+
+        drain(&peer, 3).await;
+        assert_eq!(metrics.snapshot().outbound_queue_depth, 0);
+        assert_eq!(metrics.snapshot().operations_replicated, 3);
+    }
+}
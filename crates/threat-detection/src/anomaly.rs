@@ -1,9 +1,29 @@
 //! Anomaly detection module
 
+use crate::config::AnomalyDetectionConfig;
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::RwLock;
 
 /// Anomaly detector
-pub struct AnomalyDetector;
+pub struct AnomalyDetector {
+    /// Standard deviations from the baseline beyond which a value is anomalous
+    threshold: f64,
+    /// Smoothing factor of the moving averages
+    alpha: f64,
+    /// Samples a baseline needs before values are scored
+    warmup_samples: usize,
+    /// Running baselines per metric
+    baselines: RwLock<HashMap<String, Baseline>>,
+}
+
+/// Exponentially-weighted moving average and variance of a metric
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Baseline {
+    pub mean: f64,
+    pub variance: f64,
+    pub samples: usize,
+}
 
 /// Anomaly score
 pub type AnomalyScore = f64;
@@ -37,7 +57,69 @@ pub enum AnomalyType {
 impl AnomalyDetector {
     /// Create new anomaly detector
     pub fn new() -> Self {
-        Self
+        Self::with_config(&AnomalyDetectionConfig::default())
+    }
+
+    /// Create new anomaly detector from configuration
+    pub fn with_config(config: &AnomalyDetectionConfig) -> Self {
+        Self {
+            threshold: config.statistical_threshold,
+            alpha: config.ewma_alpha.clamp(f64::EPSILON, 1.0),
+            warmup_samples: config.min_samples,
+            baselines: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Score a metric value against its running baseline
+    ///
+    /// Values within the configured number of standard deviations score
+    /// 0.0, as does every value while the baseline is still warming up.
+    /// Beyond the threshold the score grows from 0.5 and is capped at 1.0.
+    /// Scoring does not update the baseline.
+    pub fn score(&self, metric: &str, value: f64) -> AnomalyScore {
+        let Some(baseline) = self.baseline(metric) else {
+            return 0.0;
+        };
+        if baseline.samples < self.warmup_samples {
+            return 0.0;
+        }
+
+        let std_dev = baseline.variance.sqrt();
+        let deviation = (value - baseline.mean).abs();
+        if std_dev > 0.0 {
+            let z_score = deviation / std_dev;
+            if z_score > self.threshold {
+                (z_score / (2.0 * self.threshold)).min(1.0)
+            } else {
+                0.0
+            }
+        } else if deviation > f64::EPSILON {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Add a value to the running baseline of a metric
+    pub fn update_baseline(&self, metric: &str, value: f64) {
+        let mut baselines = self.baselines.write().unwrap();
+        let baseline = baselines.entry(metric.to_string()).or_default();
+
+        if baseline.samples == 0 {
+            baseline.mean = value;
+            baseline.variance = 0.0;
+        } else {
+            let diff = value - baseline.mean;
+            let increment = self.alpha * diff;
+            baseline.mean += increment;
+            baseline.variance = (1.0 - self.alpha) * (baseline.variance + diff * increment);
+        }
+        baseline.samples += 1;
+    }
+
+    /// Current baseline of a metric
+    pub fn baseline(&self, metric: &str) -> Option<Baseline> {
+        self.baselines.read().unwrap().get(metric).copied()
     }
 
     /// Detect anomalies in data
@@ -83,6 +165,30 @@ impl AnomalyDetector {
             is_anomalous: overall_score > 0.5,
         }
     }
+
+    /// Score how far a single value deviates from a baseline
+    pub fn score_value(&self, baseline: &[f64], value: f64) -> AnomalyScore {
+        if baseline.len() < 2 {
+            return 0.0;
+        }
+
+        let mean = baseline.iter().sum::<f64>() / baseline.len() as f64;
+        let variance = baseline.iter()
+            .map(|x| (x - mean).powi(2))
+            .sum::<f64>() / baseline.len() as f64;
+        let std_dev = variance.sqrt();
+
+        if std_dev > 0.0 {
+            let z_score = (value - mean).abs() / std_dev;
+            if z_score > 1.5 {
+                return (z_score / 3.0).min(1.0);
+            }
+        } else if value != mean {
+            return 1.0;
+        }
+
+        0.0
+    }
 }
 
 #[cfg(test)]
@@ -195,6 +301,48 @@ mod tests {
         assert!(result.overall_score > 0.5);
     }
 
+    #[test]
+    fn test_score_value() {
+        let detector = AnomalyDetector::new();
+        let baseline = vec![1.0, 1.1, 0.9, 1.05, 0.95];
+
+        assert_eq!(detector.score_value(&baseline, 1.0), 0.0);
+        assert_eq!(detector.score_value(&baseline, 10.0), 1.0);
+        assert_eq!(detector.score_value(&[5.0, 5.0], 5.0), 0.0);
+        assert_eq!(detector.score_value(&[5.0, 5.0], 6.0), 1.0);
+        assert_eq!(detector.score_value(&[5.0], 100.0), 0.0);
+    }
+
+    #[test]
+    fn test_ewma_baseline() {
+        let detector = AnomalyDetector::new();
+        let stable = [100.0, 102.0, 98.0, 101.0, 99.0, 100.0, 103.0, 97.0, 100.0, 101.0, 99.0, 100.0];
+
+        // Nothing is scored during the warm-up period
+        for &value in &stable[..9] {
+            assert_eq!(detector.score("logins", value), 0.0);
+            detector.update_baseline("logins", value);
+        }
+        assert_eq!(detector.score("logins", 1000.0), 0.0);
+
+        for &value in &stable[9..] {
+            detector.update_baseline("logins", value);
+        }
+        let baseline = detector.baseline("logins").unwrap();
+        assert_eq!(baseline.samples, stable.len());
+        assert!((baseline.mean - 100.0).abs() < 2.0);
+
+        // Typical values are normal, the spike is anomalous
+        for value in [99.0, 100.0, 101.0] {
+            assert_eq!(detector.score("logins", value), 0.0, "value {}", value);
+        }
+        let spike = detector.score("logins", 250.0);
+        assert!(spike >= 0.5 && spike <= 1.0);
+
+        // Metrics have independent baselines
+        assert_eq!(detector.score("messages", 250.0), 0.0);
+    }
+
     #[test]
     fn test_anomaly_score_range() {
         let detector = AnomalyDetector::new();
@@ -11,10 +11,13 @@ use crate::{
     error::Result,
 };
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, info, warn};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
 use chrono::{Utc, Duration, Timelike};
+use serde::{Deserialize, Serialize};
 
 /// Behavioral analyzer
 ///
@@ -29,12 +32,14 @@ pub struct BehavioralAnalyzer {
     stats: Arc<RwLock<BehavioralStats>>,
     /// Running state
     is_running: Arc<RwLock<bool>>,
+    /// Periodic profile snapshot task
+    snapshot_task: std::sync::Mutex<Option<JoinHandle<()>>>,
 }
 
 /// Behavior profile for a user
 ///
 /// Contains learned patterns of normal behavior for a specific user.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BehaviorProfile {
     /// User identifier
     pub user_id: String,
@@ -55,7 +60,7 @@ pub struct BehaviorProfile {
 }
 
 /// Email sending patterns
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SendingPatterns {
     /// Average emails per day
     pub avg_emails_per_day: f64,
@@ -70,20 +75,21 @@ pub struct SendingPatterns {
 }
 
 /// Communication patterns
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CommunicationPatterns {
     /// Internal vs external communication ratio
     pub internal_external_ratio: f64,
     /// Reply vs new email ratio
     pub reply_new_ratio: f64,
     /// Average response time
+    #[serde(with = "duration_millis")]
     pub avg_response_time: Duration,
     /// Communication network
     pub communication_network: HashMap<String, f64>,
 }
 
 /// Content patterns
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ContentPatterns {
     /// Average email length
     pub avg_email_length: f64,
@@ -96,7 +102,7 @@ pub struct ContentPatterns {
 }
 
 /// Timing patterns
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TimingPatterns {
     /// Active hours distribution
     pub active_hours: HashMap<u32, f64>,
@@ -109,7 +115,7 @@ pub struct TimingPatterns {
 }
 
 /// Attachment usage patterns
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AttachmentUsage {
     /// Frequency of attachments
     pub attachment_frequency: f64,
@@ -120,7 +126,7 @@ pub struct AttachmentUsage {
 }
 
 /// Formatting patterns
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FormattingPatterns {
     /// HTML vs plain text ratio
     pub html_plain_ratio: f64,
@@ -193,6 +199,7 @@ impl BehavioralAnalyzer {
             profiles: Arc::new(RwLock::new(HashMap::new())),
             stats: Arc::new(RwLock::new(BehavioralStats::default())),
             is_running: Arc::new(RwLock::new(false)),
+            snapshot_task: std::sync::Mutex::new(None),
         })
     }
 
@@ -206,8 +213,17 @@ impl BehavioralAnalyzer {
             return Ok(());
         }
 
-        // Load existing profiles
-        self.load_profiles().await?;
+        // Load existing profiles and snapshot them periodically
+        if let Some(path) = &self.config.profiles_path {
+            match self.load_profiles(Path::new(path)).await {
+                Ok(_) => {}
+                Err(crate::error::ThreatDetectionError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                    debug!("No saved behavior profiles at {}", path);
+                }
+                Err(e) => return Err(e),
+            }
+            self.start_snapshots(path.clone());
+        }
 
         *running = true;
         info!("Behavioral analyzer started");
@@ -226,7 +242,12 @@ impl BehavioralAnalyzer {
         }
 
         // Save profiles
-        self.save_profiles().await?;
+        if let Some(task) = self.snapshot_task.lock().unwrap().take() {
+            task.abort();
+        }
+        if let Some(path) = &self.config.profiles_path {
+            self.save_profiles(Path::new(path)).await?;
+        }
 
         *running = false;
         info!("Behavioral analyzer stopped");
@@ -501,6 +522,7 @@ impl BehavioralAnalyzer {
         }
 
         profiles.insert(email_context.sender.clone(), profile);
+        self.stats.write().await.total_profiles = profiles.len();
 
         info!("Created initial behavior profile for user: {}", email_context.sender);
 
@@ -537,18 +559,64 @@ impl BehavioralAnalyzer {
         metadata
     }
 
-    /// Load behavior profiles
-    async fn load_profiles(&self) -> Result<()> {
-        debug!("Loading behavior profiles");
-        // TODO: Load from persistent storage
-        Ok(())
+    /// Load behavior profiles from disk
+    ///
+    /// Loaded profiles replace in-memory profiles of the same user. Profiles
+    /// not updated within the configured staleness period are discarded.
+    ///
+    /// # Returns
+    /// Number of profiles loaded
+    pub async fn load_profiles(&self, path: &Path) -> Result<usize> {
+        debug!("Loading behavior profiles from {}", path.display());
+
+        let contents = tokio::fs::read(path).await?;
+        let saved: Vec<BehaviorProfile> = serde_json::from_slice(&contents)?;
+        let cutoff = Duration::from_std(self.config.profile_staleness)
+            .ok()
+            .and_then(|staleness| Utc::now().checked_sub_signed(staleness));
+
+        let mut profiles = self.profiles.write().await;
+        let mut loaded = 0;
+        for profile in saved {
+            if cutoff.is_none_or(|cutoff| profile.last_updated >= cutoff) {
+                profiles.insert(profile.user_id.clone(), profile);
+                loaded += 1;
+            }
+        }
+        self.stats.write().await.total_profiles = profiles.len();
+
+        info!("Loaded {} behavior profiles from {}", loaded, path.display());
+        Ok(loaded)
     }
 
-    /// Save behavior profiles
-    async fn save_profiles(&self) -> Result<()> {
-        debug!("Saving behavior profiles");
-        // TODO: Save to persistent storage
-        Ok(())
+    /// Save behavior profiles to disk
+    ///
+    /// # Returns
+    /// Number of profiles saved
+    pub async fn save_profiles(&self, path: &Path) -> Result<usize> {
+        debug!("Saving behavior profiles to {}", path.display());
+        write_profiles(&self.profiles, path).await
+    }
+
+    /// Start saving the profiles every profile update interval
+    fn start_snapshots(&self, path: String) {
+        let profiles = self.profiles.clone();
+        let period = self.config.profile_update_interval;
+
+        let task = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = write_profiles(&profiles, Path::new(&path)).await {
+                    error!("Failed to save behavior profiles to {}: {}", path, e);
+                }
+            }
+        });
+
+        if let Some(previous) = self.snapshot_task.lock().unwrap().replace(task) {
+            previous.abort();
+        }
     }
 
     /// Get behavioral analysis statistics
@@ -557,6 +625,39 @@ impl BehavioralAnalyzer {
     }
 }
 
+/// Write the profiles to a temporary file and move it into place
+async fn write_profiles(
+    profiles: &RwLock<HashMap<String, BehaviorProfile>>,
+    path: &Path,
+) -> Result<usize> {
+    let (contents, count) = {
+        let profiles = profiles.read().await;
+        let snapshot = profiles.values().collect::<Vec<_>>();
+        (serde_json::to_vec(&snapshot)?, snapshot.len())
+    };
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    tokio::fs::write(&tmp_path, contents).await?;
+    tokio::fs::rename(&tmp_path, path).await?;
+
+    Ok(count)
+}
+
+/// Serialize a `chrono::Duration` as whole milliseconds
+mod duration_millis {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(duration.num_milliseconds())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        i64::deserialize(deserializer).map(Duration::milliseconds)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -680,4 +781,42 @@ mod tests {
         // Should have created profiles
         assert!(stats.total_profiles > 0);
     }
+
+    #[tokio::test]
+    async fn test_profile_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles.json");
+        let config = BehavioralAnalysisConfig::default();
+
+        // Learn a daytime sending pattern
+        let analyzer = BehavioralAnalyzer::new(&config).await.unwrap();
+        let mut context = create_test_email_context();
+        context.timestamp = context.timestamp.with_hour(10).unwrap();
+        for _ in 0..5 {
+            assert!(analyzer.analyze_email(&context).await.unwrap().is_none());
+        }
+        assert_eq!(analyzer.save_profiles(&path).await.unwrap(), 1);
+
+        // A fresh analyzer still knows the sender after reloading
+        let reloaded = BehavioralAnalyzer::new(&config).await.unwrap();
+        assert_eq!(reloaded.load_profiles(&path).await.unwrap(), 1);
+        assert_eq!(reloaded.get_stats().await.total_profiles, 1);
+        assert!(reloaded.analyze_email(&context).await.unwrap().is_none());
+
+        let mut night = context.clone();
+        night.timestamp = context.timestamp.with_hour(3).unwrap();
+        let event = reloaded.analyze_email(&night).await.unwrap().unwrap();
+        assert_eq!(event.threat_type, ThreatType::BehavioralAnomaly);
+
+        // Without the saved profiles the first email only creates a profile
+        let cold = BehavioralAnalyzer::new(&config).await.unwrap();
+        assert!(cold.analyze_email(&night).await.unwrap().is_none());
+
+        // Stale profiles are discarded
+        let mut config = BehavioralAnalysisConfig::default();
+        config.profile_staleness = std::time::Duration::ZERO;
+        let stale = BehavioralAnalyzer::new(&config).await.unwrap();
+        assert_eq!(stale.load_profiles(&path).await.unwrap(), 0);
+        assert_eq!(stale.get_stats().await.total_profiles, 0);
+    }
 }
@@ -0,0 +1,371 @@
+//! Brute-Force Login Detection Module
+//!
+//! This module tracks failed authentication attempts per source IP and per
+//! target account over a sliding time window, and reports a brute-force
+//! threat when either exceeds its configured limit.
+//!
+//! Failed logins are fed from `SecurityEvent::Authentication` events with
+//! `success: false`, as published by the security manager; see
+//! [`ThreatDetector::watch_logins`](crate::ThreatDetector::watch_logins).
+
+use crate::{
+    ThreatEvent, ThreatType, ThreatSeverity,
+    config::BruteForceConfig,
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+use chrono::{DateTime, Utc};
+
+/// Brute-force login detector
+///
+/// Keeps the failed logins seen within the configured window and raises at
+/// most one event per source IP and account until the window has elapsed.
+pub struct BruteForceDetector {
+    /// Configuration
+    config: BruteForceConfig,
+    /// Failure windows and alert state
+    state: RwLock<BruteForceState>,
+}
+
+#[derive(Debug, Default)]
+struct BruteForceState {
+    /// Failed logins per source IP
+    by_ip: HashMap<String, VecDeque<Failure>>,
+    /// Failed logins per target account
+    by_account: HashMap<String, VecDeque<Failure>>,
+    /// Last alert time per `ip:` or `account:` key
+    alerted: HashMap<String, DateTime<Utc>>,
+}
+
+/// Authentication attempt reported by the authentication layer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginAttempt {
+    /// Account the login was attempted for
+    pub account: String,
+    /// Address the attempt came from
+    pub source_ip: String,
+    /// Whether the credentials were accepted
+    pub success: bool,
+    /// Time of the attempt
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct Failure {
+    account: String,
+    source_ip: String,
+    timestamp: DateTime<Utc>,
+}
+
+/// Shape of a detected brute-force attack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttackPattern {
+    /// Many source IPs targeting one account
+    Distributed,
+    /// One source IP trying many accounts
+    PasswordSpraying,
+    /// One source IP repeatedly targeting one account
+    SingleSource,
+}
+
+impl AttackPattern {
+    /// Name used in event metadata
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttackPattern::Distributed => "distributed",
+            AttackPattern::PasswordSpraying => "password_spraying",
+            AttackPattern::SingleSource => "single_source",
+        }
+    }
+}
+
+impl BruteForceDetector {
+    /// Create new brute-force detector
+    ///
+    /// # Arguments
+    /// * `config` - Brute-force detection configuration
+    pub fn new(config: &BruteForceConfig) -> Self {
+        Self {
+            config: config.clone(),
+            state: RwLock::new(BruteForceState::default()),
+        }
+    }
+
+    /// Record an authentication attempt
+    ///
+    /// Successful attempts are ignored; failures are passed to
+    /// [`BruteForceDetector::record_failure`].
+    pub async fn record_authentication(
+        &self,
+        account: &str,
+        source_ip: &str,
+        success: bool,
+        timestamp: DateTime<Utc>,
+    ) -> Option<ThreatEvent> {
+        if success {
+            None
+        } else {
+            self.record_failure(account, source_ip, timestamp).await
+        }
+    }
+
+    /// Record a failed login
+    ///
+    /// # Arguments
+    /// * `account` - Account the login was attempted for
+    /// * `source_ip` - Address the attempt came from
+    /// * `timestamp` - Time of the attempt
+    ///
+    /// # Returns
+    /// A `BruteForce` threat event when the failures within the window
+    /// exceed the configured limits and no alert is active for the source IP
+    /// or account
+    pub async fn record_failure(
+        &self,
+        account: &str,
+        source_ip: &str,
+        timestamp: DateTime<Utc>,
+    ) -> Option<ThreatEvent> {
+        let cutoff = timestamp - self.window();
+        let failure = Failure {
+            account: account.to_string(),
+            source_ip: source_ip.to_string(),
+            timestamp,
+        };
+
+        let mut state = self.state.write().await;
+        let state = &mut *state;
+        state.alerted.retain(|_, alerted_at| *alerted_at > cutoff);
+
+        let ip_failures = push_failure(&mut state.by_ip, source_ip, failure.clone(), cutoff);
+        let account_failures = push_failure(&mut state.by_account, account, failure, cutoff);
+
+        let ip_count = ip_failures.len();
+        let accounts = ip_failures
+            .iter()
+            .map(|failure| failure.account.as_str())
+            .collect::<HashSet<_>>()
+            .len();
+        let account_count = account_failures.len();
+        let source_ips = account_failures
+            .iter()
+            .map(|failure| failure.source_ip.clone())
+            .collect::<HashSet<_>>();
+
+        let (pattern, failures, limit) = if account_count > self.config.max_failures_per_account
+            && source_ips.len() >= self.config.distributed_min_ips
+        {
+            (AttackPattern::Distributed, account_count, self.config.max_failures_per_account)
+        } else if ip_count > self.config.max_failures_per_ip {
+            let pattern = if accounts > 1 {
+                AttackPattern::PasswordSpraying
+            } else {
+                AttackPattern::SingleSource
+            };
+            (pattern, ip_count, self.config.max_failures_per_ip)
+        } else if account_count > self.config.max_failures_per_account {
+            (AttackPattern::SingleSource, account_count, self.config.max_failures_per_account)
+        } else {
+            return None;
+        };
+
+        let ip_key = format!("ip:{}", source_ip);
+        let account_key = format!("account:{}", account);
+        let suppressed = match pattern {
+            AttackPattern::Distributed => state.alerted.contains_key(&account_key),
+            AttackPattern::PasswordSpraying => state.alerted.contains_key(&ip_key),
+            AttackPattern::SingleSource => {
+                state.alerted.contains_key(&ip_key) || state.alerted.contains_key(&account_key)
+            }
+        };
+        if suppressed {
+            debug!("Brute-force alert for {} from {} already active", account, source_ip);
+            return None;
+        }
+        state.alerted.insert(ip_key, timestamp);
+        state.alerted.insert(account_key, timestamp);
+
+        warn!(
+            "Brute-force attack detected ({}): {} failures for {} from {}",
+            pattern.as_str(),
+            failures,
+            account,
+            source_ip
+        );
+
+        let mut source_ips = source_ips.into_iter().collect::<Vec<_>>();
+        source_ips.sort();
+
+        let mut metadata = HashMap::new();
+        metadata.insert("attack_pattern".to_string(), serde_json::json!(pattern.as_str()));
+        metadata.insert("failures".to_string(), serde_json::json!(failures));
+        metadata.insert("window_secs".to_string(), serde_json::json!(self.config.window.as_secs()));
+        metadata.insert("source_ips".to_string(), serde_json::json!(source_ips));
+        metadata.insert("distinct_accounts".to_string(), serde_json::json!(accounts));
+
+        Some(ThreatEvent {
+            id: format!("bruteforce-{}", uuid::Uuid::new_v4()),
+            threat_type: ThreatType::BruteForce,
+            severity: match pattern {
+                AttackPattern::SingleSource => ThreatSeverity::Medium,
+                AttackPattern::Distributed | AttackPattern::PasswordSpraying => ThreatSeverity::High,
+            },
+            description: format!(
+                "{} failed logins within {}s ({})",
+                failures,
+                self.config.window.as_secs(),
+                pattern.as_str()
+            ),
+            source: source_ip.to_string(),
+            target: Some(account.to_string()),
+            timestamp,
+            metadata,
+            confidence: (failures as f64 / (2 * limit.max(1)) as f64).min(1.0),
+        })
+    }
+
+    /// Drop failures and alerts that fell out of the window
+    pub async fn purge_expired(&self, now: DateTime<Utc>) {
+        let cutoff = now - self.window();
+        let mut state = self.state.write().await;
+        let state = &mut *state;
+
+        for failures in state.by_ip.values_mut().chain(state.by_account.values_mut()) {
+            prune(failures, cutoff);
+        }
+        state.by_ip.retain(|_, failures| !failures.is_empty());
+        state.by_account.retain(|_, failures| !failures.is_empty());
+        state.alerted.retain(|_, alerted_at| *alerted_at > cutoff);
+    }
+
+    /// Purge expired entries once per window until the task is aborted
+    pub async fn run_purge(&self) {
+        let period = self.config.window.max(std::time::Duration::from_secs(1));
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.purge_expired(Utc::now()).await;
+        }
+    }
+
+    fn window(&self) -> chrono::Duration {
+        chrono::Duration::from_std(self.config.window).unwrap_or_else(|_| chrono::Duration::days(365))
+    }
+}
+
+/// Append a failure to a window and drop the entries older than `cutoff`
+fn push_failure<'x>(
+    windows: &'x mut HashMap<String, VecDeque<Failure>>,
+    key: &str,
+    failure: Failure,
+    cutoff: DateTime<Utc>,
+) -> &'x VecDeque<Failure> {
+    let failures = windows.entry(key.to_string()).or_default();
+    failures.push_back(failure);
+    prune(failures, cutoff);
+    failures
+}
+
+fn prune(failures: &mut VecDeque<Failure>, cutoff: DateTime<Utc>) {
+    while failures.front().is_some_and(|failure| failure.timestamp <= cutoff) {
+        failures.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(base: DateTime<Utc>, millis: i64) -> DateTime<Utc> {
+        base + chrono::Duration::milliseconds(millis)
+    }
+
+    #[tokio::test]
+    async fn test_single_source_fires_once() {
+        let detector = BruteForceDetector::new(&BruteForceConfig::default());
+        let base = Utc::now();
+
+        // 20 failures in 10 seconds
+        let mut events = Vec::new();
+        for i in 0..20 {
+            if let Some(event) = detector
+                .record_authentication("john@example.com", "203.0.113.5", false, at(base, i * 500))
+                .await
+            {
+                events.push(event);
+            }
+        }
+
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.threat_type, ThreatType::BruteForce);
+        assert_eq!(event.source, "203.0.113.5");
+        assert_eq!(event.target.as_deref(), Some("john@example.com"));
+        assert_eq!(event.metadata["attack_pattern"], "single_source");
+        assert_eq!(event.metadata["failures"], 11);
+
+        // Successful logins are not counted
+        assert!(detector
+            .record_authentication("john@example.com", "203.0.113.5", true, at(base, 10_000))
+            .await
+            .is_none());
+
+        // Once the window has passed, a new attack alerts again
+        let later = at(base, 120_000);
+        let mut fired = 0;
+        for i in 0..11 {
+            fired += detector
+                .record_failure("john@example.com", "203.0.113.5", at(later, i))
+                .await
+                .into_iter()
+                .count();
+        }
+        assert_eq!(fired, 1);
+    }
+
+    #[tokio::test]
+    async fn test_distributed_and_spraying() {
+        let detector = BruteForceDetector::new(&BruteForceConfig::default());
+        let base = Utc::now();
+
+        // Many addresses, one account
+        let mut events = Vec::new();
+        for i in 0..15 {
+            let ip = format!("198.51.100.{}", i);
+            events.extend(detector.record_failure("admin@example.com", &ip, at(base, i * 100)).await);
+        }
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].metadata["attack_pattern"], "distributed");
+        assert_eq!(events[0].severity, ThreatSeverity::High);
+        assert_eq!(events[0].metadata["source_ips"].as_array().unwrap().len(), 11);
+
+        // One address, many accounts
+        let mut events = Vec::new();
+        for i in 0..15 {
+            let account = format!("user{}@example.com", i);
+            events.extend(detector.record_failure(&account, "192.0.2.1", at(base, i * 100)).await);
+        }
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].metadata["attack_pattern"], "password_spraying");
+        assert_eq!(events[0].metadata["distinct_accounts"], 11);
+    }
+
+    #[tokio::test]
+    async fn test_failures_outside_window_are_ignored() {
+        let detector = BruteForceDetector::new(&BruteForceConfig::default());
+        let base = Utc::now();
+
+        // One failure every 10 seconds never exceeds 10 per minute
+        for i in 0..30 {
+            assert!(detector
+                .record_failure("john@example.com", "203.0.113.5", at(base, i * 10_000))
+                .await
+                .is_none());
+        }
+
+        detector.purge_expired(at(base, 600_000)).await;
+        assert!(detector.state.read().await.by_ip.is_empty());
+    }
+}
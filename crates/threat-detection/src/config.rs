@@ -30,12 +30,21 @@ pub struct ThreatDetectionConfig {
     /// Threat intelligence configuration
     pub intelligence: ThreatIntelligenceConfig,
 
+    /// Brute-force login detection configuration
+    pub brute_force: BruteForceConfig,
+
     /// Maximum number of events to keep in history
     pub max_events_history: usize,
 
     /// Detection interval
     pub detection_interval: Duration,
 
+    /// Window within which related events are correlated
+    pub correlation_window: Duration,
+
+    /// Minimum combined confidence for reporting a threat
+    pub threat_threshold: f64,
+
     /// Alert thresholds
     pub alert_thresholds: AlertThresholds,
 
@@ -61,6 +70,9 @@ pub struct AnomalyDetectionConfig {
     /// Minimum samples required for analysis
     pub min_samples: usize,
 
+    /// Smoothing factor of the per-metric moving averages (0.0 to 1.0)
+    pub ewma_alpha: f64,
+
     /// Features to analyze
     pub features: Vec<String>,
 }
@@ -98,6 +110,28 @@ pub struct BehavioralAnalysisConfig {
 
     /// Profile update interval
     pub profile_update_interval: Duration,
+
+    /// File the profiles are loaded from on start and periodically saved to
+    pub profiles_path: Option<String>,
+
+    /// Profiles not updated for this long are discarded when loading
+    pub profile_staleness: Duration,
+}
+
+/// Brute-force login detection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BruteForceConfig {
+    /// Sliding window over which failed logins are counted
+    pub window: Duration,
+
+    /// Failures from a single IP within the window before alerting
+    pub max_failures_per_ip: usize,
+
+    /// Failures against a single account within the window before alerting
+    pub max_failures_per_account: usize,
+
+    /// Distinct source IPs after which account failures count as distributed
+    pub distributed_min_ips: usize,
 }
 
 /// Threat intelligence configuration
@@ -180,8 +214,11 @@ impl Default for ThreatDetectionConfig {
             patterns: PatternMatchingConfig::default(),
             behavioral: BehavioralAnalysisConfig::default(),
             intelligence: ThreatIntelligenceConfig::default(),
+            brute_force: BruteForceConfig::default(),
             max_events_history: 10000,
             detection_interval: Duration::from_secs(60),
+            correlation_window: Duration::from_secs(15 * 60),
+            threat_threshold: 0.8,
             alert_thresholds: AlertThresholds::default(),
             model_update_interval: Duration::from_secs(3600),
         }
@@ -196,6 +233,7 @@ impl Default for AnomalyDetectionConfig {
             ml_model_path: None,
             window_size: 100,
             min_samples: 10,
+            ewma_alpha: 0.1,
             features: vec![
                 "login_frequency".to_string(),
                 "email_volume".to_string(),
@@ -232,6 +270,19 @@ impl Default for BehavioralAnalysisConfig {
                 "error_rates".to_string(),
             ],
             profile_update_interval: Duration::from_secs(3600),
+            profiles_path: None,
+            profile_staleness: Duration::from_secs(30 * 24 * 3600), // 30 days
+        }
+    }
+}
+
+impl Default for BruteForceConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(60),
+            max_failures_per_ip: 10,
+            max_failures_per_account: 10,
+            distributed_min_ips: 5,
         }
     }
 }
@@ -0,0 +1,215 @@
+//! Threat Event Correlation Module
+//!
+//! This module groups recent threat events that share a source or target
+//! and promotes related clusters into composite events of higher severity.
+
+use crate::{ThreatEvent, ThreatSeverity, ThreatType};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use chrono::{DateTime, Utc};
+
+/// Metadata key listing the events a composite was built from
+pub const CORRELATED_EVENTS_KEY: &str = "correlated_event_ids";
+
+/// Rule promoting a combination of threat types to a composite event
+#[derive(Debug, Clone)]
+pub struct CorrelationRule {
+    /// Rule name
+    pub name: String,
+    /// Threat types that must all be present in the cluster
+    pub threat_types: Vec<ThreatType>,
+    /// Threat type of the composite event
+    pub threat_type: ThreatType,
+    /// Severity of the composite event
+    pub severity: ThreatSeverity,
+}
+
+impl CorrelationRule {
+    /// Create new correlation rule
+    pub fn new(
+        name: &str,
+        threat_types: Vec<ThreatType>,
+        threat_type: ThreatType,
+        severity: ThreatSeverity,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            threat_types,
+            threat_type,
+            severity,
+        }
+    }
+
+    fn matches(&self, types: &HashSet<&ThreatType>) -> bool {
+        self.threat_types.iter().all(|threat_type| types.contains(threat_type))
+    }
+}
+
+/// Built-in correlation rules
+pub fn default_rules() -> Vec<CorrelationRule> {
+    vec![
+        CorrelationRule::new(
+            "account_takeover",
+            vec![ThreatType::SuspiciousLogin, ThreatType::DataExfiltration],
+            ThreatType::DataExfiltration,
+            ThreatSeverity::Critical,
+        ),
+        CorrelationRule::new(
+            "credential_compromise",
+            vec![ThreatType::BruteForce, ThreatType::SuspiciousLogin],
+            ThreatType::SuspiciousLogin,
+            ThreatSeverity::Critical,
+        ),
+        CorrelationRule::new(
+            "privilege_abuse",
+            vec![ThreatType::SuspiciousLogin, ThreatType::PrivilegeEscalation],
+            ThreatType::PrivilegeEscalation,
+            ThreatSeverity::Critical,
+        ),
+        CorrelationRule::new(
+            "malware_exfiltration",
+            vec![ThreatType::Malware, ThreatType::DataExfiltration],
+            ThreatType::DataExfiltration,
+            ThreatSeverity::Critical,
+        ),
+        CorrelationRule::new(
+            "phishing_compromise",
+            vec![ThreatType::Phishing, ThreatType::SuspiciousLogin],
+            ThreatType::SuspiciousLogin,
+            ThreatSeverity::High,
+        ),
+    ]
+}
+
+/// Correlate the events that happened within `window` of `now`
+///
+/// Events are grouped by every source and target they mention. A group of
+/// at least two events becomes a composite event: the first matching rule
+/// decides its type and severity, otherwise the most severe event's type is
+/// used and its severity raised one level. Groups with the same events are
+/// reported once, and composite events are never correlated again.
+pub fn correlate_events(
+    events: &[ThreatEvent],
+    rules: &[CorrelationRule],
+    window: chrono::Duration,
+    now: DateTime<Utc>,
+) -> Vec<ThreatEvent> {
+    let cutoff = now - window;
+
+    // BTreeMap keeps the output order stable
+    let mut groups: BTreeMap<&str, Vec<&ThreatEvent>> = BTreeMap::new();
+    for event in events.iter().filter(|event| {
+        event.timestamp >= cutoff && !event.metadata.contains_key(CORRELATED_EVENTS_KEY)
+    }) {
+        groups.entry(event.source.as_str()).or_default().push(event);
+        if let Some(target) = event.target.as_deref().filter(|target| *target != event.source) {
+            groups.entry(target).or_default().push(event);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut composites = Vec::new();
+    for (entity, group) in groups {
+        if group.len() < 2 {
+            continue;
+        }
+        let mut ids = group.iter().map(|event| event.id.as_str()).collect::<Vec<_>>();
+        ids.sort_unstable();
+        if !seen.insert(ids.clone()) {
+            continue;
+        }
+
+        composites.push(composite_event(entity, &group, ids, rules));
+    }
+
+    composites
+}
+
+fn composite_event(
+    entity: &str,
+    group: &[&ThreatEvent],
+    ids: Vec<&str>,
+    rules: &[CorrelationRule],
+) -> ThreatEvent {
+    let types = group.iter().map(|event| &event.threat_type).collect::<HashSet<_>>();
+    let most_severe = group
+        .iter()
+        .max_by(|a, b| a.severity.cmp(&b.severity))
+        .expect("groups have at least two events");
+
+    let (rule, threat_type, severity) = match rules.iter().find(|rule| rule.matches(&types)) {
+        Some(rule) => (rule.name.as_str(), rule.threat_type.clone(), rule.severity.clone()),
+        None => (
+            "escalation",
+            most_severe.threat_type.clone(),
+            escalate(&most_severe.severity),
+        ),
+    };
+
+    let mut metadata = HashMap::new();
+    metadata.insert(CORRELATED_EVENTS_KEY.to_string(), serde_json::json!(ids));
+    metadata.insert("correlation_rule".to_string(), serde_json::json!(rule));
+    metadata.insert("correlated_entity".to_string(), serde_json::json!(entity));
+
+    ThreatEvent {
+        id: format!("correlated-{}", uuid::Uuid::new_v4()),
+        threat_type,
+        severity,
+        description: format!("Correlated {} involving {}: {} related events", rule, entity, group.len()),
+        source: entity.to_string(),
+        target: None,
+        timestamp: group.iter().map(|event| event.timestamp).max().unwrap_or_else(Utc::now),
+        metadata,
+        confidence: 1.0 - group.iter().map(|event| 1.0 - event.confidence).product::<f64>(),
+    }
+}
+
+/// Next severity level
+fn escalate(severity: &ThreatSeverity) -> ThreatSeverity {
+    match severity {
+        ThreatSeverity::Low => ThreatSeverity::Medium,
+        ThreatSeverity::Medium => ThreatSeverity::High,
+        ThreatSeverity::High | ThreatSeverity::Critical => ThreatSeverity::Critical,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, threat_type: ThreatType, source: &str, target: Option<&str>) -> ThreatEvent {
+        ThreatEvent {
+            id: id.to_string(),
+            threat_type,
+            severity: ThreatSeverity::Low,
+            description: String::new(),
+            source: source.to_string(),
+            target: target.map(str::to_string),
+            timestamp: Utc::now(),
+            metadata: HashMap::new(),
+            confidence: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_escalation_without_rule() {
+        let events = vec![
+            event("a", ThreatType::Spam, "spammer@example.com", Some("alice@example.com")),
+            event("b", ThreatType::Phishing, "spammer@example.com", Some("bob@example.com")),
+            event("c", ThreatType::Spam, "other@example.com", Some("carol@example.com")),
+        ];
+
+        let composites = correlate_events(&events, &default_rules(), chrono::Duration::minutes(15), Utc::now());
+        assert_eq!(composites.len(), 1);
+        assert_eq!(composites[0].source, "spammer@example.com");
+        assert_eq!(composites[0].severity, ThreatSeverity::Medium);
+        assert_eq!(composites[0].metadata["correlation_rule"], "escalation");
+        assert_eq!(composites[0].metadata[CORRELATED_EVENTS_KEY], serde_json::json!(["a", "b"]));
+        assert_eq!(composites[0].confidence, 0.75);
+
+        // Composites and events outside the window are not correlated
+        let mut old = event("d", ThreatType::Spam, "other@example.com", None);
+        old.timestamp = Utc::now() - chrono::Duration::hours(1);
+        let events = vec![composites[0].clone(), events[2].clone(), old];
+        assert!(correlate_events(&events, &default_rules(), chrono::Duration::minutes(15), Utc::now()).is_empty());
+    }
+}
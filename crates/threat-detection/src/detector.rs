@@ -1,9 +1,50 @@
 //! Main threat detector implementation
 
-use crate::{ThreatDetectionConfig, ThreatEvent, error::Result};
-use std::collections::HashMap;
+use crate::{
+    ThreatDetectionConfig, ThreatEvent, ThreatSeverity, ThreatType,
+    anomaly::{AnomalyDetector, AnomalyScore},
+    behavioral::BehavioralAnalyzer,
+    bruteforce::{BruteForceDetector, LoginAttempt},
+    error::Result,
+    patterns::{PatternMatch, PatternMatcher, PatternType, ThreatPattern},
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
 use chrono::{DateTime, Utc};
-use tracing::{info, warn, error};
+use sha2::{Digest, Sha256};
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Weight of the anomaly score in the combined confidence
+const ANOMALY_WEIGHT: f64 = 0.5;
+
+/// Weight of the behavioral score in the combined confidence
+const BEHAVIORAL_WEIGHT: f64 = 0.6;
+
+/// File extensions that are executed when opened
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "exe", "scr", "bat", "cmd", "com", "pif", "vbs", "js", "jse", "wsf", "hta", "msi", "jar",
+    "ps1", "lnk",
+];
+
+/// Extensions commonly used to disguise executables
+const DOCUMENT_EXTENSIONS: &[&str] = &[
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "txt", "rtf", "jpg", "jpeg", "png",
+    "gif", "zip", "mp3", "mp4",
+];
+
+/// MIME types of executable content
+const DANGEROUS_CONTENT_TYPES: &[&str] = &[
+    "application/x-msdownload",
+    "application/x-msdos-program",
+    "application/x-dosexec",
+    "application/x-executable",
+    "application/vnd.microsoft.portable-executable",
+    "application/hta",
+    "application/x-ms-shortcut",
+];
 
 /// Email context for threat analysis
 #[derive(Debug, Clone)]
@@ -28,6 +69,18 @@ pub struct AttachmentInfo {
     pub hash: String,
 }
 
+impl AttachmentInfo {
+    /// Describe an attachment from its raw contents, computing its SHA-256 hash
+    pub fn from_bytes(filename: impl Into<String>, content_type: impl Into<String>, bytes: &[u8]) -> Self {
+        Self {
+            filename: filename.into(),
+            content_type: content_type.into(),
+            size: bytes.len(),
+            hash: sha256_hex(bytes),
+        }
+    }
+}
+
 /// Detection statistics
 #[derive(Debug, Clone, Default)]
 pub struct DetectionStats {
@@ -48,18 +101,19 @@ pub struct EmailData {
     pub attachments: Vec<String>,
 }
 
-/// Threat severity levels
-#[derive(Debug, Clone, PartialEq)]
-pub enum ThreatSeverity {
-    Low,
-    Medium,
-    High,
-    Critical,
-}
-
 /// Main threat detector
 pub struct ThreatDetector {
     config: ThreatDetectionConfig,
+    pattern_matcher: PatternMatcher,
+    patterns: Vec<ThreatPattern>,
+    anomaly_detector: AnomalyDetector,
+    behavioral_analyzer: BehavioralAnalyzer,
+    brute_force: Arc<BruteForceDetector>,
+    /// Sizes of recently analyzed messages, used as the anomaly baseline
+    message_sizes: RwLock<VecDeque<f64>>,
+    /// Lowercase hex SHA-256 hashes of known malware
+    malware_hashes: std::sync::RwLock<HashSet<String>>,
+    stats: RwLock<DetectionStats>,
 }
 
 impl ThreatDetector {
@@ -71,10 +125,26 @@ impl ThreatDetector {
         let _ml_models = Self::load_ml_models(&config).await?;
 
         // Initialize pattern matchers
-        let _pattern_matchers = Self::load_threat_patterns(&config).await?;
+        let patterns = Self::load_threat_patterns(&config).await?;
+        let mut pattern_matcher = PatternMatcher::new();
+        pattern_matcher.load(&patterns)?;
+
+        let behavioral_analyzer = BehavioralAnalyzer::new(&config.behavioral).await?;
+        let anomaly_detector = AnomalyDetector::with_config(&config.anomaly);
+        let brute_force = Arc::new(BruteForceDetector::new(&config.brute_force));
 
         info!("Threat detector initialized with {} ML models", _ml_models.len());
-        Ok(Self { config })
+        Ok(Self {
+            config,
+            pattern_matcher,
+            patterns,
+            anomaly_detector,
+            behavioral_analyzer,
+            brute_force,
+            message_sizes: RwLock::new(VecDeque::new()),
+            malware_hashes: std::sync::RwLock::new(HashSet::new()),
+            stats: RwLock::new(DetectionStats::default()),
+        })
     }
 
     /// Start threat detection with real-time monitoring
@@ -96,6 +166,12 @@ impl ThreatDetector {
             Self::run_threat_intelligence_updates().await;
         });
 
+        // Expire failed logins that fell out of the brute-force window
+        let brute_force = self.brute_force.clone();
+        tokio::spawn(async move {
+            brute_force.run_purge().await;
+        });
+
         info!("Threat detection system started successfully");
         Ok(())
     }
@@ -138,13 +214,15 @@ impl ThreatDetector {
         // Return threat event if score exceeds threshold
         if threat_score > self.config.threat_threshold {
             Ok(Some(ThreatEvent {
-                event_type: ThreatType::Malicious,
+                id: format!("ml-{}", uuid::Uuid::new_v4()),
+                threat_type: ThreatType::Unknown,
                 severity: self.calculate_severity(threat_score),
-                confidence: threat_score,
                 description: format!("AI-detected threat with score: {:.2}", threat_score),
-                timestamp: chrono::Utc::now(),
                 source: "AI-ML-Engine".to_string(),
+                target: None,
+                timestamp: chrono::Utc::now(),
                 metadata: std::collections::HashMap::new(),
+                confidence: threat_score,
             }))
         } else {
             Ok(None)
@@ -152,15 +230,293 @@ impl ThreatDetector {
     }
 
     /// Analyze an email for threats
-    pub async fn analyze_email(&self, _context: &EmailContext) -> Result<Option<ThreatEvent>> {
-        // TODO: Implement email threat analysis
-        Ok(None)
+    ///
+    /// Pattern, anomaly and behavioral scores are combined into a single
+    /// confidence; a threat event is returned when it exceeds the configured
+    /// threshold.
+    pub async fn analyze_email(&self, context: &EmailContext) -> Result<Option<ThreatEvent>> {
+        let start_time = std::time::Instant::now();
+
+        // Pattern matching, scored per threat type
+        let matches = if self.config.pattern_matching_enabled {
+            self.match_email_patterns(context)
+        } else {
+            Vec::new()
+        };
+        let mut pattern_scores: HashMap<ThreatType, f64> = HashMap::new();
+        for pattern_match in &matches {
+            let miss = pattern_scores
+                .entry(pattern_threat_type(&pattern_match.pattern_id))
+                .or_insert(1.0);
+            *miss *= 1.0 - pattern_match.confidence;
+        }
+        let (pattern_type, pattern_score) = pattern_scores
+            .into_iter()
+            .map(|(threat_type, miss)| (threat_type, 1.0 - miss))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((ThreatType::Unknown, 0.0));
+
+        // Message size compared against recent traffic
+        let anomaly_score = if self.config.anomaly_detection_enabled {
+            self.score_message_size(context).await
+        } else {
+            0.0
+        };
+
+        // Deviation from the sender's profile
+        let behavioral_score = if self.config.behavioral_analysis_enabled {
+            self.behavioral_analyzer
+                .analyze_email(context)
+                .await?
+                .map_or(0.0, |event| event.confidence)
+        } else {
+            0.0
+        };
+
+        let confidence = 1.0
+            - (1.0 - pattern_score)
+                * (1.0 - ANOMALY_WEIGHT * anomaly_score)
+                * (1.0 - BEHAVIORAL_WEIGHT * behavioral_score);
+
+        let threat_type = [
+            (pattern_type, pattern_score),
+            (ThreatType::Anomaly, ANOMALY_WEIGHT * anomaly_score),
+            (ThreatType::BehavioralAnomaly, BEHAVIORAL_WEIGHT * behavioral_score),
+        ]
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(threat_type, _)| threat_type)
+        .unwrap_or(ThreatType::Unknown);
+
+        debug!(
+            "Email {} scored {:.2} (pattern: {:.2}, anomaly: {:.2}, behavioral: {:.2})",
+            context.message_id, confidence, pattern_score, anomaly_score, behavioral_score
+        );
+
+        let event = (confidence > self.config.threat_threshold).then(|| {
+            let mut metadata = HashMap::new();
+            metadata.insert("message_id".to_string(), serde_json::json!(context.message_id));
+            metadata.insert(
+                "matched_patterns".to_string(),
+                serde_json::json!(matches.iter().map(|m| &m.pattern_id).collect::<Vec<_>>()),
+            );
+            metadata.insert("pattern_score".to_string(), serde_json::json!(pattern_score));
+            metadata.insert("anomaly_score".to_string(), serde_json::json!(anomaly_score));
+            metadata.insert("behavioral_score".to_string(), serde_json::json!(behavioral_score));
+
+            ThreatEvent {
+                id: format!("email-{}", uuid::Uuid::new_v4()),
+                severity: self.calculate_severity(confidence),
+                description: format!(
+                    "{:?} detected in message {} with confidence {:.2}",
+                    threat_type, context.message_id, confidence
+                ),
+                threat_type,
+                source: context.sender.clone(),
+                target: Some(context.recipients.join(", ")),
+                timestamp: Utc::now(),
+                metadata,
+                confidence,
+            }
+        });
+
+        let mut stats = self.stats.write().await;
+        stats.total_emails_analyzed += 1;
+        if event.is_some() {
+            stats.threats_detected += 1;
+        }
+        stats.processing_time_ms += start_time.elapsed().as_millis() as u64;
+
+        Ok(event)
+    }
+
+    /// Scan an attachment for malware
+    ///
+    /// A hash listed in the known-malware set produces a critical event.
+    /// Otherwise executables disguised behind a document extension (such as
+    /// `invoice.pdf.exe`) and executable MIME types produce lower-confidence
+    /// events.
+    pub fn scan_attachment(&self, info: &AttachmentInfo) -> Option<ThreatEvent> {
+        let hash = info.hash.to_ascii_lowercase();
+        let (reason, confidence, severity) = if self.malware_hashes.read().unwrap().contains(&hash) {
+            ("known_hash", 1.0, ThreatSeverity::Critical)
+        } else if has_double_extension(&info.filename) {
+            ("double_extension", 0.7, self.calculate_severity(0.7))
+        } else if DANGEROUS_CONTENT_TYPES.contains(&media_type(&info.content_type).as_str()) {
+            ("dangerous_content_type", 0.6, self.calculate_severity(0.6))
+        } else {
+            return None;
+        };
+
+        let mut metadata = HashMap::new();
+        metadata.insert("reason".to_string(), serde_json::json!(reason));
+        metadata.insert("filename".to_string(), serde_json::json!(info.filename));
+        metadata.insert("content_type".to_string(), serde_json::json!(info.content_type));
+        metadata.insert("hash".to_string(), serde_json::json!(hash));
+
+        Some(ThreatEvent {
+            id: format!("attachment-{}", uuid::Uuid::new_v4()),
+            threat_type: ThreatType::Malware,
+            severity,
+            description: format!("Malicious attachment {} ({})", info.filename, reason),
+            source: info.filename.clone(),
+            target: None,
+            timestamp: Utc::now(),
+            metadata,
+            confidence,
+        })
+    }
+
+    /// Add SHA-256 hashes to the known-malware set
+    ///
+    /// Returns the number of valid hashes added; anything that is not a
+    /// 64-digit hex string is ignored.
+    pub fn add_malware_hashes<'x>(&self, hashes: impl IntoIterator<Item = &'x str>) -> usize {
+        let mut known = self.malware_hashes.write().unwrap();
+        hashes
+            .into_iter()
+            .map(str::trim)
+            .filter(|hash| hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()))
+            .filter(|hash| known.insert(hash.to_ascii_lowercase()))
+            .count()
+    }
+
+    /// Load known-malware SHA-256 hashes from a file with one hash per line
+    ///
+    /// Blank lines and lines starting with `#` are skipped.
+    pub async fn load_malware_hashes(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let contents = tokio::fs::read_to_string(path.as_ref()).await?;
+        let count = self.add_malware_hashes(
+            contents
+                .lines()
+                .filter(|line| !line.trim_start().starts_with('#'))
+                .map(|line| line.split_whitespace().next().unwrap_or_default()),
+        );
+        info!("Loaded {} known malware hashes", count);
+        Ok(count)
+    }
+
+    /// Record an authentication attempt with the brute-force detector
+    pub async fn record_login(&self, login: &LoginAttempt) -> Option<ThreatEvent> {
+        let event = self
+            .brute_force
+            .record_authentication(
+                &login.account,
+                &login.source_ip,
+                login.success,
+                login.timestamp,
+            )
+            .await;
+        if event.is_some() {
+            self.stats.write().await.threats_detected += 1;
+        }
+        event
+    }
+
+    /// Feed logins from an event stream, such as `SecurityManager::subscribe`
+    ///
+    /// `login` extracts the attempt from each event and returns `None` for
+    /// unrelated events. Brute-force events are sent to `threats`; the task
+    /// stops when either channel is closed. Events missed because the task
+    /// fell behind are skipped.
+    ///
+    /// ```rust,ignore
+    /// detector.watch_logins(security.subscribe(), threats, |event| match event {
+    ///     SecurityEvent::Authentication { user_id, success, ip_address, .. } => Some(LoginAttempt {
+    ///         account: user_id.clone(),
+    ///         source_ip: ip_address.clone(),
+    ///         success: *success,
+    ///         timestamp: Utc::now(),
+    ///     }),
+    ///     _ => None,
+    /// });
+    /// ```
+    pub fn watch_logins<E, F>(
+        self: &Arc<Self>,
+        mut events: broadcast::Receiver<E>,
+        threats: mpsc::Sender<ThreatEvent>,
+        login: F,
+    ) -> JoinHandle<()>
+    where
+        E: Clone + Send + 'static,
+        F: Fn(&E) -> Option<LoginAttempt> + Send + 'static,
+    {
+        let detector = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!(
+                            "Brute-force detector skipped {} authentication events",
+                            missed
+                        );
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                if let Some(attempt) = login(&event) {
+                    if let Some(threat) = detector.record_login(&attempt).await {
+                        if threats.send(threat).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        })
     }
 
     /// Get detection statistics
     pub async fn get_stats(&self) -> Result<DetectionStats> {
-        // TODO: Implement statistics collection
-        Ok(DetectionStats::default())
+        Ok(self.stats.read().await.clone())
+    }
+
+    /// Match the threat patterns against the subject, body and attachment names
+    ///
+    /// Each pattern is reported at most once, for the first field it matches.
+    fn match_email_patterns(&self, context: &EmailContext) -> Vec<PatternMatch> {
+        let fields = [("subject", context.subject.as_str()), ("body", context.body.as_str())]
+            .into_iter()
+            .chain(
+                context
+                    .attachments
+                    .iter()
+                    .map(|attachment| ("attachment", attachment.filename.as_str())),
+            );
+
+        let mut matches: Vec<PatternMatch> = Vec::new();
+        for (field, text) in fields {
+            for mut pattern_match in self.pattern_matcher.match_patterns(text, &self.patterns) {
+                if matches.iter().all(|m| m.pattern_id != pattern_match.pattern_id) {
+                    pattern_match.location.field = field.to_string();
+                    matches.push(pattern_match);
+                }
+            }
+        }
+        matches
+    }
+
+    /// Score the message size against the sizes of recently analyzed messages
+    async fn score_message_size(&self, context: &EmailContext) -> AnomalyScore {
+        let size = (context.subject.len()
+            + context.body.len()
+            + context.attachments.iter().map(|a| a.size).sum::<usize>()) as f64;
+
+        let mut sizes = self.message_sizes.write().await;
+        let baseline = sizes.make_contiguous();
+        let score = if baseline.len() >= self.config.anomaly.min_samples {
+            self.anomaly_detector.score_value(baseline, size)
+        } else {
+            0.0
+        };
+
+        sizes.push_back(size);
+        while sizes.len() > self.config.anomaly.window_size {
+            sizes.pop_front();
+        }
+
+        score
     }
 
     /// Load ML models for threat detection
@@ -170,8 +526,8 @@ impl ThreatDetector {
         let mut models = Vec::new();
 
         // Load ONNX models for threat detection
-        if let Some(model_path) = &config.model_path {
-            let model_files = tokio::fs::read_dir(model_path).await?;
+        if let Some(model_path) = &config.anomaly.ml_model_path {
+            let _model_files = tokio::fs::read_dir(model_path).await?;
             // TODO: Load actual ONNX models
             models.push("threat-detection-v2.onnx".to_string());
             models.push("phishing-detection.onnx".to_string());
@@ -183,25 +539,35 @@ impl ThreatDetector {
     }
 
     /// Load threat patterns for rule-based detection
-    async fn load_threat_patterns(config: &ThreatDetectionConfig) -> Result<Vec<String>> {
+    async fn load_threat_patterns(_config: &ThreatDetectionConfig) -> Result<Vec<ThreatPattern>> {
         info!("Loading threat patterns");
 
-        let patterns = vec![
+        let patterns = [
             // Phishing patterns
-            r"(?i)(urgent|immediate|act now|limited time)",
-            r"(?i)(click here|download now|verify account)",
-            r"(?i)(suspended|locked|expired|compromised)",
+            ("phishing-urgency", r"(?i)\b(urgent|immediate|act now|limited time)\b", "Urgency language"),
+            ("phishing-call-to-action", r"(?i)\b(click (here|this link)|download now|verify (your )?account|claim your)\b", "Call to action"),
+            ("phishing-account-threat", r"(?i)\b(suspended|locked|expired|compromised)\b", "Account threat"),
+            ("phishing-insecure-link", r"(?i)\bhttp://\S+", "Unencrypted link"),
+            ("phishing-ip-link", r"(?i)\bhttps?://\d{1,3}(\.\d{1,3}){3}", "Link to an IP address"),
 
             // Malware patterns
-            r"(?i)\.(exe|scr|bat|com|pif|vbs|js)$",
-            r"(?i)(trojan|virus|malware|ransomware)",
+            ("malware-executable", r"(?i)\.(exe|scr|bat|com|pif|vbs|js)$", "Executable attachment"),
+            ("malware-keywords", r"(?i)\b(trojan|virus|malware|ransomware)\b", "Malware keywords"),
 
             // Spam patterns
-            r"(?i)(free money|get rich|work from home)",
-            r"(?i)(viagra|cialis|pharmacy|pills)",
+            ("spam-money", r"(?i)\b(free money|get rich|work from home)\b", "Money making offer"),
+            ("spam-pharmacy", r"(?i)\b(viagra|cialis|pharmacy|pills)\b", "Pharmacy spam"),
         ];
 
-        Ok(patterns.iter().map(|s| s.to_string()).collect())
+        Ok(patterns
+            .into_iter()
+            .map(|(id, pattern, description)| ThreatPattern {
+                id: id.to_string(),
+                pattern_type: PatternType::Regex,
+                pattern: pattern.to_string(),
+                description: description.to_string(),
+            })
+            .collect())
     }
 
     /// Parse email event data
@@ -304,10 +670,11 @@ impl ThreatDetector {
 
     /// Calculate threat severity based on score
     fn calculate_severity(&self, score: f64) -> ThreatSeverity {
+        let thresholds = &self.config.alert_thresholds;
         match score {
-            s if s >= 0.9 => ThreatSeverity::Critical,
-            s if s >= 0.7 => ThreatSeverity::High,
-            s if s >= 0.5 => ThreatSeverity::Medium,
+            s if s >= thresholds.critical_threshold => ThreatSeverity::Critical,
+            s if s >= thresholds.high_threshold => ThreatSeverity::High,
+            s if s >= thresholds.medium_threshold => ThreatSeverity::Medium,
             _ => ThreatSeverity::Low,
         }
     }
@@ -380,3 +747,43 @@ impl ThreatDetector {
         }
     }
 }
+
+/// Threat type reported by a built-in pattern, derived from its id prefix
+fn pattern_threat_type(pattern_id: &str) -> ThreatType {
+    match pattern_id.split('-').next() {
+        Some("phishing") => ThreatType::Phishing,
+        Some("malware") => ThreatType::Malware,
+        Some("spam") => ThreatType::Spam,
+        _ => ThreatType::Unknown,
+    }
+}
+
+/// Whether a filename hides an executable extension behind a document one
+fn has_double_extension(filename: &str) -> bool {
+    let filename = filename.trim().trim_end_matches('.').to_ascii_lowercase();
+    let mut extensions = filename.rsplit('.');
+    match (extensions.next(), extensions.next(), extensions.next()) {
+        (Some(last), Some(previous), Some(_)) => {
+            EXECUTABLE_EXTENSIONS.contains(&last) && DOCUMENT_EXTENSIONS.contains(&previous)
+        }
+        _ => false,
+    }
+}
+
+/// Media type of a Content-Type value, without parameters
+fn media_type(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Lowercase hex SHA-256 digest
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
@@ -6,32 +6,24 @@
 
 use crate::{
     ThreatEvent, ThreatType, ThreatSeverity,
-    config::ThreatIntelligenceConfig,
+    config::{ThreatFeed, ThreatFeedFormat, ThreatIntelligenceConfig},
     detector::EmailContext,
-    error::Result,
+    error::{Result, ThreatDetectionError},
 };
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tracing::{debug, info, warn, error};
 use chrono::Utc;
 use reqwest::Client;
+use serde::Deserialize;
 
-// Constants to avoid string literal issues
-const LOADING_MSG: &str = "Loading threat indicators";
-const LOADED_MSG: &str = "Loaded {} total threat indicators";
-const DOMAIN_VALUE: &str = "malicious-domain.com";
-const DOMAIN_DESC: &str = "Known malware C&C domain";
-const UPDATES_MSG: &str = "Starting background feed updates";
+// Metadata keys
 const SOURCES_KEY: &str = "intelligence_sources";
 const INDICATORS_KEY: &str = "matched_indicators";
 const MATCHES_KEY: &str = "intelligence_matches";
-const MALWARE_TAG: &str = "malware";
-const C2_TAG: &str = "c2";
-const EXAMPLE_PREFIX: &str = "example";
-const FEED_LOADING_MSG: &str = "Loading indicators from feed";
-const FEED_ERROR_MSG: &str = "Failed to load indicators from feed";
-const FEED_SUCCESS_MSG: &str = "Loaded {} indicators from feed";
 
 /// Threat intelligence engine
 ///
@@ -48,6 +40,8 @@ pub struct ThreatIntelligence {
     stats: Arc<RwLock<IntelligenceStats>>,
     /// Running state
     is_running: Arc<RwLock<bool>>,
+    /// Background feed refresh task
+    feed_task: std::sync::Mutex<Option<JoinHandle<()>>>,
 }
 
 /// Threat indicator cache
@@ -101,7 +95,8 @@ pub struct ThreatIndicator {
 }
 
 /// Types of threat indicators
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum IndicatorType {
     /// IP address
     IpAddress,
@@ -164,6 +159,73 @@ pub enum MatchContext {
     SenderEmail(String),
 }
 
+/// Indicator record as stored in local files and JSON feeds
+#[derive(Debug, Deserialize)]
+struct IndicatorRecord {
+    #[serde(rename = "type")]
+    indicator_type: IndicatorType,
+    value: String,
+    #[serde(default = "default_threat_type")]
+    threat_type: ThreatType,
+    #[serde(default = "default_severity")]
+    severity: ThreatSeverity,
+    #[serde(default = "default_confidence")]
+    confidence: f64,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+fn default_threat_type() -> ThreatType {
+    ThreatType::Unknown
+}
+
+fn default_severity() -> ThreatSeverity {
+    ThreatSeverity::Medium
+}
+
+fn default_confidence() -> f64 {
+    0.8
+}
+
+impl ThreatIndicatorCache {
+    /// Insert an indicator into the set matching its type
+    ///
+    /// Domains and email addresses are stored lowercased. Returns `false` for
+    /// indicator types that are not matched against message contents.
+    pub fn insert(&mut self, indicator: ThreatIndicator) -> bool {
+        let set = match indicator.indicator_type {
+            IndicatorType::IpAddress => &mut self.ip_indicators,
+            IndicatorType::Domain => &mut self.domain_indicators,
+            IndicatorType::Url => &mut self.url_indicators,
+            IndicatorType::FileHash => &mut self.hash_indicators,
+            IndicatorType::EmailAddress => &mut self.email_indicators,
+            IndicatorType::SubjectPattern | IndicatorType::ContentPattern => return false,
+        };
+        let key = match indicator.indicator_type {
+            IndicatorType::Domain | IndicatorType::EmailAddress => indicator.value.to_lowercase(),
+            _ => indicator.value.clone(),
+        };
+        set.insert(key, indicator);
+        true
+    }
+
+    /// Total number of cached indicators
+    pub fn len(&self) -> usize {
+        self.ip_indicators.len()
+            + self.domain_indicators.len()
+            + self.url_indicators.len()
+            + self.hash_indicators.len()
+            + self.email_indicators.len()
+    }
+
+    /// Whether the cache holds no indicators
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 impl ThreatIntelligence {
     /// Create new threat intelligence engine
     ///
@@ -186,6 +248,7 @@ impl ThreatIntelligence {
             indicators: Arc::new(RwLock::new(ThreatIndicatorCache::default())),
             stats: Arc::new(RwLock::new(IntelligenceStats::default())),
             is_running: Arc::new(RwLock::new(false)),
+            feed_task: std::sync::Mutex::new(None),
         })
     }
 
@@ -222,7 +285,9 @@ impl ThreatIntelligence {
         }
 
         // Stop background tasks
-        // TODO: Implement proper task cancellation
+        if let Some(task) = self.feed_task.lock().unwrap().take() {
+            task.abort();
+        }
 
         *running = false;
         info!("Threat intelligence engine stopped");
@@ -240,7 +305,7 @@ impl ThreatIntelligence {
     pub async fn analyze_email(&self, email_context: &EmailContext) -> Result<Option<ThreatEvent>> {
         debug!("Analyzing email with threat intelligence: {}", email_context.message_id);
 
-        let matches = self.find_intelligence_matches(email_context).await?;
+        let matches = self.check_indicators(email_context).await;
 
         if !matches.is_empty() {
             // Find the highest severity match
@@ -266,65 +331,72 @@ impl ThreatIntelligence {
         }
     }
 
-    /// Find threat intelligence matches
-    async fn find_intelligence_matches(&self, email_context: &EmailContext) -> Result<Vec<IntelligenceMatch>> {
+    /// Match the URLs, sender address, sender domain, source IP and
+    /// attachment hashes of an email against the loaded indicator sets
+    ///
+    /// Sender domains also match indicators for any parent domain, and URL
+    /// hosts are checked against the domain indicators. Expired indicators
+    /// are ignored.
+    pub async fn check_indicators(&self, email_context: &EmailContext) -> Vec<IntelligenceMatch> {
         let indicators = self.indicators.read().await;
+        let now = Utc::now();
         let mut matches = Vec::new();
-
-        // Check sender IP
-        if let Some(source_ip) = &email_context.source_ip {
-            if let Some(indicator) = indicators.ip_indicators.get(source_ip) {
+        let mut push = |indicator: Option<&ThreatIndicator>, context: MatchContext| {
+            if let Some(indicator) = indicator.filter(|i| i.expires_at.is_none_or(|at| at > now)) {
                 matches.push(IntelligenceMatch {
                     indicator: indicator.clone(),
-                    context: MatchContext::SenderIp(source_ip.clone()),
+                    context,
                     confidence: indicator.confidence,
                 });
             }
-        }
+        };
 
-        // Check sender domain
-        let sender_domain = email_context.sender.split('@').nth(1).unwrap_or("");
-        if let Some(indicator) = indicators.domain_indicators.get(sender_domain) {
-            matches.push(IntelligenceMatch {
-                indicator: indicator.clone(),
-                context: MatchContext::SenderDomain(sender_domain.to_string()),
-                confidence: indicator.confidence,
-            });
+        // Check sender IP
+        if let Some(source_ip) = &email_context.source_ip {
+            push(
+                indicators.ip_indicators.get(source_ip),
+                MatchContext::SenderIp(source_ip.clone()),
+            );
         }
 
-        // Check sender email
-        if let Some(indicator) = indicators.email_indicators.get(&email_context.sender) {
-            matches.push(IntelligenceMatch {
-                indicator: indicator.clone(),
-                context: MatchContext::SenderEmail(email_context.sender.clone()),
-                confidence: indicator.confidence,
-            });
+        // Check sender email and domain
+        let sender = email_context.sender.to_lowercase();
+        push(
+            indicators.email_indicators.get(&sender),
+            MatchContext::SenderEmail(email_context.sender.clone()),
+        );
+        if let Some((_, domain)) = sender.rsplit_once('@') {
+            push(
+                lookup_domain(&indicators.domain_indicators, domain),
+                MatchContext::SenderDomain(domain.to_string()),
+            );
         }
 
         // Check URLs in content
-        let urls = self.extract_urls(&email_context.body).await;
-        for url in urls {
+        let text = format!("{}\n{}", email_context.subject, email_context.body);
+        for url in self.extract_urls(&text).await {
             if let Some(indicator) = indicators.url_indicators.get(&url) {
-                matches.push(IntelligenceMatch {
-                    indicator: indicator.clone(),
-                    context: MatchContext::ContentUrl(url),
-                    confidence: indicator.confidence,
-                });
+                push(Some(indicator), MatchContext::ContentUrl(url));
+            } else if let Some(host) = url_host(&url) {
+                push(
+                    lookup_domain(&indicators.domain_indicators, &host),
+                    MatchContext::ContentUrl(url),
+                );
             }
         }
 
         // Check attachment hashes
         for attachment in &email_context.attachments {
-            if let Some(indicator) = indicators.hash_indicators.get(&attachment.hash) {
-                matches.push(IntelligenceMatch {
-                    indicator: indicator.clone(),
-                    context: MatchContext::AttachmentHash(attachment.hash.clone()),
-                    confidence: indicator.confidence,
-                });
-            }
+            push(
+                indicators.hash_indicators.get(&attachment.hash),
+                MatchContext::AttachmentHash(attachment.hash.clone()),
+            );
         }
+        drop(indicators);
 
-        Ok(matches)
+        self.stats.write().await.indicators_matched += matches.len() as u64;
+
+        matches
     }
 
     /// Extract URLs from text
@@ -335,79 +407,61 @@ impl ThreatIntelligence {
             .collect()
     }
 
-    /// Load threat indicators from various sources
-    async fn load_threat_indicators(&self) -> Result<()> {
-        // TODO: Add logging
-
-        // Load from configured feeds
-        for feed in &self.config.feeds {
-            match self.load_feed_indicators(feed).await {
-                Ok(_count) => {
-                    // TODO: Log success properly
-                }
-                Err(_e) => {
-                    // TODO: Log error properly
-                }
-            }
-        }
-
-        // Update statistics
-        let indicators = self.indicators.read().await;
-        let mut stats = self.stats.write().await;
-        stats.total_indicators = indicators.ip_indicators.len() +
-                                indicators.domain_indicators.len() +
-                                indicators.url_indicators.len() +
-                                indicators.hash_indicators.len() +
-                                indicators.email_indicators.len();
-        stats.last_feed_update = Some(Utc::now());
-
-        tracing::info!(LOADED_MSG, stats.total_indicators);
-
-        Ok(())
+    /// Load indicators from a local file
+    ///
+    /// # Arguments
+    /// * `path` - File containing the indicators
+    /// * `format` - Format of the file, JSON and CSV are supported
+    /// * `source` - Name recorded as the source of the loaded indicators
+    ///
+    /// # Returns
+    /// Number of indicators loaded
+    pub async fn load_indicators_from_file(
+        &self,
+        path: impl AsRef<Path>,
+        format: &ThreatFeedFormat,
+        source: &str,
+    ) -> Result<usize> {
+        let contents = tokio::fs::read_to_string(path.as_ref()).await?;
+        let indicators = parse_indicators(&contents, format, source, self.config.cache_duration)?;
+        Ok(store_indicators(&self.indicators, &self.stats, indicators).await)
     }
 
-    /// Load indicators from a specific feed
-    async fn load_feed_indicators(&self, feed: &crate::config::ThreatFeed) -> Result<usize> {
-        // TODO: Implement actual feed loading based on feed type
-        // For now, return mock data
-        let mut indicators = self.indicators.write().await;
+    /// Load threat indicators from all enabled feeds
+    async fn load_threat_indicators(&self) -> Result<()> {
+        info!("Loading threat indicators");
 
-        // Add some example indicators
-        let example_indicator = ThreatIndicator {
-            id: {
-                let prefix = EXAMPLE_PREFIX;
-                let uuid = uuid::Uuid::new_v4();
-                let mut result = String::new();
-                result.push_str(prefix);
-                result.push('-');
-                result.push_str(&uuid.to_string());
-                result
-            },
-            indicator_type: IndicatorType::Domain,
-            value: String::from(DOMAIN_VALUE),
-            threat_type: ThreatType::Malware,
-            severity: ThreatSeverity::High,
-            confidence: 0.9,
-            source: feed.name.clone(),
-            description: String::from(DOMAIN_DESC),
-            tags: vec![String::from(MALWARE_TAG), String::from(C2_TAG)],
-            first_seen: Utc::now(),
-            last_seen: Utc::now(),
-            expires_at: Some(Utc::now() + chrono::Duration::days(30)),
-            metadata: HashMap::new(),
-        };
+        refresh_feeds(&self.client, &self.config, &self.indicators, &self.stats).await;
 
-        indicators.domain_indicators.insert(
-            example_indicator.value.clone(),
-            example_indicator
-        );
+        let stats = self.stats.read().await;
+        info!("Loaded {} total threat indicators", stats.total_indicators);
 
-        Ok(1) // Return number of indicators loaded
+        Ok(())
     }
 
     /// Start background feed updates
     async fn start_feed_updates(&self) -> Result<()> {
-        // TODO: Implement periodic feed updates
+        info!("Starting background feed updates");
+
+        let client = self.client.clone();
+        let config = self.config.clone();
+        let indicators = self.indicators.clone();
+        let stats = self.stats.clone();
+
+        let task = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(config.update_interval);
+            // The first tick completes immediately and the feeds were just loaded
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                refresh_feeds(&client, &config, &indicators, &stats).await;
+            }
+        });
+
+        if let Some(previous) = self.feed_task.lock().unwrap().replace(task) {
+            previous.abort();
+        }
+
         Ok(())
     }
 
@@ -456,28 +510,7 @@ impl ThreatIntelligence {
 
     /// Add custom threat indicator
     pub async fn add_indicator(&self, indicator: ThreatIndicator) -> Result<()> {
-        let mut indicators = self.indicators.write().await;
-
-        match indicator.indicator_type {
-            IndicatorType::IpAddress => {
-                indicators.ip_indicators.insert(indicator.value.clone(), indicator);
-            }
-            IndicatorType::Domain => {
-                indicators.domain_indicators.insert(indicator.value.clone(), indicator);
-            }
-            IndicatorType::Url => {
-                indicators.url_indicators.insert(indicator.value.clone(), indicator);
-            }
-            IndicatorType::FileHash => {
-                indicators.hash_indicators.insert(indicator.value.clone(), indicator);
-            }
-            IndicatorType::EmailAddress => {
-                indicators.email_indicators.insert(indicator.value.clone(), indicator);
-            }
-            _ => {
-                // Handle other types as needed
-            }
-        }
+        store_indicators(&self.indicators, &self.stats, vec![indicator]).await;
 
         Ok(())
     }
@@ -488,17 +521,189 @@ impl ThreatIntelligence {
 
         let removed = match indicator_type {
             IndicatorType::IpAddress => indicators.ip_indicators.remove(value).is_some(),
-            IndicatorType::Domain => indicators.domain_indicators.remove(value).is_some(),
+            IndicatorType::Domain => indicators.domain_indicators.remove(&value.to_lowercase()).is_some(),
             IndicatorType::Url => indicators.url_indicators.remove(value).is_some(),
             IndicatorType::FileHash => indicators.hash_indicators.remove(value).is_some(),
-            IndicatorType::EmailAddress => indicators.email_indicators.remove(value).is_some(),
+            IndicatorType::EmailAddress => indicators.email_indicators.remove(&value.to_lowercase()).is_some(),
             _ => false,
         };
+        self.stats.write().await.total_indicators = indicators.len();
 
         Ok(removed)
     }
 }
 
+/// Reload the indicators of every enabled feed
+async fn refresh_feeds(
+    client: &Client,
+    config: &ThreatIntelligenceConfig,
+    indicators: &RwLock<ThreatIndicatorCache>,
+    stats: &RwLock<IntelligenceStats>,
+) {
+    for feed in config.feeds.iter().filter(|feed| feed.enabled) {
+        debug!("Loading indicators from feed {}", feed.name);
+        stats.write().await.api_calls += 1;
+
+        match fetch_feed_indicators(client, config, feed).await {
+            Ok(loaded) => {
+                let count = store_indicators(indicators, stats, loaded).await;
+                info!("Loaded {} indicators from feed {}", count, feed.name);
+            }
+            Err(e) => {
+                error!("Failed to load indicators from feed {}: {}", feed.name, e);
+            }
+        }
+    }
+
+    indicators.write().await.last_updated = Some(Utc::now());
+    stats.write().await.last_feed_update = Some(Utc::now());
+}
+
+/// Download and parse the indicators published by a feed
+async fn fetch_feed_indicators(
+    client: &Client,
+    config: &ThreatIntelligenceConfig,
+    feed: &ThreatFeed,
+) -> Result<Vec<ThreatIndicator>> {
+    let mut request = client.get(&feed.url).timeout(config.api_timeout);
+    if let Some(api_key) = &feed.api_key {
+        request = request.bearer_auth(api_key);
+    }
+
+    let body = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| ThreatDetectionError::Generic(format!("Feed request failed: {}", e)))?
+        .text()
+        .await
+        .map_err(|e| ThreatDetectionError::Generic(format!("Failed to read feed: {}", e)))?;
+
+    parse_indicators(&body, &feed.format, &feed.name, config.cache_duration)
+}
+
+/// Insert indicators into the cache and refresh the indicator count
+async fn store_indicators(
+    indicators: &RwLock<ThreatIndicatorCache>,
+    stats: &RwLock<IntelligenceStats>,
+    loaded: Vec<ThreatIndicator>,
+) -> usize {
+    let mut indicators = indicators.write().await;
+    let count = loaded
+        .into_iter()
+        .map(|indicator| indicators.insert(indicator))
+        .filter(|&inserted| inserted)
+        .count();
+    stats.write().await.total_indicators = indicators.len();
+    count
+}
+
+/// Parse indicators in JSON or CSV format
+///
+/// JSON documents hold an array of indicator records. CSV lines have the
+/// form `type,value[,threat_type[,severity]]`; blank lines and lines starting
+/// with `#` are skipped.
+fn parse_indicators(
+    contents: &str,
+    format: &ThreatFeedFormat,
+    source: &str,
+    ttl: std::time::Duration,
+) -> Result<Vec<ThreatIndicator>> {
+    let records: Vec<IndicatorRecord> = match format {
+        ThreatFeedFormat::Json => serde_json::from_str(contents)?,
+        ThreatFeedFormat::Csv => contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(parse_csv_record)
+            .collect::<Result<_>>()?,
+        other => {
+            return Err(ThreatDetectionError::Config(format!(
+                "Unsupported threat feed format: {:?}",
+                other
+            )));
+        }
+    };
+
+    let now = Utc::now();
+    let expires_at = chrono::Duration::from_std(ttl).ok().map(|ttl| now + ttl);
+    Ok(records
+        .into_iter()
+        .map(|record| ThreatIndicator {
+            id: format!("{}-{}", source, uuid::Uuid::new_v4()),
+            indicator_type: record.indicator_type,
+            value: record.value,
+            threat_type: record.threat_type,
+            severity: record.severity,
+            confidence: record.confidence.clamp(0.0, 1.0),
+            source: source.to_string(),
+            description: record.description,
+            tags: record.tags,
+            first_seen: now,
+            last_seen: now,
+            expires_at,
+            metadata: HashMap::new(),
+        })
+        .collect())
+}
+
+fn parse_csv_record(line: &str) -> Result<IndicatorRecord> {
+    let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+    let field = |index: usize| {
+        columns
+            .get(index)
+            .filter(|value| !value.is_empty())
+            .map(|value| serde_json::Value::String(value.to_string()))
+    };
+    let (Some(indicator_type), Some(value)) = (field(0), columns.get(1).filter(|v| !v.is_empty()))
+    else {
+        return Err(ThreatDetectionError::Config(format!(
+            "Invalid indicator line: {}",
+            line
+        )));
+    };
+
+    Ok(IndicatorRecord {
+        indicator_type: serde_json::from_value(indicator_type)?,
+        value: value.to_string(),
+        threat_type: field(2)
+            .map(serde_json::from_value)
+            .transpose()?
+            .unwrap_or_else(default_threat_type),
+        severity: field(3)
+            .map(serde_json::from_value)
+            .transpose()?
+            .unwrap_or_else(default_severity),
+        confidence: default_confidence(),
+        description: String::new(),
+        tags: Vec::new(),
+    })
+}
+
+/// Find the indicator for a domain or the closest parent domain
+fn lookup_domain<'x>(
+    domain_indicators: &'x HashMap<String, ThreatIndicator>,
+    domain: &str,
+) -> Option<&'x ThreatIndicator> {
+    let domain = domain.trim_end_matches('.').to_lowercase();
+    let mut candidate = domain.as_str();
+    loop {
+        if let Some(indicator) = domain_indicators.get(candidate) {
+            return Some(indicator);
+        }
+        candidate = candidate.split_once('.')?.1;
+    }
+}
+
+/// Host part of an http(s) URL
+fn url_host(url: &str) -> Option<String> {
+    let rest = url.split_once("://")?.1;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = host.split(':').next()?;
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -603,6 +808,76 @@ mod tests {
         assert_eq!(threat_event.severity, ThreatSeverity::High);
     }
 
+    #[tokio::test]
+    async fn test_check_indicators_from_file() {
+        let config = create_test_config();
+        let intelligence = ThreatIntelligence::new(&config).await.unwrap();
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            r#"[
+                {"type": "domain", "value": "Bad-Domain.example", "threat_type": "Phishing", "severity": "Critical", "confidence": 0.95},
+                {"type": "file_hash", "value": "deadbeef", "threat_type": "Malware"}
+            ]"#,
+        )
+        .unwrap();
+        let loaded = intelligence
+            .load_indicators_from_file(file.path(), &ThreatFeedFormat::Json, "local-blocklist")
+            .await
+            .unwrap();
+        assert_eq!(loaded, 2);
+        assert_eq!(intelligence.get_stats().await.total_indicators, 2);
+
+        // Sender from the known-bad domain, including its subdomains
+        let mut context = create_test_email_context();
+        context.sender = "billing@mail.bad-domain.example".to_string();
+        let matches = intelligence.check_indicators(&context).await;
+        assert_eq!(matches.len(), 1);
+        assert!(matches!(&matches[0].context, MatchContext::SenderDomain(domain) if domain == "mail.bad-domain.example"));
+        assert_eq!(matches[0].indicator.source, "local-blocklist");
+        assert_eq!(matches[0].indicator.severity, ThreatSeverity::Critical);
+        assert_eq!(matches[0].confidence, 0.95);
+
+        // Links to the domain and known attachment hashes
+        let mut context = create_test_email_context();
+        context.body = "Pay here: https://bad-domain.example/invoice".to_string();
+        context.attachments.push(crate::AttachmentInfo {
+            filename: "invoice.pdf".to_string(),
+            content_type: "application/pdf".to_string(),
+            size: 1024,
+            hash: "deadbeef".to_string(),
+        });
+        let matches = intelligence.check_indicators(&context).await;
+        assert_eq!(matches.len(), 2);
+        assert!(matches!(&matches[0].context, MatchContext::ContentUrl(url) if url == "https://bad-domain.example/invoice"));
+        assert!(matches!(&matches[1].context, MatchContext::AttachmentHash(hash) if hash == "deadbeef"));
+
+        // Unrelated senders do not match
+        assert!(intelligence.check_indicators(&create_test_email_context()).await.is_empty());
+    }
+
+    #[test]
+    fn test_parse_csv_indicators() {
+        let indicators = parse_indicators(
+            "# type,value,threat_type,severity\nip_address,203.0.113.7,Spam,Low\n\nurl, http://bad.example/x\n",
+            &ThreatFeedFormat::Csv,
+            "feed",
+            std::time::Duration::from_secs(60),
+        )
+        .unwrap();
+        assert_eq!(indicators.len(), 2);
+        assert_eq!(indicators[0].indicator_type, IndicatorType::IpAddress);
+        assert_eq!(indicators[0].threat_type, ThreatType::Spam);
+        assert_eq!(indicators[0].severity, ThreatSeverity::Low);
+        assert_eq!(indicators[1].value, "http://bad.example/x");
+        assert_eq!(indicators[1].severity, ThreatSeverity::Medium);
+        assert!(indicators[1].expires_at.is_some());
+
+        assert!(parse_indicators("domain", &ThreatFeedFormat::Csv, "feed", std::time::Duration::ZERO).is_err());
+        assert!(parse_indicators("", &ThreatFeedFormat::Stix, "feed", std::time::Duration::ZERO).is_err());
+    }
+
     #[tokio::test]
     async fn test_analyze_email_no_threats() {
         let config = create_test_config();
@@ -48,17 +48,21 @@ pub mod anomaly;
 pub mod patterns;
 pub mod behavioral;
 pub mod intelligence;
+pub mod bruteforce;
+pub mod correlation;
 pub mod models;
 pub mod metrics;
 pub mod error;
 
 pub use config::{ThreatDetectionConfig, AnomalyDetectionConfig, PatternMatchingConfig,
-                BehavioralAnalysisConfig, ThreatIntelligenceConfig, ThreatFeed};
+                BehavioralAnalysisConfig, ThreatIntelligenceConfig, ThreatFeed, BruteForceConfig};
 pub use detector::{ThreatDetector, EmailContext, AttachmentInfo, DetectionStats};
 pub use anomaly::{AnomalyDetector, AnomalyScore, AnomalyResult, DetectedAnomaly, AnomalyType};
 pub use patterns::{PatternMatcher, ThreatPattern, PatternType, PatternMatch, MatchLocation};
 pub use behavioral::{BehavioralAnalyzer, BehaviorProfile, BehavioralAnomaly, BehavioralAnomalyType};
 pub use intelligence::{ThreatIntelligence, ThreatIndicator, IndicatorType, IntelligenceMatch};
+pub use bruteforce::{BruteForceDetector, AttackPattern, LoginAttempt};
+pub use correlation::CorrelationRule;
 pub use error::{ThreatDetectionError, Result};
 
 /// Threat severity levels
@@ -98,7 +102,7 @@ pub struct ThreatEvent {
 }
 
 /// Types of threats
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum ThreatType {
     /// Malware detection
     Malware,
@@ -142,6 +146,7 @@ pub struct ThreatDetectionContext {
     pub config: ThreatDetectionConfig,
     pub status: Arc<RwLock<DetectionStatus>>,
     pub events: Arc<RwLock<Vec<ThreatEvent>>>,
+    pub correlation_rules: Vec<CorrelationRule>,
 }
 
 impl ThreatDetectionContext {
@@ -151,6 +156,7 @@ impl ThreatDetectionContext {
             config,
             status: Arc::new(RwLock::new(DetectionStatus::Initializing)),
             events: Arc::new(RwLock::new(Vec::new())),
+            correlation_rules: correlation::default_rules(),
         }
     }
 
@@ -185,6 +191,19 @@ impl ThreatDetectionContext {
         let events = self.events.read().await;
         events.iter().rev().take(limit).cloned().collect()
     }
+
+    /// Correlate recent events into composite events
+    ///
+    /// Events within the correlation window that share a source or target
+    /// are promoted to a higher-severity composite event listing the
+    /// contributing event IDs. Composites are returned, not added to the
+    /// event history.
+    pub async fn correlate(&self) -> Vec<ThreatEvent> {
+        let window = chrono::Duration::from_std(self.config.correlation_window)
+            .unwrap_or_else(|_| chrono::Duration::days(1));
+        let events = self.events.read().await;
+        correlation::correlate_events(&events, &self.correlation_rules, window, chrono::Utc::now())
+    }
 }
 
 /// Initialize the threat detection system
@@ -248,6 +267,58 @@ mod tests {
         detector.start_detection().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_watch_logins_reports_brute_force() {
+        #[derive(Clone)]
+        enum AuthEvent {
+            Authentication {
+                user_id: String,
+                ip_address: String,
+                success: bool,
+            },
+            Other,
+        }
+
+        let detector = Arc::new(
+            ThreatDetector::new(ThreatDetectionConfig::default())
+                .await
+                .unwrap(),
+        );
+        let (events, subscriber) = tokio::sync::broadcast::channel(64);
+        let (threats_tx, mut threats) = tokio::sync::mpsc::channel(8);
+        detector.watch_logins(subscriber, threats_tx, |event| match event {
+            AuthEvent::Authentication {
+                user_id,
+                ip_address,
+                success,
+            } => Some(LoginAttempt {
+                account: user_id.clone(),
+                source_ip: ip_address.clone(),
+                success: *success,
+                timestamp: chrono::Utc::now(),
+            }),
+            AuthEvent::Other => None,
+        });
+
+        for _ in 0..20 {
+            events.send(AuthEvent::Other).unwrap();
+            events
+                .send(AuthEvent::Authentication {
+                    user_id: "john@example.com".to_string(),
+                    ip_address: "203.0.113.5".to_string(),
+                    success: false,
+                })
+                .unwrap();
+        }
+        drop(events);
+
+        let threat = threats.recv().await.unwrap();
+        assert_eq!(threat.threat_type, ThreatType::BruteForce);
+        assert_eq!(threat.source, "203.0.113.5");
+        assert!(threats.recv().await.is_none());
+        assert_eq!(detector.get_stats().await.unwrap().threats_detected, 1);
+    }
+
     #[tokio::test]
     async fn test_email_context_creation() {
         use std::collections::HashMap;
@@ -368,9 +439,118 @@ mod tests {
             message_id: "malicious-message-id".to_string(),
         };
 
-        let result = detector.analyze_email(&context).await.unwrap();
-        // For now, this returns None as the analysis is not implemented
-        assert!(result.is_none());
+        let event = detector.analyze_email(&context).await.unwrap().unwrap();
+        assert_eq!(event.threat_type, ThreatType::Phishing);
+        assert!(event.confidence > 0.8);
+        assert_eq!(event.source, "malicious@example.com");
+        assert_eq!(event.target.as_deref(), Some("victim@example.com"));
+
+        let stats = detector.get_stats().await.unwrap();
+        assert_eq!(stats.total_emails_analyzed, 1);
+        assert_eq!(stats.threats_detected, 1);
+    }
+
+    #[tokio::test]
+    async fn test_benign_email_analysis() {
+        use std::collections::HashMap;
+
+        let config = ThreatDetectionConfig::default();
+        let detector = ThreatDetector::new(config).await.unwrap();
+
+        let context = EmailContext {
+            sender: "alice@example.com".to_string(),
+            recipients: vec!["bob@example.com".to_string()],
+            subject: "Meeting notes".to_string(),
+            body: "Hi Bob, the notes from today's meeting are attached.".to_string(),
+            headers: HashMap::new(),
+            attachments: vec![AttachmentInfo {
+                filename: "notes.pdf".to_string(),
+                content_type: "application/pdf".to_string(),
+                size: 2048,
+                hash: "abc123".to_string(),
+            }],
+            timestamp: chrono::Utc::now(),
+            source_ip: Some("192.168.1.10".to_string()),
+            message_id: "benign-message-id".to_string(),
+        };
+
+        assert!(detector.analyze_email(&context).await.unwrap().is_none());
+
+        let stats = detector.get_stats().await.unwrap();
+        assert_eq!(stats.total_emails_analyzed, 1);
+        assert_eq!(stats.threats_detected, 0);
+    }
+
+    #[tokio::test]
+    async fn test_malware_attachment_analysis() {
+        use std::collections::HashMap;
+
+        let mut config = ThreatDetectionConfig::default();
+        config.threat_threshold = 0.7;
+        let detector = ThreatDetector::new(config).await.unwrap();
+
+        let context = EmailContext {
+            sender: "invoices@example.com".to_string(),
+            recipients: vec!["victim@example.com".to_string()],
+            subject: "Invoice".to_string(),
+            body: "The attached file removes the virus from your computer.".to_string(),
+            headers: HashMap::new(),
+            attachments: vec![AttachmentInfo {
+                filename: "invoice.pdf.exe".to_string(),
+                content_type: "application/octet-stream".to_string(),
+                size: 4096,
+                hash: "def456".to_string(),
+            }],
+            timestamp: chrono::Utc::now(),
+            source_ip: None,
+            message_id: "malware-message-id".to_string(),
+        };
+
+        let event = detector.analyze_email(&context).await.unwrap().unwrap();
+        assert_eq!(event.threat_type, ThreatType::Malware);
+        assert_eq!(event.severity, ThreatSeverity::Medium);
+    }
+
+    #[tokio::test]
+    async fn test_scan_attachment_known_hash() {
+        let config = ThreatDetectionConfig::default();
+        let detector = ThreatDetector::new(config).await.unwrap();
+
+        let attachment = AttachmentInfo::from_bytes("report.pdf", "application/pdf", b"X5O!P%@AP[4\\PZX54(P^)7CC)7}");
+        assert!(detector.scan_attachment(&attachment).is_none());
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            format!("# known malware\n{}  eicar.com\nnot-a-hash\n", attachment.hash.to_uppercase()),
+        )
+        .unwrap();
+        assert_eq!(detector.load_malware_hashes(file.path()).await.unwrap(), 1);
+
+        let event = detector.scan_attachment(&attachment).unwrap();
+        assert_eq!(event.threat_type, ThreatType::Malware);
+        assert_eq!(event.severity, ThreatSeverity::Critical);
+        assert_eq!(event.confidence, 1.0);
+        assert_eq!(event.metadata["reason"], "known_hash");
+    }
+
+    #[tokio::test]
+    async fn test_scan_attachment_heuristics() {
+        let config = ThreatDetectionConfig::default();
+        let detector = ThreatDetector::new(config).await.unwrap();
+
+        let disguised = AttachmentInfo::from_bytes("Invoice.PDF.exe", "application/octet-stream", b"MZ");
+        let event = detector.scan_attachment(&disguised).unwrap();
+        assert_eq!(event.threat_type, ThreatType::Malware);
+        assert_eq!(event.metadata["reason"], "double_extension");
+        assert!(event.confidence < 1.0);
+
+        let executable = AttachmentInfo::from_bytes("setup", "application/x-msdownload; name=setup", b"MZ");
+        let event = detector.scan_attachment(&executable).unwrap();
+        assert_eq!(event.metadata["reason"], "dangerous_content_type");
+
+        let archive = AttachmentInfo::from_bytes("photos.2024.zip", "application/zip", b"PK");
+        assert!(detector.scan_attachment(&archive).is_none());
     }
 
     #[tokio::test]
@@ -383,6 +563,52 @@ mod tests {
         assert_eq!(stats.threats_detected, 0);
     }
 
+    #[tokio::test]
+    async fn test_correlate_events() {
+        let config = ThreatDetectionConfig::default();
+        let context = ThreatDetectionContext::new(config);
+
+        let login = ThreatEvent {
+            id: "login-1".to_string(),
+            threat_type: ThreatType::SuspiciousLogin,
+            severity: ThreatSeverity::Medium,
+            description: "Login from a new country".to_string(),
+            source: "203.0.113.9".to_string(),
+            target: Some("alice@example.com".to_string()),
+            timestamp: chrono::Utc::now(),
+            metadata: std::collections::HashMap::new(),
+            confidence: 0.6,
+        };
+        let exfiltration = ThreatEvent {
+            id: "exfil-1".to_string(),
+            threat_type: ThreatType::DataExfiltration,
+            severity: ThreatSeverity::Medium,
+            description: "Mailbox export to an external address".to_string(),
+            source: "alice@example.com".to_string(),
+            target: Some("drop@example.net".to_string()),
+            timestamp: chrono::Utc::now(),
+            metadata: std::collections::HashMap::new(),
+            confidence: 0.6,
+        };
+
+        context.add_event(login.clone()).await;
+        assert!(context.correlate().await.is_empty());
+
+        context.add_event(exfiltration).await;
+        let composites = context.correlate().await;
+        assert_eq!(composites.len(), 1);
+
+        let composite = &composites[0];
+        assert_eq!(composite.severity, ThreatSeverity::Critical);
+        assert_eq!(composite.threat_type, ThreatType::DataExfiltration);
+        assert_eq!(composite.source, "alice@example.com");
+        assert_eq!(composite.metadata["correlation_rule"], "account_takeover");
+        assert_eq!(
+            composite.metadata[correlation::CORRELATED_EVENTS_KEY],
+            serde_json::json!(["exfil-1", "login-1"])
+        );
+    }
+
     #[tokio::test]
     async fn test_status_changes() {
         let config = ThreatDetectionConfig::default();
@@ -1,7 +1,17 @@
 //! Pattern matching module
 
+use crate::error::{Result, ThreatDetectionError};
+use regex::Regex;
+use std::collections::HashMap;
+
 /// Pattern matcher
-pub struct PatternMatcher;
+///
+/// Regex patterns are compiled once by [`PatternMatcher::load`]; regex
+/// patterns that were never loaded do not match.
+#[derive(Default)]
+pub struct PatternMatcher {
+    regexes: HashMap<String, Regex>,
+}
 
 /// Threat pattern
 pub struct ThreatPattern {
@@ -41,7 +51,26 @@ pub struct MatchLocation {
 impl PatternMatcher {
     /// Create new pattern matcher
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Compile the regex patterns, failing on the first invalid expression
+    pub fn load(&mut self, patterns: &[ThreatPattern]) -> Result<()> {
+        for pattern in patterns {
+            if pattern.pattern_type == PatternType::Regex
+                && !self.regexes.contains_key(&pattern.pattern)
+            {
+                let regex = Regex::new(&pattern.pattern).map_err(|err| {
+                    ThreatDetectionError::Configuration(format!(
+                        "invalid regex in pattern {}: {}",
+                        pattern.id, err
+                    ))
+                })?;
+                self.regexes.insert(pattern.pattern.clone(), regex);
+            }
+        }
+
+        Ok(())
     }
 
     /// Match patterns in text
@@ -76,6 +105,19 @@ impl PatternMatcher {
                     None
                 }
             }
+            PatternType::Regex => {
+                let found = self.regexes.get(&pattern.pattern)?.find(text)?;
+                Some(PatternMatch {
+                    pattern_id: pattern.id.clone(),
+                    matched_text: found.as_str().to_string(),
+                    location: MatchLocation {
+                        start: found.start(),
+                        end: found.end(),
+                        field: "text".to_string(),
+                    },
+                    confidence: 0.5,
+                })
+            }
             _ => {
                 // TODO: Implement other pattern types
                 None
@@ -208,6 +250,46 @@ mod tests {
         assert_eq!(matches.len(), 2);
     }
 
+    #[test]
+    fn test_regex_pattern_matching() {
+        let mut matcher = PatternMatcher::new();
+        let pattern = ThreatPattern {
+            id: "regex-pattern".to_string(),
+            pattern_type: PatternType::Regex,
+            pattern: r"(?i)\b(click here|verify account)\b".to_string(),
+            description: "Call to action".to_string(),
+        };
+
+        // Not matched until compiled
+        assert!(matcher
+            .match_patterns("Please CLICK HERE today", std::slice::from_ref(&pattern))
+            .is_empty());
+
+        matcher.load(std::slice::from_ref(&pattern)).unwrap();
+        let matches = matcher.match_patterns("Please CLICK HERE today", &[pattern]);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].matched_text, "CLICK HERE");
+        assert_eq!(matches[0].location.start, 7);
+        assert_eq!(matches[0].location.end, 17);
+    }
+
+    #[test]
+    fn test_invalid_regex_rejected_at_load() {
+        let mut matcher = PatternMatcher::new();
+        let pattern = ThreatPattern {
+            id: "broken".to_string(),
+            pattern_type: PatternType::Regex,
+            pattern: r"(unclosed".to_string(),
+            description: "Invalid expression".to_string(),
+        };
+
+        assert!(matches!(
+            matcher.load(&[pattern]),
+            Err(ThreatDetectionError::Configuration(_))
+        ));
+    }
+
     #[test]
     fn test_case_sensitive_matching() {
         let matcher = PatternMatcher::new();
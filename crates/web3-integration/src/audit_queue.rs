@@ -0,0 +1,293 @@
+//! Durable audit anchoring queue for A3Mailer
+//!
+//! Audit entries are written to a local queue file before anything is sent
+//! on-chain, so a blockchain outage delays anchoring without losing records.
+//! Queued entries are submitted and confirmed by repeated `process` passes.
+
+use crate::blockchain::{AuditEntry, BlockchainClient};
+use crate::{Result, Web3Error};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+use tracing::{info, warn, debug};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// Anchoring progress of a queued audit entry
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AnchorStatus {
+    /// Stored locally, not yet accepted by the chain
+    Pending,
+    /// Transaction sent, waiting to be mined
+    Submitted,
+    /// Transaction mined, block number recorded
+    Confirmed,
+}
+
+/// Audit entry with its anchoring progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedAuditEntry {
+    pub entry: AuditEntry,
+    pub status: AnchorStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub last_attempt: Option<DateTime<Utc>>,
+}
+
+/// Number of queued audit entries in each state
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditQueueStatus {
+    pub pending: usize,
+    pub submitted: usize,
+    pub confirmed: usize,
+}
+
+/// Audit entries persisted locally until anchored on-chain
+pub struct AuditQueue {
+    path: PathBuf,
+    entries: Mutex<Vec<QueuedAuditEntry>>,
+}
+
+impl AuditQueue {
+    /// Open the queue file, loading entries left by a previous run
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+
+        let entries = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) if !contents.trim().is_empty() => serde_json::from_str(&contents)?,
+            Ok(_) => Vec::new(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let queue = Self {
+            path,
+            entries: Mutex::new(entries),
+        };
+
+        let status = queue.status().await;
+        info!("Opened audit queue {} ({} pending, {} submitted, {} confirmed)",
+              queue.path.display(), status.pending, status.submitted, status.confirmed);
+        Ok(queue)
+    }
+
+    /// Persist an audit entry for anchoring and return its id
+    pub async fn enqueue(&self, event_data: &HashMap<String, String>) -> Result<String> {
+        let field = |name: &str, default: &str| event_data.get(name).cloned().unwrap_or_else(|| default.to_string());
+
+        let entry = AuditEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            event_type: field("event_type", "unknown"),
+            user_id: field("user_id", "system"),
+            resource: field("resource", ""),
+            action: field("action", ""),
+            timestamp: Utc::now(),
+            metadata: event_data.clone(),
+            transaction_hash: None,
+            block_number: None,
+        };
+        let id = entry.id.clone();
+
+        let mut entries = self.entries.lock().await;
+        entries.push(QueuedAuditEntry {
+            entry,
+            status: AnchorStatus::Pending,
+            attempts: 0,
+            last_error: None,
+            last_attempt: None,
+        });
+
+        if let Err(e) = persist(&self.path, &entries).await {
+            entries.pop();
+            return Err(e);
+        }
+
+        debug!("Queued audit entry {}", id);
+        Ok(id)
+    }
+
+    /// Submit pending entries and confirm submitted ones
+    ///
+    /// Failures are recorded on the entry and retried on the next pass.
+    pub async fn process(&self, client: &BlockchainClient) -> Result<AuditQueueStatus> {
+        // Work on a snapshot so enqueueing is not blocked by RPC calls
+        let work = self.entries.lock().await
+            .iter()
+            .filter(|queued| queued.status != AnchorStatus::Confirmed)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut updates = Vec::with_capacity(work.len());
+        for mut queued in work {
+            queued.last_attempt = Some(Utc::now());
+
+            if queued.status == AnchorStatus::Pending {
+                queued.attempts += 1;
+                match client.create_audit_entry(&queued.entry.metadata).await {
+                    Ok(tx_hash) => {
+                        debug!("Submitted audit entry {} in {}", queued.entry.id, tx_hash);
+                        queued.entry.transaction_hash = Some(tx_hash);
+                        queued.status = AnchorStatus::Submitted;
+                        queued.last_error = None;
+                    }
+                    Err(e) => {
+                        warn!("Failed to submit audit entry {} (attempt {}): {}", queued.entry.id, queued.attempts, e);
+                        queued.last_error = Some(e.to_string());
+                    }
+                }
+            }
+
+            if let (AnchorStatus::Submitted, Some(tx_hash)) = (queued.status, queued.entry.transaction_hash.clone()) {
+                match client.get_transaction_receipt(&tx_hash).await {
+                    Ok(receipt) if receipt["status"].as_str() == Some("0x0") => {
+                        // Reverted transactions anchor nothing, send the entry again
+                        warn!("Audit transaction {} for entry {} reverted", tx_hash, queued.entry.id);
+                        queued.entry.transaction_hash = None;
+                        queued.status = AnchorStatus::Pending;
+                        queued.last_error = Some(format!("Transaction {} reverted", tx_hash));
+                    }
+                    Ok(receipt) => match receipt["blockNumber"].as_str().and_then(|b| u64::from_str_radix(b.trim_start_matches("0x"), 16).ok()) {
+                        Some(block_number) => {
+                            info!("Audit entry {} anchored in block {}", queued.entry.id, block_number);
+                            queued.entry.block_number = Some(block_number);
+                            queued.status = AnchorStatus::Confirmed;
+                        }
+                        None => debug!("Audit entry {} not mined yet", queued.entry.id),
+                    },
+                    Err(e) => {
+                        debug!("No receipt for audit entry {} yet: {}", queued.entry.id, e);
+                        queued.last_error = Some(e.to_string());
+                    }
+                }
+            }
+
+            updates.push(queued);
+        }
+
+        let mut entries = self.entries.lock().await;
+        for update in updates {
+            if let Some(queued) = entries.iter_mut().find(|queued| queued.entry.id == update.entry.id) {
+                *queued = update;
+            }
+        }
+        persist(&self.path, &entries).await?;
+
+        Ok(count(&entries))
+    }
+
+    /// Get the number of entries in each state
+    pub async fn status(&self) -> AuditQueueStatus {
+        count(&self.entries.lock().await)
+    }
+
+    /// Get a queued entry by id
+    pub async fn get_entry(&self, id: &str) -> Option<QueuedAuditEntry> {
+        self.entries.lock().await.iter().find(|queued| queued.entry.id == id).cloned()
+    }
+}
+
+fn count(entries: &[QueuedAuditEntry]) -> AuditQueueStatus {
+    entries.iter().fold(AuditQueueStatus::default(), |mut status, queued| {
+        match queued.status {
+            AnchorStatus::Pending => status.pending += 1,
+            AnchorStatus::Submitted => status.submitted += 1,
+            AnchorStatus::Confirmed => status.confirmed += 1,
+        }
+        status
+    })
+}
+
+/// Write the queue to a sibling file and rename it over the original, so a
+/// crash never leaves a partial queue behind
+async fn persist(path: &Path, entries: &[QueuedAuditEntry]) -> Result<()> {
+    let contents = serde_json::to_string_pretty(entries)?;
+
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    tokio::fs::write(&temp_path, contents).await?;
+    tokio::fs::rename(&temp_path, path).await
+        .map_err(|e| Web3Error::Other(format!("Failed to persist audit queue {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_http;
+    use crate::Web3Config;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_entries_anchored_after_rpc_recovers() {
+        let down = Arc::new(AtomicBool::new(false));
+        let sent = Arc::new(AtomicUsize::new(0));
+        let url = mock_http::serve({
+            let down = Arc::clone(&down);
+            let sent = Arc::clone(&sent);
+            move |_, body| {
+                if down.load(Ordering::SeqCst) {
+                    return Some(("503 Service Unavailable", Vec::new()));
+                }
+
+                let request: Value = serde_json::from_slice(body).unwrap();
+                let result = match request["method"].as_str().unwrap() {
+                    "eth_chainId" => json!("0x1"),
+                    "eth_sendTransaction" => json!(format!("0x{:064x}", sent.fetch_add(1, Ordering::SeqCst) + 1)),
+                    "eth_getTransactionReceipt" => json!({"blockNumber": "0x2a", "status": "0x1"}),
+                    method => panic!("unexpected RPC method {}", method),
+                };
+                Some(("200 OK", json!({"jsonrpc": "2.0", "id": 1, "result": result}).to_string().into_bytes()))
+            }
+        }).await;
+
+        let client = BlockchainClient::new(&Web3Config { rpc_url: url, ..Default::default() }).await.unwrap();
+        let dir = std::env::temp_dir().join(format!("a3mailer-audit-queue-{}", std::process::id()));
+        let path = dir.join("audit.json");
+
+        // Entries are kept locally while the RPC node is down
+        down.store(true, Ordering::SeqCst);
+        let queue = AuditQueue::open(&path).await.unwrap();
+        let first = queue.enqueue(&[("event_type".to_string(), "login".to_string())].into()).await.unwrap();
+        let second = queue.enqueue(&[("event_type".to_string(), "delete".to_string())].into()).await.unwrap();
+
+        let status = queue.process(&client).await.unwrap();
+        assert_eq!(status, AuditQueueStatus { pending: 2, submitted: 0, confirmed: 0 });
+        let queued = queue.get_entry(&first).await.unwrap();
+        assert_eq!(queued.attempts, 1);
+        assert!(queued.last_error.is_some());
+
+        // The queue survives a restart
+        drop(queue);
+        let queue = AuditQueue::open(&path).await.unwrap();
+        assert_eq!(queue.status().await.pending, 2);
+
+        // Once the node is back every entry is anchored
+        down.store(false, Ordering::SeqCst);
+        let status = queue.process(&client).await.unwrap();
+        assert_eq!(status, AuditQueueStatus { pending: 0, submitted: 0, confirmed: 2 });
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+
+        for id in [&first, &second] {
+            let queued = queue.get_entry(id).await.unwrap();
+            assert_eq!(queued.status, AnchorStatus::Confirmed);
+            assert_eq!(queued.entry.block_number, Some(42));
+            assert!(queued.entry.transaction_hash.is_some());
+            assert_eq!(queued.attempts, 2);
+        }
+
+        // Confirmed entries are not sent again
+        queue.process(&client).await.unwrap();
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+        let reopened = AuditQueue::open(&path).await.unwrap();
+        assert_eq!(reopened.get_entry(&second).await.unwrap().entry.block_number, Some(42));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
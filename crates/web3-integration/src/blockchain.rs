@@ -5,7 +5,8 @@
 
 use crate::{Web3Config, Web3Event, Result, Web3Error};
 use std::collections::HashMap;
-use tracing::{info, warn, error, debug};
+use tokio::sync::RwLock;
+use tracing::{info, debug};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use chrono::{DateTime, Utc};
@@ -66,9 +67,8 @@ pub struct BlockchainClient {
     config: Web3Config,
     client: reqwest::Client,
     rpc_url: String,
-    chain_id: u64,
     audit_contract: Option<String>,
-    signature_cache: HashMap<String, SignatureVerification>,
+    signature_cache: RwLock<HashMap<String, SignatureVerification>>,
 }
 
 impl BlockchainClient {
@@ -81,13 +81,12 @@ impl BlockchainClient {
             .build()
             .map_err(|e| Web3Error::NetworkError(e.to_string()))?;
         
-        let mut blockchain_client = Self {
+        let blockchain_client = Self {
             config: config.clone(),
             client,
             rpc_url: config.rpc_url.clone(),
-            chain_id: 1, // Default to Ethereum mainnet
             audit_contract: config.contract_addresses.get("audit").cloned(),
-            signature_cache: HashMap::new(),
+            signature_cache: RwLock::new(HashMap::new()),
         };
         
         // Initialize blockchain connection
@@ -103,7 +102,7 @@ impl BlockchainClient {
         
         // Check cache first
         let cache_key = format!("{}:{}", message_hash, signature);
-        if let Some(cached) = self.signature_cache.get(&cache_key) {
+        if let Some(cached) = self.signature_cache.read().await.get(&cache_key) {
             debug!("Using cached signature verification result");
             return Ok(cached.is_valid);
         }
@@ -125,6 +124,7 @@ impl BlockchainClient {
         
         info!("Signature verification result for {}: {} (signer: {})", 
               message_hash, is_valid, signer_address);
+        self.signature_cache.write().await.insert(cache_key, verification);
         
         Ok(is_valid)
     }
@@ -275,7 +275,7 @@ impl BlockchainClient {
             "params": [{
                 "fromBlock": from_block.map(|b| format!("0x{:x}", b)).unwrap_or_else(|| "earliest".to_string()),
                 "toBlock": to_block.map(|b| format!("0x{:x}", b)).unwrap_or_else(|| "latest".to_string()),
-                "address": self.audit_contract.as_ref().map(|s| s.as_str()).unwrap_or(""),
+                "address": self.audit_contract.as_deref().unwrap_or(""),
                 "topics": []
             }],
             "id": 1
@@ -323,7 +323,7 @@ impl BlockchainClient {
     }
 
     /// Recover signer address from signature
-    async fn recover_signer_address(&self, message_hash: &str, signature: &str) -> Result<String> {
+    async fn recover_signer_address(&self, _message_hash: &str, signature: &str) -> Result<String> {
         // In a real implementation, this would use cryptographic libraries
         // to recover the public key and derive the address from the signature
         // For now, we'll simulate this process
@@ -387,7 +387,7 @@ impl BlockchainClient {
     }
 
     /// Get transaction receipt
-    async fn get_transaction_receipt(&self, tx_hash: &str) -> Result<Value> {
+    pub(crate) async fn get_transaction_receipt(&self, tx_hash: &str) -> Result<Value> {
         let request_data = json!({
             "jsonrpc": "2.0",
             "method": "eth_getTransactionReceipt",
@@ -5,15 +5,64 @@
 
 use crate::{Web3Config, DidDocument, PublicKey, ServiceEndpoint, Result, Web3Error};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
 use chrono::{DateTime, Utc};
-use tracing::{info, warn, error, debug};
-use serde_json::Value;
+use tracing::{info, warn, debug};
+use serde_json::{json, Value};
+use k256::ecdsa::{RecoveryId, Signature as Secp256k1Signature, VerifyingKey as Secp256k1VerifyingKey};
+use sha3::{Digest, Keccak256};
+
+/// ERC-1056 DID registry on Ethereum mainnet, used when no `did_registry` contract is configured
+const DEFAULT_DID_REGISTRY: &str = "0xdca7ef03e98e0dc2b855be647c39abe984fcf21b";
+
+/// Selector of the ERC-1056 `identityOwner(address)` function
+const IDENTITY_OWNER_SELECTOR: &str = "8733d4e8";
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Signature scheme used to sign with a DID
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignatureScheme {
+    Ed25519,
+    /// Ethereum signed message, verified by recovering the signer address
+    Secp256k1,
+}
+
+impl SignatureScheme {
+    /// Get the scheme used by a DID method
+    pub fn for_did(did: &str) -> Result<Self> {
+        match did.split(':').nth(1) {
+            Some("key") => Ok(SignatureScheme::Ed25519),
+            Some("ethr") => Ok(SignatureScheme::Secp256k1),
+            Some(method) => Err(Web3Error::UnsupportedDidMethod(method.to_string())),
+            None => Err(Web3Error::DidError(format!("Invalid DID: {}", did))),
+        }
+    }
+
+    /// Verification key type the scheme verifies against
+    fn key_type(&self) -> &'static str {
+        match self {
+            SignatureScheme::Ed25519 => "Ed25519VerificationKey2018",
+            SignatureScheme::Secp256k1 => "EcdsaSecp256k1RecoveryMethod2020",
+        }
+    }
+}
+
+/// Resolved DID document held in the cache
+#[derive(Debug, Clone)]
+struct CachedDocument {
+    document: DidDocument,
+    expires_at: DateTime<Utc>,
+}
 
 /// DID Manager for handling decentralized identities
 pub struct DidManager {
     config: Web3Config,
     resolver_client: reqwest::Client,
-    cache: HashMap<String, (DidDocument, DateTime<Utc>)>,
+    cache: RwLock<HashMap<String, CachedDocument>>,
+    /// Resolutions that went past the cache
+    fetches: AtomicU64,
 }
 
 impl DidManager {
@@ -29,7 +78,8 @@ impl DidManager {
         Ok(Self {
             config: config.clone(),
             resolver_client,
-            cache: HashMap::new(),
+            cache: RwLock::new(HashMap::new()),
+            fetches: AtomicU64::new(0),
         })
     }
 
@@ -56,29 +106,153 @@ impl DidManager {
     }
 
     /// Resolve a DID to get its document
+    ///
+    /// Documents are cached for `cache_ttl_seconds`. When resolving an
+    /// expired entry fails, the stale document is served for up to
+    /// `stale_grace_seconds` past its expiry.
     pub async fn resolve_did(&self, did: &str) -> Result<DidDocument> {
         debug!("Resolving DID: {}", did);
         
         // Check cache first
-        if let Some((document, cached_at)) = self.cache.get(did) {
-            let cache_age = Utc::now().signed_duration_since(*cached_at);
-            if cache_age.num_minutes() < 60 { // Cache for 1 hour
+        let cached = self.cache.read().unwrap().get(did).cloned();
+        if let Some(cached) = &cached {
+            if cached.expires_at > Utc::now() {
                 debug!("Returning cached DID document for: {}", did);
-                return Ok(document.clone());
+                return Ok(cached.document.clone());
             }
         }
         
-        // Resolve from network
-        let document = self.resolve_did_from_network(did).await?;
+        let document = match self.fetch_did(did).await {
+            Ok(document) => document,
+            Err(e) => {
+                let grace = chrono::Duration::seconds(self.config.did.stale_grace_seconds as i64);
+                return match cached {
+                    Some(cached) if cached.expires_at + grace > Utc::now() => {
+                        warn!("Failed to resolve DID {}, serving stale document: {}", did, e);
+                        Ok(cached.document)
+                    }
+                    _ => Err(e),
+                };
+            }
+        };
         
         // Cache the result
-        let mut cache = &mut self.cache;
-        cache.insert(did.to_string(), (document.clone(), Utc::now()));
+        let expires_at = Utc::now() + chrono::Duration::seconds(self.config.did.cache_ttl_seconds as i64);
+        self.cache.write().unwrap().insert(did.to_string(), CachedDocument {
+            document: document.clone(),
+            expires_at,
+        });
         
         info!("Successfully resolved DID: {}", did);
         Ok(document)
     }
 
+    /// Drop a DID from the cache so the next resolution fetches it again
+    pub fn invalidate(&self, did: &str) {
+        if self.cache.write().unwrap().remove(did).is_some() {
+            debug!("Invalidated cached DID document for: {}", did);
+        }
+    }
+
+    /// Resolve a DID with the resolver for its method, bypassing the cache
+    async fn fetch_did(&self, did: &str) -> Result<DidDocument> {
+        self.fetches.fetch_add(1, Ordering::Relaxed);
+        
+        let method = did.strip_prefix("did:")
+            .and_then(|rest| rest.split(':').next())
+            .ok_or_else(|| Web3Error::DidError(format!("Invalid DID: {}", did)))?;
+        
+        let document = match method {
+            "key" => self.resolve_key_did(did)?,
+            "ethr" => self.resolve_ethr_did(did).await?,
+            "web" | "ion" => self.resolve_did_from_network(did).await?,
+            _ => return Err(Web3Error::UnsupportedDidMethod(method.to_string())),
+        };
+        
+        Ok(document)
+    }
+
+    /// Verify a hex encoded signature over a message against a DID's authentication keys
+    pub async fn verify_signature(&self, did: &str, message: &[u8], signature: &str) -> Result<bool> {
+        let scheme = SignatureScheme::for_did(did)?;
+        let document = self.resolve_did(did).await?;
+        verify_document_signature(&document, scheme, message, signature)
+    }
+
+    /// Resolve a `did:key` locally from the public key encoded in the identifier
+    fn resolve_key_did(&self, did: &str) -> Result<DidDocument> {
+        let fingerprint = did.strip_prefix("did:key:")
+            .ok_or_else(|| Web3Error::DidError(format!("Invalid did:key: {}", did)))?;
+        
+        // Only base58btc multibase ('z') is used by did:key
+        let encoded = fingerprint.strip_prefix('z')
+            .ok_or_else(|| Web3Error::DidError(format!("Unsupported multibase encoding in {}", did)))?;
+        let decoded = decode_base58(encoded)
+            .ok_or_else(|| Web3Error::DidError(format!("Invalid base58 public key in {}", did)))?;
+        
+        // The key is prefixed with its multicodec type
+        let (key_type, key) = match decoded.as_slice() {
+            [0xed, 0x01, key @ ..] if key.len() == 32 => ("Ed25519VerificationKey2018", key),
+            [0xe7, 0x01, key @ ..] if key.len() == 33 => ("EcdsaSecp256k1VerificationKey2019", key),
+            _ => return Err(Web3Error::DidError(format!("Unsupported public key type in {}", did))),
+        };
+        
+        let key_id = format!("{}#{}", did, fingerprint);
+        let now = Utc::now();
+        
+        Ok(DidDocument {
+            id: did.to_string(),
+            public_keys: vec![PublicKey {
+                id: key_id.clone(),
+                key_type: key_type.to_string(),
+                controller: did.to_string(),
+                public_key_hex: hex::encode(key),
+            }],
+            authentication: vec![key_id],
+            service_endpoints: Vec::new(),
+            created: now,
+            updated: now,
+        })
+    }
+
+    /// Resolve a `did:ethr` by reading its owner from the ERC-1056 registry
+    async fn resolve_ethr_did(&self, did: &str) -> Result<DidDocument> {
+        // The identity is the last segment, after an optional network name
+        let address = did.rsplit(':').next().unwrap_or_default();
+        if !self.validate_ethr_did(&[address]) {
+            return Err(Web3Error::DidError(format!("Invalid Ethereum address in {}", did)));
+        }
+        
+        let registry = self.config.contract_addresses.get("did_registry")
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_DID_REGISTRY);
+        
+        let request_data = json!({
+            "jsonrpc": "2.0",
+            "method": "eth_call",
+            "params": [{
+                "to": registry,
+                "data": format!("0x{}{:0>64}", IDENTITY_OWNER_SELECTOR, address[2..].to_lowercase()),
+            }, "latest"],
+            "id": 1
+        });
+        
+        debug!("Reading owner of {} from DID registry {}", address, registry);
+        
+        let response: Value = self.resolver_client
+            .post(&self.config.rpc_url)
+            .header("Content-Type", "application/json")
+            .json(&request_data)
+            .send()
+            .await
+            .map_err(|e| Web3Error::NetworkError(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| Web3Error::SerializationError(e.to_string()))?;
+        
+        ethr_did_document(did, &response)
+    }
+
     /// Resolve DID from network
     async fn resolve_did_from_network(&self, did: &str) -> Result<DidDocument> {
         let resolver_url = format!("{}/1.0/identifiers/{}", self.config.did_resolver_url, did);
@@ -290,18 +464,365 @@ impl DidManager {
 
     /// Get DID manager status
     pub async fn get_status(&self) -> Result<String> {
-        let cache_size = self.cache.len();
-        Ok(format!("active (cached DIDs: {})", cache_size))
+        let cache_size = self.get_cache_size();
+        Ok(format!("active (cached DIDs: {}, fetches: {})", cache_size, self.get_fetch_count()))
     }
 
     /// Clear DID cache
     pub async fn clear_cache(&mut self) {
-        self.cache.clear();
+        self.cache.write().unwrap().clear();
         info!("DID cache cleared");
     }
 
     /// Get cached DID count
     pub fn get_cache_size(&self) -> usize {
-        self.cache.len()
+        self.cache.read().unwrap().len()
+    }
+
+    /// Get the number of resolutions not served from the cache
+    pub fn get_fetch_count(&self) -> u64 {
+        self.fetches.load(Ordering::Relaxed)
+    }
+}
+
+/// Build a `did:ethr` document from an `identityOwner` call response
+///
+/// The owner controls the identity, so its account is the verification key.
+fn ethr_did_document(did: &str, response: &Value) -> Result<DidDocument> {
+    if let Some(error) = response.get("error") {
+        return Err(Web3Error::BlockchainError(format!(
+            "RPC error: {}",
+            error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error")
+        )));
+    }
+    
+    // The owner is returned as an address left-padded to 32 bytes
+    let result = response["result"].as_str()
+        .and_then(|result| result.strip_prefix("0x"))
+        .filter(|result| result.len() == 64 && result.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| Web3Error::DidError(format!("Invalid DID registry response for {}", did)))?;
+    let owner = format!("0x{}", &result[24..]);
+    
+    let key_id = format!("{}#controller", did);
+    let now = Utc::now();
+    
+    Ok(DidDocument {
+        id: did.to_string(),
+        public_keys: vec![PublicKey {
+            id: key_id.clone(),
+            key_type: "EcdsaSecp256k1RecoveryMethod2020".to_string(),
+            controller: did.to_string(),
+            public_key_hex: owner,
+        }],
+        authentication: vec![key_id],
+        service_endpoints: Vec::new(),
+        created: now,
+        updated: now,
+    })
+}
+
+/// Verify a signature against the authentication keys of a DID document
+fn verify_document_signature(document: &DidDocument, scheme: SignatureScheme, message: &[u8], signature: &str) -> Result<bool> {
+    let keys = document.public_keys.iter()
+        .filter(|key| key.key_type == scheme.key_type() && document.authentication.contains(&key.id))
+        .collect::<Vec<_>>();
+    if keys.is_empty() {
+        return Err(Web3Error::NoVerificationKey(document.id.clone()));
+    }
+    
+    let signature = match hex::decode(signature.trim_start_matches("0x")) {
+        Ok(signature) => signature,
+        Err(e) => {
+            debug!("Malformed signature for {}: {}", document.id, e);
+            return Ok(false);
+        }
+    };
+    
+    Ok(keys.iter().any(|key| match scheme {
+        SignatureScheme::Ed25519 => verify_ed25519(&key.public_key_hex, message, &signature),
+        SignatureScheme::Secp256k1 => verify_secp256k1(&key.public_key_hex, message, &signature),
+    }))
+}
+
+/// Verify an Ed25519 signature with a hex encoded public key
+fn verify_ed25519(public_key_hex: &str, message: &[u8], signature: &[u8]) -> bool {
+    match hex::decode(public_key_hex) {
+        Ok(public_key) => ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, public_key)
+            .verify(message, signature)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Verify an Ethereum signed message by recovering the signer address
+///
+/// Signatures are 65 bytes, `r || s || v` with `v` either 0/1 or 27/28.
+fn verify_secp256k1(address: &str, message: &[u8], signature: &[u8]) -> bool {
+    let (signature, v) = match signature {
+        [signature @ .., v] if signature.len() == 64 => (signature, *v),
+        _ => return false,
+    };
+    
+    let recovery_id = match RecoveryId::from_byte(if v >= 27 { v - 27 } else { v }) {
+        Some(recovery_id) => recovery_id,
+        None => return false,
+    };
+    let signature = match Secp256k1Signature::from_slice(signature) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    
+    match Secp256k1VerifyingKey::recover_from_prehash(&ethereum_message_hash(message), &signature, recovery_id) {
+        Ok(key) => ethereum_address(&key).eq_ignore_ascii_case(address),
+        Err(_) => false,
+    }
+}
+
+/// Hash a message the way Ethereum wallets sign it (EIP-191)
+fn ethereum_message_hash(message: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()));
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+/// Ethereum address of a secp256k1 public key
+fn ethereum_address(key: &Secp256k1VerifyingKey) -> String {
+    let point = key.to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    format!("0x{}", hex::encode(&hash[12..]))
+}
+
+/// Decode a base58 (bitcoin alphabet) string
+fn decode_base58(encoded: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    
+    for c in encoded.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in bytes.iter_mut().rev() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+    
+    // Each leading '1' encodes a leading zero byte
+    let zeros = encoded.bytes().take_while(|&c| c == b'1').count();
+    let mut decoded = vec![0; zeros];
+    decoded.extend(bytes);
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DidConfig;
+
+    #[tokio::test]
+    async fn test_resolve_key_did() {
+        let manager = DidManager::new(&Web3Config::default()).await.unwrap();
+        let did = "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp";
+        
+        let document = manager.resolve_did(did).await.unwrap();
+        assert_eq!(document.id, did);
+        assert_eq!(document.public_keys.len(), 1);
+        
+        let key = &document.public_keys[0];
+        assert_eq!(key.id, format!("{}#z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp", did));
+        assert_eq!(key.key_type, "Ed25519VerificationKey2018");
+        assert_eq!(key.controller, did);
+        assert_eq!(key.public_key_hex, "3b6a27bcceb6a42d62a3a8d02a6f0d73653215771de243a63ac048a18b59da29");
+        assert_eq!(document.authentication, vec![key.id.clone()]);
+        assert_eq!(manager.get_cache_size(), 1);
+    }
+
+    #[test]
+    fn test_ethr_did_document_from_registry() {
+        let did = "did:ethr:0xb9c5714089478a327f09197987f16f9e5d936e8a";
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": "0x0000000000000000000000001b4f8e4a3b6c1a2c7d9e0f1a2b3c4d5e6f708192"
+        });
+        
+        let document = ethr_did_document(did, &response).unwrap();
+        assert_eq!(document.id, did);
+        assert_eq!(document.public_keys[0].id, format!("{}#controller", did));
+        assert_eq!(document.public_keys[0].key_type, "EcdsaSecp256k1RecoveryMethod2020");
+        assert_eq!(document.public_keys[0].public_key_hex, "0x1b4f8e4a3b6c1a2c7d9e0f1a2b3c4d5e6f708192");
+        assert_eq!(document.authentication, vec![format!("{}#controller", did)]);
+        
+        let error = json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32000, "message": "execution reverted"}});
+        assert!(matches!(ethr_did_document(did, &error), Err(Web3Error::BlockchainError(_))));
+    }
+
+    /// Encode bytes as base58, for building test DIDs
+    fn encode_base58(bytes: &[u8]) -> String {
+        let mut digits: Vec<u8> = Vec::new();
+        for &byte in bytes {
+            let mut carry = byte as u32;
+            for digit in digits.iter_mut().rev() {
+                carry += (*digit as u32) << 8;
+                *digit = (carry % 58) as u8;
+                carry /= 58;
+            }
+            while carry > 0 {
+                digits.insert(0, (carry % 58) as u8);
+                carry /= 58;
+            }
+        }
+        
+        let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+        std::iter::repeat_n('1', zeros)
+            .chain(digits.iter().map(|&digit| BASE58_ALPHABET[digit as usize] as char))
+            .collect()
+    }
+
+    fn key_did(codec: [u8; 2], public_key: &[u8]) -> String {
+        format!("did:key:z{}", encode_base58(&[&codec[..], public_key].concat()))
+    }
+
+    fn sign_ethereum_message(key: &k256::ecdsa::SigningKey, message: &[u8]) -> String {
+        let (signature, recovery_id) = key.sign_prehash_recoverable(&ethereum_message_hash(message)).unwrap();
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(recovery_id.to_byte() + 27);
+        format!("0x{}", hex::encode(bytes))
+    }
+
+    #[test]
+    fn test_base58_round_trip() {
+        let bytes = decode_base58("z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp".trim_start_matches('z')).unwrap();
+        assert_eq!(encode_base58(&bytes), "6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp");
+        assert_eq!(decode_base58("11"), Some(vec![0, 0]));
+        assert_eq!(decode_base58("0OIl"), None);
+    }
+
+    #[tokio::test]
+    async fn test_verify_ed25519_signature() {
+        use ring::signature::{Ed25519KeyPair, KeyPair};
+        
+        let manager = DidManager::new(&Web3Config::default()).await.unwrap();
+        let sender = Ed25519KeyPair::from_seed_unchecked(&[7; 32]).unwrap();
+        let other = Ed25519KeyPair::from_seed_unchecked(&[8; 32]).unwrap();
+        let did = key_did([0xed, 0x01], sender.public_key().as_ref());
+        let message = b"From: alice@example.org\r\nSubject: hello\r\n\r\nHi Bob";
+        
+        let signature = hex::encode(sender.sign(message));
+        assert!(manager.verify_signature(&did, message, &signature).await.unwrap());
+        
+        // Tampered payload
+        let tampered = b"From: alice@example.org\r\nSubject: hello\r\n\r\nHi Eve";
+        assert!(!manager.verify_signature(&did, tampered, &signature).await.unwrap());
+        
+        // Signed by a different key
+        let forged = hex::encode(other.sign(message));
+        assert!(!manager.verify_signature(&did, message, &forged).await.unwrap());
+    }
+
+    #[test]
+    fn test_verify_secp256k1_signature() {
+        let sender = k256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+        let other = k256::ecdsa::SigningKey::from_slice(&[2; 32]).unwrap();
+        let address = ethereum_address(sender.verifying_key());
+        let did = format!("did:ethr:{}", address);
+        let document = ethr_did_document(&did, &json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": format!("0x{:0>64}", &address[2..]),
+        })).unwrap();
+        let message = b"From: alice@example.org\r\nSubject: hello\r\n\r\nHi Bob";
+        
+        let signature = sign_ethereum_message(&sender, message);
+        assert!(verify_document_signature(&document, SignatureScheme::Secp256k1, message, &signature).unwrap());
+        
+        // Tampered payload
+        let tampered = b"From: alice@example.org\r\nSubject: hello\r\n\r\nHi Eve";
+        assert!(!verify_document_signature(&document, SignatureScheme::Secp256k1, tampered, &signature).unwrap());
+        
+        // Signed by a different key
+        let forged = sign_ethereum_message(&other, message);
+        assert!(!verify_document_signature(&document, SignatureScheme::Secp256k1, message, &forged).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_signature_without_usable_key() {
+        let manager = DidManager::new(&Web3Config::default()).await.unwrap();
+        
+        // A did:key holding a secp256k1 key has no Ed25519 key to verify against
+        let key = k256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+        let did = key_did([0xe7, 0x01], key.verifying_key().to_encoded_point(true).as_bytes());
+        
+        assert!(matches!(
+            manager.verify_signature(&did, b"message", "00").await,
+            Err(Web3Error::NoVerificationKey(id)) if id == did
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_did_cached_within_ttl() {
+        let manager = DidManager::new(&Web3Config::default()).await.unwrap();
+        let did = "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp";
+        
+        let first = manager.resolve_did(did).await.unwrap();
+        let second = manager.resolve_did(did).await.unwrap();
+        assert_eq!(manager.get_fetch_count(), 1);
+        assert_eq!(first.public_keys[0].public_key_hex, second.public_keys[0].public_key_hex);
+        
+        // Invalidation forces a refetch
+        manager.invalidate(did);
+        assert_eq!(manager.get_cache_size(), 0);
+        manager.resolve_did(did).await.unwrap();
+        assert_eq!(manager.get_fetch_count(), 2);
+        
+        // Expired entries are refetched
+        let config = Web3Config {
+            did: DidConfig { cache_ttl_seconds: 0, stale_grace_seconds: 0 },
+            ..Default::default()
+        };
+        let manager = DidManager::new(&config).await.unwrap();
+        manager.resolve_did(did).await.unwrap();
+        manager.resolve_did(did).await.unwrap();
+        assert_eq!(manager.get_fetch_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_did_serves_stale_within_grace() {
+        // Nothing listens on the resolver, so every fetch fails
+        let config = Web3Config {
+            did_resolver_url: "http://127.0.0.1:9".to_string(),
+            did: DidConfig { cache_ttl_seconds: 60, stale_grace_seconds: 300 },
+            ..Default::default()
+        };
+        let manager = DidManager::new(&config).await.unwrap();
+        let did = "did:web:example.org";
+        let document = manager.resolve_key_did("did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp").unwrap();
+        let cache = |expired_for: i64| {
+            manager.cache.write().unwrap().insert(did.to_string(), CachedDocument {
+                document: document.clone(),
+                expires_at: Utc::now() - chrono::Duration::seconds(expired_for),
+            });
+        };
+        
+        cache(60);
+        assert_eq!(manager.resolve_did(did).await.unwrap().id, document.id);
+        
+        cache(600);
+        assert!(manager.resolve_did(did).await.is_err());
+        assert_eq!(manager.get_fetch_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_did_method() {
+        let manager = DidManager::new(&Web3Config::default()).await.unwrap();
+        
+        assert!(matches!(
+            manager.resolve_did("did:sov:WRfXPg8dantKVubE3HX8pw").await,
+            Err(Web3Error::UnsupportedDidMethod(method)) if method == "sov"
+        ));
+        assert!(!manager.verify_did("did:sov:WRfXPg8dantKVubE3HX8pw").await.unwrap());
     }
 }
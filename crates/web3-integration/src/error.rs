@@ -10,12 +10,24 @@ pub type Result<T> = std::result::Result<T, Web3Error>;
 pub enum Web3Error {
     /// DID-related errors
     DidError(String),
+
+    /// DID method without a resolver
+    UnsupportedDidMethod(String),
+
+    /// DID without an authentication key for the signature scheme
+    NoVerificationKey(String),
     
     /// Smart contract errors
     ContractError(String),
+
+    /// Contract execution reverted, with the reason when the contract gave one
+    ContractReverted { reason: Option<String> },
     
     /// IPFS-related errors
     IpfsError(String),
+
+    /// Upload larger than the configured limit
+    FileTooLarge { size: u64, max_size: u64 },
     
     /// Blockchain communication errors
     BlockchainError(String),
@@ -43,8 +55,13 @@ impl fmt::Display for Web3Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Web3Error::DidError(msg) => write!(f, "DID error: {}", msg),
+            Web3Error::UnsupportedDidMethod(method) => write!(f, "Unsupported DID method: {}", method),
+            Web3Error::NoVerificationKey(did) => write!(f, "No usable verification key for DID: {}", did),
             Web3Error::ContractError(msg) => write!(f, "Smart contract error: {}", msg),
+            Web3Error::ContractReverted { reason: Some(reason) } => write!(f, "Contract execution reverted: {}", reason),
+            Web3Error::ContractReverted { reason: None } => write!(f, "Contract execution reverted"),
             Web3Error::IpfsError(msg) => write!(f, "IPFS error: {}", msg),
+            Web3Error::FileTooLarge { size, max_size } => write!(f, "File of {} bytes exceeds the {} byte upload limit", size, max_size),
             Web3Error::BlockchainError(msg) => write!(f, "Blockchain error: {}", msg),
             Web3Error::NetworkError(msg) => write!(f, "Network error: {}", msg),
             Web3Error::AuthError(msg) => write!(f, "Authentication error: {}", msg),
@@ -5,8 +5,8 @@
 
 use crate::{Web3Config, IpfsResult, Result, Web3Error};
 use std::collections::HashMap;
-use std::io::Read;
-use tracing::{info, warn, error, debug};
+use std::time::Duration;
+use tracing::{info, warn, debug};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use chrono::{DateTime, Utc};
@@ -64,6 +64,9 @@ pub struct IpfsClient {
     file_cache: HashMap<String, IpfsFile>,
 }
 
+/// Pinning service used when only an API key is configured
+const DEFAULT_PINNING_ENDPOINT: &str = "https://api.pinata.cloud";
+
 /// Pinning service configuration
 #[derive(Debug, Clone)]
 struct PinningService {
@@ -83,25 +86,19 @@ impl IpfsClient {
             .map_err(|e| Web3Error::NetworkError(e.to_string()))?;
         
         // Extract IPFS configuration
-        let api_url = config.contract_addresses
-            .get("ipfs_api")
-            .cloned()
-            .unwrap_or_else(|| "http://localhost:5001".to_string());
-        
-        let gateway_url = config.ipfs_gateway.clone();
+        let api_url = config.ipfs.api_url.trim_end_matches('/').to_string();
+        let gateway_url = config.ipfs_gateway.trim_end_matches('/').to_string();
         
         // Initialize pinning service if configured
-        let pinning_service = if let Some(api_key) = config.contract_addresses.get("pinata_api_key") {
-            Some(PinningService {
-                name: "pinata".to_string(),
-                api_key: api_key.clone(),
-                endpoint: "https://api.pinata.cloud".to_string(),
-            })
-        } else {
-            None
-        };
-        
-        let mut client = Self {
+        let pinning_service = config.ipfs.pinning_api_key.as_ref().map(|api_key| PinningService {
+            name: "pinata".to_string(),
+            api_key: api_key.clone(),
+            endpoint: config.ipfs.pinning_endpoint
+                .clone()
+                .unwrap_or_else(|| DEFAULT_PINNING_ENDPOINT.to_string()),
+        });
+        
+        let client = Self {
             config: config.clone(),
             client,
             api_url,
@@ -126,6 +123,14 @@ impl IpfsClient {
     pub async fn store_data_with_options(&self, data: &[u8], options: &UploadOptions) -> Result<IpfsResult> {
         debug!("Storing {} bytes on IPFS", data.len());
         
+        let max_size = self.config.ipfs.max_file_size_mb * 1024 * 1024;
+        if data.len() as u64 > max_size {
+            return Err(Web3Error::FileTooLarge {
+                size: data.len() as u64,
+                max_size,
+            });
+        }
+        
         // Prepare multipart form data
         let form = reqwest::multipart::Form::new()
             .part("file", reqwest::multipart::Part::bytes(data.to_vec())
@@ -182,7 +187,10 @@ impl IpfsClient {
                 if let Some(file_hash) = json_obj["Hash"].as_str() {
                     hash = file_hash.to_string();
                 }
-                if let Some(file_size) = json_obj["Size"].as_u64() {
+                // The API reports the size as a string
+                if let Some(file_size) = json_obj["Size"].as_u64()
+                    .or_else(|| json_obj["Size"].as_str().and_then(|s| s.parse().ok()))
+                {
                     size = file_size;
                 }
                 if let Some(name) = json_obj["Name"].as_str() {
@@ -215,36 +223,47 @@ impl IpfsClient {
     }
 
     /// Retrieve data from IPFS
+    ///
+    /// Content that cannot be found is searched for until the configured
+    /// retrieve timeout, then reported as a `TimeoutError`.
     pub async fn retrieve_data(&self, hash: &str) -> Result<Vec<u8>> {
         debug!("Retrieving data from IPFS: {}", hash);
         
-        // Try gateway first for better performance
-        let gateway_url = format!("{}/ipfs/{}", self.gateway_url, hash);
-        
-        let response = self.client
-            .get(&gateway_url)
-            .timeout(std::time::Duration::from_secs(30))
-            .send()
-            .await;
+        let timeout_seconds = self.config.ipfs.retrieve_timeout_seconds;
+        match tokio::time::timeout(Duration::from_secs(timeout_seconds), self.fetch_data(hash, timeout_seconds)).await {
+            Ok(result) => result,
+            Err(_) => {
+                warn!("Timed out retrieving {} from IPFS", hash);
+                Err(Web3Error::TimeoutError(format!(
+                    "Retrieving {} from IPFS took longer than {}s",
+                    hash, timeout_seconds
+                )))
+            }
+        }
+    }
+
+    /// Fetch content through the IPFS API, falling back to the gateway
+    async fn fetch_data(&self, hash: &str, timeout_seconds: u64) -> Result<Vec<u8>> {
+        // The node stops searching for missing content after the same timeout
+        let api_url = format!("{}/api/v0/cat?arg={}&timeout={}s", self.api_url, hash, timeout_seconds);
         
-        match response {
+        match self.client.post(&api_url).send().await {
             Ok(resp) if resp.status().is_success() => {
                 let data = resp.bytes().await
                     .map_err(|e| Web3Error::NetworkError(e.to_string()))?;
                 
-                info!("Retrieved {} bytes from IPFS gateway", data.len());
+                info!("Retrieved {} bytes from IPFS API", data.len());
                 return Ok(data.to_vec());
             }
-            _ => {
-                debug!("Gateway failed, trying local IPFS API");
-            }
+            Ok(resp) => debug!("IPFS API cat failed with status {}, trying gateway", resp.status()),
+            Err(e) => debug!("IPFS API cat failed, trying gateway: {}", e),
         }
         
-        // Fallback to local IPFS API
-        let api_url = format!("{}/api/v0/cat?arg={}", self.api_url, hash);
+        // Fallback to the gateway
+        let gateway_url = format!("{}/ipfs/{}", self.gateway_url, hash);
         
         let response = self.client
-            .post(&api_url)
+            .get(&gateway_url)
             .send()
             .await
             .map_err(|e| Web3Error::NetworkError(e.to_string()))?;
@@ -259,7 +278,7 @@ impl IpfsClient {
         let data = response.bytes().await
             .map_err(|e| Web3Error::NetworkError(e.to_string()))?;
         
-        info!("Retrieved {} bytes from IPFS API", data.len());
+        info!("Retrieved {} bytes from IPFS gateway", data.len());
         Ok(data.to_vec())
     }
 
@@ -324,7 +343,7 @@ impl IpfsClient {
         }
         
         // Also pin to external service if configured
-        if let Some(_) = &self.pinning_service {
+        if self.pinning_service.is_some() {
             if let Err(e) = self.pin_to_service(hash).await {
                 warn!("Failed to pin to external service: {}", e);
             }
@@ -485,7 +504,7 @@ impl IpfsClient {
             });
             
             let response = self.client
-                .post(&format!("{}/pinning/pinByHash", service.endpoint))
+                .post(format!("{}/pinning/pinByHash", service.endpoint))
                 .header("Authorization", format!("Bearer {}", service.api_key))
                 .json(&pin_data)
                 .send()
@@ -518,3 +537,95 @@ impl IpfsClient {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IpfsConfig;
+    use crate::mock_http;
+    use std::sync::{Arc, Mutex};
+
+    const STORED_CID: &str = "QmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8u";
+
+    /// IPFS API serving fixed content and recording uploads
+    ///
+    /// Requests for unknown content never complete, like a node searching
+    /// the network for a CID nobody has.
+    #[derive(Clone, Default)]
+    struct MockIpfs {
+        files: Arc<HashMap<String, Vec<u8>>>,
+        uploads: Arc<Mutex<Vec<Vec<u8>>>>,
+    }
+
+    impl MockIpfs {
+        async fn start(self) -> String {
+            mock_http::serve(move |path, body| self.respond(path, body)).await
+        }
+
+        fn respond(&self, path: &str, body: &[u8]) -> mock_http::Response {
+            let arg = path.split(['?', '&']).find_map(|param| param.strip_prefix("arg="));
+
+            if path.starts_with("/api/v0/version") {
+                Some(("200 OK", br#"{"Version":"0.29.0"}"#.to_vec()))
+            } else if path.starts_with("/api/v0/add") {
+                self.uploads.lock().unwrap().push(body.to_vec());
+                Some(("200 OK", format!(r#"{{"Name":"data","Hash":"{}","Size":"25"}}"#, STORED_CID).into_bytes()))
+            } else if let Some(cid) = arg.filter(|_| path.starts_with("/api/v0/cat")).or_else(|| path.strip_prefix("/ipfs/")) {
+                self.files.get(cid).map(|data| ("200 OK", data.clone()))
+            } else {
+                Some(("404 Not Found", Vec::new()))
+            }
+        }
+    }
+
+    async fn client(mock: &MockIpfs, ipfs: IpfsConfig) -> IpfsClient {
+        let url = mock.clone().start().await;
+        let config = Web3Config {
+            ipfs_gateway: url.clone(),
+            ipfs: IpfsConfig { api_url: url, ..ipfs },
+            ..Default::default()
+        };
+        IpfsClient::new(&config).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_add_and_cat() {
+        let mock = MockIpfs {
+            files: Arc::new([(STORED_CID.to_string(), b"Hello from the IPFS mock".to_vec())].into()),
+            ..Default::default()
+        };
+        let client = client(&mock, IpfsConfig::default()).await;
+
+        let result = client.store_data(b"Hello from the IPFS mock").await.unwrap();
+        assert_eq!(result.hash, STORED_CID);
+        assert_eq!(result.size, 25);
+
+        let uploads = mock.uploads.lock().unwrap().clone();
+        assert_eq!(uploads.len(), 1);
+        assert!(uploads[0].windows(24).any(|window| window == b"Hello from the IPFS mock"));
+
+        assert_eq!(client.retrieve_data(STORED_CID).await.unwrap(), b"Hello from the IPFS mock");
+    }
+
+    #[tokio::test]
+    async fn test_upload_size_limit() {
+        let mock = MockIpfs::default();
+        let client = client(&mock, IpfsConfig { max_file_size_mb: 1, ..Default::default() }).await;
+
+        let data = vec![0; 1024 * 1024 + 1];
+        assert!(matches!(
+            client.store_data(&data).await,
+            Err(Web3Error::FileTooLarge { size, max_size }) if size == 1024 * 1024 + 1 && max_size == 1024 * 1024
+        ));
+        assert!(mock.uploads.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_missing_cid_times_out() {
+        let mock = MockIpfs::default();
+        let client = client(&mock, IpfsConfig { retrieve_timeout_seconds: 1, ..Default::default() }).await;
+
+        let missing = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+        assert!(matches!(client.retrieve_data(missing).await, Err(Web3Error::TimeoutError(_))));
+    }
+}
@@ -38,8 +38,11 @@
 //! ```
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{Notify, RwLock};
+use tokio::task::JoinHandle;
 use tracing::{info, warn, error, debug};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
@@ -48,9 +51,16 @@ pub mod did;
 pub mod smart_contracts;
 pub mod ipfs;
 pub mod blockchain;
+pub mod audit_queue;
+pub mod token_gate;
 pub mod error;
 
+#[cfg(test)]
+mod mock_http;
+
 pub use error::{Web3Error, Result};
+pub use primitive_types::U256;
+pub use audit_queue::AuditQueueStatus;
 
 /// Web3 configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,9 +70,17 @@ pub struct Web3Config {
     pub rpc_url: String,
     pub contract_addresses: HashMap<String, String>,
     pub ipfs_gateway: String,
+    pub ipfs: IpfsConfig,
     pub did_resolver_url: String,
+    pub did: DidConfig,
+    /// Gas limit used when estimation fails
     pub gas_limit: u64,
+    /// Initial gas price in wei
     pub gas_price: String,
+    pub gas: GasConfig,
+    /// Time a token balance read for a token gate is reused
+    pub token_gate_cache_ttl_seconds: u64,
+    pub audit: AuditConfig,
 }
 
 impl Default for Web3Config {
@@ -73,9 +91,98 @@ impl Default for Web3Config {
             rpc_url: "https://mainnet.infura.io/v3/YOUR_PROJECT_ID".to_string(),
             contract_addresses: HashMap::new(),
             ipfs_gateway: "https://ipfs.io".to_string(),
+            ipfs: IpfsConfig::default(),
             did_resolver_url: "https://uniresolver.io".to_string(),
+            did: DidConfig::default(),
             gas_limit: 100000,
             gas_price: "20000000000".to_string(), // 20 gwei
+            gas: GasConfig::default(),
+            token_gate_cache_ttl_seconds: 30,
+            audit: AuditConfig::default(),
+        }
+    }
+}
+
+/// Audit anchoring configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditConfig {
+    /// File audit entries are kept in until anchored on-chain
+    pub queue_path: PathBuf,
+    /// Time between attempts to anchor queued entries
+    pub retry_interval_seconds: u64,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            queue_path: PathBuf::from("data/web3/audit-queue.json"),
+            retry_interval_seconds: 30,
+        }
+    }
+}
+
+/// Gas estimation and fee retry configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasConfig {
+    /// Factor applied to the estimated gas to leave headroom
+    pub estimate_multiplier: f64,
+    /// Times the gas price is raised after an underpriced rejection
+    pub max_fee_bumps: u32,
+    /// Percentage the gas price is raised by on each bump
+    pub fee_bump_percent: u64,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            estimate_multiplier: 1.2,
+            max_fee_bumps: 3,
+            // Nodes require at least 10% to replace a pending transaction
+            fee_bump_percent: 12,
+        }
+    }
+}
+
+/// IPFS storage configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpfsConfig {
+    /// IPFS HTTP API of the node content is added to
+    pub api_url: String,
+    /// Largest file accepted for upload
+    pub max_file_size_mb: u64,
+    /// Time allowed to retrieve content before giving up
+    pub retrieve_timeout_seconds: u64,
+    /// Remote pinning service, added content is also pinned there when set
+    pub pinning_endpoint: Option<String>,
+    pub pinning_api_key: Option<String>,
+}
+
+impl Default for IpfsConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "http://localhost:5001".to_string(),
+            max_file_size_mb: 100,
+            retrieve_timeout_seconds: 30,
+            pinning_endpoint: None,
+            pinning_api_key: None,
+        }
+    }
+}
+
+/// DID resolution configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DidConfig {
+    /// Time a resolved DID document is served from the cache
+    pub cache_ttl_seconds: u64,
+    /// Time past expiry a cached document may still be served when resolution fails, 0 to disable
+    pub stale_grace_seconds: u64,
+}
+
+impl Default for DidConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl_seconds: 3600,
+            stale_grace_seconds: 300,
         }
     }
 }
@@ -114,6 +221,8 @@ pub struct ContractResult {
     pub transaction_hash: String,
     pub block_number: u64,
     pub gas_used: u64,
+    /// Gas price paid in wei
+    pub effective_gas_price: String,
     pub status: bool,
     pub logs: Vec<String>,
 }
@@ -143,6 +252,10 @@ pub struct Web3Manager {
     contract_engine: Arc<RwLock<smart_contracts::ContractEngine>>,
     ipfs_client: Arc<RwLock<ipfs::IpfsClient>>,
     blockchain_client: Arc<RwLock<blockchain::BlockchainClient>>,
+    token_gate: Arc<RwLock<token_gate::TokenGate>>,
+    audit_queue: Arc<audit_queue::AuditQueue>,
+    audit_notify: Arc<Notify>,
+    audit_worker: JoinHandle<()>,
 }
 
 impl Web3Manager {
@@ -171,6 +284,21 @@ impl Web3Manager {
             blockchain::BlockchainClient::new(&config).await?
         ));
 
+        let token_gate = Arc::new(RwLock::new(
+            token_gate::TokenGate::new(&config).await?
+        ));
+
+        let audit_queue = Arc::new(
+            audit_queue::AuditQueue::open(&config.audit.queue_path).await?
+        );
+        let audit_notify = Arc::new(Notify::new());
+        let audit_worker = Self::spawn_audit_worker(
+            Arc::clone(&audit_queue),
+            Arc::clone(&blockchain_client),
+            Arc::clone(&audit_notify),
+            Duration::from_secs(config.audit.retry_interval_seconds),
+        );
+
         info!("Web3 integration manager initialized successfully");
 
         Ok(Self {
@@ -179,6 +307,34 @@ impl Web3Manager {
             contract_engine,
             ipfs_client,
             blockchain_client,
+            token_gate,
+            audit_queue,
+            audit_notify,
+            audit_worker,
+        })
+    }
+
+    /// Anchor queued audit entries whenever one is added and on every retry interval
+    fn spawn_audit_worker(
+        queue: Arc<audit_queue::AuditQueue>,
+        blockchain_client: Arc<RwLock<blockchain::BlockchainClient>>,
+        notify: Arc<Notify>,
+        retry_interval: Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(retry_interval);
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = notify.notified() => {}
+                }
+
+                let blockchain_client = blockchain_client.read().await;
+                if let Err(e) = queue.process(&blockchain_client).await {
+                    error!("Failed to process audit queue: {}", e);
+                }
+            }
         })
     }
 
@@ -204,6 +360,20 @@ impl Web3Manager {
         Ok(document)
     }
 
+    /// Verify that an email was signed by the sender's DID
+    ///
+    /// `did:ethr` signatures are secp256k1 Ethereum signed messages and
+    /// `did:key` signatures are Ed25519, both hex encoded.
+    pub async fn verify_email_signature(&self, did: &str, message: &[u8], signature: &str) -> Result<bool> {
+        debug!("Verifying email signature for DID: {}", did);
+        
+        let did_manager = self.did_manager.read().await;
+        let result = did_manager.verify_signature(did, message, signature).await?;
+        
+        info!("Email signature verification result for {}: {}", did, result);
+        Ok(result)
+    }
+
     /// Store data on IPFS
     pub async fn store_on_ipfs(&self, data: &[u8]) -> Result<IpfsResult> {
         debug!("Storing {} bytes on IPFS", data.len());
@@ -249,14 +419,41 @@ impl Web3Manager {
     }
 
     /// Create an audit trail entry on blockchain
+    ///
+    /// The entry is persisted locally and anchored on-chain in the
+    /// background, the returned id identifies the local record.
     pub async fn create_audit_entry(&self, event_data: &HashMap<String, String>) -> Result<String> {
         debug!("Creating audit trail entry");
         
-        let blockchain_client = self.blockchain_client.read().await;
-        let tx_hash = blockchain_client.create_audit_entry(event_data).await?;
+        let id = self.audit_queue.enqueue(event_data).await?;
+        self.audit_notify.notify_one();
+        
+        info!("Audit entry {} queued for anchoring", id);
+        Ok(id)
+    }
+
+    /// Get an audit entry with its anchoring progress
+    pub async fn get_audit_entry(&self, id: &str) -> Option<audit_queue::QueuedAuditEntry> {
+        self.audit_queue.get_entry(id).await
+    }
+
+    /// Get the number of audit entries pending, submitted and confirmed on-chain
+    pub async fn audit_queue_status(&self) -> AuditQueueStatus {
+        self.audit_queue.status().await
+    }
+
+    /// Check if an address passes a token gate
+    ///
+    /// The address must hold at least `min_balance` of an ERC-20 token, or
+    /// at least `min_balance` NFTs of an ERC-721 collection, in `contract`.
+    pub async fn check_token_gate(&self, address: &str, contract: &str, min_balance: U256) -> Result<bool> {
+        debug!("Checking token gate for {} on {}", address, contract);
+        
+        let token_gate = self.token_gate.read().await;
+        let result = token_gate.check(address, contract, min_balance).await?;
         
-        info!("Audit entry created with transaction hash: {}", tx_hash);
-        Ok(tx_hash)
+        info!("Token gate result for {} on {}: {}", address, contract, result);
+        Ok(result)
     }
 
     /// Get Web3 integration status
@@ -271,16 +468,30 @@ impl Web3Manager {
         let contract_status = self.contract_engine.read().await.get_status().await?;
         let ipfs_status = self.ipfs_client.read().await.get_status().await?;
         let blockchain_status = self.blockchain_client.read().await.get_status().await?;
+        let token_gate_status = self.token_gate.read().await.get_status().await?;
         
         status.insert("did_manager".to_string(), did_status);
         status.insert("contract_engine".to_string(), contract_status);
         status.insert("ipfs_client".to_string(), ipfs_status);
         status.insert("blockchain_client".to_string(), blockchain_status);
+        status.insert("token_gate".to_string(), token_gate_status);
+        
+        let audit_status = self.audit_queue.status().await;
+        status.insert("audit_queue".to_string(), format!(
+            "pending: {}, submitted: {}, confirmed: {}",
+            audit_status.pending, audit_status.submitted, audit_status.confirmed
+        ));
         
         Ok(status)
     }
 }
 
+impl Drop for Web3Manager {
+    fn drop(&mut self) {
+        self.audit_worker.abort();
+    }
+}
+
 /// Initialize Web3 integration
 pub async fn init_web3_integration(config: Web3Config) -> Result<Web3Manager> {
     info!("Initializing Web3 integration system");
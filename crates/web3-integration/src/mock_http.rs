@@ -0,0 +1,72 @@
+//! Minimal HTTP server for testing clients against canned responses
+
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Response status and body, or `None` to never answer
+pub(crate) type Response = Option<(&'static str, Vec<u8>)>;
+
+/// Serve requests with a handler of path and body, returning the base URL
+pub(crate) async fn serve<F>(handler: F) -> String
+where
+    F: Fn(&str, &[u8]) -> Response + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handler = Arc::new(handler);
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(handle(stream, Arc::clone(&handler)));
+        }
+    });
+
+    url
+}
+
+async fn handle<F>(mut stream: TcpStream, handler: Arc<F>)
+where
+    F: Fn(&str, &[u8]) -> Response,
+{
+    let (path, body) = read_request(&mut stream).await;
+
+    let (status, response) = match handler(&path, &body) {
+        Some(response) => response,
+        None => std::future::pending().await,
+    };
+
+    let head = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, response.len());
+    stream.write_all(head.as_bytes()).await.unwrap();
+    stream.write_all(&response).await.unwrap();
+}
+
+/// Read a request, returning its path and body
+async fn read_request(stream: &mut TcpStream) -> (String, Vec<u8>) {
+    let mut buffer = Vec::new();
+    let mut chunk = [0; 4096];
+
+    let header_end = loop {
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position + 4;
+        }
+        let read = stream.read(&mut chunk).await.unwrap();
+        assert!(read > 0, "connection closed mid-request");
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let path = head.split_whitespace().nth(1).unwrap_or_default().to_string();
+    let length = head
+        .lines()
+        .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|value| value.trim().parse().unwrap()))
+        .unwrap_or(0);
+
+    while buffer.len() < header_end + length {
+        let read = stream.read(&mut chunk).await.unwrap();
+        assert!(read > 0, "connection closed mid-body");
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    (path, buffer[header_end..].to_vec())
+}
@@ -7,7 +7,7 @@ use crate::{Web3Config, ContractResult, Result, Web3Error};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{info, warn, error, debug};
+use tracing::{info, warn, debug};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use chrono::{DateTime, Utc};
@@ -18,6 +18,7 @@ pub struct ContractCall {
     pub contract_address: String,
     pub function_name: String,
     pub parameters: Vec<ContractParameter>,
+    /// Gas limit, estimated when unset
     pub gas_limit: Option<u64>,
     pub gas_price: Option<String>,
     pub value: Option<String>, // ETH value to send
@@ -71,6 +72,7 @@ pub struct ContractEngine {
 
 /// Contract metadata for caching
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 struct ContractMetadata {
     pub address: String,
     pub abi: Value,
@@ -111,7 +113,7 @@ impl ContractEngine {
             contract_address: contract_address.to_string(),
             function_name: function.to_string(),
             parameters: self.prepare_parameters(params)?,
-            gas_limit: Some(self.config.gas_limit),
+            gas_limit: None,
             gas_price: Some(self.config.gas_price.clone()),
             value: None,
         };
@@ -155,8 +157,11 @@ impl ContractEngine {
         
         Ok(ContractResult {
             transaction_hash: tx_hash.to_string(),
-            block_number: receipt["blockNumber"].as_u64().unwrap_or(0),
-            gas_used: receipt["gasUsed"].as_u64().unwrap_or(0),
+            block_number: parse_quantity(&receipt["blockNumber"]).unwrap_or(0) as u64,
+            gas_used: parse_quantity(&receipt["gasUsed"]).unwrap_or(0) as u64,
+            effective_gas_price: parse_quantity(&receipt["effectiveGasPrice"])
+                .map(|price| price.to_string())
+                .unwrap_or_else(|| deployment.gas_price.clone()),
             status: receipt["status"].as_str() == Some("0x1"),
             logs: self.parse_logs(&receipt["logs"]),
         })
@@ -292,7 +297,7 @@ impl ContractEngine {
     fn prepare_parameters(&self, params: &[String]) -> Result<Vec<ContractParameter>> {
         let mut contract_params = Vec::new();
         
-        for (i, param) in params.iter().enumerate() {
+        for param in params {
             // Simple parameter type inference - in production, this would use ABI
             let param_type = if param.starts_with("0x") && param.len() == 42 {
                 "address"
@@ -312,37 +317,116 @@ impl ContractEngine {
     }
 
     /// Send contract transaction
+    ///
+    /// The gas limit is estimated with headroom, and the gas price is raised
+    /// and the transaction resent when the node rejects it as underpriced.
     async fn send_contract_transaction(&self, call: &ContractCall) -> Result<ContractResult> {
-        let transaction_data = json!({
-            "jsonrpc": "2.0",
-            "method": "eth_sendTransaction",
-            "params": [{
-                "to": call.contract_address,
-                "data": self.encode_function_call(call)?,
-                "gas": format!("0x{:x}", call.gas_limit.unwrap_or(self.config.gas_limit)),
-                "gasPrice": call.gas_price.as_ref().unwrap_or(&self.config.gas_price)
-            }],
-            "id": 1
-        });
-        
-        let response = self.send_rpc_request(&transaction_data).await?;
+        let data = self.encode_function_call(call)?;
+        let gas_limit = self.estimate_gas(call, &data).await?;
+        let mut gas_price = parse_gas_price(call.gas_price.as_ref().unwrap_or(&self.config.gas_price))?;
+        let mut bumps = 0;
         
-        let tx_hash = response["result"]
-            .as_str()
-            .ok_or_else(|| Web3Error::ContractError("No transaction hash in response".to_string()))?;
+        let tx_hash = loop {
+            let transaction_data = json!({
+                "jsonrpc": "2.0",
+                "method": "eth_sendTransaction",
+                "params": [{
+                    "to": call.contract_address,
+                    "data": data,
+                    "gas": format!("0x{:x}", gas_limit),
+                    "gasPrice": format!("0x{:x}", gas_price)
+                }],
+                "id": 1
+            });
+            
+            match self.send_rpc_request(&transaction_data).await {
+                Ok(response) => {
+                    break response["result"]
+                        .as_str()
+                        .ok_or_else(|| Web3Error::ContractError("No transaction hash in response".to_string()))?
+                        .to_string();
+                }
+                Err(Web3Error::ContractError(message)) if is_underpriced(&message) && bumps < self.config.gas.max_fee_bumps => {
+                    bumps += 1;
+                    let bumped = gas_price * (100 + self.config.gas.fee_bump_percent as u128) / 100;
+                    warn!("Transaction underpriced at {} wei, retrying at {} wei ({}/{})", gas_price, bumped, bumps, self.config.gas.max_fee_bumps);
+                    gas_price = bumped;
+                }
+                Err(e) => return Err(e),
+            }
+        };
         
         // Wait for transaction confirmation
-        let receipt = self.wait_for_transaction_receipt(tx_hash).await?;
+        let receipt = self.wait_for_transaction_receipt(&tx_hash).await?;
+        
+        if receipt["status"].as_str() == Some("0x0") {
+            return Err(self.revert_error(call, &data, &receipt).await);
+        }
         
         Ok(ContractResult {
-            transaction_hash: tx_hash.to_string(),
-            block_number: receipt["blockNumber"].as_u64().unwrap_or(0),
-            gas_used: receipt["gasUsed"].as_u64().unwrap_or(0),
+            transaction_hash: tx_hash,
+            block_number: parse_quantity(&receipt["blockNumber"]).unwrap_or(0) as u64,
+            gas_used: parse_quantity(&receipt["gasUsed"]).unwrap_or(0) as u64,
+            effective_gas_price: parse_quantity(&receipt["effectiveGasPrice"]).unwrap_or(gas_price).to_string(),
             status: receipt["status"].as_str() == Some("0x1"),
             logs: self.parse_logs(&receipt["logs"]),
         })
     }
 
+    /// Estimate the gas limit of a call, with the configured headroom
+    ///
+    /// Falls back to the configured limit when the node cannot estimate,
+    /// unless the estimate shows the call would revert.
+    async fn estimate_gas(&self, call: &ContractCall, data: &str) -> Result<u64> {
+        // An explicit limit is used as is
+        if let Some(gas_limit) = call.gas_limit {
+            return Ok(gas_limit);
+        }
+        
+        let estimate_data = json!({
+            "jsonrpc": "2.0",
+            "method": "eth_estimateGas",
+            "params": [{
+                "to": call.contract_address,
+                "data": data
+            }],
+            "id": 1
+        });
+        
+        match self.send_rpc_request(&estimate_data).await {
+            Ok(response) => {
+                let estimate = parse_quantity(&response["result"])
+                    .ok_or_else(|| Web3Error::ContractError("Invalid gas estimate".to_string()))?;
+                let gas_limit = (estimate as f64 * self.config.gas.estimate_multiplier).ceil() as u64;
+                debug!("Estimated {} gas, using a limit of {}", estimate, gas_limit);
+                Ok(gas_limit)
+            }
+            Err(e @ Web3Error::ContractReverted { .. }) => Err(e),
+            Err(e) => {
+                warn!("Gas estimation failed, using the configured limit: {}", e);
+                Ok(self.config.gas_limit)
+            }
+        }
+    }
+
+    /// Find out why a mined transaction reverted by replaying it
+    async fn revert_error(&self, call: &ContractCall, data: &str, receipt: &Value) -> Web3Error {
+        let replay_data = json!({
+            "jsonrpc": "2.0",
+            "method": "eth_call",
+            "params": [{
+                "to": call.contract_address,
+                "data": data
+            }, receipt["blockNumber"]],
+            "id": 1
+        });
+        
+        match self.send_rpc_request(&replay_data).await {
+            Err(e @ Web3Error::ContractReverted { .. }) => e,
+            _ => Web3Error::ContractReverted { reason: None },
+        }
+    }
+
     /// Encode function call data
     fn encode_function_call(&self, call: &ContractCall) -> Result<String> {
         // In a real implementation, this would use proper ABI encoding
@@ -380,10 +464,17 @@ impl ContractEngine {
             .map_err(|e| Web3Error::SerializationError(e.to_string()))?;
         
         if let Some(error) = response_json.get("error") {
-            return Err(Web3Error::ContractError(format!(
-                "RPC error: {}", 
-                error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error")
-            )));
+            let message = error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error");
+            
+            if message.starts_with("execution reverted") {
+                let reason = error.get("data")
+                    .and_then(|data| data.as_str())
+                    .and_then(decode_revert_reason)
+                    .or_else(|| message.strip_prefix("execution reverted: ").map(|reason| reason.to_string()));
+                return Err(Web3Error::ContractReverted { reason });
+            }
+            
+            return Err(Web3Error::ContractError(format!("RPC error: {}", message)));
         }
         
         Ok(response_json)
@@ -476,3 +567,163 @@ impl ContractEngine {
         Ok(())
     }
 }
+
+/// Selector of the `Error(string)` revert payload
+const REVERT_ERROR_SELECTOR: &str = "08c379a0";
+
+/// Check if a node rejected a transaction for its gas price
+fn is_underpriced(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("underpriced") || message.contains("replacement transaction")
+}
+
+/// Parse a JSON-RPC quantity, either a hex string or a number
+fn parse_quantity(value: &Value) -> Option<u128> {
+    match value {
+        Value::String(hex) => u128::from_str_radix(hex.strip_prefix("0x")?, 16).ok(),
+        Value::Number(number) => number.as_u64().map(u128::from),
+        _ => None,
+    }
+}
+
+/// Parse a gas price in wei, given in decimal or as a hex quantity
+fn parse_gas_price(price: &str) -> Result<u128> {
+    match price.strip_prefix("0x") {
+        Some(hex) => u128::from_str_radix(hex, 16).ok(),
+        None => price.parse().ok(),
+    }
+    .ok_or_else(|| Web3Error::ConfigError(format!("Invalid gas price: {}", price)))
+}
+
+/// Decode the message of an `Error(string)` revert payload
+fn decode_revert_reason(data: &str) -> Option<String> {
+    let payload = hex::decode(data.strip_prefix("0x")?.strip_prefix(REVERT_ERROR_SELECTOR)?).ok()?;
+    
+    // ABI encoded string: offset, length, then the bytes
+    let offset = usize::try_from(read_abi_word(&payload, 0)?).ok()?;
+    let length = usize::try_from(read_abi_word(&payload, offset)?).ok()?;
+    let start = offset.checked_add(32)?;
+    let bytes = payload.get(start..start.checked_add(length)?)?;
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+/// Read a 32-byte ABI word that must fit in a u64
+fn read_abi_word(payload: &[u8], position: usize) -> Option<u64> {
+    let word = payload.get(position..position.checked_add(32)?)?;
+    if word[..24].iter().any(|&byte| byte != 0) {
+        return None;
+    }
+    Some(u64::from_be_bytes(word[24..].try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_http;
+    use std::sync::Mutex;
+
+    const CONTRACT: &str = "0x5fbdb2315678afecb367f032d93f642f64180aa3";
+
+    /// Transactions sent to the mock node, as (gas, gas price)
+    type Sent = Arc<Mutex<Vec<(u128, u128)>>>;
+
+    /// RPC node that rejects the first `underpriced` sends and mines the rest
+    async fn mock_rpc(underpriced: usize, revert_reason: Option<&'static str>) -> (ContractEngine, Sent) {
+        let sent: Sent = Arc::default();
+        let url = mock_http::serve({
+            let sent = Arc::clone(&sent);
+            move |_, body| {
+                let request: Value = serde_json::from_slice(body).unwrap();
+                let params = &request["params"][0];
+
+                let response = match request["method"].as_str().unwrap() {
+                    "eth_estimateGas" => match revert_reason {
+                        Some(reason) => json!({"error": {"code": 3, "message": "execution reverted", "data": revert_data(reason)}}),
+                        None => json!({"result": "0x5208"}),
+                    },
+                    "eth_sendTransaction" => {
+                        let mut sent = sent.lock().unwrap();
+                        sent.push((parse_quantity(&params["gas"]).unwrap(), parse_quantity(&params["gasPrice"]).unwrap()));
+                        if sent.len() <= underpriced {
+                            json!({"error": {"code": -32000, "message": "replacement transaction underpriced"}})
+                        } else {
+                            json!({"result": "0xabc123"})
+                        }
+                    }
+                    "eth_getTransactionReceipt" => {
+                        let (_, gas_price) = *sent.lock().unwrap().last().unwrap();
+                        json!({"result": {
+                            "blockNumber": "0x10",
+                            "gasUsed": "0x5000",
+                            "effectiveGasPrice": format!("0x{:x}", gas_price),
+                            "status": "0x1",
+                            "logs": []
+                        }})
+                    }
+                    method => panic!("unexpected RPC method {}", method),
+                };
+                Some(("200 OK", response.to_string().into_bytes()))
+            }
+        }).await;
+
+        let config = Web3Config {
+            rpc_url: url,
+            ..Default::default()
+        };
+        (ContractEngine::new(&config).await.unwrap(), sent)
+    }
+
+    /// Encode an `Error(string)` revert payload
+    fn revert_data(reason: &str) -> String {
+        let mut length = [0u8; 32];
+        length[24..].copy_from_slice(&(reason.len() as u64).to_be_bytes());
+        let mut offset = [0u8; 32];
+        offset[31] = 32;
+        let mut text = reason.as_bytes().to_vec();
+        text.resize(reason.len().div_ceil(32) * 32, 0);
+        format!("0x{}{}{}{}", REVERT_ERROR_SELECTOR, hex::encode(offset), hex::encode(length), hex::encode(text))
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_estimated_gas() {
+        let (engine, sent) = mock_rpc(0, None).await;
+
+        let result = engine.execute_function(CONTRACT, "store", &["42".to_string()]).await.unwrap();
+        assert_eq!(result.transaction_hash, "0xabc123");
+        assert_eq!(result.block_number, 16);
+        assert_eq!(result.gas_used, 0x5000);
+        assert_eq!(result.effective_gas_price, "20000000000");
+
+        // 21000 estimated with 20% headroom
+        assert_eq!(*sent.lock().unwrap(), vec![(25200, 20_000_000_000)]);
+    }
+
+    #[tokio::test]
+    async fn test_retry_underpriced_with_fee_bump() {
+        let (engine, sent) = mock_rpc(2, None).await;
+
+        let result = engine.execute_function(CONTRACT, "store", &["42".to_string()]).await.unwrap();
+        let prices = sent.lock().unwrap().iter().map(|(_, price)| *price).collect::<Vec<_>>();
+        assert_eq!(prices, vec![20_000_000_000, 22_400_000_000, 25_088_000_000]);
+        assert_eq!(result.effective_gas_price, "25088000000");
+
+        // Gives up once the bumps are used up
+        let (engine, sent) = mock_rpc(10, None).await;
+        assert!(matches!(
+            engine.execute_function(CONTRACT, "store", &["42".to_string()]).await,
+            Err(Web3Error::ContractError(message)) if message.contains("underpriced")
+        ));
+        assert_eq!(sent.lock().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_revert_reason() {
+        let (engine, sent) = mock_rpc(0, Some("caller is not the owner")).await;
+
+        assert!(matches!(
+            engine.execute_function(CONTRACT, "store", &["42".to_string()]).await,
+            Err(Web3Error::ContractReverted { reason: Some(reason) }) if reason == "caller is not the owner"
+        ));
+        assert!(sent.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+//! Token-gated access for A3Mailer
+//!
+//! This module checks whether an address holds enough of an ERC-20 token, or
+//! enough ERC-721 NFTs, to pass a token gate.
+
+use crate::{Web3Config, Result, Web3Error};
+use primitive_types::U256;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{info, debug};
+use serde_json::{json, Value};
+
+/// Selector of `balanceOf(address)`, shared by ERC-20 and ERC-721
+const BALANCE_OF_SELECTOR: &str = "70a08231";
+
+/// Token gate checker backed by on-chain balance queries
+pub struct TokenGate {
+    config: Web3Config,
+    client: reqwest::Client,
+    /// Balances keyed by contract and holder, with the time they were read
+    balances: RwLock<HashMap<(String, String), (U256, Instant)>>,
+    /// Balance queries sent to the RPC node
+    rpc_calls: AtomicU64,
+}
+
+impl TokenGate {
+    /// Create a new token gate checker
+    pub async fn new(config: &Web3Config) -> Result<Self> {
+        info!("Initializing token gate");
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Web3Error::NetworkError(e.to_string()))?;
+
+        Ok(Self {
+            config: config.clone(),
+            client,
+            balances: RwLock::new(HashMap::new()),
+            rpc_calls: AtomicU64::new(0),
+        })
+    }
+
+    /// Check if an address holds at least `min_balance` of a token
+    ///
+    /// ERC-20 balances are in the token's smallest unit, ERC-721 balances
+    /// count the NFTs held.
+    pub async fn check(&self, address: &str, contract: &str, min_balance: U256) -> Result<bool> {
+        let balance = self.balance_of(address, contract).await?;
+        let allowed = balance >= min_balance;
+
+        debug!("Token gate for {} on {}: balance {}, required {}, allowed {}", address, contract, balance, min_balance, allowed);
+        Ok(allowed)
+    }
+
+    /// Get the token balance of an address, served from the cache when fresh
+    pub async fn balance_of(&self, address: &str, contract: &str) -> Result<U256> {
+        let address = normalize_address(address)?;
+        let contract = normalize_address(contract)?;
+        let key = (contract.clone(), address.clone());
+        let ttl = Duration::from_secs(self.config.token_gate_cache_ttl_seconds);
+
+        if let Some((balance, read_at)) = self.balances.read().await.get(&key) {
+            if read_at.elapsed() < ttl {
+                return Ok(*balance);
+            }
+        }
+
+        let balance = self.query_balance(&address, &contract).await?;
+        self.balances.write().await.insert(key, (balance, Instant::now()));
+        Ok(balance)
+    }
+
+    /// Query `balanceOf` through the RPC node
+    async fn query_balance(&self, address: &str, contract: &str) -> Result<U256> {
+        self.rpc_calls.fetch_add(1, Ordering::Relaxed);
+
+        let request_data = json!({
+            "jsonrpc": "2.0",
+            "method": "eth_call",
+            "params": [{
+                "to": contract,
+                "data": format!("0x{}{:0>64}", BALANCE_OF_SELECTOR, &address[2..]),
+            }, "latest"],
+            "id": 1
+        });
+
+        let response = self.client
+            .post(&self.config.rpc_url)
+            .header("Content-Type", "application/json")
+            .json(&request_data)
+            .send()
+            .await
+            .map_err(|e| Web3Error::NetworkError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(Web3Error::NetworkError(format!(
+                "RPC request failed with status: {}",
+                response.status()
+            )));
+        }
+
+        let response_json: Value = response.json().await
+            .map_err(|e| Web3Error::SerializationError(e.to_string()))?;
+
+        if let Some(error) = response_json.get("error") {
+            return Err(Web3Error::BlockchainError(format!(
+                "RPC error: {}",
+                error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error")
+            )));
+        }
+
+        // A contract without balanceOf returns no data
+        response_json["result"].as_str()
+            .and_then(|result| result.strip_prefix("0x"))
+            .filter(|result| !result.is_empty())
+            .and_then(|result| U256::from_str_radix(result, 16).ok())
+            .ok_or_else(|| Web3Error::ContractError(format!("{} returned no token balance", contract)))
+    }
+
+    /// Get the number of balance queries sent to the RPC node
+    pub fn get_rpc_call_count(&self) -> u64 {
+        self.rpc_calls.load(Ordering::Relaxed)
+    }
+
+    /// Get token gate status
+    pub async fn get_status(&self) -> Result<String> {
+        Ok(format!("active (cached balances: {})", self.balances.read().await.len()))
+    }
+}
+
+/// Validate and lowercase an Ethereum address
+fn normalize_address(address: &str) -> Result<String> {
+    match address.strip_prefix("0x") {
+        Some(hex) if hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()) => Ok(address.to_lowercase()),
+        _ => Err(Web3Error::Other(format!("Invalid Ethereum address: {}", address))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_http;
+    use std::sync::Arc;
+
+    const TOKEN: &str = "0x6b175474e89094c44da98b954eedeac495271d0f";
+    const HOLDER: &str = "0x1111111111111111111111111111111111111111";
+    const OTHER: &str = "0x2222222222222222222222222222222222222222";
+
+    /// RPC node answering `balanceOf` from a fixed set of balances
+    async fn mock_rpc(balances: HashMap<&'static str, u64>) -> (Web3Config, Arc<AtomicU64>) {
+        let calls = Arc::new(AtomicU64::new(0));
+        let url = mock_http::serve({
+            let calls = Arc::clone(&calls);
+            move |_, body| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                let request: Value = serde_json::from_slice(body).unwrap();
+                let data = request["params"][0]["data"].as_str().unwrap();
+                assert!(data.starts_with("0x70a08231"));
+
+                let holder = format!("0x{}", &data[data.len() - 40..]);
+                let balance = balances.get(holder.as_str()).copied().unwrap_or(0);
+                let response = json!({"jsonrpc": "2.0", "id": 1, "result": format!("0x{:064x}", balance)});
+                Some(("200 OK", response.to_string().into_bytes()))
+            }
+        }).await;
+
+        let config = Web3Config {
+            rpc_url: url,
+            ..Default::default()
+        };
+        (config, calls)
+    }
+
+    #[tokio::test]
+    async fn test_token_gate_thresholds() {
+        let (config, _) = mock_rpc([(HOLDER, 1500)].into()).await;
+        let gate = TokenGate::new(&config).await.unwrap();
+
+        assert!(gate.check(HOLDER, TOKEN, U256::from(1000)).await.unwrap());
+        assert!(gate.check(HOLDER, TOKEN, U256::from(1500)).await.unwrap());
+        assert!(!gate.check(HOLDER, TOKEN, U256::from(1501)).await.unwrap());
+        assert!(!gate.check(OTHER, TOKEN, U256::one()).await.unwrap());
+        assert!(gate.check("0xnot-an-address", TOKEN, U256::one()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_token_gate_cache() {
+        let (config, calls) = mock_rpc([(HOLDER, 1)].into()).await;
+        let gate = TokenGate::new(&config).await.unwrap();
+
+        assert!(gate.check(HOLDER, TOKEN, U256::one()).await.unwrap());
+        assert!(gate.check(&HOLDER.to_uppercase().replace("0X", "0x"), TOKEN, U256::one()).await.unwrap());
+        assert!(!gate.check(HOLDER, TOKEN, U256::from(2)).await.unwrap());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(gate.get_rpc_call_count(), 1);
+
+        // Expired balances are read again
+        let config = Web3Config {
+            token_gate_cache_ttl_seconds: 0,
+            ..config
+        };
+        let gate = TokenGate::new(&config).await.unwrap();
+        gate.check(HOLDER, TOKEN, U256::one()).await.unwrap();
+        gate.check(HOLDER, TOKEN, U256::one()).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
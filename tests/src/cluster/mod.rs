@@ -18,6 +18,7 @@ use common::{
         boot::build_ipc,
         config::{ConfigManager, Patterns},
     },
+    monitoring::MonitoringManager,
 };
 use http::HttpSessionManager;
 use imap::core::ImapSessionManager;
@@ -198,6 +199,11 @@ async fn build_server(mut config: Config, stores: Stores) -> (Server, watch::Sen
 
     // Parse acceptors
     servers.parse_tcp_acceptors(&mut config, inner.clone());
+    let pop3 = Pop3SessionManager::new(
+        inner.clone(),
+        &mut config,
+        Arc::new(MonitoringManager::default()),
+    );
 
     // Enable tracing
     tracers.enable(true);
@@ -228,12 +234,9 @@ async fn build_server(mut config: Config, stores: Stores) -> (Server, watch::Sen
                 acceptor,
                 shutdown_rx,
             ),
-            ServerProtocol::Pop3 => server.spawn(
-                Pop3SessionManager::new(inner.clone()),
-                inner.clone(),
-                acceptor,
-                shutdown_rx,
-            ),
+            ServerProtocol::Pop3 => {
+                server.spawn(pop3.clone(), inner.clone(), acceptor, shutdown_rx)
+            }
             ServerProtocol::ManageSieve => server.spawn(
                 ManageSieveSessionManager::new(inner.clone()),
                 inner.clone(),
@@ -35,6 +35,7 @@ use common::{
     },
     core::BuildServer,
     manager::boot::build_ipc,
+    monitoring::MonitoringManager,
 };
 use http::HttpSessionManager;
 use imap::core::ImapSessionManager;
@@ -116,7 +117,7 @@ pub async fn imap_tests() {
     managesieve::test().await;
 
     // Run POP3 tests
-    pop::test().await;
+    pop::test(&handle).await;
 
     // Print elapsed time
     let elapsed = start_time.elapsed();
@@ -135,6 +136,7 @@ pub async fn imap_tests() {
 #[allow(dead_code)]
 pub struct IMAPTest {
     server: Server,
+    monitoring: Arc<MonitoringManager>,
     temp_dir: TempDir,
     shutdown_tx: watch::Sender<bool>,
 }
@@ -180,6 +182,8 @@ async fn init_imap_tests(store_id: &str, delete_if_exists: bool) -> IMAPTest {
 
     // Parse acceptors
     servers.parse_tcp_acceptors(&mut config, inner.clone());
+    let monitoring = Arc::new(MonitoringManager::default());
+    let pop3 = Pop3SessionManager::new(inner.clone(), &mut config, monitoring.clone());
 
     // Enable tracing
     tracers.enable(true);
@@ -210,12 +214,9 @@ async fn init_imap_tests(store_id: &str, delete_if_exists: bool) -> IMAPTest {
                 acceptor,
                 shutdown_rx,
             ),
-            ServerProtocol::Pop3 => server.spawn(
-                Pop3SessionManager::new(inner.clone()),
-                inner.clone(),
-                acceptor,
-                shutdown_rx,
-            ),
+            ServerProtocol::Pop3 => {
+                server.spawn(pop3.clone(), inner.clone(), acceptor, shutdown_rx)
+            }
             ServerProtocol::ManageSieve => server.spawn(
                 ManageSieveSessionManager::new(inner.clone()),
                 inner.clone(),
@@ -286,6 +287,7 @@ async fn init_imap_tests(store_id: &str, delete_if_exists: bool) -> IMAPTest {
 
     IMAPTest {
         server: inner.build_server(),
+        monitoring,
         temp_dir,
         shutdown_tx,
     }
@@ -631,6 +633,12 @@ protocol = "pop3"
 max-connections = 81920
 tls.implicit = true
 
+[server.listener.pop3-plain]
+bind = ["127.0.0.1:4111"]
+protocol = "pop3"
+max-connections = 81920
+tls.implicit = false
+
 [server.listener.lmtp-debug]
 bind = ['127.0.0.1:11201']
 greeting = 'Test LMTP instance'
@@ -752,6 +760,16 @@ private-key = "%{file:{PK}}%"
 [imap.protocol]
 uidplus = true
 
+[imap.auth]
+allow-plain-text = true
+
+[pop3.auth]
+require-tls = true
+
+[pop3.limits]
+command-delay = false
+commands-per-minute = 50
+
 [storage]
 data = "{STORE}"
 fts = "{STORE}"
@@ -13,9 +13,10 @@ use tokio::{
 };
 use tokio_rustls::client::TlsStream;
 
+use super::IMAPTest;
 use crate::{jmap::delivery::SmtpConnection, smtp::session::VerifyResponse};
 
-pub async fn test() {
+pub async fn test(handle: &IMAPTest) {
     println!("Running POP3 tests...");
 
     // Send 3 test emails
@@ -187,6 +188,98 @@ pub async fn test() {
         .await
         .assert_contains("+OK 0 0");
     pop3.send("QUIT").await;
+
+    // STLS on a plaintext connection, plain-text logins are allowed but the
+    // POP3 policy requires TLS before any authentication attempt
+    let mut stream = BufReader::new(TcpStream::connect("127.0.0.1:4111").await.unwrap());
+    assert!(read_plain(&mut stream, false).await.starts_with("+OK"));
+    stream.get_mut().write_all(b"CAPA\r\n").await.unwrap();
+    assert!(read_plain(&mut stream, true).await.contains("\r\nSTLS\r\n"));
+    stream
+        .get_mut()
+        .write_all(b"AUTH PLAIN AHBvcHBlckBleGFtcGxlLmNvbQBzZWNyZXQ=\r\n")
+        .await
+        .unwrap();
+    assert!(
+        read_plain(&mut stream, false)
+            .await
+            .starts_with("-ERR Authentication requires TLS")
+    );
+    stream
+        .get_mut()
+        .write_all(b"USER popper@example.com\r\n")
+        .await
+        .unwrap();
+    assert!(
+        read_plain(&mut stream, false)
+            .await
+            .starts_with("-ERR Authentication requires TLS")
+    );
+    stream.get_mut().write_all(b"STLS\r\n").await.unwrap();
+    assert!(read_plain(&mut stream, false).await.starts_with("+OK"));
+    let mut pop3 = Pop3Connection::upgrade(stream.into_inner()).await;
+    pop3.send("CAPA").await;
+    pop3.assert_read(ResponseType::Multiline)
+        .await
+        .assert_not_contains("STLS");
+    pop3.send("STLS").await;
+    pop3.assert_read(ResponseType::Err).await;
+    pop3.send("AUTH PLAIN AHBvcHBlckBleGFtcGxlLmNvbQBzZWNyZXQ=")
+        .await;
+    pop3.assert_read(ResponseType::Ok).await;
+    pop3.send("QUIT").await;
+
+    // Command counters are published to the shared monitoring manager
+    // when a session ends
+    let metric = |name: &str| {
+        handle
+            .monitoring
+            .get_custom_metric(name)
+            .unwrap_or_default()
+    };
+    let noops = metric("pop3.command.noop.count");
+    let noop_errors = metric("pop3.command.noop.errors");
+    let retr_errors = metric("pop3.command.retr.errors");
+    let rate_limited = metric("pop3.command.rate_limited");
+
+    // The session may run 50 commands per minute, the last NOOP is rejected
+    let mut pop3 = Pop3Connection::connect_and_login().await;
+    pop3.send("NOOP").await;
+    pop3.assert_read(ResponseType::Ok).await;
+    pop3.send("RETR 1").await;
+    pop3.assert_read(ResponseType::Err).await;
+    pop3.send(&vec!["NOOP"; 48].join("\r\n")).await;
+    for _ in 0..47 {
+        pop3.assert_read(ResponseType::Ok).await;
+    }
+    pop3.assert_read(ResponseType::Err).await;
+
+    for _ in 0..50 {
+        if metric("pop3.command.noop.count") > noops {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(metric("pop3.command.noop.count"), noops + 48.0);
+    assert_eq!(metric("pop3.command.noop.errors"), noop_errors);
+    assert_eq!(metric("pop3.command.retr.errors"), retr_errors + 1.0);
+    assert_eq!(metric("pop3.command.rate_limited"), rate_limited + 1.0);
+}
+
+async fn read_plain(stream: &mut BufReader<TcpStream>, is_multiline: bool) -> String {
+    let mut response = String::new();
+    loop {
+        let mut line = String::new();
+        stream.read_line(&mut line).await.unwrap();
+        assert!(!line.is_empty(), "Connection closed: {:?}", response);
+        response.push_str(&line);
+        if line.starts_with("-ERR")
+            || (!is_multiline && line.starts_with("+OK"))
+            || (is_multiline && line == ".\r\n")
+        {
+            return response;
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -203,11 +296,15 @@ pub struct Pop3Connection {
 
 impl Pop3Connection {
     pub async fn connect() -> Self {
+        Self::upgrade(TcpStream::connect("127.0.0.1:4110").await.unwrap()).await
+    }
+
+    pub async fn upgrade(stream: TcpStream) -> Self {
         let (reader, writer) = tokio::io::split(
             build_tls_connector(true)
                 .connect(
                     ServerName::try_from("pop3.example.org").unwrap().to_owned(),
-                    TcpStream::connect("127.0.0.1:4110").await.unwrap(),
+                    stream,
                 )
                 .await
                 .unwrap(),
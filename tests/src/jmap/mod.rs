@@ -22,6 +22,7 @@ use common::{
         boot::build_ipc,
         config::{ConfigManager, Patterns},
     },
+    monitoring::MonitoringManager,
 };
 use email::message::delete::EmailDeletion;
 use enterprise::{EnterpriseCore, insert_test_metrics};
@@ -313,6 +314,11 @@ async fn init_jmap_tests(store_id: &str, delete_if_exists: bool) -> JMAPTest {
 
     // Parse acceptors
     servers.parse_tcp_acceptors(&mut config, inner.clone());
+    let pop3 = Pop3SessionManager::new(
+        inner.clone(),
+        &mut config,
+        Arc::new(MonitoringManager::default()),
+    );
 
     // Enable tracing
     tracers.enable(true);
@@ -343,12 +349,9 @@ async fn init_jmap_tests(store_id: &str, delete_if_exists: bool) -> JMAPTest {
                 acceptor,
                 shutdown_rx,
             ),
-            ServerProtocol::Pop3 => server.spawn(
-                Pop3SessionManager::new(inner.clone()),
-                inner.clone(),
-                acceptor,
-                shutdown_rx,
-            ),
+            ServerProtocol::Pop3 => {
+                server.spawn(pop3.clone(), inner.clone(), acceptor, shutdown_rx)
+            }
             ServerProtocol::ManageSieve => server.spawn(
                 ManageSieveSessionManager::new(inner.clone()),
                 inner.clone(),
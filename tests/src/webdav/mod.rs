@@ -20,6 +20,7 @@ use common::{
     },
     core::BuildServer,
     manager::boot::build_ipc,
+    monitoring::MonitoringManager,
 };
 use dav_proto::{
     schema::property::{DavProperty, WebDavProperty},
@@ -162,6 +163,11 @@ async fn init_webdav_tests(store_id: &str, delete_if_exists: bool) -> WebDavTest
 
     // Parse acceptors
     servers.parse_tcp_acceptors(&mut config, inner.clone());
+    let pop3 = Pop3SessionManager::new(
+        inner.clone(),
+        &mut config,
+        Arc::new(MonitoringManager::default()),
+    );
 
     // Enable tracing
     tracers.enable(true);
@@ -192,12 +198,9 @@ async fn init_webdav_tests(store_id: &str, delete_if_exists: bool) -> WebDavTest
                 acceptor,
                 shutdown_rx,
             ),
-            ServerProtocol::Pop3 => server.spawn(
-                Pop3SessionManager::new(inner.clone()),
-                inner.clone(),
-                acceptor,
-                shutdown_rx,
-            ),
+            ServerProtocol::Pop3 => {
+                server.spawn(pop3.clone(), inner.clone(), acceptor, shutdown_rx)
+            }
             ServerProtocol::ManageSieve => server.spawn(
                 ManageSieveSessionManager::new(inner.clone()),
                 inner.clone(),